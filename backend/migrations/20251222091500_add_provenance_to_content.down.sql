@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS idx_questions_contribution_id;
+DROP INDEX IF EXISTS idx_questions_created_by;
+DROP INDEX IF EXISTS idx_architectures_contribution_id;
+DROP INDEX IF EXISTS idx_architectures_created_by;
+
+ALTER TABLE questions DROP COLUMN created_by, DROP COLUMN contribution_id;
+ALTER TABLE architectures DROP COLUMN created_by, DROP COLUMN contribution_id;
@@ -0,0 +1,15 @@
+-- Track where crowd-sourced content came from.
+-- contribution_id: the approved contribution the row was created from (NULL for admin-created rows)
+-- created_by: the contributor, or the admin who created the row directly
+ALTER TABLE architectures
+    ADD COLUMN contribution_id BIGINT REFERENCES contributions(id) ON DELETE SET NULL,
+    ADD COLUMN created_by BIGINT REFERENCES users(id) ON DELETE SET NULL;
+
+ALTER TABLE questions
+    ADD COLUMN contribution_id BIGINT REFERENCES contributions(id) ON DELETE SET NULL,
+    ADD COLUMN created_by BIGINT REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_architectures_created_by ON architectures(created_by);
+CREATE INDEX idx_architectures_contribution_id ON architectures(contribution_id);
+CREATE INDEX idx_questions_created_by ON questions(created_by);
+CREATE INDEX idx_questions_contribution_id ON questions(contribution_id);
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_questions_type_random_key;
+DROP INDEX IF EXISTS idx_questions_random_key;
+
+ALTER TABLE questions
+    DROP COLUMN IF EXISTS random_key,
+    DROP COLUMN IF EXISTS exposure_count;
@@ -0,0 +1,9 @@
+-- Exposure-aware question sampling.
+-- exposure_count: how often the question was served recently (decayed by the sampling job)
+-- random_key: precomputed sort key; re-rolled periodically so sampling windows rotate
+ALTER TABLE questions
+    ADD COLUMN exposure_count BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN random_key DOUBLE PRECISION NOT NULL DEFAULT random();
+
+CREATE INDEX idx_questions_random_key ON questions(random_key);
+CREATE INDEX idx_questions_type_random_key ON questions(type, random_key);
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_architectures_location_hierarchy;
+
+ALTER TABLE architectures
+    DROP COLUMN IF EXISTS district,
+    DROP COLUMN IF EXISTS city,
+    DROP COLUMN IF EXISTS province;
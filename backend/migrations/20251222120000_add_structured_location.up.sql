@@ -0,0 +1,9 @@
+-- Structured location alongside the original free-text `location`.
+-- Filled on create/update and by the admin backfill endpoint.
+ALTER TABLE architectures
+    ADD COLUMN province VARCHAR(50),
+    ADD COLUMN city VARCHAR(50),
+    ADD COLUMN district VARCHAR(50);
+
+CREATE INDEX idx_architectures_location_hierarchy
+    ON architectures (LOWER(province), LOWER(city), LOWER(district));
@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS idx_architectures_name_pinyin_trgm;
+
+ALTER TABLE architectures
+    DROP COLUMN IF EXISTS name_initials,
+    DROP COLUMN IF EXISTS name_pinyin;
+
+DROP TABLE IF EXISTS glossary_terms;
@@ -0,0 +1,24 @@
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Glossary of architectural terms (e.g. 斗拱 / dougong).
+CREATE TABLE IF NOT EXISTS glossary_terms (
+    id BIGSERIAL PRIMARY KEY,
+    slug VARCHAR(100) NOT NULL UNIQUE,
+    term VARCHAR(100) NOT NULL,
+    definition TEXT NOT NULL,
+    term_pinyin TEXT,
+    term_initials TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Pinyin search keys, filled by the application (and the search_index job for old rows).
+-- *_pinyin: space-separated syllables ("ying xian mu ta"); *_initials: "yxmt"
+ALTER TABLE architectures
+    ADD COLUMN name_pinyin TEXT,
+    ADD COLUMN name_initials TEXT;
+
+CREATE INDEX idx_architectures_name_pinyin_trgm
+    ON architectures USING GIN (REPLACE(name_pinyin, ' ', '') gin_trgm_ops);
+CREATE INDEX idx_glossary_terms_pinyin_trgm
+    ON glossary_terms USING GIN (REPLACE(term_pinyin, ' ', '') gin_trgm_ops);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_architectures_created_at;
+
+ALTER TABLE architectures DROP COLUMN IF EXISTS created_at;
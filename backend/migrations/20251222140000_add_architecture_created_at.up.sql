@@ -0,0 +1,5 @@
+-- Lets feeds and listings order architectures by age.
+ALTER TABLE architectures
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+CREATE INDEX idx_architectures_created_at ON architectures(created_at DESC);
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS architecture_translations;
+
+ALTER TABLE users DROP COLUMN IF EXISTS locale;
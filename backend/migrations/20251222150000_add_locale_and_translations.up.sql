@@ -0,0 +1,14 @@
+-- Preferred content language; NULL means "use Accept-Language".
+ALTER TABLE users ADD COLUMN locale VARCHAR(10);
+
+-- Translated architecture content. Missing fields fall back to the source row.
+CREATE TABLE IF NOT EXISTS architecture_translations (
+    architecture_id BIGINT NOT NULL REFERENCES architectures(id) ON DELETE CASCADE,
+    locale VARCHAR(10) NOT NULL,
+    name VARCHAR(100),
+    dynasty VARCHAR(50),
+    location VARCHAR(200),
+    description TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (architecture_id, locale)
+);
@@ -0,0 +1,13 @@
+-- Append-only log of security-relevant events, used by the admin abuse dashboard.
+-- kind: 'login_failed', 'rate_limited', 'content_reported', ...
+CREATE TABLE IF NOT EXISTS security_events (
+    id BIGSERIAL PRIMARY KEY,
+    kind VARCHAR(50) NOT NULL,
+    user_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    ip VARCHAR(64),
+    path VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_security_events_created_at ON security_events(created_at);
+CREATE INDEX idx_security_events_kind_created_at ON security_events(kind, created_at);
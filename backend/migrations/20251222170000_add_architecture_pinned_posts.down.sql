@@ -0,0 +1,4 @@
+ALTER TABLE architectures
+    DROP CONSTRAINT IF EXISTS architectures_pinned_posts_distinct,
+    DROP COLUMN IF EXISTS pinned_tips_post_id,
+    DROP COLUMN IF EXISTS pinned_discussion_post_id;
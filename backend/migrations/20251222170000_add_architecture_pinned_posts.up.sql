@@ -0,0 +1,7 @@
+-- One canonical discussion thread and one "visiting tips" post per architecture, chosen by admins.
+ALTER TABLE architectures
+    ADD COLUMN pinned_discussion_post_id BIGINT REFERENCES posts(id) ON DELETE SET NULL,
+    ADD COLUMN pinned_tips_post_id BIGINT REFERENCES posts(id) ON DELETE SET NULL,
+    ADD CONSTRAINT architectures_pinned_posts_distinct
+        CHECK (pinned_discussion_post_id IS DISTINCT FROM pinned_tips_post_id
+               OR pinned_discussion_post_id IS NULL);
@@ -0,0 +1,11 @@
+-- Cached metadata for URLs linked from posts, filled in by the 'unfurl_link' job.
+CREATE TABLE IF NOT EXISTS link_previews (
+    url TEXT PRIMARY KEY,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'ok', 'failed')),
+    title TEXT,
+    description TEXT,
+    image_url TEXT,
+    error TEXT,
+    fetched_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
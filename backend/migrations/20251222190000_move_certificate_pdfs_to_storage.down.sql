@@ -0,0 +1,9 @@
+ALTER TABLE certificates ADD COLUMN pdf BYTEA;
+
+-- PDFs are regenerated by the job queue on demand; stored files are left in place.
+INSERT INTO jobs (kind, payload)
+SELECT 'certificate_pdf', jsonb_build_object('certificate_id', id)
+FROM certificates
+WHERE pdf_key IS NOT NULL;
+
+ALTER TABLE certificates DROP COLUMN pdf_key;
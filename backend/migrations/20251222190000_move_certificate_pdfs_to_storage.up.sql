@@ -0,0 +1,10 @@
+-- Certificate PDFs now live in the configured storage backend; only the key is kept here.
+ALTER TABLE certificates ADD COLUMN pdf_key TEXT;
+
+-- Re-render existing PDFs into storage rather than copying blobs out of the database.
+INSERT INTO jobs (kind, payload)
+SELECT 'certificate_pdf', jsonb_build_object('certificate_id', id)
+FROM certificates
+WHERE pdf IS NOT NULL;
+
+ALTER TABLE certificates DROP COLUMN pdf;
@@ -0,0 +1,13 @@
+-- One row per user and experiment, written when the assignment is first served.
+-- Assignments themselves are computed, not stored; this table is the exposure log
+-- used to compare variants.
+CREATE TABLE IF NOT EXISTS experiment_exposures (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    experiment_key VARCHAR(50) NOT NULL,
+    variant VARCHAR(50) NOT NULL,
+    first_exposed_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    last_exposed_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, experiment_key)
+);
+
+CREATE INDEX idx_experiment_exposures_key_variant ON experiment_exposures(experiment_key, variant);
@@ -0,0 +1,2 @@
+ALTER TABLE questions DROP COLUMN IF EXISTS suspended_at;
+DROP TABLE IF EXISTS question_flags;
@@ -0,0 +1,23 @@
+-- Test-takers flag questions they think are wrong or ambiguous; admins triage the flags.
+-- reason: 'wrong_answer', 'ambiguous', 'typo' or 'other'
+-- status: 'open', 'resolved' (question fixed) or 'dismissed' (flag rejected)
+CREATE TABLE IF NOT EXISTS question_flags (
+    id BIGSERIAL PRIMARY KEY,
+    question_id BIGINT NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason VARCHAR(20) NOT NULL,
+    comment TEXT,
+    status VARCHAR(20) NOT NULL DEFAULT 'open',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    resolved_at TIMESTAMPTZ,
+    CONSTRAINT question_flags_reason_check CHECK (reason IN ('wrong_answer', 'ambiguous', 'typo', 'other')),
+    CONSTRAINT question_flags_status_check CHECK (status IN ('open', 'resolved', 'dismissed'))
+);
+
+-- One open flag per user and question.
+CREATE UNIQUE INDEX idx_question_flags_open_per_user
+    ON question_flags(question_id, user_id) WHERE status = 'open';
+CREATE INDEX idx_question_flags_status_created_at ON question_flags(status, created_at);
+
+-- Set when a question collects enough open flags; suspended questions are left out of papers.
+ALTER TABLE questions ADD COLUMN suspended_at TIMESTAMPTZ;
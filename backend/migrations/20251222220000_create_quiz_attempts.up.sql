@@ -0,0 +1,19 @@
+-- Per-attempt practice history. exam_records keeps only each user's best score;
+-- these tables keep every submitted paper and whether each answer was correct.
+CREATE TABLE IF NOT EXISTS quiz_attempts (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    score INT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_quiz_attempts_user_created_at ON quiz_attempts(user_id, created_at);
+
+CREATE TABLE IF NOT EXISTS quiz_answers (
+    attempt_id BIGINT NOT NULL REFERENCES quiz_attempts(id) ON DELETE CASCADE,
+    question_id BIGINT NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+    is_correct BOOLEAN NOT NULL,
+    PRIMARY KEY (attempt_id, question_id)
+);
+
+CREATE INDEX idx_quiz_answers_question_id ON quiz_answers(question_id);
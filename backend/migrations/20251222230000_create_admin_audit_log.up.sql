@@ -0,0 +1,15 @@
+-- Admin edits to content, with a field-level diff of what changed.
+-- entity_type: 'architecture' or 'question'
+-- diff: [{"field": "...", "before": ..., "after": ...}, ...]
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    admin_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    action VARCHAR(50) NOT NULL,
+    entity_type VARCHAR(30) NOT NULL,
+    entity_id BIGINT NOT NULL,
+    diff JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_admin_audit_log_entity ON admin_audit_log(entity_type, entity_id, created_at);
+CREATE INDEX idx_admin_audit_log_created_at ON admin_audit_log(created_at);
@@ -0,0 +1,17 @@
+-- Published versions of the terms of service; the most recently published one is current.
+CREATE TABLE IF NOT EXISTS terms_versions (
+    id BIGSERIAL PRIMARY KEY,
+    version VARCHAR(20) NOT NULL UNIQUE,
+    content TEXT NOT NULL,
+    published_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_terms_versions_published_at ON terms_versions(published_at DESC);
+
+-- Which versions each user has accepted, and when.
+CREATE TABLE IF NOT EXISTS terms_acceptances (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    terms_version_id BIGINT NOT NULL REFERENCES terms_versions(id) ON DELETE CASCADE,
+    accepted_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, terms_version_id)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE users DROP COLUMN IF EXISTS region;
+ALTER TABLE users DROP COLUMN IF EXISTS birth_year;
@@ -0,0 +1,3 @@
+-- Collected at registration when age or region gating is configured.
+ALTER TABLE users ADD COLUMN birth_year INTEGER;
+ALTER TABLE users ADD COLUMN region VARCHAR(50);
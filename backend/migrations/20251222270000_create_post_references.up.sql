@@ -0,0 +1,12 @@
+-- Entities a post links to with [[arch:123]] or [[term:slug]].
+-- entity_type: 'architecture' or 'term'; entity_id is architectures.id or glossary_terms.id.
+CREATE TABLE IF NOT EXISTS post_references (
+    post_id BIGINT NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    entity_type VARCHAR(20) NOT NULL,
+    entity_id BIGINT NOT NULL,
+    PRIMARY KEY (post_id, entity_type, entity_id),
+    CONSTRAINT post_references_entity_type_check CHECK (entity_type IN ('architecture', 'term'))
+);
+
+-- Backlinks: posts mentioning an entity.
+CREATE INDEX idx_post_references_entity ON post_references(entity_type, entity_id);
@@ -0,0 +1,15 @@
+-- Results of the last 'check_image_links' job, one row per image URL of an architecture.
+-- status: 'ok', 'broken' (error response), 'unreachable' (network failure)
+-- or 'invalid' (not a URL the server may fetch)
+CREATE TABLE IF NOT EXISTS architecture_image_checks (
+    architecture_id BIGINT NOT NULL REFERENCES architectures(id) ON DELETE CASCADE,
+    field VARCHAR(10) NOT NULL CHECK (field IN ('cover', 'carousel')),
+    url TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL CHECK (status IN ('ok', 'broken', 'unreachable', 'invalid')),
+    http_status INT,
+    error TEXT,
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (architecture_id, field, url)
+);
+
+CREATE INDEX idx_architecture_image_checks_dead ON architecture_image_checks(architecture_id) WHERE status <> 'ok';
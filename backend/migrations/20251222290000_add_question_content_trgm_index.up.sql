@@ -0,0 +1,2 @@
+-- Trigram index for finding near-identical questions (GET /api/admin/questions/duplicates).
+CREATE INDEX IF NOT EXISTS idx_questions_content_trgm ON questions USING GIN (content gin_trgm_ops);
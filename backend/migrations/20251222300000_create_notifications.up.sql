@@ -0,0 +1,16 @@
+-- In-app notifications for a user.
+-- kind: what happened, e.g. 'verification_granted' or 'verification_revoked'
+-- payload: kind-specific details for the client to render
+CREATE TABLE IF NOT EXISTS notifications (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    read_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_notifications_user_created_at ON notifications(user_id, created_at DESC);
+
+-- Why an admin made a change, when they had to say (e.g. granting verification).
+ALTER TABLE admin_audit_log ADD COLUMN reason TEXT;
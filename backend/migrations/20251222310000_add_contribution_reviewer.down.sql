@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_contributions_pending;
+ALTER TABLE contributions DROP COLUMN IF EXISTS reviewed_by;
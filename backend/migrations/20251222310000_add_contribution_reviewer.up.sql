@@ -0,0 +1,7 @@
+-- Who reviewed a contribution, for per-admin review metrics.
+-- NULL for contributions reviewed before this was tracked, or by a deleted admin.
+ALTER TABLE contributions
+ADD COLUMN reviewed_by BIGINT REFERENCES users(id) ON DELETE SET NULL;
+
+-- The review backlog is read by age.
+CREATE INDEX idx_contributions_pending ON contributions(created_at) WHERE status = 'pending';
@@ -0,0 +1,3 @@
+ALTER TABLE posts
+DROP COLUMN IF EXISTS reading_time_minutes,
+DROP COLUMN IF EXISTS excerpt;
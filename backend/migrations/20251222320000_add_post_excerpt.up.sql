@@ -0,0 +1,6 @@
+-- Plain-text excerpt and estimated reading time, computed when a post is written
+-- so list endpoints need not send the full content.
+-- NULL until the `post_excerpts` job fills them in for existing posts.
+ALTER TABLE posts
+ADD COLUMN excerpt TEXT,
+ADD COLUMN reading_time_minutes INT;
@@ -0,0 +1,5 @@
+DROP TRIGGER IF EXISTS trigger_set_username_normalized ON users;
+DROP FUNCTION IF EXISTS set_username_normalized();
+DROP INDEX IF EXISTS idx_users_username_normalized;
+ALTER TABLE users DROP COLUMN IF EXISTS username_normalized;
+DROP FUNCTION IF EXISTS normalize_username(TEXT);
@@ -0,0 +1,35 @@
+-- Usernames are unique by their normalized form, so look-alikes such as
+-- 'Admin', 'admin' and 'ａdmin' (fullwidth) cannot coexist.
+-- Normalization: drop zero-width characters, trim, NFKC, lower-case.
+CREATE OR REPLACE FUNCTION normalize_username(name TEXT)
+RETURNS TEXT AS $$
+    SELECT lower(normalize(btrim(regexp_replace(name, '[\u200B-\u200D\u2060\uFEFF]', '', 'g')), NFKC));
+$$ LANGUAGE sql IMMUTABLE STRICT;
+
+ALTER TABLE users ADD COLUMN username_normalized TEXT;
+UPDATE users SET username_normalized = normalize_username(username);
+
+-- Accounts that already collide keep working: the oldest keeps the normalized
+-- name, later ones get their ID appended and can still log in by their exact username.
+UPDATE users u
+SET username_normalized = u.username_normalized || '#' || u.id
+WHERE EXISTS (
+    SELECT 1 FROM users o
+    WHERE o.username_normalized = u.username_normalized AND o.id < u.id
+);
+
+ALTER TABLE users ALTER COLUMN username_normalized SET NOT NULL;
+CREATE UNIQUE INDEX idx_users_username_normalized ON users(username_normalized);
+
+CREATE OR REPLACE FUNCTION set_username_normalized()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.username_normalized := normalize_username(NEW.username);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_set_username_normalized
+BEFORE INSERT OR UPDATE OF username ON users
+FOR EACH ROW
+EXECUTE FUNCTION set_username_normalized();
@@ -0,0 +1,5 @@
+-- The content exactly as the author submitted it, before sanitizing.
+-- Only returned to the author, so editing starts from what they typed
+-- rather than from the ammonia output. NULL for posts written before this.
+ALTER TABLE posts
+ADD COLUMN raw_content TEXT;
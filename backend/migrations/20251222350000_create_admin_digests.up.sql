@@ -0,0 +1,9 @@
+-- Daily summaries of pending admin work, as sent to admins.
+-- Also tells the `admin_digest` job when the last one went out.
+CREATE TABLE IF NOT EXISTS admin_digests (
+    id BIGSERIAL PRIMARY KEY,
+    summary JSONB NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_admin_digests_sent_at ON admin_digests(sent_at);
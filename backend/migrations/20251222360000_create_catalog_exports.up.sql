@@ -0,0 +1,10 @@
+-- Static JSON bundles of the architecture catalog, written to storage by the
+-- `export_catalog` job. The id is the bundle's version.
+CREATE TABLE IF NOT EXISTS catalog_exports (
+    id BIGSERIAL PRIMARY KEY,
+    storage_key TEXT NOT NULL,
+    architecture_count INT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    requested_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,18 @@
+-- Optional email address, for confirming and recovering accounts.
+-- An address only belongs to an account once verified, so an unverified
+-- address cannot lock its owner out.
+ALTER TABLE users ADD COLUMN email VARCHAR(254);
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX idx_users_verified_email ON users(lower(email)) WHERE email_verified_at IS NOT NULL;
+
+-- Outstanding verification links, by SHA-256 of the emailed token.
+CREATE TABLE IF NOT EXISTS email_verifications (
+    token_hash CHAR(64) PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email VARCHAR(254) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_email_verifications_user_id ON email_verifications(user_id);
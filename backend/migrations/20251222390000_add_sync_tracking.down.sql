@@ -0,0 +1,17 @@
+DROP TRIGGER IF EXISTS trigger_tombstone_questions ON questions;
+DROP TRIGGER IF EXISTS trigger_tombstone_glossary_terms ON glossary_terms;
+DROP TRIGGER IF EXISTS trigger_tombstone_architectures ON architectures;
+DROP FUNCTION IF EXISTS record_tombstone();
+DROP TABLE IF EXISTS tombstones;
+
+DROP TRIGGER IF EXISTS trigger_touch_translated_architecture ON architecture_translations;
+DROP FUNCTION IF EXISTS touch_translated_architecture();
+DROP TRIGGER IF EXISTS trigger_touch_questions ON questions;
+DROP TRIGGER IF EXISTS trigger_touch_architectures ON architectures;
+DROP FUNCTION IF EXISTS touch_updated_at();
+
+DROP INDEX IF EXISTS idx_glossary_terms_updated_at;
+DROP INDEX IF EXISTS idx_questions_updated_at;
+DROP INDEX IF EXISTS idx_architectures_updated_at;
+ALTER TABLE questions DROP COLUMN IF EXISTS updated_at;
+ALTER TABLE architectures DROP COLUMN IF EXISTS updated_at;
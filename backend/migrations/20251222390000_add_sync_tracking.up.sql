@@ -0,0 +1,88 @@
+-- Change tracking for `GET /api/sync/changes`.
+
+-- When an entry's content last changed. Bookkeeping columns (sampling keys,
+-- exposure counts, search keys, pins) and updates that change nothing do not count.
+ALTER TABLE architectures ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
+UPDATE architectures SET updated_at = created_at;
+ALTER TABLE questions ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
+UPDATE questions SET updated_at = COALESCE(created_at, updated_at);
+
+CREATE INDEX idx_architectures_updated_at ON architectures(updated_at);
+CREATE INDEX idx_questions_updated_at ON questions(updated_at);
+CREATE INDEX idx_glossary_terms_updated_at ON glossary_terms(updated_at);
+
+CREATE OR REPLACE FUNCTION touch_updated_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at := CURRENT_TIMESTAMP;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_touch_architectures
+BEFORE UPDATE OF category, name, dynasty, location, province, city, district,
+    description, cover_img, carousel_imgs ON architectures
+FOR EACH ROW
+WHEN ((OLD.category, OLD.name, OLD.dynasty, OLD.location, OLD.province, OLD.city, OLD.district,
+       OLD.description, OLD.cover_img, OLD.carousel_imgs)
+    IS DISTINCT FROM (NEW.category, NEW.name, NEW.dynasty, NEW.location, NEW.province, NEW.city,
+       NEW.district, NEW.description, NEW.cover_img, NEW.carousel_imgs))
+EXECUTE FUNCTION touch_updated_at();
+
+CREATE TRIGGER trigger_touch_questions
+BEFORE UPDATE OF type, content, options, answer, analysis ON questions
+FOR EACH ROW
+WHEN ((OLD.type, OLD.content, OLD.options, OLD.answer, OLD.analysis)
+    IS DISTINCT FROM (NEW.type, NEW.content, NEW.options, NEW.answer, NEW.analysis))
+EXECUTE FUNCTION touch_updated_at();
+
+-- Translations are part of the architecture entry.
+CREATE OR REPLACE FUNCTION touch_translated_architecture()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE architectures SET updated_at = CURRENT_TIMESTAMP
+    WHERE id = COALESCE(NEW.architecture_id, OLD.architecture_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_touch_translated_architecture
+AFTER INSERT OR UPDATE OR DELETE ON architecture_translations
+FOR EACH ROW
+EXECUTE FUNCTION touch_translated_architecture();
+
+-- Deleted public entries, so offline copies can drop them.
+-- entity_type: 'architecture', 'glossary_term' or 'question'
+CREATE TABLE IF NOT EXISTS tombstones (
+    entity_type VARCHAR(20) NOT NULL,
+    entity_id BIGINT NOT NULL,
+    deleted_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (entity_type, entity_id)
+);
+
+CREATE INDEX idx_tombstones_deleted_at ON tombstones(deleted_at);
+
+-- TG_ARGV[0] is the entity type.
+CREATE OR REPLACE FUNCTION record_tombstone()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO tombstones (entity_type, entity_id) VALUES (TG_ARGV[0], OLD.id)
+    ON CONFLICT (entity_type, entity_id) DO UPDATE SET deleted_at = CURRENT_TIMESTAMP;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_tombstone_architectures
+AFTER DELETE ON architectures
+FOR EACH ROW
+EXECUTE FUNCTION record_tombstone('architecture');
+
+CREATE TRIGGER trigger_tombstone_glossary_terms
+AFTER DELETE ON glossary_terms
+FOR EACH ROW
+EXECUTE FUNCTION record_tombstone('glossary_term');
+
+CREATE TRIGGER trigger_tombstone_questions
+AFTER DELETE ON questions
+FOR EACH ROW
+EXECUTE FUNCTION record_tombstone('question');
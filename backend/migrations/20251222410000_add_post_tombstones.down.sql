@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS trigger_tombstone_posts ON posts;
+DROP TRIGGER IF EXISTS trigger_tombstone_posts_hidden ON posts;
+DELETE FROM tombstones WHERE entity_type = 'post';
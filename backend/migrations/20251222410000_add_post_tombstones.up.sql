@@ -0,0 +1,13 @@
+-- Posts leave a tombstone when they are hidden (soft delete) or removed
+-- with their author, so caches of the feed can drop them.
+CREATE TRIGGER trigger_tombstone_posts_hidden
+AFTER UPDATE OF deleted_at ON posts
+FOR EACH ROW
+WHEN (OLD.deleted_at IS NULL AND NEW.deleted_at IS NOT NULL)
+EXECUTE FUNCTION record_tombstone('post');
+
+CREATE TRIGGER trigger_tombstone_posts
+AFTER DELETE ON posts
+FOR EACH ROW
+WHEN (OLD.deleted_at IS NULL)
+EXECUTE FUNCTION record_tombstone('post');
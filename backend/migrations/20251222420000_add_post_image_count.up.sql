@@ -0,0 +1,5 @@
+-- Images embedded in a post, counted against the author's image quota.
+ALTER TABLE posts ADD COLUMN image_count INT NOT NULL DEFAULT 0;
+
+UPDATE posts
+SET image_count = (SELECT COUNT(*) FROM regexp_matches(content, '<img[\s/>]', 'gi'));
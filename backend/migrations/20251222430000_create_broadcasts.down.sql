@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_notifications_broadcast_id;
+DROP TABLE IF EXISTS broadcasts;
+ALTER TABLE users DROP COLUMN IF EXISTS last_active_at;
@@ -0,0 +1,30 @@
+-- Last sign-in (or registration), for targeting recently active users.
+ALTER TABLE users ADD COLUMN last_active_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP;
+
+UPDATE users u SET last_active_at = GREATEST(
+    u.created_at,
+    (SELECT MAX(created_at) FROM posts WHERE user_id = u.id),
+    (SELECT MAX(created_at) FROM comments WHERE user_id = u.id)
+);
+
+CREATE INDEX idx_users_last_active_at ON users(last_active_at);
+
+-- Notifications sent by admins to many users at once. The `broadcast_notifications`
+-- job delivers them in batches of users ordered by ID; `cursor` is the last user
+-- reached, so a retried batch never notifies anyone twice.
+CREATE TABLE IF NOT EXISTS broadcasts (
+    id BIGSERIAL PRIMARY KEY,
+    title VARCHAR(100) NOT NULL,
+    message TEXT NOT NULL,
+    segment VARCHAR(20) NOT NULL,
+    created_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    recipients INT NOT NULL,
+    delivered INT NOT NULL DEFAULT 0,
+    cursor BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TIMESTAMPTZ
+);
+
+-- Read counts per broadcast.
+CREATE INDEX idx_notifications_broadcast_id ON notifications(((payload->>'broadcast_id')::BIGINT))
+WHERE kind = 'broadcast';
@@ -0,0 +1,21 @@
+-- Accounts at OAuth providers that can sign in as a user.
+-- `subject` is the provider's stable user ID (WeChat: unionid, else openid).
+CREATE TABLE IF NOT EXISTS oauth_identities (
+    provider VARCHAR(20) NOT NULL,
+    subject VARCHAR(128) NOT NULL,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (provider, subject),
+    -- One account per provider and user
+    UNIQUE (user_id, provider)
+);
+
+-- Sign-ins in progress, by SHA-256 of the `state` sent to the provider.
+-- `link_user_id` is set when a signed-in user is linking an account.
+CREATE TABLE IF NOT EXISTS oauth_states (
+    state_hash CHAR(64) PRIMARY KEY,
+    provider VARCHAR(20) NOT NULL,
+    link_user_id BIGINT REFERENCES users(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,9 @@
+-- Two-factor authentication with an authenticator app (TOTP).
+-- `totp_secret` is the secret encrypted under a key derived from JWT_SECRET;
+-- it is set at enrollment and enabled once a first code is verified.
+-- `totp_last_step` is the time step of the last accepted code, so a code
+-- cannot be used twice.
+ALTER TABLE users
+    ADD COLUMN totp_secret BYTEA,
+    ADD COLUMN totp_enabled_at TIMESTAMPTZ,
+    ADD COLUMN totp_last_step BIGINT;
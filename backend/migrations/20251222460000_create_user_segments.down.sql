@@ -0,0 +1,2 @@
+ALTER TABLE broadcasts DROP COLUMN IF EXISTS filter;
+DROP FUNCTION IF EXISTS segment_user_ids(TIMESTAMPTZ, BIGINT, BOOLEAN, INT);
@@ -0,0 +1,26 @@
+-- Users matching an admin segment filter; NULL arguments match everyone.
+-- Shared by segment previews, exports and broadcasts so they always agree.
+CREATE OR REPLACE FUNCTION segment_user_ids(
+    registered_before TIMESTAMPTZ,
+    min_posts BIGINT,
+    verified BOOLEAN,
+    active_within_days INT
+) RETURNS TABLE (user_id BIGINT) LANGUAGE sql STABLE AS $$
+    SELECT u.id FROM users u
+    WHERE (registered_before IS NULL OR u.created_at < registered_before)
+      AND (verified IS NULL OR u.is_verified = verified)
+      AND (active_within_days IS NULL
+           OR u.last_active_at > CURRENT_TIMESTAMP - make_interval(days => active_within_days))
+      AND (min_posts IS NULL OR min_posts <= 0
+           OR (SELECT COUNT(*) FROM posts p
+               WHERE p.user_id = u.id AND p.deleted_at IS NULL) >= min_posts)
+$$;
+
+-- Broadcasts now target a segment filter; the named segments become presets.
+ALTER TABLE broadcasts ADD COLUMN filter JSONB NOT NULL DEFAULT '{}';
+
+UPDATE broadcasts SET filter = CASE segment
+    WHEN 'verified' THEN '{"is_verified": true}'::JSONB
+    WHEN 'active' THEN '{"active_within_days": 30}'::JSONB
+    ELSE '{}'::JSONB
+END;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS question_translations;
+DROP FUNCTION IF EXISTS touch_translated_question();
@@ -0,0 +1,26 @@
+-- Translated exam questions. Missing fields fall back to the source row;
+-- answers are option letters, so they are shared by every language.
+CREATE TABLE IF NOT EXISTS question_translations (
+    question_id BIGINT NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+    locale VARCHAR(10) NOT NULL,
+    content TEXT,
+    options JSONB,
+    analysis TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (question_id, locale)
+);
+
+-- Translations are part of the question entry.
+CREATE OR REPLACE FUNCTION touch_translated_question()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE questions SET updated_at = CURRENT_TIMESTAMP
+    WHERE id = COALESCE(NEW.question_id, OLD.question_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_touch_translated_question
+AFTER INSERT OR UPDATE OR DELETE ON question_translations
+FOR EACH ROW
+EXECUTE FUNCTION touch_translated_question();
@@ -0,0 +1,11 @@
+-- Exam accommodations granted to a user by an admin: more time, larger
+-- option labels, or a shorter paper. Users without a row sit the standard exam.
+CREATE TABLE IF NOT EXISTS exam_accommodations (
+    user_id BIGINT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    time_multiplier DOUBLE PRECISION NOT NULL DEFAULT 1.0
+        CHECK (time_multiplier >= 1.0 AND time_multiplier <= 3.0),
+    large_labels BOOLEAN NOT NULL DEFAULT FALSE,
+    question_count INT CHECK (question_count > 0),
+    granted_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_architectures_category_random_key;
+DROP INDEX IF EXISTS idx_architectures_random_key;
+
+ALTER TABLE architectures DROP COLUMN IF EXISTS random_key;
@@ -0,0 +1,7 @@
+-- Random picks for "surprise me" (GET /api/architectures/random).
+-- random_key: precomputed sort key; re-rolled with the question keys so picks rotate
+ALTER TABLE architectures
+    ADD COLUMN random_key DOUBLE PRECISION NOT NULL DEFAULT random();
+
+CREATE INDEX idx_architectures_random_key ON architectures(random_key);
+CREATE INDEX idx_architectures_category_random_key ON architectures(category, random_key);
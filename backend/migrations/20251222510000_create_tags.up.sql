@@ -0,0 +1,26 @@
+-- Tags authors put on their posts. `slug` is the URL form of the name
+-- (pinyin for Chinese, e.g. "斗拱" -> "dougong"); `name` is the spelling
+-- the tag was first used with.
+CREATE TABLE IF NOT EXISTS tags (
+    id BIGSERIAL PRIMARY KEY,
+    slug VARCHAR(200) NOT NULL UNIQUE,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS post_tags (
+    post_id BIGINT NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    tag_id BIGINT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (post_id, tag_id)
+);
+
+CREATE INDEX idx_post_tags_tag_id ON post_tags(tag_id);
+
+-- The most active tags of the last days, rewritten by the trending_tags job.
+CREATE TABLE IF NOT EXISTS trending_tags (
+    tag_id BIGINT PRIMARY KEY REFERENCES tags(id) ON DELETE CASCADE,
+    posts BIGINT NOT NULL,
+    authors BIGINT NOT NULL,
+    score DOUBLE PRECISION NOT NULL,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,6 @@
+-- When a user who asked to delete their own account is deleted for good.
+-- NULL unless a deletion is pending; the user can cancel until then.
+ALTER TABLE users ADD COLUMN deletion_scheduled_at TIMESTAMPTZ;
+
+CREATE INDEX idx_users_deletion_scheduled_at ON users(deletion_scheduled_at)
+    WHERE deletion_scheduled_at IS NOT NULL;
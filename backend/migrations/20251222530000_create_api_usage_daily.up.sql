@@ -0,0 +1,11 @@
+-- Authenticated API requests per user and UTC day. Sampled: every Nth request
+-- of a user is recorded as N, so counts are accurate to within N per day.
+CREATE TABLE IF NOT EXISTS api_usage_daily (
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    requests BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, day)
+);
+
+-- Anomaly views scan one day across all users.
+CREATE INDEX idx_api_usage_daily_day ON api_usage_daily(day);
@@ -0,0 +1,4 @@
+-- Bans: a banned user can still sign in and read, but not write.
+-- ban_reason is set while a ban is in place; banned_until NULL means indefinitely.
+ALTER TABLE users ADD COLUMN banned_until TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN ban_reason TEXT;
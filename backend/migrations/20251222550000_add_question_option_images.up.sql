@@ -0,0 +1,3 @@
+-- Optional images for question options, aligned with `options` by index.
+-- Entries are URLs or null; a shorter array means the rest have no image.
+ALTER TABLE questions ADD COLUMN option_images JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_audit_logs_admin;
+ALTER INDEX idx_audit_logs_created_at RENAME TO idx_admin_audit_log_created_at;
+ALTER INDEX idx_audit_logs_entity RENAME TO idx_admin_audit_log_entity;
+ALTER TABLE audit_logs RENAME TO admin_audit_log;
@@ -0,0 +1,9 @@
+-- The audit trail now covers every admin mutation, not just content edits.
+-- action: 'create', 'update', 'delete', 'review' or 'merge'
+-- For creations and deletions the diff holds every field, with null on the
+-- missing side.
+ALTER TABLE admin_audit_log RENAME TO audit_logs;
+ALTER INDEX idx_admin_audit_log_entity RENAME TO idx_audit_logs_entity;
+ALTER INDEX idx_admin_audit_log_created_at RENAME TO idx_audit_logs_created_at;
+
+CREATE INDEX idx_audit_logs_admin ON audit_logs(admin_id, created_at);
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_architectures_footprint;
+DROP INDEX IF EXISTS idx_architectures_height;
+ALTER TABLE architectures DROP COLUMN IF EXISTS bays_deep;
+ALTER TABLE architectures DROP COLUMN IF EXISTS bays_wide;
+ALTER TABLE architectures DROP COLUMN IF EXISTS footprint_m2;
+ALTER TABLE architectures DROP COLUMN IF EXISTS height_m;
@@ -0,0 +1,9 @@
+-- Measured size of an architecture, always stored in metric units.
+-- bays_wide / bays_deep: number of bays (间) across the front and in depth.
+ALTER TABLE architectures ADD COLUMN height_m DOUBLE PRECISION CHECK (height_m > 0);
+ALTER TABLE architectures ADD COLUMN footprint_m2 DOUBLE PRECISION CHECK (footprint_m2 > 0);
+ALTER TABLE architectures ADD COLUMN bays_wide INTEGER CHECK (bays_wide > 0);
+ALTER TABLE architectures ADD COLUMN bays_deep INTEGER CHECK (bays_deep > 0);
+
+CREATE INDEX idx_architectures_height ON architectures(height_m) WHERE height_m IS NOT NULL;
+CREATE INDEX idx_architectures_footprint ON architectures(footprint_m2) WHERE footprint_m2 IS NOT NULL;
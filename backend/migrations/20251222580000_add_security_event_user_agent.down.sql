@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_security_events_user_created_at;
+ALTER TABLE security_events DROP COLUMN IF EXISTS user_agent;
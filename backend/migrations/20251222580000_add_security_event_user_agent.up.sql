@@ -0,0 +1,6 @@
+-- Account events (sign-ins, registration, password changes) are shown to the
+-- user they belong to, with the device they came from.
+ALTER TABLE security_events ADD COLUMN user_agent VARCHAR(500);
+
+CREATE INDEX idx_security_events_user_created_at ON security_events(user_id, created_at)
+    WHERE user_id IS NOT NULL;
@@ -0,0 +1,13 @@
+-- Placeholders for cover and post images, filled in by the 'image_placeholder' job.
+CREATE TABLE IF NOT EXISTS image_placeholders (
+    url TEXT PRIMARY KEY,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'ok', 'failed')),
+    -- '#rrggbb'
+    dominant_color CHAR(7),
+    blurhash VARCHAR(100),
+    width INT,
+    height INT,
+    error TEXT,
+    processed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,13 @@
+-- Printable PDF dossiers of architectures, rendered by the 'architecture_dossier' job.
+-- source_updated_at: architectures.updated_at when the dossier was requested;
+-- a dossier older than its architecture is rendered again on the next request.
+CREATE TABLE IF NOT EXISTS architecture_dossiers (
+    architecture_id BIGINT NOT NULL REFERENCES architectures(id) ON DELETE CASCADE,
+    locale VARCHAR(10) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'ready')),
+    storage_key TEXT,
+    source_updated_at TIMESTAMPTZ NOT NULL,
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    generated_at TIMESTAMPTZ,
+    PRIMARY KEY (architecture_id, locale)
+);
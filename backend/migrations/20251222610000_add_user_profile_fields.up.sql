@@ -0,0 +1,6 @@
+-- Public profile fields, edited by the user. NULL when not set.
+ALTER TABLE users
+    ADD COLUMN bio TEXT,
+    ADD COLUMN location VARCHAR(100),
+    ADD COLUMN website TEXT,
+    ADD COLUMN avatar_url TEXT;
@@ -0,0 +1,14 @@
+-- Reference images and PDF scans attached to a contribution, for reviewers.
+-- contribution_id becomes NULL when the contribution is deleted with its
+-- author, so the retention job can still find and delete the stored file.
+CREATE TABLE IF NOT EXISTS contribution_attachments (
+    id BIGSERIAL PRIMARY KEY,
+    contribution_id BIGINT REFERENCES contributions(id) ON DELETE SET NULL,
+    storage_key TEXT NOT NULL UNIQUE,
+    filename VARCHAR(200) NOT NULL,
+    content_type VARCHAR(50) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_contribution_attachments_contribution ON contribution_attachments(contribution_id);
@@ -0,0 +1,3 @@
+-- Preferences validated by the server (see models::user::UserSettings).
+-- The language is kept in users.locale.
+ALTER TABLE users ADD COLUMN user_settings JSONB NOT NULL DEFAULT '{}'::jsonb;
@@ -0,0 +1,11 @@
+ALTER TABLE contributions ADD COLUMN admin_comment TEXT;
+
+UPDATE contributions c
+SET admin_comment = (
+    SELECT m.body FROM contribution_messages m
+    WHERE m.contribution_id = c.id AND m.from_reviewer
+    ORDER BY m.id DESC
+    LIMIT 1
+);
+
+DROP TABLE IF EXISTS contribution_messages;
@@ -0,0 +1,20 @@
+-- Conversation between reviewers and the contributor about a contribution,
+-- replacing contributions.admin_comment. Existing comments become a reviewer
+-- message in their contribution's thread.
+CREATE TABLE IF NOT EXISTS contribution_messages (
+    id BIGSERIAL PRIMARY KEY,
+    contribution_id BIGINT NOT NULL REFERENCES contributions(id) ON DELETE CASCADE,
+    author_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    from_reviewer BOOLEAN NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_contribution_messages_contribution ON contribution_messages(contribution_id, id);
+
+INSERT INTO contribution_messages (contribution_id, author_id, from_reviewer, body, created_at)
+SELECT id, reviewed_by, TRUE, admin_comment, COALESCE(reviewed_at, created_at)
+FROM contributions
+WHERE admin_comment IS NOT NULL AND admin_comment <> '';
+
+ALTER TABLE contributions DROP COLUMN admin_comment;
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_contributions_unaudited;
+ALTER TABLE contributions
+DROP COLUMN IF EXISTS audited_at,
+DROP COLUMN IF EXISTS audited_by,
+DROP COLUMN IF EXISTS auto_approved;
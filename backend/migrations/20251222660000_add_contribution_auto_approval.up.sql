@@ -0,0 +1,10 @@
+-- Contributions published without review under the auto-approval rules
+-- (see config::AutoApprovalConfig). Admins check them afterwards: audited_at is
+-- set when one is confirmed or rolled back.
+ALTER TABLE contributions
+ADD COLUMN auto_approved BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN audited_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+ADD COLUMN audited_at TIMESTAMPTZ;
+
+CREATE INDEX idx_contributions_unaudited ON contributions(created_at)
+WHERE auto_approved AND audited_at IS NULL;
@@ -0,0 +1,38 @@
+-- Private messages between two users. A conversation stores its pair once,
+-- with the lower user ID first.
+CREATE TABLE IF NOT EXISTS conversations (
+    id BIGSERIAL PRIMARY KEY,
+    user_low_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    user_high_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_message_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_low_id, user_high_id),
+    CHECK (user_low_id < user_high_id)
+);
+
+CREATE INDEX idx_conversations_low ON conversations(user_low_id, last_message_at DESC);
+CREATE INDEX idx_conversations_high ON conversations(user_high_id, last_message_at DESC);
+
+CREATE TABLE IF NOT EXISTS messages (
+    id BIGSERIAL PRIMARY KEY,
+    conversation_id BIGINT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    sender_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- When the recipient opened the conversation after this message arrived.
+    read_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_messages_conversation ON messages(conversation_id, id DESC);
+CREATE INDEX idx_messages_unread ON messages(conversation_id, sender_id) WHERE read_at IS NULL;
+
+-- Users who may not message the blocker.
+CREATE TABLE IF NOT EXISTS user_blocks (
+    blocker_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    blocked_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (blocker_id, blocked_id),
+    CHECK (blocker_id <> blocked_id)
+);
+
+CREATE INDEX idx_user_blocks_blocked ON user_blocks(blocked_id);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS architecture_redirects;
@@ -0,0 +1,10 @@
+-- IDs of architectures merged into others, so old links keep working.
+-- Redirects always point at a live architecture: merging a target again
+-- re-points the redirects to it.
+CREATE TABLE IF NOT EXISTS architecture_redirects (
+    from_id BIGINT PRIMARY KEY,
+    to_id BIGINT NOT NULL REFERENCES architectures(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_architecture_redirects_to ON architecture_redirects(to_id);
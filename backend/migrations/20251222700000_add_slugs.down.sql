@@ -0,0 +1,3 @@
+ALTER TABLE architecture_redirects DROP COLUMN IF EXISTS from_slug;
+ALTER TABLE posts DROP COLUMN IF EXISTS slug;
+ALTER TABLE architectures DROP COLUMN IF EXISTS slug;
@@ -0,0 +1,12 @@
+-- URL slugs for architectures and posts: the name or title in pinyin, with a
+-- numeric suffix on collisions. Rows created before slugs existed get theirs
+-- from the background slug job.
+ALTER TABLE architectures ADD COLUMN slug TEXT;
+ALTER TABLE posts ADD COLUMN slug TEXT;
+
+CREATE UNIQUE INDEX idx_architectures_slug ON architectures(slug);
+CREATE UNIQUE INDEX idx_posts_slug ON posts(slug);
+
+-- The slug a merged architecture had, so slug links redirect like ID links.
+ALTER TABLE architecture_redirects ADD COLUMN from_slug TEXT;
+CREATE UNIQUE INDEX idx_architecture_redirects_slug ON architecture_redirects(from_slug);
@@ -0,0 +1,13 @@
+-- Timed competitions. While one runs the question bank is locked and the
+-- exam leaderboard leaves out scores submitted since it started.
+CREATE TABLE IF NOT EXISTS exam_events (
+    id BIGSERIAL PRIMARY KEY,
+    title TEXT NOT NULL,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    created_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX idx_exam_events_ends_at ON exam_events(ends_at);
@@ -0,0 +1,16 @@
+-- Archives of a user's own data, written by the `export_user_data` job.
+-- user_id is cleared when the account goes, so the cleanup job can still
+-- find and delete the file.
+CREATE TABLE IF NOT EXISTS data_exports (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    job_id BIGINT NOT NULL,
+    storage_key TEXT,
+    size_bytes BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ready_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_data_exports_user ON data_exports(user_id, created_at DESC);
+CREATE INDEX idx_data_exports_expires_at ON data_exports(expires_at) WHERE storage_key IS NOT NULL;
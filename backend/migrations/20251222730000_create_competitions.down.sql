@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS competition_entries;
+DROP TABLE IF EXISTS competitions;
@@ -0,0 +1,36 @@
+-- Competitions: a fixed paper answered by registered users within a time
+-- window. Each one holds an exam event over the same window, so the
+-- question bank stays locked while it runs.
+CREATE TABLE IF NOT EXISTS competitions (
+    id BIGSERIAL PRIMARY KEY,
+    title TEXT NOT NULL,
+    description TEXT,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    -- The paper, in the order it is served.
+    question_ids BIGINT[] NOT NULL,
+    exam_event_id BIGINT REFERENCES exam_events(id) ON DELETE SET NULL,
+    created_by BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at > starts_at),
+    CHECK (cardinality(question_ids) > 0)
+);
+
+CREATE INDEX idx_competitions_starts_at ON competitions(starts_at DESC);
+
+-- One row per registered user; scored once on submission.
+CREATE TABLE IF NOT EXISTS competition_entries (
+    competition_id BIGINT NOT NULL REFERENCES competitions(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    registered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- When the paper was first served.
+    started_at TIMESTAMPTZ,
+    submitted_at TIMESTAMPTZ,
+    answers JSONB,
+    -- Correct answers.
+    score INT,
+    PRIMARY KEY (competition_id, user_id),
+    CHECK (submitted_at IS NULL OR (started_at IS NOT NULL AND score IS NOT NULL))
+);
+
+CREATE INDEX idx_competition_entries_user ON competition_entries(user_id);
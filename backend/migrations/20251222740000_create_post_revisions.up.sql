@@ -0,0 +1,16 @@
+-- When the author last edited a post; NULL for posts never edited.
+ALTER TABLE posts
+ADD COLUMN edited_at TIMESTAMPTZ;
+
+-- The title and content a post had before each edit.
+CREATE TABLE IF NOT EXISTS post_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    post_id BIGINT NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    raw_content TEXT,
+    -- When this version was replaced.
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_post_revisions_post_id ON post_revisions(post_id, id DESC);
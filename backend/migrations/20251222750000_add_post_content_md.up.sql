@@ -0,0 +1,7 @@
+-- The Markdown source of a post, rendered to the sanitized HTML in
+-- `content`. NULL for posts written before Markdown was accepted.
+ALTER TABLE posts
+ADD COLUMN content_md TEXT;
+
+ALTER TABLE post_revisions
+ADD COLUMN content_md TEXT;
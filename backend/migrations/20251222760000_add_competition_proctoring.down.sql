@@ -0,0 +1,4 @@
+ALTER TABLE competition_entries
+DROP COLUMN IF EXISTS paste_count,
+DROP COLUMN IF EXISTS focus_loss_count,
+DROP COLUMN IF EXISTS proctoring_consent;
@@ -0,0 +1,6 @@
+-- Proctoring signals reported by the client with a competition submission.
+-- Kept only for entrants who agreed when registering; NULL when not reported.
+ALTER TABLE competition_entries
+ADD COLUMN proctoring_consent BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN focus_loss_count INT,
+ADD COLUMN paste_count INT;
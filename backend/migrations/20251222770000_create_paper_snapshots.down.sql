@@ -0,0 +1,7 @@
+ALTER TABLE competitions DROP COLUMN IF EXISTS paper_snapshot_id;
+
+ALTER TABLE quiz_attempts
+DROP COLUMN IF EXISTS answers,
+DROP COLUMN IF EXISTS paper_snapshot_id;
+
+DROP TABLE IF EXISTS paper_snapshots;
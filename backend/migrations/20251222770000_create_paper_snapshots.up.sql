@@ -0,0 +1,37 @@
+-- The questions of a paper as they were when it was set or scored, so that
+-- later edits to the question bank do not rewrite past results.
+CREATE TABLE IF NOT EXISTS paper_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    questions JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Both NULL for attempts submitted before snapshots were kept.
+ALTER TABLE quiz_attempts
+ADD COLUMN paper_snapshot_id BIGINT REFERENCES paper_snapshots(id),
+ADD COLUMN answers JSONB;
+
+ALTER TABLE competitions ADD COLUMN paper_snapshot_id BIGINT REFERENCES paper_snapshots(id);
+
+-- Existing competitions are frozen as their questions stand now
+DO $$
+DECLARE
+    c RECORD;
+    snapshot_id BIGINT;
+BEGIN
+    FOR c IN SELECT id, question_ids FROM competitions LOOP
+        INSERT INTO paper_snapshots (questions)
+        SELECT COALESCE(jsonb_agg(jsonb_build_object(
+            'id', q.id, 'question_type', q.type, 'content', q.content,
+            'options', q.options, 'option_images', q.option_images,
+            'answer', q.answer, 'analysis', q.analysis, 'created_at', q.created_at,
+            'contribution_id', q.contribution_id, 'created_by', q.created_by
+        ) ORDER BY p.position), '[]')
+        FROM UNNEST(c.question_ids) WITH ORDINALITY AS p(id, position)
+        JOIN questions q ON q.id = p.id
+        RETURNING id INTO snapshot_id;
+        UPDATE competitions SET paper_snapshot_id = snapshot_id WHERE id = c.id;
+    END LOOP;
+END $$;
+
+ALTER TABLE competitions ALTER COLUMN paper_snapshot_id SET NOT NULL;
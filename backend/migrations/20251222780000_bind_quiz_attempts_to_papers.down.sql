@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_paper_snapshots_created_at;
+DROP INDEX IF EXISTS idx_quiz_attempts_paper_snapshot;
@@ -0,0 +1,6 @@
+-- Practice papers are snapshotted when served, and each is submitted once.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_quiz_attempts_paper_snapshot
+ON quiz_attempts (paper_snapshot_id);
+
+-- Snapshots of papers that were served but never submitted are purged.
+CREATE INDEX IF NOT EXISTS idx_paper_snapshots_created_at ON paper_snapshots (created_at);
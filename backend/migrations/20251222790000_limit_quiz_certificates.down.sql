@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_certificates_paper_snapshot;
+DROP INDEX IF EXISTS idx_certificates_quiz_daily;
+
+ALTER TABLE certificates
+DROP COLUMN IF EXISTS quiz_day,
+DROP COLUMN IF EXISTS paper_snapshot_id;
@@ -0,0 +1,20 @@
+-- The paper a certificate was earned on; NULL for certificates issued before
+-- papers were snapshotted when served.
+ALTER TABLE certificates
+ADD COLUMN paper_snapshot_id BIGINT REFERENCES paper_snapshots(id),
+ADD COLUMN quiz_day DATE;
+
+-- One quiz certificate per user per UTC day. Of those already issued on the
+-- same day, the first takes the day; the rest stay valid but have none.
+UPDATE certificates c
+SET quiz_day = (c.issued_at AT TIME ZONE 'UTC')::date
+WHERE c.kind = 'quiz'
+  AND c.id = (
+      SELECT MIN(d.id) FROM certificates d
+      WHERE d.kind = 'quiz'
+        AND d.user_id = c.user_id
+        AND (d.issued_at AT TIME ZONE 'UTC')::date = (c.issued_at AT TIME ZONE 'UTC')::date
+  );
+
+CREATE UNIQUE INDEX idx_certificates_quiz_daily ON certificates(user_id, quiz_day) WHERE kind = 'quiz';
+CREATE UNIQUE INDEX idx_certificates_paper_snapshot ON certificates(paper_snapshot_id);
@@ -0,0 +1 @@
+ALTER TABLE exam_records DROP COLUMN IF EXISTS achieved_at;
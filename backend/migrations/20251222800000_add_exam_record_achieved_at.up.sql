@@ -0,0 +1,7 @@
+-- When the best score was reached. created_at moves on every retake, so a
+-- worse retake during an exam event would otherwise hide an older best score.
+ALTER TABLE exam_records ADD COLUMN achieved_at TIMESTAMPTZ;
+UPDATE exam_records SET achieved_at = COALESCE(created_at, CURRENT_TIMESTAMP);
+ALTER TABLE exam_records
+    ALTER COLUMN achieved_at SET NOT NULL,
+    ALTER COLUMN achieved_at SET DEFAULT CURRENT_TIMESTAMP;
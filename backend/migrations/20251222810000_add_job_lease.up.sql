@@ -0,0 +1,6 @@
+-- When a job was last claimed. A job still 'running' long after that lost its
+-- worker (crash, restart, or a failed status update) and is claimed again.
+ALTER TABLE jobs ADD COLUMN locked_at TIMESTAMPTZ;
+UPDATE jobs SET locked_at = CURRENT_TIMESTAMP WHERE status = 'running';
+
+CREATE INDEX idx_jobs_running ON jobs(locked_at) WHERE status = 'running';
@@ -0,0 +1,5 @@
+ALTER TABLE post_revisions ADD COLUMN raw_content TEXT;
+UPDATE post_revisions SET raw_content = content_md;
+
+ALTER TABLE posts ADD COLUMN raw_content TEXT;
+UPDATE posts SET raw_content = content_md;
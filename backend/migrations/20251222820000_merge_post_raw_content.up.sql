@@ -0,0 +1,8 @@
+-- Keep one source column. Since posts are written in Markdown, raw_content
+-- held the same text as content_md; posts written before Markdown keep their
+-- raw HTML there, which is valid Markdown input as well.
+UPDATE posts SET content_md = raw_content WHERE content_md IS NULL;
+ALTER TABLE posts DROP COLUMN raw_content;
+
+UPDATE post_revisions SET content_md = raw_content WHERE content_md IS NULL;
+ALTER TABLE post_revisions DROP COLUMN raw_content;
@@ -0,0 +1 @@
+ALTER TABLE paper_snapshots DROP COLUMN IF EXISTS option_orders;
@@ -0,0 +1,4 @@
+-- The option order each question was shown in, by question id, for papers
+-- that shuffle options. Kept here rather than in the exam token, which the
+-- examinee can read. NULL when options were shown as stored.
+ALTER TABLE paper_snapshots ADD COLUMN option_orders JSONB;
@@ -0,0 +1,275 @@
+// src/api_docs.rs
+
+//! OpenAPI documents, one per audience.
+//!
+//! The public document is the read-only API researchers and partners may use
+//! without an account; it never mentions account or admin endpoints. The user
+//! document adds everything a logged-in user can call, and the admin document
+//! the `/api/admin` surface. Documents are built from the table below, which
+//! must be kept in step with `routes.rs`.
+
+use utoipa::openapi::{
+    Components, HttpMethod, InfoBuilder, OpenApi, OpenApiBuilder, PathsBuilder, Required,
+    ResponseBuilder,
+    path::{OperationBuilder, ParameterBuilder, ParameterIn},
+    security::{HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme},
+};
+
+use Audience::{Admin, Public, User};
+use HttpMethod::{Delete, Get, Post, Put};
+
+/// Name of the security scheme for endpoints that need a token.
+const BEARER: &str = "bearer";
+
+/// Who a document is written for. Each audience also sees the endpoints of
+/// the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Audience {
+    /// Read-only endpoints anyone may call.
+    Public,
+    /// Endpoints for signed-up users, including registration and login.
+    User,
+    /// Admin-only endpoints.
+    Admin,
+}
+
+struct Endpoint {
+    audience: Audience,
+    method: HttpMethod,
+    path: &'static str,
+    summary: &'static str,
+}
+
+const fn endpoint(
+    audience: Audience,
+    method: HttpMethod,
+    path: &'static str,
+    summary: &'static str,
+) -> Endpoint {
+    Endpoint { audience, method, path, summary }
+}
+
+const ENDPOINTS: &[Endpoint] = &[
+    endpoint(Public, Get, "/api/architectures", "List architectures"),
+    endpoint(Public, Get, "/api/architectures/compare", "Compare dimensions of a few architectures"),
+    endpoint(Public, Get, "/api/architectures/random", "Pick a random architecture"),
+    endpoint(Public, Get, "/api/architectures/{id}", "Get an architecture by ID or slug"),
+    endpoint(Public, Get, "/api/architectures/{id}/dossier.pdf", "Download a printable dossier PDF"),
+    endpoint(Public, Get, "/api/glossary", "List glossary terms"),
+    endpoint(Public, Get, "/api/glossary/{slug}", "Get a glossary term"),
+    endpoint(Public, Get, "/api/posts", "List posts"),
+    endpoint(Public, Get, "/api/posts/{id}", "Get a post by ID or slug"),
+    endpoint(Public, Get, "/api/posts/{id}/comments", "List a post's comments"),
+    endpoint(Public, Get, "/api/posts/{id}/revisions", "Earlier versions of a post"),
+    endpoint(Public, Get, "/api/series/{id}", "Get a series"),
+    endpoint(Public, Get, "/api/tags/trending", "Trending tags"),
+    endpoint(Public, Get, "/api/tags/{slug}", "Get a topic page"),
+    endpoint(Public, Get, "/api/contributions/leaderboard", "Contributor leaderboard"),
+    endpoint(Public, Get, "/api/terms", "Current terms of service"),
+    endpoint(Public, Get, "/api/certificates/{code}", "Verify a certificate"),
+    endpoint(Public, Get, "/api/certificates/{code}/pdf", "Download a certificate PDF"),
+    endpoint(Public, Get, "/api/users/{id}", "A user's public profile"),
+    endpoint(Public, Get, "/api/users/{id}/followers", "A user's followers"),
+    endpoint(Public, Get, "/api/users/{id}/activity", "A user's activity timeline"),
+    endpoint(Public, Get, "/api/stats/public", "Site-wide statistics"),
+    endpoint(Public, Get, "/api/stats/online", "Online user count"),
+    endpoint(Public, Get, "/api/feed/discover", "Discovery feed"),
+    endpoint(Public, Get, "/api/sync/changes", "Changes for offline copies"),
+    endpoint(Public, Get, "/api/quiz/generate", "Generate a practice paper"),
+    endpoint(Public, Get, "/api/quiz/leaderboard", "Quiz leaderboard"),
+    endpoint(Public, Get, "/api/quiz/architecture/{id}", "Quiz about an architecture"),
+    endpoint(Public, Get, "/api/competitions", "List competitions"),
+    endpoint(Public, Get, "/api/competitions/{id}", "Get a competition"),
+    endpoint(Public, Get, "/api/competitions/{id}/results", "Final ranking of a competition"),
+    endpoint(Public, Get, "/api/docs/openapi.json", "This document"),
+    endpoint(User, Post, "/api/auth/register", "Register"),
+    endpoint(User, Post, "/api/auth/login", "Log in"),
+    endpoint(User, Get, "/api/auth/form-token", "Get a token for form timing checks"),
+    endpoint(User, Post, "/api/auth/logout", "Log out and revoke the token"),
+    endpoint(User, Post, "/api/auth/verify-email", "Confirm an email address"),
+    endpoint(User, Post, "/api/auth/forgot-password", "Request a password reset link"),
+    endpoint(User, Post, "/api/auth/reset-password", "Reset a password"),
+    endpoint(User, Get, "/api/auth/oauth/{provider}/authorize", "Start an OAuth sign-in"),
+    endpoint(User, Post, "/api/auth/oauth/{provider}/callback", "Finish an OAuth sign-in"),
+    endpoint(User, Post, "/api/auth/oauth/{provider}/link", "Link an OAuth account"),
+    endpoint(User, Post, "/api/auth/2fa/enroll", "Start two-factor enrollment"),
+    endpoint(User, Post, "/api/auth/2fa/verify", "Verify a two-factor code"),
+    endpoint(User, Get, "/api/auth/qualification", "Start the qualification exam"),
+    endpoint(User, Post, "/api/auth/qualification/submit", "Submit the qualification exam"),
+    endpoint(User, Post, "/api/posts", "Create a post"),
+    endpoint(User, Put, "/api/posts/{id}", "Edit a post"),
+    endpoint(User, Delete, "/api/posts/{id}", "Delete a post"),
+    endpoint(User, Post, "/api/posts/{id}/like", "Like or unlike a post"),
+    endpoint(User, Post, "/api/posts/{id}/favorite", "Favorite or unfavorite a post"),
+    endpoint(User, Post, "/api/posts/{id}/comments", "Comment on a post"),
+    endpoint(User, Delete, "/api/posts/{id}/comments/{comment_id}", "Delete a comment"),
+    endpoint(User, Post, "/api/series", "Create a series"),
+    endpoint(User, Post, "/api/series/{id}/posts", "Add a post to a series"),
+    endpoint(User, Put, "/api/series/{id}/posts", "Reorder a series"),
+    endpoint(User, Delete, "/api/series/{id}/posts/{post_id}", "Remove a post from a series"),
+    endpoint(User, Post, "/api/users/{id}/follow", "Follow or unfollow a user"),
+    endpoint(User, Post, "/api/users/{id}/block", "Block or unblock a user"),
+    endpoint(User, Post, "/api/users/{id}/mute", "Mute or unmute a user"),
+    endpoint(User, Get, "/api/profile/me", "Current user's profile"),
+    endpoint(User, Put, "/api/profile/me", "Edit the current user's bio, location, website and avatar"),
+    endpoint(User, Delete, "/api/profile/me", "Schedule deletion of the current user's account"),
+    endpoint(User, Delete, "/api/profile/me/deletion", "Cancel a pending account deletion"),
+    endpoint(User, Get, "/api/profile/export", "The current user's latest data export"),
+    endpoint(User, Post, "/api/profile/export", "Request an export of the current user's data"),
+    endpoint(User, Get, "/api/profile/posts", "Current user's posts"),
+    endpoint(User, Get, "/api/profile/favorites", "Current user's favorites"),
+    endpoint(User, Get, "/api/profile/contributions", "Current user's contributions"),
+    endpoint(User, Get, "/api/profile/certificates", "Current user's certificates"),
+    endpoint(User, Get, "/api/profile/quiz-stats", "Current user's quiz statistics"),
+    endpoint(User, Get, "/api/profile/activity-heatmap", "Current user's daily activity"),
+    endpoint(User, Get, "/api/profile/usage", "Current user's API usage"),
+    endpoint(User, Get, "/api/profile/limits", "Current user's quota usage"),
+    endpoint(User, Get, "/api/profile/sessions", "Devices the current user is signed in on"),
+    endpoint(User, Delete, "/api/profile/sessions/{id}", "Sign out of one device"),
+    endpoint(User, Get, "/api/profile/security-events", "Recent sign-ins and account changes"),
+    endpoint(User, Get, "/api/profile/blocks", "Users the current user blocked or muted"),
+    endpoint(User, Put, "/api/profile/locale", "Set the preferred locale"),
+    endpoint(User, Get, "/api/profile/settings", "Current user's settings"),
+    endpoint(User, Put, "/api/profile/settings", "Replace the current user's settings"),
+    endpoint(User, Put, "/api/profile/email", "Set the email address"),
+    endpoint(User, Post, "/api/contributions", "Submit a contribution"),
+    endpoint(User, Post, "/api/contributions/validate", "Check a contribution without submitting"),
+    endpoint(User, Post, "/api/contributions/{id}/attachments", "Attach a reference file for reviewers"),
+    endpoint(User, Get, "/api/contributions/{id}/messages", "Review thread of an own contribution"),
+    endpoint(User, Post, "/api/contributions/{id}/messages", "Reply to reviewers"),
+    endpoint(User, Post, "/api/questions/{id}/flag", "Flag a question"),
+    endpoint(User, Get, "/api/notifications", "List notifications"),
+    endpoint(User, Post, "/api/notifications/read", "Mark notifications as read"),
+    endpoint(User, Post, "/api/messages/{user_id}", "Send a private message"),
+    endpoint(User, Get, "/api/conversations", "List conversations"),
+    endpoint(User, Get, "/api/conversations/{id}/messages", "Messages of a conversation"),
+    endpoint(User, Post, "/api/terms/accept", "Accept the terms of service"),
+    endpoint(User, Post, "/api/quiz/submit", "Submit a practice paper"),
+    endpoint(User, Get, "/api/quiz/attempts/{id}/paper", "A submitted practice paper as scored"),
+    endpoint(User, Post, "/api/competitions/{id}/register", "Register for a competition"),
+    endpoint(User, Get, "/api/competitions/{id}/paper", "Open a competition paper"),
+    endpoint(User, Post, "/api/competitions/{id}/submit", "Submit a competition paper"),
+    endpoint(User, Get, "/api/docs/user.json", "User API document"),
+    endpoint(Admin, Get, "/api/admin/users", "List users"),
+    endpoint(Admin, Post, "/api/admin/users", "Create a user"),
+    endpoint(Admin, Put, "/api/admin/users/{id}", "Update a user"),
+    endpoint(Admin, Delete, "/api/admin/users/{id}", "Delete a user"),
+    endpoint(Admin, Get, "/api/admin/users/{id}/overview", "User overview"),
+    endpoint(Admin, Put, "/api/admin/users/{id}/verification", "Grant or revoke verification"),
+    endpoint(Admin, Put, "/api/admin/users/{id}/ban", "Ban a user or lift their ban"),
+    endpoint(Admin, Put, "/api/admin/users/{id}/exam-accommodations", "Grant exam accommodations"),
+    endpoint(Admin, Delete, "/api/admin/users/{id}/exam-accommodations", "Withdraw exam accommodations"),
+    endpoint(Admin, Get, "/api/admin/architectures", "List architectures"),
+    endpoint(Admin, Post, "/api/admin/architectures", "Create an architecture"),
+    endpoint(Admin, Get, "/api/admin/architectures/link-report", "Image link report"),
+    endpoint(Admin, Post, "/api/admin/architectures/link-report", "Check image links"),
+    endpoint(Admin, Post, "/api/admin/architectures/locations/backfill", "Backfill structured locations"),
+    endpoint(Admin, Get, "/api/admin/architectures/export", "List catalog bundles"),
+    endpoint(Admin, Post, "/api/admin/architectures/export", "Export the catalog"),
+    endpoint(Admin, Put, "/api/admin/architectures/{id}/pins", "Set pinned posts"),
+    endpoint(Admin, Post, "/api/admin/architectures/{id}/merge", "Merge a duplicate architecture into another"),
+    endpoint(Admin, Put, "/api/admin/architectures/{id}/translations/{locale}", "Add or update a translation"),
+    endpoint(Admin, Delete, "/api/admin/architectures/{id}/translations/{locale}", "Delete a translation"),
+    endpoint(Admin, Put, "/api/admin/architectures/{id}", "Update an architecture"),
+    endpoint(Admin, Delete, "/api/admin/architectures/{id}", "Delete an architecture"),
+    endpoint(Admin, Get, "/api/admin/questions", "List questions"),
+    endpoint(Admin, Post, "/api/admin/questions", "Create a question"),
+    endpoint(Admin, Get, "/api/admin/questions/flagged", "List flagged questions"),
+    endpoint(Admin, Get, "/api/admin/questions/duplicates", "Find duplicate questions"),
+    endpoint(Admin, Post, "/api/admin/questions/duplicates/merge", "Merge duplicate questions"),
+    endpoint(Admin, Post, "/api/admin/questions/bulk-delete", "Delete questions"),
+    endpoint(Admin, Put, "/api/admin/questions/{id}", "Update a question"),
+    endpoint(Admin, Delete, "/api/admin/questions/{id}", "Delete a question"),
+    endpoint(Admin, Put, "/api/admin/questions/{id}/flags", "Resolve a question's flags"),
+    endpoint(Admin, Get, "/api/admin/questions/{id}/translations", "List a question's translations"),
+    endpoint(Admin, Put, "/api/admin/questions/{id}/translations/{locale}", "Add or update a question translation"),
+    endpoint(Admin, Delete, "/api/admin/questions/{id}/translations/{locale}", "Delete a question translation"),
+    endpoint(Admin, Post, "/api/admin/glossary", "Create a glossary term"),
+    endpoint(Admin, Put, "/api/admin/glossary/{id}", "Update a glossary term"),
+    endpoint(Admin, Delete, "/api/admin/glossary/{id}", "Delete a glossary term"),
+    endpoint(Admin, Get, "/api/admin/contributions", "List contributions"),
+    endpoint(Admin, Get, "/api/admin/contributions/metrics", "Contribution review metrics"),
+    endpoint(Admin, Get, "/api/admin/contributions/unaudited", "Auto-approved contributions to check"),
+    endpoint(Admin, Get, "/api/admin/contributions/{id}/attachments", "Reference files of a contribution"),
+    endpoint(Admin, Get, "/api/admin/contributions/{id}/messages", "Review thread of a contribution"),
+    endpoint(Admin, Post, "/api/admin/contributions/{id}/messages", "Ask the contributor a question"),
+    endpoint(Admin, Put, "/api/admin/contributions/{id}/review", "Review a contribution"),
+    endpoint(Admin, Post, "/api/admin/contributions/{id}/confirm", "Confirm an auto-approved contribution"),
+    endpoint(Admin, Post, "/api/admin/contributions/{id}/rollback", "Roll back an auto-approved contribution"),
+    endpoint(Admin, Post, "/api/admin/contributions/{id}/revert", "Revert an approved contribution"),
+    endpoint(Admin, Get, "/api/admin/abuse", "Abuse summary"),
+    endpoint(Admin, Get, "/api/admin/usage/anomalies", "Users with unusual API usage"),
+    endpoint(Admin, Get, "/api/admin/online", "List online users"),
+    endpoint(Admin, Get, "/api/admin/experiments", "Experiment results"),
+    endpoint(Admin, Get, "/api/admin/audit-logs", "List audit log entries"),
+    endpoint(Admin, Get, "/api/admin/audit-logs/{id}/diff", "Audit log entry diff"),
+    endpoint(Admin, Post, "/api/admin/terms", "Publish terms of service"),
+    endpoint(Admin, Get, "/api/admin/exam-events", "List exam events"),
+    endpoint(Admin, Post, "/api/admin/exam-events", "Schedule an exam event"),
+    endpoint(Admin, Delete, "/api/admin/exam-events/{id}", "Cancel or end an exam event"),
+    endpoint(Admin, Post, "/api/admin/competitions", "Create a competition"),
+    endpoint(Admin, Delete, "/api/admin/competitions/{id}", "Delete a competition that has not started"),
+    endpoint(Admin, Get, "/api/admin/competitions/{id}/entries", "Entrants with their proctoring signals"),
+    endpoint(Admin, Post, "/api/admin/notifications/broadcast", "Broadcast a notification"),
+    endpoint(Admin, Get, "/api/admin/notifications/broadcasts", "List broadcasts"),
+    endpoint(Admin, Get, "/api/admin/segments/preview", "Preview a user segment"),
+    endpoint(Admin, Get, "/api/admin/segments/export", "Export a user segment as CSV"),
+    endpoint(Admin, Get, "/api/admin/system/log-level", "Current log level"),
+    endpoint(Admin, Put, "/api/admin/system/log-level", "Change the log level"),
+    endpoint(Admin, Get, "/api/admin/system/integrity", "Check data invariants"),
+    endpoint(Admin, Post, "/api/admin/system/integrity/repair", "Repair data invariants"),
+    endpoint(Admin, Get, "/api/admin/docs/openapi.json", "Admin API document"),
+];
+
+/// The OpenAPI document for `audience`.
+pub fn document(audience: Audience) -> OpenApi {
+    let mut paths = PathsBuilder::new().build();
+    for e in ENDPOINTS.iter().filter(|e| e.audience <= audience) {
+        let mut operation = OperationBuilder::new()
+            .summary(Some(e.summary))
+            .tag(e.path.split('/').nth(2).unwrap_or_default())
+            .response("200", ResponseBuilder::new().description("Success"));
+        for name in path_params(e.path) {
+            operation = operation.parameter(
+                ParameterBuilder::new()
+                    .name(name)
+                    .parameter_in(ParameterIn::Path)
+                    .required(Required::True),
+            );
+        }
+        if e.audience != Public {
+            operation = operation.security(SecurityRequirement::new(BEARER, Vec::<String>::new()));
+        }
+        paths.add_path_operation(e.path, vec![e.method.clone()], operation);
+    }
+
+    let title = match audience {
+        Public => "Ancient Architecture API",
+        User => "Ancient Architecture API (users)",
+        Admin => "Ancient Architecture API (admin)",
+    };
+    let mut components = Components::new();
+    if audience != Public {
+        components.add_security_scheme(
+            BEARER,
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+
+    OpenApiBuilder::new()
+        .info(InfoBuilder::new().title(title).version(env!("CARGO_PKG_VERSION")))
+        .paths(paths)
+        .components(Some(components))
+        .build()
+}
+
+/// Names of the `{...}` segments in a route template.
+fn path_params(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+}
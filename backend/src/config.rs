@@ -1,7 +1,10 @@
 // src/config.rs
 
 use dotenvy::dotenv;
-use std::env;
+use log::LevelFilter;
+use sqlx::{ConnectOptions, postgres::PgConnectOptions};
+use ipnet::IpNet;
+use std::{env, net::IpAddr, path::PathBuf, time::Duration};
 
 /// Application configuration loaded from environment variables.
 #[derive(Debug, Clone)]
@@ -18,11 +21,704 @@ pub struct Config {
     pub admin_username: Option<String>,
     /// Admin password for initial seeding.
     pub admin_password: Option<String>,
+    /// Where generated files (certificate PDFs, exports) are kept.
+    pub storage: StorageConfig,
+    /// SQL statements slower than this are logged as warnings (default: 200ms).
+    pub slow_query_threshold_ms: u64,
+    /// Extra requirements checked at registration.
+    pub registration: RegistrationConfig,
+    /// Where handler panics are reported (`SENTRY_DSN`); None only logs them.
+    pub sentry_dsn: Option<String>,
+    /// Check admin routes against the user's current role instead of the one in
+    /// the token (`ADMIN_ROLE_REVALIDATE`, default: true).
+    pub revalidate_admin_role: bool,
+    /// Comments or likes on a post within this many seconds of an unread
+    /// notification about them are added to that notification instead of sending
+    /// another (`COMMENT_NOTIFICATION_WINDOW_SECS`, default: 600; 0 notifies for
+    /// every one).
+    pub comment_notification_window_secs: u64,
+    /// Where the daily admin digest is posted as JSON (`ADMIN_DIGEST_WEBHOOK_URL`);
+    /// None only delivers it as in-app notifications.
+    pub admin_digest_webhook_url: Option<String>,
+    /// Where business events are logged; see `utils::audit_stream`.
+    pub audit_log: AuditLogConfig,
+    /// How outgoing email is delivered; see `utils::mailer`.
+    pub mail: MailConfig,
+    /// Per-user limits; see `services::quotas`.
+    pub quotas: QuotaConfig,
+    /// Sign-in with third-party accounts; see `services::oauth`.
+    pub oauth: OAuthConfig,
+    /// Bot checks on registration and new posts; see `services::spam`.
+    pub spam: SpamConfig,
+    /// Per-route-group request rate limits; see `utils::rate_limit`.
+    pub rate_limit: RateLimitConfig,
+    /// Which contributions of trusted users skip review; see `services::contribution_review`.
+    pub auto_approval: AutoApprovalConfig,
+    /// TrueType font embedded in generated PDFs (`PDF_FONT_PATH`); it should
+    /// cover CJK. None uses Helvetica, which only covers Latin text.
+    pub pdf_font: Option<PathBuf>,
+    /// Proxies whose forwarding headers name the client (`TRUSTED_PROXIES`,
+    /// comma-separated addresses or CIDR ranges); see `utils::client_ip`.
+    /// Defaults to loopback and private ranges, where the bundled nginx runs;
+    /// empty trusts no one.
+    pub trusted_proxies: Vec<IpNet>,
+}
+
+/// Storage backend selected by `STORAGE_BACKEND` (`local` or `s3`).
+#[derive(Debug, Clone)]
+pub enum StorageConfig {
+    /// Files under a local directory (`STORAGE_LOCAL_ROOT`, default `./storage`).
+    Local { root: PathBuf },
+    /// An S3 bucket. `endpoint` switches to path-style URLs for S3-compatible services.
+    S3 {
+        bucket: String,
+        region: String,
+        endpoint: Option<String>,
+        access_key_id: String,
+        secret_access_key: String,
+    },
+}
+
+impl StorageConfig {
+    fn from_env() -> Self {
+        match env::var("STORAGE_BACKEND").as_deref() {
+            Ok("s3") => StorageConfig::S3 {
+                bucket: env::var("S3_BUCKET").expect("S3_BUCKET must be set"),
+                region: env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+                endpoint: env::var("S3_ENDPOINT").ok().filter(|v| !v.is_empty()),
+                access_key_id: env::var("S3_ACCESS_KEY_ID").expect("S3_ACCESS_KEY_ID must be set"),
+                secret_access_key: env::var("S3_SECRET_ACCESS_KEY")
+                    .expect("S3_SECRET_ACCESS_KEY must be set"),
+            },
+            Ok("local") | Err(_) => StorageConfig::Local {
+                root: env::var("STORAGE_LOCAL_ROOT")
+                    .unwrap_or_else(|_| "./storage".to_string())
+                    .into(),
+            },
+            Ok(other) => panic!("Unknown STORAGE_BACKEND '{}'", other),
+        }
+    }
+}
+
+/// Optional registration requirements; all off by default.
+#[derive(Debug, Clone, Default)]
+pub struct RegistrationConfig {
+    /// `REGISTRATION_MIN_AGE`: registrants must give a birth year and turn at
+    /// least this old in the current year.
+    pub min_age: Option<i32>,
+    /// `REGISTRATION_REGIONS`: comma-separated region codes (e.g. `CN,HK,MO`).
+    /// When non-empty, registrants must pick one of them.
+    pub regions: Vec<String>,
+    /// `REGISTRATION_REQUIRE_EMAIL`: registrants must give an email address.
+    pub require_email: bool,
+}
+
+impl RegistrationConfig {
+    fn from_env() -> Self {
+        let min_age = env::var("REGISTRATION_MIN_AGE")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| v.parse().expect("REGISTRATION_MIN_AGE must be a number"));
+        let regions = env::var("REGISTRATION_REGIONS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty())
+            .collect();
+        let require_email = env::var("REGISTRATION_REQUIRE_EMAIL")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()
+            .expect("REGISTRATION_REQUIRE_EMAIL must be true or false");
+
+        Self { min_age, regions, require_email }
+    }
+}
+
+/// Per-user quotas; each is unlimited when unset or empty.
+#[derive(Debug, Clone, Default)]
+pub struct QuotaConfig {
+    /// `QUOTA_POSTS_PER_DAY`: posts a user may write in 24 hours.
+    pub posts_per_day: Option<u32>,
+    /// `QUOTA_IMAGES_PER_USER`: images embedded across a user's posts.
+    pub images_per_user: Option<u32>,
+    /// `QUOTA_FAVORITES_PER_USER`: posts a user may keep favorited.
+    pub favorites_per_user: Option<u32>,
+}
+
+impl QuotaConfig {
+    fn from_env() -> Self {
+        let limit = |name: &str| {
+            env::var(name)
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map(|v| v.parse().unwrap_or_else(|_| panic!("{} must be a number", name)))
+        };
+
+        Self {
+            posts_per_day: limit("QUOTA_POSTS_PER_DAY"),
+            images_per_user: limit("QUOTA_IMAGES_PER_USER"),
+            favorites_per_user: limit("QUOTA_FAVORITES_PER_USER"),
+        }
+    }
+}
+
+/// Bot checks on registration and post forms. Both are off by default.
+#[derive(Debug, Clone, Default)]
+pub struct SpamConfig {
+    /// `SPAM_HONEYPOT_FIELD`: a form field hidden from people, which must be
+    /// absent or empty.
+    pub honeypot_field: Option<String>,
+    /// `SPAM_MIN_FORM_SECS`: forms need a `form_token` at least this many
+    /// seconds old; 0 turns the check off.
+    pub min_form_secs: u64,
+    /// `SPAM_ACTION`: what happens to a suspected bot submission.
+    pub action: SpamAction,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SpamAction {
+    /// `reject` (default): refuse it with 400.
+    #[default]
+    Reject,
+    /// `flag`: accept it and record a `spam_suspected` security event.
+    Flag,
+}
+
+impl SpamConfig {
+    fn from_env() -> Self {
+        let action = match env::var("SPAM_ACTION").unwrap_or_default().as_str() {
+            "" | "reject" => SpamAction::Reject,
+            "flag" => SpamAction::Flag,
+            other => panic!("SPAM_ACTION must be 'reject' or 'flag', got '{}'", other),
+        };
+
+        Self {
+            honeypot_field: env::var("SPAM_HONEYPOT_FIELD").ok().filter(|v| !v.is_empty()),
+            min_form_secs: env::var("SPAM_MIN_FORM_SECS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .expect("SPAM_MIN_FORM_SECS must be a number"),
+            action,
+        }
+    }
+}
+
+/// Rules for publishing contributions of trusted users without review.
+/// Off by default.
+#[derive(Debug, Clone)]
+pub struct AutoApprovalConfig {
+    /// `AUTO_APPROVE_TYPES`: comma-separated contribution types (`question`,
+    /// `architecture`) that trusted users publish directly. Empty turns
+    /// auto-approval off.
+    pub types: Vec<String>,
+    /// `TRUSTED_MIN_APPROVED`: reviewed approvals a user needs to be trusted
+    /// (default: 5).
+    pub min_approved: u32,
+    /// `TRUSTED_MAX_REJECTED_PERCENT`: the most of a user's reviewed
+    /// contributions that may have been rejected (default: 10).
+    pub max_rejected_percent: u32,
+}
+
+impl Default for AutoApprovalConfig {
+    fn default() -> Self {
+        Self {
+            types: Vec::new(),
+            min_approved: 5,
+            max_rejected_percent: 10,
+        }
+    }
+}
+
+impl AutoApprovalConfig {
+    fn from_env() -> Self {
+        let default = Self::default();
+        let types: Vec<String> = env::var("AUTO_APPROVE_TYPES")
+            .unwrap_or_default()
+            .split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect();
+        if let Some(other) = types.iter().find(|t| *t != "architecture" && *t != "question") {
+            panic!("AUTO_APPROVE_TYPES: unknown contribution type '{}'", other);
+        }
+        let number = |name: &str, default: u32| {
+            env::var(name)
+                .ok()
+                .filter(|v| !v.is_empty())
+                .map_or(default, |v| {
+                    v.parse().unwrap_or_else(|_| panic!("{} must be a number", name))
+                })
+        };
+
+        Self {
+            types,
+            min_approved: number("TRUSTED_MIN_APPROVED", default.min_approved),
+            max_rejected_percent: number(
+                "TRUSTED_MAX_REJECTED_PERCENT",
+                default.max_rejected_percent,
+            ),
+        }
+    }
+}
+
+/// Request rate limits per route group, enforced by `utils::rate_limit`.
+/// Each limit is `<requests per minute>,<burst>`, e.g. `20,5`; empty or `off`
+/// turns that group's limit off. All are off by default.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitConfig {
+    /// `RATE_LIMIT_AUTH`: every `/api/auth/*` route, always keyed by client IP.
+    pub auth: Option<RateLimit>,
+    /// `RATE_LIMIT_READ`: `GET` requests everywhere else.
+    pub read: Option<RateLimit>,
+    /// `RATE_LIMIT_WRITE`: other methods everywhere else.
+    pub write: Option<RateLimit>,
+    /// `RATE_LIMIT_KEY`: whose budget read and write requests count against.
+    pub key: RateLimitKey,
+}
+
+/// A budget of `burst` requests, refilled at `per_minute` requests a minute.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub per_minute: u32,
+    pub burst: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum RateLimitKey {
+    /// `ip` (default): the client address, shared by everyone behind it.
+    #[default]
+    Ip,
+    /// `user`: the signed-in user, falling back to the address for anonymous requests.
+    User,
+}
+
+impl RateLimitConfig {
+    fn from_env() -> Self {
+        let limit = |name: &str| {
+            let value = env::var(name).unwrap_or_default();
+            if value.is_empty() || value == "off" {
+                return None;
+            }
+            let parsed = value.split_once(',').and_then(|(per_minute, burst)| {
+                Some(RateLimit {
+                    per_minute: per_minute.trim().parse().ok().filter(|n| *n > 0)?,
+                    burst: burst.trim().parse().ok().filter(|n| *n > 0)?,
+                })
+            });
+            Some(parsed.unwrap_or_else(|| {
+                panic!("{} must be '<per minute>,<burst>' or 'off', got '{}'", name, value)
+            }))
+        };
+        let key = match env::var("RATE_LIMIT_KEY").unwrap_or_default().as_str() {
+            "" | "ip" => RateLimitKey::Ip,
+            "user" => RateLimitKey::User,
+            other => panic!("RATE_LIMIT_KEY must be 'ip' or 'user', got '{}'", other),
+        };
+
+        Self {
+            auth: limit("RATE_LIMIT_AUTH"),
+            read: limit("RATE_LIMIT_READ"),
+            write: limit("RATE_LIMIT_WRITE"),
+            key,
+        }
+    }
+}
+
+/// OAuth login providers. Each is enabled by setting `OAUTH_<PROVIDER>_CLIENT_ID`
+/// (`GITHUB`, `GOOGLE` or `WECHAT`) along with `_CLIENT_SECRET` and `_REDIRECT_URI`.
+#[derive(Debug, Clone, Default)]
+pub struct OAuthConfig {
+    pub github: Option<OAuthProviderConfig>,
+    pub google: Option<OAuthProviderConfig>,
+    pub wechat: Option<OAuthProviderConfig>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OAuthProviderConfig {
+    /// Client ID (WeChat: AppID).
+    pub client_id: String,
+    /// Client secret (WeChat: AppSecret).
+    pub client_secret: String,
+    /// Page of the web client the provider sends users back to with `code`
+    /// and `state`; it must be registered with the provider.
+    pub redirect_uri: String,
+    /// Overrides of the provider's authorize, token and user info endpoints,
+    /// for test servers. None uses the real ones.
+    pub authorize_url: Option<String>,
+    pub token_url: Option<String>,
+    pub userinfo_url: Option<String>,
+}
+
+impl OAuthConfig {
+    fn from_env() -> Self {
+        let provider = |name: &str| {
+            let var = |key: &str| {
+                env::var(format!("OAUTH_{}_{}", name, key))
+                    .ok()
+                    .filter(|v| !v.is_empty())
+            };
+            let client_id = var("CLIENT_ID")?;
+            let required = |key: &str| {
+                var(key).unwrap_or_else(|| panic!("OAUTH_{}_{} must be set", name, key))
+            };
+            Some(OAuthProviderConfig {
+                client_secret: required("CLIENT_SECRET"),
+                redirect_uri: required("REDIRECT_URI"),
+                client_id,
+                authorize_url: None,
+                token_url: None,
+                userinfo_url: None,
+            })
+        };
+
+        Self {
+            github: provider("GITHUB"),
+            google: provider("GOOGLE"),
+            wechat: provider("WECHAT"),
+        }
+    }
+}
+
+/// Outgoing email.
+#[derive(Debug, Clone)]
+pub struct MailConfig {
+    pub transport: MailTransport,
+    /// `MAIL_FROM`: sender address (default: `no-reply@localhost`).
+    pub from: String,
+    /// `EMAIL_VERIFICATION_URL`: page of the web client that confirms an address;
+    /// `?token=...` is appended. Without it, emails only carry the token.
+    pub verification_url: Option<String>,
+    /// `PASSWORD_RESET_URL`: page of the web client that sets a new password,
+    /// used the same way.
+    pub password_reset_url: Option<String>,
+}
+
+/// Mail transport selected by `MAIL_TRANSPORT` (`log` or `http`).
+#[derive(Debug, Clone)]
+pub enum MailTransport {
+    /// Messages are written to the application log, not sent.
+    Log,
+    /// Messages are posted to a mail relay's HTTP API (`MAIL_HTTP_URL`, with
+    /// `MAIL_HTTP_API_KEY` as a bearer token if set).
+    Http { url: String, api_key: Option<String> },
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            transport: MailTransport::Log,
+            from: "no-reply@localhost".to_string(),
+            verification_url: None,
+            password_reset_url: None,
+        }
+    }
+}
+
+impl MailConfig {
+    fn from_env() -> Self {
+        let transport = match env::var("MAIL_TRANSPORT").as_deref() {
+            Ok("http") => MailTransport::Http {
+                url: env::var("MAIL_HTTP_URL").expect("MAIL_HTTP_URL must be set"),
+                api_key: env::var("MAIL_HTTP_API_KEY").ok().filter(|v| !v.is_empty()),
+            },
+            Ok("log") | Err(_) => MailTransport::Log,
+            Ok(other) => panic!("Unknown MAIL_TRANSPORT '{}'", other),
+        };
+        let defaults = Self::default();
+
+        Self {
+            transport,
+            from: env::var("MAIL_FROM").ok().filter(|v| !v.is_empty()).unwrap_or(defaults.from),
+            verification_url: env::var("EMAIL_VERIFICATION_URL").ok().filter(|v| !v.is_empty()),
+            password_reset_url: env::var("PASSWORD_RESET_URL").ok().filter(|v| !v.is_empty()),
+        }
+    }
+}
+
+/// The audit log: daily files named `audit.<date>.log`.
+#[derive(Debug, Clone)]
+pub struct AuditLogConfig {
+    /// `AUDIT_LOG_DIR` (default: `./logs`).
+    pub dir: PathBuf,
+    /// `AUDIT_LOG_RETENTION_DAYS`: older files are deleted (default: 365).
+    pub retention_days: usize,
+}
+
+impl Default for AuditLogConfig {
+    fn default() -> Self {
+        Self {
+            dir: "./logs".into(),
+            retention_days: 365,
+        }
+    }
+}
+
+impl AuditLogConfig {
+    fn from_env() -> Self {
+        let default = Self::default();
+        let dir = env::var("AUDIT_LOG_DIR")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map_or(default.dir, PathBuf::from);
+        let retention_days = env::var("AUDIT_LOG_RETENTION_DAYS")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map_or(default.retention_days, |v| {
+                v.parse().expect("AUDIT_LOG_RETENTION_DAYS must be a number")
+            });
+
+        Self { dir, retention_days }
+    }
 }
 
 // Business Logic Constants
 pub const EXAM_QUESTION_COUNT: i64 = 20;
+/// Time allowed for the qualification exam before accommodations.
+pub const EXAM_DURATION_SECS: u64 = 900;
+/// How long a served practice paper can be submitted; its snapshot is purged
+/// after this if it never was.
+pub const PRACTICE_PAPER_TTL_SECS: u64 = 86400;
 pub const PASSING_SCORE_PERCENTAGE: f64 = 60.0;
+/// Candidate window size per requested question when sampling a paper.
+pub const QUESTION_CANDIDATE_FACTOR: i64 = 5;
+/// How often the sampling keys are re-rolled and exposure counts decayed.
+pub const QUESTION_SAMPLING_INTERVAL_SECS: u64 = 3600;
+/// How often missing pinyin search keys are filled in.
+pub const SEARCH_INDEX_INTERVAL_SECS: u64 = 600;
+/// Word similarity at which a pinyin query matches a name despite typos.
+pub const PINYIN_WORD_SIMILARITY: f32 = 0.5;
+/// How often the job queue is polled for due work.
+pub const JOB_QUEUE_POLL_INTERVAL_SECS: u64 = 5;
+/// Attempts before a queued job is marked failed.
+pub const JOB_MAX_ATTEMPTS: i32 = 5;
+/// How long a claimed job may stay running before it is assumed lost and
+/// claimed again. Longer than any job should take.
+pub const JOB_LEASE_SECS: u64 = 900;
+/// Open flags from distinct users that pull a question from papers until reviewed.
+pub const QUESTION_FLAG_SUSPEND_THRESHOLD: i64 = 3;
+/// Default trigram similarity at which two questions are reported as duplicates.
+pub const QUESTION_DUPLICATE_THRESHOLD: f32 = 0.8;
+/// Minimum quiz score that earns a certificate.
+pub const QUIZ_CERTIFICATE_SCORE: i32 = 90;
+/// Lifetime of the signed download link for a certificate PDF.
+pub const CERTIFICATE_PDF_URL_SECS: u64 = 300;
+/// Lifetime of the signed download link for an architecture dossier PDF.
+pub const DOSSIER_URL_SECS: u64 = 300;
+/// A dossier render pending this long is assumed lost and queued again.
+pub const DOSSIER_REQUEUE_SECS: i64 = 900;
+/// Images larger than this are left out of dossiers.
+pub const DOSSIER_IMAGE_MAX_BYTES: usize = 5 * 1024 * 1024;
+/// At most this many images (cover first) are printed in a dossier.
+pub const DOSSIER_MAX_IMAGES: usize = 8;
+/// At most this many community posts are cited in a dossier.
+pub const DOSSIER_MAX_CITED_POSTS: i64 = 10;
+/// How long `GET /api/stats/public` serves a cached snapshot.
+pub const PUBLIC_STATS_CACHE_SECS: u64 = 3600;
+/// How long `GET /api/profile/activity-heatmap` serves a user's cached heatmap.
+pub const ACTIVITY_HEATMAP_CACHE_SECS: u64 = 600;
+/// Days covered by the activity heatmap, today included.
+pub const ACTIVITY_HEATMAP_DAYS: i32 = 365;
+/// Hosts link previews are never fetched from, on top of those `utils::http_client`
+/// always refuses. Suffix match, so subdomains are covered.
+pub const UNFURL_DENIED_DOMAINS: &[&str] = &[];
+/// When non-empty, link previews are only fetched from these hosts (suffix match).
+pub const UNFURL_ALLOWED_DOMAINS: &[&str] = &[];
+/// At most this many links per post are unfurled.
+pub const UNFURL_MAX_LINKS: usize = 5;
+/// Cached previews older than this are fetched again when linked from a new post.
+pub const UNFURL_REFRESH_DAYS: i32 = 7;
+/// Image URLs probed at once by the `check_image_links` job.
+pub const IMAGE_LINK_CHECK_CONCURRENCY: usize = 8;
+/// Images larger than this get no placeholder.
+pub const IMAGE_PLACEHOLDER_MAX_BYTES: usize = 10 * 1024 * 1024;
+/// At most this many images per post get a placeholder.
+pub const IMAGE_PLACEHOLDER_MAX_IMAGES: usize = 10;
+/// How long an emailed verification link stays valid.
+pub const EMAIL_VERIFICATION_TTL_HOURS: i32 = 48;
+/// How long a user has to finish an OAuth sign-in at the provider.
+pub const OAUTH_STATE_TTL_MINUTES: i32 = 10;
+/// How long an emailed password reset link stays valid.
+pub const PASSWORD_RESET_TTL_MINUTES: i32 = 60;
+/// Length of a two-factor (TOTP) time step.
+pub const TOTP_STEP_SECS: i64 = 30;
+/// Digits in a two-factor code.
+pub const TOTP_DIGITS: u32 = 6;
+/// Time steps of clock drift accepted either way when checking a code.
+pub const TOTP_SKEW_STEPS: i64 = 1;
+/// Issuer shown next to the account in authenticator apps.
+pub const TOTP_ISSUER: &str = "Ancient Arch";
+/// Further reset requests for the same account within this many seconds send no email.
+pub const PASSWORD_RESET_MIN_INTERVAL_SECS: u64 = 60;
+/// How far `until` in sync responses trails the server clock, so changes
+/// committed late by slow transactions still reach the next sync.
+pub const SYNC_OVERLAP_SECS: i64 = 5;
+/// Largest reference file a contributor can attach.
+pub const CONTRIBUTION_ATTACHMENT_MAX_BYTES: usize = 10 * 1024 * 1024;
+/// Reference files per contribution at most.
+pub const CONTRIBUTION_MAX_ATTACHMENTS: i64 = 5;
+/// Lifetime of the signed links reviewers open attachments with.
+pub const CONTRIBUTION_ATTACHMENT_URL_SECS: u64 = 300;
+/// Attachments are deleted this many days after their contribution is reviewed.
+pub const CONTRIBUTION_ATTACHMENT_RETENTION_DAYS: i32 = 30;
+/// Lifetime of the signed download links for catalog bundles.
+pub const CATALOG_EXPORT_URL_SECS: u64 = 3600;
+/// Lifetime of the signed download link for a personal data export.
+pub const DATA_EXPORT_URL_SECS: u64 = 300;
+/// Personal data exports are deleted this many days after they are written.
+pub const DATA_EXPORT_RETENTION_DAYS: i32 = 7;
+/// A user can request a new data export this often.
+pub const DATA_EXPORT_MIN_INTERVAL_HOURS: i64 = 24;
+/// A user counts as online if they made an authenticated request this recently.
+pub const ONLINE_WINDOW_SECS: u64 = 300;
+/// How long `admin_middleware` trusts a role it read from the database.
+/// Role changes made through the admin API apply immediately regardless.
+pub const ADMIN_ROLE_CACHE_SECS: u64 = 30;
+/// Length of plain-text excerpts: stored with each post, and used as the
+/// summaries in lite responses.
+pub const EXCERPT_CHARS: usize = 140;
+/// Reading speeds behind a post's estimated reading time.
+pub const READING_CJK_CHARS_PER_MINUTE: usize = 300;
+pub const READING_WORDS_PER_MINUTE: usize = 200;
+/// Hours between admin digests.
+pub const ADMIN_DIGEST_INTERVAL_HOURS: i32 = 24;
+/// How often the `admin_digest` job checks whether a digest is due.
+pub const ADMIN_DIGEST_CHECK_INTERVAL_SECS: u64 = 3600;
+/// How often revocations and sessions of expired tokens are purged.
+pub const REVOKED_TOKEN_PURGE_INTERVAL_SECS: u64 = 3600;
+/// A session's last use is written at most this often.
+pub const SESSION_TOUCH_INTERVAL_SECS: i64 = 60;
+/// Longest user agent kept for a session or security event, in characters.
+pub const SESSION_USER_AGENT_MAX_CHARS: usize = 500;
+/// Users notified per `broadcast_notifications` job.
+pub const BROADCAST_BATCH_SIZE: i64 = 500;
+/// The `active` broadcast segment: users who signed in within this many days.
+pub const BROADCAST_ACTIVE_DAYS: i32 = 30;
+/// Seconds a user must wait between two comments on the same post.
+pub const COMMENT_MIN_INTERVAL_SECS: i64 = 15;
+/// Comments a user may post on the same post per hour.
+pub const COMMENT_HOURLY_LIMIT_PER_POST: i64 = 30;
+/// How long a form token from `GET /api/auth/form-token` is accepted.
+pub const FORM_TOKEN_TTL_HOURS: i64 = 24;
+/// Users shown in a segment preview.
+pub const SEGMENT_SAMPLE_SIZE: i64 = 10;
+/// Offending IDs listed per integrity check.
+pub const INTEGRITY_SAMPLE_SIZE: usize = 20;
+/// How often the `retention` job prunes old records.
+pub const RETENTION_INTERVAL_SECS: u64 = 24 * 3600;
+/// How long users who deleted their own account have to change their mind.
+pub const ACCOUNT_DELETION_GRACE_DAYS: i32 = 14;
+/// How often the `account_deletion` job deletes accounts whose grace period is over.
+pub const ACCOUNT_DELETION_INTERVAL_SECS: u64 = 3600;
+/// Every this many authenticated requests of a user, one is recorded in
+/// `api_usage_daily` (as this many).
+pub const USAGE_SAMPLE_EVERY: u32 = 10;
+/// Days of usage shown by `GET /api/profile/usage`.
+pub const USAGE_HISTORY_DAYS: i32 = 30;
+/// Daily usage counts are kept this long.
+pub const USAGE_RETENTION_DAYS: i32 = 90;
+/// Days before the one looked at that a user's usual usage is averaged over.
+pub const USAGE_ANOMALY_BASELINE_DAYS: i32 = 14;
+/// A day counts as anomalous when the user made this many times their usual requests.
+pub const USAGE_ANOMALY_FACTOR: f64 = 5.0;
+/// Days with fewer requests than this never count as anomalous.
+pub const USAGE_ANOMALY_MIN_REQUESTS: i64 = 1000;
+/// Deletions are listed by the sync API for this long; older cursors must resync.
+pub const TOMBSTONE_RETENTION_DAYS: i32 = 90;
+/// How often posts without an excerpt get one.
+pub const POST_EXCERPT_INTERVAL_SECS: u64 = 600;
+/// How often architectures and posts without a slug get one.
+pub const SLUG_BACKFILL_INTERVAL_SECS: u64 = 600;
+/// Tags an author may put on one post.
+pub const POST_TAG_LIMIT: usize = 5;
+/// How often the `trending_tags` job recomputes the trending list.
+pub const TRENDING_TAGS_INTERVAL_SECS: u64 = 600;
+/// Only posts this recent count towards trending tags.
+pub const TRENDING_TAGS_WINDOW_DAYS: i32 = 7;
+/// Age at which a post counts half as much towards its tags' trend.
+pub const TRENDING_TAGS_HALF_LIFE_HOURS: f64 = 48.0;
+/// Tags kept in the trending list.
+pub const TRENDING_TAGS_LIMIT: i64 = 20;
+/// Related architectures shown on a tag's topic page.
+pub const TOPIC_ARCHITECTURE_LIMIT: i64 = 10;
+/// Largest JSON request body accepted by `json_guard_middleware`.
+pub const MAX_JSON_BODY_BYTES: usize = 256 * 1024;
+/// Deepest nesting of arrays and objects allowed in a JSON request body.
+pub const MAX_JSON_DEPTH: usize = 32;
+/// Longest array allowed anywhere in a JSON request body.
+pub const MAX_JSON_ARRAY_LEN: usize = 1000;
+/// Inclusive length bounds for user-entered text, counted in characters as
+/// users see them (see `utils::text_length`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthLimit {
+    pub min: usize,
+    pub max: usize,
+}
+
+pub const USERNAME_LENGTH: LengthLimit = LengthLimit { min: 3, max: 50 };
+pub const PROFILE_BIO_LENGTH: LengthLimit = LengthLimit { min: 0, max: 500 };
+pub const PROFILE_LOCATION_LENGTH: LengthLimit = LengthLimit { min: 0, max: 100 };
+/// Longest website or avatar URL on a profile.
+pub const PROFILE_URL_MAX_LEN: usize = 500;
+pub const POST_TITLE_LENGTH: LengthLimit = LengthLimit { min: 1, max: 100 };
+pub const POST_CONTENT_LENGTH: LengthLimit = LengthLimit { min: 1, max: 10000 };
+pub const TAG_NAME_LENGTH: LengthLimit = LengthLimit { min: 1, max: 30 };
+pub const COMMENT_LENGTH: LengthLimit = LengthLimit { min: 1, max: 1000 };
+pub const SERIES_TITLE_LENGTH: LengthLimit = LengthLimit { min: 1, max: 100 };
+pub const SERIES_DESCRIPTION_LENGTH: LengthLimit = LengthLimit { min: 0, max: 1000 };
+pub const ARCH_NAME_LENGTH: LengthLimit = LengthLimit { min: 1, max: 100 };
+/// Category, dynasty, province, city and district.
+pub const ARCH_LABEL_LENGTH: LengthLimit = LengthLimit { min: 1, max: 50 };
+pub const ARCH_LOCATION_LENGTH: LengthLimit = LengthLimit { min: 1, max: 200 };
+pub const ARCH_DESCRIPTION_LENGTH: LengthLimit = LengthLimit { min: 1, max: 20000 };
+/// Upper bounds for architecture dimensions, to catch unit mix-ups.
+pub const ARCH_MAX_HEIGHT_M: f64 = 1000.0;
+pub const ARCH_MAX_FOOTPRINT_M2: f64 = 2_000_000.0;
+/// Long corridors run to hundreds of bays.
+pub const ARCH_MAX_BAYS: i32 = 500;
+/// Architectures compared side by side at most.
+pub const ARCH_COMPARE_MAX: usize = 5;
+pub const QUESTION_CONTENT_LENGTH: LengthLimit = LengthLimit { min: 1, max: 1000 };
+pub const QUESTION_OPTION_LENGTH: LengthLimit = LengthLimit { min: 0, max: 500 };
+pub const QUESTION_ANSWER_LENGTH: LengthLimit = LengthLimit { min: 1, max: 500 };
+pub const QUESTION_ANALYSIS_LENGTH: LengthLimit = LengthLimit { min: 0, max: 2000 };
+/// Free-text comment on a question flag.
+pub const FLAG_COMMENT_LENGTH: LengthLimit = LengthLimit { min: 0, max: 500 };
+pub const GLOSSARY_TERM_LENGTH: LengthLimit = LengthLimit { min: 1, max: 100 };
+pub const GLOSSARY_DEFINITION_LENGTH: LengthLimit = LengthLimit { min: 1, max: 5000 };
+pub const TERMS_CONTENT_LENGTH: LengthLimit = LengthLimit { min: 1, max: 50000 };
+/// Reasons admins give for a change, shown to the affected user.
+pub const ADMIN_REASON_LENGTH: LengthLimit = LengthLimit { min: 1, max: 500 };
+/// A message between reviewers and the contributor about a contribution.
+pub const CONTRIBUTION_MESSAGE_LENGTH: LengthLimit = LengthLimit { min: 1, max: 2000 };
+/// A private message between two users.
+pub const DIRECT_MESSAGE_LENGTH: LengthLimit = LengthLimit { min: 1, max: 2000 };
+pub const EXAM_EVENT_TITLE_LENGTH: LengthLimit = LengthLimit { min: 1, max: 100 };
+/// Longest exam event, so a mistyped end date cannot lock the question bank
+/// for months.
+pub const EXAM_EVENT_MAX_DAYS: i64 = 7;
+pub const COMPETITION_TITLE_LENGTH: LengthLimit = LengthLimit { min: 1, max: 100 };
+pub const COMPETITION_DESCRIPTION_LENGTH: LengthLimit = LengthLimit { min: 1, max: 2000 };
+/// Most questions on a competition paper.
+pub const COMPETITION_MAX_QUESTIONS: usize = 100;
+/// Longest URL slug of an architecture or post, before any collision suffix.
+pub const SLUG_MAX_LEN: usize = 80;
+/// Longest glossary slug (the column is VARCHAR(100)).
+pub const GLOSSARY_SLUG_MAX_LEN: usize = 100;
+/// Request budget for most route groups.
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 10;
+/// Tighter budget for search-heavy public listings, so slow searches give up
+/// before they tie up the pool.
+pub const SEARCH_REQUEST_TIMEOUT_SECS: u64 = 5;
+/// Admin reports and bulk operations get more time.
+pub const ADMIN_REQUEST_TIMEOUT_SECS: u64 = 30;
+/// Server-side `statement_timeout` for pooled connections. Dropping a timed-out
+/// request only stops waiting for its query; this stops Postgres running it.
+/// Must be at least the largest request budget. Migrations run without it.
+pub const STATEMENT_TIMEOUT_SECS: u64 = 30;
+/// `TRUSTED_PROXIES` when unset: loopback and private ranges, which covers
+/// the nginx container on the compose network.
+pub const DEFAULT_TRUSTED_PROXIES: &str =
+    "127.0.0.0/8,::1/128,10.0.0.0/8,172.16.0.0/12,192.168.0.0/16,fc00::/7";
 
 impl Config {
     /// Loads configuration from `.env` file and environment variables.
@@ -43,6 +739,60 @@ impl Config {
 
         let admin_username = env::var("ADMIN_USERNAME").ok();
         let admin_password = env::var("ADMIN_PASSWORD").ok();
+        let storage = StorageConfig::from_env();
+
+        let slow_query_threshold_ms = env::var("SLOW_QUERY_THRESHOLD_MS")
+            .unwrap_or_else(|_| "200".to_string())
+            .parse()
+            .expect("SLOW_QUERY_THRESHOLD_MS must be a number");
+
+        let registration = RegistrationConfig::from_env();
+
+        let sentry_dsn = env::var("SENTRY_DSN").ok().filter(|v| !v.is_empty());
+
+        let revalidate_admin_role = env::var("ADMIN_ROLE_REVALIDATE")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()
+            .expect("ADMIN_ROLE_REVALIDATE must be true or false");
+
+        let comment_notification_window_secs = env::var("COMMENT_NOTIFICATION_WINDOW_SECS")
+            .unwrap_or_else(|_| "600".to_string())
+            .parse()
+            .expect("COMMENT_NOTIFICATION_WINDOW_SECS must be a number");
+
+        let admin_digest_webhook_url =
+            env::var("ADMIN_DIGEST_WEBHOOK_URL").ok().filter(|v| !v.is_empty());
+
+        let audit_log = AuditLogConfig::from_env();
+
+        let mail = MailConfig::from_env();
+
+        let quotas = QuotaConfig::from_env();
+
+        let oauth = OAuthConfig::from_env();
+
+        let spam = SpamConfig::from_env();
+
+        let rate_limit = RateLimitConfig::from_env();
+
+        let auto_approval = AutoApprovalConfig::from_env();
+
+        let pdf_font = env::var("PDF_FONT_PATH")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from);
+
+        let trusted_proxies = env::var("TRUSTED_PROXIES")
+            .unwrap_or_else(|_| DEFAULT_TRUSTED_PROXIES.to_string())
+            .split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                v.parse::<IpNet>()
+                    .or_else(|_| v.parse::<IpAddr>().map(IpNet::from))
+                    .unwrap_or_else(|_| panic!("TRUSTED_PROXIES has an invalid address '{}'", v))
+            })
+            .collect();
 
         Self {
             database_url,
@@ -51,6 +801,39 @@ impl Config {
             jwt_expiration,
             admin_username,
             admin_password,
+            storage,
+            slow_query_threshold_ms,
+            registration,
+            sentry_dsn,
+            revalidate_admin_role,
+            comment_notification_window_secs,
+            admin_digest_webhook_url,
+            audit_log,
+            mail,
+            quotas,
+            oauth,
+            spam,
+            rate_limit,
+            auto_approval,
+            pdf_font,
+            trusted_proxies,
         }
     }
+
+    /// Connection options for the application pool.
+    ///
+    /// Every statement is logged at DEBUG with its duration, inside the span of the
+    /// request that ran it; statements over the slow-query threshold are logged at
+    /// WARN. Statements outliving `STATEMENT_TIMEOUT_SECS` are cancelled server-side.
+    pub fn pg_connect_options(&self) -> PgConnectOptions {
+        self.database_url
+            .parse::<PgConnectOptions>()
+            .expect("DATABASE_URL must be a valid Postgres URL")
+            .options([("statement_timeout", format!("{}s", STATEMENT_TIMEOUT_SECS))])
+            .log_statements(LevelFilter::Debug)
+            .log_slow_statements(
+                LevelFilter::Warn,
+                Duration::from_millis(self.slow_query_threshold_ms),
+            )
+    }
 }
@@ -2,7 +2,7 @@
 
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
 use serde_json::json;
@@ -18,6 +18,9 @@ pub enum AppError {
     // 400 Bad Request
     BadRequest(String),
 
+    // 400 Bad Request with per-field details
+    Validation(validator::ValidationErrors),
+
     // 401 Unauthorized
     AuthError(String),
 
@@ -26,6 +29,21 @@ pub enum AppError {
 
     // 409 Conflict (e.g., duplicate username)
     Conflict(String),
+
+    // 413 Payload Too Large
+    PayloadTooLarge(String),
+
+    // 429 Too Many Requests (a per-user quota is used up)
+    QuotaExceeded(String),
+
+    // 429 Too Many Requests (acting too fast; may retry after a delay)
+    RateLimited {
+        message: String,
+        retry_after_secs: u64,
+    },
+
+    // 504 Gateway Timeout (request budget or statement_timeout exceeded)
+    Timeout(String),
 }
 
 impl fmt::Display for AppError {
@@ -49,9 +67,35 @@ impl IntoResponse for AppError {
                 )
             }
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::Validation(errors) => {
+                let body = Json(json!({
+                    "error": "Validation failed",
+                    "fields": errors,
+                }));
+                return (StatusCode::BAD_REQUEST, body).into_response();
+            }
             AppError::AuthError(msg) => (StatusCode::UNAUTHORIZED, msg),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg),
+            AppError::QuotaExceeded(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
+            AppError::RateLimited {
+                message,
+                retry_after_secs,
+            } => {
+                let body = Json(json!({
+                    "error": message,
+                    "code": "rate_limited",
+                    "retry_after": retry_after_secs,
+                }));
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(header::RETRY_AFTER, retry_after_secs.to_string())],
+                    body,
+                )
+                    .into_response();
+            }
+            AppError::Timeout(msg) => (StatusCode::GATEWAY_TIMEOUT, msg),
         };
         let body = Json(json!({
             "error": error_message,
@@ -63,8 +107,40 @@ impl IntoResponse for AppError {
 
 /// Converts `sqlx::Error` into `AppError::InternalServerError`.
 /// Allows using `?` operator on database queries.
+///
+/// Queries cancelled by `statement_timeout` and waits for a free connection
+/// that time out become `AppError::Timeout` instead.
 impl From<sqlx::Error> for AppError {
     fn from(err: sqlx::Error) -> Self {
+        let timed_out = match &err {
+            sqlx::Error::PoolTimedOut => true,
+            // 57014: query_canceled
+            sqlx::Error::Database(db) => db.code().as_deref() == Some("57014"),
+            _ => false,
+        };
+        if timed_out {
+            tracing::warn!("Database timeout: {}", err);
+            AppError::Timeout("The request took too long. Please try again.".to_string())
+        } else {
+            AppError::InternalServerError(err.to_string())
+        }
+    }
+}
+
+impl From<crate::storage::StorageError> for AppError {
+    fn from(err: crate::storage::StorageError) -> Self {
+        AppError::InternalServerError(err.to_string())
+    }
+}
+
+impl From<crate::utils::mailer::MailError> for AppError {
+    fn from(err: crate::utils::mailer::MailError) -> Self {
+        AppError::InternalServerError(err.to_string())
+    }
+}
+
+impl From<crate::utils::pdf::PdfError> for AppError {
+    fn from(err: crate::utils::pdf::PdfError) -> Self {
         AppError::InternalServerError(err.to_string())
     }
 }
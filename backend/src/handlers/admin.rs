@@ -1,35 +1,84 @@
 // src/handlers/admin.rs
 
+use std::{sync::Arc, time::Duration};
+
 use axum::{
     Json,
-    extract::{Extension, Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Postgres, QueryBuilder};
 use validator::Validate;
 
 use crate::{
+    config::{
+        ARCH_MAX_BAYS, CATALOG_EXPORT_URL_SECS, COMPETITION_MAX_QUESTIONS,
+        CONTRIBUTION_ATTACHMENT_URL_SECS, Config,
+        EXAM_EVENT_MAX_DAYS, GLOSSARY_SLUG_MAX_LEN, ONLINE_WINDOW_SECS, QUESTION_DUPLICATE_THRESHOLD, SEGMENT_SAMPLE_SIZE,
+        USAGE_ANOMALY_BASELINE_DAYS,
+    },
     error::AppError,
     models::{
-        architecture::CreateArchRequest, contribution::Contribution,
-        question::CreateQuestionRequest, user::User,
+        api_usage::{UsageAnomalies, UsageAnomalyParams},
+        audit_log::{AuditDiff, AuditLogEntry, FieldChange},
+        architecture::{
+            AdminArchitecture, Architecture, CreateArchRequest, MergeArchitectureRequest, SetPinnedPostsRequest,
+            UpsertTranslationRequest, validate_footprint, validate_height,
+        },
+        comment::Comment,
+        competition::{Competition, CompetitionEntry, CreateCompetitionRequest},
+        contribution::{
+            AgeBucket, BacklogAge, Contribution, ContributionAttachment, ContributionMetrics,
+            CreateContributionMessageRequest, ReviewerMetrics, RevertContributionRequest,
+            TrustLevel, UnauditedContribution,
+        },
+        exam_event::{CreateExamEventRequest, ExamEvent},
+        exam_record::{ExamAccommodations, SetExamAccommodationsRequest},
+        experiment::{ExperimentSummary, VariantExposures},
+        glossary::{CreateGlossaryTermRequest, UpdateGlossaryTermRequest},
+        post::Post,
+        question::{
+            CreateQuestionRequest, DeleteQuestionsRequest, FlaggedQuestion, MergeQuestionsRequest,
+            Question, QuestionFlag, QuestionTranslation, ResolveFlagsRequest,
+            UpsertQuestionTranslationRequest, validate_option_images,
+        },
+        security_event::{AbuseSummary, Offender},
+        stats::{AdminOnlineStats, OnlineUser},
+        terms::{PublishTermsRequest, TermsVersion},
+        user::{Ban, User},
     },
+    jobs::queue,
+    services::{
+        account_deletion, api_usage, audit_log, bans, broadcasts, catalog_export, contribution_messages,
+        contribution_review::{self, Published}, exam_accommodations, exam_events, experiments, image_links,
+        image_placeholders,
+        integrity, notifications, paper_snapshots,
+        question_duplicates, redirects, security_events, slugs,
+        segments::{self, UserFilter},
+    },
+    storage::Storage,
+    utils::audit_stream,
     utils::hash::hash_password,
-    utils::jwt::Claims,
+    utils::jwt::AuthUser,
     utils::html::clean_html,
+    utils::locale::{DEFAULT_LOCALE, canonical_locale},
+    utils::location::parse_location,
+    utils::permissions::{Permission, Role},
+    utils::role_cache::RoleCache,
+    utils::text_length,
+    utils::log_filter::{LogFilter, LogFilterError},
+    utils::presence::Presence,
+    utils::pinyin::{pinyin_keys, slugify},
+    utils::units::Measure,
 };
 
 // --- DTOs ---
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct AdminCreateUserRequest {
-    #[validate(length(
-        min = 3,
-        max = 50,
-        message = "Username length must be between 3 and 50 characters."
-    ))]
+    #[validate(custom(function = text_length::username))]
     pub username: String,
     #[validate(length(
         min = 4,
@@ -37,42 +86,253 @@ pub struct AdminCreateUserRequest {
         message = "Password length must be between 4 and 128 characters."
     ))]
     pub password: String,
-    pub role: String, // 'user' or 'admin'
+    #[validate(custom(function = validate_role))]
+    pub role: String, // 'user', 'moderator', 'editor' or 'admin'
 }
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct AdminUpdateUserRequest {
-    #[validate(length(min = 3, max = 50))]
+    #[validate(custom(function = text_length::username))]
     pub username: Option<String>,
-    #[validate(length(min = 1, max = 20))]
+    #[validate(custom(function = validate_role))]
     pub role: Option<String>,
     #[validate(length(min = 4, max = 128))]
     pub password: Option<String>,
     pub is_verified: Option<bool>,
 }
 
+/// Restricts roles to the known ones, see [`Role`].
+fn validate_role(role: &str) -> Result<(), validator::ValidationError> {
+    if Role::parse(role).is_none() {
+        return Err(validator::ValidationError::new("unknown_role"));
+    }
+    Ok(())
+}
+
+/// DTO for granting or revoking a user's verified status.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetVerificationRequest {
+    pub is_verified: bool,
+    /// Shown to the user and kept in the audit log.
+    #[validate(custom(function = text_length::admin_reason))]
+    pub reason: String,
+}
+/// DTO for banning a user or lifting their ban.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetBanRequest {
+    pub banned: bool,
+    /// When the ban ends; omit for an indefinite ban. Ignored when lifting.
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Shown to the user and kept in the audit log.
+    #[validate(custom(function = text_length::admin_reason))]
+    pub reason: String,
+}
+
+
+/// Query parameters accepted by destructive admin operations.
+#[derive(Debug, Deserialize)]
+pub struct DryRunParams {
+    /// When true, report what would be affected and roll back instead of committing.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Paging for the per-user overview.
+#[derive(Debug, Deserialize)]
+pub struct UserOverviewParams {
+    /// Most recent items returned per list (default 20, max 100).
+    pub limit: Option<i64>,
+}
+
+/// A post the user liked.
+#[derive(Debug, Serialize)]
+pub struct LikedPost {
+    pub post_id: i64,
+    pub title: String,
+    pub post_deleted: bool,
+    pub liked_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Totals behind the (truncated) lists of a user overview.
+#[derive(Debug, Serialize)]
+pub struct UserActivityCounts {
+    pub posts: i64,
+    pub comments: i64,
+    pub likes: i64,
+    pub contributions: i64,
+}
+
+/// Everything a user has done, for support staff investigating reports.
+/// Lists are newest first and include soft-deleted posts and comments.
+#[derive(Debug, Serialize)]
+pub struct UserOverview {
+    pub user: User,
+    pub counts: UserActivityCounts,
+    pub posts: Vec<Post>,
+    pub comments: Vec<Comment>,
+    pub likes: Vec<LikedPost>,
+    pub contributions: Vec<Contribution>,
+    /// Under the current auto-approval rules.
+    pub trust_level: TrustLevel,
+}
+
+/// Options for the structured location backfill.
+#[derive(Debug, Deserialize)]
+pub struct LocationBackfillParams {
+    /// Report the result without writing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Re-parse rows that already have structured fields too.
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnparsedLocation {
+    pub id: i64,
+    pub location: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LocationBackfillReport {
+    pub scanned: usize,
+    pub updated: usize,
+    /// Rows whose location could not be parsed; these need manual editing.
+    pub unparsed: Vec<UnparsedLocation>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicateQuestionParams {
+    /// Minimum trigram similarity of the content (0.3-1, default 0.8).
+    pub threshold: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImageLinkCheckParams {
+    /// Check one architecture instead of all of them.
+    pub architecture_id: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImageLinkReportParams {
+    /// List working links too, not just dead ones.
+    #[serde(default)]
+    pub include_ok: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImageLinkCheck {
+    pub architecture_id: i64,
+    pub architecture_name: String,
+    /// 'cover' or 'carousel'.
+    pub field: String,
+    pub url: String,
+    /// 'ok', 'broken', 'unreachable' or 'invalid'.
+    pub status: String,
+    pub http_status: Option<i32>,
+    pub error: Option<String>,
+    pub checked_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImageLinkReport {
+    /// When the oldest result in the report was taken; None before the first check.
+    pub checked_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// A check job is queued or running, so results may be about to change.
+    pub in_progress: bool,
+    pub total: i64,
+    pub dead: i64,
+    pub links: Vec<ImageLinkCheck>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CatalogExport {
+    pub version: i64,
+    pub architecture_count: i32,
+    pub size_bytes: i64,
+    pub requested_by: Option<i64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Short-lived link to the bundle.
+    pub download_url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CatalogExportList {
+    /// An export job is queued or running.
+    pub in_progress: bool,
+    /// Newest first.
+    pub exports: Vec<CatalogExport>,
+}
+
+/// Filters for auditing where architectures and questions came from.
+#[derive(Debug, Deserialize)]
+pub struct ContentSourceParams {
+    /// 'contribution' (crowd-sourced) or 'admin' (created directly in the panel).
+    pub source: Option<String>,
+    pub created_by: Option<i64>,
+    pub contribution_id: Option<i64>,
+    /// Page size (default 50, max 200).
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl ContentSourceParams {
+    fn validate_source(&self) -> Result<(), AppError> {
+        match self.source.as_deref() {
+            None | Some("contribution") | Some("admin") => Ok(()),
+            Some(_) => Err(AppError::BadRequest(
+                "source must be 'contribution' or 'admin'".to_string(),
+            )),
+        }
+    }
+}
+
+/// Time window for the abuse dashboard.
+#[derive(Debug, Deserialize)]
+pub struct AbuseParams {
+    /// '1h', '24h' (default), '7d' or '30d'.
+    pub window: Option<String>,
+    /// How many offending IPs/users to list (default 10, max 100).
+    pub limit: Option<i64>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ReviewContributionRequest {
     pub status: String, // 'approved' or 'rejected'
+    /// Added to the review thread; blank means none.
     pub admin_comment: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct UpdateArchRequest {
-    #[validate(length(min = 1, max = 50))]
+    #[validate(custom(function = text_length::arch_label))]
     pub category: Option<String>,
-    #[validate(length(min = 1, max = 100))]
+    #[validate(custom(function = text_length::arch_name))]
     pub name: Option<String>,
-    #[validate(length(min = 1, max = 50))]
+    #[validate(custom(function = text_length::arch_label))]
     pub dynasty: Option<String>,
-    #[validate(length(min = 1, max = 200))]
+    #[validate(custom(function = text_length::arch_location))]
     pub location: Option<String>,
-    #[validate(length(min = 1, max = 20000))]
+    #[validate(custom(function = text_length::arch_label))]
+    pub province: Option<String>,
+    #[validate(custom(function = text_length::arch_label))]
+    pub city: Option<String>,
+    #[validate(custom(function = text_length::arch_label))]
+    pub district: Option<String>,
+    #[validate(custom(function = text_length::arch_description))]
     pub description: Option<String>,
     #[validate(length(min = 1, max = 500))]
     pub cover_img: Option<String>,
     #[validate(custom(function = validate_optional_carousel_urls))]
     pub carousel_imgs: Option<Vec<String>>,
+    #[validate(custom(function = validate_height))]
+    pub height_m: Option<Measure>,
+    #[validate(custom(function = validate_footprint))]
+    pub footprint_m2: Option<Measure>,
+    #[validate(range(min = 1, max = ARCH_MAX_BAYS))]
+    pub bays_wide: Option<i32>,
+    #[validate(range(min = 1, max = ARCH_MAX_BAYS))]
+    pub bays_deep: Option<i32>,
 }
 
 fn validate_optional_carousel_urls(urls: &[String]) -> Result<(), validator::ValidationError> {
@@ -88,19 +348,21 @@ fn validate_optional_carousel_urls(urls: &[String]) -> Result<(), validator::Val
 pub struct UpdateQuestionRequest {
     #[validate(length(min = 1, max = 20))]
     pub question_type: Option<String>,
-    #[validate(length(min = 1, max = 1000))]
+    #[validate(custom(function = text_length::question_content))]
     pub content: Option<String>,
     #[validate(custom(function = validate_optional_options))]
     pub options: Option<Vec<String>>,
-    #[validate(length(min = 1, max = 500))]
+    #[validate(custom(function = validate_option_images))]
+    pub option_images: Option<Vec<Option<String>>>,
+    #[validate(custom(function = text_length::question_answer))]
     pub answer: Option<String>,
-    #[validate(length(max = 2000))]
+    #[validate(custom(function = text_length::question_analysis))]
     pub analysis: Option<String>,
 }
 
 fn validate_optional_options(options: &[String]) -> Result<(), validator::ValidationError> {
     for opt in options {
-        if opt.len() > 500 {
+        if text_length::question_option(opt).is_err() {
             return Err(validator::ValidationError::new("option_too_long"));
         }
     }
@@ -128,8 +390,128 @@ pub async fn list_users(State(pool): State<PgPool>) -> Result<impl IntoResponse,
     Ok(Json(users))
 }
 
+/// Shows a user's posts, comments, likes and contributions in one call.
+pub async fn get_user_overview(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    Path(id): Path<i64>,
+    Query(params): Query<UserOverviewParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, username, '********' as "password!", role, is_verified, created_at
+        FROM users
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    let counts = sqlx::query_as!(
+        UserActivityCounts,
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM posts WHERE user_id = $1) as "posts!",
+            (SELECT COUNT(*) FROM comments WHERE user_id = $1) as "comments!",
+            (SELECT COUNT(*) FROM post_likes WHERE user_id = $1) as "likes!",
+            (SELECT COUNT(*) FROM contributions WHERE user_id = $1) as "contributions!"
+        "#,
+        id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    let posts = sqlx::query_as!(
+        Post,
+        r#"
+        SELECT
+            id, user_id, slug, title, content, excerpt, reading_time_minutes,
+            created_at, updated_at, edited_at, deleted_at,
+            likes_count, comments_count, favorites_count,
+            FALSE as "is_liked!", FALSE as "is_favorited!"
+        FROM posts
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+        id,
+        limit
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let comments = sqlx::query_as!(
+        Comment,
+        r#"
+        SELECT id, post_id, user_id, content, root_id, parent_id, created_at, updated_at, deleted_at
+        FROM comments
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+        id,
+        limit
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let likes = sqlx::query_as!(
+        LikedPost,
+        r#"
+        SELECT pl.post_id, p.title, (p.deleted_at IS NOT NULL) as "post_deleted!", pl.created_at as liked_at
+        FROM post_likes pl
+        JOIN posts p ON p.id = pl.post_id
+        WHERE pl.user_id = $1
+        ORDER BY pl.created_at DESC
+        LIMIT $2
+        "#,
+        id,
+        limit
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let contributions = sqlx::query_as!(
+        Contribution,
+        r#"
+        SELECT id, user_id, type, data, status, auto_approved,
+            (SELECT COUNT(*) FROM contribution_messages m WHERE m.contribution_id = contributions.id) as "message_count!",
+            created_at, reviewed_at
+        FROM contributions
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+        id,
+        limit
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let trust_level =
+        contribution_review::trust_level(&mut *pool.acquire().await?, &config.auto_approval, id)
+            .await?;
+
+    Ok(Json(UserOverview {
+        user,
+        counts,
+        posts,
+        comments,
+        likes,
+        contributions,
+        trust_level,
+    }))
+}
+
 pub async fn update_user(
     State(pool): State<PgPool>,
+    State(roles): State<RoleCache>,
+    admin: AuthUser,
     Path(id): Path<i64>,
     Json(payload): Json<AdminUpdateUserRequest>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -147,6 +529,27 @@ pub async fn update_user(
         return Ok(StatusCode::OK);
     }
 
+    // Field names only: passwords must not reach the audit log
+    let mut fields = Vec::new();
+    if payload.username.is_some() {
+        fields.push("username");
+    }
+    if payload.role.is_some() {
+        fields.push("role");
+    }
+    if payload.password.is_some() {
+        fields.push("password");
+    }
+    if payload.is_verified.is_some() {
+        fields.push("is_verified");
+    }
+    let role = payload.role.clone();
+
+    let mut tx = pool.begin().await?;
+    let before = user_snapshot(&mut tx, id)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
+
     if let Some(new_username) = payload.username {
         separated.push("username = ");
         separated.push_bind_unseparated(new_username);
@@ -155,8 +558,8 @@ pub async fn update_user(
         separated.push("role = ");
         separated.push_bind_unseparated(new_role);
     }
-    if let Some(new_password) = payload.password {
-        let hashed = hash_password(&new_password)?;
+    if let Some(new_password) = &payload.password {
+        let hashed = hash_password(new_password)?;
         separated.push("password = ");
         separated.push_bind_unseparated(hashed);
     }
@@ -168,7 +571,7 @@ pub async fn update_user(
     builder.push(" WHERE id = ");
     builder.push_bind(id);
 
-    let result = builder.build().execute(&pool).await.map_err(|e| {
+    builder.build().execute(&mut *tx).await.map_err(|e| {
         if e.to_string().contains("unique constraint") {
             AppError::Conflict("Username already exists".to_string())
         } else {
@@ -176,15 +579,305 @@ pub async fn update_user(
         }
     })?;
 
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound("User not found".to_string()));
+    let mut after = user_snapshot(&mut tx, id).await?.unwrap_or_default();
+    if payload.password.is_some() {
+        // Marks the change without the hash itself
+        after["password"] = serde_json::json!("(changed)");
     }
+    audit_log::record_update(&mut tx, admin.id, "user", id, &before, &after, None).await?;
+
+    tx.commit().await?;
+    roles.invalidate(id);
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "user_updated",
+        actor_id = admin.id,
+        user_id = id,
+        fields = fields.join(","),
+        role
+    );
 
     Ok(StatusCode::OK)
 }
 
+/// The audited fields of a user as JSON, locking the row.
+async fn user_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    id: i64,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT jsonb_build_object('username', username, 'role', role, 'is_verified', is_verified)
+            as "snapshot!"
+        FROM users
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+/// Grants or revokes a user's verified status.
+///
+/// The reason is recorded in the audit log and sent to the user as a
+/// notification. Setting the status the user already has changes nothing.
+pub async fn set_user_verification(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<SetVerificationRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut tx = pool.begin().await?;
+
+    let was_verified = sqlx::query_scalar!(
+        "SELECT is_verified FROM users WHERE id = $1 FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    if was_verified == payload.is_verified {
+        return Ok(Json(serde_json::json!({
+            "is_verified": was_verified,
+            "changed": false
+        })));
+    }
+
+    sqlx::query!(
+        "UPDATE users SET is_verified = $1 WHERE id = $2",
+        payload.is_verified,
+        id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    audit_log::record_update(
+        &mut tx,
+        admin.id,
+        "user",
+        id,
+        &serde_json::json!({ "is_verified": was_verified }),
+        &serde_json::json!({ "is_verified": payload.is_verified }),
+        Some(&payload.reason),
+    )
+    .await?;
+
+    let kind = if payload.is_verified {
+        notifications::VERIFICATION_GRANTED
+    } else {
+        notifications::VERIFICATION_REVOKED
+    };
+    notifications::notify(
+        &mut *tx,
+        id,
+        kind,
+        serde_json::json!({ "reason": payload.reason }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "user_verification_changed",
+        actor_id = admin.id,
+        user_id = id,
+        is_verified = payload.is_verified,
+        reason = payload.reason
+    );
+
+    Ok(Json(serde_json::json!({
+        "is_verified": payload.is_verified,
+        "changed": true
+    })))
+}
+
+/// Bans a user, for a while or indefinitely, or lifts their ban.
+///
+/// Banned users keep their account and content, which stays visible, but
+/// every write they attempt is refused until the ban ends. Staff cannot be
+/// banned; change their role first. The reason is recorded in the audit log
+/// and sent to the user as a notification.
+pub async fn set_user_ban(
+    State(pool): State<PgPool>,
+    moderator: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<SetBanRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if id == moderator.id {
+        return Err(AppError::BadRequest("Cannot ban yourself".to_string()));
+    }
+    let until = if payload.banned { payload.until } else { None };
+    if until.is_some_and(|until| until <= chrono::Utc::now()) {
+        return Err(AppError::BadRequest("until must be in the future".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let current = sqlx::query!(
+        "SELECT role, banned_until, ban_reason FROM users WHERE id = $1 FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    if payload.banned && Role::parse(&current.role).is_some_and(Role::is_staff) {
+        return Err(AppError::BadRequest(
+            "Cannot ban staff; change their role first".to_string(),
+        ));
+    }
+    if !payload.banned && current.ban_reason.is_none() {
+        return Ok(Json(serde_json::json!({ "ban": null, "changed": false })));
+    }
+
+    let ban = if payload.banned {
+        bans::ban(&mut *tx, id, until, &payload.reason).await?;
+        Some(Ban {
+            banned_until: until,
+            reason: payload.reason.clone(),
+        })
+    } else {
+        bans::lift(&mut *tx, id).await?;
+        None
+    };
+
+    audit_log::record_update(
+        &mut tx,
+        moderator.id,
+        "user",
+        id,
+        &serde_json::json!({ "banned_until": current.banned_until, "ban_reason": current.ban_reason }),
+        &serde_json::json!({
+            "banned_until": until,
+            "ban_reason": ban.as_ref().map(|ban| &ban.reason)
+        }),
+        Some(&payload.reason),
+    )
+    .await?;
+
+    let (kind, notice) = if payload.banned {
+        (
+            notifications::ACCOUNT_BANNED,
+            serde_json::json!({ "banned_until": until, "reason": payload.reason }),
+        )
+    } else {
+        (
+            notifications::ACCOUNT_UNBANNED,
+            serde_json::json!({ "reason": payload.reason }),
+        )
+    };
+    notifications::notify(&mut *tx, id, kind, notice).await?;
+
+    tx.commit().await?;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = if payload.banned { "user_banned" } else { "user_unbanned" },
+        actor_id = moderator.id,
+        user_id = id,
+        banned_until = ?until,
+        reason = payload.reason
+    );
+
+    Ok(Json(serde_json::json!({ "ban": ban, "changed": true })))
+}
+
+/// Grants exam accommodations to a user, replacing any earlier grant.
+///
+/// They apply to exams issued from now on; the change and its reason are
+/// kept in the audit log.
+pub async fn set_exam_accommodations(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<SetExamAccommodationsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query_scalar!("SELECT id FROM users WHERE id = $1 FOR UPDATE", id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
+    let before = exam_accommodations::find(&mut *tx, id).await?;
+
+    let accommodations = ExamAccommodations {
+        time_multiplier: payload.time_multiplier,
+        large_labels: payload.large_labels,
+        question_count: payload.question_count,
+    };
+    exam_accommodations::set(&mut *tx, id, &accommodations, admin.id).await?;
+
+    audit_log::record_update(
+        &mut tx,
+        admin.id,
+        "user",
+        id,
+        &serde_json::json!({ "exam_accommodations": before }),
+        &serde_json::json!({ "exam_accommodations": accommodations }),
+        Some(&payload.reason),
+    )
+    .await?;
+
+    tx.commit().await?;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "exam_accommodations_granted",
+        actor_id = admin.id,
+        user_id = id,
+        time_multiplier = accommodations.time_multiplier,
+        large_labels = accommodations.large_labels,
+        question_count = accommodations.question_count,
+        reason = payload.reason
+    );
+
+    Ok(Json(accommodations))
+}
+
+/// Withdraws a user's exam accommodations; later exams are the standard one.
+pub async fn clear_exam_accommodations(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let before = exam_accommodations::find(&mut *tx, id)
+        .await?
+        .ok_or(AppError::NotFound("No exam accommodations for this user".to_string()))?;
+    exam_accommodations::clear(&mut *tx, id).await?;
+
+    audit_log::record_update(
+        &mut tx,
+        admin.id,
+        "user",
+        id,
+        &serde_json::json!({ "exam_accommodations": before }),
+        &serde_json::json!({ "exam_accommodations": null }),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "exam_accommodations_cleared",
+        actor_id = admin.id,
+        user_id = id
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 pub async fn create_user(
     State(pool): State<PgPool>,
+    admin: AuthUser,
     Json(payload): Json<AdminCreateUserRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     payload
@@ -193,6 +886,7 @@ pub async fn create_user(
 
     let hashed_password = hash_password(&payload.password)?;
 
+    let mut tx = pool.begin().await?;
     let id = sqlx::query!(
         r#"
         INSERT INTO users (username, password, role)
@@ -203,7 +897,7 @@ pub async fn create_user(
         hashed_password,
         payload.role
     )
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
         if e.to_string().contains("unique constraint") {
@@ -214,15 +908,25 @@ pub async fn create_user(
     })?
     .id;
 
+    let after = user_snapshot(&mut tx, id).await?.unwrap_or_default();
+    audit_log::record_create(&mut tx, admin.id, "user", id, &after).await?;
+    tx.commit().await?;
+
     Ok((StatusCode::CREATED, Json(serde_json::json!({"id": id}))))
 }
 
+/// Deletes a user, handing their posts and comments over to the ghost user.
+///
+/// With `?dry_run=true` the same transaction is opened, the impact is counted
+/// and everything is rolled back, so admins can see what would happen first.
 pub async fn delete_user(
     State(pool): State<PgPool>,
-    Extension(claims): Extension<Claims>,
+    State(roles): State<RoleCache>,
+    admin: AuthUser,
     Path(id): Path<i64>,
-) -> Result<impl IntoResponse, AppError> {
-    let current_user_id = claims.sub.parse::<i64>().unwrap_or(0);
+    Query(params): Query<DryRunParams>,
+) -> Result<Response, AppError> {
+    let current_user_id = admin.id;
     if id == current_user_id {
         return Err(AppError::BadRequest("Cannot delete yourself".to_string()));
     }
@@ -230,10 +934,8 @@ pub async fn delete_user(
     let mut tx = pool.begin().await?;
 
     // 1. Fetch the ghost user ID for account deletion redirection
-    let ghost_id = sqlx::query!("SELECT id FROM users WHERE username = 'ghost'")
-        .fetch_optional(&mut *tx)
+    let ghost_id = account_deletion::ghost_id(&mut *tx)
         .await?
-        .map(|r| r.id)
         .ok_or_else(|| AppError::InternalServerError("Ghost user not found".to_string()))?;
 
     // Prevent deletion of the system-critical ghost user
@@ -241,74 +943,154 @@ pub async fn delete_user(
         return Err(AppError::BadRequest("Cannot delete the ghost user".to_string()));
     }
 
-    // 2. Transfer posts to the ghost user
-    sqlx::query!(
-        "UPDATE posts SET user_id = $1 WHERE user_id = $2",
-        ghost_id,
-        id
-    )
-    .execute(&mut *tx)
-    .await?;
-
-    // 3. Transfer comments to the ghost user
-    sqlx::query!(
-        "UPDATE comments SET user_id = $1 WHERE user_id = $2",
-        ghost_id,
-        id
-    )
-    .execute(&mut *tx)
-    .await?;
-    
-    // 4. Note on interactions (likes/favorites):
-    // These are usually handled via ON DELETE CASCADE in the database schema.
-    // We keep this behavior as likes are personal and don't need transfer.
+    // 2. Count everything the deletion touches
+    let impact = account_deletion::impact(&mut *tx, id)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
 
-    // 5. Delete the target user
-    let result = sqlx::query!("DELETE FROM users WHERE id = $1", id)
-        .execute(&mut *tx)
-        .await?;
+    if params.dry_run {
+        tx.rollback().await?;
+        return Ok(Json(impact).into_response());
+    }
 
-    if result.rows_affected() == 0 {
+    // 3. Hand posts and comments to the ghost user and delete the rest
+    let before = user_snapshot(&mut tx, id).await?.unwrap_or_default();
+    if !account_deletion::transfer_and_delete(&mut tx, ghost_id, id).await? {
         return Err(AppError::NotFound("User not found".to_string()));
     }
+    audit_log::record_delete(&mut tx, current_user_id, "user", id, &before).await?;
 
     tx.commit().await?;
+    roles.invalidate(id);
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "user_deleted",
+        actor_id = current_user_id,
+        user_id = id,
+        username = impact.username,
+        posts_transferred = impact.posts_to_transfer
+    );
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(StatusCode::NO_CONTENT.into_response())
 }
 
 // --- Architecture Management ---
 
+/// Lists architectures with their provenance, for auditing crowd-sourced entries.
+pub async fn list_architectures(
+    State(pool): State<PgPool>,
+    Query(params): Query<ContentSourceParams>,
+) -> Result<impl IntoResponse, AppError> {
+    params.validate_source()?;
+    let limit = params.limit.unwrap_or(50).clamp(1, 200);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT a.id, a.slug, a.category, a.name, a.dynasty, a.location, a.province, a.city, a.district,
+               a.description, a.cover_img,
+               a.carousel_imgs as "carousel_imgs: sqlx::types::Json<Vec<String>>",
+               a.contribution_id, a.created_by, a.height_m, a.footprint_m2, a.bays_wide, a.bays_deep,
+               ip.dominant_color as "cover_color?", ip.blurhash as "cover_blurhash?"
+        FROM architectures a
+        LEFT JOIN image_placeholders ip ON ip.url = a.cover_img AND ip.status = 'ok'
+        WHERE ($1::TEXT IS NULL OR ($1 = 'contribution') = (a.contribution_id IS NOT NULL))
+          AND ($2::BIGINT IS NULL OR a.created_by = $2)
+          AND ($3::BIGINT IS NULL OR a.contribution_id = $3)
+        ORDER BY a.id DESC
+        LIMIT $4 OFFSET $5
+        "#,
+        params.source,
+        params.created_by,
+        params.contribution_id,
+        limit,
+        offset
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let list: Vec<AdminArchitecture> = rows
+        .into_iter()
+        .map(|r| AdminArchitecture {
+            architecture: Architecture {
+                id: r.id,
+                slug: r.slug,
+                category: r.category,
+                name: r.name,
+                dynasty: r.dynasty,
+                location: r.location,
+                province: r.province,
+                city: r.city,
+                district: r.district,
+                description: r.description,
+                cover_img: r.cover_img,
+                carousel_imgs: r.carousel_imgs,
+                height_m: r.height_m,
+                footprint_m2: r.footprint_m2,
+                bays_wide: r.bays_wide,
+                bays_deep: r.bays_deep,
+                cover_color: r.cover_color,
+                cover_blurhash: r.cover_blurhash,
+            },
+            contribution_id: r.contribution_id,
+            created_by: r.created_by,
+        })
+        .collect();
+
+    Ok(Json(list))
+}
+
 pub async fn create_architecture(
     State(pool): State<PgPool>,
+    admin: AuthUser,
     Json(payload): Json<CreateArchRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let admin_id = admin.id;
+    let loc = payload.structured_location();
+    let keys = pinyin_keys(&payload.name);
+    let dims = payload.dimensions();
     let carousel_json = serde_json::to_value(payload.carousel_imgs).unwrap_or_default();
     
     let clean_desc = clean_html(&payload.description);
 
+    let mut tx = pool.begin().await?;
+    let slug = slugs::for_architecture(&mut tx, &payload.name).await?;
     let id = sqlx::query!(
         r#"
-        INSERT INTO architectures (category, name, dynasty, location, description, cover_img, carousel_imgs)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        INSERT INTO architectures (category, name, dynasty, location, province, city, district, description, cover_img, carousel_imgs, created_by, name_pinyin, name_initials, height_m, footprint_m2, bays_wide, bays_deep, slug)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
         RETURNING id
         "#,
-        payload.category, payload.name, payload.dynasty, payload.location, clean_desc, payload.cover_img, carousel_json
+        payload.category, payload.name, payload.dynasty, payload.location, loc.province, loc.city, loc.district, clean_desc, payload.cover_img, carousel_json, admin_id, keys.full, keys.initials,
+        dims.height_m, dims.footprint_m2, dims.bays_wide, dims.bays_deep, slug
     )
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await?
     .id;
 
-    Ok((StatusCode::CREATED, Json(serde_json::json!({"id": id}))))
+    let after = architecture_snapshot(&mut tx, id).await?.unwrap_or_default();
+    audit_log::record_create(&mut tx, admin_id, "architecture", id, &after).await?;
+    tx.commit().await?;
+    image_placeholders::queue_cover(&pool, id, payload.cover_img).await;
+
+    Ok((StatusCode::CREATED, Json(serde_json::json!({"id": id, "slug": slug}))))
 }
 
+/// Updates an architecture and records what changed in the audit log.
 pub async fn update_architecture(
     State(pool): State<PgPool>,
+    admin: AuthUser,
     Path(id): Path<i64>,
     Json(payload): Json<UpdateArchRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let admin_id = admin.id;
+
+    let mut tx = pool.begin().await?;
+    let before = architecture_snapshot(&mut tx, id)
+        .await?
+        .ok_or(AppError::NotFound("Architecture not found".to_string()))?;
 
     let mut builder: QueryBuilder<Postgres> = QueryBuilder::new("UPDATE architectures SET ");
     let mut separated = builder.separated(", ");
@@ -318,6 +1100,11 @@ pub async fn update_architecture(
         separated.push_bind_unseparated(v);
     }
     if let Some(v) = payload.name {
+        let keys = pinyin_keys(&v);
+        separated.push("name_pinyin = ");
+        separated.push_bind_unseparated(keys.full);
+        separated.push("name_initials = ");
+        separated.push_bind_unseparated(keys.initials);
         separated.push("name = ");
         separated.push_bind_unseparated(v);
     }
@@ -325,15 +1112,39 @@ pub async fn update_architecture(
         separated.push("dynasty = ");
         separated.push_bind_unseparated(v);
     }
+    let explicit_location =
+        payload.province.is_some() || payload.city.is_some() || payload.district.is_some();
     if let Some(v) = payload.location {
+        // A new location without explicit structured fields is re-parsed.
+        if !explicit_location {
+            let loc = parse_location(&v);
+            separated.push("province = ");
+            separated.push_bind_unseparated(loc.province);
+            separated.push("city = ");
+            separated.push_bind_unseparated(loc.city);
+            separated.push("district = ");
+            separated.push_bind_unseparated(loc.district);
+        }
         separated.push("location = ");
         separated.push_bind_unseparated(v);
     }
+    if let Some(v) = payload.province {
+        separated.push("province = ");
+        separated.push_bind_unseparated(v);
+    }
+    if let Some(v) = payload.city {
+        separated.push("city = ");
+        separated.push_bind_unseparated(v);
+    }
+    if let Some(v) = payload.district {
+        separated.push("district = ");
+        separated.push_bind_unseparated(v);
+    }
     if let Some(v) = payload.description {
         separated.push("description = ");
         separated.push_bind_unseparated(clean_html(&v));
     }
-    if let Some(v) = payload.cover_img {
+    if let Some(v) = payload.cover_img.clone() {
         separated.push("cover_img = ");
         separated.push_bind_unseparated(v);
     }
@@ -341,175 +1152,2639 @@ pub async fn update_architecture(
         separated.push("carousel_imgs = ");
         separated.push_bind_unseparated(serde_json::to_value(v).unwrap_or_default());
     }
+    if let Some(v) = payload.height_m.as_ref().and_then(Measure::metres) {
+        separated.push("height_m = ");
+        separated.push_bind_unseparated(v);
+    }
+    if let Some(v) = payload.footprint_m2.as_ref().and_then(Measure::square_metres) {
+        separated.push("footprint_m2 = ");
+        separated.push_bind_unseparated(v);
+    }
+    if let Some(v) = payload.bays_wide {
+        separated.push("bays_wide = ");
+        separated.push_bind_unseparated(v);
+    }
+    if let Some(v) = payload.bays_deep {
+        separated.push("bays_deep = ");
+        separated.push_bind_unseparated(v);
+    }
 
     builder.push(" WHERE id = ");
     builder.push_bind(id);
+    builder.build().execute(&mut *tx).await?;
 
-    let result = builder.build().execute(&pool).await?;
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound("Architecture not found".to_string()));
+    let after = architecture_snapshot(&mut tx, id).await?.unwrap_or_default();
+    audit_log::record_update(&mut tx, admin_id, "architecture", id, &before, &after, None)
+        .await?;
+
+    tx.commit().await?;
+    if let Some(cover_img) = payload.cover_img {
+        image_placeholders::queue_cover(&pool, id, cover_img).await;
     }
     Ok(StatusCode::OK)
 }
 
-pub async fn delete_architecture(
+/// The audited fields of an architecture as JSON, locking the row.
+/// Search keys derived from the name and the change timestamp are left out.
+async fn architecture_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    id: i64,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT to_jsonb(a) - ARRAY['name_pinyin', 'name_initials', 'updated_at'] as "snapshot!"
+        FROM architectures a
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+/// Fills province/city/district from the free-text `location`.
+/// By default only rows without any structured field are touched.
+pub async fn backfill_locations(
     State(pool): State<PgPool>,
-    Path(id): Path<i64>,
+    admin: AuthUser,
+    Query(params): Query<LocationBackfillParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    let result = sqlx::query!("DELETE FROM architectures WHERE id = $1", id)
-        .execute(&pool)
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, location, province, city, district FROM architectures
+        WHERE $1 OR (province IS NULL AND city IS NULL AND district IS NULL)
+        ORDER BY id
+        "#,
+        params.overwrite
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let mut report = LocationBackfillReport {
+        scanned: rows.len(),
+        updated: 0,
+        unparsed: Vec::new(),
+    };
+
+    let mut tx = pool.begin().await?;
+    for row in rows {
+        let loc = parse_location(&row.location);
+        if loc.is_empty() {
+            report.unparsed.push(UnparsedLocation {
+                id: row.id,
+                location: row.location,
+            });
+            continue;
+        }
+
+        sqlx::query!(
+            "UPDATE architectures SET province = $1, city = $2, district = $3 WHERE id = $4",
+            loc.province,
+            loc.city,
+            loc.district,
+            row.id
+        )
+        .execute(&mut *tx)
         .await?;
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound("Architecture not found".to_string()));
+        audit_log::record_update(
+            &mut tx,
+            admin.id,
+            "architecture",
+            row.id,
+            &serde_json::json!({ "province": row.province, "city": row.city, "district": row.district }),
+            &serde_json::json!({ "province": loc.province, "city": loc.city, "district": loc.district }),
+            None,
+        )
+        .await?;
+        report.updated += 1;
     }
-    Ok(StatusCode::NO_CONTENT)
-}
 
-// --- Question Management ---
+    if params.dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
 
-pub async fn create_question(
+    Ok(Json(report))
+}
+
+/// Queues a check of architecture image URLs; results appear in the link report.
+pub async fn check_image_links(
     State(pool): State<PgPool>,
-    Json(payload): Json<CreateQuestionRequest>,
+    Query(params): Query<ImageLinkCheckParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if let Some(id) = params.architecture_id {
+        sqlx::query!("SELECT id FROM architectures WHERE id = $1", id)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or(AppError::NotFound("Architecture not found".to_string()))?;
+    }
 
-    let options_json = serde_json::to_value(payload.options).unwrap_or_default();
-    
-    let clean_content = clean_html(&payload.content);
-    let clean_answer = clean_html(&payload.answer);
-    let clean_analysis = payload.analysis.as_ref().map(|a| clean_html(a));
+    let job_id = queue::enqueue(
+        &pool,
+        image_links::CHECK_IMAGE_LINKS_JOB,
+        serde_json::json!({ "architecture_id": params.architecture_id }),
+    )
+    .await?;
 
-    let id = sqlx::query!(
-        "INSERT INTO questions (type, content, options, answer, analysis) VALUES ($1, $2, $3, $4, $5) RETURNING id",
-        payload.question_type, clean_content, options_json, clean_answer, clean_analysis
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({ "job_id": job_id }))))
+}
+
+/// Results of the last image link check, dead links first.
+pub async fn get_image_link_report(
+    State(pool): State<PgPool>,
+    Query(params): Query<ImageLinkReportParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let summary = sqlx::query!(
+        r#"
+        SELECT
+            MIN(checked_at) as checked_since,
+            COUNT(*) as "total!",
+            COUNT(*) FILTER (WHERE status <> 'ok') as "dead!",
+            EXISTS (
+                SELECT 1 FROM jobs WHERE kind = $1 AND status IN ('pending', 'running')
+            ) as "in_progress!"
+        FROM architecture_image_checks
+        "#,
+        image_links::CHECK_IMAGE_LINKS_JOB
     )
     .fetch_one(&pool)
-    .await?
-    .id;
+    .await?;
 
-    Ok((StatusCode::CREATED, Json(serde_json::json!({"id": id}))))
+    let links = sqlx::query_as!(
+        ImageLinkCheck,
+        r#"
+        SELECT c.architecture_id, a.name as architecture_name, c.field, c.url,
+               c.status, c.http_status, c.error, c.checked_at
+        FROM architecture_image_checks c
+        JOIN architectures a ON a.id = c.architecture_id
+        WHERE $1 OR c.status <> 'ok'
+        ORDER BY c.status = 'ok', c.architecture_id, c.field, c.url
+        "#,
+        params.include_ok
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(ImageLinkReport {
+        checked_since: summary.checked_since,
+        in_progress: summary.in_progress,
+        total: summary.total,
+        dead: summary.dead,
+        links,
+    }))
 }
 
-pub async fn update_question(
+/// Queues an export of the whole catalog to a new versioned JSON bundle.
+pub async fn export_catalog(
     State(pool): State<PgPool>,
-    Path(id): Path<i64>,
-    Json(payload): Json<UpdateQuestionRequest>,
+    admin: AuthUser,
 ) -> Result<impl IntoResponse, AppError> {
-    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let job_id = queue::enqueue(
+        &pool,
+        catalog_export::EXPORT_CATALOG_JOB,
+        serde_json::json!({ "requested_by": admin.id }),
+    )
+    .await?;
 
-    let mut builder: QueryBuilder<Postgres> = QueryBuilder::new("UPDATE questions SET ");
-    let mut separated = builder.separated(", ");
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({ "job_id": job_id }))))
+}
 
-    if let Some(v) = payload.question_type {
-        separated.push("type = ");
-        separated.push_bind_unseparated(v);
-    }
-    if let Some(v) = payload.content {
-        separated.push("content = ");
-        separated.push_bind_unseparated(clean_html(&v));
-    }
-    if let Some(v) = payload.options {
-        separated.push("options = ");
-        separated.push_bind_unseparated(serde_json::to_value(v).unwrap_or_default());
-    }
-    if let Some(v) = payload.answer {
-        separated.push("answer = ");
-        separated.push_bind_unseparated(clean_html(&v));
-    }
-    if let Some(v) = payload.analysis {
-        separated.push("analysis = ");
-        separated.push_bind_unseparated(clean_html(&v));
-    }
+/// Catalog bundles written so far, with download links.
+pub async fn list_catalog_exports(
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn Storage>>,
+) -> Result<impl IntoResponse, AppError> {
+    let in_progress = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM jobs WHERE kind = $1 AND status IN ('pending', 'running')) as "in_progress!""#,
+        catalog_export::EXPORT_CATALOG_JOB
+    )
+    .fetch_one(&pool)
+    .await?;
 
-    builder.push(" WHERE id = ");
-    builder.push_bind(id);
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, storage_key, architecture_count, size_bytes, requested_by, created_at
+        FROM catalog_exports
+        ORDER BY id DESC
+        "#
+    )
+    .fetch_all(&pool)
+    .await?;
 
-    let result = builder.build().execute(&pool).await?;
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound("Question not found".to_string()));
+    let mut exports = Vec::with_capacity(rows.len());
+    for row in rows {
+        exports.push(CatalogExport {
+            download_url: storage
+                .signed_url(&row.storage_key, Duration::from_secs(CATALOG_EXPORT_URL_SECS))
+                .await?,
+            version: row.id,
+            architecture_count: row.architecture_count,
+            size_bytes: row.size_bytes,
+            requested_by: row.requested_by,
+            created_at: row.created_at,
+        });
     }
-    Ok(StatusCode::OK)
+
+    Ok(Json(CatalogExportList { in_progress, exports }))
 }
 
-pub async fn delete_question(
+/// Adds or replaces the translation of an architecture into `locale`.
+pub async fn upsert_architecture_translation(
     State(pool): State<PgPool>,
-    Path(id): Path<i64>,
+    admin: AuthUser,
+    Path((id, locale)): Path<(i64, String)>,
+    Json(payload): Json<UpsertTranslationRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let result = sqlx::query!("DELETE FROM questions WHERE id = $1", id)
-        .execute(&pool)
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let locale = canonical_locale(&locale)
+        .filter(|l| *l != DEFAULT_LOCALE)
+        .ok_or_else(|| AppError::BadRequest(format!("Cannot translate into '{}'", locale)))?;
+
+    let mut tx = pool.begin().await?;
+    let exists = sqlx::query!("SELECT id FROM architectures WHERE id = $1 FOR UPDATE", id)
+        .fetch_optional(&mut *tx)
         .await?;
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound("Question not found".to_string()));
+    if exists.is_none() {
+        return Err(AppError::NotFound("Architecture not found".to_string()));
     }
-    Ok(StatusCode::NO_CONTENT)
+    let before = architecture_translation_snapshot(&mut tx, id, locale).await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO architecture_translations (architecture_id, locale, name, dynasty, location, description)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (architecture_id, locale) DO UPDATE SET
+            name = EXCLUDED.name,
+            dynasty = EXCLUDED.dynasty,
+            location = EXCLUDED.location,
+            description = EXCLUDED.description,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        id,
+        locale,
+        payload.name,
+        payload.dynasty,
+        payload.location,
+        payload.description.as_deref().map(clean_html)
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let after = architecture_translation_snapshot(&mut tx, id, locale).await?;
+    audit_log::record_update(
+        &mut tx,
+        admin.id,
+        "architecture",
+        id,
+        &translation_field(locale, before),
+        &translation_field(locale, after),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(StatusCode::OK)
 }
 
-// --- Contribution Management ---
+pub async fn delete_architecture_translation(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path((id, locale)): Path<(i64, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let locale = canonical_locale(&locale).unwrap_or_default();
 
-/// Lists all contributions.
-pub async fn list_contributions(State(pool): State<PgPool>) -> Result<impl IntoResponse, AppError> {
-    let list = sqlx::query_as!(
-        Contribution,
-        "SELECT id, user_id, type, data, status, admin_comment, created_at, reviewed_at FROM contributions ORDER BY created_at ASC"
+    let mut tx = pool.begin().await?;
+    let before = architecture_translation_snapshot(&mut tx, id, locale)
+        .await?
+        .ok_or(AppError::NotFound("Translation not found".to_string()))?;
+    sqlx::query!(
+        "DELETE FROM architecture_translations WHERE architecture_id = $1 AND locale = $2",
+        id,
+        locale
     )
-    .fetch_all(&pool)
+    .execute(&mut *tx)
     .await?;
-    Ok(Json(list))
+
+    audit_log::record_update(
+        &mut tx,
+        admin.id,
+        "architecture",
+        id,
+        &translation_field(locale, Some(before)),
+        &translation_field(locale, None),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
-/// Reviews a contribution (Approve/Reject).
-pub async fn review_contribution(
+/// The translatable fields of an architecture's translation as JSON.
+async fn architecture_translation_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    id: i64,
+    locale: &str,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT jsonb_build_object('name', name, 'dynasty', dynasty, 'location', location, 'description', description)
+            as "snapshot!"
+        FROM architecture_translations
+        WHERE architecture_id = $1 AND locale = $2
+        "#,
+        id,
+        locale
+    )
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+/// A translation as an audit snapshot, keyed `translations.<locale>` so it
+/// diffs as one field of the translated architecture or question.
+fn translation_field(locale: &str, translation: Option<serde_json::Value>) -> serde_json::Value {
+    let mut snapshot = serde_json::Map::new();
+    snapshot.insert(format!("translations.{}", locale), translation.unwrap_or_default());
+    serde_json::Value::Object(snapshot)
+}
+
+/// Pins a discussion post and a visiting-tips post to an architecture page.
+/// Both slots are replaced; pass `null` to unpin one.
+pub async fn set_pinned_posts(
     State(pool): State<PgPool>,
+    admin: AuthUser,
     Path(id): Path<i64>,
-    Json(payload): Json<ReviewContributionRequest>,
+    Json(payload): Json<SetPinnedPostsRequest>,
 ) -> Result<impl IntoResponse, AppError> {
+    if payload.discussion_post_id.is_some() && payload.discussion_post_id == payload.tips_post_id {
+        return Err(AppError::BadRequest(
+            "The same post cannot fill both pinned slots".to_string(),
+        ));
+    }
+
+    for post_id in [payload.discussion_post_id, payload.tips_post_id].into_iter().flatten() {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM posts WHERE id = $1 AND deleted_at IS NULL) as "exists!""#,
+            post_id
+        )
+        .fetch_one(&pool)
+        .await?;
+        if !exists {
+            return Err(AppError::BadRequest(format!("Post {} does not exist", post_id)));
+        }
+    }
+
     let mut tx = pool.begin().await?;
+    let before = architecture_snapshot(&mut tx, id)
+        .await?
+        .ok_or(AppError::NotFound("Architecture not found".to_string()))?;
 
-    let contrib = sqlx::query_as!(
-        Contribution,
-        "SELECT * FROM contributions WHERE id = $1 AND status = 'pending'",
+    sqlx::query!(
+        "UPDATE architectures SET pinned_discussion_post_id = $1, pinned_tips_post_id = $2 WHERE id = $3",
+        payload.discussion_post_id,
+        payload.tips_post_id,
         id
     )
-    .fetch_optional(&mut *tx)
-    .await?
-    .ok_or(AppError::NotFound(
-        "Pending contribution not found".to_string(),
-    ))?;
+    .execute(&mut *tx)
+    .await?;
 
-    if payload.status == "approved" {
-        match contrib.r#type.as_str() {
-            "architecture" => {
-                let data: CreateArchRequest = serde_json::from_value(contrib.data)?;
-                let carousel = serde_json::to_value(data.carousel_imgs).unwrap_or_default();
-                let clean_desc = clean_html(&data.description);
-                sqlx::query!(
-                    "INSERT INTO architectures (category, name, dynasty, location, description, cover_img, carousel_imgs) VALUES ($1, $2, $3, $4, $5, $6, $7)",
-                    data.category, data.name, data.dynasty, data.location, clean_desc, data.cover_img, carousel
-                ).execute(&mut *tx).await?;
-            }
-            "question" => {
-                let data: CreateQuestionRequest = serde_json::from_value(contrib.data)?;
-                let options = serde_json::to_value(data.options).unwrap_or_default();
-                let clean_content = clean_html(&data.content);
-                let clean_answer = clean_html(&data.answer);
-                let clean_analysis = data.analysis.map(|a| clean_html(&a));
-                
-                sqlx::query!(
-                    "INSERT INTO questions (type, content, options, answer, analysis) VALUES ($1, $2, $3, $4, $5)",
-                    data.question_type, clean_content, options, clean_answer, clean_analysis
-                ).execute(&mut *tx).await?;
-            }
-            _ => return Err(AppError::BadRequest("Unknown type".to_string())),
-        }
+    let after = architecture_snapshot(&mut tx, id).await?.unwrap_or_default();
+    audit_log::record_update(&mut tx, admin.id, "architecture", id, &before, &after, None).await?;
+
+    tx.commit().await?;
+    Ok(StatusCode::OK)
+}
+
+pub async fn delete_architecture(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut tx = pool.begin().await?;
+    let before = architecture_snapshot(&mut tx, id)
+        .await?
+        .ok_or(AppError::NotFound("Architecture not found".to_string()))?;
+    sqlx::query!("DELETE FROM architectures WHERE id = $1", id)
+        .execute(&mut *tx)
+        .await?;
+    audit_log::record_delete(&mut tx, admin.id, "architecture", id, &before).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "architecture_deleted",
+        actor_id = admin.id,
+        architecture_id = id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Merges a duplicate architecture into another and deletes it.
+///
+/// Posts mentioning the duplicate mention the remaining one instead, and
+/// lookups of the duplicate's ID redirect there. The audit log keeps the
+/// duplicate's last fields.
+pub async fn merge_architecture(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<MergeArchitectureRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    if payload.keep_id == id {
+        return Err(AppError::BadRequest(
+            "An architecture cannot be merged into itself".to_string(),
+        ));
     }
 
+    let mut tx = pool.begin().await?;
+    let before = architecture_snapshot(&mut tx, id)
+        .await?
+        .ok_or(AppError::NotFound("Architecture not found".to_string()))?;
+    architecture_snapshot(&mut tx, payload.keep_id)
+        .await?
+        .ok_or(AppError::NotFound("Target architecture not found".to_string()))?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO post_references (post_id, entity_type, entity_id)
+        SELECT post_id, 'architecture', $2 FROM post_references
+        WHERE entity_type = 'architecture' AND entity_id = $1
+        ON CONFLICT DO NOTHING
+        "#,
+        id,
+        payload.keep_id
+    )
+    .execute(&mut *tx)
+    .await?;
     sqlx::query!(
-        "UPDATE contributions SET status = $1, admin_comment = $2, reviewed_at = NOW() WHERE id = $3",
-        payload.status, payload.admin_comment, id
+        "DELETE FROM post_references WHERE entity_type = 'architecture' AND entity_id = $1",
+        id
     )
     .execute(&mut *tx)
     .await?;
+    let slug = before.get("slug").and_then(|slug| slug.as_str());
+    redirects::add_architecture(&mut tx, id, slug, payload.keep_id).await?;
+    sqlx::query!("DELETE FROM architectures WHERE id = $1", id)
+        .execute(&mut *tx)
+        .await?;
 
+    let reason = format!("Merged into #{}", payload.keep_id);
+    audit_log::record(
+        &mut tx,
+        admin.id,
+        "merge",
+        "architecture",
+        id,
+        &audit_log::field_diff(&before, &serde_json::Value::Null),
+        Some(&reason),
+    )
+    .await?;
     tx.commit().await?;
-    Ok(StatusCode::OK)
-}
\ No newline at end of file
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "architecture_merged",
+        actor_id = admin.id,
+        architecture_id = id,
+        keep_id = payload.keep_id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// --- Question Management ---
+
+/// Lists questions with their provenance, for auditing crowd-sourced entries.
+pub async fn list_questions(
+    State(pool): State<PgPool>,
+    Query(params): Query<ContentSourceParams>,
+) -> Result<impl IntoResponse, AppError> {
+    params.validate_source()?;
+    let limit = params.limit.unwrap_or(50).clamp(1, 200);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let list = sqlx::query_as!(
+        Question,
+        r#"
+        SELECT
+            id, type as "question_type", content,
+            options as "options: sqlx::types::Json<Vec<String>>",
+            option_images as "option_images: sqlx::types::Json<Vec<Option<String>>>",
+            answer, analysis, created_at, contribution_id, created_by
+        FROM questions
+        WHERE ($1::TEXT IS NULL OR ($1 = 'contribution') = (contribution_id IS NOT NULL))
+          AND ($2::BIGINT IS NULL OR created_by = $2)
+          AND ($3::BIGINT IS NULL OR contribution_id = $3)
+        ORDER BY id DESC
+        LIMIT $4 OFFSET $5
+        "#,
+        params.source,
+        params.created_by,
+        params.contribution_id,
+        limit,
+        offset
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(list))
+}
+
+/// The flag triage queue: questions with open flags, suspended ones first.
+pub async fn list_flagged_questions(
+    State(pool): State<PgPool>,
+) -> Result<impl IntoResponse, AppError> {
+    let flags = sqlx::query_as!(
+        QuestionFlag,
+        r#"
+        SELECT id, question_id, user_id, reason, comment, status, created_at, resolved_at
+        FROM question_flags
+        WHERE status = 'open'
+        ORDER BY created_at
+        "#
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let question_ids: Vec<i64> = flags.iter().map(|f| f.question_id).collect();
+    let questions = sqlx::query!(
+        r#"
+        SELECT id, content, answer, suspended_at
+        FROM questions
+        WHERE id = ANY($1)
+        ORDER BY suspended_at IS NULL, suspended_at, id
+        "#,
+        &question_ids
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let mut by_question: std::collections::HashMap<i64, Vec<QuestionFlag>> =
+        std::collections::HashMap::new();
+    for flag in flags {
+        by_question.entry(flag.question_id).or_default().push(flag);
+    }
+
+    let queue: Vec<FlaggedQuestion> = questions
+        .into_iter()
+        .map(|q| FlaggedQuestion {
+            question_id: q.id,
+            content: q.content,
+            answer: q.answer,
+            suspended_at: q.suspended_at,
+            flags: by_question.remove(&q.id).unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(Json(queue))
+}
+
+/// Closes every open flag on a question and returns it to papers.
+/// Edit the question first (`PUT /questions/{id}`) when the flags were right.
+pub async fn resolve_question_flags(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<ResolveFlagsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut tx = pool.begin().await?;
+
+    let closed = sqlx::query!(
+        r#"
+        UPDATE question_flags SET status = $1, resolved_at = NOW()
+        WHERE question_id = $2 AND status = 'open'
+        "#,
+        payload.status,
+        id
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    if closed == 0 {
+        return Err(AppError::NotFound(
+            "No open flags for this question".to_string(),
+        ));
+    }
+
+    let suspended_at =
+        sqlx::query_scalar!("SELECT suspended_at FROM questions WHERE id = $1 FOR UPDATE", id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .flatten();
+    sqlx::query!("UPDATE questions SET suspended_at = NULL WHERE id = $1", id)
+        .execute(&mut *tx)
+        .await?;
+
+    audit_log::record_update(
+        &mut tx,
+        admin.id,
+        "question",
+        id,
+        &serde_json::json!({ "open_flags": closed, "suspended_at": suspended_at }),
+        &serde_json::json!({ "open_flags": 0, "flags_closed_as": payload.status }),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(Json(serde_json::json!({ "closed": closed })))
+}
+
+pub async fn create_question(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Json(payload): Json<CreateQuestionRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let admin_id = admin.id;
+
+    let images = payload.option_images.unwrap_or_default();
+    if images.len() > payload.options.len() {
+        return Err(AppError::BadRequest(
+            "option_images has more entries than options".to_string(),
+        ));
+    }
+
+    let options_json = serde_json::to_value(payload.options).unwrap_or_default();
+    let images_json = serde_json::to_value(images).unwrap_or_default();
+    
+    let clean_content = clean_html(&payload.content);
+    let clean_answer = clean_html(&payload.answer);
+    let clean_analysis = payload.analysis.as_ref().map(|a| clean_html(a));
+
+    let mut tx = pool.begin().await?;
+    let id = sqlx::query!(
+        "INSERT INTO questions (type, content, options, option_images, answer, analysis, created_by) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
+        payload.question_type, clean_content, options_json, images_json, clean_answer, clean_analysis, admin_id
+    )
+    .fetch_one(&mut *tx)
+    .await?
+    .id;
+
+    let after = question_snapshot(&mut tx, id).await?.unwrap_or_default();
+    audit_log::record_create(&mut tx, admin_id, "question", id, &after).await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(serde_json::json!({"id": id}))))
+}
+
+/// Updates a question and records what changed in the audit log.
+pub async fn update_question(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<UpdateQuestionRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let admin_id = admin.id;
+
+    let mut tx = pool.begin().await?;
+    let before = question_snapshot(&mut tx, id)
+        .await?
+        .ok_or(AppError::NotFound("Question not found".to_string()))?;
+
+    if let Some(images) = &payload.option_images {
+        let option_count = match &payload.options {
+            Some(options) => options.len(),
+            None => before["options"].as_array().map_or(0, Vec::len),
+        };
+        if images.len() > option_count {
+            return Err(AppError::BadRequest(
+                "option_images has more entries than options".to_string(),
+            ));
+        }
+    }
+
+    let mut builder: QueryBuilder<Postgres> = QueryBuilder::new("UPDATE questions SET ");
+    let mut separated = builder.separated(", ");
+
+    if let Some(v) = payload.question_type {
+        separated.push("type = ");
+        separated.push_bind_unseparated(v);
+    }
+    if let Some(v) = payload.content {
+        separated.push("content = ");
+        separated.push_bind_unseparated(clean_html(&v));
+    }
+    if let Some(v) = payload.options {
+        separated.push("options = ");
+        separated.push_bind_unseparated(serde_json::to_value(v).unwrap_or_default());
+    }
+    if let Some(v) = payload.option_images {
+        separated.push("option_images = ");
+        separated.push_bind_unseparated(serde_json::to_value(v).unwrap_or_default());
+    }
+    if let Some(v) = payload.answer {
+        separated.push("answer = ");
+        separated.push_bind_unseparated(clean_html(&v));
+    }
+    if let Some(v) = payload.analysis {
+        separated.push("analysis = ");
+        separated.push_bind_unseparated(clean_html(&v));
+    }
+
+    builder.push(" WHERE id = ");
+    builder.push_bind(id);
+    builder.build().execute(&mut *tx).await?;
+
+    let after = question_snapshot(&mut tx, id).await?.unwrap_or_default();
+    audit_log::record_update(&mut tx, admin_id, "question", id, &before, &after, None).await?;
+
+    tx.commit().await?;
+    Ok(StatusCode::OK)
+}
+
+/// The audited fields of a question as JSON, locking the row.
+/// Sampling bookkeeping and the change timestamp are left out.
+async fn question_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    id: i64,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT to_jsonb(q) - ARRAY['random_key', 'exposure_count', 'updated_at'] as "snapshot!"
+        FROM questions q
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+pub async fn delete_question(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut tx = pool.begin().await?;
+    let before = question_snapshot(&mut tx, id)
+        .await?
+        .ok_or(AppError::NotFound("Question not found".to_string()))?;
+    sqlx::query!("DELETE FROM questions WHERE id = $1", id)
+        .execute(&mut *tx)
+        .await?;
+    audit_log::record_delete(&mut tx, admin.id, "question", id, &before).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "question_deleted",
+        actor_id = admin.id,
+        question_id = id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Lists a question's translations.
+pub async fn list_question_translations(
+    State(pool): State<PgPool>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let exists = sqlx::query!("SELECT id FROM questions WHERE id = $1", id)
+        .fetch_optional(&pool)
+        .await?;
+    if exists.is_none() {
+        return Err(AppError::NotFound("Question not found".to_string()));
+    }
+
+    let translations = sqlx::query_as!(
+        QuestionTranslation,
+        r#"
+        SELECT locale, content, options as "options: sqlx::types::Json<Vec<String>>",
+               analysis, updated_at
+        FROM question_translations
+        WHERE question_id = $1
+        ORDER BY locale
+        "#,
+        id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(translations))
+}
+
+/// Adds or replaces the translation of a question into `locale`.
+pub async fn upsert_question_translation(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path((id, locale)): Path<(i64, String)>,
+    Json(payload): Json<UpsertQuestionTranslationRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let locale = canonical_locale(&locale)
+        .filter(|l| *l != DEFAULT_LOCALE)
+        .ok_or_else(|| AppError::BadRequest(format!("Cannot translate into '{}'", locale)))?;
+
+    let mut tx = pool.begin().await?;
+    let source_options = sqlx::query_scalar!(
+        r#"SELECT jsonb_array_length(options) as "count!" FROM questions WHERE id = $1 FOR UPDATE"#,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound("Question not found".to_string()))?;
+    if let Some(options) = &payload.options
+        && options.len() != source_options as usize
+    {
+        return Err(AppError::BadRequest(format!(
+            "The question has {} options; a translation must have the same",
+            source_options
+        )));
+    }
+    let before = question_translation_snapshot(&mut tx, id, locale).await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO question_translations (question_id, locale, content, options, analysis)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (question_id, locale) DO UPDATE SET
+            content = EXCLUDED.content,
+            options = EXCLUDED.options,
+            analysis = EXCLUDED.analysis,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        id,
+        locale,
+        payload.content.as_deref().map(clean_html),
+        payload.options.map(|v| serde_json::to_value(v).unwrap_or_default()),
+        payload.analysis.as_deref().map(clean_html)
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let after = question_translation_snapshot(&mut tx, id, locale).await?;
+    audit_log::record_update(
+        &mut tx,
+        admin.id,
+        "question",
+        id,
+        &translation_field(locale, before),
+        &translation_field(locale, after),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(StatusCode::OK)
+}
+
+pub async fn delete_question_translation(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path((id, locale)): Path<(i64, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let locale = canonical_locale(&locale).unwrap_or_default();
+
+    let mut tx = pool.begin().await?;
+    let before = question_translation_snapshot(&mut tx, id, locale)
+        .await?
+        .ok_or(AppError::NotFound("Translation not found".to_string()))?;
+    sqlx::query!(
+        "DELETE FROM question_translations WHERE question_id = $1 AND locale = $2",
+        id,
+        locale
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    audit_log::record_update(
+        &mut tx,
+        admin.id,
+        "question",
+        id,
+        &translation_field(locale, Some(before)),
+        &translation_field(locale, None),
+        None,
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The translatable fields of a question's translation as JSON.
+async fn question_translation_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    id: i64,
+    locale: &str,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT jsonb_build_object('content', content, 'options', options, 'analysis', analysis)
+            as "snapshot!"
+        FROM question_translations
+        WHERE question_id = $1 AND locale = $2
+        "#,
+        id,
+        locale
+    )
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+/// Clusters of near-identical questions, for merging or deleting in bulk.
+pub async fn list_duplicate_questions(
+    State(pool): State<PgPool>,
+    Query(params): Query<DuplicateQuestionParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let threshold = params.threshold.unwrap_or(QUESTION_DUPLICATE_THRESHOLD);
+    if !(0.3..=1.0).contains(&threshold) {
+        return Err(AppError::BadRequest(
+            "threshold must be between 0.3 and 1".to_string(),
+        ));
+    }
+
+    let clusters = question_duplicates::find_clusters(&pool, threshold).await?;
+    Ok(Json(clusters))
+}
+
+/// Merges each group of duplicates into the question that is kept.
+/// All merges run in one transaction: if any ID is missing, nothing changes.
+/// With `?dry_run=true` the merges are rolled back after counting.
+pub async fn merge_duplicate_questions(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Query(params): Query<DryRunParams>,
+    Json(payload): Json<MergeQuestionsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut ids: Vec<i64> = Vec::new();
+    for merge in &payload.merges {
+        ids.push(merge.keep_id);
+        ids.extend(&merge.duplicate_ids);
+    }
+    let mut unique = ids.clone();
+    unique.sort_unstable();
+    unique.dedup();
+    if unique.len() != ids.len() {
+        return Err(AppError::BadRequest(
+            "A question can appear only once across all merges".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    let found = sqlx::query_scalar!(
+        "SELECT id FROM questions WHERE id = ANY($1) ORDER BY id FOR UPDATE",
+        &unique
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    if found.len() != unique.len() {
+        let missing: Vec<String> = unique
+            .iter()
+            .filter(|id| !found.contains(id))
+            .map(|id| id.to_string())
+            .collect();
+        return Err(AppError::NotFound(format!(
+            "Questions not found: {}",
+            missing.join(", ")
+        )));
+    }
+
+    let mut merged = 0;
+    for merge in &payload.merges {
+        let mut deleted = Vec::with_capacity(merge.duplicate_ids.len());
+        for &duplicate_id in &merge.duplicate_ids {
+            deleted.push(question_snapshot(&mut tx, duplicate_id).await?.unwrap_or_default());
+        }
+        question_duplicates::merge(&mut tx, merge.keep_id, &merge.duplicate_ids).await?;
+        merged += merge.duplicate_ids.len();
+
+        let changes = audit_log::field_diff(
+            &serde_json::json!({}),
+            &serde_json::json!({ "merged_ids": merge.duplicate_ids }),
+        );
+        audit_log::record(&mut tx, admin.id, "merge", "question", merge.keep_id, &changes, None)
+            .await?;
+        for (&duplicate_id, before) in merge.duplicate_ids.iter().zip(&deleted) {
+            audit_log::record_delete(&mut tx, admin.id, "question", duplicate_id, before).await?;
+        }
+    }
+
+    if params.dry_run {
+        tx.rollback().await?;
+        return Ok(Json(serde_json::json!({ "merged": merged, "dry_run": true })));
+    }
+    tx.commit().await?;
+    for merge in &payload.merges {
+        tracing::info!(
+            target: audit_stream::TARGET,
+            action = "questions_merged",
+            actor_id = admin.id,
+            question_id = merge.keep_id,
+            deleted_ids = ?merge.duplicate_ids
+        );
+    }
+    Ok(Json(serde_json::json!({ "merged": merged, "dry_run": false })))
+}
+
+/// Deletes several questions at once. Unknown IDs are ignored.
+/// With `?dry_run=true` the deletion is rolled back after counting.
+pub async fn delete_questions(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Query(params): Query<DryRunParams>,
+    Json(payload): Json<DeleteQuestionsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut tx = pool.begin().await?;
+    let rows = sqlx::query!(
+        r#"
+        DELETE FROM questions q WHERE id = ANY($1)
+        RETURNING id, to_jsonb(q) - ARRAY['random_key', 'exposure_count', 'updated_at'] as "snapshot!"
+        "#,
+        &payload.ids
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let deleted: Vec<i64> = rows.iter().map(|row| row.id).collect();
+    if params.dry_run {
+        tx.rollback().await?;
+        return Ok(Json(serde_json::json!({ "deleted": deleted.len(), "dry_run": true })));
+    }
+    for row in &rows {
+        audit_log::record_delete(&mut tx, admin.id, "question", row.id, &row.snapshot).await?;
+    }
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "questions_deleted",
+        actor_id = admin.id,
+        question_ids = ?deleted
+    );
+    let deleted = deleted.len();
+
+    Ok(Json(serde_json::json!({ "deleted": deleted, "dry_run": false })))
+}
+
+// --- Contribution Management ---
+
+/// Lists all contributions.
+pub async fn list_contributions(State(pool): State<PgPool>) -> Result<impl IntoResponse, AppError> {
+    let list = sqlx::query_as!(
+        Contribution,
+        r#"
+        SELECT id, user_id, type, data, status, auto_approved,
+            (SELECT COUNT(*) FROM contribution_messages m WHERE m.contribution_id = contributions.id) as "message_count!",
+            created_at, reviewed_at
+        FROM contributions
+        ORDER BY created_at ASC
+        "#
+    )
+    .fetch_all(&pool)
+    .await?;
+    Ok(Json(list))
+}
+
+/// Question contributions can only be reviewed by admins who manage questions.
+fn check_can_review(admin: &AuthUser, r#type: &str) -> Result<(), AppError> {
+    if r#type == "question" && !admin.can(Permission::ManageQuestions) {
+        return Err(AppError::AuthError(
+            "You are not authorized to review question contributions".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Reference files attached to a contribution, with short-lived links to open them.
+pub async fn list_contribution_attachments(
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn Storage>>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let r#type = sqlx::query_scalar!("SELECT type FROM contributions WHERE id = $1", id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(AppError::NotFound("Contribution not found".to_string()))?;
+    check_can_review(&admin, &r#type)?;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, storage_key, filename, content_type, size_bytes, created_at
+        FROM contribution_attachments
+        WHERE contribution_id = $1
+        ORDER BY id
+        "#,
+        id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let mut attachments = Vec::with_capacity(rows.len());
+    for row in rows {
+        attachments.push(ContributionAttachment {
+            download_url: Some(
+                storage
+                    .signed_url(
+                        &row.storage_key,
+                        Duration::from_secs(CONTRIBUTION_ATTACHMENT_URL_SECS),
+                    )
+                    .await?,
+            ),
+            id: row.id,
+            filename: row.filename,
+            content_type: row.content_type,
+            size_bytes: row.size_bytes,
+            created_at: row.created_at,
+        });
+    }
+
+    Ok(Json(attachments))
+}
+
+/// The review thread of a contribution, oldest first.
+pub async fn list_contribution_messages(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let r#type = sqlx::query_scalar!("SELECT type FROM contributions WHERE id = $1", id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(AppError::NotFound("Contribution not found".to_string()))?;
+    check_can_review(&admin, &r#type)?;
+
+    Ok(Json(contribution_messages::list(&pool, id).await?))
+}
+
+/// Asks the contributor of a pending contribution something, leaving it
+/// pending. The contributor is notified.
+pub async fn post_contribution_message(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<CreateContributionMessageRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut tx = pool.begin().await?;
+    let contrib = sqlx::query!(
+        "SELECT user_id, type, status FROM contributions WHERE id = $1 FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound("Contribution not found".to_string()))?;
+    check_can_review(&admin, &contrib.r#type)?;
+    if contrib.status != "pending" {
+        return Err(AppError::BadRequest(
+            "Messages can only be added before review".to_string(),
+        ));
+    }
+
+    let message =
+        contribution_messages::post(&mut tx, id, contrib.user_id, admin.id, true, &payload.body)
+            .await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(message)))
+}
+
+/// Reviews a contribution (Approve/Reject) and notifies the contributor. A
+/// comment given with the review is added to the thread as the reviewer's last
+/// message.
+pub async fn review_contribution(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<ReviewContributionRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let comment = payload
+        .admin_comment
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+    if let Some(comment) = comment {
+        text_length::contribution_message(comment)
+            .map_err(|e| AppError::BadRequest(format!("admin_comment: {}", e)))?;
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let contrib = sqlx::query_as!(
+        Contribution,
+        r#"
+        SELECT id, user_id, type, data, status, auto_approved,
+            (SELECT COUNT(*) FROM contribution_messages m WHERE m.contribution_id = contributions.id) as "message_count!",
+            created_at, reviewed_at
+        FROM contributions
+        WHERE id = $1 AND status = 'pending'
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound(
+        "Pending contribution not found".to_string(),
+    ))?;
+
+    check_can_review(&admin, &contrib.r#type)?;
+
+    let mut new_cover = None;
+    if payload.status == "approved" {
+        match contribution_review::publish(&mut tx, &contrib).await? {
+            Published::Architecture { id: arch_id, cover_img } => {
+                let after = architecture_snapshot(&mut tx, arch_id).await?.unwrap_or_default();
+                audit_log::record_create(&mut tx, admin.id, "architecture", arch_id, &after).await?;
+                new_cover = Some((arch_id, cover_img));
+            }
+            Published::Question { id: question_id } => {
+                let after = question_snapshot(&mut tx, question_id).await?.unwrap_or_default();
+                audit_log::record_create(&mut tx, admin.id, "question", question_id, &after).await?;
+            }
+        }
+    }
+
+    sqlx::query!(
+        "UPDATE contributions SET status = $1, reviewed_at = NOW(), reviewed_by = $2 WHERE id = $3",
+        payload.status, admin.id, id
+    )
+    .execute(&mut *tx)
+    .await?;
+    notifications::notify(
+        &mut *tx,
+        contrib.user_id,
+        notifications::CONTRIBUTION_REVIEWED,
+        serde_json::json!({"contribution_id": id, "status": payload.status}),
+    )
+    .await?;
+    if let Some(comment) = comment {
+        contribution_messages::post(&mut tx, id, contrib.user_id, admin.id, true, comment).await?;
+    }
+
+    let changes = audit_log::field_diff(
+        &serde_json::json!({ "status": contrib.status }),
+        &serde_json::json!({ "status": payload.status, "admin_comment": comment }),
+    );
+    audit_log::record(&mut tx, admin.id, "review", "contribution", id, &changes, None).await?;
+
+    tx.commit().await?;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "contribution_reviewed",
+        actor_id = admin.id,
+        contribution_id = id,
+        contributor_id = contrib.user_id,
+        status = payload.status
+    );
+    if let Some((arch_id, cover_img)) = new_cover {
+        image_placeholders::queue_cover(&pool, arch_id, cover_img).await;
+    }
+    Ok(StatusCode::OK)
+}
+
+/// Auto-approved contributions no admin has checked yet, oldest first.
+pub async fn list_unaudited_contributions(
+    State(pool): State<PgPool>,
+) -> Result<impl IntoResponse, AppError> {
+    let list = sqlx::query_as!(
+        UnauditedContribution,
+        r#"
+        SELECT c.id, c.user_id, u.username, c.type, c.data, c.created_at,
+            COALESCE(
+                (SELECT a.id FROM architectures a WHERE a.contribution_id = c.id),
+                (SELECT q.id FROM questions q WHERE q.contribution_id = c.id)
+            ) as published_id
+        FROM contributions c
+        JOIN users u ON u.id = c.user_id
+        WHERE c.auto_approved AND c.audited_at IS NULL
+        ORDER BY c.created_at ASC
+        "#
+    )
+    .fetch_all(&pool)
+    .await?;
+    Ok(Json(list))
+}
+
+/// Locks an auto-approved contribution that has not been checked yet.
+async fn lock_unaudited(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    admin: &AuthUser,
+    id: i64,
+) -> Result<(i64, String), AppError> {
+    let contrib = sqlx::query!(
+        r#"
+        SELECT user_id, type FROM contributions
+        WHERE id = $1 AND auto_approved AND audited_at IS NULL
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or(AppError::NotFound(
+        "Unaudited contribution not found".to_string(),
+    ))?;
+    check_can_review(admin, &contrib.r#type)?;
+    Ok((contrib.user_id, contrib.r#type))
+}
+
+/// Confirms an auto-approved contribution, which then counts towards its
+/// contributor's trust.
+pub async fn confirm_contribution(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut tx = pool.begin().await?;
+    lock_unaudited(&mut tx, &admin, id).await?;
+
+    sqlx::query!(
+        "UPDATE contributions SET audited_by = $1, audited_at = NOW() WHERE id = $2",
+        admin.id,
+        id
+    )
+    .execute(&mut *tx)
+    .await?;
+    audit_log::record(&mut tx, admin.id, "confirm", "contribution", id, &[], None).await?;
+    tx.commit().await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// Rolls back an auto-approved contribution that has not been checked yet,
+/// like `revert_contribution`.
+pub async fn rollback_contribution(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<RevertContributionRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut tx = pool.begin().await?;
+    let (contributor_id, r#type) = lock_unaudited(&mut tx, &admin, id).await?;
+    unpublish(&mut tx, &admin, id, contributor_id, &r#type, "rollback", payload.reason.as_deref())
+        .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "contribution_rolled_back",
+        actor_id = admin.id,
+        contribution_id = id,
+        contributor_id
+    );
+    Ok(StatusCode::OK)
+}
+
+/// Reverts an approved contribution whose approval turned out to be wrong:
+/// deletes the architecture or question it published, if still there, and
+/// marks it rejected, notifying the contributor. A reason is added to the
+/// review thread. The audit log keeps the deleted entry with its edit history.
+pub async fn revert_contribution(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<RevertContributionRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut tx = pool.begin().await?;
+    let contrib = sqlx::query!(
+        "SELECT user_id, type FROM contributions WHERE id = $1 AND status = 'approved' FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound(
+        "Approved contribution not found".to_string(),
+    ))?;
+    check_can_review(&admin, &contrib.r#type)?;
+    unpublish(
+        &mut tx,
+        &admin,
+        id,
+        contrib.user_id,
+        &contrib.r#type,
+        "revert",
+        payload.reason.as_deref(),
+    )
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "contribution_reverted",
+        actor_id = admin.id,
+        contribution_id = id,
+        contributor_id = contrib.user_id
+    );
+    Ok(StatusCode::OK)
+}
+
+/// Deletes what an approved contribution published and marks it rejected, as
+/// audit `action`. An auto-approved contribution counts as checked afterwards.
+async fn unpublish(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    admin: &AuthUser,
+    id: i64,
+    contributor_id: i64,
+    r#type: &str,
+    action: &str,
+    reason: Option<&str>,
+) -> Result<(), AppError> {
+    if r#type == "architecture" {
+        let arch_id =
+            sqlx::query_scalar!("SELECT id FROM architectures WHERE contribution_id = $1", id)
+                .fetch_optional(&mut **tx)
+                .await?;
+        if let Some(arch_id) = arch_id {
+            let before = architecture_snapshot(tx, arch_id).await?.unwrap_or_default();
+            sqlx::query!("DELETE FROM architectures WHERE id = $1", arch_id)
+                .execute(&mut **tx)
+                .await?;
+            audit_log::record_delete(tx, admin.id, "architecture", arch_id, &before).await?;
+        }
+    } else {
+        exam_events::ensure_bank_unlocked(&mut **tx).await?;
+        let question_id =
+            sqlx::query_scalar!("SELECT id FROM questions WHERE contribution_id = $1", id)
+                .fetch_optional(&mut **tx)
+                .await?;
+        if let Some(question_id) = question_id {
+            let before = question_snapshot(tx, question_id).await?.unwrap_or_default();
+            sqlx::query!("DELETE FROM questions WHERE id = $1", question_id)
+                .execute(&mut **tx)
+                .await?;
+            audit_log::record_delete(tx, admin.id, "question", question_id, &before).await?;
+        }
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE contributions
+        SET status = 'rejected',
+            audited_by = CASE WHEN auto_approved AND audited_at IS NULL THEN $1 ELSE audited_by END,
+            audited_at = CASE WHEN auto_approved THEN COALESCE(audited_at, NOW()) END
+        WHERE id = $2
+        "#,
+        admin.id,
+        id
+    )
+    .execute(&mut **tx)
+    .await?;
+    notifications::notify(
+        &mut **tx,
+        contributor_id,
+        notifications::CONTRIBUTION_REVIEWED,
+        serde_json::json!({"contribution_id": id, "status": "rejected"}),
+    )
+    .await?;
+    if let Some(reason) = reason {
+        contribution_messages::post(tx, id, contributor_id, admin.id, true, reason).await?;
+    }
+    let changes = audit_log::field_diff(
+        &serde_json::json!({ "status": "approved" }),
+        &serde_json::json!({ "status": "rejected" }),
+    );
+    audit_log::record(tx, admin.id, action, "contribution", id, &changes, reason).await?;
+    Ok(())
+}
+
+/// Time window for the review metrics.
+#[derive(Debug, Deserialize)]
+pub struct ReviewMetricsParams {
+    /// '7d', '30d' (default) or '90d'. Applies to reviews, not to the backlog.
+    pub window: Option<String>,
+}
+
+/// How quickly contributions get reviewed: review times and approval rates
+/// per admin over the window, and how old the pending backlog is.
+pub async fn contribution_metrics(
+    State(pool): State<PgPool>,
+    Query(params): Query<ReviewMetricsParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let window = params.window.unwrap_or_else(|| "30d".to_string());
+    let duration = match window.as_str() {
+        "7d" => chrono::Duration::days(7),
+        "30d" => chrono::Duration::days(30),
+        "90d" => chrono::Duration::days(90),
+        _ => {
+            return Err(AppError::BadRequest(
+                "window must be one of '7d', '30d', '90d'".to_string(),
+            ));
+        }
+    };
+    let since = chrono::Utc::now() - duration;
+
+    let reviews = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) as "reviewed!",
+            PERCENTILE_CONT(0.5) WITHIN GROUP (
+                ORDER BY EXTRACT(EPOCH FROM reviewed_at - created_at)::FLOAT8
+            ) as median_review_secs,
+            PERCENTILE_CONT(0.9) WITHIN GROUP (
+                ORDER BY EXTRACT(EPOCH FROM reviewed_at - created_at)::FLOAT8
+            ) as p90_review_secs
+        FROM contributions
+        WHERE reviewed_at >= $1 AND NOT auto_approved
+        "#,
+        since
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    let reviewers = sqlx::query!(
+        r#"
+        SELECT
+            c.reviewed_by as admin_id,
+            u.username as "admin_username?",
+            COUNT(*) as "reviewed!",
+            COUNT(*) FILTER (WHERE c.status = 'approved') as "approved!",
+            PERCENTILE_CONT(0.5) WITHIN GROUP (
+                ORDER BY EXTRACT(EPOCH FROM c.reviewed_at - c.created_at)::FLOAT8
+            ) as median_review_secs
+        FROM contributions c
+        LEFT JOIN users u ON u.id = c.reviewed_by
+        WHERE c.reviewed_at >= $1 AND NOT c.auto_approved
+        GROUP BY c.reviewed_by, u.username
+        ORDER BY COUNT(*) DESC, c.reviewed_by
+        "#,
+        since
+    )
+    .fetch_all(&pool)
+    .await?
+    .into_iter()
+    .map(|r| ReviewerMetrics {
+        admin_id: r.admin_id,
+        admin_username: r.admin_username,
+        reviewed: r.reviewed,
+        approved: r.approved,
+        approval_rate: (1000.0 * r.approved as f64 / r.reviewed as f64).round() / 10.0,
+        median_review_secs: r.median_review_secs,
+    })
+    .collect();
+
+    let backlog = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) as "pending!",
+            COUNT(*) FILTER (WHERE created_at > NOW() - INTERVAL '1 day') as "under_1d!",
+            COUNT(*) FILTER (
+                WHERE created_at <= NOW() - INTERVAL '1 day' AND created_at > NOW() - INTERVAL '3 days'
+            ) as "from_1d_to_3d!",
+            COUNT(*) FILTER (
+                WHERE created_at <= NOW() - INTERVAL '3 days' AND created_at > NOW() - INTERVAL '7 days'
+            ) as "from_3d_to_7d!",
+            COUNT(*) FILTER (WHERE created_at <= NOW() - INTERVAL '7 days') as "over_7d!",
+            MIN(created_at) as oldest_created_at
+        FROM contributions
+        WHERE status = 'pending'
+        "#
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(ContributionMetrics {
+        window,
+        reviewed: reviews.reviewed,
+        median_review_secs: reviews.median_review_secs,
+        p90_review_secs: reviews.p90_review_secs,
+        reviewers,
+        backlog: BacklogAge {
+            pending: backlog.pending,
+            oldest_created_at: backlog.oldest_created_at,
+            buckets: vec![
+                AgeBucket { label: "<1d", count: backlog.under_1d },
+                AgeBucket { label: "1-3d", count: backlog.from_1d_to_3d },
+                AgeBucket { label: "3-7d", count: backlog.from_3d_to_7d },
+                AgeBucket { label: ">7d", count: backlog.over_7d },
+            ],
+        },
+    }))
+}
+
+// --- Glossary Management ---
+
+/// The slug for a glossary term, cut to fit its column.
+fn glossary_slug(text: &str) -> Result<String, AppError> {
+    let slug = slugify(text);
+    let slug = slug[..slug.len().min(GLOSSARY_SLUG_MAX_LEN)].trim_end_matches('-');
+    if slug.is_empty() {
+        return Err(AppError::BadRequest("Could not derive a slug from the term".to_string()));
+    }
+    Ok(slug.to_string())
+}
+
+pub async fn create_glossary_term(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Json(payload): Json<CreateGlossaryTermRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let slug = glossary_slug(payload.slug.as_deref().unwrap_or(&payload.term))?;
+    let keys = pinyin_keys(&payload.term);
+
+    let mut tx = pool.begin().await?;
+    let id = sqlx::query!(
+        r#"
+        INSERT INTO glossary_terms (slug, term, definition, term_pinyin, term_initials)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+        slug,
+        payload.term,
+        clean_html(&payload.definition),
+        keys.full,
+        keys.initials
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        if e.to_string().contains("glossary_terms_slug_key") {
+            AppError::Conflict(format!("Slug '{}' is already in use", slug))
+        } else {
+            AppError::InternalServerError(e.to_string())
+        }
+    })?
+    .id;
+
+    let after = glossary_snapshot(&mut tx, id).await?.unwrap_or_default();
+    audit_log::record_create(&mut tx, admin.id, "glossary_term", id, &after).await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(serde_json::json!({"id": id, "slug": slug}))))
+}
+
+pub async fn update_glossary_term(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<UpdateGlossaryTermRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut tx = pool.begin().await?;
+    let before = glossary_snapshot(&mut tx, id)
+        .await?
+        .ok_or(AppError::NotFound("Glossary term not found".to_string()))?;
+
+    let mut builder: QueryBuilder<Postgres> =
+        QueryBuilder::new("UPDATE glossary_terms SET updated_at = NOW()");
+
+    if let Some(v) = payload.slug {
+        builder.push(", slug = ");
+        builder.push_bind(glossary_slug(&v)?);
+    }
+    if let Some(v) = payload.term {
+        let keys = pinyin_keys(&v);
+        builder.push(", term = ");
+        builder.push_bind(v);
+        builder.push(", term_pinyin = ");
+        builder.push_bind(keys.full);
+        builder.push(", term_initials = ");
+        builder.push_bind(keys.initials);
+    }
+    if let Some(v) = payload.definition {
+        builder.push(", definition = ");
+        builder.push_bind(clean_html(&v));
+    }
+
+    builder.push(" WHERE id = ");
+    builder.push_bind(id);
+
+    builder.build().execute(&mut *tx).await.map_err(|e| {
+        if e.to_string().contains("glossary_terms_slug_key") {
+            AppError::Conflict("Slug is already in use".to_string())
+        } else {
+            AppError::InternalServerError(e.to_string())
+        }
+    })?;
+
+    let after = glossary_snapshot(&mut tx, id).await?.unwrap_or_default();
+    audit_log::record_update(&mut tx, admin.id, "glossary_term", id, &before, &after, None)
+        .await?;
+
+    tx.commit().await?;
+    Ok(StatusCode::OK)
+}
+
+pub async fn delete_glossary_term(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut tx = pool.begin().await?;
+    let before = glossary_snapshot(&mut tx, id)
+        .await?
+        .ok_or(AppError::NotFound("Glossary term not found".to_string()))?;
+    sqlx::query!("DELETE FROM glossary_terms WHERE id = $1", id)
+        .execute(&mut *tx)
+        .await?;
+    audit_log::record_delete(&mut tx, admin.id, "glossary_term", id, &before).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "glossary_term_deleted",
+        actor_id = admin.id,
+        term_id = id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The audited fields of a glossary term as JSON, locking the row.
+/// Search keys derived from the term and the change timestamp are left out.
+async fn glossary_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    id: i64,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT to_jsonb(g) - ARRAY['term_pinyin', 'term_initials', 'updated_at'] as "snapshot!"
+        FROM glossary_terms g
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+// --- Abuse Monitoring ---
+
+/// Summarizes rate-limit hits, failed logins, reports and suspected spam over a window,
+/// with the IPs and users behind most of them.
+pub async fn abuse_summary(
+    State(pool): State<PgPool>,
+    Query(params): Query<AbuseParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let window = params.window.unwrap_or_else(|| "24h".to_string());
+    let duration = match window.as_str() {
+        "1h" => chrono::Duration::hours(1),
+        "24h" => chrono::Duration::hours(24),
+        "7d" => chrono::Duration::days(7),
+        "30d" => chrono::Duration::days(30),
+        _ => {
+            return Err(AppError::BadRequest(
+                "window must be one of '1h', '24h', '7d', '30d'".to_string(),
+            ));
+        }
+    };
+    let since = chrono::Utc::now() - duration;
+    let limit = params.limit.unwrap_or(10).clamp(1, 100);
+
+    let counts = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE kind = $2) as "rate_limit_hits!",
+            COUNT(*) FILTER (WHERE kind = $3) as "failed_logins!",
+            COUNT(*) FILTER (WHERE kind = $4) as "reports!",
+            COUNT(*) FILTER (WHERE kind = $5) as "spam_suspected!"
+        FROM security_events
+        WHERE created_at >= $1
+        "#,
+        since,
+        security_events::RATE_LIMITED,
+        security_events::LOGIN_FAILED,
+        security_events::CONTENT_REPORTED,
+        security_events::SPAM_SUSPECTED
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    // Successful sign-ins and other account events are not abuse.
+    let abuse_kinds: Vec<String> =
+        security_events::ABUSE_KINDS.iter().map(|k| k.to_string()).collect();
+    let top_ips = sqlx::query_as!(
+        Offender,
+        r#"
+        SELECT ip, NULL::BIGINT as user_id, NULL::TEXT as username,
+               COUNT(*) as "events!", MAX(created_at) as "last_seen!"
+        FROM security_events
+        WHERE created_at >= $1 AND ip IS NOT NULL AND kind = ANY($3)
+        GROUP BY ip
+        ORDER BY 4 DESC, 5 DESC
+        LIMIT $2
+        "#,
+        since,
+        limit,
+        &abuse_kinds
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let top_users = sqlx::query_as!(
+        Offender,
+        r#"
+        SELECT NULL::TEXT as ip, e.user_id, u.username as "username?",
+               COUNT(*) as "events!", MAX(e.created_at) as "last_seen!"
+        FROM security_events e
+        JOIN users u ON u.id = e.user_id
+        WHERE e.created_at >= $1 AND e.kind = ANY($3)
+        GROUP BY e.user_id, u.username
+        ORDER BY 4 DESC, 5 DESC
+        LIMIT $2
+        "#,
+        since,
+        limit,
+        &abuse_kinds
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(AbuseSummary {
+        window,
+        since,
+        rate_limit_hits: counts.rate_limit_hits,
+        failed_logins: counts.failed_logins,
+        reports: counts.reports,
+        spam_suspected: counts.spam_suspected,
+        top_ips,
+        top_users,
+    }))
+}
+
+/// Lists the users counted by `GET /api/stats/online`, most recently active first.
+pub async fn list_online_users(
+    State(pool): State<PgPool>,
+    State(presence): State<Presence>,
+) -> Result<impl IntoResponse, AppError> {
+    let online = presence.online(std::time::Duration::from_secs(ONLINE_WINDOW_SECS));
+    let ids: Vec<i64> = online.iter().map(|(id, _)| *id).collect();
+
+    let names: std::collections::HashMap<i64, String> = sqlx::query!(
+        "SELECT id, username FROM users WHERE id = ANY($1)",
+        &ids
+    )
+    .fetch_all(&pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.id, row.username))
+    .collect();
+
+    // Users deleted since their last request are skipped.
+    let users: Vec<OnlineUser> = online
+        .into_iter()
+        .filter_map(|(id, ago)| {
+            names.get(&id).map(|username| OnlineUser {
+                id,
+                username: username.clone(),
+                last_seen_secs_ago: ago.as_secs(),
+            })
+        })
+        .collect();
+
+    Ok(Json(AdminOnlineStats {
+        online_users: users.len(),
+        window_secs: ONLINE_WINDOW_SECS,
+        users,
+    }))
+}
+
+/// Users who made far more API requests on a day than they usually do:
+/// candidates for a closer look, and later for fair-use limits.
+pub async fn usage_anomalies(
+    State(pool): State<PgPool>,
+    Query(params): Query<UsageAnomalyParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let date = params.date.unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let anomalies = api_usage::anomalies(&pool, date, limit).await?;
+
+    Ok(Json(UsageAnomalies {
+        date,
+        baseline_days: USAGE_ANOMALY_BASELINE_DAYS,
+        anomalies,
+    }))
+}
+
+// --- Experiments ---
+
+/// Lists running experiments with the number of exposed users per variant.
+pub async fn list_experiments(State(pool): State<PgPool>) -> Result<impl IntoResponse, AppError> {
+    let counts: std::collections::HashMap<(String, String), i64> = sqlx::query!(
+        r#"
+        SELECT experiment_key, variant, COUNT(*) as "users!"
+        FROM experiment_exposures
+        GROUP BY experiment_key, variant
+        "#
+    )
+    .fetch_all(&pool)
+    .await?
+    .into_iter()
+    .map(|row| ((row.experiment_key, row.variant), row.users))
+    .collect();
+
+    let summaries: Vec<ExperimentSummary> = experiments::EXPERIMENTS
+        .iter()
+        .map(|e| ExperimentSummary {
+            key: e.key.to_string(),
+            variants: e
+                .variants
+                .iter()
+                .map(|v| VariantExposures {
+                    variant: v.to_string(),
+                    users: counts
+                        .get(&(e.key.to_string(), v.to_string()))
+                        .copied()
+                        .unwrap_or(0),
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(Json(summaries))
+}
+
+// --- Audit Log ---
+
+/// Filters and pagination for the audit log.
+#[derive(Debug, Deserialize)]
+pub struct AuditLogParams {
+    pub admin_id: Option<i64>,
+    pub action: Option<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<i64>,
+    /// Entries at or after this time.
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Entries before this time.
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Page size (default 50, max 200).
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Lists admin actions, newest first.
+pub async fn list_audit_logs(
+    State(pool): State<PgPool>,
+    Query(params): Query<AuditLogParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 200);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let entries = sqlx::query_as!(
+        AuditLogEntry,
+        r#"
+        SELECT
+            l.id, l.admin_id, u.username as "admin_username?", l.action, l.entity_type, l.entity_id,
+            ARRAY(SELECT c->>'field' FROM jsonb_array_elements(l.diff) c) as "fields!",
+            l.reason, l.created_at
+        FROM audit_logs l
+        LEFT JOIN users u ON u.id = l.admin_id
+        WHERE ($1::BIGINT IS NULL OR l.admin_id = $1)
+          AND ($2::TEXT IS NULL OR l.action = $2)
+          AND ($3::TEXT IS NULL OR l.entity_type = $3)
+          AND ($4::BIGINT IS NULL OR l.entity_id = $4)
+          AND ($5::TIMESTAMPTZ IS NULL OR l.created_at >= $5)
+          AND ($6::TIMESTAMPTZ IS NULL OR l.created_at < $6)
+        ORDER BY l.created_at DESC, l.id DESC
+        LIMIT $7 OFFSET $8
+        "#,
+        params.admin_id,
+        params.action,
+        params.entity_type,
+        params.entity_id,
+        params.since,
+        params.until,
+        limit,
+        offset
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(entries))
+}
+
+/// Shows the field-level changes of one audited action.
+pub async fn get_audit_diff(
+    State(pool): State<PgPool>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT l.id, l.admin_id, u.username as "admin_username?", l.action, l.entity_type,
+               l.entity_id, l.diff, l.reason, l.created_at
+        FROM audit_logs l
+        LEFT JOIN users u ON u.id = l.admin_id
+        WHERE l.id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Audit log entry not found".to_string()))?;
+
+    let changes: Vec<FieldChange> = serde_json::from_value(row.diff)
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+    Ok(Json(AuditDiff {
+        id: row.id,
+        admin_id: row.admin_id,
+        admin_username: row.admin_username,
+        action: row.action,
+        entity_type: row.entity_type,
+        entity_id: row.entity_id,
+        reason: row.reason,
+        created_at: row.created_at,
+        changes,
+    }))
+}
+
+// --- Terms of Service ---
+
+/// Publishes a new terms version. It becomes current immediately, so users who
+/// have not accepted it can no longer post until they do.
+pub async fn publish_terms(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Json(payload): Json<PublishTermsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut tx = pool.begin().await?;
+    let terms = sqlx::query_as!(
+        TermsVersion,
+        r#"
+        INSERT INTO terms_versions (version, content)
+        VALUES ($1, $2)
+        RETURNING id, version, content, published_at
+        "#,
+        payload.version,
+        clean_html(&payload.content)
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        if e.to_string().contains("terms_versions_version_key") {
+            AppError::Conflict(format!("Terms version '{}' already exists", payload.version))
+        } else {
+            AppError::InternalServerError(e.to_string())
+        }
+    })?;
+
+    let after = serde_json::json!({ "version": terms.version, "content": terms.content });
+    audit_log::record_create(&mut tx, admin.id, "terms", terms.id, &after).await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(terms)))
+}
+
+// --- Exam Events ---
+
+/// Exam events, latest start first.
+pub async fn list_exam_events(State(pool): State<PgPool>) -> Result<impl IntoResponse, AppError> {
+    let events = sqlx::query_as!(
+        ExamEvent,
+        r#"
+        SELECT id, title, starts_at, ends_at, created_by, created_at
+        FROM exam_events
+        ORDER BY starts_at DESC
+        "#
+    )
+    .fetch_all(&pool)
+    .await?;
+    Ok(Json(events))
+}
+
+/// Schedules an exam event. Events may overlap; the question bank stays
+/// locked while any of them runs.
+pub async fn create_exam_event(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Json(payload): Json<CreateExamEventRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if payload.ends_at <= payload.starts_at {
+        return Err(AppError::BadRequest("ends_at must be after starts_at".to_string()));
+    }
+    if payload.ends_at <= chrono::Utc::now() {
+        return Err(AppError::BadRequest("ends_at must be in the future".to_string()));
+    }
+    if payload.ends_at - payload.starts_at > chrono::Duration::days(EXAM_EVENT_MAX_DAYS) {
+        return Err(AppError::BadRequest(format!(
+            "An exam event lasts at most {} days",
+            EXAM_EVENT_MAX_DAYS
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+    let event = sqlx::query_as!(
+        ExamEvent,
+        r#"
+        INSERT INTO exam_events (title, starts_at, ends_at, created_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, title, starts_at, ends_at, created_by, created_at
+        "#,
+        payload.title.trim(),
+        payload.starts_at,
+        payload.ends_at,
+        admin.id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    let after = serde_json::json!({
+        "title": event.title,
+        "starts_at": event.starts_at,
+        "ends_at": event.ends_at,
+    });
+    audit_log::record_create(&mut tx, admin.id, "exam_event", event.id, &after).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "exam_event_scheduled",
+        actor_id = admin.id,
+        exam_event_id = event.id,
+        starts_at = %event.starts_at,
+        ends_at = %event.ends_at
+    );
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+/// Cancels an exam event that has not started, or ends a running one now,
+/// unlocking the question bank and publishing the scores held back.
+pub async fn cancel_exam_event(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut tx = pool.begin().await?;
+    let event = sqlx::query!(
+        r#"
+        SELECT starts_at, ends_at, starts_at <= NOW() as "started!"
+        FROM exam_events
+        WHERE id = $1 AND ends_at > NOW()
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound(
+        "Upcoming or running exam event not found".to_string(),
+    ))?;
+
+    let before = serde_json::json!({ "ends_at": event.ends_at });
+    if event.started {
+        let ends_at = sqlx::query_scalar!(
+            "UPDATE exam_events SET ends_at = GREATEST(NOW(), starts_at + INTERVAL '1 microsecond') WHERE id = $1 RETURNING ends_at",
+            id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let after = serde_json::json!({ "ends_at": ends_at });
+        audit_log::record_update(&mut tx, admin.id, "exam_event", id, &before, &after, None).await?;
+    } else {
+        sqlx::query!("DELETE FROM exam_events WHERE id = $1", id)
+            .execute(&mut *tx)
+            .await?;
+        let before = serde_json::json!({ "starts_at": event.starts_at, "ends_at": event.ends_at });
+        audit_log::record_delete(&mut tx, admin.id, "exam_event", id, &before).await?;
+    }
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = if event.started { "exam_event_ended" } else { "exam_event_cancelled" },
+        actor_id = admin.id,
+        exam_event_id = id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// --- Competitions ---
+
+/// Creates a competition with a fixed paper, and the exam event that locks
+/// the question bank over its window.
+pub async fn create_competition(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Json(payload): Json<CreateCompetitionRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if payload.ends_at <= payload.starts_at {
+        return Err(AppError::BadRequest("ends_at must be after starts_at".to_string()));
+    }
+    if payload.ends_at <= chrono::Utc::now() {
+        return Err(AppError::BadRequest("ends_at must be in the future".to_string()));
+    }
+    if payload.ends_at - payload.starts_at > chrono::Duration::days(EXAM_EVENT_MAX_DAYS) {
+        return Err(AppError::BadRequest(format!(
+            "A competition lasts at most {} days",
+            EXAM_EVENT_MAX_DAYS
+        )));
+    }
+    let ids = &payload.question_ids;
+    if ids.is_empty() || ids.len() > COMPETITION_MAX_QUESTIONS {
+        return Err(AppError::BadRequest(format!(
+            "A paper has between 1 and {} questions",
+            COMPETITION_MAX_QUESTIONS
+        )));
+    }
+    let mut unique = ids.clone();
+    unique.sort_unstable();
+    unique.dedup();
+    if unique.len() != ids.len() {
+        return Err(AppError::BadRequest("A question appears twice on the paper".to_string()));
+    }
+    let found = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM questions WHERE id = ANY($1) AND suspended_at IS NULL"#,
+        ids
+    )
+    .fetch_one(&pool)
+    .await?;
+    if found != ids.len() as i64 {
+        return Err(AppError::BadRequest(
+            "Some questions do not exist or are suspended".to_string(),
+        ));
+    }
+
+    let title = payload.title.trim();
+    let mut tx = pool.begin().await?;
+    let event_id = sqlx::query_scalar!(
+        "INSERT INTO exam_events (title, starts_at, ends_at, created_by) VALUES ($1, $2, $3, $4) RETURNING id",
+        title,
+        payload.starts_at,
+        payload.ends_at,
+        admin.id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    let (snapshot_id, _) = paper_snapshots::take(&mut tx, ids).await?;
+    let competition = sqlx::query_as!(
+        Competition,
+        r#"
+        INSERT INTO competitions (title, description, starts_at, ends_at, question_ids, exam_event_id, paper_snapshot_id, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, title, description, starts_at, ends_at,
+            cardinality(question_ids) as "question_count!",
+            0::BIGINT as "registrations!", FALSE as "registered!", FALSE as "submitted!",
+            FALSE as "proctoring_consent!", created_at
+        "#,
+        title,
+        payload.description.as_deref().map(clean_html),
+        payload.starts_at,
+        payload.ends_at,
+        ids,
+        event_id,
+        snapshot_id,
+        admin.id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    let after = serde_json::json!({
+        "title": competition.title,
+        "starts_at": competition.starts_at,
+        "ends_at": competition.ends_at,
+        "question_ids": ids,
+        "exam_event_id": event_id,
+    });
+    audit_log::record_create(&mut tx, admin.id, "competition", competition.id, &after).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "competition_created",
+        actor_id = admin.id,
+        competition_id = competition.id,
+        exam_event_id = event_id,
+        starts_at = %competition.starts_at,
+        ends_at = %competition.ends_at
+    );
+    Ok((StatusCode::CREATED, Json(competition)))
+}
+
+/// Entrants of a competition in order of registration, with their
+/// proctoring signals where they consented to them.
+pub async fn list_competition_entries(
+    State(pool): State<PgPool>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM competitions WHERE id = $1) as "exists!""#,
+        id
+    )
+    .fetch_one(&pool)
+    .await?;
+    if !exists {
+        return Err(AppError::NotFound("Competition not found".to_string()));
+    }
+
+    let entries = sqlx::query_as!(
+        CompetitionEntry,
+        r#"
+        SELECT e.user_id, u.username, e.registered_at, e.started_at, e.submitted_at, e.score,
+            e.proctoring_consent, e.focus_loss_count, e.paste_count
+        FROM competition_entries e
+        JOIN users u ON u.id = e.user_id
+        WHERE e.competition_id = $1
+        ORDER BY e.registered_at, e.user_id
+        "#,
+        id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(entries))
+}
+
+/// Deletes a competition that has not started, with its exam event and
+/// registrations.
+pub async fn delete_competition(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut tx = pool.begin().await?;
+    let competition = sqlx::query!(
+        r#"
+        SELECT title, starts_at, ends_at, exam_event_id, paper_snapshot_id, starts_at <= NOW() as "started!"
+        FROM competitions
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound("Competition not found".to_string()))?;
+    if competition.started {
+        return Err(AppError::Conflict(
+            "A competition cannot be deleted once it has started".to_string(),
+        ));
+    }
+
+    sqlx::query!("DELETE FROM competitions WHERE id = $1", id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!(
+        "DELETE FROM paper_snapshots WHERE id = $1",
+        competition.paper_snapshot_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    if let Some(event_id) = competition.exam_event_id {
+        sqlx::query!("DELETE FROM exam_events WHERE id = $1", event_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let before = serde_json::json!({
+        "title": competition.title,
+        "starts_at": competition.starts_at,
+        "ends_at": competition.ends_at,
+    });
+    audit_log::record_delete(&mut tx, admin.id, "competition", id, &before).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "competition_deleted",
+        actor_id = admin.id,
+        competition_id = id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// --- Notifications ---
+
+/// DTO for announcing something to many users.
+#[derive(Debug, Deserialize, Validate)]
+pub struct BroadcastRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub title: String,
+    #[validate(length(min = 1, max = 2000))]
+    pub message: String,
+    #[serde(default)]
+    pub segment: broadcasts::Segment,
+    /// A custom audience, as in segment previews. Takes precedence over `segment`.
+    #[validate(nested)]
+    pub filter: Option<UserFilter>,
+}
+
+/// A broadcast with its delivery stats.
+#[derive(Debug, Serialize)]
+pub struct Broadcast {
+    pub id: i64,
+    pub title: String,
+    pub message: String,
+    /// A preset name, or `custom`.
+    pub segment: String,
+    pub filter: serde_json::Value,
+    pub created_by: Option<i64>,
+    /// Users in the segment when the broadcast was sent.
+    pub recipients: i32,
+    /// Notifications created so far.
+    pub delivered: i32,
+    /// Of those, how many have been read.
+    pub read: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// None while batches are still being delivered.
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Sends a notification to every user in a segment, in background batches.
+pub async fn broadcast_notification(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Json(payload): Json<BroadcastRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let (segment, filter) = match payload.filter {
+        Some(filter) => ("custom", filter),
+        None => (payload.segment.as_str(), payload.segment.filter()),
+    };
+
+    let broadcast_id = broadcasts::create(
+        &pool,
+        admin.id,
+        payload.title.trim(),
+        &clean_html(&payload.message),
+        segment,
+        &filter,
+    )
+    .await?;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "notification_broadcast",
+        actor_id = admin.id,
+        broadcast_id,
+        segment
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({ "broadcast_id": broadcast_id }))))
+}
+
+/// Broadcasts, newest first, with how far each has been delivered and read.
+pub async fn list_broadcasts(State(pool): State<PgPool>) -> Result<impl IntoResponse, AppError> {
+    let list = sqlx::query_as!(
+        Broadcast,
+        r#"
+        SELECT b.id, b.title, b.message, b.segment, b.filter, b.created_by, b.recipients,
+               b.delivered,
+               (SELECT COUNT(*) FROM notifications n
+                WHERE n.kind = $1 AND (n.payload->>'broadcast_id')::BIGINT = b.id
+                  AND n.read_at IS NOT NULL) as "read!",
+               b.created_at, b.finished_at
+        FROM broadcasts b
+        ORDER BY b.id DESC
+        LIMIT 100
+        "#,
+        notifications::BROADCAST
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(list))
+}
+
+// --- Segments ---
+
+/// Counts the users matching a filter and shows a random few of them, to
+/// check an audience before broadcasting to or exporting it.
+pub async fn preview_segment(
+    State(pool): State<PgPool>,
+    Query(filter): Query<UserFilter>,
+) -> Result<impl IntoResponse, AppError> {
+    filter.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let count = segments::count(&pool, &filter).await?;
+    let sample = segments::sample(&pool, &filter, SEGMENT_SAMPLE_SIZE).await?;
+
+    Ok(Json(serde_json::json!({ "count": count, "sample": sample })))
+}
+
+/// Downloads the users matching a filter as CSV.
+pub async fn export_segment(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+    Query(filter): Query<UserFilter>,
+) -> Result<impl IntoResponse, AppError> {
+    filter.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let users = segments::all(&pool, &filter).await?;
+
+    let mut csv = String::from("id,username,is_verified,posts,created_at,last_active_at\n");
+    for user in &users {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            user.id,
+            csv_field(&user.username),
+            user.is_verified,
+            user.posts,
+            user.created_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            user.last_active_at.map(|t| t.to_rfc3339()).unwrap_or_default()
+        ));
+    }
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "segment_exported",
+        actor_id = admin.id,
+        filter = %serde_json::to_value(&filter).unwrap_or_default(),
+        users = users.len()
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"segment.csv\""),
+        ],
+        csv,
+    ))
+}
+
+/// Quotes a CSV field if it needs it. Fields starting with a formula
+/// character are prefixed with `'` so spreadsheets show them as text.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+// --- System ---
+
+/// DTO for changing the log filter, in `RUST_LOG` syntax.
+#[derive(Debug, Deserialize)]
+pub struct SetLogLevelRequest {
+    pub filter: String,
+}
+
+/// Shows the log filter in effect.
+pub async fn get_log_level(State(log_filter): State<LogFilter>) -> impl IntoResponse {
+    Json(serde_json::json!({ "filter": log_filter.current() }))
+}
+
+/// Changes the log filter without a restart, e.g. to `info,backend=debug` while
+/// diagnosing an incident. The change is lost on restart.
+pub async fn set_log_level(
+    State(log_filter): State<LogFilter>,
+    admin: AuthUser,
+    Json(payload): Json<SetLogLevelRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let previous = log_filter.current();
+    log_filter.set(payload.filter.trim()).map_err(|e| match e {
+        LogFilterError::Invalid(msg) => AppError::BadRequest(format!("Invalid log filter: {}", msg)),
+        LogFilterError::Detached => {
+            AppError::InternalServerError("Log filter is not reloadable".to_string())
+        }
+        LogFilterError::Reload(msg) => AppError::InternalServerError(msg),
+    })?;
+
+    tracing::warn!(
+        "Log filter changed from '{}' to '{}' by admin {}",
+        previous,
+        log_filter.current(),
+        admin.id
+    );
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "log_level_changed",
+        actor_id = admin.id,
+        from = previous,
+        to = log_filter.current()
+    );
+    Ok(Json(serde_json::json!({ "filter": log_filter.current() })))
+}
+
+/// Checks invariants the schema cannot enforce (post counters, comment
+/// threads, ...) without changing anything.
+pub async fn verify_integrity(State(pool): State<PgPool>) -> Result<impl IntoResponse, AppError> {
+    let report = integrity::verify(&pool).await?;
+    Ok(Json(report))
+}
+
+/// Runs the integrity checks and repairs what can be derived, reporting what
+/// is left.
+pub async fn repair_integrity(
+    State(pool): State<PgPool>,
+    admin: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let report = integrity::repair(&pool).await?;
+
+    let repaired: Vec<&str> =
+        report.checks.iter().filter(|c| c.violations > 0).map(|c| c.name).collect();
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "integrity_repaired",
+        actor_id = admin.id,
+        checks = repaired.join(",")
+    );
+    Ok(Json(report))
+}
@@ -1,65 +1,446 @@
 // src/handlers/architecture.rs
 
+use std::{sync::Arc, time::Duration};
+
 use axum::{
     Json,
     extract::{Path, Query, State},
-    response::IntoResponse,
+    http::{StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
 };
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::{error::AppError, models::architecture::Architecture};
+use crate::{
+    config::{ARCH_COMPARE_MAX, DOSSIER_URL_SECS, PINYIN_WORD_SIMILARITY},
+    error::AppError,
+    models::architecture::{
+        Architecture, ArchitectureComparison, ArchitectureDetail, ArchitectureSummary,
+        Comparison, PinnedPost, PinnedPosts,
+    },
+    services::{
+        dossiers::{self, DossierStatus},
+        redirects, references,
+        slugs::Lookup,
+    },
+    storage::Storage,
+    utils::{
+        lite::{self, Lite},
+        locale::PreferredLocale,
+        pinyin::normalize_query,
+        units,
+    },
+};
 
 /// Query parameters for listing architectures.
 #[derive(Debug, Deserialize)]
 pub struct ListParams {
     pub category: Option<String>,
+    /// Name keyword; also matches pinyin ("yingxian muta"), partial pinyin,
+    /// initials ("yxmt") and near misspellings.
     pub q: Option<String>,
+    /// Hierarchical location filters, matched case-insensitively.
+    pub province: Option<String>,
+    pub city: Option<String>,
+    pub district: Option<String>,
+    /// Height range in metres; entries without a height are left out.
+    pub min_height: Option<f64>,
+    pub max_height: Option<f64>,
+    /// Footprint range in square metres, likewise.
+    pub min_footprint: Option<f64>,
+    pub max_footprint: Option<f64>,
+    /// 'height', 'footprint' or 'bays' (across the front) to sort by that
+    /// dimension, largest first; entries without it come last. By ID otherwise.
+    pub sort: Option<String>,
+    /// 'desc' (default) or 'asc'.
+    pub order: Option<String>,
+}
+
+/// Query parameters for comparing architectures.
+#[derive(Debug, Deserialize)]
+pub struct CompareParams {
+    /// Comma-separated IDs, e.g. `1,5,9`.
+    pub ids: String,
+    /// 'metric' (default) or 'imperial'.
+    pub units: Option<String>,
+}
+
+/// Query parameters for picking a random architecture.
+#[derive(Debug, Deserialize)]
+pub struct RandomParams {
+    pub category: Option<String>,
+    pub dynasty: Option<String>,
 }
 
-/// Lists all architectures, optionally filtered by category and search keyword.
+/// Lists all architectures, optionally filtered by category, location and search keyword.
+/// Content is served in the caller's preferred locale where a translation exists.
+/// Lite clients get summaries without the description and carousel.
 pub async fn list_architectures(
     State(pool): State<PgPool>,
+    PreferredLocale(locale): PreferredLocale,
+    Lite(lite): Lite,
     Query(params): Query<ListParams>,
 ) -> Result<impl IntoResponse, AppError> {
+    if let Some(sort) = params.sort.as_deref()
+        && !["height", "footprint", "bays"].contains(&sort)
+    {
+        return Err(AppError::BadRequest(format!("Unknown sort '{}'", sort)));
+    }
+    let direction = match params.order.as_deref() {
+        None | Some("desc") => 1.0,
+        Some("asc") => -1.0,
+        Some(other) => return Err(AppError::BadRequest(format!("Unknown order '{}'", other))),
+    };
+
     // Prepare search pattern
-    let search_pattern = params.q.map(|k| format!("%{}%", k));
+    let search_pattern = params.q.as_ref().map(|k| format!("%{}%", k));
+    let pinyin_query = params.q.as_deref().and_then(normalize_query);
+
+    let mut tx = pool.begin().await?;
+    // `<%` can use the trigram index; it compares against this setting.
+    sqlx::query!(
+        "SELECT set_config('pg_trgm.word_similarity_threshold', $1, true)",
+        PINYIN_WORD_SIMILARITY.to_string()
+    )
+    .fetch_one(&mut *tx)
+    .await?;
 
     // Unified query handling optional filters
     let architectures = sqlx::query_as!(
         Architecture,
         r#"
-        SELECT id, category, name, dynasty, location, description, cover_img, carousel_imgs as "carousel_imgs: sqlx::types::Json<Vec<String>>"
-        FROM architectures
-        WHERE ($1::TEXT IS NULL OR category = $1)
-          AND ($2::TEXT IS NULL OR name ILIKE $2)
+        SELECT a.id, a.slug, a.category,
+               COALESCE(t.name, a.name) as "name!",
+               COALESCE(t.dynasty, a.dynasty) as "dynasty!",
+               COALESCE(t.location, a.location) as "location!",
+               a.province, a.city, a.district,
+               COALESCE(t.description, a.description) as "description!",
+               a.cover_img, a.carousel_imgs as "carousel_imgs: sqlx::types::Json<Vec<String>>",
+               a.height_m, a.footprint_m2, a.bays_wide, a.bays_deep,
+               ip.dominant_color as "cover_color?", ip.blurhash as "cover_blurhash?"
+        FROM architectures a
+        LEFT JOIN architecture_translations t ON t.architecture_id = a.id AND t.locale = $7
+        LEFT JOIN image_placeholders ip ON ip.url = a.cover_img AND ip.status = 'ok'
+        WHERE ($1::TEXT IS NULL OR a.category = $1)
+          AND ($2::TEXT IS NULL OR a.name ILIKE $2 OR t.name ILIKE $2
+               OR ($6::TEXT IS NOT NULL AND (
+                    REPLACE(a.name_pinyin, ' ', '') LIKE '%' || $6 || '%'
+                    OR a.name_initials LIKE $6 || '%'
+                    OR $6 <% REPLACE(a.name_pinyin, ' ', '')
+               )))
+          AND ($3::TEXT IS NULL OR LOWER(a.province) = LOWER($3))
+          AND ($4::TEXT IS NULL OR LOWER(a.city) = LOWER($4))
+          AND ($5::TEXT IS NULL OR LOWER(a.district) = LOWER($5))
+          AND ($8::FLOAT8 IS NULL OR a.height_m >= $8)
+          AND ($9::FLOAT8 IS NULL OR a.height_m <= $9)
+          AND ($10::FLOAT8 IS NULL OR a.footprint_m2 >= $10)
+          AND ($11::FLOAT8 IS NULL OR a.footprint_m2 <= $11)
+        ORDER BY
+            (CASE $12::TEXT
+                WHEN 'height' THEN a.height_m
+                WHEN 'footprint' THEN a.footprint_m2
+                WHEN 'bays' THEN a.bays_wide::FLOAT8
+             END) * $13 DESC NULLS LAST,
+            a.id
         "#,
         params.category,
-        search_pattern
+        search_pattern,
+        params.province,
+        params.city,
+        params.district,
+        pinyin_query,
+        locale,
+        params.min_height,
+        params.max_height,
+        params.min_footprint,
+        params.max_footprint,
+        params.sort,
+        direction
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok((
+        [(header::CONTENT_LANGUAGE, locale)],
+        lite::respond::<_, ArchitectureSummary>(lite, architectures),
+    ))
+}
+
+/// Lists a few architectures side by side with their dimensions, in the
+/// order given, translated when possible.
+pub async fn compare_architectures(
+    State(pool): State<PgPool>,
+    PreferredLocale(locale): PreferredLocale,
+    Query(params): Query<CompareParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let ids = params
+        .ids
+        .split(',')
+        .map(|id| id.trim().parse::<i64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| AppError::BadRequest("ids must be comma-separated numbers".to_string()))?;
+    if !(2..=ARCH_COMPARE_MAX).contains(&ids.len()) {
+        return Err(AppError::BadRequest(format!(
+            "Compare between 2 and {} architectures",
+            ARCH_COMPARE_MAX
+        )));
+    }
+    let imperial = match params.units.as_deref() {
+        None | Some("metric") => false,
+        Some("imperial") => true,
+        Some(other) => return Err(AppError::BadRequest(format!("Unknown units '{}'", other))),
+    };
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT a.id, COALESCE(t.name, a.name) as "name!", a.category,
+               COALESCE(t.dynasty, a.dynasty) as "dynasty!", a.province, a.cover_img,
+               a.height_m, a.footprint_m2, a.bays_wide, a.bays_deep
+        FROM architectures a
+        LEFT JOIN architecture_translations t ON t.architecture_id = a.id AND t.locale = $2
+        WHERE a.id = ANY($1)
+        "#,
+        &ids,
+        locale
     )
     .fetch_all(&pool)
     .await?;
 
-    Ok(Json(architectures))
+    let mut architectures = Vec::with_capacity(ids.len());
+    for id in &ids {
+        let row = rows
+            .iter()
+            .find(|row| row.id == *id)
+            .ok_or_else(|| AppError::NotFound(format!("Architecture {} not found", id)))?;
+        let (height, footprint) = if imperial {
+            (
+                row.height_m.map(|m| units::round2(units::metres_to_feet(m))),
+                row.footprint_m2.map(|m2| units::round2(units::square_metres_to_square_feet(m2))),
+            )
+        } else {
+            (row.height_m, row.footprint_m2)
+        };
+        architectures.push(ArchitectureComparison {
+            id: row.id,
+            name: row.name.clone(),
+            category: row.category.clone(),
+            dynasty: row.dynasty.clone(),
+            province: row.province.clone(),
+            cover_img: row.cover_img.clone(),
+            height,
+            footprint,
+            bays_wide: row.bays_wide,
+            bays_deep: row.bays_deep,
+        });
+    }
+
+    Ok((
+        [(header::CONTENT_LANGUAGE, locale)],
+        Json(Comparison {
+            units: if imperial { "imperial" } else { "metric" },
+            architectures,
+        }),
+    ))
 }
 
-/// Retrieves a single architecture by ID.
-pub async fn get_architecture(
+/// Picks one architecture at random, optionally of a category and dynasty
+/// (matched on the untranslated value), for "surprise me".
+///
+/// Instead of `ORDER BY RANDOM()` over the catalog, this takes the first entry
+/// at or after a random pivot on the indexed `random_key`, wrapping around to
+/// the start when there is none.
+pub async fn random_architecture(
     State(pool): State<PgPool>,
-    Path(id): Path<i64>,
+    PreferredLocale(locale): PreferredLocale,
+    Query(params): Query<RandomParams>,
 ) -> Result<impl IntoResponse, AppError> {
     let architecture = sqlx::query_as!(
         Architecture,
         r#"
-                    SELECT id, category, name, dynasty, location, description, cover_img, carousel_imgs as "carousel_imgs: sqlx::types::Json<Vec<String>>"
-        FROM architectures
-        WHERE id = $1
+        WITH pivot AS (SELECT random() AS r),
+        picked AS (
+            (SELECT a.id, 0 AS lap FROM architectures a, pivot
+             WHERE ($1::TEXT IS NULL OR a.category = $1)
+               AND ($2::TEXT IS NULL OR a.dynasty = $2)
+               AND a.random_key >= pivot.r
+             ORDER BY a.random_key
+             LIMIT 1)
+            UNION ALL
+            (SELECT a.id, 1 AS lap FROM architectures a, pivot
+             WHERE ($1::TEXT IS NULL OR a.category = $1)
+               AND ($2::TEXT IS NULL OR a.dynasty = $2)
+               AND a.random_key < pivot.r
+             ORDER BY a.random_key
+             LIMIT 1)
+            ORDER BY lap
+            LIMIT 1
+        )
+        SELECT a.id, a.slug, a.category,
+               COALESCE(t.name, a.name) as "name!",
+               COALESCE(t.dynasty, a.dynasty) as "dynasty!",
+               COALESCE(t.location, a.location) as "location!",
+               a.province, a.city, a.district,
+               COALESCE(t.description, a.description) as "description!",
+               a.cover_img, a.carousel_imgs as "carousel_imgs: sqlx::types::Json<Vec<String>>",
+               a.height_m, a.footprint_m2, a.bays_wide, a.bays_deep,
+               ip.dominant_color as "cover_color?", ip.blurhash as "cover_blurhash?"
+        FROM picked
+        JOIN architectures a ON a.id = picked.id
+        LEFT JOIN architecture_translations t ON t.architecture_id = a.id AND t.locale = $3
+        LEFT JOIN image_placeholders ip ON ip.url = a.cover_img AND ip.status = 'ok'
         "#,
-        id
+        params.category,
+        params.dynasty,
+        locale
     )
     .fetch_optional(&pool)
     .await?
-    .ok_or(AppError::NotFound("Architecture not found".to_string()))?;
+    .ok_or(AppError::NotFound("No architecture matches".to_string()))?;
+
+    Ok(([(header::CONTENT_LANGUAGE, locale)], Json(architecture)))
+}
+
+/// 301 to the same `path` (after the ID) of the architecture `to_id`.
+fn moved(to_id: i64, path: &str) -> Response {
+    let location = format!("/api/architectures/{}{}", to_id, path);
+    (
+        StatusCode::MOVED_PERMANENTLY,
+        [(header::LOCATION, location.clone())],
+        Json(serde_json::json!({ "moved_to": to_id, "location": location })),
+    )
+        .into_response()
+}
+
+/// Answers a lookup of an architecture that does not exist: a redirect to
+/// the architecture it was merged into, or 404.
+async fn moved_or_missing(pool: &PgPool, id: i64, path: &str) -> Result<Response, AppError> {
+    let to_id = redirects::resolve_architecture(pool, id)
+        .await?
+        .ok_or(AppError::NotFound("Architecture not found".to_string()))?;
+    Ok(moved(to_id, path))
+}
+
+/// Retrieves a single architecture by ID or slug, translated when possible,
+/// together with its pinned discussion and visiting-tips posts.
+/// IDs and slugs of merged architectures redirect to the one they were merged into.
+pub async fn get_architecture(
+    State(pool): State<PgPool>,
+    PreferredLocale(locale): PreferredLocale,
+    Path(key): Path<String>,
+) -> Result<Response, AppError> {
+    let id = match Lookup::parse(&key) {
+        Lookup::Id(id) => id,
+        Lookup::Slug(slug) => {
+            let id = sqlx::query_scalar!("SELECT id FROM architectures WHERE slug = $1", slug)
+                .fetch_optional(&pool)
+                .await?;
+            match id {
+                Some(id) => id,
+                None => {
+                    let to_id = redirects::resolve_architecture_slug(&pool, &slug)
+                        .await?
+                        .ok_or(AppError::NotFound("Architecture not found".to_string()))?;
+                    return Ok(moved(to_id, ""));
+                }
+            }
+        }
+    };
+
+    let architecture = sqlx::query_as!(
+        Architecture,
+        r#"
+        SELECT a.id, a.slug, a.category,
+               COALESCE(t.name, a.name) as "name!",
+               COALESCE(t.dynasty, a.dynasty) as "dynasty!",
+               COALESCE(t.location, a.location) as "location!",
+               a.province, a.city, a.district,
+               COALESCE(t.description, a.description) as "description!",
+               a.cover_img, a.carousel_imgs as "carousel_imgs: sqlx::types::Json<Vec<String>>",
+               a.height_m, a.footprint_m2, a.bays_wide, a.bays_deep,
+               ip.dominant_color as "cover_color?", ip.blurhash as "cover_blurhash?"
+        FROM architectures a
+        LEFT JOIN architecture_translations t ON t.architecture_id = a.id AND t.locale = $2
+        LEFT JOIN image_placeholders ip ON ip.url = a.cover_img AND ip.status = 'ok'
+        WHERE a.id = $1
+        "#,
+        id,
+        locale
+    )
+    .fetch_optional(&pool)
+    .await?;
+    let Some(architecture) = architecture else {
+        return moved_or_missing(&pool, id, "").await;
+    };
+
+    let pinned = sqlx::query!(
+        r#"
+        SELECT p.id, p.title, p.user_id, p.created_at,
+               (p.id = a.pinned_discussion_post_id) as "is_discussion!"
+        FROM architectures a
+        JOIN posts p ON p.id IN (a.pinned_discussion_post_id, a.pinned_tips_post_id)
+        WHERE a.id = $1 AND p.deleted_at IS NULL
+        "#,
+        id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let mut pinned_posts = PinnedPosts::default();
+    for row in pinned {
+        let post = PinnedPost {
+            id: row.id,
+            title: row.title,
+            user_id: row.user_id,
+            created_at: row.created_at,
+        };
+        if row.is_discussion {
+            pinned_posts.discussion = Some(post);
+        } else {
+            pinned_posts.visiting_tips = Some(post);
+        }
+    }
+
+    let mentioned_in_posts = references::architecture_mentions(&pool, id).await?;
+
+    Ok((
+        [(header::CONTENT_LANGUAGE, locale)],
+        Json(ArchitectureDetail {
+            architecture,
+            pinned_posts,
+            mentioned_in_posts,
+        }),
+    )
+        .into_response())
+}
+
+/// Redirects to a short-lived signed URL for the printable dossier of an
+/// architecture, in the preferred locale.
+///
+/// Answers 202 while the dossier is being rendered, which happens on the first
+/// request and after the architecture changes; clients retry after a few seconds.
+pub async fn download_dossier(
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn Storage>>,
+    PreferredLocale(locale): PreferredLocale,
+    Path(id): Path<i64>,
+) -> Result<Response, AppError> {
+    let Some(status) = dossiers::request(&pool, id, &locale).await? else {
+        return moved_or_missing(&pool, id, "/dossier.pdf").await;
+    };
 
-    Ok(Json(architecture))
+    match status {
+        DossierStatus::Ready(key) => {
+            let url = storage
+                .signed_url(&key, Duration::from_secs(DOSSIER_URL_SECS))
+                .await?;
+            Ok(Redirect::temporary(&url).into_response())
+        }
+        DossierStatus::Pending => Ok((
+            StatusCode::ACCEPTED,
+            [(header::RETRY_AFTER, "5")],
+            Json(serde_json::json!({ "status": "pending" })),
+        )
+            .into_response()),
+    }
 }
@@ -1,43 +1,99 @@
 // src/handlers/auth.rs
 
-use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use std::borrow::Cow;
+
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, header},
+    response::IntoResponse,
+};
+use chrono::Datelike;
 use serde_json::json;
 use sqlx::PgPool;
-use validator::Validate;
+use validator::{Validate, ValidationError, ValidationErrors};
 
 use crate::{
-    config::Config,
+    config::{Config, OAuthProviderConfig, RegistrationConfig},
     error::AppError,
-    models::user::{CreateUserRequest, LoginRequest, User},
+    models::user::{
+        CreateUserRequest, ForgotPasswordRequest, LoginRequest, OAuthCallbackRequest,
+        ResetPasswordRequest, TwoFactorCodeRequest, User, VerifyEmailRequest, is_valid_username,
+    },
+    services::{
+        email_verification,
+        oauth::{self, Provider},
+        password_reset, security_events, sessions, spam, terms, token_revocation, two_factor,
+    },
     utils::{
-        hash::{hash_password, verify_password},
-        jwt::sign_jwt,
+        audit_stream,
+        client_ip::ClientIp,
+        hash::{hash_password, new_token, verify_password},
+        jwt::{AuthUser, Claims, bearer_token, sign_jwt},
     },
 };
 
 /// Registers a new user.
 ///
 /// Hashes the password using Argon2 before storing it.
+/// Once terms of service are published, the current version must be accepted.
+/// Configured age, region and email requirements are checked along with the
+/// payload, and all failures are reported per field. A given email address is
+/// sent a verification link. Bot checks run first; see `services::spam`.
 /// Returns 201 Created and the user object (excluding password).
 pub async fn register(
     State(pool): State<PgPool>,
+    State(config): State<Config>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
     Json(payload): Json<CreateUserRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let suspect = spam::screen(
+        &pool,
+        &config.spam,
+        &config.jwt_secret,
+        &payload.traps,
+        None,
+        ip.as_deref(),
+        "/api/auth/register",
+    )
+    .await?;
+
+    let mut errors = payload.validate().err().unwrap_or_default();
+    check_requirements(&config.registration, &payload, chrono::Utc::now().year(), &mut errors);
+    if !errors.is_empty() {
+        return Err(AppError::Validation(errors));
+    }
+
+    let current_terms = terms::current(&pool).await?;
+    if let Some(current) = &current_terms {
+        terms::check_version(current, payload.accepted_terms_version.as_deref())?;
+    }
+
+    let email = payload.email.as_deref().map(str::trim);
+    if let Some(email) = email
+        && email_verification::is_taken(&pool, email, None).await?
+    {
+        return Err(AppError::Conflict("This email address is already in use".to_string()));
+    }
 
     let hashed_password = hash_password(&payload.password)?;
+    let mut tx = pool.begin().await?;
 
     let user = sqlx::query_as!(
         User,
         r#"
-        INSERT INTO users (username, password)
-        VALUES ($1, $2)
+        INSERT INTO users (username, password, birth_year, region, email)
+        VALUES ($1, $2, $3, $4, $5)
         RETURNING id, username, password, role, is_verified, created_at
         "#,
         payload.username,
-        hashed_password
+        hashed_password,
+        payload.birth_year,
+        payload.region,
+        email
     )
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
         // Postgres error code for unique violation is 23505
@@ -49,16 +105,103 @@ pub async fn register(
         }
     })?;
 
+    if let Some(current) = &current_terms {
+        terms::record_acceptance(&mut *tx, user.id, current.id).await?;
+    }
+    if let Some(email) = email {
+        email_verification::start(&mut tx, &config.mail, user.id, email).await?;
+    }
+    tx.commit().await?;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "register",
+        user_id = user.id,
+        username = user.username
+    );
+    security_events::record(
+        &pool,
+        security_events::REGISTERED,
+        Some(user.id),
+        ip.as_deref(),
+        user_agent(&headers),
+        "/api/auth/register",
+    )
+    .await;
+    if let Some(reason) = suspect {
+        spam::flag(&pool, reason, user.id, ip.as_deref(), "/api/auth/register").await;
+    }
+
     Ok((StatusCode::CREATED, Json(user)))
 }
 
+/// Checks the configured age and region requirements, adding a field error for each
+/// one that fails. A birth year or region given without a requirement is only
+/// sanity-checked.
+fn check_requirements(
+    settings: &RegistrationConfig,
+    payload: &CreateUserRequest,
+    current_year: i32,
+    errors: &mut ValidationErrors,
+) {
+    match (payload.birth_year, settings.min_age) {
+        (None, Some(_)) => errors.add(
+            "birth_year",
+            ValidationError::new("required").with_message(Cow::from("Birth year is required.")),
+        ),
+        (Some(year), _) if !(1900..=current_year).contains(&year) => errors.add(
+            "birth_year",
+            ValidationError::new("range").with_message(Cow::from("Birth year is not valid.")),
+        ),
+        (Some(year), Some(min_age)) if current_year - year < min_age => {
+            let mut error = ValidationError::new("min_age").with_message(Cow::from(format!(
+                "You must be at least {} years old to register.",
+                min_age
+            )));
+            error.add_param(Cow::from("min_age"), &min_age);
+            errors.add("birth_year", error);
+        }
+        _ => {}
+    }
+
+    if settings.require_email && payload.email.is_none() {
+        errors.add(
+            "email",
+            ValidationError::new("required").with_message(Cow::from("Email address is required.")),
+        );
+    }
+
+    if settings.regions.is_empty() {
+        return;
+    }
+    match payload.region.as_deref() {
+        None => errors.add(
+            "region",
+            ValidationError::new("required").with_message(Cow::from("Region is required.")),
+        ),
+        Some(region) if !settings.regions.iter().any(|r| r == region) => {
+            let mut error = ValidationError::new("region").with_message(Cow::from(
+                "Registration is not available in this region.",
+            ));
+            error.add_param(Cow::from("allowed"), &settings.regions);
+            errors.add("region", error);
+        }
+        _ => {}
+    }
+}
+
 /// Authenticates a user and returns a JWT token.
 ///
-/// Verifies the username and password against the database.
+/// Verifies the username and password against the database. Usernames match
+/// regardless of case, width and zero-width characters.
 /// If valid, signs a JWT token with the user's ID and role.
+/// Failed attempts are recorded as security events for the abuse dashboard.
+/// Logging in never requires the current terms, but may accept them; the response
+/// tells the client whether they are still pending.
 pub async fn login(
     State(pool): State<PgPool>,
     State(config): State<Config>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
@@ -74,7 +217,11 @@ pub async fn login(
             is_verified,
             created_at
         FROM users
-        WHERE username = $1
+        WHERE username = $1 OR username_normalized = normalize_username($1)
+        -- An exact match wins over a look-alike (only possible for accounts
+        -- that collided before usernames were normalized)
+        ORDER BY (username = $1) DESC
+        LIMIT 1
         "#,
         payload.username
     )
@@ -85,25 +232,503 @@ pub async fn login(
         AppError::InternalServerError(e.to_string())
     })?;
 
-    let user = user.ok_or(AppError::AuthError("User not found".to_string()))?;
+    let Some(user) = user else {
+        tracing::info!(
+            target: audit_stream::TARGET,
+            action = "login_failed",
+            username = payload.username,
+            ip = ip.as_deref(),
+            reason = "unknown_user"
+        );
+        security_events::record(
+            &pool,
+            security_events::LOGIN_FAILED,
+            None,
+            ip.as_deref(),
+            user_agent(&headers),
+            "/api/auth/login",
+        )
+        .await;
+        return Err(AppError::AuthError("User not found".to_string()));
+    };
 
     let is_valid = verify_password(&payload.password, &user.password)?;
 
     if !is_valid {
+        tracing::info!(
+            target: audit_stream::TARGET,
+            action = "login_failed",
+            user_id = user.id,
+            username = user.username,
+            ip = ip.as_deref(),
+            reason = "wrong_password"
+        );
+        security_events::record(
+            &pool,
+            security_events::LOGIN_FAILED,
+            Some(user.id),
+            ip.as_deref(),
+            user_agent(&headers),
+            "/api/auth/login",
+        )
+        .await;
         return Err(AppError::AuthError("Invalid password".to_string()));
     }
 
+    let mfa = second_factor(
+        &pool,
+        &config,
+        &user,
+        payload.totp_code.as_deref(),
+        ip.as_deref(),
+        user_agent(&headers),
+        "/api/auth/login",
+    )
+    .await?;
+
+    if let Some(version) = payload.accepted_terms_version.as_deref()
+        && let Some(current) = terms::current(&pool).await?
+    {
+        terms::check_version(&current, Some(version))?;
+        terms::record_acceptance(&pool, user.id, current.id).await?;
+    }
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "login",
+        user_id = user.id,
+        username = user.username,
+        ip = ip.as_deref(),
+        mfa
+    );
+    security_events::record(
+        &pool,
+        security_events::LOGIN,
+        Some(user.id),
+        ip.as_deref(),
+        user_agent(&headers),
+        "/api/auth/login",
+    )
+    .await;
+
+    let session =
+        start_session(&pool, &config, &user, mfa, ip.as_deref(), user_agent(&headers)).await?;
+    Ok(Json(session))
+}
+
+/// Checks the second factor of a sign-in: accounts with two-factor
+/// authentication must send a valid code. Returns whether one was checked.
+async fn second_factor(
+    pool: &PgPool,
+    config: &Config,
+    user: &User,
+    code: Option<&str>,
+    ip: Option<&str>,
+    user_agent: Option<&str>,
+    path: &str,
+) -> Result<bool, AppError> {
+    if !two_factor::is_enabled(pool, user.id).await? {
+        return Ok(false);
+    }
+    let result = match code {
+        Some(code) => two_factor::verify(pool, &config.jwt_secret, user.id, code).await,
+        None => Err(AppError::AuthError("Two-factor code required".to_string())),
+    };
+    if let Err(e) = result {
+        tracing::info!(
+            target: audit_stream::TARGET,
+            action = "login_failed",
+            user_id = user.id,
+            username = user.username,
+            ip,
+            reason = if code.is_some() { "wrong_totp_code" } else { "missing_totp_code" }
+        );
+        security_events::record(
+            pool,
+            security_events::LOGIN_FAILED,
+            Some(user.id),
+            ip,
+            user_agent,
+            path,
+        )
+        .await;
+        return Err(e);
+    }
+    Ok(true)
+}
+
+/// The `User-Agent` of a request, if readable.
+fn user_agent(headers: &HeaderMap) -> Option<&str> {
+    headers.get(header::USER_AGENT).and_then(|v| v.to_str().ok())
+}
+
+/// Signs a token for a user who just authenticated, records the session with
+/// the client's address and user agent, and notes the activity.
+/// `mfa` marks a sign-in that passed two-factor authentication.
+async fn start_session(
+    pool: &PgPool,
+    config: &Config,
+    user: &User,
+    mfa: bool,
+    ip: Option<&str>,
+    user_agent: Option<&str>,
+) -> Result<serde_json::Value, AppError> {
+    let terms_accepted = terms::has_accepted_current(pool, user.id).await?;
+    sqlx::query!("UPDATE users SET last_active_at = CURRENT_TIMESTAMP WHERE id = $1", user.id)
+        .execute(pool)
+        .await?;
+
     let token = sign_jwt(
         user.id,
         &user.username,
         &user.role,
         &config.jwt_secret,
         config.jwt_expiration,
+        mfa,
     )?;
+    sessions::record(pool, user.id, &token, config.jwt_expiration, ip, user_agent).await?;
 
-    Ok(Json(json!({
+    Ok(json!({
         "token": token,
         "type": "Bearer",
-        "is_verified": user.is_verified
+        "is_verified": user.is_verified,
+        "terms_accepted": terms_accepted,
+        "mfa": mfa
+    }))
+}
+
+/// Revokes the token the request was made with, so it stops working before it
+/// expires, and ends its session. Other sessions of the user are unaffected.
+pub async fn logout(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Extension(claims): Extension<Claims>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let token = bearer_token(&headers)
+        .ok_or(AppError::AuthError("Missing or invalid token".to_string()))?;
+    token_revocation::revoke(&pool, token, &claims, user.id).await?;
+    sessions::forget(&pool, token).await?;
+
+    tracing::info!(target: audit_stream::TARGET, action = "logout", user_id = user.id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Issues a `form_token` for a registration or post form that is being shown,
+/// for the bot checks' timing (`SPAM_MIN_FORM_SECS`).
+pub async fn form_token(State(config): State<Config>) -> impl IntoResponse {
+    let token = spam::form_token(&config.jwt_secret, chrono::Utc::now().timestamp());
+    Json(json!({ "form_token": token }))
+}
+
+/// Starts turning on two-factor authentication: returns a new secret to add
+/// to an authenticator app. It takes effect once `verify_two_factor` accepts
+/// a code from the app.
+pub async fn enroll_two_factor(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let username = sqlx::query_scalar!("SELECT username FROM users WHERE id = $1", user.id)
+        .fetch_one(&pool)
+        .await?;
+    let enrollment = two_factor::enroll(&pool, &config.jwt_secret, user.id, &username).await?;
+
+    Ok(Json(json!({
+        "secret": enrollment.secret,
+        "otpauth_uri": enrollment.uri
     })))
 }
+
+/// Checks a code from the authenticator app; the first one turns two-factor
+/// authentication on. Returns a new token marked as having passed it.
+pub async fn verify_two_factor(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+    user: AuthUser,
+    Json(payload): Json<TwoFactorCodeRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let turned_on = two_factor::verify(&pool, &config.jwt_secret, user.id, &payload.code).await?;
+    if turned_on {
+        tracing::info!(target: audit_stream::TARGET, action = "two_factor_enabled", user_id = user.id);
+    }
+
+    let user = sqlx::query_as!(
+        User,
+        "SELECT id, username, password, role, is_verified, created_at FROM users WHERE id = $1",
+        user.id
+    )
+    .fetch_one(&pool)
+    .await?;
+    let session =
+        start_session(&pool, &config, &user, true, ip.as_deref(), user_agent(&headers)).await?;
+    Ok(Json(session))
+}
+
+/// Confirms an email address with the token from the verification email.
+pub async fn verify_email(
+    State(pool): State<PgPool>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let email = email_verification::confirm(&pool, &payload.token).await?;
+
+    Ok(Json(json!({ "email": email, "email_verified": true })))
+}
+
+/// Emails a password reset link to the account with this verified address.
+/// Answers the same whether or not there is one.
+pub async fn forgot_password(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let sent_to = password_reset::request(&pool, &config.mail, payload.email.trim()).await?;
+    if let Some(user_id) = sent_to {
+        tracing::info!(
+            target: audit_stream::TARGET,
+            action = "password_reset_requested",
+            user_id
+        );
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "message": "If this address belongs to an account, a reset link has been sent."
+        })),
+    ))
+}
+
+/// Sets a new password with the token from the reset email.
+pub async fn reset_password(
+    State(pool): State<PgPool>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let user_id = password_reset::reset(&pool, &payload.token, &payload.password).await?;
+    tracing::info!(target: audit_stream::TARGET, action = "password_reset", user_id);
+    security_events::record(
+        &pool,
+        security_events::PASSWORD_CHANGED,
+        Some(user_id),
+        ip.as_deref(),
+        user_agent(&headers),
+        "/api/auth/reset-password",
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// An enabled provider by name, or 404.
+fn oauth_provider<'a>(
+    config: &'a Config,
+    name: &str,
+) -> Result<(Provider, &'a OAuthProviderConfig), AppError> {
+    Provider::parse(name)
+        .and_then(|p| Some((p, p.config(&config.oauth)?)))
+        .ok_or_else(|| AppError::NotFound(format!("Sign-in with '{}' is not available", name)))
+}
+
+/// Starts signing in with an OAuth provider; the client sends the user to the
+/// returned URL.
+pub async fn oauth_authorize(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    Path(provider): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let (provider, settings) = oauth_provider(&config, &provider)?;
+    let url = oauth::begin(&pool, provider, settings, None).await?;
+
+    Ok(Json(json!({ "authorize_url": url })))
+}
+
+/// Starts linking an OAuth provider account to the current user, so they can
+/// sign in with it afterwards.
+pub async fn oauth_link(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    user: AuthUser,
+    Path(provider): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let (provider, settings) = oauth_provider(&config, &provider)?;
+    let url = oauth::begin(&pool, provider, settings, Some(user.id)).await?;
+
+    Ok(Json(json!({ "authorize_url": url })))
+}
+
+/// Finishes an OAuth flow with the `code` and `state` the provider sent back.
+///
+/// A link started by `oauth_link` attaches the account to that user. Otherwise
+/// a linked account signs in as its user, and an unknown one gets a new
+/// account named after it, unless registration has requirements the provider
+/// cannot vouch for. Users with two-factor authentication must send a code
+/// along; without one the flow has to be started again.
+pub async fn oauth_callback(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+    Path(provider): Path<String>,
+    Json(payload): Json<OAuthCallbackRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let (provider, settings) = oauth_provider(&config, &provider)?;
+    let (link_user_id, identity) =
+        oauth::complete(&pool, provider, settings, &payload.code, &payload.state).await?;
+    let linked_to = oauth::find_user(&pool, provider, &identity.subject).await?;
+
+    if let Some(user_id) = link_user_id {
+        if linked_to != Some(user_id) {
+            oauth::link(&pool, user_id, provider, &identity.subject).await?;
+            tracing::info!(
+                target: audit_stream::TARGET,
+                action = "oauth_linked",
+                user_id,
+                provider = provider.as_str()
+            );
+        }
+        return Ok(Json(json!({ "linked": provider.as_str() })));
+    }
+
+    let accepted_terms = payload.accepted_terms_version.as_deref();
+    let (user_id, created) = match linked_to {
+        Some(user_id) => (user_id, false),
+        None => {
+            let user_id =
+                create_oauth_user(&pool, &config, provider, &identity, accepted_terms).await?;
+            (user_id, true)
+        }
+    };
+    let user = sqlx::query_as!(
+        User,
+        "SELECT id, username, password, role, is_verified, created_at FROM users WHERE id = $1",
+        user_id
+    )
+    .fetch_one(&pool)
+    .await?;
+    let path = format!("/api/auth/oauth/{}/callback", provider.as_str());
+    let mfa = second_factor(
+        &pool,
+        &config,
+        &user,
+        payload.totp_code.as_deref(),
+        ip.as_deref(),
+        user_agent(&headers),
+        &path,
+    )
+    .await?;
+    if !created
+        && let Some(version) = accepted_terms
+        && let Some(current) = terms::current(&pool).await?
+    {
+        terms::check_version(&current, Some(version))?;
+        terms::record_acceptance(&pool, user.id, current.id).await?;
+    }
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "login",
+        user_id = user.id,
+        username = user.username,
+        ip = ip.as_deref(),
+        provider = provider.as_str(),
+        mfa
+    );
+    let kind = if created { security_events::REGISTERED } else { security_events::LOGIN };
+    security_events::record(&pool, kind, Some(user.id), ip.as_deref(), user_agent(&headers), &path)
+        .await;
+
+    let mut session =
+        start_session(&pool, &config, &user, mfa, ip.as_deref(), user_agent(&headers)).await?;
+    session["created"] = json!(created);
+    Ok(Json(session))
+}
+
+/// Registers a user for a provider account that is not linked yet. The
+/// username follows the account's name within the rules `register` applies,
+/// with a number added if it or a look-alike is taken. Published terms must
+/// be accepted as at registration.
+async fn create_oauth_user(
+    pool: &PgPool,
+    config: &Config,
+    provider: Provider,
+    identity: &oauth::Identity,
+    accepted_terms_version: Option<&str>,
+) -> Result<i64, AppError> {
+    let settings = &config.registration;
+    if settings.min_age.is_some() || !settings.regions.is_empty() || settings.require_email {
+        return Err(AppError::BadRequest(format!(
+            "Please register with a username first, then link your {} account",
+            provider.as_str()
+        )));
+    }
+    let current_terms = terms::current(pool).await?;
+    if let Some(current) = &current_terms {
+        terms::check_version(current, accepted_terms_version)?;
+    }
+
+    let base: String = identity
+        .name
+        .as_deref()
+        .unwrap_or_default()
+        .chars()
+        .filter_map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' => Some(c),
+            '-' => Some('_'),
+            _ => None,
+        })
+        .take(40)
+        .collect();
+    let base = if is_valid_username(&base) {
+        base
+    } else {
+        format!("{}_user", provider.as_str())
+    };
+    // Nobody knows this password; the user can set one through a password reset.
+    let unusable_password = hash_password(&new_token())?;
+
+    let mut tx = pool.begin().await?;
+    let mut user_id = None;
+    for attempt in 0..5 {
+        let username = match attempt {
+            0 => base.clone(),
+            _ => format!("{}_{}", base, rand::random::<u16>() % 10000),
+        };
+        user_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO users (username, password) VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            RETURNING id
+            "#,
+            username,
+            unusable_password
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if user_id.is_some() {
+            break;
+        }
+    }
+    let user_id = user_id
+        .ok_or_else(|| AppError::Conflict("Could not find a free username".to_string()))?;
+
+    if let Some(current) = &current_terms {
+        terms::record_acceptance(&mut *tx, user_id, current.id).await?;
+    }
+    oauth::link(&mut *tx, user_id, provider, &identity.subject).await?;
+    tx.commit().await?;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "register",
+        user_id,
+        provider = provider.as_str()
+    );
+
+    Ok(user_id)
+}
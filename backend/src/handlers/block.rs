@@ -0,0 +1,146 @@
+// src/handlers/block.rs
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use sqlx::PgPool;
+
+use crate::{
+    error::AppError,
+    models::user::{BlockList, BlockedUser},
+    utils::jwt::AuthUser,
+};
+
+/// Rejects the caller themselves and users that do not exist.
+async fn ensure_other_user(pool: &PgPool, user_id: i64, other_id: i64) -> Result<(), AppError> {
+    if other_id == user_id {
+        return Err(AppError::BadRequest(
+            "You cannot block or mute yourself".to_string(),
+        ));
+    }
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM users WHERE id = $1) as "exists!""#,
+        other_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Err(AppError::NotFound("User not found".to_string()));
+    }
+    Ok(())
+}
+
+/// Blocks a user, or unblocks them if already blocked.
+///
+/// Their posts and comments are hidden from the caller's listings, neither
+/// side can message the other, and they cannot comment on the caller's posts
+/// or reply to the caller's comments. Blocking also ends follows both ways.
+pub async fn toggle_block(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(blocked_id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    ensure_other_user(&pool, user.id, blocked_id).await?;
+
+    let mut tx = pool.begin().await?;
+    let unblocked = sqlx::query!(
+        "DELETE FROM user_blocks WHERE blocker_id = $1 AND blocked_id = $2",
+        user.id,
+        blocked_id
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    if !unblocked {
+        sqlx::query!(
+            "INSERT INTO user_blocks (blocker_id, blocked_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            user.id,
+            blocked_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            DELETE FROM follows
+            WHERE (follower_id = $1 AND followee_id = $2) OR (follower_id = $2 AND followee_id = $1)
+            "#,
+            user.id,
+            blocked_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(Json(serde_json::json!({ "blocked": !unblocked })))
+}
+
+/// Mutes a user, or unmutes them if already muted. Their posts and comments
+/// are hidden from the caller's listings; nothing else changes.
+pub async fn toggle_mute(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(muted_id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    ensure_other_user(&pool, user.id, muted_id).await?;
+
+    let unmuted = sqlx::query!(
+        "DELETE FROM user_mutes WHERE muter_id = $1 AND muted_id = $2",
+        user.id,
+        muted_id
+    )
+    .execute(&pool)
+    .await?
+    .rows_affected()
+        > 0;
+    if !unmuted {
+        sqlx::query!(
+            "INSERT INTO user_mutes (muter_id, muted_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            user.id,
+            muted_id
+        )
+        .execute(&pool)
+        .await?;
+    }
+
+    Ok(Json(serde_json::json!({ "muted": !unmuted })))
+}
+
+/// The users the current user blocked and muted, most recent first.
+pub async fn list_blocks(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let blocked = sqlx::query_as!(
+        BlockedUser,
+        r#"
+        SELECT u.id, u.username, u.avatar_url, b.created_at as since
+        FROM user_blocks b
+        JOIN users u ON u.id = b.blocked_id
+        WHERE b.blocker_id = $1
+        ORDER BY b.created_at DESC
+        "#,
+        user.id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let muted = sqlx::query_as!(
+        BlockedUser,
+        r#"
+        SELECT u.id, u.username, u.avatar_url, m.created_at as since
+        FROM user_mutes m
+        JOIN users u ON u.id = m.muted_id
+        WHERE m.muter_id = $1
+        ORDER BY m.created_at DESC
+        "#,
+        user.id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(BlockList { blocked, muted }))
+}
@@ -0,0 +1,63 @@
+// src/handlers/certificate.rs
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::{IntoResponse, Redirect},
+};
+use sqlx::PgPool;
+
+use crate::{
+    config::CERTIFICATE_PDF_URL_SECS, error::AppError, models::certificate::CertificateResponse,
+    storage::Storage,
+};
+
+/// Public verification of a certificate by its code.
+pub async fn verify_certificate(
+    State(pool): State<PgPool>,
+    Path(code): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let cert = sqlx::query_as!(
+        CertificateResponse,
+        r#"
+        SELECT c.code, u.username, c.kind, c.score, c.issued_at,
+               (c.pdf_key IS NOT NULL) as "pdf_ready!"
+        FROM certificates c
+        JOIN users u ON c.user_id = u.id
+        WHERE c.code = $1
+        "#,
+        code.to_uppercase()
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Certificate not found".to_string()))?;
+
+    Ok(Json(cert))
+}
+
+/// Redirects to a short-lived signed URL for the rendered certificate PDF.
+/// Returns 404 until the background job has produced it.
+pub async fn download_certificate_pdf(
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn Storage>>,
+    Path(code): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let key = sqlx::query_scalar!(
+        "SELECT pdf_key FROM certificates WHERE code = $1",
+        code.to_uppercase()
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Certificate not found".to_string()))?
+    .ok_or(AppError::NotFound(
+        "Certificate PDF is still being generated".to_string(),
+    ))?;
+
+    let url = storage
+        .signed_url(&key, Duration::from_secs(CERTIFICATE_PDF_URL_SECS))
+        .await?;
+
+    Ok(Redirect::temporary(&url))
+}
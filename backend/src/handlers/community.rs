@@ -1,5 +1,5 @@
 use axum::{
-    Extension, Json,
+    Json,
     extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
@@ -8,40 +8,75 @@ use sqlx::PgPool;
 use validator::Validate;
 
 use crate::{
+    config::Config,
     error::AppError,
-    models::post::{CreatePostRequest, Post, PostListParams},
-    utils::jwt::{Claims, VerifiedUser},
-    utils::html::clean_html,
+    models::post::{
+        CreatePostRequest, Post, PostDetail, PostListParams, PostRevision, PostSummary,
+        UpdatePostRequest,
+    },
+    services::{
+        image_placeholders, quotas, references, series,
+        slugs::{self, Lookup},
+        spam, tags, terms, unfurl, user_settings,
+    },
+    utils::client_ip::ClientIp,
+    utils::jwt::{AuthUser, OptionalAuthUser, VerifiedUser},
+    utils::permissions::Permission,
+    utils::html::{clean_html, excerpt, image_count, reading_time_minutes, render_markdown},
+    utils::lite::{self, Lite},
+    utils::audit_stream,
 };
 
 /// Create a new post.
 /// Automatically restricted to Verified users or Admins via the VerifiedUser extractor.
 pub async fn create_post(
     State(pool): State<PgPool>,
+    State(config): State<Config>,
     user: VerifiedUser,
+    ClientIp(ip): ClientIp,
     Json(payload): Json<CreatePostRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    // 1. Validate payload using the validator crate
+    // 1. Validate payload using the validator crate, after the bot checks
+    let suspect = spam::screen(
+        &pool,
+        &config.spam,
+        &config.jwt_secret,
+        &payload.traps,
+        Some(user.id),
+        ip.as_deref(),
+        "/api/posts",
+    )
+    .await?;
     payload
         .validate()
         .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    terms::ensure_accepted(&pool, user.id).await?;
 
-    // 2. Sanitize HTML content to prevent XSS
+    // 2. Render the Markdown and sanitize HTML to prevent XSS
     let clean_title = clean_html(&payload.title);
-    let clean_content = clean_html(&payload.content);
+    let clean_content = render_markdown(&payload.content);
+    let images = image_count(&clean_content);
 
     // 3. Insert into the database (Permissions checked by VerifiedUser extractor)
+    let mut tx = pool.begin().await?;
+    quotas::check_post(&mut tx, &config.quotas, user.id, images.into()).await?;
+    let slug = slugs::for_post(&mut tx, &payload.title).await?;
     let post_id = sqlx::query!(
         r#"
-        INSERT INTO posts (user_id, title, content)
-        VALUES ($1, $2, $3)
+        INSERT INTO posts (user_id, title, content, content_md, excerpt, reading_time_minutes, image_count, slug)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
         RETURNING id
         "#,
         user.id,
         clean_title,
-        clean_content
+        clean_content,
+        payload.content,
+        excerpt(&clean_content),
+        reading_time_minutes(&clean_content),
+        images,
+        slug
     )
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
         tracing::error!("Failed to create post: {:?}", e);
@@ -49,21 +84,202 @@ pub async fn create_post(
     })?
     .id;
 
+    // 4. Resolve [[arch:..]] / [[term:..]] cross-references
+    let refs = references::extract_references(&clean_content);
+    references::store(&mut tx, post_id, &refs).await?;
+    tags::store(&mut tx, post_id, &tags::normalize(&payload.tags)).await?;
+    tx.commit().await?;
+    if let Some(reason) = suspect {
+        let path = format!("/api/posts/{}", post_id);
+        spam::flag(&pool, reason, user.id, ip.as_deref(), &path).await;
+    }
+
+    // 5. Queue link previews and image placeholders
+    queue_media(&pool, post_id, &clean_content).await;
+
     Ok((
         StatusCode::CREATED,
-        Json(serde_json::json!({"id": post_id})),
+        Json(serde_json::json!({"id": post_id, "slug": slug})),
+    ))
+}
+
+/// Queues link previews and image placeholders for post content.
+/// A failure here must not fail the post itself.
+async fn queue_media(pool: &PgPool, post_id: i64, content: &str) {
+    let urls = unfurl::extract_urls(content);
+    if let Err(e) = unfurl::queue_previews(pool, &urls).await {
+        tracing::warn!("Failed to queue link previews for post {}: {:?}", post_id, e);
+    }
+    let image_urls = image_placeholders::image_urls(content);
+    if let Err(e) = image_placeholders::queue_placeholders(pool, &image_urls).await {
+        tracing::warn!("Failed to queue image placeholders for post {}: {:?}", post_id, e);
+    }
+}
+
+/// Edit a post. Only the author may edit; the replaced title and content are
+/// kept as a revision, and the post keeps its slug, likes and comments.
+pub async fn update_post(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<UpdatePostRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    terms::ensure_accepted(&pool, user.id).await?;
+
+    let mut tx = pool.begin().await?;
+    let post = sqlx::query!(
+        r#"
+        SELECT user_id, slug, title, content, content_md, image_count, edited_at
+        FROM posts
+        WHERE id = $1 AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound("Post not found".to_string()))?;
+    if post.user_id != user.id {
+        return Err(AppError::AuthError(
+            "You can only edit your own posts".to_string(),
+        ));
+    }
+
+    let title = payload
+        .title
+        .as_deref()
+        .map_or_else(|| post.title.clone(), clean_html);
+    let (content, content_md) = match payload.content {
+        Some(markdown) => (render_markdown(&markdown), Some(markdown)),
+        None => (post.content.clone(), post.content_md.clone()),
+    };
+    if title == post.title && content == post.content && content_md == post.content_md {
+        return Ok(Json(
+            serde_json::json!({"id": id, "slug": post.slug, "edited_at": post.edited_at}),
+        ));
+    }
+    let images = image_count(&content);
+    if images > post.image_count {
+        quotas::check_images(&mut tx, &config.quotas, user.id, (images - post.image_count).into())
+            .await?;
+    }
+
+    sqlx::query!(
+        "INSERT INTO post_revisions (post_id, title, content, content_md) VALUES ($1, $2, $3, $4)",
+        id,
+        post.title,
+        post.content,
+        post.content_md
+    )
+    .execute(&mut *tx)
+    .await?;
+    let edited_at = sqlx::query_scalar!(
+        r#"
+        UPDATE posts
+        SET title = $2, content = $3, content_md = $4, excerpt = $5,
+            reading_time_minutes = $6, image_count = $7, edited_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        RETURNING edited_at as "edited_at!"
+        "#,
+        id,
+        title,
+        content,
+        content_md,
+        excerpt(&content),
+        reading_time_minutes(&content),
+        images
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    references::replace(&mut tx, id, &references::extract_references(&content)).await?;
+    tx.commit().await?;
+
+    queue_media(&pool, id, &content).await;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "post_edited",
+        actor_id = user.id,
+        post_id = id
+    );
+
+    Ok(Json(
+        serde_json::json!({"id": id, "slug": post.slug, "edited_at": edited_at}),
     ))
 }
 
+/// Earlier versions of a post, most recently replaced first. Only the author
+/// gets their Markdown source.
+pub async fn list_post_revisions(
+    State(pool): State<PgPool>,
+    OptionalAuthUser(user): OptionalAuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let author_id = sqlx::query_scalar!(
+        "SELECT user_id FROM posts WHERE id = $1 AND deleted_at IS NULL",
+        id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Post not found".to_string()))?;
+    let is_author = user.is_some_and(|u| u.id == author_id);
+
+    let revisions = sqlx::query_as!(
+        PostRevision,
+        r#"
+        SELECT id, title, content, CASE WHEN $2 THEN content_md END as content_md, created_at
+        FROM post_revisions
+        WHERE post_id = $1
+        ORDER BY id DESC
+        "#,
+        id,
+        is_author
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(revisions))
+}
+
 /// List posts (Recent first).
 /// Filter out soft-deleted posts.
-/// Supports cursor-based pagination.
+/// Supports cursor-based pagination. Lite clients get summaries instead of the content.
+/// Without `sort`, logged-in users get the order from their settings.
+/// `feed=following` limits the list to authors the caller follows.
+/// Posts of users the caller blocked or muted are left out.
+#[tracing::instrument(skip_all, fields(sort = tracing::field::Empty))]
 pub async fn list_posts(
     State(pool): State<PgPool>,
+    OptionalAuthUser(user): OptionalAuthUser,
+    Lite(lite): Lite,
     Query(params): Query<PostListParams>,
 ) -> Result<impl IntoResponse, AppError> {
     let limit = params.limit.unwrap_or(20).min(100);
-    let sort = params.sort.unwrap_or_else(|| "new".to_string());
+    let viewer_id = user.as_ref().map(|user| user.id);
+    let follower_id = match params.feed.as_deref() {
+        None => None,
+        Some("following") => match &user {
+            Some(user) => Some(user.id),
+            None => {
+                return Err(AppError::AuthError(
+                    "Log in to see posts from people you follow".to_string(),
+                ));
+            }
+        },
+        Some(feed) => return Err(AppError::BadRequest(format!("Unknown feed '{}'", feed))),
+    };
+    let sort = match (params.sort, user) {
+        (Some(sort), _) => sort,
+        (None, Some(user)) => user_settings::default_post_sort(&pool, user.id)
+            .await?
+            .as_str()
+            .to_string(),
+        (None, None) => "new".to_string(),
+    };
+    tracing::Span::current().record("sort", sort.as_str());
     
     // Prepare search pattern: "%keyword%"
     let search_pattern = params.q.map(|k| format!("%{}%", k));
@@ -73,13 +289,18 @@ pub async fn list_posts(
             Post,
             r#"
             SELECT 
-                id, user_id, title, content, 
-                created_at, updated_at, deleted_at,
+                id, user_id, slug, title, content, excerpt, reading_time_minutes,
+                created_at, updated_at, edited_at, deleted_at,
                 likes_count, comments_count, favorites_count,
                 FALSE as "is_liked!", FALSE as "is_favorited!"
             FROM posts
             WHERE deleted_at IS NULL
               AND ($2::TEXT IS NULL OR title ILIKE $2)
+              AND ($3::BIGINT IS NULL OR user_id IN (SELECT followee_id FROM follows WHERE follower_id = $3))
+              AND ($4::BIGINT IS NULL OR user_id NOT IN (
+                  SELECT blocked_id FROM user_blocks WHERE blocker_id = $4
+                  UNION ALL SELECT muted_id FROM user_mutes WHERE muter_id = $4
+              ))
             ORDER BY (
                 (likes_count * 5 + comments_count * 3 + favorites_count * 10)::FLOAT / 
                 POW(EXTRACT(EPOCH FROM (NOW() - created_at)) / 3600 + 2, 1.5)
@@ -87,7 +308,9 @@ pub async fn list_posts(
             LIMIT $1
             "#,
             limit,
-            search_pattern
+            search_pattern,
+            follower_id,
+            viewer_id
         )
         .fetch_all(&pool)
         .await
@@ -100,20 +323,27 @@ pub async fn list_posts(
             Post,
             r#"
             SELECT 
-                id, user_id, title, content, 
-                created_at, updated_at, deleted_at,
+                id, user_id, slug, title, content, excerpt, reading_time_minutes,
+                created_at, updated_at, edited_at, deleted_at,
                 likes_count, comments_count, favorites_count,
                 FALSE as "is_liked!", FALSE as "is_favorited!"
             FROM posts
             WHERE deleted_at IS NULL
               AND ($1::TIMESTAMPTZ IS NULL OR created_at < $1)
               AND ($3::TEXT IS NULL OR title ILIKE $3)
+              AND ($4::BIGINT IS NULL OR user_id IN (SELECT followee_id FROM follows WHERE follower_id = $4))
+              AND ($5::BIGINT IS NULL OR user_id NOT IN (
+                  SELECT blocked_id FROM user_blocks WHERE blocker_id = $5
+                  UNION ALL SELECT muted_id FROM user_mutes WHERE muter_id = $5
+              ))
             ORDER BY created_at DESC
             LIMIT $2
             "#,
             params.cursor,
             limit,
-            search_pattern
+            search_pattern,
+            follower_id,
+            viewer_id
         )
         .fetch_all(&pool)
         .await
@@ -123,24 +353,31 @@ pub async fn list_posts(
         })?
     };
 
-    Ok(Json(posts))
+    Ok(lite::respond::<_, PostSummary>(lite, posts))
 }
 
-/// Get a single post by ID.
+/// Get a single post by ID or slug.
 pub async fn get_post(
     State(pool): State<PgPool>,
-    claims: Option<Extension<Claims>>,
-    Path(id): Path<i64>,
+    OptionalAuthUser(user): OptionalAuthUser,
+    Path(key): Path<String>,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = claims.map(|c| c.sub.parse::<i64>().unwrap_or(0));
+    let user_id = user.map(|u| u.id);
+    let id = match Lookup::parse(&key) {
+        Lookup::Id(id) => id,
+        Lookup::Slug(slug) => sqlx::query_scalar!("SELECT id FROM posts WHERE slug = $1", slug)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or(AppError::NotFound("Post not found".to_string()))?,
+    };
 
     let post = if let Some(uid) = user_id {
         sqlx::query_as!(
             Post,
             r#"
             SELECT 
-                p.id, p.user_id, p.title, p.content, 
-                p.created_at, p.updated_at, p.deleted_at,
+                p.id, p.user_id, p.slug, p.title, p.content, p.excerpt, p.reading_time_minutes,
+                p.created_at, p.updated_at, p.edited_at, p.deleted_at,
                 p.likes_count, p.comments_count, p.favorites_count,
                 (EXISTS (SELECT 1 FROM post_likes WHERE user_id = $2 AND post_id = p.id)) as "is_liked!",
                 (EXISTS (SELECT 1 FROM post_favorites WHERE user_id = $2 AND post_id = p.id)) as "is_favorited!"
@@ -161,8 +398,8 @@ pub async fn get_post(
             Post,
             r#"
             SELECT 
-                id, user_id, title, content, 
-                created_at, updated_at, deleted_at,
+                id, user_id, slug, title, content, excerpt, reading_time_minutes,
+                created_at, updated_at, edited_at, deleted_at,
                 likes_count, comments_count, favorites_count,
                 FALSE as "is_liked!", FALSE as "is_favorited!"
             FROM posts
@@ -179,17 +416,44 @@ pub async fn get_post(
     };
 
     let post = post.ok_or(AppError::NotFound("Post not found".to_string()))?;
-    Ok(Json(post))
+    let link_cards = unfurl::cards_for(&pool, &post.content).await?;
+    let images = image_placeholders::placeholders_for(&pool, &post.content).await?;
+    let references = references::links_for_post(&pool, post.id).await?;
+    let series = series::nav_for_post(&pool, post.id).await?;
+    let tags = tags::for_post(&pool, post.id).await?;
+
+    // Only the author gets the Markdown source, to edit from.
+    // Posts written before it was kept fall back to the sanitized content.
+    let content_md = if user_id == Some(post.user_id) {
+        sqlx::query_scalar!(
+            r#"SELECT COALESCE(content_md, content) as "content_md!" FROM posts WHERE id = $1"#,
+            post.id
+        )
+        .fetch_optional(&pool)
+        .await?
+    } else {
+        None
+    };
+
+    Ok(Json(PostDetail {
+        post,
+        content_md,
+        link_cards,
+        images,
+        references,
+        series,
+        tags,
+    }))
 }
 
 /// Delete a post (Soft Delete).
-/// Requires: Login + (Author OR Admin).
+/// Requires: Login + (Author OR a role that moderates content).
 pub async fn delete_post(
     State(pool): State<PgPool>,
-    Extension(claims): Extension<Claims>,
+    user: AuthUser,
     Path(id): Path<i64>,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+    let user_id = user.id;
 
     // 1. Fetch Post to check ownership
     let post = sqlx::query!(
@@ -202,7 +466,7 @@ pub async fn delete_post(
     .ok_or(AppError::NotFound("Post not found".to_string()))?;
 
     // 2. Check Permission
-    if post.user_id != user_id && claims.role != "admin" {
+    if post.user_id != user_id && !user.can(Permission::ModerateContent) {
         return Err(AppError::AuthError(
             "You are not authorized to delete this post".to_string(),
         ));
@@ -216,6 +480,13 @@ pub async fn delete_post(
             tracing::error!("Failed to delete post: {:?}", e);
             AppError::InternalServerError(e.to_string())
         })?;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "post_deleted",
+        actor_id = user_id,
+        post_id = id,
+        author_id = post.user_id
+    );
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -0,0 +1,335 @@
+// src/handlers/competition.rs
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use sqlx::PgPool;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    handlers::qualification::normalize_answer,
+    models::{
+        competition::{
+            Competition, CompetitionPaper, CompetitionResult, CompetitionSubmission,
+            RegisterCompetitionRequest, SubmitCompetitionRequest,
+        },
+        question::PublicQuestion,
+    },
+    services::{competitions, paper_snapshots, terms},
+    utils::{
+        audit_stream,
+        jwt::{AuthUser, OptionalAuthUser},
+        locale::PreferredLocale,
+    },
+};
+
+/// Competitions with what `viewer` has done in them, newest first; only
+/// `id` when given.
+async fn load(
+    pool: &PgPool,
+    viewer: Option<i64>,
+    id: Option<i64>,
+) -> Result<Vec<Competition>, sqlx::Error> {
+    sqlx::query_as!(
+        Competition,
+        r#"
+        SELECT c.id, c.title, c.description, c.starts_at, c.ends_at,
+            cardinality(c.question_ids) as "question_count!",
+            (SELECT COUNT(*) FROM competition_entries e WHERE e.competition_id = c.id) as "registrations!",
+            EXISTS (
+                SELECT 1 FROM competition_entries e WHERE e.competition_id = c.id AND e.user_id = $1
+            ) as "registered!",
+            EXISTS (
+                SELECT 1 FROM competition_entries e
+                WHERE e.competition_id = c.id AND e.user_id = $1 AND e.submitted_at IS NOT NULL
+            ) as "submitted!",
+            EXISTS (
+                SELECT 1 FROM competition_entries e
+                WHERE e.competition_id = c.id AND e.user_id = $1 AND e.proctoring_consent
+            ) as "proctoring_consent!",
+            c.created_at
+        FROM competitions c
+        WHERE ($2::BIGINT IS NULL OR c.id = $2)
+        ORDER BY c.starts_at DESC
+        LIMIT 50
+        "#,
+        viewer,
+        id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Lists competitions, most recently started first.
+pub async fn list_competitions(
+    State(pool): State<PgPool>,
+    OptionalAuthUser(user): OptionalAuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let competitions = load(&pool, user.map(|u| u.id), None).await?;
+    Ok(Json(competitions))
+}
+
+/// Gets a competition.
+pub async fn get_competition(
+    State(pool): State<PgPool>,
+    OptionalAuthUser(user): OptionalAuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let competition = load(&pool, user.map(|u| u.id), Some(id))
+        .await?
+        .pop()
+        .ok_or(AppError::NotFound("Competition not found".to_string()))?;
+    Ok(Json(competition))
+}
+
+/// Registers the current user for a competition. Open until it ends;
+/// registering twice is harmless, and may change the proctoring consent
+/// until the paper is submitted.
+pub async fn register(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+    payload: Option<Json<RegisterCompetitionRequest>>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(payload) = payload.unwrap_or_default();
+    let ended = sqlx::query_scalar!(
+        r#"SELECT ends_at <= NOW() as "ended!" FROM competitions WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Competition not found".to_string()))?;
+    if ended {
+        return Err(AppError::Conflict(
+            "Registration is closed: the competition has ended".to_string(),
+        ));
+    }
+    terms::ensure_accepted(&pool, user.id).await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO competition_entries (competition_id, user_id, proctoring_consent)
+        VALUES ($1, $2, COALESCE($3, FALSE))
+        ON CONFLICT (competition_id, user_id) DO UPDATE
+        SET proctoring_consent = COALESCE($3, competition_entries.proctoring_consent)
+        WHERE competition_entries.submitted_at IS NULL
+        "#,
+        id,
+        user.id,
+        payload.proctoring_consent
+    )
+    .execute(&pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Serves the paper to a registered user while the competition runs, in
+/// their preferred locale where translated. The first time starts their
+/// clock, which breaks ties in the ranking.
+pub async fn get_paper(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    PreferredLocale(locale): PreferredLocale,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let competition = sqlx::query!(
+        r#"
+        SELECT paper_snapshot_id, ends_at, starts_at > NOW() as "upcoming!", ends_at <= NOW() as "ended!"
+        FROM competitions
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Competition not found".to_string()))?;
+    if competition.upcoming {
+        return Err(AppError::Conflict(
+            "The competition has not started yet".to_string(),
+        ));
+    }
+    if competition.ended {
+        return Err(AppError::Conflict("The competition has ended".to_string()));
+    }
+
+    let entry = sqlx::query!(
+        r#"
+        UPDATE competition_entries
+        SET started_at = COALESCE(started_at, NOW())
+        WHERE competition_id = $1 AND user_id = $2
+        RETURNING started_at as "started_at!", submitted_at
+        "#,
+        id,
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::AuthError(
+        "Register for this competition first".to_string(),
+    ))?;
+    if entry.submitted_at.is_some() {
+        return Err(AppError::Conflict(
+            "You have already submitted this competition".to_string(),
+        ));
+    }
+
+    let questions = competitions::paper(&pool, competition.paper_snapshot_id, &locale)
+        .await?
+        .into_iter()
+        .map(PublicQuestion::from_question)
+        .collect();
+
+    Ok((
+        [(header::CONTENT_LANGUAGE, locale)],
+        Json(CompetitionPaper {
+            competition_id: id,
+            questions,
+            started_at: entry.started_at,
+            ends_at: competition.ends_at,
+        }),
+    ))
+}
+
+/// Submits the current user's answers. A paper is scored once, as the
+/// number of correct answers against the paper as it was set; later
+/// submissions are refused. Proctoring signals are kept only if the user
+/// consented when registering.
+pub async fn submit(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+    Json(req): Json<SubmitCompetitionRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    req.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let competition = sqlx::query!(
+        r#"SELECT question_ids, paper_snapshot_id, ends_at <= NOW() as "ended!" FROM competitions WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Competition not found".to_string()))?;
+    if competition.ended {
+        return Err(AppError::Conflict("The competition has ended".to_string()));
+    }
+    for qid in req.answers.keys() {
+        if !competition.question_ids.contains(qid) {
+            return Err(AppError::BadRequest(format!(
+                "Question ID {} is not on this paper.",
+                qid
+            )));
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    let entry = sqlx::query!(
+        "SELECT started_at, submitted_at, proctoring_consent FROM competition_entries WHERE competition_id = $1 AND user_id = $2 FOR UPDATE",
+        id,
+        user.id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::AuthError(
+        "Register for this competition first".to_string(),
+    ))?;
+    if entry.submitted_at.is_some() {
+        return Err(AppError::Conflict(
+            "You have already submitted this competition".to_string(),
+        ));
+    }
+    if entry.started_at.is_none() {
+        return Err(AppError::BadRequest(
+            "Open the paper before submitting".to_string(),
+        ));
+    }
+
+    let score = paper_snapshots::load(&mut *tx, competition.paper_snapshot_id)
+        .await?
+        .iter()
+        .filter(|q| {
+            req.answers
+                .get(&q.id)
+                .is_some_and(|ans| normalize_answer(ans, None) == normalize_answer(&q.answer, None))
+        })
+        .count() as i32;
+
+    let answers = serde_json::to_value(&req.answers)?;
+    let signals = req.proctoring.filter(|_| entry.proctoring_consent);
+    let submitted_at = sqlx::query_scalar!(
+        r#"
+        UPDATE competition_entries
+        SET submitted_at = NOW(), answers = $3, score = $4, focus_loss_count = $5, paste_count = $6
+        WHERE competition_id = $1 AND user_id = $2
+        RETURNING submitted_at as "submitted_at!"
+        "#,
+        id,
+        user.id,
+        answers,
+        score,
+        signals.as_ref().map(|s| s.focus_loss_count),
+        signals.as_ref().map(|s| s.paste_count)
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "competition_submitted",
+        actor_id = user.id,
+        competition_id = id,
+        score
+    );
+    Ok(Json(CompetitionSubmission {
+        score,
+        total_questions: competition.question_ids.len() as i32,
+        submitted_at,
+    }))
+}
+
+/// The final ranking, published once the competition ends.
+///
+/// Ties on score go to whoever took less time from opening the paper to
+/// submitting it, then to the earlier submission; ranks are never shared.
+pub async fn get_results(
+    State(pool): State<PgPool>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let ends_at = sqlx::query_scalar!("SELECT ends_at FROM competitions WHERE id = $1", id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(AppError::NotFound("Competition not found".to_string()))?;
+    if ends_at > chrono::Utc::now() {
+        return Err(AppError::Conflict(format!(
+            "Results are published when the competition ends at {}",
+            ends_at.to_rfc3339()
+        )));
+    }
+
+    let results = sqlx::query_as!(
+        CompetitionResult,
+        r#"
+        SELECT
+            ROW_NUMBER() OVER (
+                ORDER BY e.score DESC, e.submitted_at - e.started_at, e.submitted_at, e.user_id
+            ) as "rank!",
+            e.user_id, u.username, u.avatar_url,
+            e.score as "score!",
+            (EXTRACT(EPOCH FROM e.submitted_at - e.started_at) * 1000)::BIGINT as "duration_ms!",
+            e.submitted_at as "submitted_at!"
+        FROM competition_entries e
+        JOIN users u ON u.id = e.user_id
+        WHERE e.competition_id = $1 AND e.submitted_at IS NOT NULL
+        ORDER BY 1
+        "#,
+        id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(results))
+}
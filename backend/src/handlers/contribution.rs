@@ -1,60 +1,103 @@
-use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
 use sqlx::PgPool;
 use validator::Validate;
 
 use crate::{
+    config::{CONTRIBUTION_MAX_ATTACHMENTS, Config},
     error::AppError,
     models::{
-        architecture::CreateArchRequest, contribution::CreateContributionRequest,
+        architecture::CreateArchRequest,
+        contribution::{
+            AttachmentParams, Contribution, ContributionAttachment, ContributionCheck, ContributorLeaderboard,
+            ContributorRanking, CreateContributionMessageRequest, CreateContributionRequest,
+            DuplicateMatch,
+        },
         question::CreateQuestionRequest,
     },
-    utils::jwt::VerifiedUser,
+    services::{
+        contribution_attachments, contribution_messages,
+        contribution_review::{self, Published},
+        image_placeholders,
+    },
+    storage::Storage,
+    utils::{
+        audit_stream,
+        html::clean_html,
+        jwt::{AuthUser, VerifiedUser},
+    },
 };
 
-/// Submit a new contribution.
-/// Enforces "once per day" via DB index and strict data validation.
-pub async fn create_contribution(
-    State(pool): State<PgPool>,
-    user: VerifiedUser,
-    Json(payload): Json<CreateContributionRequest>,
-) -> Result<impl IntoResponse, AppError> {
+/// The contribution data, parsed into the request type it will be published with.
+enum ContributionData {
+    Architecture(CreateArchRequest),
+    Question(CreateQuestionRequest),
+}
+
+/// Strict validation shared by submission and the pre-check endpoint.
+fn parse_contribution(payload: &CreateContributionRequest) -> Result<ContributionData, AppError> {
     // 1. Basic validation
     payload
         .validate()
         .map_err(|e| AppError::BadRequest(e.to_string()))?;
 
     // 2. Strict Payload Validation
-    if payload.r#type != "architecture" && payload.r#type != "question" {
-        return Err(AppError::BadRequest(
-            "Invalid contribution type".to_string(),
-        ));
-    }
-
-    // We try to deserialize the JSON 'data' to ensure it's valid for the target type.
+    // We deserialize the JSON 'data' and validate it as the target type would be.
     match payload.r#type.as_str() {
         "architecture" => {
-            let _: CreateArchRequest = serde_json::from_value(payload.data.clone())
+            let data: CreateArchRequest = serde_json::from_value(payload.data.clone())
+                .map_err(|e| AppError::BadRequest(format!("Invalid architecture data: {}", e)))?;
+            data.validate()
                 .map_err(|e| AppError::BadRequest(format!("Invalid architecture data: {}", e)))?;
+            Ok(ContributionData::Architecture(data))
         }
         "question" => {
-            let _: CreateQuestionRequest = serde_json::from_value(payload.data.clone())
+            let data: CreateQuestionRequest = serde_json::from_value(payload.data.clone())
+                .map_err(|e| AppError::BadRequest(format!("Invalid question data: {}", e)))?;
+            data.validate()
                 .map_err(|e| AppError::BadRequest(format!("Invalid question data: {}", e)))?;
+            Ok(ContributionData::Question(data))
         }
-        _ => unreachable!(), // Handled by validator
+        _ => Err(AppError::BadRequest(
+            "Invalid contribution type".to_string(),
+        )),
     }
+}
+
+/// Submit a new contribution.
+/// Enforces "once per day" via DB index and strict data validation.
+/// Contributions the auto-approval rules allow are published right away.
+pub async fn create_contribution(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    user: VerifiedUser,
+    Json(payload): Json<CreateContributionRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    parse_contribution(&payload)?;
 
     // 3. Insert into DB
-    let id = sqlx::query!(
+    let mut tx = pool.begin().await?;
+    let contrib = sqlx::query_as!(
+        Contribution,
         r#"
         INSERT INTO contributions (user_id, type, data)
         VALUES ($1, $2, $3)
-        RETURNING id
+        RETURNING id, user_id, type, data, status, auto_approved, 0::BIGINT as "message_count!",
+            created_at, reviewed_at
         "#,
         user.id,
         payload.r#type,
         payload.data
     )
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
         // Handle "once per day" unique constraint violation
@@ -67,8 +110,324 @@ pub async fn create_contribution(
             tracing::error!("Failed to submit contribution: {:?}", e);
             AppError::InternalServerError(e.to_string())
         }
-    })?
-    .id;
+    })?;
+
+    // 4. Publish without review if the user is trusted with this type
+    let auto_approved =
+        contribution_review::auto_approves(&mut tx, &config.auto_approval, user.id, &contrib.r#type)
+            .await?;
+    let mut new_cover = None;
+    if auto_approved {
+        if let Published::Architecture { id, cover_img } =
+            contribution_review::publish(&mut tx, &contrib).await?
+        {
+            new_cover = Some((id, cover_img));
+        }
+        sqlx::query!(
+            "UPDATE contributions SET status = 'approved', auto_approved = TRUE, reviewed_at = NOW() WHERE id = $1",
+            contrib.id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    if auto_approved {
+        tracing::info!(
+            target: audit_stream::TARGET,
+            action = "contribution_auto_approved",
+            actor_id = user.id,
+            contribution_id = contrib.id
+        );
+    }
+    if let Some((arch_id, cover_img)) = new_cover {
+        image_placeholders::queue_cover(&pool, arch_id, cover_img).await;
+    }
+    let status = if auto_approved { "approved" } else { "pending" };
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({ "id": contrib.id, "status": status })),
+    ))
+}
+
+/// Attaches a reference image or PDF scan to one of the user's pending
+/// contributions. The body is the raw file; its type is read from its first
+/// bytes. Only reviewers can open attachments.
+pub async fn upload_attachment(
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn Storage>>,
+    user: VerifiedUser,
+    Path(id): Path<i64>,
+    Query(params): Query<AttachmentParams>,
+    body: Bytes,
+) -> Result<impl IntoResponse, AppError> {
+    let kind = contribution_attachments::sniff(&body).ok_or(AppError::BadRequest(
+        "Attachments must be JPEG, PNG or WebP images or PDF files".to_string(),
+    ))?;
+
+    let mut tx = pool.begin().await?;
+    // Locking the contribution serializes uploads, so the count below holds.
+    let status = sqlx::query_scalar!(
+        "SELECT status FROM contributions WHERE id = $1 AND user_id = $2 FOR UPDATE",
+        id,
+        user.id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound("Contribution not found".to_string()))?;
+    if status != "pending" {
+        return Err(AppError::BadRequest(
+            "Attachments can only be added before review".to_string(),
+        ));
+    }
+    let count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM contribution_attachments WHERE contribution_id = $1"#,
+        id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if count >= CONTRIBUTION_MAX_ATTACHMENTS {
+        return Err(AppError::BadRequest(format!(
+            "A contribution can have at most {} attachments",
+            CONTRIBUTION_MAX_ATTACHMENTS
+        )));
+    }
+
+    let key = contribution_attachments::storage_key(id, kind);
+    let filename = contribution_attachments::display_name(params.filename.as_deref(), kind);
+    let size_bytes = body.len() as i64;
+    storage.put(&key, body.to_vec(), kind.content_type).await?;
+
+    let saved = async {
+        let attachment = sqlx::query_as!(
+            ContributionAttachment,
+            r#"
+            INSERT INTO contribution_attachments (contribution_id, storage_key, filename, content_type, size_bytes)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, filename, content_type, size_bytes, created_at, NULL::TEXT as download_url
+            "#,
+            id,
+            key,
+            filename,
+            kind.content_type,
+            size_bytes
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(attachment)
+    }
+    .await;
+    let attachment = match saved {
+        Ok(attachment) => attachment,
+        Err(e) => {
+            // Nothing refers to the file, so the retention job would never find it.
+            if let Err(e) = storage.delete(&key).await {
+                tracing::warn!("Attachment {} not deleted: {}", key, e);
+            }
+            return Err(e.into());
+        }
+    };
+
+    Ok((StatusCode::CREATED, Json(attachment)))
+}
+
+/// The review thread of one of the user's contributions, oldest first.
+pub async fn list_messages(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    sqlx::query_scalar!(
+        "SELECT id FROM contributions WHERE id = $1 AND user_id = $2",
+        id,
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Contribution not found".to_string()))?;
+
+    Ok(Json(contribution_messages::list(&pool, id).await?))
+}
+
+/// Answers the reviewers of one of the user's pending contributions.
+/// Reviewers who have written in the thread are notified.
+pub async fn post_message(
+    State(pool): State<PgPool>,
+    user: VerifiedUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<CreateContributionMessageRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let mut tx = pool.begin().await?;
+    let status = sqlx::query_scalar!(
+        "SELECT status FROM contributions WHERE id = $1 AND user_id = $2 FOR UPDATE",
+        id,
+        user.id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound("Contribution not found".to_string()))?;
+    if status != "pending" {
+        return Err(AppError::BadRequest(
+            "Messages can only be added before review".to_string(),
+        ));
+    }
+
+    let message =
+        contribution_messages::post(&mut tx, id, user.id, user.id, false, &payload.body).await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(message)))
+}
+
+/// Runs the submission checks without inserting anything.
+///
+/// Returns 400 with the same message submitting would, or a preview of the
+/// sanitized data with likely duplicates. Does not count as the daily submission.
+pub async fn validate_contribution(
+    State(pool): State<PgPool>,
+    user: VerifiedUser,
+    Json(payload): Json<CreateContributionRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let (cleaned, duplicates) = match parse_contribution(&payload)? {
+        ContributionData::Architecture(data) => {
+            let duplicates = sqlx::query_as!(
+                DuplicateMatch,
+                r#"
+                SELECT 'architecture' as "source!", id as "id!", name as "title!"
+                FROM architectures WHERE LOWER(name) = LOWER($1)
+                UNION ALL
+                SELECT 'contribution', id, data->>'name'
+                FROM contributions
+                WHERE type = 'architecture' AND status = 'pending'
+                  AND LOWER(data->>'name') = LOWER($1)
+                "#,
+                data.name
+            )
+            .fetch_all(&pool)
+            .await?;
+
+            let loc = data.structured_location();
+            let cleaned = serde_json::json!({
+                "category": data.category,
+                "name": data.name,
+                "dynasty": data.dynasty,
+                "location": data.location,
+                "province": loc.province,
+                "city": loc.city,
+                "district": loc.district,
+                "description": clean_html(&data.description),
+                "cover_img": data.cover_img,
+                "carousel_imgs": data.carousel_imgs,
+            });
+            (cleaned, duplicates)
+        }
+        ContributionData::Question(data) => {
+            let content = clean_html(&data.content);
+            let duplicates = sqlx::query_as!(
+                DuplicateMatch,
+                r#"
+                SELECT 'question' as "source!", id as "id!", content as "title!"
+                FROM questions WHERE content = $1
+                UNION ALL
+                SELECT 'contribution', id, data->>'content'
+                FROM contributions
+                WHERE type = 'question' AND status = 'pending' AND data->>'content' = $2
+                "#,
+                content,
+                data.content
+            )
+            .fetch_all(&pool)
+            .await?;
+
+            let cleaned = serde_json::json!({
+                "question_type": data.question_type,
+                "content": content,
+                "options": data.options,
+                "answer": clean_html(&data.answer),
+                "analysis": data.analysis.map(|a| clean_html(&a)),
+            });
+            (cleaned, duplicates)
+        }
+    };
+
+    let submitted_today = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM contributions
+            WHERE user_id = $1
+              AND CAST(created_at AT TIME ZONE 'UTC' AS DATE) = CAST(NOW() AT TIME ZONE 'UTC' AS DATE)
+        ) as "exists!"
+        "#,
+        user.id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(ContributionCheck {
+        r#type: payload.r#type,
+        cleaned,
+        duplicates,
+        can_submit_today: !submitted_today,
+    }))
+}
+
+/// Time window for the contributor leaderboard.
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardParams {
+    /// '7d', '30d' (default) or 'all'.
+    pub window: Option<String>,
+    /// How many contributors to list (default 10, max 50).
+    pub limit: Option<i64>,
+}
+
+/// Ranks contributors by how many of their submissions were approved in the
+/// window. Admins and the ghost user are left out.
+pub async fn get_leaderboard(
+    State(pool): State<PgPool>,
+    Query(params): Query<LeaderboardParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let window = params.window.unwrap_or_else(|| "30d".to_string());
+    let since = match window.as_str() {
+        "7d" => Some(chrono::Utc::now() - chrono::Duration::days(7)),
+        "30d" => Some(chrono::Utc::now() - chrono::Duration::days(30)),
+        "all" => None,
+        _ => {
+            return Err(AppError::BadRequest(
+                "window must be one of '7d', '30d', 'all'".to_string(),
+            ));
+        }
+    };
+    let limit = params.limit.unwrap_or(10).clamp(1, 50);
+
+    let entries = sqlx::query_as!(
+        ContributorRanking,
+        r#"
+        SELECT
+            RANK() OVER (ORDER BY COUNT(*) DESC) as "rank!",
+            u.username,
+            COUNT(*) as "approved!",
+            COUNT(*) FILTER (WHERE c.type = 'architecture') as "architectures!",
+            COUNT(*) FILTER (WHERE c.type = 'question') as "questions!"
+        FROM contributions c
+        JOIN users u ON u.id = c.user_id
+        WHERE c.status = 'approved'
+          AND ($1::TIMESTAMPTZ IS NULL OR c.reviewed_at >= $1)
+          AND u.role <> 'admin'
+          AND u.username <> 'ghost'
+        GROUP BY u.id, u.username
+        ORDER BY COUNT(*) DESC, MAX(c.reviewed_at), u.username
+        LIMIT $2
+        "#,
+        since,
+        limit
+    )
+    .fetch_all(&pool)
+    .await?;
 
-    Ok((StatusCode::CREATED, Json(serde_json::json!({ "id": id }))))
+    Ok(Json(ContributorLeaderboard { window, entries }))
 }
@@ -0,0 +1,20 @@
+// src/handlers/docs.rs
+
+use axum::{Json, response::IntoResponse};
+
+use crate::api_docs::{self, Audience};
+
+/// The OpenAPI document of the public, read-only API.
+pub async fn public_document() -> impl IntoResponse {
+    Json(api_docs::document(Audience::Public))
+}
+
+/// The OpenAPI document for logged-in users.
+pub async fn user_document() -> impl IntoResponse {
+    Json(api_docs::document(Audience::User))
+}
+
+/// The OpenAPI document including the admin endpoints.
+pub async fn admin_document() -> impl IntoResponse {
+    Json(api_docs::document(Audience::Admin))
+}
@@ -0,0 +1,33 @@
+// src/handlers/feed.rs
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+
+use crate::{error::AppError, services::feed, state::AppState, utils::jwt::OptionalAuthUser};
+
+#[derive(Debug, Deserialize)]
+pub struct FeedParams {
+    /// Number of items to return (default: 20, max: 50).
+    pub limit: Option<usize>,
+}
+
+/// Mixed discovery feed of trending posts, new architectures and unanswered posts.
+/// Personalised by interaction history when the caller is logged in.
+pub async fn discover(
+    State(state): State<AppState>,
+    OptionalAuthUser(user): OptionalAuthUser,
+    Query(params): Query<FeedParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.map(|u| u.id);
+    let limit = params.limit.unwrap_or(20).clamp(1, 50);
+
+    let affinity = feed::load_affinity(&state.pool, user_id).await?;
+    let candidates = feed::load_candidates(&state.pool).await?;
+    let items = feed::rank(candidates, &affinity, state.feed_scorer.as_ref(), limit);
+
+    Ok(Json(items))
+}
@@ -0,0 +1,52 @@
+// src/handlers/files.rs
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+
+use crate::{
+    config::Config,
+    error::AppError,
+    storage::{self, Storage, local},
+};
+
+/// Signature carried by URLs from `LocalStorage::signed_url`.
+#[derive(Debug, Deserialize)]
+pub struct SignedFileParams {
+    pub expires: u64,
+    pub signature: String,
+}
+
+/// Serves a stored file to holders of a valid, unexpired signed URL.
+pub async fn serve_signed_file(
+    State(config): State<Config>,
+    State(storage): State<Arc<dyn Storage>>,
+    Path(key): Path<String>,
+    Query(params): Query<SignedFileParams>,
+) -> Result<impl IntoResponse, AppError> {
+    if !local::verify(config.jwt_secret.as_bytes(), &key, params.expires, &params.signature) {
+        return Err(AppError::AuthError("Invalid or expired link".to_string()));
+    }
+
+    let bytes = storage
+        .get(&key)
+        .await?
+        .ok_or(AppError::NotFound("File not found".to_string()))?;
+    let filename = key.rsplit('/').next().unwrap_or(&key).to_string();
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, storage::content_type_for(&key).to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}\"", filename),
+            ),
+        ],
+        bytes,
+    ))
+}
@@ -0,0 +1,97 @@
+// src/handlers/follow.rs
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use sqlx::PgPool;
+
+use crate::{
+    error::AppError,
+    models::user::{Follower, FollowerListParams},
+    utils::jwt::AuthUser,
+};
+
+/// Follows a user, or unfollows them if already following.
+/// Their posts then show up in the `following` post feed.
+pub async fn toggle_follow(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(followee_id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    if followee_id == user.id {
+        return Err(AppError::BadRequest(
+            "You cannot follow yourself".to_string(),
+        ));
+    }
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM users WHERE id = $1) as "exists!""#,
+        followee_id
+    )
+    .fetch_one(&pool)
+    .await?;
+    if !exists {
+        return Err(AppError::NotFound("User not found".to_string()));
+    }
+
+    let unfollowed = sqlx::query!(
+        "DELETE FROM follows WHERE follower_id = $1 AND followee_id = $2",
+        user.id,
+        followee_id
+    )
+    .execute(&pool)
+    .await?
+    .rows_affected()
+        > 0;
+    if !unfollowed {
+        // A concurrent follow already inserted the row; the outcome is the same.
+        sqlx::query!(
+            "INSERT INTO follows (follower_id, followee_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            user.id,
+            followee_id
+        )
+        .execute(&pool)
+        .await?;
+    }
+
+    Ok(Json(serde_json::json!({ "following": !unfollowed })))
+}
+
+/// Followers of a user, most recent first.
+pub async fn list_followers(
+    State(pool): State<PgPool>,
+    Path(id): Path<i64>,
+    Query(params): Query<FollowerListParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM users WHERE id = $1) as "exists!""#,
+        id
+    )
+    .fetch_one(&pool)
+    .await?;
+    if !exists {
+        return Err(AppError::NotFound("User not found".to_string()));
+    }
+
+    let followers = sqlx::query_as!(
+        Follower,
+        r#"
+        SELECT u.id, u.username, u.avatar_url, f.created_at as followed_at
+        FROM follows f
+        JOIN users u ON u.id = f.follower_id
+        WHERE f.followee_id = $1
+          AND ($2::TIMESTAMPTZ IS NULL OR f.created_at < $2)
+        ORDER BY f.created_at DESC
+        LIMIT $3
+        "#,
+        id,
+        params.cursor,
+        limit
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(followers))
+}
@@ -0,0 +1,82 @@
+// src/handlers/glossary.rs
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{
+    config::PINYIN_WORD_SIMILARITY, error::AppError, models::glossary::GlossaryTerm,
+    utils::pinyin::normalize_query,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct GlossaryParams {
+    /// Matches the term itself, its pinyin (full, partial or initials), or close misspellings.
+    pub q: Option<String>,
+}
+
+/// Lists glossary terms alphabetically by pinyin, optionally filtered by `q`.
+pub async fn list_terms(
+    State(pool): State<PgPool>,
+    Query(params): Query<GlossaryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let pattern = params.q.as_ref().map(|k| format!("%{}%", k));
+    let pinyin = params.q.as_deref().and_then(normalize_query);
+
+    let mut tx = pool.begin().await?;
+    // `<%` can use the trigram index; it compares against this setting.
+    sqlx::query!(
+        "SELECT set_config('pg_trgm.word_similarity_threshold', $1, true)",
+        PINYIN_WORD_SIMILARITY.to_string()
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let terms = sqlx::query_as!(
+        GlossaryTerm,
+        r#"
+        SELECT id, slug, term, term_pinyin, definition, created_at, updated_at
+        FROM glossary_terms
+        WHERE $1::TEXT IS NULL
+           OR term ILIKE $1
+           OR ($2::TEXT IS NOT NULL AND (
+                REPLACE(term_pinyin, ' ', '') LIKE '%' || $2 || '%'
+                OR term_initials LIKE $2 || '%'
+                OR $2 <% REPLACE(term_pinyin, ' ', '')
+           ))
+        ORDER BY term_pinyin, id
+        "#,
+        pattern,
+        pinyin
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(terms))
+}
+
+/// Retrieves a single glossary term by slug.
+pub async fn get_term(
+    State(pool): State<PgPool>,
+    Path(slug): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let term = sqlx::query_as!(
+        GlossaryTerm,
+        r#"
+        SELECT id, slug, term, term_pinyin, definition, created_at, updated_at
+        FROM glossary_terms
+        WHERE slug = $1
+        "#,
+        slug
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Glossary term not found".to_string()))?;
+
+    Ok(Json(term))
+}
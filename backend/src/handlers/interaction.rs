@@ -1,5 +1,5 @@
 use axum::{
-    Extension, Json,
+    Json,
     extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
@@ -8,19 +8,25 @@ use sqlx::PgPool;
 use validator::Validate;
 
 use crate::{
+    config::Config,
     error::AppError,
     models::comment::{CommentListParams, CommentResponse, CreateCommentRequest},
-    utils::jwt::Claims,
+    services::{blocks, comment_throttle, notifications, quotas, terms},
+    utils::jwt::{AuthUser, OptionalAuthUser},
     utils::html::clean_html,
+    utils::permissions::Permission,
+    utils::audit_stream,
 };
 
 /// Toggle Like on a post.
+/// A like notifies the post's author, batched like comments.
 pub async fn toggle_like(
     State(pool): State<PgPool>,
-    Extension(claims): Extension<Claims>,
+    State(config): State<Config>,
+    user: AuthUser,
     Path(post_id): Path<i64>,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+    let user_id = user.id;
 
     let mut tx = pool
         .begin()
@@ -74,13 +80,25 @@ pub async fn toggle_like(
             AppError::InternalServerError(e.to_string())
         })?;
 
-        sqlx::query!(
-            "UPDATE posts SET likes_count = likes_count + 1 WHERE id = $1",
+        let author_id = sqlx::query_scalar!(
+            "UPDATE posts SET likes_count = likes_count + 1 WHERE id = $1 RETURNING user_id",
             post_id
         )
-        .execute(&mut *tx)
+        .fetch_one(&mut *tx)
         .await
         .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        // The update above holds the post's row lock
+        if author_id != user_id {
+            notifications::notify_like(
+                &mut tx,
+                author_id,
+                post_id,
+                user_id,
+                config.comment_notification_window_secs,
+            )
+            .await?;
+        }
     }
 
     tx.commit()
@@ -93,10 +111,11 @@ pub async fn toggle_like(
 /// Toggle Favorite on a post.
 pub async fn toggle_favorite(
     State(pool): State<PgPool>,
-    Extension(claims): Extension<Claims>,
+    State(config): State<Config>,
+    user: AuthUser,
     Path(post_id): Path<i64>,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+    let user_id = user.id;
 
     let mut tx = pool
         .begin()
@@ -132,6 +151,8 @@ pub async fn toggle_favorite(
         .await
         .map_err(|e| AppError::InternalServerError(e.to_string()))?;
     } else {
+        quotas::check_favorite(&mut tx, &config.quotas, user_id).await?;
+
         sqlx::query!(
             "INSERT INTO post_favorites (user_id, post_id) VALUES ($1, $2)",
             user_id,
@@ -161,25 +182,32 @@ pub async fn toggle_favorite(
 }
 
 /// Create a new comment.
+/// The post's author is notified, with comments in quick succession batched together.
+/// A reply also notifies the author of the comment it answers, unless that is
+/// the post's author, who hears of it anyway.
 pub async fn create_comment(
     State(pool): State<PgPool>,
-    Extension(claims): Extension<Claims>,
+    State(config): State<Config>,
+    user: AuthUser,
     Path(post_id): Path<i64>,
     Json(payload): Json<CreateCommentRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     payload
         .validate()
         .map_err(|e| AppError::BadRequest(e.to_string()))?;
-    let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+    let user_id = user.id;
+    terms::ensure_accepted(&pool, user_id).await?;
 
     let mut tx = pool.begin().await?;
+    comment_throttle::check(&mut tx, user_id, post_id).await?;
 
     // 1. Determine root_id and parent_id for nested comments
     let mut root_id: Option<i64> = None;
+    let mut parent_author_id = None;
     if let Some(pid) = payload.parent_id {
         // Fetch parent to determine the thread's root
         let parent = sqlx::query!(
-            "SELECT id, root_id FROM comments WHERE id = $1 AND post_id = $2",
+            "SELECT id, root_id, user_id, (deleted_at IS NOT NULL) as \"deleted!\" FROM comments WHERE id = $1 AND post_id = $2",
             pid,
             post_id
         )
@@ -189,6 +217,9 @@ pub async fn create_comment(
 
         // If the parent already belongs to a root, use it; otherwise, the parent is the root.
         root_id = Some(parent.root_id.unwrap_or(parent.id));
+        if !parent.deleted {
+            parent_author_id = Some(parent.user_id);
+        }
     }
 
     // 2. Sanitize input to ensure safety
@@ -212,13 +243,48 @@ pub async fn create_comment(
     .id;
 
     // 3. Update Post Count
-    sqlx::query!(
-        "UPDATE posts SET comments_count = comments_count + 1 WHERE id = $1",
+    let author_id = sqlx::query_scalar!(
+        "UPDATE posts SET comments_count = comments_count + 1 WHERE id = $1 RETURNING user_id",
         post_id
     )
-    .execute(&mut *tx)
+    .fetch_one(&mut *tx)
     .await?;
 
+    // Users the post's author or the replied-to author blocked may not answer them
+    let addressees: Vec<i64> = std::iter::once(author_id).chain(parent_author_id).collect();
+    if blocks::blocked_by_any(&mut *tx, user_id, &addressees).await? {
+        return Err(AppError::AuthError(
+            "You cannot reply to this user".to_string(),
+        ));
+    }
+
+    // 4. Notify the author; the update above holds the post's row lock
+    if author_id != user_id {
+        notifications::notify_comment(
+            &mut tx,
+            author_id,
+            post_id,
+            new_id,
+            config.comment_notification_window_secs,
+        )
+        .await?;
+    }
+    if let Some(parent_author_id) =
+        parent_author_id.filter(|&id| id != user_id && id != author_id)
+    {
+        notifications::notify(
+            &mut *tx,
+            parent_author_id,
+            notifications::COMMENT_REPLY,
+            serde_json::json!({
+                "post_id": post_id,
+                "comment_id": new_id,
+                "parent_id": payload.parent_id,
+            }),
+        )
+        .await?;
+    }
+
     tx.commit().await?;
 
     Ok((
@@ -227,9 +293,58 @@ pub async fn create_comment(
     ))
 }
 
+/// Delete a comment (Soft Delete). Replies to it stay.
+/// Requires: Login + (Author OR a role that moderates content).
+pub async fn delete_comment(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path((post_id, comment_id)): Path<(i64, i64)>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let author_id = sqlx::query_scalar!(
+        "SELECT user_id FROM comments WHERE id = $1 AND post_id = $2 AND deleted_at IS NULL FOR UPDATE",
+        comment_id,
+        post_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound("Comment not found".to_string()))?;
+
+    if author_id != user.id && !user.can(Permission::ModerateContent) {
+        return Err(AppError::AuthError(
+            "You are not authorized to delete this comment".to_string(),
+        ));
+    }
+
+    sqlx::query!("UPDATE comments SET deleted_at = NOW() WHERE id = $1", comment_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!(
+        "UPDATE posts SET comments_count = GREATEST(comments_count - 1, 0) WHERE id = $1",
+        post_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "comment_deleted",
+        actor_id = user.id,
+        comment_id,
+        post_id,
+        author_id
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// List all comments for a post.
+/// Comments of users the caller blocked or muted are left out.
 pub async fn list_comments(
     State(pool): State<PgPool>,
+    OptionalAuthUser(user): OptionalAuthUser,
     Path(post_id): Path<i64>,
     Query(params): Query<CommentListParams>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -245,12 +360,17 @@ pub async fn list_comments(
         FROM comments c
         JOIN users u ON c.user_id = u.id
         WHERE c.post_id = $1 AND c.deleted_at IS NULL
+          AND ($4::BIGINT IS NULL OR c.user_id NOT IN (
+              SELECT blocked_id FROM user_blocks WHERE blocker_id = $4
+              UNION ALL SELECT muted_id FROM user_mutes WHERE muter_id = $4
+          ))
         ORDER BY c.created_at ASC
         LIMIT $2 OFFSET $3
         "#,
         post_id,
         limit,
-        offset
+        offset,
+        user.map(|user| user.id)
     )
     .fetch_all(&pool)
     .await?;
@@ -0,0 +1,176 @@
+// src/handlers/message.rs
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sqlx::PgPool;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::message::{
+        ConversationList, ConversationListParams, ConversationSummary, DirectMessage,
+        MessageListParams, SendMessageRequest,
+    },
+    services::{blocks, messages, terms},
+    utils::html::clean_html,
+    utils::jwt::AuthUser,
+    utils::permissions::Role,
+};
+
+/// Sends a private message to a user.
+///
+/// Starting a conversation takes a verified user or staff; once it exists,
+/// either side may reply. Users who blocked one another cannot message.
+pub async fn send_message(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(recipient_id): Path<i64>,
+    Json(payload): Json<SendMessageRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if recipient_id == user.id {
+        return Err(AppError::BadRequest(
+            "You cannot message yourself".to_string(),
+        ));
+    }
+    terms::ensure_accepted(&pool, user.id).await?;
+
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM users WHERE id = $1) as "exists!""#,
+        recipient_id
+    )
+    .fetch_one(&pool)
+    .await?;
+    if !exists {
+        return Err(AppError::NotFound("User not found".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+    if blocks::between(&mut *tx, user.id, recipient_id).await? {
+        return Err(AppError::AuthError(
+            "You cannot message this user".to_string(),
+        ));
+    }
+    if messages::find(&mut tx, user.id, recipient_id)
+        .await?
+        .is_none()
+    {
+        let sender = sqlx::query!("SELECT is_verified, role FROM users WHERE id = $1", user.id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !sender.is_verified && !Role::parse(&sender.role).is_some_and(Role::is_staff) {
+            return Err(AppError::AuthError(
+                "You must be a verified contributor to start a conversation.".to_string(),
+            ));
+        }
+    }
+
+    let message =
+        messages::send(&mut tx, user.id, recipient_id, &clean_html(&payload.body)).await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(message)))
+}
+
+/// The current user's conversations, most recently active first, with
+/// unread counts.
+pub async fn list_conversations(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Query(params): Query<ConversationListParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+
+    let conversations = sqlx::query_as!(
+        ConversationSummary,
+        r#"
+        SELECT c.id, u.id as user_id, u.username, u.avatar_url,
+            last.body as "last_message?", c.last_message_at,
+            (SELECT COUNT(*) FROM messages m
+             WHERE m.conversation_id = c.id AND m.sender_id <> $1 AND m.read_at IS NULL) as "unread!"
+        FROM conversations c
+        JOIN users u ON u.id = CASE WHEN c.user_low_id = $1 THEN c.user_high_id ELSE c.user_low_id END
+        LEFT JOIN LATERAL (
+            SELECT body FROM messages WHERE conversation_id = c.id ORDER BY id DESC LIMIT 1
+        ) last ON TRUE
+        WHERE (c.user_low_id = $1 OR c.user_high_id = $1)
+          AND ($2::TIMESTAMPTZ IS NULL OR c.last_message_at < $2)
+        ORDER BY c.last_message_at DESC
+        LIMIT $3
+        "#,
+        user.id,
+        params.cursor,
+        limit
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let unread = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM messages m
+        JOIN conversations c ON c.id = m.conversation_id
+        WHERE (c.user_low_id = $1 OR c.user_high_id = $1)
+          AND m.sender_id <> $1 AND m.read_at IS NULL
+        "#,
+        user.id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(ConversationList {
+        unread,
+        conversations,
+    }))
+}
+
+/// Messages of one of the current user's conversations, newest first.
+/// Marks the messages the user received there as read.
+pub async fn list_messages(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+    Query(params): Query<MessageListParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    sqlx::query_scalar!(
+        "SELECT id FROM conversations WHERE id = $1 AND (user_low_id = $2 OR user_high_id = $2)",
+        id,
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Conversation not found".to_string()))?;
+
+    let messages = sqlx::query_as!(
+        DirectMessage,
+        r#"
+        SELECT id, conversation_id, sender_id, body, created_at, read_at
+        FROM messages
+        WHERE conversation_id = $1 AND ($2::BIGINT IS NULL OR id < $2)
+        ORDER BY id DESC
+        LIMIT $3
+        "#,
+        id,
+        params.before,
+        limit
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE messages SET read_at = NOW() WHERE conversation_id = $1 AND sender_id <> $2 AND read_at IS NULL",
+        id,
+        user.id
+    )
+    .execute(&pool)
+    .await?;
+
+    Ok(Json(messages))
+}
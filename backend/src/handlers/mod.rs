@@ -3,9 +3,25 @@
 pub mod admin;
 pub mod architecture;
 pub mod auth;
+pub mod block;
+pub mod certificate;
 pub mod community;
+pub mod competition;
 pub mod contribution;
+pub mod docs;
+pub mod feed;
+pub mod files;
+pub mod follow;
+pub mod glossary;
 pub mod interaction;
+pub mod message;
+pub mod notification;
 pub mod profile;
 pub mod qualification;
+pub mod question;
 pub mod quiz;
+pub mod series;
+pub mod stats;
+pub mod sync;
+pub mod tags;
+pub mod terms;
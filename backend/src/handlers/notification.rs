@@ -0,0 +1,72 @@
+// src/handlers/notification.rs
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use sqlx::PgPool;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::notification::{MarkReadRequest, Notification, NotificationList, NotificationParams},
+    utils::jwt::AuthUser,
+};
+
+/// The current user's latest notifications, newest first.
+pub async fn list_notifications(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Query(params): Query<NotificationParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let notifications = sqlx::query_as!(
+        Notification,
+        r#"
+        SELECT id, kind, payload, created_at, read_at
+        FROM notifications
+        WHERE user_id = $1 AND (NOT $2 OR read_at IS NULL)
+        ORDER BY created_at DESC, id DESC
+        LIMIT 50
+        "#,
+        user.id,
+        params.unread_only
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let unread = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM notifications WHERE user_id = $1 AND read_at IS NULL"#,
+        user.id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(NotificationList {
+        unread,
+        notifications,
+    }))
+}
+
+/// Marks the given notifications, or all of them, as read.
+pub async fn mark_read(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<MarkReadRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE notifications SET read_at = NOW()
+        WHERE user_id = $1 AND read_at IS NULL AND ($2::BIGINT[] IS NULL OR id = ANY($2))
+        "#,
+        user.id,
+        payload.ids.as_deref()
+    )
+    .execute(&pool)
+    .await?
+    .rows_affected();
+
+    Ok(Json(serde_json::json!({ "updated": updated })))
+}
@@ -1,32 +1,65 @@
+use std::{sync::Arc, time::Duration};
+
 use axum::{
-    Extension, Json,
-    extract::{Query, State},
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
 use sqlx::PgPool;
+use validator::Validate;
 
 use crate::{
+    config::{
+        ACTIVITY_HEATMAP_DAYS, Config, DATA_EXPORT_MIN_INTERVAL_HOURS, DATA_EXPORT_URL_SECS,
+    },
     error::AppError,
     models::{
+        certificate::CertificateResponse,
         contribution::Contribution,
-        post::{Post, PostListParams},
-        user::{FavoritePostResponse, MeResponse},
+        exam_record::{AccuracyBucket, QuizStats},
+        api_usage::UsageResponse,
+        post::{Post, PostListParams, PostSummary},
+        security_event::SecurityEventParams,
+        stats::{ActivityDay, ActivityHeatmap},
+        user::{
+            AccountDeletionResponse, ActivityItem, ActivityParams, DataExport, DeleteAccountRequest, FavoritePostResponse, MeResponse,
+            PublicProfile, UpdateEmailRequest, UpdateLocaleRequest, UpdateProfileRequest,
+            UserSettings,
+        },
+    },
+    jobs::queue,
+    services::{
+        account_deletion, api_usage, bans, data_exports, email_verification, exam_accommodations, experiments, quotas,
+        security_events, sessions, user_settings,
+    },
+    state::AppState,
+    storage::Storage,
+    utils::{
+        audit_stream,
+        hash::verify_password,
+        html::excerpt,
+        jwt::{AuthUser, bearer_token},
+        lite::{self, Lite},
+        locale::canonical_locale,
     },
-    utils::jwt::Claims,
 };
 
 /// Get current user's profile and statistics.
+#[tracing::instrument(skip_all)]
 pub async fn get_me(
     State(pool): State<PgPool>,
-    Extension(claims): Extension<Claims>,
+    user: AuthUser,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+    let user_id = user.id;
 
     // Using subqueries for counts is efficient given our indexes on user_id and post_id.
     let me = sqlx::query!(
         r#"
         SELECT 
-            u.id, u.username, u.role, u.is_verified, u.created_at,
+            u.id, u.username, u.role, u.is_verified, u.locale, u.birth_year, u.region, u.created_at,
+            u.email, (u.email_verified_at IS NOT NULL) as "email_verified!", u.deletion_scheduled_at,
+            u.bio, u.location, u.website, u.avatar_url,
             (SELECT COUNT(*) FROM posts WHERE user_id = u.id AND deleted_at IS NULL) as posts_count,
             (SELECT COUNT(*) FROM post_likes pl JOIN posts p ON pl.post_id = p.id WHERE p.user_id = u.id) as total_likes_received
         FROM users u
@@ -38,33 +71,246 @@ pub async fn get_me(
     .await?
     .ok_or(AppError::NotFound("User not found".to_string()))?;
 
+    let experiments = experiments::assignments(me.id);
+    experiments::record_exposures(&pool, me.id, &experiments).await;
+    let exam_accommodations = exam_accommodations::find(&pool, me.id).await?;
+    let ban = bans::active(&pool, me.id).await?;
+
     Ok(Json(MeResponse {
         id: me.id,
         username: me.username,
         role: me.role,
         is_verified: me.is_verified,
+        locale: me.locale,
+        birth_year: me.birth_year,
+        region: me.region,
+        email: me.email,
+        email_verified: me.email_verified,
         created_at: me.created_at,
+        bio: me.bio,
+        location: me.location,
+        website: me.website,
+        avatar_url: me.avatar_url,
         posts_count: me.posts_count.unwrap_or(0),
         total_likes_received: me.total_likes_received.unwrap_or(0),
+        experiments,
+        exam_accommodations,
+        deletion_scheduled_at: me.deletion_scheduled_at,
+        ban,
     }))
 }
 
+/// Replaces the current user's public profile fields.
+pub async fn update_profile(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<UpdateProfileRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let cleaned = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let profile = sqlx::query!(
+        r#"
+        UPDATE users SET bio = $1, location = $2, website = $3, avatar_url = $4
+        WHERE id = $5
+        RETURNING bio, location, website, avatar_url
+        "#,
+        cleaned(payload.bio),
+        cleaned(payload.location),
+        cleaned(payload.website),
+        cleaned(payload.avatar_url),
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    Ok(Json(serde_json::json!({
+        "bio": profile.bio,
+        "location": profile.location,
+        "website": profile.website,
+        "avatar_url": profile.avatar_url,
+    })))
+}
+
+/// A user's public profile, for clicking through from a post or comment author.
+pub async fn get_user_profile(
+    State(pool): State<PgPool>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let profile = sqlx::query_as!(
+        PublicProfile,
+        r#"
+        SELECT
+            u.id, u.username, u.avatar_url, u.bio, u.location, u.website, u.is_verified,
+            (SELECT COUNT(*) FROM posts WHERE user_id = u.id AND deleted_at IS NULL) as "posts_count!",
+            (SELECT COUNT(*) FROM follows WHERE followee_id = u.id) as "followers_count!",
+            (SELECT COUNT(*) FROM follows WHERE follower_id = u.id) as "following_count!",
+            u.created_at as joined_at
+        FROM users u
+        WHERE u.id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    Ok(Json(profile))
+}
+
+/// A user's posts, comments and approved contributions, newest first.
+/// Deleted posts and comments, and comments on deleted posts, are left out.
+pub async fn get_user_activity(
+    State(pool): State<PgPool>,
+    Path(id): Path<i64>,
+    Query(params): Query<ActivityParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM users WHERE id = $1) as "exists!""#,
+        id
+    )
+    .fetch_one(&pool)
+    .await?;
+    if !exists {
+        return Err(AppError::NotFound("User not found".to_string()));
+    }
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT kind as "kind!", id as "id!", title, body, post_id, contribution_type,
+            published_id, created_at as "created_at!"
+        FROM (
+            SELECT 'post' as kind, p.id, p.title, p.excerpt as body, NULL::BIGINT as post_id,
+                NULL::TEXT as contribution_type, NULL::BIGINT as published_id, p.created_at
+            FROM posts p
+            WHERE p.user_id = $1 AND p.deleted_at IS NULL
+            UNION ALL
+            SELECT 'comment', c.id, p.title, c.content, c.post_id, NULL, NULL, c.created_at
+            FROM comments c
+            JOIN posts p ON p.id = c.post_id
+            WHERE c.user_id = $1 AND c.deleted_at IS NULL AND p.deleted_at IS NULL
+            UNION ALL
+            SELECT 'contribution', ct.id, ct.data->>'name', ct.data->>'content', NULL, ct.type,
+                COALESCE(a.id, q.id), COALESCE(ct.reviewed_at, ct.created_at)
+            FROM contributions ct
+            LEFT JOIN architectures a ON a.contribution_id = ct.id
+            LEFT JOIN questions q ON q.contribution_id = ct.id
+            WHERE ct.user_id = $1 AND ct.status = 'approved'
+        ) activity
+        WHERE $2::TIMESTAMPTZ IS NULL OR created_at < $2
+        ORDER BY created_at DESC, id DESC
+        LIMIT $3
+        "#,
+        id,
+        params.cursor,
+        limit
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let items: Vec<ActivityItem> = rows
+        .into_iter()
+        .map(|row| {
+            // Posts store their excerpt; comments and questions are cut here.
+            let excerpt = match row.kind.as_str() {
+                "post" => row.body,
+                _ => row.body.as_deref().map(excerpt),
+            };
+            ActivityItem {
+                kind: row.kind,
+                id: row.id,
+                title: row.title,
+                excerpt,
+                post_id: row.post_id,
+                contribution_type: row.contribution_type,
+                published_id: row.published_id,
+                created_at: row.created_at,
+            }
+        })
+        .collect();
+
+    Ok(Json(items))
+}
+
+/// Schedules the current user's account for deletion, after confirming their
+/// password. Until the grace period is over the account works as before and
+/// the deletion can be cancelled; then posts and comments pass to the ghost
+/// user, as when an admin deletes an account, and everything else is deleted.
+pub async fn delete_me(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<DeleteAccountRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let account = sqlx::query!("SELECT username, password FROM users WHERE id = $1", user.id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
+    if account.username == "ghost" {
+        return Err(AppError::BadRequest("Cannot delete the ghost user".to_string()));
+    }
+    if !verify_password(&payload.password, &account.password)? {
+        return Err(AppError::AuthError("Invalid password".to_string()));
+    }
+
+    let deletion_scheduled_at = account_deletion::schedule(&pool, user.id).await?;
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "account_deletion_scheduled",
+        user_id = user.id,
+        deletion_scheduled_at = %deletion_scheduled_at
+    );
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(AccountDeletionResponse {
+            deletion_scheduled_at,
+        }),
+    ))
+}
+
+/// Cancels the current user's pending account deletion.
+pub async fn cancel_account_deletion(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    if !account_deletion::cancel(&pool, user.id).await? {
+        return Err(AppError::NotFound("No account deletion is pending".to_string()));
+    }
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "account_deletion_cancelled",
+        user_id = user.id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// List posts created by the current user.
 /// Includes real interaction status (is_liked, is_favorited).
+/// Lite clients get summaries instead of the content.
 pub async fn list_my_posts(
     State(pool): State<PgPool>,
-    Extension(claims): Extension<Claims>,
+    user: AuthUser,
+    Lite(lite): Lite,
     Query(params): Query<PostListParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+    let user_id = user.id;
     let limit = params.limit.unwrap_or(20).min(100);
 
     let posts = sqlx::query_as!(
         Post,
         r#"
         SELECT 
-            p.id, p.user_id, p.title, p.content, 
-            p.created_at, p.updated_at, p.deleted_at,
+            p.id, p.user_id, p.slug, p.title, p.content, p.excerpt, p.reading_time_minutes,
+            p.created_at, p.updated_at, p.edited_at, p.deleted_at,
             p.likes_count, p.comments_count, p.favorites_count,
             (pl.user_id IS NOT NULL) as "is_liked!",
             (pf.user_id IS NOT NULL) as "is_favorited!"
@@ -83,15 +329,15 @@ pub async fn list_my_posts(
     .fetch_all(&pool)
     .await?;
 
-    Ok(Json(posts))
+    Ok(lite::respond::<_, PostSummary>(lite, posts))
 }
 
 /// List posts favorited by the current user.
 pub async fn list_my_favorites(
     State(pool): State<PgPool>,
-    Extension(claims): Extension<Claims>,
+    user: AuthUser,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+    let user_id = user.id;
 
     let favorites = sqlx::query_as!(
         FavoritePostResponse,
@@ -117,14 +363,16 @@ pub async fn list_my_favorites(
 /// This is open to all logged-in users to view their own history.
 pub async fn list_my_contributions(
     State(pool): State<PgPool>,
-    Extension(claims): Extension<Claims>,
+    user: AuthUser,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+    let user_id = user.id;
 
     let list = sqlx::query_as!(
         Contribution,
         r#"
-        SELECT id, user_id, type, data, status, admin_comment, created_at, reviewed_at
+        SELECT id, user_id, type, data, status, auto_approved,
+            (SELECT COUNT(*) FROM contribution_messages m WHERE m.contribution_id = contributions.id) as "message_count!",
+            created_at, reviewed_at
         FROM contributions
         WHERE user_id = $1
         ORDER BY created_at DESC
@@ -136,3 +384,486 @@ pub async fn list_my_contributions(
 
     Ok(Json(list))
 }
+
+/// List certificates issued to the current user.
+pub async fn list_my_certificates(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id;
+
+    let list = sqlx::query_as!(
+        CertificateResponse,
+        r#"
+        SELECT c.code, u.username, c.kind, c.score, c.issued_at,
+               (c.pdf_key IS NOT NULL) as "pdf_ready!"
+        FROM certificates c
+        JOIN users u ON c.user_id = u.id
+        WHERE c.user_id = $1
+        ORDER BY c.issued_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(list))
+}
+
+/// The current user's latest data export, with a download link once ready.
+pub async fn get_data_export(
+    State(pool): State<PgPool>,
+    State(storage): State<Arc<dyn Storage>>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let mut conn = pool.acquire().await?;
+    let export = latest_data_export(&mut conn, user.id)
+        .await?
+        .ok_or(AppError::NotFound("No data export requested".to_string()))?;
+    Ok(Json(with_download_url(export, storage.as_ref()).await?))
+}
+
+/// Queues an archive of everything the current user has put on the site. While
+/// one is being written the same export is returned; a new one can be
+/// requested every `DATA_EXPORT_MIN_INTERVAL_HOURS`, or straight after a failure.
+pub async fn request_data_export(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        "SELECT pg_advisory_xact_lock(hashtext('data_export:' || $1::BIGINT))",
+        user.id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if let Some(latest) = latest_data_export(&mut tx, user.id).await? {
+        if latest.status == "pending" {
+            return Ok((StatusCode::ACCEPTED, Json(latest)));
+        }
+        let next_at = latest.created_at + chrono::Duration::hours(DATA_EXPORT_MIN_INTERVAL_HOURS);
+        let wait = (next_at - chrono::Utc::now()).num_seconds();
+        if latest.status != "failed" && wait > 0 {
+            return Err(AppError::RateLimited {
+                message: "A data export was requested recently".to_string(),
+                retry_after_secs: wait as u64,
+            });
+        }
+    }
+
+    let id = sqlx::query_scalar!(r#"SELECT nextval('data_exports_id_seq') as "id!""#)
+        .fetch_one(&mut *tx)
+        .await?;
+    let job_id = queue::enqueue(
+        &mut *tx,
+        data_exports::EXPORT_USER_DATA_JOB,
+        serde_json::json!({ "export_id": id }),
+    )
+    .await?;
+    let export = sqlx::query_as!(
+        DataExport,
+        r#"
+        INSERT INTO data_exports (id, user_id, job_id) VALUES ($1, $2, $3)
+        RETURNING id, 'pending' as "status!", created_at, ready_at, expires_at, size_bytes,
+            NULL::TEXT as download_url
+        "#,
+        id,
+        user.id,
+        job_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "data_export_requested",
+        user_id = user.id,
+        export_id = id
+    );
+    Ok((StatusCode::ACCEPTED, Json(export)))
+}
+
+/// The user's most recent data export, without a download link.
+async fn latest_data_export(
+    conn: &mut sqlx::PgConnection,
+    user_id: i64,
+) -> Result<Option<DataExport>, sqlx::Error> {
+    sqlx::query_as!(
+        DataExport,
+        r#"
+        SELECT e.id,
+            CASE
+                WHEN e.ready_at IS NOT NULL AND e.storage_key IS NOT NULL AND e.expires_at > NOW() THEN 'ready'
+                WHEN e.ready_at IS NOT NULL THEN 'expired'
+                WHEN j.status = 'failed' THEN 'failed'
+                ELSE 'pending'
+            END as "status!",
+            e.created_at, e.ready_at, e.expires_at, e.size_bytes, NULL::TEXT as download_url
+        FROM data_exports e
+        LEFT JOIN jobs j ON j.id = e.job_id
+        WHERE e.user_id = $1
+        ORDER BY e.id DESC
+        LIMIT 1
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+}
+
+async fn with_download_url(
+    mut export: DataExport,
+    storage: &dyn Storage,
+) -> Result<DataExport, AppError> {
+    if export.status == "ready" {
+        let url = storage
+            .signed_url(
+                &data_exports::archive_key(export.id),
+                Duration::from_secs(DATA_EXPORT_URL_SECS),
+            )
+            .await?;
+        export.download_url = Some(url);
+    }
+    Ok(export)
+}
+
+/// How much of each quota the current user has used.
+pub async fn get_limits(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let mut conn = pool.acquire().await?;
+    let status = quotas::status(&mut conn, &config.quotas, user.id).await?;
+
+    Ok(Json(status))
+}
+
+/// Devices the current user is signed in on, most recently used first.
+pub async fn list_sessions(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let token = bearer_token(&headers).unwrap_or_default();
+    let list = sessions::list(&pool, user.id, token).await?;
+
+    Ok(Json(list))
+}
+
+/// Recent sign-ins, failed sign-in attempts, registration and password
+/// changes on the current user's account, newest first, with the address and
+/// device each came from.
+pub async fn list_security_events(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Query(params): Query<SecurityEventParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let list = security_events::list_for_user(&pool, user.id, params.cursor, limit).await?;
+
+    Ok(Json(list))
+}
+
+/// Signs the current user out of one device; its token stops working.
+pub async fn revoke_session(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    if !sessions::revoke(&pool, user.id, id).await? {
+        return Err(AppError::NotFound("Session not found".to_string()));
+    }
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "session_revoked",
+        user_id = user.id,
+        session_id = id
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Set (or clear, with `null`) the current user's preferred content language.
+pub async fn update_locale(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<UpdateLocaleRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id;
+
+    let locale = match payload.locale.as_deref() {
+        None => None,
+        Some(tag) => Some(canonical_locale(tag).ok_or_else(|| {
+            AppError::BadRequest(format!("Unsupported locale '{}'", tag))
+        })?),
+    };
+
+    sqlx::query!("UPDATE users SET locale = $1 WHERE id = $2", locale, user_id)
+        .execute(&pool)
+        .await?;
+
+    Ok(Json(serde_json::json!({ "locale": locale })))
+}
+
+/// The current user's settings.
+pub async fn get_settings(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let settings = user_settings::load(&pool, user.id)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
+    Ok(Json(settings))
+}
+
+/// Replaces the current user's settings. Unknown keys and values are
+/// rejected with 400; omitted keys are reset to their defaults.
+pub async fn update_settings(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut settings: UserSettings = serde_json::from_value(payload)
+        .map_err(|e| AppError::BadRequest(format!("Invalid settings: {}", e)))?;
+    if let Some(tag) = settings.language.as_deref() {
+        let locale = canonical_locale(tag)
+            .ok_or_else(|| AppError::BadRequest(format!("Unsupported locale '{}'", tag)))?;
+        settings.language = Some(locale.to_string());
+    }
+
+    user_settings::save(&pool, user.id, &settings).await?;
+
+    Ok(Json(settings))
+}
+
+/// Sets the current user's email address and emails a verification link.
+/// Giving the current address again re-sends the link until it is verified.
+pub async fn update_email(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    user: AuthUser,
+    Json(payload): Json<UpdateEmailRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let email = payload.email.trim();
+
+    if email_verification::is_taken(&pool, email, Some(user.id)).await? {
+        return Err(AppError::Conflict("This email address is already in use".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+    let current = sqlx::query!(
+        r#"
+        SELECT email, (email_verified_at IS NOT NULL) as "verified!"
+        FROM users WHERE id = $1 FOR UPDATE
+        "#,
+        user.id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if current.verified && current.email.as_deref() == Some(email) {
+        return Ok(Json(serde_json::json!({ "email": email, "email_verified": true })));
+    }
+
+    sqlx::query!(
+        "UPDATE users SET email = $1, email_verified_at = NULL WHERE id = $2",
+        email,
+        user.id
+    )
+    .execute(&mut *tx)
+    .await?;
+    email_verification::start(&mut tx, &config.mail, user.id, email).await?;
+    tx.commit().await?;
+
+    Ok(Json(serde_json::json!({ "email": email, "email_verified": false })))
+}
+
+/// Daily activity over the past year for a GitHub-style heatmap: contributions,
+/// posts and quiz papers per UTC day, cached per user for ten minutes.
+pub async fn get_activity_heatmap(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    if let Some(heatmap) = state.activity_heatmaps.get(&user.id) {
+        return Ok(Json(heatmap));
+    }
+
+    let to = chrono::Utc::now().date_naive();
+    let from = to - chrono::Days::new(ACTIVITY_HEATMAP_DAYS as u64 - 1);
+    let days = sqlx::query_as!(
+        ActivityDay,
+        r#"
+        WITH events AS (
+            SELECT created_at, 'contribution' as kind FROM contributions
+            WHERE user_id = $1 AND created_at >= $2::DATE
+            UNION ALL
+            SELECT created_at, 'post' FROM posts
+            WHERE user_id = $1 AND created_at >= $2::DATE AND deleted_at IS NULL
+            UNION ALL
+            SELECT created_at, 'quiz' FROM quiz_attempts
+            WHERE user_id = $1 AND created_at >= $2::DATE
+        )
+        SELECT
+            (created_at AT TIME ZONE 'UTC')::DATE as "date!",
+            COUNT(*) FILTER (WHERE kind = 'contribution') as "contributions!",
+            COUNT(*) FILTER (WHERE kind = 'post') as "posts!",
+            COUNT(*) FILTER (WHERE kind = 'quiz') as "quizzes!",
+            COUNT(*) as "total!"
+        FROM events
+        GROUP BY 1
+        ORDER BY 1
+        "#,
+        user.id,
+        from
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let heatmap = ActivityHeatmap {
+        from,
+        to,
+        total: days.iter().map(|d| d.total).sum(),
+        days,
+        generated_at: chrono::Utc::now(),
+    };
+    state.activity_heatmaps.insert(user.id, heatmap.clone());
+    Ok(Json(heatmap))
+}
+
+/// The current user's API requests per day over the last 30 days.
+pub async fn get_usage(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let days = api_usage::history(&state.pool, user.id).await?;
+
+    Ok(Json(UsageResponse {
+        sample_every: state.usage.every(),
+        total: days.iter().map(|d| d.requests).sum(),
+        days,
+    }))
+}
+
+/// Practice statistics: accuracy by question type and difficulty, streaks and
+/// the user's percentile among all players.
+pub async fn get_quiz_stats(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id;
+
+    let by_type = sqlx::query_as!(
+        AccuracyBucket,
+        r#"
+        SELECT
+            q.type as "label!",
+            COUNT(*) as "answered!",
+            COUNT(*) FILTER (WHERE a.is_correct) as "correct!",
+            ROUND(100.0 * AVG(a.is_correct::INT), 1)::FLOAT8 as "accuracy!"
+        FROM quiz_answers a
+        JOIN quiz_attempts t ON t.id = a.attempt_id
+        JOIN questions q ON q.id = a.question_id
+        WHERE t.user_id = $1
+        GROUP BY q.type
+        ORDER BY q.type
+        "#,
+        user_id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    let by_difficulty = sqlx::query_as!(
+        AccuracyBucket,
+        r#"
+        WITH difficulty AS (
+            SELECT question_id,
+                   CASE WHEN AVG(is_correct::INT) >= 0.7 THEN 'easy'
+                        WHEN AVG(is_correct::INT) >= 0.4 THEN 'medium'
+                        ELSE 'hard' END as level
+            FROM quiz_answers
+            GROUP BY question_id
+        )
+        SELECT
+            d.level as "label!",
+            COUNT(*) as "answered!",
+            COUNT(*) FILTER (WHERE a.is_correct) as "correct!",
+            ROUND(100.0 * AVG(a.is_correct::INT), 1)::FLOAT8 as "accuracy!"
+        FROM quiz_answers a
+        JOIN quiz_attempts t ON t.id = a.attempt_id
+        JOIN difficulty d ON d.question_id = a.question_id
+        WHERE t.user_id = $1
+        GROUP BY d.level
+        ORDER BY MIN(CASE d.level WHEN 'easy' THEN 1 WHEN 'medium' THEN 2 ELSE 3 END)
+        "#,
+        user_id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    // Gaps and islands: consecutive days share the same (day - row_number).
+    let streaks = sqlx::query!(
+        r#"
+        WITH days AS (
+            SELECT DISTINCT (created_at AT TIME ZONE 'UTC')::DATE as day
+            FROM quiz_attempts
+            WHERE user_id = $1
+        ),
+        runs AS (
+            SELECT MAX(day) as last_day, COUNT(*) as len
+            FROM (SELECT day, day - ROW_NUMBER() OVER (ORDER BY day)::INT as grp FROM days) d
+            GROUP BY grp
+        )
+        SELECT
+            COALESCE(MAX(len), 0) as "longest!",
+            COALESCE(MAX(len) FILTER (
+                WHERE last_day >= (NOW() AT TIME ZONE 'UTC')::DATE - 1
+            ), 0) as "current!"
+        FROM runs
+        "#,
+        user_id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    let ranking = sqlx::query!(
+        r#"
+        WITH best AS (
+            SELECT user_id, MAX(score) as best FROM quiz_attempts GROUP BY user_id
+        ),
+        ranked AS (
+            SELECT user_id, best, PERCENT_RANK() OVER (ORDER BY best) as pr FROM best
+        )
+        SELECT
+            (SELECT COUNT(*) FROM quiz_attempts WHERE user_id = $1) as "attempts!",
+            (SELECT best FROM ranked WHERE user_id = $1) as best_score,
+            (SELECT ROUND((100 * pr)::NUMERIC, 1)::FLOAT8 FROM ranked WHERE user_id = $1) as percentile
+        "#,
+        user_id
+    )
+    .fetch_one(&pool)
+    .await?;
+
+    let questions_answered: i64 = by_type.iter().map(|b| b.answered).sum();
+    let correct_answers: i64 = by_type.iter().map(|b| b.correct).sum();
+    let accuracy = if questions_answered > 0 {
+        (1000.0 * correct_answers as f64 / questions_answered as f64).round() / 10.0
+    } else {
+        0.0
+    };
+
+    Ok(Json(QuizStats {
+        attempts: ranking.attempts,
+        questions_answered,
+        correct_answers,
+        accuracy,
+        best_score: ranking.best_score,
+        percentile: ranking.percentile,
+        current_streak_days: streaks.current,
+        longest_streak_days: streaks.longest,
+        by_type,
+        by_difficulty,
+    }))
+}
@@ -1,34 +1,66 @@
 use std::collections::HashMap;
 
-use axum::{Extension, Json, extract::State, response::IntoResponse};
+use axum::{Json, extract::State, http::header, response::IntoResponse};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
-use sqlx::{PgPool, Postgres};
+use sqlx::PgPool;
 
 use crate::{
-    config::{Config, EXAM_QUESTION_COUNT, PASSING_SCORE_PERCENTAGE},
+    config::{Config, EXAM_DURATION_SECS, EXAM_QUESTION_COUNT, PASSING_SCORE_PERCENTAGE},
     error::AppError,
     models::{
         exam_record::{ExamResponse, SubmitExamRequest},
-        question::{PublicQuestion, Question},
+        question::PublicQuestion,
     },
-    utils::jwt::Claims as AuthClaims,
+    services::{certificates, exam_accommodations, paper_snapshots, question_selection},
+    utils::{jwt::AuthUser, locale::PreferredLocale},
 };
 
 /// JWT Claims for the exam session to prevent tampering.
 #[derive(Debug, Serialize, Deserialize)]
 struct ExamClaims {
+    /// The user the exam was issued to; its deadline may be theirs alone.
+    pub sub: i64,
     /// List of question IDs assigned to the user.
     pub qids: Vec<i64>,
+    /// The snapshot of the questions taken when the exam was served, which
+    /// it is scored against. It also keeps the shuffled option order, as the
+    /// token is signed but readable.
+    pub sid: i64,
     /// Expiration timestamp.
     pub exp: usize,
 }
 
-/// Helper struct for fetching answer keys.
-#[derive(sqlx::FromRow)]
-struct AnswerKey {
-    id: i64,
-    answer: String,
+/// Shuffles the options in place and returns the permutation applied,
+/// where `perm[shown_index] = original_index`.
+fn shuffle_options(options: &mut Vec<String>) -> Vec<u8> {
+    let mut perm: Vec<u8> = (0..options.len() as u8).collect();
+    perm.shuffle(&mut rand::thread_rng());
+
+    let original = std::mem::take(options);
+    *options = perm.iter().map(|&i| original[i as usize].clone()).collect();
+    perm
+}
+
+/// Maps option letters picked on a shuffled paper back to the original letters.
+/// The result is upper-cased, sorted and deduplicated so "CA" and "AC" compare equal.
+pub(crate) fn normalize_answer(answer: &str, perm: Option<&[u8]>) -> String {
+    let mut letters: Vec<char> = answer
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| {
+            let c = c.to_ascii_uppercase();
+            let shown = (c as u8 - b'A') as usize;
+            match perm.and_then(|p| p.get(shown)) {
+                Some(&original) => (b'A' + original) as char,
+                None => c,
+            }
+        })
+        .collect();
+    letters.sort_unstable();
+    letters.dedup();
+    letters.into_iter().collect()
 }
 
 /// Helper function to calculate score.
@@ -44,10 +76,10 @@ fn calculate_score(
     }
 
     for (q_id, user_ans) in user_answers {
-        if let Some(correct_ans) = db_answers.get(q_id) {
-            if user_ans == correct_ans {
-                correct_count += 1;
-            }
+        if let Some(correct_ans) = db_answers.get(q_id)
+            && user_ans == correct_ans
+        {
+            correct_count += 1;
         }
     }
 
@@ -55,33 +87,56 @@ fn calculate_score(
     (correct_count, score)
 }
 
-/// Generates a qualification exam with 20 random questions and an ExamToken.
+/// Generates a qualification exam with 20 random questions and an ExamToken,
+/// in the caller's preferred locale where translated. The questions are
+/// snapshotted, and the exam is scored against the snapshot.
+///
+/// Accommodations granted to the caller shorten the paper and extend the
+/// deadline carried by the token.
 pub async fn generate_exam(
     State(pool): State<PgPool>,
     State(config): State<Config>,
+    user: AuthUser,
+    PreferredLocale(locale): PreferredLocale,
 ) -> Result<impl IntoResponse, AppError> {
-    let questions = sqlx::query_as!(
-        Question,
-        r#"
-        SELECT
-            id, type as "question_type", content,
-            options as "options: sqlx::types::Json<Vec<String>>",
-            answer, analysis, created_at
-        FROM questions
-        ORDER BY RANDOM()
-        LIMIT $1
-        "#,
-        EXAM_QUESTION_COUNT
-    )
-    .fetch_all(&pool)
-    .await?;
+    let accommodations = exam_accommodations::find(&pool, user.id)
+        .await?
+        .unwrap_or_default();
+    let count = accommodations
+        .question_count
+        .map_or(EXAM_QUESTION_COUNT, i64::from)
+        .min(EXAM_QUESTION_COUNT);
+
+    let mut questions = question_selection::pick_questions(&pool, None, count, &locale).await?;
 
     let qids: Vec<i64> = questions.iter().map(|q| q.id).collect();
+    let mut conn = pool.acquire().await?;
+    let (sid, _) = paper_snapshots::take(&mut conn, &qids).await?;
+
+    // Shuffle options per delivery so shared letter keys ("always A") are useless.
+    let perms: HashMap<i64, Vec<u8>> = questions
+        .iter_mut()
+        .map(|q| {
+            let perm = shuffle_options(&mut q.options.0);
+            let images = std::mem::take(&mut q.option_images.0);
+            q.option_images.0 = perm
+                .iter()
+                .map(|&i| images.get(i as usize).cloned().flatten())
+                .collect();
+            (q.id, perm)
+        })
+        .collect();
+    paper_snapshots::set_option_orders(&mut *conn, sid, &perms).await?;
 
-    // Create Exam Token (Expires in 15 minutes)
-    let expires_in = 900; // 15 mins
+    // Create Exam Token (Expires in 15 minutes, longer with extended time)
+    let expires_in = (EXAM_DURATION_SECS as f64 * accommodations.time_multiplier).round() as usize;
     let exp = (chrono::Utc::now().timestamp() as usize) + expires_in;
-    let claims = ExamClaims { qids, exp };
+    let claims = ExamClaims {
+        sub: user.id,
+        qids,
+        sid,
+        exp,
+    };
 
     let exam_token = encode(
         &Header::default(),
@@ -92,26 +147,25 @@ pub async fn generate_exam(
 
     let public_questions: Vec<PublicQuestion> = questions
         .into_iter()
-        .map(|q| PublicQuestion {
-            id: q.id,
-            question_type: q.question_type,
-            content: q.content,
-            options: q.options,
-        })
+        .map(PublicQuestion::from_question)
         .collect();
 
-    Ok(Json(ExamResponse {
-        questions: public_questions,
-        exam_token,
-        expires_in: expires_in as u64,
-    }))
+    Ok((
+        [(header::CONTENT_LANGUAGE, locale)],
+        Json(ExamResponse {
+            questions: public_questions,
+            exam_token,
+            expires_in: expires_in as u64,
+            accommodations,
+        }),
+    ))
 }
 
 /// Submits the qualification exam with ExamToken verification.
 pub async fn submit_exam(
     State(pool): State<PgPool>,
     State(config): State<Config>,
-    Extension(claims): Extension<AuthClaims>,
+    user: AuthUser,
     Json(req): Json<SubmitExamRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     // 1. Verify Exam Token
@@ -124,7 +178,18 @@ pub async fn submit_exam(
         AppError::BadRequest("Invalid or expired exam token. Please restart the exam.".to_string())
     })?;
 
-    let allowed_qids = token_data.claims.qids;
+    let ExamClaims {
+        sub,
+        qids: allowed_qids,
+        sid,
+        ..
+    } = token_data.claims;
+
+    if sub != user.id {
+        return Err(AppError::BadRequest(
+            "This exam was issued to another user. Please restart the exam.".to_string(),
+        ));
+    }
 
     // 2. Security Check: Ensure user submitted exactly the questions we gave them.
     for qid in req.answers.keys() {
@@ -142,30 +207,31 @@ pub async fn submit_exam(
         ));
     }
 
-    // 3. Fetch Answer Keys
-    let mut query_builder =
-        sqlx::QueryBuilder::<Postgres>::new("SELECT id, answer FROM questions WHERE id IN (");
-    let mut separated = query_builder.separated(",");
-    for id in &allowed_qids {
-        separated.push_bind(id);
-    }
-    separated.push_unseparated(")");
-
-    let db_answers_vec: Vec<AnswerKey> = query_builder.build_query_as().fetch_all(&pool).await?;
-
-    let db_map: HashMap<i64, String> = db_answers_vec
+    // 3. Answer keys as they were when the exam was served
+    let db_map: HashMap<i64, String> = paper_snapshots::load(&pool, sid)
+        .await?
         .into_iter()
-        .map(|k| (k.id, k.answer))
+        .map(|q| (q.id, normalize_answer(&q.answer, None)))
+        .collect();
+
+    // Translate the shown letters back to the original option order.
+    let perms = paper_snapshots::option_orders(&pool, sid).await?;
+    let user_answers: HashMap<i64, String> = req
+        .answers
+        .iter()
+        .map(|(qid, ans)| (*qid, normalize_answer(ans, perms.get(qid).map(Vec::as_slice))))
         .collect();
 
-    let (correct_count, score) = calculate_score(&req.answers, &db_map);
+    let (correct_count, score) = calculate_score(&user_answers, &db_map);
     let passed = score >= PASSING_SCORE_PERCENTAGE;
-    let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+    let user_id = user.id;
 
+    let mut certificate_code = None;
     if passed {
         sqlx::query!("UPDATE users SET is_verified = TRUE WHERE id = $1", user_id)
             .execute(&pool)
             .await?;
+        certificate_code = certificates::issue(&pool, user_id, "qualification", score, sid).await?;
     }
 
     Ok(Json(serde_json::json!({
@@ -173,6 +239,7 @@ pub async fn submit_exam(
         "correct_count": correct_count,
         "total_questions": db_map.len(),
         "passed": passed,
+        "certificate_code": certificate_code,
         "message": if passed { "Verification successful!" } else { "Score too low. Try again." }
     })))
 }
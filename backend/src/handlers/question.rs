@@ -0,0 +1,83 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sqlx::PgPool;
+use validator::Validate;
+
+use crate::{
+    config::QUESTION_FLAG_SUSPEND_THRESHOLD,
+    error::AppError,
+    models::question::FlagQuestionRequest,
+    utils::jwt::AuthUser,
+};
+
+/// Flag a question as wrong or ambiguous.
+///
+/// The flag is queued for admin triage. Once enough users have open flags on a
+/// question it is suspended from new papers until an admin closes the flags.
+pub async fn flag_question(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(question_id): Path<i64>,
+    Json(payload): Json<FlagQuestionRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload
+        .validate()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let user_id = user.id;
+
+    let mut tx = pool.begin().await?;
+
+    // Lock the question so concurrent flags agree on when the threshold is crossed.
+    sqlx::query!("SELECT id FROM questions WHERE id = $1 FOR UPDATE", question_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound("Question not found".to_string()))?;
+
+    let id = sqlx::query!(
+        r#"
+        INSERT INTO question_flags (question_id, user_id, reason, comment)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+        question_id,
+        user_id,
+        payload.reason,
+        payload.comment
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        if e.to_string().contains("idx_question_flags_open_per_user") {
+            AppError::Conflict("You have already flagged this question".to_string())
+        } else {
+            AppError::InternalServerError(e.to_string())
+        }
+    })?
+    .id;
+
+    let suspended = sqlx::query!(
+        r#"
+        UPDATE questions SET suspended_at = NOW()
+        WHERE id = $1 AND suspended_at IS NULL
+          AND (SELECT COUNT(*) FROM question_flags WHERE question_id = $1 AND status = 'open') >= $2
+        "#,
+        question_id,
+        QUESTION_FLAG_SUSPEND_THRESHOLD
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+
+    tx.commit().await?;
+
+    if suspended {
+        tracing::info!("Question {} suspended after repeated flags", question_id);
+    }
+
+    Ok((StatusCode::CREATED, Json(serde_json::json!({ "id": id }))))
+}
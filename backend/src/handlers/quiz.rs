@@ -2,149 +2,171 @@
 
 use std::collections::HashMap;
 
-use axum::{Extension, Json, extract::State, response::IntoResponse};
-use sqlx::{PgPool, Postgres};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::header,
+    response::IntoResponse,
+};
+use sqlx::{PgPool, types::Json as JsonColumn};
 
 use crate::{
+    config::{Config, PRACTICE_PAPER_TTL_SECS, QUIZ_CERTIFICATE_SCORE},
     error::AppError,
     models::{
-        exam_record::{LeaderboardEntry, SubmitExamRequest},
-        question::Question,
+        exam_record::{
+            AttemptPaper, AttemptQuestion, LeaderboardEntry, PracticePaper, SubmitExamRequest,
+        },
+        question::PublicQuestion,
     },
-    utils::jwt::Claims,
+    services::{auto_quiz, certificates, paper_snapshots, question_selection},
+    utils::{jwt::AuthUser, locale::PreferredLocale},
 };
 
-/// Helper struct for fetching answer keys from the database.
-#[derive(sqlx::FromRow)]
-struct AnswerKey {
-    id: i64,
-    answer: String,
-    #[allow(dead_code)]
-    question_type: String,
-}
-
 /// Generates a random quiz paper.
 ///
-/// Selects 6 random single-choice questions and 4 random multiple-choice questions.
-/// Returns the questions without the correct answers (hidden by DTO if implemented, currently raw).
-/// Note: In a production app, we should use a DTO to hide `answer` field.
-pub async fn generate_paper(State(pool): State<PgPool>) -> Result<impl IntoResponse, AppError> {
-    let single_question = sqlx::query_as!(
-        Question,
-        r#"
-        SELECT
-            id,
-            type as "question_type",
-            content,
-            options as "options: sqlx::types::Json<Vec<String>>",
-            answer,
-            analysis,
-            created_at
-        FROM questions
-        WHERE type = 'single'
-        ORDER BY RANDOM()
-        LIMIT 6
-        "#
-    )
-    .fetch_all(&pool)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to fetch single question: {:?}", e);
-        AppError::InternalServerError(e.to_string())
-    })?;
+/// Selects 6 single-choice and 4 multiple-choice questions via exposure-aware sampling
+/// and snapshots them; the paper is scored against the snapshot named by the
+/// returned token. Questions are served without answers, in the caller's
+/// preferred locale where translated.
+pub async fn generate_paper(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    PreferredLocale(locale): PreferredLocale,
+) -> Result<impl IntoResponse, AppError> {
+    let single_question = question_selection::pick_questions(&pool, Some("single"), 6, &locale)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to fetch single question: {:?}", e);
+            AppError::InternalServerError(e.to_string())
+        })?;
 
-    let multiple_questions = sqlx::query_as!(
-        Question,
-        r#"
-        SELECT
-            id,
-            type as "question_type",
-            content,
-            options as "options: sqlx::types::Json<Vec<String>>",
-            answer,
-            analysis,
-            created_at
-        FROM questions
-        WHERE type = 'multiple'
-        ORDER BY RANDOM()
-        LIMIT 4
-        "#
-    )
-    .fetch_all(&pool)
-    .await
-    .map_err(|e| {
-        tracing::error!("Failed to fetch multiple questions: {:?}", e);
-        AppError::InternalServerError(e.to_string())
-    })?;
+    let multiple_questions =
+        question_selection::pick_questions(&pool, Some("multiple"), 4, &locale)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to fetch multiple questions: {:?}", e);
+            AppError::InternalServerError(e.to_string())
+        })?;
 
-    let mut paper = Vec::new();
-    paper.extend(single_question);
-    paper.extend(multiple_questions);
+    let question_ids: Vec<i64> = single_question
+        .iter()
+        .chain(&multiple_questions)
+        .map(|q| q.id)
+        .collect();
+
+    // Freeze the paper as served
+    let mut conn = pool.acquire().await?;
+    let (snapshot_id, mut questions) = paper_snapshots::take(&mut conn, &question_ids).await?;
+    paper_snapshots::translate(&mut *conn, &mut questions, &locale).await?;
+    let exam_token = paper_snapshots::sign(snapshot_id, &config.jwt_secret)
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
 
-    Ok(Json(paper))
+    Ok((
+        [(header::CONTENT_LANGUAGE, locale)],
+        Json(PracticePaper {
+            questions: questions
+                .into_iter()
+                .map(PublicQuestion::from_question)
+                .collect(),
+            exam_token,
+            expires_in: PRACTICE_PAPER_TTL_SECS,
+        }),
+    ))
 }
 
 /// Submits a user's exam answers and calculates the score.
 ///
-/// * Validates the token and extracts User ID.
-/// * Compares user answers with database records.
-/// * Calculates score (10 points per correct answer).
+/// * Validates the paper token and loads the snapshot it names.
+/// * Calculates score (10 points per correct answer) against the snapshot.
+/// * Keeps the attempt; each paper is submitted once.
 /// * Saves or updates the result (Upsert) in `exam_records`.
 pub async fn submit_paper(
     State(pool): State<PgPool>,
-    Extension(claims): Extension<Claims>,
+    State(config): State<Config>,
+    user: AuthUser,
     Json(req): Json<SubmitExamRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let question_ids: Vec<i64> = req.answers.keys().cloned().collect();
+    let snapshot_id = paper_snapshots::verify(&req.exam_token, &config.jwt_secret).ok_or_else(|| {
+        AppError::BadRequest("Invalid or expired paper token. Please generate a new paper.".to_string())
+    })?;
 
-    if question_ids.is_empty() {
+    if req.answers.is_empty() {
         return Err(AppError::BadRequest("No answers submitted".to_string()));
     }
 
-    // Use QueryBuilder for dynamic IN clause
-    let mut query_builder = sqlx::QueryBuilder::<Postgres>::new(
-        "SELECT
-            id,
-            answer,
-            type as question_type FROM questions WHERE id IN (",
-    );
-
-    let mut separated = query_builder.separated(",");
-    for id in &question_ids {
-        separated.push_bind(id);
+    // Score against the paper as it was served, kept with the attempt
+    let mut tx = pool.begin().await?;
+    let questions = paper_snapshots::load(&mut *tx, snapshot_id).await?;
+    for qid in req.answers.keys() {
+        if !questions.iter().any(|q| q.id == *qid) {
+            return Err(AppError::BadRequest(format!(
+                "Question ID {} is not on this paper.",
+                qid
+            )));
+        }
     }
-    separated.push_unseparated(")");
-
-    let db_answers: Vec<AnswerKey> = query_builder
-        .build_query_as()
-        .fetch_all(&pool)
-        .await
-        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
 
     let mut total_score = 0;
     let mut correct_count = 0;
+    let mut answered_ids = Vec::new();
+    let mut answered_correct = Vec::new();
 
-    let db_map: HashMap<i64, AnswerKey> = db_answers.into_iter().map(|k| (k.id, k)).collect();
-
-    for (q_id, user_ans) in &req.answers {
-        if let Some(correct) = db_map.get(q_id) {
-            // Simple strict string matching
-            if user_ans == &correct.answer {
-                total_score += 10;
-                correct_count += 1;
-            }
+    for q in &questions {
+        let Some(answer) = req.answers.get(&q.id) else {
+            continue;
+        };
+        // Simple strict string matching
+        let is_correct = *answer == q.answer;
+        if is_correct {
+            total_score += 10;
+            correct_count += 1;
         }
+        answered_ids.push(q.id);
+        answered_correct.push(is_correct);
     }
 
-    let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+    let user_id = user.id;
 
-    // Upsert: keep the highest score if user retakes the exam
+    // Keep the full attempt for the practice statistics
+    let attempt_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO quiz_attempts (user_id, score, paper_snapshot_id, answers)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (paper_snapshot_id) DO NOTHING
+        RETURNING id
+        "#,
+        user_id,
+        total_score as i32,
+        snapshot_id,
+        JsonColumn(&req.answers) as _
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::Conflict(
+        "This paper has already been submitted".to_string(),
+    ))?;
+    sqlx::query!(
+        r#"
+        INSERT INTO quiz_answers (attempt_id, question_id, is_correct)
+        SELECT $1, * FROM UNNEST($2::BIGINT[], $3::BOOLEAN[])
+        "#,
+        attempt_id,
+        &answered_ids,
+        &answered_correct
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    // Upsert: keep the highest score, and when it was reached, if user retakes the exam
     sqlx::query!(
         r#"
         INSERT INTO exam_records (user_id, score)
         VALUES ($1, $2)
         ON CONFLICT(user_id) DO UPDATE SET
-            score = CASE WHEN EXCLUDED.score > exam_records.score THEN EXCLUDED.score ELSE exam_records.score END,
+            score = GREATEST(EXCLUDED.score, exam_records.score),
+            achieved_at = CASE WHEN EXCLUDED.score > exam_records.score THEN CURRENT_TIMESTAMP ELSE exam_records.achieved_at END,
             created_at = CURRENT_TIMESTAMP
         "#,
         user_id,
@@ -157,15 +179,69 @@ pub async fn submit_paper(
         AppError::InternalServerError(e.to_string())
     })?;
 
+    let certificate_code = if total_score >= QUIZ_CERTIFICATE_SCORE {
+        certificates::issue(&pool, user_id, "quiz", total_score as f64, snapshot_id).await?
+    } else {
+        None
+    };
+
     Ok(Json(serde_json::json!({
         "score": total_score,
         "correct_count": correct_count,
-        "total_questions": question_ids.len(),
+        "certificate_code": certificate_code,
+        "total_questions": questions.len(),
         "message": "Exam submmited successfully"
     })))
 }
 
+/// The current user's submitted paper as it was scored, in the order it was
+/// served. Attempts from before papers were snapshotted have none.
+pub async fn get_attempt_paper(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let attempt = sqlx::query!(
+        r#"
+        SELECT score, created_at, paper_snapshot_id,
+            answers as "answers: JsonColumn<HashMap<i64, String>>"
+        FROM quiz_attempts
+        WHERE id = $1 AND user_id = $2
+        "#,
+        id,
+        user.id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Attempt not found".to_string()))?;
+    let snapshot_id = attempt.paper_snapshot_id.ok_or(AppError::NotFound(
+        "No paper was kept for this attempt".to_string(),
+    ))?;
+    let answers = attempt.answers.map(|a| a.0).unwrap_or_default();
+
+    let questions = paper_snapshots::load(&pool, snapshot_id)
+        .await?
+        .into_iter()
+        .map(|q| {
+            let user_answer = answers.get(&q.id).cloned().unwrap_or_default();
+            AttemptQuestion {
+                is_correct: user_answer == q.answer,
+                user_answer,
+                question: PublicQuestion::from_question(q),
+            }
+        })
+        .collect();
+
+    Ok(Json(AttemptPaper {
+        attempt_id: id,
+        score: attempt.score,
+        created_at: attempt.created_at,
+        questions,
+    }))
+}
+
 /// Retrieves the top 5 high scores from the leaderboard.
+/// Scores reached during a running exam event are held back until it ends.
 pub async fn get_leaderboard(State(pool): State<PgPool>) -> Result<impl IntoResponse, AppError> {
     let leaderboard = sqlx::query_as!(
         LeaderboardEntry,
@@ -173,9 +249,14 @@ pub async fn get_leaderboard(State(pool): State<PgPool>) -> Result<impl IntoResp
         SELECT
             u.username,
             e.score,
-            e.created_at
+            e.created_at,
+            e.achieved_at
         FROM exam_records e
         JOIN users u ON e.user_id = u.id
+        WHERE NOT EXISTS (
+            SELECT 1 FROM exam_events ev
+            WHERE ev.starts_at <= e.achieved_at AND ev.ends_at > NOW()
+        )
         ORDER BY e.score DESC
         LIMIT 5
         "#
@@ -189,3 +270,17 @@ pub async fn get_leaderboard(State(pool): State<PgPool>) -> Result<impl IntoResp
 
     Ok(Json(leaderboard))
 }
+
+/// Generates a "test yourself" quiz from an architecture's own data.
+/// Unlike papers, these questions include their answers and are never scored.
+pub async fn architecture_quiz(
+    State(pool): State<PgPool>,
+    PreferredLocale(locale): PreferredLocale,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let quiz = auto_quiz::for_architecture(&pool, id, &locale)
+        .await?
+        .ok_or(AppError::NotFound("Architecture not found".to_string()))?;
+
+    Ok(Json(quiz))
+}
@@ -0,0 +1,221 @@
+// src/handlers/series.rs
+
+use std::collections::HashSet;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sqlx::{PgPool, Postgres, Transaction};
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::series::{
+        AddSeriesPostRequest, CreateSeriesRequest, ReorderSeriesRequest, Series, SeriesDetail,
+        SeriesPart,
+    },
+    utils::{html::clean_html, jwt::AuthUser},
+};
+
+/// Create a new, empty series owned by the current user.
+pub async fn create_series(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<CreateSeriesRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let user_id = user.id;
+
+    let id = sqlx::query!(
+        "INSERT INTO series (user_id, title, description) VALUES ($1, $2, $3) RETURNING id",
+        user_id,
+        clean_html(&payload.title),
+        payload.description.as_deref().map(clean_html)
+    )
+    .fetch_one(&pool)
+    .await?
+    .id;
+
+    Ok((StatusCode::CREATED, Json(serde_json::json!({ "id": id }))))
+}
+
+/// Get a series and its parts in order. Deleted posts are left out.
+pub async fn get_series(
+    State(pool): State<PgPool>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let series = sqlx::query_as!(
+        Series,
+        "SELECT id, user_id, title, description, created_at FROM series WHERE id = $1",
+        id
+    )
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::NotFound("Series not found".to_string()))?;
+
+    let posts = sqlx::query_as!(
+        SeriesPart,
+        r#"
+        SELECT
+            ROW_NUMBER() OVER (ORDER BY sp.position, sp.post_id) as "part!",
+            p.id as post_id, p.title
+        FROM series_posts sp
+        JOIN posts p ON p.id = sp.post_id AND p.deleted_at IS NULL
+        WHERE sp.series_id = $1
+        ORDER BY sp.position, sp.post_id
+        "#,
+        id
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(SeriesDetail { series, posts }))
+}
+
+/// Append one of the author's posts to the end of their series.
+pub async fn add_series_post(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<AddSeriesPostRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id;
+
+    let mut tx = pool.begin().await?;
+    lock_own_series(&mut tx, id, user_id).await?;
+
+    let post = sqlx::query!(
+        "SELECT user_id FROM posts WHERE id = $1 AND deleted_at IS NULL",
+        payload.post_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::BadRequest("Post not found".to_string()))?;
+    if post.user_id != user_id {
+        return Err(AppError::BadRequest(
+            "Only your own posts can be added to your series".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO series_posts (series_id, post_id, position)
+        SELECT $1, $2, COALESCE(MAX(position), 0) + 1
+        FROM series_posts
+        WHERE series_id = $1
+        "#,
+        id,
+        payload.post_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        if e.to_string().contains("unique constraint") {
+            AppError::Conflict("This post is already part of a series".to_string())
+        } else {
+            AppError::from(e)
+        }
+    })?;
+
+    tx.commit().await?;
+    Ok(StatusCode::CREATED)
+}
+
+/// Take a post out of a series. The post itself is kept.
+pub async fn remove_series_post(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path((id, post_id)): Path<(i64, i64)>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id;
+
+    let mut tx = pool.begin().await?;
+    lock_own_series(&mut tx, id, user_id).await?;
+
+    let result = sqlx::query!(
+        "DELETE FROM series_posts WHERE series_id = $1 AND post_id = $2",
+        id,
+        post_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Post is not part of this series".to_string()));
+    }
+
+    tx.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Reorder a series. `post_ids` must list every visible part exactly once;
+/// parts whose posts were deleted keep their old position.
+pub async fn reorder_series_posts(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+    Json(payload): Json<ReorderSeriesRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user.id;
+
+    let mut tx = pool.begin().await?;
+    lock_own_series(&mut tx, id, user_id).await?;
+
+    let current: HashSet<i64> = sqlx::query_scalar!(
+        r#"
+        SELECT sp.post_id
+        FROM series_posts sp
+        JOIN posts p ON p.id = sp.post_id AND p.deleted_at IS NULL
+        WHERE sp.series_id = $1
+        "#,
+        id
+    )
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .collect();
+
+    let requested: HashSet<i64> = payload.post_ids.iter().copied().collect();
+    if requested.len() != payload.post_ids.len() || requested != current {
+        return Err(AppError::BadRequest(
+            "post_ids must list every post in the series exactly once".to_string(),
+        ));
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE series_posts sp
+        SET position = o.position::INT
+        FROM UNNEST($2::BIGINT[]) WITH ORDINALITY AS o(post_id, position)
+        WHERE sp.series_id = $1 AND sp.post_id = o.post_id
+        "#,
+        id,
+        &payload.post_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(StatusCode::OK)
+}
+
+/// Locks a series for changes by its author.
+async fn lock_own_series(
+    tx: &mut Transaction<'_, Postgres>,
+    id: i64,
+    user_id: i64,
+) -> Result<(), AppError> {
+    let series = sqlx::query!("SELECT user_id FROM series WHERE id = $1 FOR UPDATE", id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(AppError::NotFound("Series not found".to_string()))?;
+
+    if series.user_id != user_id {
+        return Err(AppError::AuthError(
+            "You are not authorized to modify this series".to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,46 @@
+// src/handlers/stats.rs
+
+use std::time::Duration;
+
+use axum::{Json, extract::State, response::IntoResponse};
+
+use crate::{
+    config::ONLINE_WINDOW_SECS,
+    error::AppError,
+    models::stats::{OnlineStats, PublicStats},
+    state::AppState,
+    utils::presence::Presence,
+};
+
+/// Public aggregates for the "about the project" page, cached for an hour.
+pub async fn get_public_stats(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    if let Some(stats) = state.public_stats.get(&()) {
+        return Ok(Json(stats));
+    }
+
+    let stats = sqlx::query_as!(
+        PublicStats,
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM architectures) as "total_architectures!",
+            (SELECT COUNT(DISTINCT user_id) FROM contributions WHERE status = 'approved') as "verified_contributors!",
+            (SELECT COUNT(*) FROM questions) as "questions_in_bank!",
+            (SELECT COUNT(*) FROM posts
+             WHERE deleted_at IS NULL AND created_at >= NOW() - INTERVAL '7 days') as "posts_this_week!",
+            NOW() as "generated_at!"
+        "#
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    state.public_stats.insert((), stats.clone());
+    Ok(Json(stats))
+}
+
+/// Number of users who made an authenticated request in the last few minutes.
+pub async fn get_online_stats(State(presence): State<Presence>) -> impl IntoResponse {
+    Json(OnlineStats {
+        online_users: presence.online_count(Duration::from_secs(ONLINE_WINDOW_SECS)),
+        window_secs: ONLINE_WINDOW_SECS,
+    })
+}
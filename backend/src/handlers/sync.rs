@@ -0,0 +1,104 @@
+// src/handlers/sync.rs
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use sqlx::PgPool;
+
+use crate::{
+    config::{SYNC_OVERLAP_SECS, TOMBSTONE_RETENTION_DAYS},
+    error::AppError,
+    models::sync::{EntityChanges, SyncChanges, SyncParams},
+};
+
+/// IDs of architectures, glossary terms and questions created, updated or
+/// deleted after `since`, for clients keeping an offline copy.
+pub async fn get_changes(
+    State(pool): State<PgPool>,
+    Query(params): Query<SyncParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let since = params.since;
+    let now = sqlx::query_scalar!(r#"SELECT CURRENT_TIMESTAMP as "now!""#)
+        .fetch_one(&pool)
+        .await?;
+
+    let mut architectures = EntityChanges::default();
+    for row in sqlx::query!(
+        r#"
+        SELECT id, (created_at > $1) as "created!"
+        FROM architectures
+        WHERE updated_at > $1
+        ORDER BY id
+        "#,
+        since
+    )
+    .fetch_all(&pool)
+    .await?
+    {
+        architectures.push(row.id, row.created);
+    }
+
+    let mut glossary = EntityChanges::default();
+    for row in sqlx::query!(
+        r#"
+        SELECT id, (created_at > $1) as "created!"
+        FROM glossary_terms
+        WHERE updated_at > $1
+        ORDER BY id
+        "#,
+        since
+    )
+    .fetch_all(&pool)
+    .await?
+    {
+        glossary.push(row.id, row.created);
+    }
+
+    let mut questions = EntityChanges::default();
+    for row in sqlx::query!(
+        r#"
+        SELECT id, (COALESCE(created_at, updated_at) > $1) as "created!"
+        FROM questions
+        WHERE updated_at > $1
+        ORDER BY id
+        "#,
+        since
+    )
+    .fetch_all(&pool)
+    .await?
+    {
+        questions.push(row.id, row.created);
+    }
+
+    for row in sqlx::query!(
+        r#"
+        SELECT entity_type, entity_id
+        FROM tombstones
+        WHERE deleted_at > $1 AND entity_type IN ('architecture', 'glossary_term', 'question')
+        ORDER BY entity_id
+        "#,
+        since
+    )
+    .fetch_all(&pool)
+    .await?
+    {
+        let changes = match row.entity_type.as_str() {
+            "architecture" => &mut architectures,
+            "glossary_term" => &mut glossary,
+            "question" => &mut questions,
+            _ => continue,
+        };
+        changes.deleted.push(row.entity_id);
+    }
+
+    Ok(Json(SyncChanges {
+        since,
+        until: now - chrono::Duration::seconds(SYNC_OVERLAP_SECS),
+        reset_required: since < now - chrono::Duration::days(TOMBSTONE_RETENTION_DAYS.into()),
+        architectures,
+        glossary,
+        questions,
+    }))
+}
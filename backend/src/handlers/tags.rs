@@ -0,0 +1,60 @@
+// src/handlers/tags.rs
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::header,
+    response::IntoResponse,
+};
+use sqlx::PgPool;
+
+use crate::{
+    config::TOPIC_ARCHITECTURE_LIMIT,
+    error::AppError,
+    models::{
+        post::PostSummary,
+        tag::{TopicPage, TopicParams},
+    },
+    services::tags,
+    utils::locale::PreferredLocale,
+};
+
+/// Lists the tags most used in the last week, as of the last
+/// `trending_tags` job run.
+pub async fn trending_tags(State(pool): State<PgPool>) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(tags::trending(&pool).await?))
+}
+
+/// A topic page: the tag's posts, newest first, and the architectures
+/// they are about. Architecture names are translated when possible.
+pub async fn get_topic(
+    State(pool): State<PgPool>,
+    PreferredLocale(locale): PreferredLocale,
+    Path(slug): Path<String>,
+    Query(params): Query<TopicParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let (tag_id, tag) = tags::find(&pool, &slug)
+        .await?
+        .ok_or(AppError::NotFound("Tag not found".to_string()))?;
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+
+    let posts_count = tags::post_count(&pool, tag_id).await?;
+    let posts = tags::posts(&pool, tag_id, params.cursor, limit)
+        .await?
+        .into_iter()
+        .map(PostSummary::from)
+        .collect();
+    let architectures =
+        tags::related_architectures(&pool, tag_id, &tag.name, &locale, TOPIC_ARCHITECTURE_LIMIT)
+            .await?;
+
+    Ok((
+        [(header::CONTENT_LANGUAGE, locale)],
+        Json(TopicPage {
+            tag,
+            posts_count,
+            posts,
+            architectures,
+        }),
+    ))
+}
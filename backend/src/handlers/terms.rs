@@ -0,0 +1,47 @@
+// src/handlers/terms.rs
+
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sqlx::PgPool;
+use validator::Validate;
+
+use crate::{
+    error::AppError,
+    models::terms::AcceptTermsRequest,
+    services::terms,
+    utils::jwt::AuthUser,
+};
+
+/// Returns the current terms of service.
+pub async fn get_current_terms(
+    State(pool): State<PgPool>,
+) -> Result<impl IntoResponse, AppError> {
+    let current = terms::current(&pool)
+        .await?
+        .ok_or(AppError::NotFound("No terms of service published".to_string()))?;
+
+    Ok(Json(current))
+}
+
+/// Accepts the current terms of service for the logged-in user.
+/// The version must match, so clients cannot accept text they have not shown.
+pub async fn accept_terms(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<AcceptTermsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate().map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let user_id = user.id;
+
+    let current = terms::current(&pool)
+        .await?
+        .ok_or(AppError::NotFound("No terms of service published".to_string()))?;
+    terms::check_version(&current, Some(&payload.version))?;
+    terms::record_acceptance(&pool, user_id, current.id).await?;
+
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,38 @@
+// src/jobs/account_deletion.rs
+
+use sqlx::PgPool;
+
+use crate::{services::account_deletion, utils::audit_stream};
+
+/// Deletes the accounts whose owners asked for it and whose grace period is
+/// over, each in its own transaction.
+pub async fn run(pool: PgPool) -> Result<(), sqlx::Error> {
+    let due = account_deletion::due(&pool).await?;
+    if due.is_empty() {
+        return Ok(());
+    }
+    let Some(ghost_id) = account_deletion::ghost_id(&pool).await? else {
+        tracing::error!("Ghost user not found; {} account deletions postponed", due.len());
+        return Ok(());
+    };
+
+    for user_id in due {
+        let mut tx = pool.begin().await?;
+        let Some(impact) = account_deletion::impact(&mut *tx, user_id).await? else {
+            continue;
+        };
+        account_deletion::transfer_and_delete(&mut tx, ghost_id, user_id).await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            target: audit_stream::TARGET,
+            action = "user_deleted",
+            actor_id = user_id,
+            user_id = user_id,
+            username = impact.username,
+            posts_transferred = impact.posts_to_transfer,
+            reason = "self_deletion"
+        );
+    }
+    Ok(())
+}
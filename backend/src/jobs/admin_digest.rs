@@ -0,0 +1,108 @@
+// src/jobs/admin_digest.rs
+
+//! Daily digest of pending admin work: contributions awaiting review, flagged
+//! questions and content reports. Each admin gets it as a notification, and it
+//! is posted to `ADMIN_DIGEST_WEBHOOK_URL` when set (e.g. a chat channel).
+
+use sqlx::PgPool;
+
+use crate::{
+    config::ADMIN_DIGEST_INTERVAL_HOURS,
+    models::admin_digest::AdminDigest,
+    services::{notifications, security_events},
+    utils::http_client::{self, OutboundPolicy},
+};
+
+/// Sends a digest unless one went out in the last `ADMIN_DIGEST_INTERVAL_HOURS`
+/// or there is nothing pending. Runs hourly, so a restart neither skips nor repeats a day.
+pub async fn run(pool: PgPool, webhook_url: Option<String>) -> Result<(), sqlx::Error> {
+    // A few minutes' slack, so the hourly check doesn't push each digest an hour later.
+    let recent = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM admin_digests
+            WHERE sent_at > NOW() - make_interval(hours => $1) + INTERVAL '5 minutes'
+        ) as "exists!"
+        "#,
+        ADMIN_DIGEST_INTERVAL_HOURS
+    )
+    .fetch_one(&pool)
+    .await?;
+    if recent {
+        return Ok(());
+    }
+
+    let digest = build(&pool).await?;
+    if digest.is_empty() {
+        return Ok(());
+    }
+    let summary = serde_json::to_value(&digest).unwrap_or_default();
+
+    let mut tx = pool.begin().await?;
+    sqlx::query!("INSERT INTO admin_digests (summary) VALUES ($1)", summary)
+        .execute(&mut *tx)
+        .await?;
+    let admins = sqlx::query_scalar!("SELECT id FROM users WHERE role = 'admin'")
+        .fetch_all(&mut *tx)
+        .await?;
+    for admin_id in admins {
+        notifications::notify(&mut *tx, admin_id, notifications::ADMIN_DIGEST, summary.clone())
+            .await?;
+    }
+    tx.commit().await?;
+
+    // Best effort: the digest is already recorded and in admins' notifications.
+    if let Some(url) = webhook_url {
+        let body = serde_json::json!({"text": message(&digest), "digest": summary});
+        match http_client::post_json(&url, &body, &OutboundPolicy::default()).await {
+            Ok(resp) if resp.status.is_success() => {}
+            Ok(resp) => tracing::warn!("Admin digest webhook returned HTTP {}", resp.status),
+            Err(e) => tracing::warn!("Failed to post admin digest: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Counts the work currently waiting for admins.
+pub async fn build(pool: &PgPool) -> Result<AdminDigest, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM contributions WHERE status = 'pending') as "pending_contributions!",
+            (SELECT MIN(created_at) FROM contributions WHERE status = 'pending') as oldest_pending_at,
+            (SELECT COUNT(*) FROM question_flags WHERE status = 'open') as "open_flags!",
+            (SELECT COUNT(DISTINCT question_id) FROM question_flags WHERE status = 'open') as "flagged_questions!",
+            (SELECT COUNT(*) FROM questions WHERE suspended_at IS NOT NULL) as "suspended_questions!",
+            (SELECT COUNT(*) FROM security_events
+             WHERE kind = $1 AND created_at > NOW() - make_interval(hours => $2)) as "reports!",
+            NOW() as "generated_at!"
+        "#,
+        security_events::CONTENT_REPORTED,
+        ADMIN_DIGEST_INTERVAL_HOURS
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(AdminDigest {
+        pending_contributions: row.pending_contributions,
+        oldest_pending_at: row.oldest_pending_at,
+        open_flags: row.open_flags,
+        flagged_questions: row.flagged_questions,
+        suspended_questions: row.suspended_questions,
+        reports: row.reports,
+        generated_at: row.generated_at,
+    })
+}
+
+/// One-line summary for chat webhooks.
+pub fn message(digest: &AdminDigest) -> String {
+    format!(
+        "Pending admin work: {} contributions, {} open flags on {} questions ({} suspended), {} reports in the last day",
+        digest.pending_contributions,
+        digest.open_flags,
+        digest.flagged_questions,
+        digest.suspended_questions,
+        digest.reports
+    )
+}
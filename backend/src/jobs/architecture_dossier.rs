@@ -0,0 +1,141 @@
+// src/jobs/architecture_dossier.rs
+
+use sqlx::{PgPool, types::Json};
+
+use crate::{
+    config::{DOSSIER_MAX_CITED_POSTS, DOSSIER_MAX_IMAGES},
+    error::AppError,
+    services::dossiers::{self, CitedPost, Dossier, DossierImage},
+    storage::Storage,
+    utils::pdf::PdfFont,
+};
+
+/// Renders the dossier of `payload.architecture_id` in `payload.locale` and stores it.
+/// Architectures deleted since the request are skipped.
+pub async fn run(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    font: Option<&PdfFont>,
+    payload: &serde_json::Value,
+) -> Result<(), AppError> {
+    let architecture_id = payload["architecture_id"]
+        .as_i64()
+        .ok_or_else(|| AppError::BadRequest("Missing architecture_id".to_string()))?;
+    let locale = payload["locale"]
+        .as_str()
+        .ok_or_else(|| AppError::BadRequest("Missing locale".to_string()))?;
+
+    let Some(arch) = sqlx::query!(
+        r#"
+        SELECT a.id, a.category,
+               COALESCE(t.name, a.name) as "name!",
+               COALESCE(t.dynasty, a.dynasty) as "dynasty!",
+               COALESCE(t.location, a.location) as "location!",
+               a.province, a.city, a.district,
+               COALESCE(t.description, a.description) as "description!",
+               a.cover_img, a.carousel_imgs as "carousel_imgs: Json<Vec<String>>",
+               a.height_m, a.footprint_m2, a.bays_wide, a.bays_deep,
+               a.contribution_id, u.username as "author?"
+        FROM architectures a
+        LEFT JOIN architecture_translations t ON t.architecture_id = a.id AND t.locale = $2
+        LEFT JOIN users u ON u.id = a.created_by
+        WHERE a.id = $1
+        "#,
+        architecture_id,
+        locale
+    )
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    let mut urls: Vec<String> = Vec::new();
+    for url in std::iter::once(arch.cover_img).chain(arch.carousel_imgs.0) {
+        if !urls.contains(&url) && urls.len() < DOSSIER_MAX_IMAGES {
+            urls.push(url);
+        }
+    }
+    let colors = sqlx::query!(
+        r#"
+        SELECT url, dominant_color as "dominant_color!"
+        FROM image_placeholders
+        WHERE url = ANY($1) AND status = 'ok'
+        "#,
+        &urls
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut images = Vec::with_capacity(urls.len());
+    for url in urls {
+        images.push(DossierImage {
+            jpeg: dossiers::fetch_jpeg(&url).await,
+            color: colors
+                .iter()
+                .find(|c| c.url == url)
+                .map(|c| c.dominant_color.clone()),
+            url,
+        });
+    }
+
+    let posts = sqlx::query_as!(
+        CitedPost,
+        r#"
+        SELECT p.id, p.title, u.username as author, p.created_at
+        FROM post_references r
+        JOIN posts p ON p.id = r.post_id
+        JOIN users u ON u.id = p.user_id
+        WHERE r.entity_type = 'architecture' AND r.entity_id = $1 AND p.deleted_at IS NULL
+        ORDER BY p.created_at, p.id
+        LIMIT $2
+        "#,
+        architecture_id,
+        DOSSIER_MAX_CITED_POSTS
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let pdf = dossiers::render(
+        &Dossier {
+            id: arch.id,
+            locale: locale.to_string(),
+            name: arch.name,
+            category: arch.category,
+            dynasty: arch.dynasty,
+            location: arch.location,
+            province: arch.province,
+            city: arch.city,
+            district: arch.district,
+            description: arch.description,
+            height_m: arch.height_m,
+            footprint_m2: arch.footprint_m2,
+            bays_wide: arch.bays_wide,
+            bays_deep: arch.bays_deep,
+            images,
+            author: arch.author,
+            contribution_id: arch.contribution_id,
+            posts,
+            generated_at: chrono::Utc::now(),
+        },
+        font,
+    )?;
+
+    let key = dossiers::storage_key(architecture_id, locale);
+    storage.put(&key, pdf, "application/pdf").await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE architecture_dossiers
+        SET status = 'ready', storage_key = $3, generated_at = NOW()
+        WHERE architecture_id = $1 AND locale = $2
+        "#,
+        architecture_id,
+        locale,
+        key
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+// src/jobs/broadcast.rs
+
+use sqlx::PgPool;
+
+use crate::{error::AppError, services::broadcasts};
+
+/// Delivers one batch of the broadcast `payload.broadcast_id`.
+pub async fn run(pool: &PgPool, payload: &serde_json::Value) -> Result<(), AppError> {
+    let broadcast_id = payload["broadcast_id"]
+        .as_i64()
+        .ok_or_else(|| AppError::BadRequest("Missing broadcast_id".to_string()))?;
+
+    broadcasts::deliver_batch(pool, broadcast_id).await
+}
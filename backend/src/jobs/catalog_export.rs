@@ -0,0 +1,45 @@
+// src/jobs/catalog_export.rs
+
+use sqlx::PgPool;
+
+use crate::{error::AppError, services::catalog_export, storage::Storage};
+
+/// Writes a new catalog bundle to storage and records it in `catalog_exports`.
+/// `payload.requested_by` is the admin who asked for it.
+pub async fn run(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    payload: &serde_json::Value,
+) -> Result<(), AppError> {
+    let requested_by = payload["requested_by"].as_i64();
+
+    // Taking the version up front lets the bundle carry it; a failed attempt leaves a gap.
+    let version = sqlx::query_scalar!(r#"SELECT nextval('catalog_exports_id_seq') as "version!""#)
+        .fetch_one(pool)
+        .await?;
+
+    let bundle = catalog_export::build(pool, version).await?;
+    let body = serde_json::to_vec(&bundle)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to encode bundle: {}", e)))?;
+
+    let key = catalog_export::bundle_key(version);
+    let size = body.len() as i64;
+    storage.put(&key, body, "application/json").await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO catalog_exports (id, storage_key, architecture_count, size_bytes, requested_by)
+        -- The requesting admin may have been deleted in the meantime.
+        VALUES ($1, $2, $3, $4, (SELECT id FROM users WHERE id = $5))
+        "#,
+        version,
+        key,
+        bundle.architectures.len() as i32,
+        size,
+        requested_by
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+// src/jobs/certificate_pdf.rs
+
+use sqlx::PgPool;
+
+use crate::{
+    error::AppError,
+    storage::Storage,
+    utils::pdf::{PAGE_HEIGHT, PAGE_WIDTH, PdfFont, PdfPage},
+};
+
+/// Renders the PDF for the certificate in `payload.certificate_id` and stores it.
+pub async fn run(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    font: Option<&PdfFont>,
+    payload: &serde_json::Value,
+) -> Result<(), AppError> {
+    let certificate_id = payload["certificate_id"]
+        .as_i64()
+        .ok_or_else(|| AppError::BadRequest("Missing certificate_id".to_string()))?;
+
+    let cert = sqlx::query!(
+        r#"
+        SELECT c.code, c.kind, c.score, c.issued_at, u.username
+        FROM certificates c
+        JOIN users u ON c.user_id = u.id
+        WHERE c.id = $1
+        "#,
+        certificate_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::NotFound("Certificate not found".to_string()))?;
+
+    let title = match cert.kind.as_str() {
+        "qualification" => "Certificate of Qualification",
+        _ => "Certificate of Excellence",
+    };
+    let achievement = match cert.kind.as_str() {
+        "qualification" => "has passed the Ancient Arch qualification exam".to_string(),
+        _ => "has completed the Ancient Arch architecture quiz".to_string(),
+    };
+
+    let mut page = PdfPage::new();
+    page.rect(30.0, 30.0, PAGE_WIDTH - 60.0, PAGE_HEIGHT - 60.0, 3.0)
+        .rect(40.0, 40.0, PAGE_WIDTH - 80.0, PAGE_HEIGHT - 80.0, 0.8)
+        .centered_text(470.0, 16.0, false, "ANCIENT ARCH")
+        .centered_text(410.0, 34.0, true, title)
+        .centered_text(350.0, 14.0, false, "This certifies that")
+        .centered_text(305.0, 28.0, true, &cert.username)
+        .centered_text(260.0, 14.0, false, &achievement)
+        .centered_text(235.0, 14.0, false, &format!("with a score of {:.0}", cert.score))
+        .text(80.0, 90.0, 11.0, false, &format!("Issued: {}", cert.issued_at.format("%Y-%m-%d")))
+        .text(80.0, 72.0, 11.0, false, &format!("Verification code: {}", cert.code))
+        .text(
+            80.0,
+            54.0,
+            9.0,
+            false,
+            &format!("Verify at /api/certificates/{}", cert.code),
+        );
+
+    let key = format!("certificates/{}.pdf", cert.code);
+    let pdf = page.finish(font)?;
+    storage.put(&key, pdf, "application/pdf").await?;
+
+    sqlx::query!(
+        "UPDATE certificates SET pdf_key = $1 WHERE id = $2",
+        key,
+        certificate_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
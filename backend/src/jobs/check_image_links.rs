@@ -0,0 +1,68 @@
+// src/jobs/check_image_links.rs
+
+use std::collections::HashSet;
+
+use sqlx::{PgPool, types::Json};
+
+use crate::{error::AppError, services::image_links};
+
+/// Probes the image URLs of every architecture, or only of `payload.architecture_id`,
+/// and replaces their rows in `architecture_image_checks`.
+pub async fn run(pool: &PgPool, payload: &serde_json::Value) -> Result<(), AppError> {
+    let architecture_id = payload["architecture_id"].as_i64();
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, cover_img, carousel_imgs as "carousel_imgs: Json<Vec<String>>"
+        FROM architectures
+        WHERE $1::BIGINT IS NULL OR id = $1
+        ORDER BY id
+        "#,
+        architecture_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut targets: Vec<(i64, &str, String)> = Vec::new();
+    for row in rows {
+        targets.push((row.id, "cover", row.cover_img));
+        for url in row.carousel_imgs.0 {
+            targets.push((row.id, "carousel", url));
+        }
+    }
+
+    let urls: HashSet<String> = targets.iter().map(|(_, _, url)| url.clone()).collect();
+    let results = image_links::probe_all(urls).await;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        "DELETE FROM architecture_image_checks WHERE $1::BIGINT IS NULL OR architecture_id = $1",
+        architecture_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    for (id, field, url) in &targets {
+        let Some(result) = results.get(url) else { continue };
+        // Architectures deleted while probing are skipped rather than failing the job.
+        sqlx::query!(
+            r#"
+            INSERT INTO architecture_image_checks (architecture_id, field, url, status, http_status, error)
+            SELECT $1, $2, $3, $4, $5, $6
+            WHERE EXISTS (SELECT 1 FROM architectures WHERE id = $1)
+            ON CONFLICT DO NOTHING
+            "#,
+            id,
+            field,
+            url,
+            result.status,
+            result.http_status,
+            result.error
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+// src/jobs/contribution_attachments.rs
+
+use std::sync::Arc;
+
+use sqlx::PgPool;
+
+use crate::{config::CONTRIBUTION_ATTACHMENT_RETENTION_DAYS, storage::Storage};
+
+/// Deletes the attachments of contributions reviewed more than
+/// `CONTRIBUTION_ATTACHMENT_RETENTION_DAYS` ago, and of contributions deleted
+/// with their author. Files that cannot be deleted are retried next time.
+pub async fn run(pool: PgPool, storage: Arc<dyn Storage>) -> Result<(), sqlx::Error> {
+    let expired = sqlx::query!(
+        r#"
+        SELECT a.id, a.storage_key
+        FROM contribution_attachments a
+        LEFT JOIN contributions c ON c.id = a.contribution_id
+        WHERE c.id IS NULL
+           OR c.reviewed_at < CURRENT_TIMESTAMP - make_interval(days => $1)
+        "#,
+        CONTRIBUTION_ATTACHMENT_RETENTION_DAYS
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    for attachment in expired {
+        if let Err(e) = storage.delete(&attachment.storage_key).await {
+            tracing::warn!("Attachment {} not deleted: {}", attachment.storage_key, e);
+            continue;
+        }
+        sqlx::query!(
+            "DELETE FROM contribution_attachments WHERE id = $1",
+            attachment.id
+        )
+        .execute(&pool)
+        .await?;
+    }
+
+    Ok(())
+}
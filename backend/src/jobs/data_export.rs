@@ -0,0 +1,109 @@
+// src/jobs/data_export.rs
+
+use std::sync::Arc;
+
+use flate2::{Compression, write::GzEncoder};
+use sqlx::PgPool;
+
+use crate::{
+    config::DATA_EXPORT_RETENTION_DAYS,
+    error::AppError,
+    services::{data_exports, notifications},
+    storage::Storage,
+};
+
+/// Writes the archive of the export in `payload.export_id` and tells the user
+/// it is ready. Exports already written, or whose user is gone, are skipped.
+pub async fn run(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    payload: &serde_json::Value,
+) -> Result<(), AppError> {
+    let export_id = payload["export_id"]
+        .as_i64()
+        .ok_or_else(|| AppError::BadRequest("Missing export_id".to_string()))?;
+
+    let user_id = sqlx::query_scalar!(
+        "SELECT user_id FROM data_exports WHERE id = $1 AND ready_at IS NULL",
+        export_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .flatten();
+    let Some(user_id) = user_id else {
+        return Ok(());
+    };
+    let Some(archive) = data_exports::build(pool, user_id).await? else {
+        return Ok(());
+    };
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, &archive)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to encode archive: {}", e)))?;
+    let body = encoder
+        .finish()
+        .map_err(|e| AppError::InternalServerError(format!("Failed to compress archive: {}", e)))?;
+
+    let key = data_exports::archive_key(export_id);
+    let size = body.len() as i64;
+    storage.put(&key, body, "application/gzip").await?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        r#"
+        UPDATE data_exports
+        SET storage_key = $2, size_bytes = $3, ready_at = NOW(),
+            expires_at = NOW() + make_interval(days => $4)
+        WHERE id = $1
+        "#,
+        export_id,
+        key,
+        size,
+        DATA_EXPORT_RETENTION_DAYS
+    )
+    .execute(&mut *tx)
+    .await?;
+    notifications::notify(
+        &mut *tx,
+        user_id,
+        notifications::DATA_EXPORT_READY,
+        serde_json::json!({ "export_id": export_id }),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Deletes the archives of expired exports, and of accounts deleted since.
+/// The rows of expired exports stay so users see them as expired; files that
+/// cannot be deleted are retried next time.
+pub async fn purge_expired(pool: PgPool, storage: Arc<dyn Storage>) -> Result<(), sqlx::Error> {
+    let expired = sqlx::query!(
+        r#"
+        SELECT id, user_id, storage_key as "storage_key!"
+        FROM data_exports
+        WHERE storage_key IS NOT NULL AND (expires_at < NOW() OR user_id IS NULL)
+        "#
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    for export in expired {
+        if let Err(e) = storage.delete(&export.storage_key).await {
+            tracing::warn!("Data export {} not deleted: {}", export.storage_key, e);
+            continue;
+        }
+        if export.user_id.is_some() {
+            sqlx::query!("UPDATE data_exports SET storage_key = NULL WHERE id = $1", export.id)
+                .execute(&pool)
+                .await?;
+        } else {
+            sqlx::query!("DELETE FROM data_exports WHERE id = $1", export.id)
+                .execute(&pool)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+// src/jobs/image_placeholder.rs
+
+use sqlx::PgPool;
+
+use crate::{
+    error::AppError,
+    services::image_placeholders::{self, PlaceholderError},
+};
+
+/// Computes the placeholder for `payload.url` and stores the outcome.
+///
+/// Rejected images are recorded as failed right away; network errors are
+/// returned so the queue retries them.
+pub async fn run(pool: &PgPool, payload: &serde_json::Value) -> Result<(), AppError> {
+    let url = payload["url"]
+        .as_str()
+        .ok_or_else(|| AppError::BadRequest("Missing url".to_string()))?;
+
+    match image_placeholders::fetch_placeholder(url).await {
+        Ok(placeholder) => {
+            sqlx::query!(
+                r#"
+                UPDATE image_placeholders
+                SET status = 'ok', dominant_color = $2, blurhash = $3, width = $4, height = $5,
+                    error = NULL, processed_at = NOW()
+                WHERE url = $1
+                "#,
+                url,
+                placeholder.dominant_color,
+                placeholder.blurhash,
+                placeholder.width,
+                placeholder.height
+            )
+            .execute(pool)
+            .await?;
+            Ok(())
+        }
+        Err(PlaceholderError::Rejected(reason)) => {
+            sqlx::query!(
+                r#"
+                UPDATE image_placeholders
+                SET status = 'failed', error = $2, processed_at = NOW()
+                WHERE url = $1
+                "#,
+                url,
+                reason
+            )
+            .execute(pool)
+            .await?;
+            Ok(())
+        }
+        Err(PlaceholderError::Transient(reason)) => Err(AppError::InternalServerError(reason)),
+    }
+}
@@ -0,0 +1,146 @@
+// src/jobs/mod.rs
+
+//! Periodic background jobs, started once from `main`.
+
+pub mod account_deletion;
+pub mod admin_digest;
+pub mod architecture_dossier;
+pub mod broadcast;
+pub mod catalog_export;
+pub mod certificate_pdf;
+pub mod check_image_links;
+pub mod contribution_attachments;
+pub mod data_export;
+pub mod image_placeholder;
+pub mod post_excerpts;
+pub mod queue;
+pub mod question_sampling;
+pub mod retention;
+pub mod revoked_tokens;
+pub mod search_index;
+pub mod send_email;
+pub mod slugs;
+pub mod trending_tags;
+pub mod unfurl_link;
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use sqlx::PgPool;
+
+use crate::{
+    config::{
+        ACCOUNT_DELETION_INTERVAL_SECS, ADMIN_DIGEST_CHECK_INTERVAL_SECS, Config,
+        JOB_QUEUE_POLL_INTERVAL_SECS, POST_EXCERPT_INTERVAL_SECS, QUESTION_SAMPLING_INTERVAL_SECS,
+        RETENTION_INTERVAL_SECS, REVOKED_TOKEN_PURGE_INTERVAL_SECS, SEARCH_INDEX_INTERVAL_SECS,
+        SLUG_BACKFILL_INTERVAL_SECS, TRENDING_TAGS_INTERVAL_SECS,
+    },
+    storage::Storage,
+    utils::{mailer, pdf::PdfFont},
+};
+
+/// Spawns every periodic job onto the Tokio runtime.
+pub fn spawn_all(pool: PgPool, storage: Arc<dyn Storage>, config: &Config) {
+    spawn_periodic(
+        "question_sampling",
+        Duration::from_secs(QUESTION_SAMPLING_INTERVAL_SECS),
+        pool.clone(),
+        question_sampling::run,
+    );
+    spawn_periodic(
+        "search_index",
+        Duration::from_secs(SEARCH_INDEX_INTERVAL_SECS),
+        pool.clone(),
+        search_index::run,
+    );
+    spawn_periodic(
+        "post_excerpts",
+        Duration::from_secs(POST_EXCERPT_INTERVAL_SECS),
+        pool.clone(),
+        post_excerpts::run,
+    );
+    spawn_periodic(
+        "slugs",
+        Duration::from_secs(SLUG_BACKFILL_INTERVAL_SECS),
+        pool.clone(),
+        slugs::run,
+    );
+    spawn_periodic(
+        "trending_tags",
+        Duration::from_secs(TRENDING_TAGS_INTERVAL_SECS),
+        pool.clone(),
+        trending_tags::run,
+    );
+    spawn_periodic(
+        "revoked_tokens",
+        Duration::from_secs(REVOKED_TOKEN_PURGE_INTERVAL_SECS),
+        pool.clone(),
+        revoked_tokens::run,
+    );
+    spawn_periodic(
+        "retention",
+        Duration::from_secs(RETENTION_INTERVAL_SECS),
+        pool.clone(),
+        retention::run,
+    );
+    let attachment_storage = storage.clone();
+    spawn_periodic(
+        "contribution_attachments",
+        Duration::from_secs(RETENTION_INTERVAL_SECS),
+        pool.clone(),
+        move |pool| contribution_attachments::run(pool, attachment_storage.clone()),
+    );
+    let export_storage = storage.clone();
+    spawn_periodic(
+        "data_exports",
+        Duration::from_secs(RETENTION_INTERVAL_SECS),
+        pool.clone(),
+        move |pool| data_export::purge_expired(pool, export_storage.clone()),
+    );
+    spawn_periodic(
+        "account_deletion",
+        Duration::from_secs(ACCOUNT_DELETION_INTERVAL_SECS),
+        pool.clone(),
+        account_deletion::run,
+    );
+    let webhook_url = config.admin_digest_webhook_url.clone();
+    spawn_periodic(
+        "admin_digest",
+        Duration::from_secs(ADMIN_DIGEST_CHECK_INTERVAL_SECS),
+        pool.clone(),
+        move |pool| admin_digest::run(pool, webhook_url.clone()),
+    );
+    let mailer = mailer::from_config(&config.mail);
+    let font = config.pdf_font.as_deref().and_then(|path| {
+        PdfFont::load(path)
+            .map_err(|e| tracing::error!("Cannot use PDF font {}: {}", path.display(), e))
+            .ok()
+    });
+    if font.is_none() {
+        tracing::warn!("No PDF font; generated PDFs only render Latin text");
+    }
+    spawn_periodic(
+        "job_queue",
+        Duration::from_secs(JOB_QUEUE_POLL_INTERVAL_SECS),
+        pool,
+        move |pool| queue::run_pending(pool, storage.clone(), mailer.clone(), font.clone()),
+    );
+}
+
+/// Runs `job` every `period`. Failures are logged and retried on the next tick.
+fn spawn_periodic<F, Fut>(name: &'static str, period: Duration, pool: PgPool, job: F)
+where
+    F: Fn(PgPool) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), sqlx::Error>> + Send,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = job(pool.clone()).await {
+                tracing::error!("Background job '{}' failed: {:?}", name, e);
+            }
+        }
+    });
+}
@@ -0,0 +1,32 @@
+// src/jobs/post_excerpts.rs
+
+use sqlx::PgPool;
+
+use crate::utils::html::{excerpt, reading_time_minutes};
+
+/// Posts handled per run, so a large backlog doesn't hold everything at once.
+const BATCH_SIZE: i64 = 500;
+
+/// Fills the excerpt and reading time of posts that don't have them yet
+/// (posts created before they existed, or inserted directly in SQL).
+pub async fn run(pool: PgPool) -> Result<(), sqlx::Error> {
+    let posts = sqlx::query!(
+        "SELECT id, content FROM posts WHERE excerpt IS NULL ORDER BY id LIMIT $1",
+        BATCH_SIZE
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    for row in posts {
+        sqlx::query!(
+            "UPDATE posts SET excerpt = $1, reading_time_minutes = $2 WHERE id = $3",
+            excerpt(&row.content),
+            reading_time_minutes(&row.content),
+            row.id
+        )
+        .execute(&pool)
+        .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,25 @@
+// src/jobs/question_sampling.rs
+
+use sqlx::PgPool;
+
+/// Maintains the sampling index used by `services::question_selection`.
+///
+/// * Re-rolls `random_key` so the candidate windows rotate across the bank.
+/// * Halves `exposure_count` so it reflects recent traffic and new questions
+///   don't get served exclusively until they catch up.
+/// * Re-rolls the architectures' `random_key` too, so the uneven gaps between
+///   keys don't favour the same entries for `GET /api/architectures/random`.
+pub async fn run(pool: PgPool) -> Result<(), sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE questions SET random_key = random(), exposure_count = exposure_count / 2"
+    )
+    .execute(&pool)
+    .await?;
+
+    tracing::debug!("Rebalanced sampling keys for {} questions", result.rows_affected());
+
+    sqlx::query!("UPDATE architectures SET random_key = random()")
+        .execute(&pool)
+        .await?;
+    Ok(())
+}
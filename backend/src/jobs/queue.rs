@@ -0,0 +1,174 @@
+// src/jobs/queue.rs
+
+//! Durable job queue backed by the `jobs` table.
+//!
+//! Work is enqueued inside the caller's transaction and picked up by the
+//! `job_queue` periodic task. Claiming uses `FOR UPDATE SKIP LOCKED`, so several
+//! instances can drain the queue concurrently. A claim is a lease: a job still
+//! running `JOB_LEASE_SECS` after it was claimed is claimed again, as its
+//! worker died or could not record the outcome.
+
+use std::sync::Arc;
+
+use sqlx::{PgExecutor, PgPool};
+
+use crate::{
+    config::{JOB_LEASE_SECS, JOB_MAX_ATTEMPTS},
+    error::AppError,
+    jobs::{
+        architecture_dossier, broadcast, catalog_export, certificate_pdf, check_image_links,
+        data_export, image_placeholder, send_email, unfurl_link,
+    },
+    services::{
+        self, broadcasts, certificates, data_exports, dossiers, image_links, image_placeholders,
+        unfurl,
+    },
+    storage::Storage,
+    utils::{
+        mailer::{self, Mailer},
+        pdf::PdfFont,
+    },
+};
+
+/// A job taken off the queue; `attempts` counts this one.
+#[derive(Debug)]
+pub struct ClaimedJob {
+    pub id: i64,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+}
+
+/// Adds a job to the queue. Pass a transaction to enqueue atomically with other writes.
+pub async fn enqueue<'e, E: PgExecutor<'e>>(
+    executor: E,
+    kind: &str,
+    payload: serde_json::Value,
+) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query!(
+        "INSERT INTO jobs (kind, payload) VALUES ($1, $2) RETURNING id",
+        kind,
+        payload
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.id)
+}
+
+/// Drains every job that is due, one at a time. Generated PDFs embed `font`.
+pub async fn run_pending(
+    pool: PgPool,
+    storage: Arc<dyn Storage>,
+    mailer: Arc<dyn Mailer>,
+    font: Option<PdfFont>,
+) -> Result<(), sqlx::Error> {
+    while let Some(job) = claim_next(&pool).await? {
+        match dispatch(&pool, storage.as_ref(), mailer.as_ref(), font.as_ref(), &job).await {
+            Ok(()) => {
+                sqlx::query!(
+                    "UPDATE jobs SET status = 'done', finished_at = CURRENT_TIMESTAMP, last_error = NULL WHERE id = $1",
+                    job.id
+                )
+                .execute(&pool)
+                .await?;
+            }
+            Err(e) => {
+                tracing::warn!("Job {} ({}) failed on attempt {}: {}", job.id, job.kind, job.attempts, e);
+                // Retry with linear backoff until attempts run out.
+                let give_up = job.attempts >= JOB_MAX_ATTEMPTS;
+                sqlx::query!(
+                    r#"
+                    UPDATE jobs SET
+                        status = CASE WHEN $2 THEN 'failed' ELSE 'pending' END,
+                        last_error = $3,
+                        run_at = CURRENT_TIMESTAMP + make_interval(secs => 30 * attempts),
+                        finished_at = CASE WHEN $2 THEN CURRENT_TIMESTAMP ELSE NULL END
+                    WHERE id = $1
+                    "#,
+                    job.id,
+                    give_up,
+                    e.to_string()
+                )
+                .execute(&pool)
+                .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Claims the next due job: a pending one, or one whose lease ran out. Jobs
+/// whose lease ran out on their last attempt are marked failed instead.
+pub async fn claim_next(pool: &PgPool) -> Result<Option<ClaimedJob>, sqlx::Error> {
+    let lease_secs = JOB_LEASE_SECS as f64;
+
+    sqlx::query!(
+        r#"
+        UPDATE jobs SET
+            status = 'failed',
+            last_error = 'Lease expired while running',
+            finished_at = CURRENT_TIMESTAMP
+        WHERE status = 'running'
+          AND locked_at < CURRENT_TIMESTAMP - make_interval(secs => $1)
+          AND attempts >= $2
+        "#,
+        lease_secs,
+        JOB_MAX_ATTEMPTS
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query_as!(
+        ClaimedJob,
+        r#"
+        UPDATE jobs SET status = 'running', attempts = attempts + 1, locked_at = CURRENT_TIMESTAMP
+        WHERE id = (
+            SELECT id FROM jobs
+            WHERE (status = 'pending' AND run_at <= CURRENT_TIMESTAMP)
+               OR (status = 'running'
+                   AND locked_at < CURRENT_TIMESTAMP - make_interval(secs => $1)
+                   AND attempts < $2)
+            ORDER BY run_at
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, kind, payload, attempts
+        "#,
+        lease_secs,
+        JOB_MAX_ATTEMPTS
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+async fn dispatch(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    mailer: &dyn Mailer,
+    font: Option<&PdfFont>,
+    job: &ClaimedJob,
+) -> Result<(), AppError> {
+    match job.kind.as_str() {
+        certificates::RENDER_PDF_JOB => {
+            certificate_pdf::run(pool, storage, font, &job.payload).await
+        }
+        dossiers::DOSSIER_JOB => {
+            architecture_dossier::run(pool, storage, font, &job.payload).await
+        }
+        unfurl::UNFURL_JOB => unfurl_link::run(pool, &job.payload).await,
+        image_links::CHECK_IMAGE_LINKS_JOB => check_image_links::run(pool, &job.payload).await,
+        image_placeholders::PLACEHOLDER_JOB => image_placeholder::run(pool, &job.payload).await,
+        services::catalog_export::EXPORT_CATALOG_JOB => {
+            catalog_export::run(pool, storage, &job.payload).await
+        }
+        data_exports::EXPORT_USER_DATA_JOB => data_export::run(pool, storage, &job.payload).await,
+        mailer::SEND_EMAIL_JOB => send_email::run(mailer, &job.payload).await,
+        broadcasts::BROADCAST_JOB => broadcast::run(pool, &job.payload).await,
+        other => Err(AppError::InternalServerError(format!(
+            "Unknown job kind '{}'",
+            other
+        ))),
+    }
+}
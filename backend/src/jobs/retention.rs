@@ -0,0 +1,29 @@
+// src/jobs/retention.rs
+
+use sqlx::PgPool;
+
+use crate::{
+    config::TOMBSTONE_RETENTION_DAYS,
+    services::{api_usage, paper_snapshots},
+};
+
+/// Drops tombstones older than `TOMBSTONE_RETENTION_DAYS` (clients that last
+/// synced before then are told to start over instead), abandoned OAuth sign-ins,
+/// old API usage counts and snapshots of papers never submitted.
+pub async fn run(pool: PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM tombstones WHERE deleted_at < CURRENT_TIMESTAMP - make_interval(days => $1)",
+        TOMBSTONE_RETENTION_DAYS
+    )
+    .execute(&pool)
+    .await?;
+
+    sqlx::query!("DELETE FROM oauth_states WHERE expires_at < CURRENT_TIMESTAMP")
+        .execute(&pool)
+        .await?;
+
+    api_usage::purge(&pool).await?;
+    paper_snapshots::purge(&pool).await?;
+
+    Ok(())
+}
@@ -0,0 +1,16 @@
+// src/jobs/revoked_tokens.rs
+
+use sqlx::PgPool;
+
+/// Forgets revocations of tokens that have expired by now, as those are
+/// rejected on their expiry alone, and the sessions of such tokens.
+pub async fn run(pool: PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM revoked_tokens WHERE expires_at < CURRENT_TIMESTAMP")
+        .execute(&pool)
+        .await?;
+    sqlx::query!("DELETE FROM sessions WHERE expires_at < CURRENT_TIMESTAMP")
+        .execute(&pool)
+        .await?;
+
+    Ok(())
+}
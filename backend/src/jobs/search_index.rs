@@ -0,0 +1,41 @@
+// src/jobs/search_index.rs
+
+use sqlx::PgPool;
+
+use crate::utils::pinyin::pinyin_keys;
+
+/// Fills pinyin search keys for rows that don't have them yet
+/// (rows created before pinyin search existed, or inserted directly in SQL).
+pub async fn run(pool: PgPool) -> Result<(), sqlx::Error> {
+    let archs = sqlx::query!("SELECT id, name FROM architectures WHERE name_pinyin IS NULL")
+        .fetch_all(&pool)
+        .await?;
+    for row in archs {
+        let keys = pinyin_keys(&row.name);
+        sqlx::query!(
+            "UPDATE architectures SET name_pinyin = $1, name_initials = $2 WHERE id = $3",
+            keys.full,
+            keys.initials,
+            row.id
+        )
+        .execute(&pool)
+        .await?;
+    }
+
+    let terms = sqlx::query!("SELECT id, term FROM glossary_terms WHERE term_pinyin IS NULL")
+        .fetch_all(&pool)
+        .await?;
+    for row in terms {
+        let keys = pinyin_keys(&row.term);
+        sqlx::query!(
+            "UPDATE glossary_terms SET term_pinyin = $1, term_initials = $2 WHERE id = $3",
+            keys.full,
+            keys.initials,
+            row.id
+        )
+        .execute(&pool)
+        .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+// src/jobs/send_email.rs
+
+use crate::{
+    error::AppError,
+    utils::mailer::{Email, Mailer},
+};
+
+/// Delivers the email in `payload`.
+pub async fn run(mailer: &dyn Mailer, payload: &serde_json::Value) -> Result<(), AppError> {
+    let email: Email = serde_json::from_value(payload.clone())
+        .map_err(|e| AppError::BadRequest(format!("Invalid email payload: {}", e)))?;
+    mailer.send(&email).await?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+// src/jobs/slugs.rs
+
+use sqlx::PgPool;
+
+use crate::services::slugs;
+
+/// Rows handled per run, so a large backlog doesn't hold everything at once.
+const BATCH_SIZE: i64 = 500;
+
+/// Gives slugs to architectures and posts that don't have one yet
+/// (rows created before slugs existed, or inserted directly in SQL).
+pub async fn run(pool: PgPool) -> Result<(), sqlx::Error> {
+    let archs = sqlx::query!(
+        "SELECT id, name FROM architectures WHERE slug IS NULL ORDER BY id LIMIT $1",
+        BATCH_SIZE
+    )
+    .fetch_all(&pool)
+    .await?;
+    for row in archs {
+        let mut tx = pool.begin().await?;
+        let slug = slugs::for_architecture(&mut tx, &row.name).await?;
+        sqlx::query!(
+            "UPDATE architectures SET slug = $1 WHERE id = $2 AND slug IS NULL",
+            slug,
+            row.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+    }
+
+    let posts = sqlx::query!(
+        "SELECT id, title FROM posts WHERE slug IS NULL ORDER BY id LIMIT $1",
+        BATCH_SIZE
+    )
+    .fetch_all(&pool)
+    .await?;
+    for row in posts {
+        let mut tx = pool.begin().await?;
+        let slug = slugs::for_post(&mut tx, &row.title).await?;
+        sqlx::query!(
+            "UPDATE posts SET slug = $1 WHERE id = $2 AND slug IS NULL",
+            slug,
+            row.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+// src/jobs/trending_tags.rs
+
+use sqlx::PgPool;
+
+use crate::services::tags;
+
+/// Recomputes the trending tags.
+pub async fn run(pool: PgPool) -> Result<(), sqlx::Error> {
+    let count = tags::refresh_trending(&pool).await?;
+    tracing::debug!("Recomputed {} trending tags", count);
+    Ok(())
+}
@@ -0,0 +1,53 @@
+// src/jobs/unfurl_link.rs
+
+use sqlx::PgPool;
+
+use crate::{
+    error::AppError,
+    services::unfurl::{self, UnfurlError},
+};
+
+/// Fetches the preview for `payload.url` and stores the outcome.
+///
+/// Rejected URLs are recorded as failed right away; network errors are
+/// returned so the queue retries them.
+pub async fn run(pool: &PgPool, payload: &serde_json::Value) -> Result<(), AppError> {
+    let url = payload["url"]
+        .as_str()
+        .ok_or_else(|| AppError::BadRequest("Missing url".to_string()))?;
+
+    match unfurl::fetch_preview(url).await {
+        Ok(preview) => {
+            sqlx::query!(
+                r#"
+                UPDATE link_previews
+                SET status = 'ok', title = $2, description = $3, image_url = $4,
+                    error = NULL, fetched_at = NOW()
+                WHERE url = $1
+                "#,
+                url,
+                preview.title,
+                preview.description,
+                preview.image_url
+            )
+            .execute(pool)
+            .await?;
+            Ok(())
+        }
+        Err(UnfurlError::Rejected(reason)) => {
+            sqlx::query!(
+                r#"
+                UPDATE link_previews
+                SET status = 'failed', error = $2, fetched_at = NOW()
+                WHERE url = $1
+                "#,
+                url,
+                reason
+            )
+            .execute(pool)
+            .await?;
+            Ok(())
+        }
+        Err(UnfurlError::Transient(reason)) => Err(AppError::InternalServerError(reason)),
+    }
+}
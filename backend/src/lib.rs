@@ -1,11 +1,15 @@
 // src/lib.rs
 
+pub mod api_docs;
 pub mod config;
 pub mod error;
 pub mod handlers;
+pub mod jobs;
 pub mod models;
 pub mod routes;
+pub mod services;
 pub mod state;
+pub mod storage;
 pub mod utils;
 
 // Re-export specific items for convenience if needed
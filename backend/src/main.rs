@@ -1,18 +1,53 @@
 // src/main.rs
 
 use backend::config::Config;
+use backend::jobs;
 use backend::routes;
+use backend::services::{exam_events, integrity, question_import};
 use backend::state::AppState;
 use backend::utils::hash::hash_password;
 use dotenvy::dotenv;
-use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
+use sqlx::{Connection, PgConnection, PgPool};
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
-use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use backend::utils::audit_stream::{self, AuditLayer};
+use backend::utils::log_filter::LogFilter;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{
+    EnvFilter, Layer, filter::filter_fn, fmt, layer::SubscriberExt, reload,
+    util::SubscriberInitExt,
+};
+
+/// What the binary was asked to do.
+enum Command {
+    /// Run the HTTP server (no arguments).
+    Serve,
+    /// `import-questions <file>`: load questions into the bank, then exit.
+    ImportQuestions(PathBuf),
+    /// `verify-integrity [--repair]`: check (and repair) data invariants, then exit.
+    VerifyIntegrity { repair: bool },
+}
+
+fn parse_args() -> Command {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        [] => Command::Serve,
+        ["import-questions", file] => Command::ImportQuestions(PathBuf::from(file)),
+        ["verify-integrity"] => Command::VerifyIntegrity { repair: false },
+        ["verify-integrity", "--repair"] => Command::VerifyIntegrity { repair: true },
+        _ => {
+            eprintln!("Usage: backend [import-questions <file> | verify-integrity [--repair]]");
+            std::process::exit(2);
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() {
+    let command = parse_args();
+
     // Load .env file (if present)
     dotenv().ok();
 
@@ -21,24 +56,48 @@ async fn main() {
 
     let file_appender = tracing_appender::rolling::daily("logs", "app.log");
     let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
-    let env_filter = EnvFilter::new(&config.rust_log);
+    // Reloadable, so admins can change the level at runtime
+    let (env_filter, filter_handle) = reload::Layer::new(EnvFilter::new(&config.rust_log));
     let stdout_layer = fmt::layer().with_writer(std::io::stdout).with_target(false);
     let file_layer = fmt::layer().with_writer(non_blocking).with_ansi(false);
 
+    // Business events go to their own files, whatever the application log level
+    let audit_appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("audit")
+        .filename_suffix("log")
+        .max_log_files(config.audit_log.retention_days)
+        .build(&config.audit_log.dir)
+        .expect("Failed to open the audit log directory");
+    // Blocks rather than drops events when the writer falls behind
+    let (audit_writer, _audit_guard) = tracing_appender::non_blocking::NonBlockingBuilder::default()
+        .lossy(false)
+        .finish(audit_appender);
+
     // Initialize Tracing (Logging)
     tracing_subscriber::registry()
-        .with(env_filter)
-        .with(stdout_layer)
-        .with(file_layer)
+        .with(
+            stdout_layer
+                .and_then(file_layer)
+                .with_filter(env_filter)
+                .with_filter(filter_fn(|metadata| !audit_stream::is_audit(metadata))),
+        )
+        .with(AuditLayer::new(audit_writer).with_filter(filter_fn(audit_stream::is_audit)))
         .init();
 
+    // Panics in handlers become 500s; record where they happened for the report
+    backend::utils::error_report::install_panic_hook();
+
+    // Pooled connections log slow statements and cancel runaway ones
+    let connect_options = config.pg_connect_options();
+
     // Initialize Database Pool with Retry
     let mut retry_count = 0;
     let pool = loop {
         match PgPoolOptions::new()
             .max_connections(5)
             .acquire_timeout(Duration::from_secs(3))
-            .connect(&config.database_url)
+            .connect_with(connect_options.clone())
             .await
         {
             Ok(pool) => break pool,
@@ -59,23 +118,41 @@ async fn main() {
     tracing::info!("Database connected...");
 
     // Run Migrations Automatically
+    // A dedicated connection, so long backfills are not cut off by statement_timeout.
     tracing::info!("Running migrations...");
+    let mut migration_conn = PgConnection::connect(&config.database_url)
+        .await
+        .expect("Failed to open migration connection");
     sqlx::migrate!("./migrations")
-        .run(&pool)
+        .run(&mut migration_conn)
         .await
         .expect("Failed to run database migrations");
+    migration_conn.close().await.ok();
     tracing::info!("Migrations applied successfully.");
 
+    if let Command::ImportQuestions(file) = command {
+        let imported = import_questions(&pool, &file).await;
+        // Flush the log file before exiting
+        drop(_guard);
+        std::process::exit(if imported { 0 } else { 1 });
+    }
+    if let Command::VerifyIntegrity { repair } = command {
+        let clean = verify_integrity(&pool, repair).await;
+        drop(_guard);
+        std::process::exit(if clean { 0 } else { 1 });
+    }
+
     // Seed Admin User
     if let Err(e) = seed_admin_user(&pool, &config).await {
         tracing::error!("Failed to seed admin user: {:?}", e);
     }
 
     // Create AppState
-    let state = AppState {
-        pool: pool.clone(),
-        config: config.clone(),
-    };
+    let mut state = AppState::new(pool.clone(), config.clone());
+    state.log_filter = LogFilter::new(filter_handle, &config.rust_log);
+
+    // Start background jobs
+    jobs::spawn_all(pool.clone(), state.storage.clone(), &config);
 
     // Create the Axum application router
     let app = routes::create_router(state);
@@ -86,15 +163,99 @@ async fn main() {
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
 
-    // Start the server
-    axum::serve(listener, app).await.unwrap();
+    // Start the server (connect info lets handlers see the peer address)
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
+}
+
+/// Runs `import-questions`; returns whether it succeeded.
+async fn import_questions(pool: &PgPool, file: &Path) -> bool {
+    let json = match std::fs::read_to_string(file) {
+        Ok(json) => json,
+        Err(e) => {
+            tracing::error!("Cannot read {}: {}", file.display(), e);
+            return false;
+        }
+    };
+
+    match exam_events::running(pool).await {
+        Ok(Some(event)) => {
+            tracing::error!(
+                "The question bank is locked until exam event '{}' ends at {}",
+                event.title,
+                event.ends_at
+            );
+            return false;
+        }
+        Ok(None) => {}
+        Err(e) => {
+            tracing::error!("Import failed: {}", e);
+            return false;
+        }
+    }
+
+    match question_import::import_questions(pool, &json).await {
+        Ok(summary) => {
+            tracing::info!(
+                "Imported {} questions from {} ({} already present, {} non-question entries skipped)",
+                summary.imported,
+                file.display(),
+                summary.duplicates,
+                summary.skipped
+            );
+            true
+        }
+        Err(e) => {
+            tracing::error!("Import failed: {}", e);
+            false
+        }
+    }
+}
+
+/// Runs `verify-integrity`, printing the report; returns whether no violation is left.
+async fn verify_integrity(pool: &PgPool, repair: bool) -> bool {
+    let report = if repair {
+        integrity::repair(pool).await
+    } else {
+        integrity::verify(pool).await
+    };
+    match report {
+        Ok(report) => {
+            for check in &report.checks {
+                match check.remaining {
+                    Some(remaining) => tracing::info!(
+                        "{}: {} found, {} left",
+                        check.name,
+                        check.violations,
+                        remaining
+                    ),
+                    None => tracing::info!("{}: {} found", check.name, check.violations),
+                }
+                if !check.sample_ids.is_empty() {
+                    tracing::info!("  {} {:?}", check.description, check.sample_ids);
+                }
+            }
+            report.is_clean()
+        }
+        Err(e) => {
+            tracing::error!("Integrity check failed: {}", e);
+            false
+        }
+    }
 }
 
 async fn seed_admin_user(pool: &PgPool, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
     if let (Some(username), Some(password)) = (&config.admin_username, &config.admin_password) {
-        let user_exists = sqlx::query!("SELECT id FROM users WHERE username = $1", username)
-            .fetch_optional(pool)
-            .await?;
+        let user_exists = sqlx::query!(
+            "SELECT id FROM users WHERE username_normalized = normalize_username($1)",
+            username
+        )
+        .fetch_optional(pool)
+        .await?;
 
         if user_exists.is_none() {
             tracing::info!("Seeding admin user: {}", username);
@@ -0,0 +1,28 @@
+// src/models/admin_digest.rs
+
+use serde::{Deserialize, Serialize};
+
+/// Pending admin work, as sent in the daily digest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdminDigest {
+    pub pending_contributions: i64,
+    pub oldest_pending_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Open flags, and the distinct questions they are on.
+    pub open_flags: i64,
+    pub flagged_questions: i64,
+    /// Questions pulled from papers by repeated flags, awaiting review.
+    pub suspended_questions: i64,
+    /// Posts and comments reported in the last day.
+    pub reports: i64,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl AdminDigest {
+    /// Whether there is anything for admins to do.
+    pub fn is_empty(&self) -> bool {
+        self.pending_contributions == 0
+            && self.open_flags == 0
+            && self.suspended_questions == 0
+            && self.reports == 0
+    }
+}
@@ -0,0 +1,50 @@
+// src/models/api_usage.rs
+
+use serde::{Deserialize, Serialize};
+
+/// Authenticated requests made on one UTC day.
+#[derive(Debug, Serialize)]
+pub struct UsageDay {
+    pub date: chrono::NaiveDate,
+    pub requests: i64,
+}
+
+/// Response for `GET /api/profile/usage`.
+#[derive(Debug, Serialize)]
+pub struct UsageResponse {
+    /// Requests are sampled: every `sample_every`th one is recorded as that many.
+    pub sample_every: u32,
+    /// Days with requests over the last 30, oldest first.
+    pub days: Vec<UsageDay>,
+    pub total: i64,
+}
+
+/// A user who made far more requests on a day than they usually do.
+#[derive(Debug, Serialize)]
+pub struct UsageAnomaly {
+    pub user_id: i64,
+    pub username: String,
+    pub requests: i64,
+    /// Average requests per day over the preceding days, days without any included.
+    pub baseline: f64,
+    /// `requests` over `baseline`; None for users with no earlier requests.
+    pub ratio: Option<f64>,
+}
+
+/// Response for `GET /api/admin/usage/anomalies`.
+#[derive(Debug, Serialize)]
+pub struct UsageAnomalies {
+    pub date: chrono::NaiveDate,
+    /// Days before `date` the baseline is averaged over.
+    pub baseline_days: i32,
+    pub anomalies: Vec<UsageAnomaly>,
+}
+
+/// Query parameters for `GET /api/admin/usage/anomalies`.
+#[derive(Debug, Deserialize)]
+pub struct UsageAnomalyParams {
+    /// UTC day to look at (default: today).
+    pub date: Option<chrono::NaiveDate>,
+    /// How many users to list (default 20, max 100).
+    pub limit: Option<i64>,
+}
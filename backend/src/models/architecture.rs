@@ -5,11 +5,24 @@ use sqlx::{prelude::FromRow, types::Json};
 use validator::Validate;
 use url::Url;
 
+use crate::{
+    config::{ARCH_MAX_BAYS, ARCH_MAX_FOOTPRINT_M2, ARCH_MAX_HEIGHT_M},
+    utils::{
+        html::excerpt,
+        location::{ParsedLocation, parse_location},
+        text_length,
+        units::Measure,
+    },
+};
+
 /// Represents the 'architectures' table in the database.
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Architecture {
     pub id: i64,
 
+    /// Stable URL slug; None until given to entries older than slugs.
+    pub slug: Option<String>,
+
     /// Architecture category (e.g., "Palace", "Bridge").
     pub category: String,
 
@@ -18,8 +31,14 @@ pub struct Architecture {
     /// Historical dynasty (e.g., "Ming", "Qing").
     pub dynasty: String,
 
+    /// Original free-text location, kept as entered.
     pub location: String,
 
+    /// Structured location parsed from `location` (or set explicitly by an admin).
+    pub province: Option<String>,
+    pub city: Option<String>,
+    pub district: Option<String>,
+
     pub description: String,
 
     /// URL to the cover image.
@@ -29,25 +48,247 @@ pub struct Architecture {
     /// Stored as a JSON array in the database.
     /// `sqlx::types::Json` handles automatic serialization/deserialization.
     pub carousel_imgs: Json<Vec<String>>,
+
+    /// Height in metres.
+    pub height_m: Option<f64>,
+    /// Area covered, in square metres.
+    pub footprint_m2: Option<f64>,
+    /// Bays (间) across the front and in depth.
+    pub bays_wide: Option<i32>,
+    pub bays_deep: Option<i32>,
+
+    /// Placeholder for the cover image while it loads: its most common color
+    /// (`#rrggbb`) and BlurHash. None until the image has been processed.
+    pub cover_color: Option<String>,
+    pub cover_blurhash: Option<String>,
+}
+
+/// An architecture as admins see it, with where it came from.
+#[derive(Debug, Serialize)]
+pub struct AdminArchitecture {
+    #[serde(flatten)]
+    pub architecture: Architecture,
+
+    /// The approved contribution this entry came from (None if created by an admin).
+    pub contribution_id: Option<i64>,
+
+    /// The contributor or admin who authored this entry.
+    pub created_by: Option<i64>,
+}
+
+/// An architecture in lite list responses: no description or carousel.
+#[derive(Debug, Serialize)]
+pub struct ArchitectureSummary {
+    pub id: i64,
+    pub slug: Option<String>,
+    pub category: String,
+    pub name: String,
+    pub dynasty: String,
+    pub province: Option<String>,
+    pub city: Option<String>,
+    pub district: Option<String>,
+    /// Start of the description as plain text.
+    pub summary: String,
+    pub cover_img: String,
+    pub cover_color: Option<String>,
+    pub cover_blurhash: Option<String>,
+    pub height_m: Option<f64>,
+    pub footprint_m2: Option<f64>,
+    pub bays_wide: Option<i32>,
+    pub bays_deep: Option<i32>,
+}
+
+impl From<Architecture> for ArchitectureSummary {
+    fn from(a: Architecture) -> Self {
+        Self {
+            summary: excerpt(&a.description),
+            id: a.id,
+            slug: a.slug,
+            category: a.category,
+            name: a.name,
+            dynasty: a.dynasty,
+            province: a.province,
+            city: a.city,
+            district: a.district,
+            cover_img: a.cover_img,
+            cover_color: a.cover_color,
+            cover_blurhash: a.cover_blurhash,
+            height_m: a.height_m,
+            footprint_m2: a.footprint_m2,
+            bays_wide: a.bays_wide,
+            bays_deep: a.bays_deep,
+        }
+    }
 }
 
 /// DTO for creating a new architecture entry.
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateArchRequest {
-    #[validate(length(min = 1, max = 50))]
+    #[validate(custom(function = text_length::arch_label))]
     pub category: String,
-    #[validate(length(min = 1, max = 100))]
+    #[validate(custom(function = text_length::arch_name))]
     pub name: String,
-    #[validate(length(min = 1, max = 50))]
+    #[validate(custom(function = text_length::arch_label))]
     pub dynasty: String,
-    #[validate(length(min = 1, max = 200))]
+    #[validate(custom(function = text_length::arch_location))]
     pub location: String,
-    #[validate(length(min = 1, max = 20000))]
+    /// Optional structured location; parsed from `location` when all are omitted.
+    #[validate(custom(function = text_length::arch_label))]
+    pub province: Option<String>,
+    #[validate(custom(function = text_length::arch_label))]
+    pub city: Option<String>,
+    #[validate(custom(function = text_length::arch_label))]
+    pub district: Option<String>,
+    #[validate(custom(function = text_length::arch_description))]
     pub description: String,
     #[validate(length(min = 1, max = 500), custom(function = validate_url_string))]
     pub cover_img: String,
     #[validate(custom(function = validate_carousel_urls))]
     pub carousel_imgs: Vec<String>,
+    /// A number in metres, or text with a unit ("120 ft", "10 丈").
+    #[validate(custom(function = validate_height))]
+    pub height_m: Option<Measure>,
+    /// A number in square metres, or text with a unit ("2 亩").
+    #[validate(custom(function = validate_footprint))]
+    pub footprint_m2: Option<Measure>,
+    #[validate(range(min = 1, max = ARCH_MAX_BAYS))]
+    pub bays_wide: Option<i32>,
+    #[validate(range(min = 1, max = ARCH_MAX_BAYS))]
+    pub bays_deep: Option<i32>,
+}
+
+/// DTO for adding or replacing a translation. Omitted fields fall back to the source text.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpsertTranslationRequest {
+    #[validate(custom(function = text_length::arch_name))]
+    pub name: Option<String>,
+    #[validate(custom(function = text_length::arch_label))]
+    pub dynasty: Option<String>,
+    #[validate(custom(function = text_length::arch_location))]
+    pub location: Option<String>,
+    #[validate(custom(function = text_length::arch_description))]
+    pub description: Option<String>,
+}
+
+/// A community post pinned to an architecture page.
+#[derive(Debug, Serialize, FromRow)]
+pub struct PinnedPost {
+    pub id: i64,
+    pub title: String,
+    pub user_id: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The posts pinned to an architecture page; deleted posts are omitted.
+#[derive(Debug, Default, Serialize)]
+pub struct PinnedPosts {
+    /// The canonical discussion thread about this architecture.
+    pub discussion: Option<PinnedPost>,
+    /// Practical advice for visitors (opening hours, transport, ...).
+    pub visiting_tips: Option<PinnedPost>,
+}
+
+/// Dimensions in metric units, as stored.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Dimensions {
+    pub height_m: Option<f64>,
+    pub footprint_m2: Option<f64>,
+    pub bays_wide: Option<i32>,
+    pub bays_deep: Option<i32>,
+}
+
+/// One entry of `GET /api/architectures/compare`. Heights and areas are in
+/// the requested unit system.
+#[derive(Debug, Serialize)]
+pub struct ArchitectureComparison {
+    pub id: i64,
+    pub name: String,
+    pub category: String,
+    pub dynasty: String,
+    pub province: Option<String>,
+    pub cover_img: String,
+    pub height: Option<f64>,
+    pub footprint: Option<f64>,
+    pub bays_wide: Option<i32>,
+    pub bays_deep: Option<i32>,
+}
+
+/// Response for `GET /api/architectures/compare`.
+#[derive(Debug, Serialize)]
+pub struct Comparison {
+    /// 'metric' (m, m²) or 'imperial' (ft, ft²).
+    pub units: &'static str,
+    /// In the order the IDs were given.
+    pub architectures: Vec<ArchitectureComparison>,
+}
+
+/// Response for `GET /api/architectures/{id}`.
+#[derive(Debug, Serialize)]
+pub struct ArchitectureDetail {
+    #[serde(flatten)]
+    pub architecture: Architecture,
+    pub pinned_posts: PinnedPosts,
+    /// Posts that reference this entry with `[[arch:ID]]`.
+    pub mentioned_in_posts: i64,
+}
+
+/// DTO for setting the pinned posts. `null` or an omitted field unpins that slot.
+#[derive(Debug, Deserialize)]
+pub struct SetPinnedPostsRequest {
+    pub discussion_post_id: Option<i64>,
+    pub tips_post_id: Option<i64>,
+}
+
+/// DTO for merging a duplicate architecture into another.
+#[derive(Debug, Deserialize)]
+pub struct MergeArchitectureRequest {
+    /// The architecture that stays.
+    pub keep_id: i64,
+}
+
+impl CreateArchRequest {
+    /// Structured location to store: the explicit fields if any were given,
+    /// otherwise whatever can be parsed from `location`.
+    pub fn structured_location(&self) -> ParsedLocation {
+        if self.province.is_some() || self.city.is_some() || self.district.is_some() {
+            ParsedLocation {
+                province: self.province.clone(),
+                city: self.city.clone(),
+                district: self.district.clone(),
+            }
+        } else {
+            parse_location(&self.location)
+        }
+    }
+
+    /// Dimensions to store, converted to metric units. Only call on a
+    /// validated request; unparsable measures are dropped.
+    pub fn dimensions(&self) -> Dimensions {
+        Dimensions {
+            height_m: self.height_m.as_ref().and_then(Measure::metres),
+            footprint_m2: self.footprint_m2.as_ref().and_then(Measure::square_metres),
+            bays_wide: self.bays_wide,
+            bays_deep: self.bays_deep,
+        }
+    }
+}
+
+/// A height must have a known length unit and be at most `ARCH_MAX_HEIGHT_M`.
+pub(crate) fn validate_height(height: &Measure) -> Result<(), validator::ValidationError> {
+    match height.metres() {
+        Some(m) if m <= ARCH_MAX_HEIGHT_M => Ok(()),
+        Some(_) => Err(validator::ValidationError::new("height_out_of_range")),
+        None => Err(validator::ValidationError::new("invalid_length")),
+    }
+}
+
+/// A footprint must have a known area unit and be at most `ARCH_MAX_FOOTPRINT_M2`.
+pub(crate) fn validate_footprint(footprint: &Measure) -> Result<(), validator::ValidationError> {
+    match footprint.square_metres() {
+        Some(m2) if m2 <= ARCH_MAX_FOOTPRINT_M2 => Ok(()),
+        Some(_) => Err(validator::ValidationError::new("footprint_out_of_range")),
+        None => Err(validator::ValidationError::new("invalid_area")),
+    }
 }
 
 /// Validates that a string is a correctly formatted URL.
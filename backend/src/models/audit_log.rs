@@ -0,0 +1,45 @@
+// src/models/audit_log.rs
+
+use serde::{Deserialize, Serialize};
+
+/// One changed field of an audited action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    /// Old value; null if the field was unset.
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+/// An audit log row, as listed (without the diff itself).
+#[derive(Debug, Serialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub admin_id: Option<i64>,
+    pub admin_username: Option<String>,
+    /// 'create', 'update', 'delete', 'review' or 'merge'.
+    pub action: String,
+    /// 'architecture', 'question', 'user', 'contribution', 'glossary_term',
+    /// 'terms' or 'broadcast'.
+    pub entity_type: String,
+    pub entity_id: i64,
+    /// Names of the changed fields.
+    pub fields: Vec<String>,
+    /// Given by the admin for changes that require one.
+    pub reason: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Response of `GET /api/admin/audit-logs/{id}/diff`.
+#[derive(Debug, Serialize)]
+pub struct AuditDiff {
+    pub id: i64,
+    pub admin_id: Option<i64>,
+    pub admin_username: Option<String>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub reason: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub changes: Vec<FieldChange>,
+}
@@ -0,0 +1,18 @@
+// src/models/certificate.rs
+
+use serde::Serialize;
+use sqlx::FromRow;
+
+/// Public view of a certificate, returned by the verification endpoint.
+/// The rendered PDF is served separately.
+#[derive(Debug, Serialize, FromRow)]
+pub struct CertificateResponse {
+    pub code: String,
+    pub username: String,
+    /// 'qualification' or 'quiz'.
+    pub kind: String,
+    pub score: f64,
+    pub issued_at: chrono::DateTime<chrono::Utc>,
+    /// False until the background job has rendered the PDF.
+    pub pdf_ready: bool,
+}
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use validator::Validate;
 
+use crate::utils::text_length;
+
 /// Represents the 'comments' table in the database.
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Comment {
@@ -19,11 +21,7 @@ pub struct Comment {
 /// DTO for creating a new comment.
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateCommentRequest {
-    #[validate(length(
-        min = 1,
-        max = 1000,
-        message = "Comment must be between 1 and 1000 characters"
-    ))]
+    #[validate(custom(function = text_length::comment))]
     pub content: String,
 
     /// Optional: the ID of the comment being replied to.
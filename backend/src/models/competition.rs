@@ -0,0 +1,115 @@
+// src/models/competition.rs
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{models::question::PublicQuestion, utils::text_length};
+
+/// A competition as listed, with what the viewer has done in it.
+#[derive(Debug, Serialize)]
+pub struct Competition {
+    pub id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub starts_at: chrono::DateTime<chrono::Utc>,
+    pub ends_at: chrono::DateTime<chrono::Utc>,
+    pub question_count: i32,
+    pub registrations: i64,
+    /// Always false for anonymous viewers.
+    pub registered: bool,
+    pub submitted: bool,
+    /// Whether the viewer agreed to proctoring signals when registering.
+    pub proctoring_consent: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// DTO for creating a competition.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateCompetitionRequest {
+    #[validate(custom(function = text_length::competition_title))]
+    pub title: String,
+    #[validate(custom(function = text_length::competition_description))]
+    pub description: Option<String>,
+    pub starts_at: chrono::DateTime<chrono::Utc>,
+    pub ends_at: chrono::DateTime<chrono::Utc>,
+    /// The paper, in the order it is served.
+    pub question_ids: Vec<i64>,
+}
+
+/// DTO for registering for a competition. The body may be omitted.
+#[derive(Debug, Default, Deserialize)]
+pub struct RegisterCompetitionRequest {
+    /// Agreement to have proctoring signals stored with the submission.
+    /// Omitted on a repeated registration keeps the earlier answer.
+    pub proctoring_consent: Option<bool>,
+}
+
+/// The paper served to a registered user while the competition runs.
+#[derive(Debug, Serialize)]
+pub struct CompetitionPaper {
+    pub competition_id: i64,
+    pub questions: Vec<PublicQuestion>,
+    /// When the user first opened the paper; their time counts from here.
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub ends_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// DTO for submitting a competition paper.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SubmitCompetitionRequest {
+    /// Key: question ID. Value: the option letters picked.
+    pub answers: HashMap<i64, String>,
+    /// Counted by the client while the paper was open. Stored only for
+    /// entrants who consented when registering.
+    #[validate(nested)]
+    pub proctoring: Option<ProctoringSignals>,
+}
+
+/// What the client noticed while the paper was open.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ProctoringSignals {
+    /// Times the page lost focus, e.g. by switching tabs.
+    #[validate(range(min = 0, max = 10000))]
+    pub focus_loss_count: i32,
+    /// Times text was pasted into an answer.
+    #[validate(range(min = 0, max = 10000))]
+    pub paste_count: i32,
+}
+
+/// The score of a submitted paper.
+#[derive(Debug, Serialize)]
+pub struct CompetitionSubmission {
+    pub score: i32,
+    pub total_questions: i32,
+    pub submitted_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A row of the final ranking.
+#[derive(Debug, Serialize)]
+pub struct CompetitionResult {
+    pub rank: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub score: i32,
+    /// Time from opening the paper to submitting it.
+    pub duration_ms: i64,
+    pub submitted_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An entrant as seen by admins, with their proctoring signals.
+#[derive(Debug, Serialize)]
+pub struct CompetitionEntry {
+    pub user_id: i64,
+    pub username: String,
+    pub registered_at: chrono::DateTime<chrono::Utc>,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub submitted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub score: Option<i32>,
+    pub proctoring_consent: bool,
+    /// None when not reported, or without consent.
+    pub focus_loss_count: Option<i32>,
+    pub paste_count: Option<i32>,
+}
@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use validator::Validate;
 
-/// Represents the 'contributions' table.
+use crate::utils::text_length;
+
+/// Represents the 'contributions' table (without the reviewer).
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Contribution {
     pub id: i64,
@@ -10,7 +12,10 @@ pub struct Contribution {
     pub r#type: String, // 'architecture' or 'question'
     pub data: serde_json::Value,
     pub status: String, // 'pending', 'approved', 'rejected'
-    pub admin_comment: Option<String>,
+    /// Approved without review under the auto-approval rules.
+    pub auto_approved: bool,
+    /// Messages in the review thread, see `ContributionMessage`.
+    pub message_count: i64,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub reviewed_at: Option<chrono::DateTime<chrono::Utc>>,
 }
@@ -42,3 +47,161 @@ fn validate_data_size(data: &serde_json::Value) -> Result<(), validator::Validat
     }
     Ok(())
 }
+
+/// How far a user is trusted with contributions, from their review history.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrustLevel {
+    /// Nothing approved yet.
+    New,
+    Contributor,
+    /// Meets `AutoApprovalConfig`; contributions of the configured types skip review.
+    Trusted,
+}
+
+/// An auto-approved contribution awaiting an admin's check.
+#[derive(Debug, Serialize)]
+pub struct UnauditedContribution {
+    pub id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub r#type: String,
+    pub data: serde_json::Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// The architecture or question it published; None if since deleted.
+    pub published_id: Option<i64>,
+}
+
+/// Body of `POST /api/admin/contributions/{id}/revert` and `.../rollback`.
+#[derive(Debug, Deserialize, Validate)]
+pub struct RevertContributionRequest {
+    /// Added to the review thread for the contributor.
+    #[validate(custom(function = text_length::contribution_message))]
+    pub reason: Option<String>,
+}
+
+/// A reference file attached to a contribution.
+#[derive(Debug, Serialize)]
+pub struct ContributionAttachment {
+    pub id: i64,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Short-lived link to the file; only given to reviewers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+}
+
+/// A message in the review thread of a contribution.
+#[derive(Debug, Serialize)]
+pub struct ContributionMessage {
+    pub id: i64,
+    /// None once the author's account is deleted.
+    pub author_id: Option<i64>,
+    pub author_username: Option<String>,
+    /// True for reviewers, false for the contributor.
+    pub from_reviewer: bool,
+    pub body: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// DTO for adding a message to a review thread.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateContributionMessageRequest {
+    #[validate(custom(function = text_length::contribution_message))]
+    pub body: String,
+}
+
+/// Query parameters of `POST /api/contributions/{id}/attachments`.
+#[derive(Debug, Deserialize)]
+pub struct AttachmentParams {
+    /// The original file name, shown to reviewers.
+    pub filename: Option<String>,
+}
+
+/// Existing content that a contribution appears to duplicate.
+#[derive(Debug, Serialize)]
+pub struct DuplicateMatch {
+    /// 'architecture', 'question' or 'contribution' (a pending submission).
+    pub source: String,
+    pub id: i64,
+    /// The architecture name or question text that matched.
+    pub title: String,
+}
+
+/// Result of `POST /api/contributions/validate` for a payload that passed validation.
+#[derive(Debug, Serialize)]
+pub struct ContributionCheck {
+    pub r#type: String,
+    /// The data as it would be published on approval, with HTML sanitized.
+    pub cleaned: serde_json::Value,
+    /// Likely duplicates. Submitting is still allowed; reviewers decide.
+    pub duplicates: Vec<DuplicateMatch>,
+    /// False when the user already submitted today, so submitting would return 409.
+    pub can_submit_today: bool,
+}
+
+/// One row of `GET /api/contributions/leaderboard`.
+#[derive(Debug, Serialize)]
+pub struct ContributorRanking {
+    /// Users with the same count share a rank.
+    pub rank: i64,
+    pub username: String,
+    /// Contributions approved within the window.
+    pub approved: i64,
+    pub architectures: i64,
+    pub questions: i64,
+}
+
+/// Response of `GET /api/contributions/leaderboard`.
+#[derive(Debug, Serialize)]
+pub struct ContributorLeaderboard {
+    /// The selected window ('7d', '30d' or 'all').
+    pub window: String,
+    pub entries: Vec<ContributorRanking>,
+}
+
+/// Review activity of one admin. Reviews from before reviewers were recorded,
+/// or by since-deleted admins, are grouped under a null `admin_id`.
+#[derive(Debug, Serialize)]
+pub struct ReviewerMetrics {
+    pub admin_id: Option<i64>,
+    pub admin_username: Option<String>,
+    pub reviewed: i64,
+    pub approved: i64,
+    /// Percentage of reviews that approved, one decimal.
+    pub approval_rate: f64,
+    pub median_review_secs: Option<f64>,
+}
+
+/// Pending contributions in one age range.
+#[derive(Debug, Serialize)]
+pub struct AgeBucket {
+    /// '<1d', '1-3d', '3-7d' or '>7d'.
+    pub label: &'static str,
+    pub count: i64,
+}
+
+/// How long pending contributions have been waiting.
+#[derive(Debug, Serialize)]
+pub struct BacklogAge {
+    pub pending: i64,
+    pub oldest_created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub buckets: Vec<AgeBucket>,
+}
+
+/// Response of `GET /api/admin/contributions/metrics`.
+#[derive(Debug, Serialize)]
+pub struct ContributionMetrics {
+    /// The selected window ('7d', '30d' or '90d').
+    pub window: String,
+    /// Contributions reviewed within the window.
+    pub reviewed: i64,
+    /// Time from submission to review; null when nothing was reviewed.
+    pub median_review_secs: Option<f64>,
+    pub p90_review_secs: Option<f64>,
+    /// Most active reviewers first.
+    pub reviewers: Vec<ReviewerMetrics>,
+    pub backlog: BacklogAge,
+}
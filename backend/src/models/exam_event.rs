@@ -0,0 +1,26 @@
+// src/models/exam_event.rs
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::utils::text_length;
+
+/// Represents the 'exam_events' table.
+#[derive(Debug, Serialize)]
+pub struct ExamEvent {
+    pub id: i64,
+    pub title: String,
+    pub starts_at: chrono::DateTime<chrono::Utc>,
+    pub ends_at: chrono::DateTime<chrono::Utc>,
+    pub created_by: Option<i64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// DTO for scheduling an exam event.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateExamEventRequest {
+    #[validate(custom(function = text_length::exam_event_title))]
+    pub title: String,
+    pub starts_at: chrono::DateTime<chrono::Utc>,
+    pub ends_at: chrono::DateTime<chrono::Utc>,
+}
@@ -2,6 +2,9 @@
 
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use validator::Validate;
+
+use crate::utils::text_length;
 
 /// Represents the 'exam_records' table in the database.
 /// Stores the results of user quizzes.
@@ -10,7 +13,10 @@ pub struct ExamRecord {
     pub id: i64,
     pub user_id: i64,
     pub score: i64,
+    /// When the score was last submitted.
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the score was reached; unchanged by retakes that don't beat it.
+    pub achieved_at: chrono::DateTime<chrono::Utc>,
 }
 
 /// Aggregated struct for displaying the leaderboard.
@@ -20,6 +26,8 @@ pub struct LeaderboardEntry {
     pub username: String,
     pub score: i64,
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the score was reached.
+    pub achieved_at: chrono::DateTime<chrono::Utc>,
 }
 
 /// DTO for returning generated exam.
@@ -28,12 +36,60 @@ pub struct ExamResponse {
     pub questions: Vec<crate::models::question::PublicQuestion>,
     pub exam_token: String,
     pub expires_in: u64, // seconds
+    /// The accommodations this paper was built with.
+    pub accommodations: ExamAccommodations,
+}
+
+/// DTO for returning a generated practice paper.
+#[derive(Debug, Serialize)]
+pub struct PracticePaper {
+    pub questions: Vec<crate::models::question::PublicQuestion>,
+    /// Names the snapshot the paper is scored against; submit it with the answers.
+    pub exam_token: String,
+    pub expires_in: u64, // seconds
+}
+
+/// Exam accommodations an admin granted to a user.
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct ExamAccommodations {
+    /// Factor applied to the exam time limit.
+    pub time_multiplier: f64,
+    /// Whether clients should render option labels larger.
+    pub large_labels: bool,
+    /// Questions on the paper instead of the standard count; None for all.
+    pub question_count: Option<i32>,
+}
+
+impl Default for ExamAccommodations {
+    /// The standard exam.
+    fn default() -> Self {
+        Self {
+            time_multiplier: 1.0,
+            large_labels: false,
+            question_count: None,
+        }
+    }
+}
+
+/// DTO for granting exam accommodations to a user.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetExamAccommodationsRequest {
+    #[validate(range(min = 1.0, max = 3.0))]
+    pub time_multiplier: f64,
+    #[serde(default)]
+    pub large_labels: bool,
+    /// At most the standard 20 questions.
+    #[validate(range(min = 5, max = 20))]
+    pub question_count: Option<i32>,
+    /// Kept in the audit log.
+    #[validate(custom(function = text_length::admin_reason))]
+    pub reason: String,
 }
 
 /// DTO for submitting a quiz attempt.
 #[derive(Debug, Deserialize)]
 pub struct SubmitExamRequest {
-    /// The token received from generate_exam.
+    /// The token received with the paper or exam.
     pub exam_token: String,
 
     /// User's answers map.
@@ -41,3 +97,54 @@ pub struct SubmitExamRequest {
     /// Value: User's selected option (String)
     pub answers: std::collections::HashMap<i64, String>,
 }
+
+/// A submitted practice paper as it was scored.
+#[derive(Debug, Serialize)]
+pub struct AttemptPaper {
+    pub attempt_id: i64,
+    pub score: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub questions: Vec<AttemptQuestion>,
+}
+
+/// A question of a submitted paper with the user's answer. The answer key
+/// and analysis are left out: the bank is shared with the qualification
+/// exam.
+#[derive(Debug, Serialize)]
+pub struct AttemptQuestion {
+    #[serde(flatten)]
+    pub question: crate::models::question::PublicQuestion,
+    pub user_answer: String,
+    pub is_correct: bool,
+}
+
+/// Answers and accuracy within one group of questions.
+#[derive(Debug, Serialize, FromRow)]
+pub struct AccuracyBucket {
+    /// The question type ('single' / 'multiple') or difficulty ('easy' / 'medium' / 'hard').
+    pub label: String,
+    pub answered: i64,
+    pub correct: i64,
+    /// Correct share in percent, one decimal.
+    pub accuracy: f64,
+}
+
+/// Practice statistics for the current user, over all submitted quiz papers.
+#[derive(Debug, Serialize)]
+pub struct QuizStats {
+    pub attempts: i64,
+    pub questions_answered: i64,
+    pub correct_answers: i64,
+    pub accuracy: f64,
+    pub best_score: Option<i32>,
+    /// Share of players (in percent) whose best score is lower than this user's;
+    /// None before the first attempt.
+    pub percentile: Option<f64>,
+    /// Consecutive UTC days with at least one attempt, ending today or yesterday.
+    pub current_streak_days: i64,
+    pub longest_streak_days: i64,
+    pub by_type: Vec<AccuracyBucket>,
+    /// Difficulty is judged from every player's accuracy on each question:
+    /// 'easy' at 70% or above, 'hard' below 40%.
+    pub by_difficulty: Vec<AccuracyBucket>,
+}
@@ -0,0 +1,18 @@
+// src/models/experiment.rs
+
+use serde::Serialize;
+
+/// Exposed users of one variant.
+#[derive(Debug, Serialize)]
+pub struct VariantExposures {
+    pub variant: String,
+    pub users: i64,
+}
+
+/// Exposure counts of a running experiment, for the admin dashboard.
+#[derive(Debug, Serialize)]
+pub struct ExperimentSummary {
+    pub key: String,
+    /// Every configured variant, in order, including those nobody has seen yet.
+    pub variants: Vec<VariantExposures>,
+}
@@ -0,0 +1,49 @@
+// src/models/glossary.rs
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::utils::text_length;
+
+/// Represents the 'glossary_terms' table.
+#[derive(Debug, Serialize, FromRow)]
+pub struct GlossaryTerm {
+    pub id: i64,
+
+    /// Stable identifier used in URLs and references (e.g. "dougong").
+    pub slug: String,
+
+    pub term: String,
+
+    /// Toneless pinyin of the term, e.g. "dou gong".
+    pub term_pinyin: Option<String>,
+
+    pub definition: String,
+
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// DTO for creating a glossary term.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateGlossaryTermRequest {
+    /// Defaults to the pinyin of `term` when omitted.
+    #[validate(length(min = 1, max = 100))]
+    pub slug: Option<String>,
+    #[validate(custom(function = text_length::glossary_term))]
+    pub term: String,
+    #[validate(custom(function = text_length::glossary_definition))]
+    pub definition: String,
+}
+
+/// DTO for updating a glossary term. All fields are optional.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateGlossaryTermRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub slug: Option<String>,
+    #[validate(custom(function = text_length::glossary_term))]
+    pub term: Option<String>,
+    #[validate(custom(function = text_length::glossary_definition))]
+    pub definition: Option<String>,
+}
@@ -0,0 +1,34 @@
+// src/models/integrity.rs
+
+use serde::Serialize;
+
+/// The outcome of one invariant check.
+#[derive(Debug, Serialize)]
+pub struct IntegrityCheck {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Rows breaking the invariant when the check ran.
+    pub violations: i64,
+    /// The first few of their IDs (see `description` for which table).
+    pub sample_ids: Vec<i64>,
+    /// Violations left after repairing; absent when only checking.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<i64>,
+}
+
+/// Response for `GET /api/admin/system/integrity` and
+/// `POST /api/admin/system/integrity/repair`.
+#[derive(Debug, Serialize)]
+pub struct IntegrityReport {
+    pub repaired: bool,
+    pub checked_at: chrono::DateTime<chrono::Utc>,
+    pub checks: Vec<IntegrityCheck>,
+}
+
+impl IntegrityReport {
+    /// Whether no violation is left.
+    pub fn is_clean(&self) -> bool {
+        self.checks.iter().all(|c| c.remaining.unwrap_or(c.violations) == 0)
+    }
+}
+
@@ -0,0 +1,65 @@
+// src/models/message.rs
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::utils::text_length;
+
+/// Represents the 'messages' table.
+#[derive(Debug, Serialize)]
+pub struct DirectMessage {
+    pub id: i64,
+    pub conversation_id: i64,
+    pub sender_id: i64,
+    pub body: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// When the recipient first saw the message.
+    pub read_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// One conversation in `GET /api/conversations`, from the current user's side.
+#[derive(Debug, Serialize)]
+pub struct ConversationSummary {
+    pub id: i64,
+    /// The other participant.
+    pub user_id: i64,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub last_message: Option<String>,
+    pub last_message_at: chrono::DateTime<chrono::Utc>,
+    /// Messages from the other participant the current user has not read.
+    pub unread: i64,
+}
+
+/// Response of `GET /api/conversations`.
+#[derive(Debug, Serialize)]
+pub struct ConversationList {
+    /// Unread messages across all conversations.
+    pub unread: i64,
+    pub conversations: Vec<ConversationSummary>,
+}
+
+/// Query parameters of `GET /api/conversations`.
+#[derive(Debug, Deserialize)]
+pub struct ConversationListParams {
+    /// `last_message_at` of the last conversation on the previous page.
+    pub cursor: Option<chrono::DateTime<chrono::Utc>>,
+    /// Default 20, at most 100.
+    pub limit: Option<i64>,
+}
+
+/// Query parameters of `GET /api/conversations/{id}/messages`.
+#[derive(Debug, Deserialize)]
+pub struct MessageListParams {
+    /// Only messages older than this message ID, for paging backwards.
+    pub before: Option<i64>,
+    /// Default 50, at most 100.
+    pub limit: Option<i64>,
+}
+
+/// DTO for sending a private message.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SendMessageRequest {
+    #[validate(custom(function = text_length::direct_message))]
+    pub body: String,
+}
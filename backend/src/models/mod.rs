@@ -1,9 +1,29 @@
 // src/models/mod.rs
 
+pub mod admin_digest;
+pub mod api_usage;
 pub mod architecture;
+pub mod audit_log;
+pub mod certificate;
 pub mod comment;
+pub mod competition;
 pub mod contribution;
+pub mod exam_event;
 pub mod exam_record;
+pub mod experiment;
+pub mod glossary;
+pub mod integrity;
+pub mod message;
+pub mod notification;
 pub mod post;
 pub mod question;
+pub mod quota;
+pub mod security_event;
+pub mod series;
+pub mod session;
+pub mod spam;
+pub mod stats;
+pub mod sync;
+pub mod tag;
+pub mod terms;
 pub mod user;
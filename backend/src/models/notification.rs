@@ -0,0 +1,35 @@
+// src/models/notification.rs
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Represents the 'notifications' table.
+#[derive(Debug, Serialize)]
+pub struct Notification {
+    pub id: i64,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub read_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Response of `GET /api/notifications`.
+#[derive(Debug, Serialize)]
+pub struct NotificationList {
+    pub unread: i64,
+    pub notifications: Vec<Notification>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationParams {
+    /// Only list notifications that have not been read.
+    #[serde(default)]
+    pub unread_only: bool,
+}
+
+/// DTO for marking notifications as read. Omitting `ids` marks all of them.
+#[derive(Debug, Deserialize, Validate)]
+pub struct MarkReadRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub ids: Option<Vec<i64>>,
+}
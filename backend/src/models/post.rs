@@ -2,17 +2,31 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use validator::Validate;
 
+use crate::{
+    config::POST_TAG_LIMIT,
+    models::{series::SeriesNav, spam::FormTraps, tag::Tag},
+    utils::{html::excerpt, pinyin::slugify, text_length},
+};
+
 /// Represents the 'posts' table in the database.
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Post {
     pub id: i64,
     pub user_id: i64,
+    /// Stable URL slug; None until given to posts older than slugs.
+    pub slug: Option<String>,
     pub title: String,
+    /// The content rendered from Markdown and sanitized, safe to display.
     pub content: String,
+    /// Start of the content as plain text; None until computed for older posts.
+    pub excerpt: Option<String>,
+    pub reading_time_minutes: Option<i32>,
 
     // Using chrono for proper time handling
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the author last edited the post; None if never edited.
+    pub edited_at: Option<chrono::DateTime<chrono::Utc>>,
     pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
 
     pub likes_count: i32,
@@ -28,22 +42,150 @@ pub struct Post {
     pub is_favorited: bool,
 }
 
+/// A post in lite list responses: a summary instead of the content.
+#[derive(Debug, Serialize)]
+pub struct PostSummary {
+    pub id: i64,
+    pub user_id: i64,
+    pub slug: Option<String>,
+    pub title: String,
+    /// Start of the content as plain text.
+    pub summary: String,
+    pub reading_time_minutes: Option<i32>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub likes_count: i32,
+    pub comments_count: i32,
+    pub favorites_count: i32,
+    pub is_liked: bool,
+    pub is_favorited: bool,
+}
+
+impl From<Post> for PostSummary {
+    fn from(p: Post) -> Self {
+        Self {
+            summary: p.excerpt.unwrap_or_else(|| excerpt(&p.content)),
+            reading_time_minutes: p.reading_time_minutes,
+            id: p.id,
+            user_id: p.user_id,
+            slug: p.slug,
+            title: p.title,
+            created_at: p.created_at,
+            likes_count: p.likes_count,
+            comments_count: p.comments_count,
+            favorites_count: p.favorites_count,
+            is_liked: p.is_liked,
+            is_favorited: p.is_favorited,
+        }
+    }
+}
+
+/// Preview of a URL linked from a post, fetched in the background.
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkCard {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+}
+
+/// Placeholder for an image in a post, painted until the image loads.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImagePlaceholder {
+    pub url: String,
+    /// Most common color, as `#rrggbb`.
+    pub dominant_color: String,
+    pub blurhash: String,
+    /// Size of the image in pixels.
+    pub width: i32,
+    pub height: i32,
+}
+
+/// An architecture or glossary term referenced from a post with `[[arch:..]]` or `[[term:..]]`.
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityLink {
+    /// 'architecture' or 'term'.
+    pub kind: String,
+    pub id: i64,
+    /// Glossary slug; None for architectures.
+    pub slug: Option<String>,
+    /// Architecture name or glossary term.
+    pub title: String,
+}
+
+/// Response for `GET /api/posts/{id}`.
+#[derive(Debug, Serialize)]
+pub struct PostDetail {
+    #[serde(flatten)]
+    pub post: Post,
+    /// The Markdown source, to edit from. Only sent to the author; posts
+    /// older than the kept source fall back to `content`.
+    pub content_md: Option<String>,
+    /// Previews for the links in `content` that have been fetched so far.
+    pub link_cards: Vec<LinkCard>,
+    /// Placeholders for the images in `content` that have been processed so far.
+    pub images: Vec<ImagePlaceholder>,
+    /// Entities referenced in `content`.
+    pub references: Vec<EntityLink>,
+    /// Previous/next parts when the post belongs to a series.
+    pub series: Option<SeriesNav>,
+    pub tags: Vec<Tag>,
+}
+
 /// DTO for creating a new post.
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreatePostRequest {
-    #[validate(length(
-        min = 1,
-        max = 100,
-        message = "Title length must be between 1 and 100 chars"
-    ))]
+    #[validate(custom(function = text_length::post_title))]
     pub title: String,
 
-    #[validate(length(
-        min = 1,
-        max = 10000,
-        message = "Content length must be between 1 and 10000 chars"
-    ))]
+    /// Markdown, rendered to sanitized HTML.
+    #[validate(custom(function = text_length::post_content))]
     pub content: String,
+
+    /// Up to five topics, e.g. ["斗拱", "辽代"]. Spellings with the same slug are one tag.
+    #[serde(default)]
+    #[validate(custom(function = validate_tags))]
+    pub tags: Vec<String>,
+
+    #[serde(flatten)]
+    pub traps: FormTraps,
+}
+
+/// DTO for editing a post. Omitted fields are kept.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdatePostRequest {
+    #[validate(custom(function = text_length::post_title))]
+    pub title: Option<String>,
+
+    /// Markdown, as when creating.
+    #[validate(custom(function = text_length::post_content))]
+    pub content: Option<String>,
+}
+
+/// A version of a post replaced by an edit.
+#[derive(Debug, Serialize)]
+pub struct PostRevision {
+    pub id: i64,
+    pub title: String,
+    /// Sanitized HTML, as in [`Post`].
+    pub content: String,
+    /// The Markdown source; only sent to the author.
+    pub content_md: Option<String>,
+    /// When the author replaced this version.
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Limits the number of tags, their length, and requires each to have a slug.
+fn validate_tags(tags: &[String]) -> Result<(), validator::ValidationError> {
+    if tags.len() > POST_TAG_LIMIT {
+        return Err(validator::ValidationError::new("too_many_tags"));
+    }
+    for tag in tags {
+        text_length::tag_name(tag.trim())?;
+        if slugify(tag).is_empty() {
+            return Err(validator::ValidationError::new("invalid_tag"));
+        }
+    }
+    Ok(())
 }
 
 /// Query parameters for listing posts.
@@ -60,4 +202,7 @@ pub struct PostListParams {
 
     /// Search keyword for title match.
     pub q: Option<String>,
+
+    /// 'following' for posts by users the caller follows only (requires login).
+    pub feed: Option<String>,
 }
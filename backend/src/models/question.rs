@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::{prelude::FromRow, types::Json};
 use validator::Validate;
 
+use crate::utils::{math, text_length};
+
 /// Represents the 'questions' table in the database.
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Question {
@@ -21,6 +23,10 @@ pub struct Question {
     /// Stored as a JSON array in the database.
     pub options: Json<Vec<String>>,
 
+    /// Image URL per option, aligned with `options` by index; None (or a
+    /// missing entry) where an option has no image.
+    pub option_images: Json<Vec<Option<String>>>,
+
     /// The correct answer key or content.
     pub answer: String,
 
@@ -28,6 +34,12 @@ pub struct Question {
     pub analysis: Option<String>,
 
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// The approved contribution this question came from (None if created by an admin).
+    pub contribution_id: Option<i64>,
+
+    /// The contributor or admin who authored this question.
+    pub created_by: Option<i64>,
 }
 
 /// DTO for sending question to client (excludes answer and analysis).
@@ -38,6 +50,38 @@ pub struct PublicQuestion {
     pub question_type: String,
     pub content: String,
     pub options: Json<Vec<String>>,
+    /// The options ready to display, in the same order as `options`.
+    pub option_details: Vec<OptionDetail>,
+}
+
+/// How to display an option of a served question.
+#[derive(Debug, Serialize)]
+pub struct OptionDetail {
+    /// The option as safe HTML, with `$...$` math rendered as MathML.
+    pub html: String,
+    pub image_url: Option<String>,
+}
+
+impl PublicQuestion {
+    /// The question without its answer, with options rendered for display.
+    pub fn from_question(q: Question) -> Self {
+        let option_details = q
+            .options
+            .iter()
+            .enumerate()
+            .map(|(i, text)| OptionDetail {
+                html: math::render(text),
+                image_url: q.option_images.get(i).cloned().flatten(),
+            })
+            .collect();
+        Self {
+            id: q.id,
+            question_type: q.question_type,
+            content: q.content,
+            options: q.options,
+            option_details,
+        }
+    }
 }
 
 /// DTO for creating a new question.
@@ -45,13 +89,16 @@ pub struct PublicQuestion {
 pub struct CreateQuestionRequest {
     #[validate(length(min = 1, max = 20), custom(function = validate_question_type))]
     pub question_type: String,
-    #[validate(length(min = 1, max = 1000))]
+    #[validate(custom(function = text_length::question_content))]
     pub content: String,
     #[validate(custom(function = validate_options))]
     pub options: Vec<String>,
-    #[validate(length(min = 1, max = 500))]
+    /// Image URL per option, aligned with `options`; may be shorter.
+    #[validate(custom(function = validate_option_images))]
+    pub option_images: Option<Vec<Option<String>>>,
+    #[validate(custom(function = text_length::question_answer))]
     pub answer: String,
-    #[validate(length(max = 2000))]
+    #[validate(custom(function = text_length::question_analysis))]
     pub analysis: Option<String>,
 }
 
@@ -69,9 +116,174 @@ fn validate_options(options: &[String]) -> Result<(), validator::ValidationError
         return Err(validator::ValidationError::new("options_cannot_be_empty"));
     }
     for opt in options {
-        if opt.len() > 500 {
+        if text_length::question_option(opt).is_err() {
             return Err(validator::ValidationError::new("option_too_long"));
         }
     }
     Ok(())
 }
+
+/// Option images must be web or site-relative URLs, so `javascript:` and
+/// `data:` links never reach the page.
+pub(crate) fn validate_option_images(
+    images: &[Option<String>],
+) -> Result<(), validator::ValidationError> {
+    for url in images.iter().flatten() {
+        if url.len() > 500 {
+            return Err(validator::ValidationError::new("url_too_long"));
+        }
+        let web = url.starts_with("https://") || url.starts_with("http://");
+        let relative = url.starts_with('/') && !url.starts_with("//");
+        if !(web || relative) || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(validator::ValidationError::new("invalid_image_url"));
+        }
+    }
+    Ok(())
+}
+
+/// DTO for adding or replacing a translation. Omitted fields fall back to the
+/// source text. Answers are option letters, so translated `options` must keep
+/// the source's count and order.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpsertQuestionTranslationRequest {
+    #[validate(custom(function = text_length::question_content))]
+    pub content: Option<String>,
+    #[validate(custom(function = validate_options))]
+    pub options: Option<Vec<String>>,
+    #[validate(custom(function = text_length::question_analysis))]
+    pub analysis: Option<String>,
+}
+
+/// Represents the 'question_translations' table.
+#[derive(Debug, Serialize, FromRow)]
+pub struct QuestionTranslation {
+    pub locale: String,
+    pub content: Option<String>,
+    pub options: Option<Json<Vec<String>>>,
+    pub analysis: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Represents the 'question_flags' table.
+#[derive(Debug, Serialize, FromRow)]
+pub struct QuestionFlag {
+    pub id: i64,
+    pub question_id: i64,
+    pub user_id: i64,
+    /// 'wrong_answer', 'ambiguous', 'typo' or 'other'.
+    pub reason: String,
+    pub comment: Option<String>,
+    /// 'open', 'resolved' or 'dismissed'.
+    pub status: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// DTO for flagging a question.
+#[derive(Debug, Deserialize, Validate)]
+pub struct FlagQuestionRequest {
+    #[validate(custom(function = validate_flag_reason))]
+    pub reason: String,
+    #[validate(custom(function = text_length::flag_comment))]
+    pub comment: Option<String>,
+}
+
+/// Restricts flag reasons to the values allowed by the database.
+fn validate_flag_reason(reason: &str) -> Result<(), validator::ValidationError> {
+    if !matches!(reason, "wrong_answer" | "ambiguous" | "typo" | "other") {
+        return Err(validator::ValidationError::new("invalid_flag_reason"));
+    }
+    Ok(())
+}
+
+/// A question in the admin triage queue, with its open flags.
+#[derive(Debug, Serialize)]
+pub struct FlaggedQuestion {
+    pub question_id: i64,
+    pub content: String,
+    pub answer: String,
+    /// When the question was pulled from papers; None while it is still served.
+    pub suspended_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub flags: Vec<QuestionFlag>,
+}
+
+/// DTO for closing the open flags of a question.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResolveFlagsRequest {
+    /// 'resolved' (the question was fixed) or 'dismissed' (the flags were wrong).
+    #[validate(custom(function = validate_flag_resolution))]
+    pub status: String,
+}
+
+fn validate_flag_resolution(status: &str) -> Result<(), validator::ValidationError> {
+    if status != "resolved" && status != "dismissed" {
+        return Err(validator::ValidationError::new("invalid_flag_resolution"));
+    }
+    Ok(())
+}
+
+/// A question generated on the fly from an architecture entry.
+///
+/// Not stored and not part of the curated bank: it never appears in exams or
+/// quizzes, carries no id, and ships with its answer for client-side checking.
+#[derive(Debug, Serialize)]
+pub struct AutoQuestion {
+    /// What is asked about: 'dynasty', 'province' or 'category'.
+    pub kind: &'static str,
+    pub prompt: String,
+    pub options: Vec<String>,
+    pub answer: String,
+}
+
+/// The "test yourself" widget for an architecture detail page.
+#[derive(Debug, Serialize)]
+pub struct ArchitectureQuiz {
+    pub architecture_id: i64,
+    /// Always 'generated', to tell these apart from bank questions.
+    pub source: &'static str,
+    pub questions: Vec<AutoQuestion>,
+}
+
+/// A question in a duplicate cluster.
+#[derive(Debug, Serialize)]
+pub struct DuplicateQuestion {
+    pub id: i64,
+    #[serde(rename = "type")]
+    pub question_type: String,
+    pub content: String,
+    pub answer: String,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub contribution_id: Option<i64>,
+    pub created_by: Option<i64>,
+    /// Papers the question was served in recently; a hint for which copy to keep.
+    pub exposure_count: i64,
+}
+
+/// Questions whose content is near-identical, oldest first.
+#[derive(Debug, Serialize)]
+pub struct DuplicateCluster {
+    /// The highest similarity between two questions of the cluster (0-1).
+    pub similarity: f32,
+    pub questions: Vec<DuplicateQuestion>,
+}
+
+/// DTO for merging duplicate questions into the copy that is kept.
+#[derive(Debug, Deserialize, Validate)]
+pub struct MergeQuestionsRequest {
+    #[validate(length(min = 1, max = 100), nested)]
+    pub merges: Vec<QuestionMerge>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct QuestionMerge {
+    pub keep_id: i64,
+    #[validate(length(min = 1, max = 50))]
+    pub duplicate_ids: Vec<i64>,
+}
+
+/// DTO for deleting several questions at once.
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeleteQuestionsRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub ids: Vec<i64>,
+}
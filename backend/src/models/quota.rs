@@ -0,0 +1,27 @@
+// src/models/quota.rs
+
+use serde::Serialize;
+
+/// How much of one quota is used.
+#[derive(Debug, Serialize)]
+pub struct QuotaUsage {
+    pub used: i64,
+    /// None when unlimited (not configured, or the user is an admin).
+    pub limit: Option<i64>,
+}
+
+impl QuotaUsage {
+    pub fn remaining(&self) -> Option<i64> {
+        self.limit.map(|limit| (limit - self.used).max(0))
+    }
+}
+
+/// Response for `GET /api/profile/limits`.
+#[derive(Debug, Serialize)]
+pub struct QuotaStatus {
+    /// Posts written in the last 24 hours, deleted ones included.
+    pub posts_per_day: QuotaUsage,
+    /// Images embedded in the user's posts that are not deleted.
+    pub images: QuotaUsage,
+    pub favorites: QuotaUsage,
+}
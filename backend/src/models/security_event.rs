@@ -0,0 +1,52 @@
+// src/models/security_event.rs
+
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+/// An IP address or user with many security events in the window.
+#[derive(Debug, Serialize, FromRow)]
+pub struct Offender {
+    pub ip: Option<String>,
+    pub user_id: Option<i64>,
+    pub username: Option<String>,
+    pub events: i64,
+    pub last_seen: chrono::DateTime<chrono::Utc>,
+}
+
+/// Summary served by the admin abuse dashboard.
+#[derive(Debug, Serialize)]
+pub struct AbuseSummary {
+    /// The selected window ('1h', '24h', '7d' or '30d').
+    pub window: String,
+    pub since: chrono::DateTime<chrono::Utc>,
+    pub rate_limit_hits: i64,
+    pub failed_logins: i64,
+    pub reports: i64,
+    /// Registrations and posts that failed the bot checks.
+    pub spam_suspected: i64,
+    pub top_ips: Vec<Offender>,
+    pub top_users: Vec<Offender>,
+}
+
+/// An event on the user's own account, as listed by
+/// `GET /api/profile/security-events`.
+#[derive(Debug, Serialize)]
+pub struct SecurityEvent {
+    pub id: i64,
+    /// 'login', 'login_failed', 'registered' or 'password_changed'.
+    pub kind: String,
+    /// Browser and system read from the user agent, e.g. "Firefox on Windows".
+    pub device: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Query parameters for `GET /api/profile/security-events`.
+#[derive(Debug, Deserialize)]
+pub struct SecurityEventParams {
+    /// Page size (default 20, max 100).
+    pub limit: Option<i64>,
+    /// Only events before this time, for the next page.
+    pub cursor: Option<chrono::DateTime<chrono::Utc>>,
+}
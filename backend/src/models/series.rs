@@ -0,0 +1,73 @@
+// src/models/series.rs
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::utils::text_length;
+
+/// Represents the 'series' table in the database.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Series {
+    pub id: i64,
+    pub user_id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One part of a series.
+#[derive(Debug, Serialize)]
+pub struct SeriesPart {
+    /// 1-based, counting only posts that are not deleted.
+    pub part: i64,
+    pub post_id: i64,
+    pub title: String,
+}
+
+/// Response for `GET /api/series/{id}`.
+#[derive(Debug, Serialize)]
+pub struct SeriesDetail {
+    #[serde(flatten)]
+    pub series: Series,
+    pub posts: Vec<SeriesPart>,
+}
+
+/// A neighbouring part, linked from post detail.
+#[derive(Debug, Serialize)]
+pub struct SeriesLink {
+    pub post_id: i64,
+    pub title: String,
+}
+
+/// Where a post sits in its series, shown on post detail.
+#[derive(Debug, Serialize)]
+pub struct SeriesNav {
+    pub id: i64,
+    pub title: String,
+    pub part: i64,
+    pub total: i64,
+    pub previous: Option<SeriesLink>,
+    pub next: Option<SeriesLink>,
+}
+
+/// DTO for creating a series.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateSeriesRequest {
+    #[validate(custom(function = text_length::series_title))]
+    pub title: String,
+    #[validate(custom(function = text_length::series_description))]
+    pub description: Option<String>,
+}
+
+/// DTO for appending a post to a series.
+#[derive(Debug, Deserialize)]
+pub struct AddSeriesPostRequest {
+    pub post_id: i64,
+}
+
+/// DTO for reordering a series. Must list every part exactly once.
+#[derive(Debug, Deserialize)]
+pub struct ReorderSeriesRequest {
+    pub post_ids: Vec<i64>,
+}
@@ -0,0 +1,19 @@
+// src/models/session.rs
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// A signed-in device, as listed by `GET /api/profile/sessions`.
+#[derive(Debug, Serialize)]
+pub struct Session {
+    pub id: i64,
+    /// Browser and system read from the user agent, e.g. "Firefox on Windows".
+    pub device: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session the request was made with.
+    pub current: bool,
+}
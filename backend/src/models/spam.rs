@@ -0,0 +1,14 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// Fields a registration or post form may carry besides its own, for the
+/// bot checks in `services::spam`. Flattened into the request DTOs.
+#[derive(Debug, Default, Deserialize)]
+pub struct FormTraps {
+    /// From `GET /api/auth/form-token` when the form was shown.
+    pub form_token: Option<String>,
+    /// Anything else, including the honeypot field if it was filled in.
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
+}
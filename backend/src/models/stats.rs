@@ -0,0 +1,67 @@
+// src/models/stats.rs
+
+use serde::Serialize;
+
+/// Non-sensitive site-wide aggregates for the "about the project" page.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicStats {
+    pub total_architectures: i64,
+    /// Users with at least one approved contribution.
+    pub verified_contributors: i64,
+    pub questions_in_bank: i64,
+    /// Posts created in the last 7 days.
+    pub posts_this_week: i64,
+    /// When these numbers were computed; they are cached for an hour.
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One day with activity in a user's heatmap.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityDay {
+    /// UTC date.
+    pub date: chrono::NaiveDate,
+    /// Contributions submitted, whatever their review outcome.
+    pub contributions: i64,
+    /// Posts written and not deleted.
+    pub posts: i64,
+    /// Quiz papers submitted.
+    pub quizzes: i64,
+    pub total: i64,
+}
+
+/// Response for `GET /api/profile/activity-heatmap`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityHeatmap {
+    pub from: chrono::NaiveDate,
+    pub to: chrono::NaiveDate,
+    /// Days with any activity, oldest first; days without are left out.
+    pub days: Vec<ActivityDay>,
+    /// Sum of `total` over all days.
+    pub total: i64,
+    /// When this was computed; heatmaps are cached for ten minutes.
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Soft real-time count of active users for `GET /api/stats/online`.
+#[derive(Debug, Serialize)]
+pub struct OnlineStats {
+    pub online_users: usize,
+    /// A user is online if they made an authenticated request within this many seconds.
+    pub window_secs: u64,
+}
+
+/// An active user as listed for admins.
+#[derive(Debug, Serialize)]
+pub struct OnlineUser {
+    pub id: i64,
+    pub username: String,
+    pub last_seen_secs_ago: u64,
+}
+
+/// Admin view of who is online right now.
+#[derive(Debug, Serialize)]
+pub struct AdminOnlineStats {
+    pub online_users: usize,
+    pub window_secs: u64,
+    pub users: Vec<OnlineUser>,
+}
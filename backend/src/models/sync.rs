@@ -0,0 +1,41 @@
+// src/models/sync.rs
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct SyncParams {
+    /// The `until` of the previous sync (or the `generated_at` of a catalog bundle).
+    pub since: chrono::DateTime<chrono::Utc>,
+}
+
+/// IDs of the entries of one kind that changed since the cursor.
+#[derive(Debug, Default, Serialize)]
+pub struct EntityChanges {
+    pub created: Vec<i64>,
+    pub updated: Vec<i64>,
+    pub deleted: Vec<i64>,
+}
+
+impl EntityChanges {
+    pub fn push(&mut self, id: i64, created: bool) {
+        if created {
+            self.created.push(id);
+        } else {
+            self.updated.push(id);
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncChanges {
+    pub since: chrono::DateTime<chrono::Utc>,
+    /// Pass as `since` next time. Slightly behind the server clock, so the next
+    /// sync may repeat a few IDs but never misses a change still being committed.
+    pub until: chrono::DateTime<chrono::Utc>,
+    /// True when `since` is older than the tombstone retention, so some
+    /// deletions may be missing: drop the local copy and download it again.
+    pub reset_required: bool,
+    pub architectures: EntityChanges,
+    pub glossary: EntityChanges,
+    pub questions: EntityChanges,
+}
@@ -0,0 +1,60 @@
+// src/models/tag.rs
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::post::PostSummary;
+
+/// A tag as shown on posts and topic pages.
+#[derive(Debug, Clone, Serialize)]
+pub struct Tag {
+    pub slug: String,
+    pub name: String,
+}
+
+/// A tag in `GET /api/tags/trending`, with its activity over the window.
+#[derive(Debug, Serialize)]
+pub struct TrendingTag {
+    pub slug: String,
+    pub name: String,
+    /// Visible posts with the tag in the window.
+    pub posts: i64,
+    /// Distinct authors of those posts.
+    pub authors: i64,
+    /// Posts weighted by age, newer ones counting more; the list is ordered by it.
+    pub score: f64,
+    pub computed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An architecture on a topic page.
+#[derive(Debug, Serialize)]
+pub struct RelatedArchitecture {
+    pub id: i64,
+    pub name: String,
+    pub category: String,
+    pub dynasty: String,
+    pub cover_img: String,
+    /// Posts with the tag that reference the architecture.
+    pub mentions: i64,
+}
+
+/// Response for `GET /api/tags/{slug}`.
+#[derive(Debug, Serialize)]
+pub struct TopicPage {
+    pub tag: Tag,
+    /// Visible posts with the tag, over all pages.
+    pub posts_count: i64,
+    /// Newest first, one page.
+    pub posts: Vec<PostSummary>,
+    /// Architectures named by the tag or referenced from its posts.
+    pub architectures: Vec<RelatedArchitecture>,
+}
+
+/// Query parameters for a topic page.
+#[derive(Debug, Deserialize)]
+pub struct TopicParams {
+    /// Cursor for pagination: the created_at timestamp of the last post in the previous page.
+    pub cursor: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Number of posts to return (default: 20, max: 100).
+    pub limit: Option<i64>,
+}
@@ -0,0 +1,33 @@
+// src/models/terms.rs
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::utils::text_length;
+
+/// Represents the 'terms_versions' table in the database.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct TermsVersion {
+    pub id: i64,
+    /// Label shown to users and sent back on acceptance, e.g. "2025-12".
+    pub version: String,
+    pub content: String,
+    pub published_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// DTO for publishing a new terms version (Admin).
+#[derive(Debug, Deserialize, Validate)]
+pub struct PublishTermsRequest {
+    #[validate(length(min = 1, max = 20))]
+    pub version: String,
+    #[validate(custom(function = text_length::terms_content))]
+    pub content: String,
+}
+
+/// DTO for accepting the current terms.
+#[derive(Debug, Deserialize, Validate)]
+pub struct AcceptTermsRequest {
+    #[validate(length(min = 1, max = 20))]
+    pub version: String,
+}
@@ -3,11 +3,22 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use validator::Validate;
+
+use crate::{
+    config::PROFILE_URL_MAX_LEN,
+    models::{exam_record::ExamAccommodations, spam::FormTraps},
+    utils::text_length,
+};
 use regex::Regex;
-use std::sync::LazyLock;
+use std::{collections::BTreeMap, sync::LazyLock};
 
 static USERNAME_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9_]+$").unwrap());
 
+/// Whether `name` passes the checks a username chosen at registration does.
+pub fn is_valid_username(name: &str) -> bool {
+    USERNAME_REGEX.is_match(name) && text_length::username(name).is_ok()
+}
+
 /// Represents the 'users' table in the database.
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct User {
@@ -37,9 +48,200 @@ pub struct MeResponse {
     pub username: String,
     pub role: String,
     pub is_verified: bool,
+    /// Preferred content language; None means "follow Accept-Language".
+    pub locale: Option<String>,
+    pub birth_year: Option<i32>,
+    pub region: Option<String>,
+    pub email: Option<String>,
+    /// Whether `email` has been confirmed through the emailed link.
+    pub email_verified: bool,
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Public profile fields, see `PublicProfile`.
+    pub bio: Option<String>,
+    pub location: Option<String>,
+    pub website: Option<String>,
+    pub avatar_url: Option<String>,
     pub posts_count: i64,
     pub total_likes_received: i64,
+    /// Variant per running A/B experiment, e.g. {"onboarding_flow": "control"}.
+    pub experiments: BTreeMap<String, String>,
+    /// Exam accommodations granted by an admin; None for the standard exam.
+    pub exam_accommodations: Option<ExamAccommodations>,
+    /// When the account will be deleted, if the user asked for it.
+    pub deletion_scheduled_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// The ban in force on the account, if any.
+    pub ban: Option<Ban>,
+}
+
+/// What anyone can see about a user, for clicking through from a post or comment author.
+#[derive(Debug, Serialize)]
+pub struct PublicProfile {
+    pub id: i64,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub location: Option<String>,
+    pub website: Option<String>,
+    pub is_verified: bool,
+    /// Posts not deleted.
+    pub posts_count: i64,
+    pub followers_count: i64,
+    pub following_count: i64,
+    pub joined_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// One follower in `GET /api/users/{id}/followers`.
+#[derive(Debug, Serialize)]
+pub struct Follower {
+    pub id: i64,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub followed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Query parameters of `GET /api/users/{id}/followers`.
+#[derive(Debug, Deserialize)]
+pub struct FollowerListParams {
+    /// `followed_at` of the last follower on the previous page.
+    pub cursor: Option<chrono::DateTime<chrono::Utc>>,
+    /// Default 20, at most 100.
+    pub limit: Option<i64>,
+}
+
+/// A user the current user blocked or muted, in `GET /api/profile/blocks`.
+#[derive(Debug, Serialize)]
+pub struct BlockedUser {
+    pub id: i64,
+    pub username: String,
+    pub avatar_url: Option<String>,
+    pub since: chrono::DateTime<chrono::Utc>,
+}
+
+/// Response of `GET /api/profile/blocks`.
+#[derive(Debug, Serialize)]
+pub struct BlockList {
+    pub blocked: Vec<BlockedUser>,
+    pub muted: Vec<BlockedUser>,
+}
+
+/// One entry of `GET /api/users/{id}/activity`.
+#[derive(Debug, Serialize)]
+pub struct ActivityItem {
+    /// `post`, `comment` or `contribution`.
+    pub kind: String,
+    /// ID of the post, comment or contribution.
+    pub id: i64,
+    /// Title of the post, or of the post commented on; name of a contributed
+    /// architecture. Null for contributed questions.
+    pub title: Option<String>,
+    pub excerpt: Option<String>,
+    /// Post a comment belongs to.
+    pub post_id: Option<i64>,
+    /// `architecture` or `question`, for contributions.
+    pub contribution_type: Option<String>,
+    /// Architecture or question a contribution was published as, unless it
+    /// has since been deleted.
+    pub published_id: Option<i64>,
+    /// When it was posted; for contributions, when they were approved.
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Query parameters of `GET /api/users/{id}/activity`.
+#[derive(Debug, Deserialize)]
+pub struct ActivityParams {
+    /// `created_at` of the last item on the previous page.
+    pub cursor: Option<chrono::DateTime<chrono::Utc>>,
+    /// Default 20, at most 100.
+    pub limit: Option<i64>,
+}
+
+/// DTO for replacing the current user's public profile fields.
+/// Omitted, null or blank fields are cleared.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateProfileRequest {
+    #[validate(custom(function = text_length::profile_bio))]
+    pub bio: Option<String>,
+    #[validate(custom(function = text_length::profile_location))]
+    pub location: Option<String>,
+    #[validate(custom(function = validate_profile_url))]
+    pub website: Option<String>,
+    #[validate(custom(function = validate_profile_url))]
+    pub avatar_url: Option<String>,
+}
+
+/// Profile links must be absolute web URLs, so `javascript:` and `data:`
+/// links never reach the page. Blank values are allowed and mean "not set".
+fn validate_profile_url(url: &str) -> Result<(), validator::ValidationError> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Ok(());
+    }
+    if url.len() > PROFILE_URL_MAX_LEN {
+        return Err(validator::ValidationError::new("url_too_long"));
+    }
+    let web = matches!(
+        url::Url::parse(url),
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.host_str().is_some()
+    );
+    if !web || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(validator::ValidationError::new("invalid_url"));
+    }
+    Ok(())
+}
+
+/// A ban in force: the user may read but not write.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Ban {
+    /// None for an indefinite ban.
+    pub banned_until: Option<chrono::DateTime<chrono::Utc>>,
+    pub reason: String,
+}
+
+
+/// What deleting a user touches. Returned as the body of an admin dry run.
+#[derive(Debug, Serialize)]
+pub struct UserDeletionImpact {
+    pub user_id: i64,
+    pub username: String,
+    /// Posts (including soft-deleted ones) reassigned to the ghost user.
+    pub posts_to_transfer: i64,
+    /// Comments reassigned to the ghost user.
+    pub comments_to_transfer: i64,
+    // The following are removed by ON DELETE CASCADE.
+    pub contributions_to_delete: i64,
+    pub likes_to_delete: i64,
+    pub favorites_to_delete: i64,
+    pub exam_records_to_delete: i64,
+}
+
+/// DTO for deleting one's own account.
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeleteAccountRequest {
+    /// The current password, to confirm.
+    #[validate(length(min = 1, max = 128))]
+    pub password: String,
+}
+
+/// Response for `DELETE /api/profile/me`.
+#[derive(Debug, Serialize)]
+pub struct AccountDeletionResponse {
+    /// When the account will be deleted unless the deletion is cancelled.
+    pub deletion_scheduled_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The current user's latest data export, in `/api/profile/export`.
+#[derive(Debug, Serialize)]
+pub struct DataExport {
+    pub id: i64,
+    /// `pending`, `ready`, `failed` or `expired`.
+    pub status: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub ready_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the archive is deleted.
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub size_bytes: Option<i64>,
+    /// Short-lived link to the gzipped JSON archive, while it is ready.
+    pub download_url: Option<String>,
 }
 
 /// DTO for a favorited post item, including joined post info.
@@ -54,11 +256,7 @@ pub struct FavoritePostResponse {
 /// DTO for creating a new user (Registration).
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateUserRequest {
-    #[validate(length(
-        min = 3,
-        max = 50,
-        message = "Username length must be between 3 and 50 characters."
-    ), regex(
+    #[validate(custom(function = text_length::username), regex(
         path = *USERNAME_REGEX,
         message = "Username can only contain alphanumeric characters and underscores."
     ))]
@@ -69,6 +267,127 @@ pub struct CreateUserRequest {
         message = "Password length must be between 4 and 128 characters."
     ))]
     pub password: String,
+    /// Required once terms of service are published; must name the current version.
+    pub accepted_terms_version: Option<String>,
+    /// Required when a minimum age is configured.
+    pub birth_year: Option<i32>,
+    /// Required when registration regions are configured.
+    #[validate(length(min = 1, max = 50))]
+    pub region: Option<String>,
+    /// For confirming and recovering the account; required when configured.
+    #[validate(email(message = "Email address is not valid."), length(max = 254))]
+    pub email: Option<String>,
+    #[serde(flatten)]
+    pub traps: FormTraps,
+}
+
+/// DTO for setting the current user's email address.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateEmailRequest {
+    #[validate(email(message = "Email address is not valid."), length(max = 254))]
+    pub email: String,
+}
+
+/// DTO for finishing an OAuth sign-in with what the provider sent back.
+#[derive(Debug, Deserialize, Validate)]
+pub struct OAuthCallbackRequest {
+    #[validate(length(min = 1, max = 512))]
+    pub code: String,
+    #[validate(length(min = 1, max = 128))]
+    pub state: String,
+    /// Accepts the current terms; required when the sign-in creates an
+    /// account and terms are published.
+    pub accepted_terms_version: Option<String>,
+    /// Code from the authenticator app, required when the account that signs
+    /// in has two-factor authentication.
+    #[validate(length(min = 1, max = 16))]
+    pub totp_code: Option<String>,
+}
+
+/// DTO for a code from the authenticator app.
+#[derive(Debug, Deserialize, Validate)]
+pub struct TwoFactorCodeRequest {
+    #[validate(length(min = 1, max = 16))]
+    pub code: String,
+}
+
+/// DTO for asking for a password reset link.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(length(min = 1, max = 254))]
+    pub email: String,
+}
+
+/// DTO for setting a new password with a reset token.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub token: String,
+    #[validate(length(
+        min = 4,
+        max = 128,
+        message = "Password length must be between 4 and 128 characters."
+    ))]
+    pub password: String,
+}
+
+/// DTO for confirming an email address.
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyEmailRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub token: String,
+}
+
+/// Preferences of the current user. Unknown keys are rejected; omitted keys
+/// take their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserSettings {
+    /// Preferred content language, one of `SUPPORTED_LOCALES`; None means
+    /// "follow Accept-Language". Stored in `users.locale`.
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub email_notifications: EmailNotificationSettings,
+    /// Order of the post list when the request gives none.
+    #[serde(default)]
+    pub default_post_sort: PostSort,
+}
+
+/// Which notifications are also sent by email. All off by default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailNotificationSettings {
+    /// New comments on the user's posts.
+    #[serde(default)]
+    pub post_comments: bool,
+    /// Announcements from the admins.
+    #[serde(default)]
+    pub broadcasts: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PostSort {
+    #[default]
+    New,
+    Hot,
+}
+
+impl PostSort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PostSort::New => "new",
+            PostSort::Hot => "hot",
+        }
+    }
+}
+
+/// DTO for changing the preferred content language.
+#[derive(Debug, Deserialize)]
+pub struct UpdateLocaleRequest {
+    /// One of `SUPPORTED_LOCALES`, or null to clear.
+    pub locale: Option<String>,
 }
 
 /// DTO for user login.
@@ -78,4 +397,10 @@ pub struct LoginRequest {
     pub username: String,
     #[validate(length(min = 1, max = 128))]
     pub password: String,
+    /// Accepts the current terms while logging in.
+    pub accepted_terms_version: Option<String>,
+    /// Code from the authenticator app, for accounts with two-factor
+    /// authentication.
+    #[validate(length(min = 1, max = 16))]
+    pub totp_code: Option<String>,
 }
@@ -1,31 +1,65 @@
 // src/routes.rs
 
-// use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{
     Router,
+    extract::{DefaultBodyLimit, MatchedPath, Request},
     http::Method,
     middleware,
     routing::{delete, get, post, put},
 };
-// use tower_governor::{GovernorLayer, governor::GovernorConfigBuilder};
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tower_http::{catch_panic::CatchPanicLayer, cors::CorsLayer, trace::TraceLayer};
 
 use crate::{
+    config::{
+        ADMIN_REQUEST_TIMEOUT_SECS, CONTRIBUTION_ATTACHMENT_MAX_BYTES, DEFAULT_REQUEST_TIMEOUT_SECS,
+        RateLimitKey, SEARCH_REQUEST_TIMEOUT_SECS,
+    },
     handlers::{
-        admin, architecture, auth, community, contribution, interaction, profile, qualification,
-        quiz,
+        admin, architecture, auth, block, certificate, community, competition, contribution, docs,
+        feed, files,
+        follow, glossary, interaction, message, notification, profile, qualification, question, quiz,
+        series, stats, sync, tags, terms,
     },
+    services::exam_events,
     state::AppState,
+    utils::error_report::ErrorReporter,
+    utils::json_guard::json_guard_middleware,
     utils::jwt::{admin_middleware, auth_middleware, optional_auth_middleware},
+    utils::permissions::{self, Permission},
+    utils::rate_limit::{self, ClientKey},
+    utils::timeout::timeout_middleware,
 };
 
+/// Opens the span every request is traced in. `route` is the matched route
+/// template (e.g. `/api/posts/{id}`), so SQL statement logs and slow-query
+/// warnings emitted inside can be attributed to a handler.
+fn request_span(req: &Request) -> tracing::Span {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str)
+        .unwrap_or("<unmatched>");
+    tracing::info_span!("request", method = %req.method(), route, uri = %req.uri())
+}
+
+/// Caps how long every route in the group may take; see `timeout_middleware`.
+fn with_budget(routes: Router<AppState>, secs: u64) -> Router<AppState> {
+    routes.layer(middleware::from_fn_with_state(
+        Duration::from_secs(secs),
+        timeout_middleware,
+    ))
+}
+
 /// Assembles the main application router.
 ///
 /// * Merges all sub-routers (auth, architecture, quiz, admin).
-/// * Applies global middleware (Trace, CORS).
+/// * Applies global middleware (JSON body limits, Panic capture, Trace, CORS).
 /// * Injects global state (Database Pool).
 pub fn create_router(state: AppState) -> Router {
+    let reporter = ErrorReporter::new(state.config.sentry_dsn.as_deref());
+
     let origins = [
         "http://localhost:3000".parse().unwrap(),
         "http://127.0.0.1:3000".parse().unwrap(),
@@ -39,20 +73,22 @@ pub fn create_router(state: AppState) -> Router {
             axum::http::header::CONTENT_TYPE,
         ]);
 
-    // let governor_conf = GovernorConfigBuilder::default()
-    //     .per_second(2)
-    //     .burst_size(5)
-    //     .finish()
-    //     .unwrap();
-
-    // let governor_conf = Arc::new(governor_conf);
-
     let auth_routes = Router::new()
         .route("/register", post(auth::register))
         .route("/login", post(auth::login))
-        // Qualification routes (Protected)
+        .route("/form-token", get(auth::form_token))
+        .route("/verify-email", post(auth::verify_email))
+        .route("/forgot-password", post(auth::forgot_password))
+        .route("/reset-password", post(auth::reset_password))
+        .route("/oauth/{provider}/authorize", get(auth::oauth_authorize))
+        .route("/oauth/{provider}/callback", post(auth::oauth_callback))
+        // Logout, account linking, two-factor and qualification routes (Protected)
         .merge(
             Router::new()
+                .route("/logout", post(auth::logout))
+                .route("/2fa/enroll", post(auth::enroll_two_factor))
+                .route("/2fa/verify", post(auth::verify_two_factor))
+                .route("/oauth/{provider}/link", post(auth::oauth_link))
                 .route("/qualification", get(qualification::generate_exam))
                 .route("/qualification/submit", post(qualification::submit_exam))
                 .layer(middleware::from_fn_with_state(
@@ -63,10 +99,27 @@ pub fn create_router(state: AppState) -> Router {
 
     let architecture_routes = Router::new()
         .route("/", get(architecture::list_architectures))
-        .route("/{id}", get(architecture::get_architecture));
+        .route("/compare", get(architecture::compare_architectures))
+        .route("/random", get(architecture::random_architecture))
+        .route("/{id}", get(architecture::get_architecture))
+        .route("/{id}/dossier.pdf", get(architecture::download_dossier));
+
+    let tag_routes = Router::new()
+        .route("/trending", get(tags::trending_tags))
+        .route("/{slug}", get(tags::get_topic));
+
+    let glossary_routes = Router::new()
+        .route("/", get(glossary::list_terms))
+        .route("/{slug}", get(glossary::get_term));
 
     let post_routes = Router::new()
-        .route("/", get(community::list_posts))
+        .route(
+            "/",
+            get(community::list_posts).layer(middleware::from_fn_with_state(
+                state.clone(),
+                optional_auth_middleware,
+            )),
+        )
         .route(
             "/{id}",
             get(community::get_post).layer(middleware::from_fn_with_state(
@@ -74,14 +127,50 @@ pub fn create_router(state: AppState) -> Router {
                 optional_auth_middleware,
             )),
         )
-        .route("/{id}/comments", get(interaction::list_comments))
+        .route(
+            "/{id}/revisions",
+            get(community::list_post_revisions).layer(middleware::from_fn_with_state(
+                state.clone(),
+                optional_auth_middleware,
+            )),
+        )
+        .route(
+            "/{id}/comments",
+            get(interaction::list_comments).layer(middleware::from_fn_with_state(
+                state.clone(),
+                optional_auth_middleware,
+            )),
+        )
         .merge(
             Router::new()
                 .route("/", post(community::create_post))
-                .route("/{id}", delete(community::delete_post))
+                .route(
+                    "/{id}",
+                    put(community::update_post).delete(community::delete_post),
+                )
                 .route("/{id}/like", post(interaction::toggle_like))
                 .route("/{id}/favorite", post(interaction::toggle_favorite))
                 .route("/{id}/comments", post(interaction::create_comment))
+                .route(
+                    "/{id}/comments/{comment_id}",
+                    delete(interaction::delete_comment),
+                )
+                .layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    auth_middleware,
+                )),
+        );
+
+    let series_routes = Router::new()
+        .route("/{id}", get(series::get_series))
+        .merge(
+            Router::new()
+                .route("/", post(series::create_series))
+                .route(
+                    "/{id}/posts",
+                    post(series::add_series_post).put(series::reorder_series_posts),
+                )
+                .route("/{id}/posts/{post_id}", delete(series::remove_series_post))
                 .layer(middleware::from_fn_with_state(
                     state.clone(),
                     auth_middleware,
@@ -89,74 +178,424 @@ pub fn create_router(state: AppState) -> Router {
         );
 
     let profile_routes = Router::new()
-        .route("/me", get(profile::get_me))
+        .route(
+            "/me",
+            get(profile::get_me)
+                .put(profile::update_profile)
+                .delete(profile::delete_me),
+        )
+        .route("/me/deletion", delete(profile::cancel_account_deletion))
+        .route(
+            "/export",
+            get(profile::get_data_export).post(profile::request_data_export),
+        )
         .route("/posts", get(profile::list_my_posts))
         .route("/favorites", get(profile::list_my_favorites))
         .route("/contributions", get(profile::list_my_contributions))
+        .route("/certificates", get(profile::list_my_certificates))
+        .route("/quiz-stats", get(profile::get_quiz_stats))
+        .route("/activity-heatmap", get(profile::get_activity_heatmap))
+        .route("/usage", get(profile::get_usage))
+        .route("/limits", get(profile::get_limits))
+        .route("/sessions", get(profile::list_sessions))
+        .route("/sessions/{id}", delete(profile::revoke_session))
+        .route("/security-events", get(profile::list_security_events))
+        .route("/blocks", get(block::list_blocks))
+        .route("/locale", put(profile::update_locale))
+        .route(
+            "/settings",
+            get(profile::get_settings).put(profile::update_settings),
+        )
+        .route("/email", put(profile::update_email))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
         ));
 
+    let user_routes = Router::new()
+        .route("/{id}", get(profile::get_user_profile))
+        .route("/{id}/followers", get(follow::list_followers))
+        .route("/{id}/activity", get(profile::get_user_activity))
+        .merge(
+            Router::new()
+                .route("/{id}/follow", post(follow::toggle_follow))
+                .route("/{id}/block", post(block::toggle_block))
+                .route("/{id}/mute", post(block::toggle_mute))
+                .layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    auth_middleware,
+                )),
+        );
+
     let contribution_routes = Router::new()
-        .route("/", post(contribution::create_contribution))
+        .route("/leaderboard", get(contribution::get_leaderboard))
+        .merge(
+            Router::new()
+                .route("/", post(contribution::create_contribution))
+                .route("/validate", post(contribution::validate_contribution))
+                .route(
+                    "/{id}/messages",
+                    get(contribution::list_messages).post(contribution::post_message),
+                )
+                .route(
+                    "/{id}/attachments",
+                    post(contribution::upload_attachment)
+                        .layer(DefaultBodyLimit::max(CONTRIBUTION_ATTACHMENT_MAX_BYTES)),
+                )
+                .layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    auth_middleware,
+                )),
+        );
+
+    let question_routes = Router::new()
+        .route("/{id}/flag", post(question::flag_question))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
         ));
 
+    let notification_routes = Router::new()
+        .route("/", get(notification::list_notifications))
+        .route("/read", post(notification::mark_read))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ));
+
+    let message_routes = Router::new()
+        .route("/{user_id}", post(message::send_message))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ));
+
+    let conversation_routes = Router::new()
+        .route("/", get(message::list_conversations))
+        .route("/{id}/messages", get(message::list_messages))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ));
+
+    let terms_routes = Router::new()
+        .route("/", get(terms::get_current_terms))
+        .merge(
+            Router::new()
+                .route("/accept", post(terms::accept_terms))
+                .layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    auth_middleware,
+                )),
+        );
+
+    let certificate_routes = Router::new()
+        .route("/{code}", get(certificate::verify_certificate))
+        .route("/{code}/pdf", get(certificate::download_certificate_pdf));
+
+    let file_routes = Router::new().route("/{*key}", get(files::serve_signed_file));
+
+    let feed_routes = Router::new()
+        .route("/discover", get(feed::discover))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            optional_auth_middleware,
+        ));
+
+    let stats_routes = Router::new()
+        .route("/public", get(stats::get_public_stats))
+        .route("/online", get(stats::get_online_stats));
+
+    let sync_routes = Router::new().route("/changes", get(sync::get_changes));
+
+    let competition_routes = Router::new()
+        .route("/{id}/results", get(competition::get_results))
+        .merge(
+            Router::new()
+                .route("/", get(competition::list_competitions))
+                .route("/{id}", get(competition::get_competition))
+                .layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    optional_auth_middleware,
+                )),
+        )
+        .merge(
+            Router::new()
+                .route("/{id}/register", post(competition::register))
+                .route("/{id}/paper", get(competition::get_paper))
+                .route("/{id}/submit", post(competition::submit))
+                .layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    auth_middleware,
+                )),
+        );
+
     let quiz_routes = Router::new()
         .route("/generate", get(quiz::generate_paper))
         .route("/leaderboard", get(quiz::get_leaderboard))
+        .route("/architecture/{id}", get(quiz::architecture_quiz))
         // Protected quiz routes
         .merge(
             Router::new()
                 .route("/submit", post(quiz::submit_paper))
+                .route("/attempts/{id}/paper", get(quiz::get_attempt_paper))
                 .layer(middleware::from_fn_with_state(
                     state.clone(),
                     auth_middleware,
                 )),
         );
 
-    let admin_routes = Router::new()
+    let docs_routes = Router::new()
+        .route("/openapi.json", get(docs::public_document))
+        .merge(
+            Router::new()
+                .route("/user.json", get(docs::user_document))
+                .layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    auth_middleware,
+                )),
+        );
+
+    // Each group needs its own permission; any staff role may read the docs.
+    let user_admin_routes = Router::new()
         .route("/users", get(admin::list_users).post(admin::create_user))
         .route(
             "/users/{id}",
             put(admin::update_user).delete(admin::delete_user),
         )
-        .route("/architectures", post(admin::create_architecture))
+        .route(
+            "/users/{id}/verification",
+            put(admin::set_user_verification),
+        )
+        .route(
+            "/users/{id}/exam-accommodations",
+            put(admin::set_exam_accommodations).delete(admin::clear_exam_accommodations),
+        )
+        .route("/notifications/broadcast", post(admin::broadcast_notification))
+        .route("/notifications/broadcasts", get(admin::list_broadcasts))
+        .route("/segments/preview", get(admin::preview_segment))
+        .route("/segments/export", get(admin::export_segment));
+
+    let moderation_routes = Router::new()
+        .route("/users/{id}/overview", get(admin::get_user_overview))
+        .route("/users/{id}/ban", put(admin::set_user_ban))
+        .route("/abuse", get(admin::abuse_summary))
+        .route("/usage/anomalies", get(admin::usage_anomalies));
+
+    let catalog_routes = Router::new()
+        .route(
+            "/architectures",
+            get(admin::list_architectures).post(admin::create_architecture),
+        )
+        .route(
+            "/architectures/link-report",
+            get(admin::get_image_link_report).post(admin::check_image_links),
+        )
+        .route(
+            "/architectures/locations/backfill",
+            post(admin::backfill_locations),
+        )
+        .route(
+            "/architectures/export",
+            get(admin::list_catalog_exports).post(admin::export_catalog),
+        )
+        .route("/architectures/{id}/pins", put(admin::set_pinned_posts))
+        .route("/architectures/{id}/merge", post(admin::merge_architecture))
+        .route(
+            "/architectures/{id}/translations/{locale}",
+            put(admin::upsert_architecture_translation)
+                .delete(admin::delete_architecture_translation),
+        )
         .route(
             "/architectures/{id}",
             delete(admin::delete_architecture).put(admin::update_architecture),
         )
-        .route("/questions", post(admin::create_question))
+        .route("/glossary", post(admin::create_glossary_term))
         .route(
-            "/questions/{id}",
-            delete(admin::delete_question).put(admin::update_question),
+            "/glossary/{id}",
+            put(admin::update_glossary_term).delete(admin::delete_glossary_term),
         )
+        // Question contributions also need ManageQuestions, checked on review
         .route("/contributions", get(admin::list_contributions))
+        .route("/contributions/metrics", get(admin::contribution_metrics))
+        .route(
+            "/contributions/unaudited",
+            get(admin::list_unaudited_contributions),
+        )
+        .route(
+            "/contributions/{id}/attachments",
+            get(admin::list_contribution_attachments),
+        )
+        .route(
+            "/contributions/{id}/messages",
+            get(admin::list_contribution_messages).post(admin::post_contribution_message),
+        )
         .route(
             "/contributions/{id}/review",
             put(admin::review_contribution),
         )
+        .route(
+            "/contributions/{id}/confirm",
+            post(admin::confirm_contribution),
+        )
+        .route(
+            "/contributions/{id}/rollback",
+            post(admin::rollback_contribution),
+        )
+        .route(
+            "/contributions/{id}/revert",
+            post(admin::revert_contribution),
+        );
+
+    let question_bank_routes = Router::new()
+        .route(
+            "/questions",
+            get(admin::list_questions).post(admin::create_question),
+        )
+        .route("/questions/flagged", get(admin::list_flagged_questions))
+        .route("/questions/duplicates", get(admin::list_duplicate_questions))
+        .route(
+            "/questions/duplicates/merge",
+            post(admin::merge_duplicate_questions),
+        )
+        .route("/questions/bulk-delete", post(admin::delete_questions))
+        .route(
+            "/questions/{id}",
+            delete(admin::delete_question).put(admin::update_question),
+        )
+        .route(
+            "/questions/{id}/flags",
+            put(admin::resolve_question_flags),
+        )
+        .route(
+            "/questions/{id}/translations",
+            get(admin::list_question_translations),
+        )
+        .route(
+            "/questions/{id}/translations/{locale}",
+            put(admin::upsert_question_translation).delete(admin::delete_question_translation),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            exam_events::lock_question_bank,
+        ));
+
+    let site_routes = Router::new()
+        .route("/online", get(admin::list_online_users))
+        .route("/experiments", get(admin::list_experiments))
+        .route("/audit-logs", get(admin::list_audit_logs))
+        .route("/audit-logs/{id}/diff", get(admin::get_audit_diff))
+        .route("/terms", post(admin::publish_terms))
+        .route(
+            "/exam-events",
+            get(admin::list_exam_events).post(admin::create_exam_event),
+        )
+        .route("/exam-events/{id}", delete(admin::cancel_exam_event))
+        .route("/competitions", post(admin::create_competition))
+        .route("/competitions/{id}", delete(admin::delete_competition))
+        .route("/competitions/{id}/entries", get(admin::list_competition_entries))
+        .route(
+            "/system/log-level",
+            get(admin::get_log_level).put(admin::set_log_level),
+        )
+        .route("/system/integrity", get(admin::verify_integrity))
+        .route("/system/integrity/repair", post(admin::repair_integrity));
+
+    let admin_routes = Router::new()
+        .route("/docs/openapi.json", get(docs::admin_document))
+        .merge(permissions::guard(user_admin_routes, Permission::ManageUsers))
+        .merge(permissions::guard(moderation_routes, Permission::ModerateContent))
+        .merge(permissions::guard(catalog_routes, Permission::ManageArchitectures))
+        .merge(permissions::guard(question_bank_routes, Permission::ManageQuestions))
+        .merge(permissions::guard(site_routes, Permission::ManageSite))
         // Double middleware protection: Auth first, then Admin check
-        .layer(middleware::from_fn(admin_middleware))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            admin_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
         ));
 
+    let api_routes = Router::new()
+        .nest(
+            "/api/architectures",
+            with_budget(architecture_routes, SEARCH_REQUEST_TIMEOUT_SECS),
+        )
+        .nest("/api/glossary", with_budget(glossary_routes, SEARCH_REQUEST_TIMEOUT_SECS))
+        .nest("/api/posts", with_budget(post_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest("/api/series", with_budget(series_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest("/api/tags", with_budget(tag_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest("/api/profile", with_budget(profile_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest("/api/users", with_budget(user_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest(
+            "/api/contributions",
+            with_budget(contribution_routes, DEFAULT_REQUEST_TIMEOUT_SECS),
+        )
+        .nest("/api/questions", with_budget(question_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest(
+            "/api/notifications",
+            with_budget(notification_routes, DEFAULT_REQUEST_TIMEOUT_SECS),
+        )
+        .nest("/api/messages", with_budget(message_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest(
+            "/api/conversations",
+            with_budget(conversation_routes, DEFAULT_REQUEST_TIMEOUT_SECS),
+        )
+        .nest("/api/terms", with_budget(terms_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest(
+            "/api/certificates",
+            with_budget(certificate_routes, DEFAULT_REQUEST_TIMEOUT_SECS),
+        )
+        .nest("/api/files", with_budget(file_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest("/api/stats", with_budget(stats_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest("/api/sync", with_budget(sync_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest("/api/feed", with_budget(feed_routes, SEARCH_REQUEST_TIMEOUT_SECS))
+        .nest("/api/quiz", with_budget(quiz_routes, DEFAULT_REQUEST_TIMEOUT_SECS))
+        .nest(
+            "/api/competitions",
+            with_budget(competition_routes, DEFAULT_REQUEST_TIMEOUT_SECS),
+        )
+        .nest("/api/admin", with_budget(admin_routes, ADMIN_REQUEST_TIMEOUT_SECS))
+        .nest("/api/docs", with_budget(docs_routes, DEFAULT_REQUEST_TIMEOUT_SECS));
+
+    // Rate limits: auth routes strictly per address, the rest per the configured
+    // key with separate budgets for reads and writes
+    let limits = &state.config.rate_limit;
+    let by_ip = ClientKey::new(RateLimitKey::Ip, &state.config);
+    let by_client = ClientKey::new(limits.key, &state.config);
+    let auth_routes = rate_limit::limit(
+        with_budget(auth_routes, DEFAULT_REQUEST_TIMEOUT_SECS),
+        limits.auth,
+        None,
+        by_ip,
+        state.pool.clone(),
+    );
+    let api_routes = rate_limit::limit(
+        api_routes,
+        limits.read,
+        Some(&[Method::GET, Method::HEAD]),
+        by_client.clone(),
+        state.pool.clone(),
+    );
+    let api_routes = rate_limit::limit(
+        api_routes,
+        limits.write,
+        Some(&[Method::POST, Method::PUT, Method::PATCH, Method::DELETE]),
+        by_client,
+        state.pool.clone(),
+    );
+
     Router::new()
         .nest("/api/auth", auth_routes)
-        .nest("/api/architectures", architecture_routes)
-        .nest("/api/posts", post_routes)
-        .nest("/api/profile", profile_routes)
-        .nest("/api/contributions", contribution_routes)
-        .nest("/api/quiz", quiz_routes)
-        .nest("/api/admin", admin_routes)
+        .merge(api_routes)
         // Global Middleware (applied from outside in)
-        .layer(TraceLayer::new_for_http())
+        // Before any handler or extractor parses a body
+        .layer(middleware::from_fn(json_guard_middleware))
+        // Panics are caught inside the request span so they are logged with it
+        .layer(CatchPanicLayer::custom(move |panic| reporter.handle_panic(panic)))
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
         .layer(cors)
-        // .layer(GovernorLayer::new(governor_conf))
         .with_state(state)
 }
@@ -0,0 +1,111 @@
+// src/services/account_deletion.rs
+
+//! Deleting accounts, by an admin or by the user themselves.
+//!
+//! Either way the user's posts and comments are handed over to the ghost
+//! user so discussions stay readable; everything personal goes with the
+//! account through `ON DELETE CASCADE`. Users who delete their own account
+//! get a grace period first, in which they can change their mind; the
+//! `account_deletion` job deletes the account once it is over.
+
+use sqlx::{PgConnection, PgExecutor, PgPool};
+
+use crate::{config::ACCOUNT_DELETION_GRACE_DAYS, models::user::UserDeletionImpact};
+
+/// ID of the ghost user that inherits deleted users' posts and comments.
+pub async fn ghost_id<'e, E: PgExecutor<'e>>(executor: E) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar!("SELECT id FROM users WHERE username = 'ghost'")
+        .fetch_optional(executor)
+        .await
+}
+
+/// Counts everything deleting the user touches; None if there is no such user.
+pub async fn impact<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+) -> Result<Option<UserDeletionImpact>, sqlx::Error> {
+    sqlx::query_as!(
+        UserDeletionImpact,
+        r#"
+        SELECT
+            u.id as user_id,
+            u.username,
+            (SELECT COUNT(*) FROM posts WHERE user_id = u.id) as "posts_to_transfer!",
+            (SELECT COUNT(*) FROM comments WHERE user_id = u.id) as "comments_to_transfer!",
+            (SELECT COUNT(*) FROM contributions WHERE user_id = u.id) as "contributions_to_delete!",
+            (SELECT COUNT(*) FROM post_likes WHERE user_id = u.id) as "likes_to_delete!",
+            (SELECT COUNT(*) FROM post_favorites WHERE user_id = u.id) as "favorites_to_delete!",
+            (SELECT COUNT(*) FROM exam_records WHERE user_id = u.id) as "exam_records_to_delete!"
+        FROM users u
+        WHERE u.id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+/// Hands the user's posts and comments to `ghost_id`, then deletes the user.
+/// Returns false if there was no such user.
+pub async fn transfer_and_delete(
+    conn: &mut PgConnection,
+    ghost_id: i64,
+    user_id: i64,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query!("UPDATE posts SET user_id = $1 WHERE user_id = $2", ghost_id, user_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query!("UPDATE comments SET user_id = $1 WHERE user_id = $2", ghost_id, user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    // Likes, favorites, contributions and the like are personal and go with
+    // the user through ON DELETE CASCADE.
+    let result = sqlx::query!("DELETE FROM users WHERE id = $1", user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Schedules the user's own account for deletion after the grace period.
+/// Asking again keeps the original date. Returns when it will happen.
+pub async fn schedule(
+    pool: &PgPool,
+    user_id: i64,
+) -> Result<chrono::DateTime<chrono::Utc>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        UPDATE users
+        SET deletion_scheduled_at = COALESCE(
+            deletion_scheduled_at,
+            CURRENT_TIMESTAMP + make_interval(days => $2)
+        )
+        WHERE id = $1
+        RETURNING deletion_scheduled_at as "deletion_scheduled_at!"
+        "#,
+        user_id,
+        ACCOUNT_DELETION_GRACE_DAYS
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Cancels a pending deletion. Returns false if none was pending.
+pub async fn cancel(pool: &PgPool, user_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE users SET deletion_scheduled_at = NULL WHERE id = $1 AND deletion_scheduled_at IS NOT NULL",
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Users whose grace period is over.
+pub async fn due(pool: &PgPool) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT id FROM users WHERE deletion_scheduled_at <= CURRENT_TIMESTAMP ORDER BY id"
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,103 @@
+// src/services/api_usage.rs
+
+//! Per-user API usage, recorded by the auth middlewares through
+//! [`UsageSampler`](crate::utils::usage_sampler::UsageSampler) and kept per day.
+//! The basis for spotting clients that hammer the API, and for fair-use
+//! limits later on.
+
+use sqlx::PgPool;
+
+use crate::{
+    config::{
+        USAGE_ANOMALY_BASELINE_DAYS, USAGE_ANOMALY_FACTOR, USAGE_ANOMALY_MIN_REQUESTS,
+        USAGE_HISTORY_DAYS, USAGE_RETENTION_DAYS,
+    },
+    models::api_usage::{UsageAnomaly, UsageDay},
+};
+
+/// Adds `requests` to the user's count for today.
+pub async fn record(pool: &PgPool, user_id: i64, requests: i64) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO api_usage_daily (user_id, day, requests)
+        VALUES ($1, (CURRENT_TIMESTAMP AT TIME ZONE 'UTC')::DATE, $2)
+        ON CONFLICT (user_id, day) DO UPDATE SET requests = api_usage_daily.requests + $2
+        "#,
+        user_id,
+        requests
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The user's days with requests over the last `USAGE_HISTORY_DAYS`, oldest first.
+pub async fn history(pool: &PgPool, user_id: i64) -> Result<Vec<UsageDay>, sqlx::Error> {
+    sqlx::query_as!(
+        UsageDay,
+        r#"
+        SELECT day as date, requests
+        FROM api_usage_daily
+        WHERE user_id = $1
+          AND day > (CURRENT_TIMESTAMP AT TIME ZONE 'UTC')::DATE - $2::INT
+        ORDER BY day
+        "#,
+        user_id,
+        USAGE_HISTORY_DAYS
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Users with at least `USAGE_ANOMALY_MIN_REQUESTS` on `day` and at least
+/// `USAGE_ANOMALY_FACTOR` times their average over the days before it.
+/// Users new to the API count as anomalous once over the minimum.
+pub async fn anomalies(
+    pool: &PgPool,
+    day: chrono::NaiveDate,
+    limit: i64,
+) -> Result<Vec<UsageAnomaly>, sqlx::Error> {
+    sqlx::query_as!(
+        UsageAnomaly,
+        r#"
+        WITH baseline AS (
+            SELECT user_id, SUM(requests)::FLOAT8 / $2::INT as average
+            FROM api_usage_daily
+            WHERE day >= $1::DATE - $2::INT AND day < $1
+            GROUP BY user_id
+        )
+        SELECT
+            d.user_id,
+            u.username,
+            d.requests,
+            COALESCE(b.average, 0) as "baseline!",
+            d.requests / NULLIF(b.average, 0) as ratio
+        FROM api_usage_daily d
+        JOIN users u ON u.id = d.user_id
+        LEFT JOIN baseline b ON b.user_id = d.user_id
+        WHERE d.day = $1
+          AND d.requests >= $3
+          AND d.requests >= $4 * COALESCE(b.average, 0)
+        ORDER BY ratio DESC NULLS FIRST, d.requests DESC
+        LIMIT $5
+        "#,
+        day,
+        USAGE_ANOMALY_BASELINE_DAYS,
+        USAGE_ANOMALY_MIN_REQUESTS,
+        USAGE_ANOMALY_FACTOR,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Drops counts older than `USAGE_RETENTION_DAYS`.
+pub async fn purge(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM api_usage_daily WHERE day < (CURRENT_TIMESTAMP AT TIME ZONE 'UTC')::DATE - $1::INT",
+        USAGE_RETENTION_DAYS
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
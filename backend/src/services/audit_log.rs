@@ -0,0 +1,106 @@
+// src/services/audit_log.rs
+
+//! Audit trail of admin actions.
+//!
+//! Handlers snapshot the row as JSON before and after the change, inside the
+//! same transaction, and store only the fields that differ. A creation has a
+//! null `before` and a deletion a null `after`, so every field is stored.
+
+use serde_json::Value;
+use sqlx::{Postgres, Transaction};
+
+use crate::models::audit_log::FieldChange;
+
+/// Compares two row snapshots (JSON objects) field by field.
+/// Changes are sorted by field name; a field missing on one side counts as null.
+pub fn field_diff(before: &Value, after: &Value) -> Vec<FieldChange> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let old = before.get(field).unwrap_or(&Value::Null);
+            let new = after.get(field).unwrap_or(&Value::Null);
+            (old != new).then(|| FieldChange {
+                field: field.clone(),
+                before: old.clone(),
+                after: new.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Stores an update of `entity_type`/`entity_id` by `admin_id`, with the
+/// admin's `reason` if they gave one. Edits that changed nothing are not logged.
+pub async fn record_update(
+    tx: &mut Transaction<'_, Postgres>,
+    admin_id: i64,
+    entity_type: &str,
+    entity_id: i64,
+    before: &Value,
+    after: &Value,
+    reason: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let changes = field_diff(before, after);
+    if changes.is_empty() {
+        return Ok(());
+    }
+    record(tx, admin_id, "update", entity_type, entity_id, &changes, reason).await
+}
+
+/// Stores the creation of `entity_type`/`entity_id` with its initial fields.
+pub async fn record_create(
+    tx: &mut Transaction<'_, Postgres>,
+    admin_id: i64,
+    entity_type: &str,
+    entity_id: i64,
+    after: &Value,
+) -> Result<(), sqlx::Error> {
+    let changes = field_diff(&Value::Null, after);
+    record(tx, admin_id, "create", entity_type, entity_id, &changes, None).await
+}
+
+/// Stores the deletion of `entity_type`/`entity_id` with its last fields.
+pub async fn record_delete(
+    tx: &mut Transaction<'_, Postgres>,
+    admin_id: i64,
+    entity_type: &str,
+    entity_id: i64,
+    before: &Value,
+) -> Result<(), sqlx::Error> {
+    let changes = field_diff(before, &Value::Null);
+    record(tx, admin_id, "delete", entity_type, entity_id, &changes, None).await
+}
+
+/// Stores any admin `action` with its field changes, even if there are none.
+pub async fn record(
+    tx: &mut Transaction<'_, Postgres>,
+    admin_id: i64,
+    action: &str,
+    entity_type: &str,
+    entity_id: i64,
+    changes: &[FieldChange],
+    reason: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO audit_logs (admin_id, action, entity_type, entity_id, diff, reason)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        admin_id,
+        action,
+        entity_type,
+        entity_id,
+        serde_json::to_value(changes).unwrap_or_default(),
+        reason
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
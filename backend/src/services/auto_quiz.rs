@@ -0,0 +1,138 @@
+// src/services/auto_quiz.rs
+
+//! "Test yourself" questions generated from architecture metadata.
+//!
+//! Each question asks for one attribute of the entry (dynasty, province,
+//! category) and uses values from other entries as distractors. Nothing is
+//! stored; the curated question bank is not involved.
+
+use rand::seq::SliceRandom;
+use sqlx::PgPool;
+
+use crate::models::question::{ArchitectureQuiz, AutoQuestion};
+
+/// Wrong options offered next to the right one.
+const DISTRACTORS: i64 = 3;
+
+/// Builds the quiz for an architecture, or None if it does not exist.
+///
+/// Names and dynasties use the translation for `locale` when there is one, so
+/// the options match what the detail page shows.
+pub async fn for_architecture(
+    pool: &PgPool,
+    id: i64,
+    locale: &str,
+) -> Result<Option<ArchitectureQuiz>, sqlx::Error> {
+    let Some(arch) = sqlx::query!(
+        r#"
+        SELECT a.category, a.province,
+               COALESCE(t.name, a.name) as "name!",
+               COALESCE(t.dynasty, a.dynasty) as "dynasty!"
+        FROM architectures a
+        LEFT JOIN architecture_translations t ON t.architecture_id = a.id AND t.locale = $2
+        WHERE a.id = $1
+        "#,
+        id,
+        locale
+    )
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let dynasties = sqlx::query_scalar!(
+        r#"
+        SELECT d as "d!" FROM (
+            SELECT DISTINCT COALESCE(t.dynasty, a.dynasty) as d
+            FROM architectures a
+            LEFT JOIN architecture_translations t ON t.architecture_id = a.id AND t.locale = $2
+        ) x
+        WHERE d <> $1
+        ORDER BY random()
+        LIMIT $3
+        "#,
+        arch.dynasty,
+        locale,
+        DISTRACTORS
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let categories = sqlx::query_scalar!(
+        r#"
+        SELECT c as "c!" FROM (SELECT DISTINCT category as c FROM architectures) x
+        WHERE c <> $1
+        ORDER BY random()
+        LIMIT $2
+        "#,
+        arch.category,
+        DISTRACTORS
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut questions = Vec::new();
+    questions.extend(build(
+        "dynasty",
+        format!("In which dynasty was {} built?", arch.name),
+        arch.dynasty,
+        dynasties,
+    ));
+    if let Some(province) = arch.province {
+        let provinces = sqlx::query_scalar!(
+            r#"
+            SELECT p as "p!" FROM (
+                SELECT DISTINCT province as p FROM architectures WHERE province IS NOT NULL
+            ) x
+            WHERE p <> $1
+            ORDER BY random()
+            LIMIT $2
+            "#,
+            province,
+            DISTRACTORS
+        )
+        .fetch_all(pool)
+        .await?;
+        questions.extend(build(
+            "province",
+            format!("In which province is {}?", arch.name),
+            province,
+            provinces,
+        ));
+    }
+    questions.extend(build(
+        "category",
+        format!("What kind of structure is {}?", arch.name),
+        arch.category,
+        categories,
+    ));
+
+    Ok(Some(ArchitectureQuiz {
+        architecture_id: id,
+        source: "generated",
+        questions,
+    }))
+}
+
+/// Shuffles the answer in among the distractors. Without any distractor there
+/// is nothing to choose from, so no question is made.
+fn build(
+    kind: &'static str,
+    prompt: String,
+    answer: String,
+    distractors: Vec<String>,
+) -> Option<AutoQuestion> {
+    if distractors.is_empty() {
+        return None;
+    }
+    let mut options = distractors;
+    options.push(answer.clone());
+    options.shuffle(&mut rand::thread_rng());
+    Some(AutoQuestion {
+        kind,
+        prompt,
+        options,
+        answer,
+    })
+}
@@ -0,0 +1,58 @@
+// src/services/bans.rs
+
+//! Bans and suspensions. A banned user keeps their account and content, and
+//! can sign in and read, but every write is refused by the auth middlewares
+//! until the ban is lifted or runs out.
+
+use sqlx::PgExecutor;
+
+use crate::models::user::Ban;
+
+/// The ban on the user, if one is in force.
+pub async fn active<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+) -> Result<Option<Ban>, sqlx::Error> {
+    sqlx::query_as!(
+        Ban,
+        r#"
+        SELECT banned_until, ban_reason as "reason!"
+        FROM users
+        WHERE id = $1
+          AND ban_reason IS NOT NULL
+          AND (banned_until IS NULL OR banned_until > CURRENT_TIMESTAMP)
+        "#,
+        user_id
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+/// Bans the user until `until` (None: indefinitely), replacing any earlier ban.
+pub async fn ban<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users SET banned_until = $2, ban_reason = $3 WHERE id = $1",
+        user_id,
+        until,
+        reason
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Lifts the user's ban, if any.
+pub async fn lift<'e, E: PgExecutor<'e>>(executor: E, user_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users SET banned_until = NULL, ban_reason = NULL WHERE id = $1",
+        user_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
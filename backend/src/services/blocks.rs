@@ -0,0 +1,46 @@
+// src/services/blocks.rs
+
+//! Users blocking or muting other users. Both hide the other user's posts and
+//! comments from the listings the user sees. A block also cuts private
+//! messages both ways and keeps the blocked user from replying to the blocker.
+
+use sqlx::PgExecutor;
+
+/// Whether either user has blocked the other.
+pub async fn between<'e, E: PgExecutor<'e>>(
+    executor: E,
+    a: i64,
+    b: i64,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM user_blocks
+            WHERE (blocker_id = $1 AND blocked_id = $2) OR (blocker_id = $2 AND blocked_id = $1)
+        ) as "blocked!"
+        "#,
+        a,
+        b
+    )
+    .fetch_one(executor)
+    .await
+}
+
+/// Whether any of `blocker_ids` has blocked `user_id`.
+pub async fn blocked_by_any<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+    blocker_ids: &[i64],
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM user_blocks WHERE blocked_id = $1 AND blocker_id = ANY($2)
+        ) as "blocked!"
+        "#,
+        user_id,
+        blocker_ids
+    )
+    .fetch_one(executor)
+    .await
+}
@@ -0,0 +1,176 @@
+// src/services/broadcasts.rs
+
+//! Notifications sent by an admin to every user in a segment.
+//!
+//! The audience is a [`UserFilter`]; the named [`Segment`]s are presets for
+//! common ones. The filter is stored with the broadcast, so every batch
+//! selects users the same way.
+//!
+//! Creating a broadcast queues a `broadcast_notifications` job that notifies one
+//! batch of users and queues itself again for the next, so a large audience
+//! never holds a long transaction. Progress is kept on the `broadcasts` row.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{
+    config::{BROADCAST_ACTIVE_DAYS, BROADCAST_BATCH_SIZE},
+    error::AppError,
+    jobs::queue,
+    services::{audit_log, notifications, segments::{self, UserFilter}},
+};
+
+/// Job kind that delivers the next batch of a broadcast.
+pub const BROADCAST_JOB: &str = "broadcast_notifications";
+
+/// Who receives a broadcast.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Segment {
+    #[default]
+    All,
+    /// Users with verified status.
+    Verified,
+    /// Users who signed in within `BROADCAST_ACTIVE_DAYS`.
+    Active,
+}
+
+impl Segment {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Segment::All => "all",
+            Segment::Verified => "verified",
+            Segment::Active => "active",
+        }
+    }
+
+    /// The filter this preset stands for.
+    pub fn filter(self) -> UserFilter {
+        match self {
+            Segment::All => UserFilter::default(),
+            Segment::Verified => UserFilter {
+                is_verified: Some(true),
+                ..UserFilter::default()
+            },
+            Segment::Active => UserFilter {
+                active_within_days: Some(BROADCAST_ACTIVE_DAYS),
+                ..UserFilter::default()
+            },
+        }
+    }
+}
+
+/// Records a broadcast to the users matching `filter` and queues its first
+/// batch. `segment` names the audience in listings. Returns the broadcast ID.
+pub async fn create(
+    pool: &PgPool,
+    admin_id: i64,
+    title: &str,
+    message: &str,
+    segment: &str,
+    filter: &UserFilter,
+) -> Result<i64, AppError> {
+    let mut tx = pool.begin().await?;
+
+    // Users who join while it is delivered are reached too, so this is an estimate.
+    let recipients = segments::count(&mut *tx, filter).await?;
+    let broadcast_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO broadcasts (title, message, segment, filter, created_by, recipients)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id
+        "#,
+        title,
+        message,
+        segment,
+        serde_json::to_value(filter).unwrap_or_default(),
+        admin_id,
+        recipients as i32
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    queue::enqueue(&mut *tx, BROADCAST_JOB, serde_json::json!({ "broadcast_id": broadcast_id }))
+        .await?;
+    audit_log::record_create(
+        &mut tx,
+        admin_id,
+        "broadcast",
+        broadcast_id,
+        &serde_json::json!({
+            "title": title,
+            "message": message,
+            "segment": segment,
+            "filter": filter,
+            "recipients": recipients
+        }),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(broadcast_id)
+}
+
+/// Notifies the next batch of recipients, then queues the batch after it or
+/// marks the broadcast finished.
+pub async fn deliver_batch(pool: &PgPool, broadcast_id: i64) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    let Some(broadcast) = sqlx::query!(
+        r#"
+        SELECT title, message, filter, cursor
+        FROM broadcasts
+        WHERE id = $1 AND finished_at IS NULL
+        FOR UPDATE
+        "#,
+        broadcast_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        // Already finished; a duplicate job has nothing to do.
+        return Ok(());
+    };
+
+    let filter: UserFilter = serde_json::from_value(broadcast.filter)
+        .map_err(|e| AppError::InternalServerError(format!("Bad broadcast filter: {}", e)))?;
+    let user_ids =
+        segments::ids_after(&mut *tx, &filter, broadcast.cursor, BROADCAST_BATCH_SIZE).await?;
+
+    notifications::notify_many(
+        &mut *tx,
+        &user_ids,
+        notifications::BROADCAST,
+        serde_json::json!({
+            "broadcast_id": broadcast_id,
+            "title": broadcast.title,
+            "message": broadcast.message,
+        }),
+    )
+    .await?;
+
+    let done = (user_ids.len() as i64) < BROADCAST_BATCH_SIZE;
+    sqlx::query!(
+        r#"
+        UPDATE broadcasts SET
+            delivered = delivered + $2,
+            cursor = COALESCE($3, cursor),
+            finished_at = CASE WHEN $4 THEN CURRENT_TIMESTAMP END
+        WHERE id = $1
+        "#,
+        broadcast_id,
+        user_ids.len() as i32,
+        user_ids.last().copied(),
+        done
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if !done {
+        queue::enqueue(&mut *tx, BROADCAST_JOB, serde_json::json!({ "broadcast_id": broadcast_id }))
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+// src/services/catalog_export.rs
+
+//! Static JSON bundles of the architecture catalog, for offline app bundles and CDNs.
+//!
+//! An admin queues an `export_catalog` job, which writes every architecture with
+//! its translations and image metadata to `exports/catalog/v{version}.json`.
+//! Versions only ever grow, so a client can compare its bundle with the latest
+//! one listed by `GET /api/admin/architectures/export`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, types::Json};
+
+/// Job kind that writes a catalog bundle to storage.
+pub const EXPORT_CATALOG_JOB: &str = "export_catalog";
+
+/// Layout of the bundle; bumped when fields are renamed or removed.
+pub const BUNDLE_FORMAT: u32 = 1;
+
+/// Storage key of the bundle with `version`.
+pub fn bundle_key(version: i64) -> String {
+    format!("exports/catalog/v{}.json", version)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CatalogBundle {
+    pub format: u32,
+    pub version: i64,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub architectures: Vec<CatalogEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub id: i64,
+    pub category: String,
+    pub name: String,
+    pub dynasty: String,
+    pub location: String,
+    pub province: Option<String>,
+    pub city: Option<String>,
+    pub district: Option<String>,
+    pub description: String,
+    pub cover_img: String,
+    pub carousel_imgs: Vec<String>,
+    /// Translated fields by locale; missing fields fall back to the source ones.
+    pub translations: BTreeMap<String, CatalogTranslation>,
+    /// The cover followed by the carousel, with the last link check of each.
+    pub images: Vec<CatalogImage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CatalogTranslation {
+    pub name: Option<String>,
+    pub dynasty: Option<String>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CatalogImage {
+    /// 'cover' or 'carousel'.
+    pub field: String,
+    pub url: String,
+    /// 'ok', 'broken', 'unreachable' or 'invalid'; None if never checked.
+    pub link_status: Option<String>,
+    pub checked_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Reads the whole catalog into a bundle numbered `version`.
+pub async fn build(pool: &PgPool, version: i64) -> Result<CatalogBundle, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, category, name, dynasty, location, province, city, district,
+               description, cover_img, carousel_imgs as "carousel_imgs: Json<Vec<String>>"
+        FROM architectures
+        ORDER BY id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut translations: HashMap<i64, BTreeMap<String, CatalogTranslation>> = HashMap::new();
+    for t in sqlx::query!(
+        "SELECT architecture_id, locale, name, dynasty, location, description FROM architecture_translations"
+    )
+    .fetch_all(pool)
+    .await?
+    {
+        translations.entry(t.architecture_id).or_default().insert(
+            t.locale,
+            CatalogTranslation {
+                name: t.name,
+                dynasty: t.dynasty,
+                location: t.location,
+                description: t.description,
+            },
+        );
+    }
+
+    let mut checks = HashMap::new();
+    for c in sqlx::query!(
+        "SELECT architecture_id, field, url, status, checked_at FROM architecture_image_checks"
+    )
+    .fetch_all(pool)
+    .await?
+    {
+        checks.insert((c.architecture_id, c.field, c.url), (c.status, c.checked_at));
+    }
+
+    let architectures = rows
+        .into_iter()
+        .map(|a| {
+            let carousel = a.carousel_imgs.0;
+            let images = std::iter::once(("cover", &a.cover_img))
+                .chain(carousel.iter().map(|url| ("carousel", url)))
+                .map(|(field, url)| {
+                    let check = checks.get(&(a.id, field.to_string(), url.clone()));
+                    CatalogImage {
+                        field: field.to_string(),
+                        url: url.clone(),
+                        link_status: check.map(|(status, _)| status.clone()),
+                        checked_at: check.map(|(_, at)| *at),
+                    }
+                })
+                .collect();
+
+            CatalogEntry {
+                translations: translations.remove(&a.id).unwrap_or_default(),
+                images,
+                id: a.id,
+                category: a.category,
+                name: a.name,
+                dynasty: a.dynasty,
+                location: a.location,
+                province: a.province,
+                city: a.city,
+                district: a.district,
+                description: a.description,
+                cover_img: a.cover_img,
+                carousel_imgs: carousel,
+            }
+        })
+        .collect();
+
+    Ok(CatalogBundle {
+        format: BUNDLE_FORMAT,
+        version,
+        generated_at: chrono::Utc::now(),
+        architectures,
+    })
+}
@@ -0,0 +1,72 @@
+// src/services/certificates.rs
+
+use rand::Rng;
+use sqlx::PgPool;
+
+use crate::jobs::queue;
+
+/// Job kind that renders a certificate's PDF.
+pub const RENDER_PDF_JOB: &str = "certificate_pdf";
+
+/// Issues a certificate for a score on the paper frozen in `paper_snapshot_id`
+/// and queues its PDF for rendering.
+///
+/// Returns the verification code, or `None` when the user already holds a
+/// qualification certificate, already earned a quiz certificate this UTC day,
+/// or the paper already earned one.
+pub async fn issue(
+    pool: &PgPool,
+    user_id: i64,
+    kind: &str,
+    score: f64,
+    paper_snapshot_id: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    let code = generate_code();
+    let mut tx = pool.begin().await?;
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO certificates (code, user_id, kind, score, paper_snapshot_id, quiz_day)
+        VALUES ($1, $2, $3::VARCHAR, $4, $5,
+                CASE WHEN $3::VARCHAR = 'quiz' THEN (CURRENT_TIMESTAMP AT TIME ZONE 'UTC')::date END)
+        ON CONFLICT DO NOTHING
+        RETURNING id
+        "#,
+        code,
+        user_id,
+        kind,
+        score,
+        paper_snapshot_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(row) = inserted else {
+        return Ok(None);
+    };
+
+    queue::enqueue(
+        &mut *tx,
+        RENDER_PDF_JOB,
+        serde_json::json!({ "certificate_id": row.id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some(code))
+}
+
+/// Random 16-character code, grouped for readability (e.g. "7KQ2-M9XD-...").
+/// Ambiguous characters (0/O, 1/I) are left out.
+fn generate_code() -> String {
+    const ALPHABET: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+    let mut rng = rand::thread_rng();
+    (0..4)
+        .map(|_| {
+            (0..4)
+                .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
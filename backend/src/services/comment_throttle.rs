@@ -0,0 +1,72 @@
+// src/services/comment_throttle.rs
+
+//! Limits on how fast one user may comment on one post, against flooding
+//! busy threads: `COMMENT_MIN_INTERVAL_SECS` between comments and
+//! `COMMENT_HOURLY_LIMIT_PER_POST` per hour. Admins are exempt.
+//!
+//! Deleted comments still count. The check locks the user's row, like the
+//! quota checks; run it in the transaction that adds the comment.
+
+use sqlx::PgConnection;
+
+use crate::{
+    config::{COMMENT_HOURLY_LIMIT_PER_POST, COMMENT_MIN_INTERVAL_SECS},
+    error::AppError,
+};
+
+/// Fails with `AppError::RateLimited`, saying when to retry, if the user may
+/// not comment on the post yet.
+pub async fn check(conn: &mut PgConnection, user_id: i64, post_id: i64) -> Result<(), AppError> {
+    let exempt = sqlx::query_scalar!(
+        r#"SELECT role = 'admin' as "admin!" FROM users WHERE id = $1 FOR UPDATE"#,
+        user_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .unwrap_or(false);
+    if exempt {
+        return Ok(());
+    }
+
+    // Seconds until the last comment is far enough back, and until the
+    // oldest comment that keeps the hour full drops out of it.
+    let wait = sqlx::query!(
+        r#"
+        SELECT
+            (SELECT CEIL(EXTRACT(EPOCH FROM
+                        MAX(created_at) + make_interval(secs => $3) - CURRENT_TIMESTAMP))::BIGINT
+             FROM comments
+             WHERE user_id = $1 AND post_id = $2) as interval_wait,
+            (SELECT CEIL(EXTRACT(EPOCH FROM
+                        created_at + INTERVAL '1 hour' - CURRENT_TIMESTAMP))::BIGINT
+             FROM comments
+             WHERE user_id = $1 AND post_id = $2
+               AND created_at > CURRENT_TIMESTAMP - INTERVAL '1 hour'
+             ORDER BY created_at DESC
+             OFFSET $4 LIMIT 1) as hourly_wait
+        "#,
+        user_id,
+        post_id,
+        COMMENT_MIN_INTERVAL_SECS as f64,
+        COMMENT_HOURLY_LIMIT_PER_POST - 1
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if let Some(secs) = wait.hourly_wait.filter(|secs| *secs > 0) {
+        return Err(AppError::RateLimited {
+            message: format!(
+                "You can post at most {} comments per hour on this post",
+                COMMENT_HOURLY_LIMIT_PER_POST
+            ),
+            retry_after_secs: secs as u64,
+        });
+    }
+    if let Some(secs) = wait.interval_wait.filter(|secs| *secs > 0) {
+        return Err(AppError::RateLimited {
+            message: "You are commenting too fast on this post".to_string(),
+            retry_after_secs: secs as u64,
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,23 @@
+// src/services/competitions.rs
+
+//! Competitions: a fixed paper answered by registered users within a time
+//! window. Creating one schedules an exam event over the same window, which
+//! locks the question bank while it runs, and snapshots the paper, so edits
+//! made before it starts or after it ends leave it as set. Each entry is
+//! scored once, and the ranking is published when the window closes.
+
+use sqlx::PgPool;
+
+use crate::{models::question::Question, services::paper_snapshots};
+
+/// The questions of the paper frozen in `snapshot_id`, in the order set, in
+/// `locale` where translated.
+pub async fn paper(
+    pool: &PgPool,
+    snapshot_id: i64,
+    locale: &str,
+) -> Result<Vec<Question>, sqlx::Error> {
+    let mut questions = paper_snapshots::load(pool, snapshot_id).await?;
+    paper_snapshots::translate(pool, &mut questions, locale).await?;
+    Ok(questions)
+}
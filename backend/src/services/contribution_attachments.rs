@@ -0,0 +1,67 @@
+// src/services/contribution_attachments.rs
+
+//! Reference files (photos, PDF scans) contributors attach for reviewers.
+//!
+//! Files go to blob storage under `contribution-attachments/`; only reviewers
+//! get links to them. The `contribution_attachments` job deletes them once the
+//! contribution has been reviewed for `CONTRIBUTION_ATTACHMENT_RETENTION_DAYS`.
+
+/// Longest stored file name, in characters.
+const FILENAME_MAX_CHARS: usize = 200;
+
+/// A file type attachments may have, recognized from the file's first bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttachmentKind {
+    pub extension: &'static str,
+    pub content_type: &'static str,
+}
+
+/// The type of an uploaded file, or None if it is not a JPEG, PNG, WebP or PDF.
+/// The declared content type is not trusted.
+pub fn sniff(bytes: &[u8]) -> Option<AttachmentKind> {
+    let (extension, content_type) = if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        ("jpg", "image/jpeg")
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        ("png", "image/png")
+    } else if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        ("webp", "image/webp")
+    } else if bytes.starts_with(b"%PDF-") {
+        ("pdf", "application/pdf")
+    } else {
+        return None;
+    };
+    Some(AttachmentKind {
+        extension,
+        content_type,
+    })
+}
+
+/// A fresh storage key for an attachment of a contribution.
+pub fn storage_key(contribution_id: i64, kind: AttachmentKind) -> String {
+    format!(
+        "contribution-attachments/{}/{}.{}",
+        contribution_id,
+        hex::encode(rand::random::<[u8; 16]>()),
+        kind.extension
+    )
+}
+
+/// The name to show reviewers: the last path segment of what the client sent,
+/// without control characters, or a generic name.
+pub fn display_name(filename: Option<&str>, kind: AttachmentKind) -> String {
+    let name: String = filename
+        .unwrap_or_default()
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(FILENAME_MAX_CHARS)
+        .collect();
+    let name = name.trim();
+    if name.is_empty() {
+        format!("attachment.{}", kind.extension)
+    } else {
+        name.to_string()
+    }
+}
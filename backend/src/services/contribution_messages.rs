@@ -0,0 +1,94 @@
+// src/services/contribution_messages.rs
+
+//! Review threads: messages between reviewers and the contributor, so
+//! reviewers can ask for clarification without rejecting a contribution.
+//!
+//! Both sides write while the contribution is pending; the comment given with
+//! the review becomes the reviewer's last message.
+
+use sqlx::{PgConnection, PgPool};
+
+use crate::{models::contribution::ContributionMessage, services::notifications};
+
+/// The thread of a contribution, oldest first.
+pub async fn list(
+    pool: &PgPool,
+    contribution_id: i64,
+) -> Result<Vec<ContributionMessage>, sqlx::Error> {
+    sqlx::query_as!(
+        ContributionMessage,
+        r#"
+        SELECT m.id, m.author_id, u.username as "author_username?", m.from_reviewer, m.body, m.created_at
+        FROM contribution_messages m
+        LEFT JOIN users u ON u.id = m.author_id
+        WHERE m.contribution_id = $1
+        ORDER BY m.id
+        "#,
+        contribution_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Adds a message to the thread and notifies the other side: the contributor
+/// of a reviewer's message, or the reviewers who have written in the thread of
+/// the contributor's. Callers should hold the contribution's row lock.
+pub async fn post(
+    conn: &mut PgConnection,
+    contribution_id: i64,
+    contributor_id: i64,
+    author_id: i64,
+    from_reviewer: bool,
+    body: &str,
+) -> Result<ContributionMessage, sqlx::Error> {
+    let message = sqlx::query_as!(
+        ContributionMessage,
+        r#"
+        WITH m AS (
+            INSERT INTO contribution_messages (contribution_id, author_id, from_reviewer, body)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, author_id, from_reviewer, body, created_at
+        )
+        SELECT m.id as "id!", m.author_id, u.username as "author_username?",
+            m.from_reviewer as "from_reviewer!", m.body as "body!", m.created_at as "created_at!"
+        FROM m
+        LEFT JOIN users u ON u.id = m.author_id
+        "#,
+        contribution_id,
+        author_id,
+        from_reviewer,
+        body
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let recipients = if from_reviewer {
+        vec![contributor_id]
+    } else {
+        sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT author_id as "author_id!"
+            FROM contribution_messages
+            WHERE contribution_id = $1 AND from_reviewer AND author_id IS NOT NULL
+            "#,
+            contribution_id
+        )
+        .fetch_all(&mut *conn)
+        .await?
+    };
+    let recipients: Vec<i64> = recipients
+        .into_iter()
+        .filter(|&id| id != author_id)
+        .collect();
+    if !recipients.is_empty() {
+        notifications::notify_many(
+            &mut *conn,
+            &recipients,
+            notifications::CONTRIBUTION_MESSAGE,
+            serde_json::json!({"contribution_id": contribution_id, "message_id": message.id}),
+        )
+        .await?;
+    }
+
+    Ok(message)
+}
@@ -0,0 +1,119 @@
+// src/services/contribution_review.rs
+
+//! Publishing approved contributions, and the trust rules under which some are
+//! approved without review.
+//!
+//! A user is trusted once enough of their contributions passed review and few
+//! were rejected (`AutoApprovalConfig`). Auto-approved contributions count
+//! only once an admin confirms them, and rolled back ones count as rejected,
+//! so trust cannot grow from unchecked work. Question contributions are not
+//! published while an exam event locks the question bank.
+
+use sqlx::PgConnection;
+
+use crate::{
+    config::AutoApprovalConfig,
+    error::AppError,
+    models::{
+        architecture::CreateArchRequest,
+        contribution::{Contribution, TrustLevel},
+        question::CreateQuestionRequest,
+    },
+    services::{exam_events, slugs},
+    utils::{html::clean_html, pinyin::pinyin_keys},
+};
+
+/// What approving a contribution created.
+pub enum Published {
+    Architecture { id: i64, cover_img: String },
+    Question { id: i64 },
+}
+
+/// Creates the architecture or question of a contribution, linked to it.
+/// The contribution's own status is left to the caller.
+pub async fn publish(
+    conn: &mut PgConnection,
+    contrib: &Contribution,
+) -> Result<Published, AppError> {
+    match contrib.r#type.as_str() {
+        "architecture" => {
+            let data: CreateArchRequest = serde_json::from_value(contrib.data.clone())?;
+            let loc = data.structured_location();
+            let keys = pinyin_keys(&data.name);
+            let dims = data.dimensions();
+            let carousel = serde_json::to_value(&data.carousel_imgs).unwrap_or_default();
+            let clean_desc = clean_html(&data.description);
+            let slug = slugs::for_architecture(&mut *conn, &data.name).await?;
+            let id = sqlx::query_scalar!(
+                "INSERT INTO architectures (category, name, dynasty, location, province, city, district, description, cover_img, carousel_imgs, contribution_id, created_by, name_pinyin, name_initials, height_m, footprint_m2, bays_wide, bays_deep, slug) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19) RETURNING id",
+                data.category, data.name, data.dynasty, data.location, loc.province, loc.city, loc.district, clean_desc, data.cover_img, carousel, contrib.id, contrib.user_id, keys.full, keys.initials,
+                dims.height_m, dims.footprint_m2, dims.bays_wide, dims.bays_deep, slug
+            ).fetch_one(&mut *conn).await?;
+            Ok(Published::Architecture { id, cover_img: data.cover_img })
+        }
+        "question" => {
+            exam_events::ensure_bank_unlocked(&mut *conn).await?;
+            let data: CreateQuestionRequest = serde_json::from_value(contrib.data.clone())?;
+            let options = serde_json::to_value(data.options).unwrap_or_default();
+            let clean_content = clean_html(&data.content);
+            let clean_answer = clean_html(&data.answer);
+            let clean_analysis = data.analysis.map(|a| clean_html(&a));
+            let id = sqlx::query_scalar!(
+                "INSERT INTO questions (type, content, options, answer, analysis, contribution_id, created_by) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
+                data.question_type, clean_content, options, clean_answer, clean_analysis, contrib.id, contrib.user_id
+            ).fetch_one(&mut *conn).await?;
+            Ok(Published::Question { id })
+        }
+        _ => Err(AppError::BadRequest("Unknown type".to_string())),
+    }
+}
+
+/// The trust level of a user under `rules`.
+pub async fn trust_level(
+    conn: &mut PgConnection,
+    rules: &AutoApprovalConfig,
+    user_id: i64,
+) -> Result<TrustLevel, sqlx::Error> {
+    let history = sqlx::query!(
+        r#"
+        SELECT
+            COUNT(*) FILTER (
+                WHERE status = 'approved' AND (NOT auto_approved OR audited_at IS NOT NULL)
+            ) as "approved!",
+            COUNT(*) FILTER (WHERE status = 'rejected') as "rejected!"
+        FROM contributions
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let reviewed = history.approved + history.rejected;
+    Ok(if history.approved == 0 {
+        TrustLevel::New
+    } else if history.approved >= i64::from(rules.min_approved)
+        && history.rejected * 100 <= i64::from(rules.max_rejected_percent) * reviewed
+    {
+        TrustLevel::Trusted
+    } else {
+        TrustLevel::Contributor
+    })
+}
+
+/// Whether a new contribution of `type` by the user skips review.
+pub async fn auto_approves(
+    conn: &mut PgConnection,
+    rules: &AutoApprovalConfig,
+    user_id: i64,
+    r#type: &str,
+) -> Result<bool, sqlx::Error> {
+    if !rules.types.iter().any(|t| t == r#type) {
+        return Ok(false);
+    }
+    // Left for review until the exam event is over
+    if r#type == "question" && exam_events::running(&mut *conn).await?.is_some() {
+        return Ok(false);
+    }
+    Ok(trust_level(conn, rules, user_id).await? == TrustLevel::Trusted)
+}
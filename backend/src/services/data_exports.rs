@@ -0,0 +1,103 @@
+// src/services/data_exports.rs
+
+//! Archives of everything a user has put on the site, so they can take their
+//! data with them.
+//!
+//! `POST /api/profile/export` queues an `export_user_data` job, which writes
+//! the user's profile, posts, comments, likes, favorites, exam records,
+//! competition entries and contributions as gzipped JSON to `exports/users/{id}.json.gz`. The archive
+//! is downloaded through short-lived signed links and deleted after
+//! `DATA_EXPORT_RETENTION_DAYS`.
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+/// Job kind that writes a user's data archive to storage.
+pub const EXPORT_USER_DATA_JOB: &str = "export_user_data";
+
+/// Layout of the archive; bumped when fields are renamed or removed.
+pub const ARCHIVE_FORMAT: u32 = 1;
+
+/// Storage key of the archive of export `id`.
+pub fn archive_key(id: i64) -> String {
+    format!("exports/users/{}.json.gz", id)
+}
+
+/// Everything stored about a user that they provided or caused. Each section
+/// is a list of rows as stored, oldest first; soft-deleted posts and comments
+/// are included with their `deleted_at`.
+#[derive(Debug, Serialize)]
+pub struct UserDataArchive {
+    pub format: u32,
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    /// The account, without credentials.
+    pub profile: serde_json::Value,
+    pub posts: serde_json::Value,
+    pub comments: serde_json::Value,
+    pub likes: serde_json::Value,
+    pub favorites: serde_json::Value,
+    pub exam_records: serde_json::Value,
+    /// Including answers and any proctoring signals.
+    pub competition_entries: serde_json::Value,
+    pub contributions: serde_json::Value,
+}
+
+/// Reads the archive of `user_id`; None if the account no longer exists.
+pub async fn build(pool: &PgPool, user_id: i64) -> Result<Option<UserDataArchive>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT
+            jsonb_build_object(
+                'id', u.id, 'username', u.username, 'email', u.email,
+                'email_verified_at', u.email_verified_at, 'role', u.role,
+                'is_verified', u.is_verified, 'locale', u.locale, 'birth_year', u.birth_year,
+                'region', u.region, 'bio', u.bio, 'location', u.location, 'website', u.website,
+                'avatar_url', u.avatar_url, 'settings', u.user_settings,
+                'two_factor_enabled', u.totp_enabled_at IS NOT NULL,
+                'created_at', u.created_at, 'last_active_at', u.last_active_at
+            ) as "profile!",
+            (SELECT COALESCE(jsonb_agg(to_jsonb(p) ORDER BY p.id), '[]')
+             FROM posts p WHERE p.user_id = u.id) as "posts!",
+            (SELECT COALESCE(jsonb_agg(to_jsonb(c) ORDER BY c.id), '[]')
+             FROM comments c WHERE c.user_id = u.id) as "comments!",
+            (SELECT COALESCE(jsonb_agg(
+                jsonb_build_object('post_id', l.post_id, 'created_at', l.created_at)
+                ORDER BY l.created_at), '[]')
+             FROM post_likes l WHERE l.user_id = u.id) as "likes!",
+            (SELECT COALESCE(jsonb_agg(
+                jsonb_build_object('post_id', f.post_id, 'created_at', f.created_at)
+                ORDER BY f.created_at), '[]')
+             FROM post_favorites f WHERE f.user_id = u.id) as "favorites!",
+            (SELECT COALESCE(jsonb_agg(
+                jsonb_build_object('id', e.id, 'score', e.score, 'created_at', e.created_at)
+                ORDER BY e.id), '[]')
+             FROM exam_records e WHERE e.user_id = u.id) as "exam_records!",
+            (SELECT COALESCE(jsonb_agg(to_jsonb(ce) ORDER BY ce.registered_at), '[]')
+             FROM competition_entries ce WHERE ce.user_id = u.id) as "competition_entries!",
+            (SELECT COALESCE(jsonb_agg(
+                jsonb_build_object(
+                    'id', ct.id, 'type', ct.type, 'data', ct.data, 'status', ct.status,
+                    'created_at', ct.created_at, 'reviewed_at', ct.reviewed_at
+                ) ORDER BY ct.id), '[]')
+             FROM contributions ct WHERE ct.user_id = u.id) as "contributions!"
+        FROM users u
+        WHERE u.id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| UserDataArchive {
+        format: ARCHIVE_FORMAT,
+        generated_at: chrono::Utc::now(),
+        profile: row.profile,
+        posts: row.posts,
+        comments: row.comments,
+        likes: row.likes,
+        favorites: row.favorites,
+        exam_records: row.exam_records,
+        competition_entries: row.competition_entries,
+        contributions: row.contributions,
+    }))
+}
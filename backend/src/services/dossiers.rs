@@ -0,0 +1,569 @@
+// src/services/dossiers.rs
+
+//! Printable PDF dossiers of architectures, for site packets.
+//!
+//! `GET /api/architectures/{id}/dossier.pdf` queues an `architecture_dossier`
+//! job the first time, and again whenever the architecture has changed since.
+//! The job renders the description, gallery, location and sources into a PDF
+//! in storage; later requests are redirected to a signed link to it.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use url::Url;
+
+use crate::{
+    config::{DOSSIER_IMAGE_MAX_BYTES, DOSSIER_REQUEUE_SECS},
+    jobs::queue,
+    utils::{
+        html::plain_text,
+        http_client::{self, OutboundPolicy},
+        pdf::{ImageId, PdfDocument, PdfError, PdfFont, PdfPage, wrap},
+    },
+};
+
+/// Job kind that renders one dossier.
+pub const DOSSIER_JOB: &str = "architecture_dossier";
+
+/// Where OpenStreetMap searches for a place; the location is appended.
+const MAP_SEARCH_URL: &str = "https://www.openstreetmap.org/search";
+
+const MARGIN: f32 = 56.0;
+const BODY_SIZE: f32 = 10.5;
+const IMAGE_MAX_HEIGHT: f32 = 300.0;
+
+/// Storage key of the dossier of an architecture in `locale`.
+pub fn storage_key(architecture_id: i64, locale: &str) -> String {
+    format!("dossiers/architecture-{}-{}.pdf", architecture_id, locale)
+}
+
+/// Outcome of asking for a dossier.
+#[derive(Debug, PartialEq)]
+pub enum DossierStatus {
+    /// Up to date; the storage key of the PDF.
+    Ready(String),
+    /// Being rendered.
+    Pending,
+}
+
+/// Returns the dossier if it is up to date, or queues a render unless one is
+/// already under way. None when the architecture does not exist.
+///
+/// A render that has been pending for `DOSSIER_REQUEUE_SECS` is assumed lost
+/// (its job ran out of attempts) and is queued again.
+pub async fn request(
+    pool: &PgPool,
+    architecture_id: i64,
+    locale: &str,
+) -> Result<Option<DossierStatus>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let Some(updated_at) = sqlx::query_scalar!(
+        "SELECT updated_at FROM architectures WHERE id = $1",
+        architecture_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let claimed = sqlx::query_scalar!(
+        r#"
+        INSERT INTO architecture_dossiers (architecture_id, locale, source_updated_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (architecture_id, locale) DO UPDATE
+        SET status = 'pending', source_updated_at = EXCLUDED.source_updated_at, requested_at = NOW()
+        WHERE (architecture_dossiers.status = 'ready'
+               AND architecture_dossiers.source_updated_at < EXCLUDED.source_updated_at)
+           OR (architecture_dossiers.status = 'pending'
+               AND architecture_dossiers.requested_at < NOW() - make_interval(secs => $4))
+        RETURNING architecture_id
+        "#,
+        architecture_id,
+        locale,
+        updated_at,
+        DOSSIER_REQUEUE_SECS as f64
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if claimed.is_some() {
+        queue::enqueue(
+            &mut *tx,
+            DOSSIER_JOB,
+            serde_json::json!({ "architecture_id": architecture_id, "locale": locale }),
+        )
+        .await?;
+        tx.commit().await?;
+        return Ok(Some(DossierStatus::Pending));
+    }
+
+    let current = sqlx::query!(
+        r#"
+        SELECT status, storage_key
+        FROM architecture_dossiers
+        WHERE architecture_id = $1 AND locale = $2
+        "#,
+        architecture_id,
+        locale
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(Some(match (current.status.as_str(), current.storage_key) {
+        ("ready", Some(key)) => DossierStatus::Ready(key),
+        _ => DossierStatus::Pending,
+    }))
+}
+
+/// Everything printed in a dossier.
+#[derive(Debug, Default)]
+pub struct Dossier {
+    pub id: i64,
+    pub locale: String,
+    pub name: String,
+    pub category: String,
+    pub dynasty: String,
+    pub location: String,
+    pub province: Option<String>,
+    pub city: Option<String>,
+    pub district: Option<String>,
+    /// Sanitized HTML.
+    pub description: String,
+    pub height_m: Option<f64>,
+    pub footprint_m2: Option<f64>,
+    pub bays_wide: Option<i32>,
+    pub bays_deep: Option<i32>,
+    /// Cover first, then the carousel.
+    pub images: Vec<DossierImage>,
+    /// Who contributed or entered the architecture, and the contribution it came from.
+    pub author: Option<String>,
+    pub contribution_id: Option<i64>,
+    /// Community posts that reference the architecture, oldest first.
+    pub posts: Vec<CitedPost>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct DossierImage {
+    pub url: String,
+    /// The image when it is a JPEG that could be downloaded.
+    pub jpeg: Option<Vec<u8>>,
+    /// Dominant color (`#rrggbb`), painted in place of images that are not embedded.
+    pub color: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct CitedPost {
+    pub id: i64,
+    pub title: String,
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Downloads an image to embed, or None if it cannot be fetched or is not a JPEG.
+/// Failures are only logged: a dossier without a photo is still useful.
+pub async fn fetch_jpeg(url: &str) -> Option<Vec<u8>> {
+    let policy = OutboundPolicy {
+        max_body_bytes: DOSSIER_IMAGE_MAX_BYTES + 1,
+        user_agent: "ancient-arch-dossier/1.0",
+        ..OutboundPolicy::default()
+    };
+    match http_client::get(url, &policy).await {
+        Ok(resp)
+            if resp.status.is_success()
+                && resp.body.len() <= DOSSIER_IMAGE_MAX_BYTES
+                && resp.body.starts_with(&[0xFF, 0xD8]) =>
+        {
+            Some(resp.body)
+        }
+        Ok(_) => None,
+        Err(e) => {
+            tracing::warn!("Dossier image {} not fetched: {}", url, e);
+            None
+        }
+    }
+}
+
+/// Headings and labels, in Chinese for Chinese locales and English otherwise.
+struct Labels {
+    dossier: &'static str,
+    overview: &'static str,
+    category: &'static str,
+    dynasty: &'static str,
+    height: &'static str,
+    footprint: &'static str,
+    bays: &'static str,
+    square_metres: &'static str,
+    times: &'static str,
+    description: &'static str,
+    gallery: &'static str,
+    figure: &'static str,
+    not_embedded: &'static str,
+    location: &'static str,
+    region: &'static str,
+    map: &'static str,
+    sources: &'static str,
+    catalog_entry: &'static str,
+    contributed_by: &'static str,
+    entered_by: &'static str,
+    image_source: &'static str,
+    community_post: &'static str,
+    generated: &'static str,
+}
+
+impl Labels {
+    fn for_locale(locale: &str) -> Self {
+        match locale {
+            "zh-CN" => Labels {
+                dossier: "建筑档案",
+                overview: "概况",
+                category: "类别",
+                dynasty: "年代",
+                height: "高度",
+                footprint: "占地面积",
+                bays: "开间 × 进深",
+                square_metres: "平方米",
+                times: " × ",
+                description: "简介",
+                gallery: "图集",
+                figure: "图",
+                not_embedded: "图片无法嵌入，请访问来源链接查看",
+                location: "位置",
+                region: "行政区",
+                map: "地图",
+                sources: "资料来源",
+                catalog_entry: "Ancient Arch 建筑名录条目",
+                contributed_by: "贡献者",
+                entered_by: "录入",
+                image_source: "图片来源",
+                community_post: "社区文章",
+                generated: "生成于",
+            },
+            "zh-TW" => Labels {
+                dossier: "建築檔案",
+                overview: "概況",
+                category: "類別",
+                dynasty: "年代",
+                height: "高度",
+                footprint: "佔地面積",
+                bays: "開間 × 進深",
+                square_metres: "平方公尺",
+                times: " × ",
+                description: "簡介",
+                gallery: "圖集",
+                figure: "圖",
+                not_embedded: "圖片無法嵌入，請造訪來源連結查看",
+                location: "位置",
+                region: "行政區",
+                map: "地圖",
+                sources: "資料來源",
+                catalog_entry: "Ancient Arch 建築名錄條目",
+                contributed_by: "貢獻者",
+                entered_by: "錄入",
+                image_source: "圖片來源",
+                community_post: "社群文章",
+                generated: "產生於",
+            },
+            _ => Labels {
+                dossier: "Site dossier",
+                overview: "Overview",
+                category: "Category",
+                dynasty: "Dynasty",
+                height: "Height",
+                footprint: "Footprint",
+                bays: "Bays (width x depth)",
+                square_metres: "sq m",
+                times: " x ",
+                description: "Description",
+                gallery: "Gallery",
+                figure: "Fig.",
+                not_embedded: "Image not embedded; see the source link",
+                location: "Location",
+                region: "Region",
+                map: "Map",
+                sources: "Sources",
+                catalog_entry: "Ancient Arch catalog entry",
+                contributed_by: "Contributed by",
+                entered_by: "Entered by",
+                image_source: "Image source",
+                community_post: "Community post",
+                generated: "Generated",
+            },
+        }
+    }
+}
+
+/// Lays text and images out top to bottom, starting new pages as needed.
+struct Layout {
+    doc: PdfDocument,
+    page: PdfPage,
+    y: f32,
+    pages: u32,
+    footer: String,
+}
+
+impl Layout {
+    fn new(font: Option<&PdfFont>, footer: String) -> Self {
+        let page = PdfPage::portrait();
+        Layout {
+            doc: PdfDocument::new(font),
+            y: page.height() - MARGIN,
+            page,
+            pages: 1,
+            footer,
+        }
+    }
+
+    fn content_width(&self) -> f32 {
+        self.page.width() - 2.0 * MARGIN
+    }
+
+    /// Starts a new page unless `height` still fits above the footer.
+    fn ensure(&mut self, height: f32) {
+        if self.y - height < MARGIN + 12.0 {
+            self.break_page();
+        }
+    }
+
+    fn break_page(&mut self) {
+        let next = PdfPage::portrait();
+        self.y = next.height() - MARGIN;
+        let done = std::mem::replace(&mut self.page, next);
+        self.push_with_footer(done);
+        self.pages += 1;
+    }
+
+    fn push_with_footer(&mut self, mut page: PdfPage) {
+        let footer = format!("{} - {}", self.footer, self.pages);
+        page.text(MARGIN, MARGIN - 24.0, 8.0, false, &footer);
+        self.doc.push(page);
+    }
+
+    fn title(&mut self, text: &str, subtitle: &str) {
+        for line in wrap(text, 22.0, self.content_width()) {
+            self.y -= 28.0;
+            self.page.text(MARGIN, self.y, 22.0, true, &line);
+        }
+        self.y -= 18.0;
+        self.page.text(MARGIN, self.y, 11.0, false, subtitle);
+        self.y -= 10.0;
+    }
+
+    fn heading(&mut self, text: &str) {
+        // Keep a heading together with the first lines after it.
+        self.ensure(60.0);
+        self.y -= 24.0;
+        self.page.text(MARGIN, self.y, 14.0, true, text);
+        self.y -= 6.0;
+    }
+
+    fn paragraph(&mut self, text: &str, size: f32) {
+        for line in wrap(text, size, self.content_width()) {
+            self.ensure(size * 1.5);
+            self.y -= size * 1.5;
+            self.page.text(MARGIN, self.y, size, false, &line);
+        }
+        self.y -= size * 0.5;
+    }
+
+    fn field(&mut self, label: &str, value: &str) {
+        self.paragraph(&format!("{}: {}", label, value), BODY_SIZE);
+    }
+
+    /// Draws an embedded image scaled to the content width, or a box in
+    /// `color` when there is none, followed by its caption.
+    fn figure(&mut self, image: Option<ImageId>, color: [u8; 3], caption: &str, note: &str) {
+        let width = self.content_width();
+        let (w, h) = match image {
+            Some(image) => {
+                let scale = (width / image.width as f32)
+                    .min(IMAGE_MAX_HEIGHT / image.height as f32)
+                    .min(1.0);
+                (image.width as f32 * scale, image.height as f32 * scale)
+            }
+            None => (width, 90.0),
+        };
+        self.ensure(h + 30.0);
+        self.y -= h + 6.0;
+        match image {
+            Some(image) => {
+                self.page.image(image, MARGIN, self.y, w, h);
+            }
+            None => {
+                self.page.fill_rect(MARGIN, self.y, w, h, color);
+                self.page
+                    .text(MARGIN + 10.0, self.y + h / 2.0 - 4.0, 9.0, false, note);
+            }
+        }
+        self.paragraph(caption, 9.0);
+    }
+
+    fn finish(mut self) -> Result<Vec<u8>, PdfError> {
+        let last = std::mem::take(&mut self.page);
+        self.push_with_footer(last);
+        self.doc.finish()
+    }
+}
+
+/// Splits sanitized HTML into plain-text paragraphs.
+fn paragraphs(html: &str) -> Vec<String> {
+    let marked = [
+        "</p>",
+        "<br>",
+        "<br/>",
+        "<br />",
+        "</li>",
+        "</h1>",
+        "</h2>",
+        "</h3>",
+        "</h4>",
+        "</blockquote>",
+    ]
+    .iter()
+    .fold(html.to_string(), |text, tag| text.replace(tag, "\u{1}"));
+    marked
+        .split('\u{1}')
+        .map(plain_text)
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+fn parse_color(hex: &str) -> Option<[u8; 3]> {
+    let hex = hex.strip_prefix('#')?;
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// A link to the place on OpenStreetMap.
+pub fn map_url(dossier: &Dossier) -> String {
+    let place = [&dossier.district, &dossier.city, &dossier.province]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let query = if place.is_empty() {
+        dossier.location.clone()
+    } else {
+        format!("{} {}", dossier.name, place.join(" "))
+    };
+    Url::parse_with_params(MAP_SEARCH_URL, [("query", query)])
+        .map(String::from)
+        .unwrap_or_else(|_| MAP_SEARCH_URL.to_string())
+}
+
+/// Renders a dossier as an A4 portrait PDF with `font` embedded.
+pub fn render(dossier: &Dossier, font: Option<&PdfFont>) -> Result<Vec<u8>, PdfError> {
+    let labels = Labels::for_locale(&dossier.locale);
+    let mut layout = Layout::new(
+        font,
+        format!("{} - {}", dossier.name, labels.dossier),
+    );
+
+    let generated = format!(
+        "{} - {} {}",
+        labels.dossier,
+        labels.generated,
+        dossier.generated_at.format("%Y-%m-%d")
+    );
+    layout.title(&dossier.name, &generated);
+
+    // Overview
+    layout.heading(labels.overview);
+    layout.field(labels.category, &dossier.category);
+    layout.field(labels.dynasty, &dossier.dynasty);
+    layout.field(labels.location, &dossier.location);
+    if let Some(height) = dossier.height_m {
+        layout.field(labels.height, &format!("{} m", height));
+    }
+    if let Some(footprint) = dossier.footprint_m2 {
+        layout.field(
+            labels.footprint,
+            &format!("{} {}", footprint, labels.square_metres),
+        );
+    }
+    if let (Some(wide), Some(deep)) = (dossier.bays_wide, dossier.bays_deep) {
+        layout.field(labels.bays, &format!("{}{}{}", wide, labels.times, deep));
+    }
+
+    // Description
+    layout.heading(labels.description);
+    for paragraph in paragraphs(&dossier.description) {
+        layout.paragraph(&paragraph, BODY_SIZE);
+    }
+
+    // Sources are numbered: the catalog entry, then each image, then posts.
+    let image_source = |i: usize| i + 2;
+
+    // Gallery
+    if !dossier.images.is_empty() {
+        layout.heading(labels.gallery);
+        for (i, image) in dossier.images.iter().enumerate() {
+            let embedded = image
+                .jpeg
+                .clone()
+                .and_then(|jpeg| layout.doc.add_jpeg(jpeg));
+            let color = image
+                .color
+                .as_deref()
+                .and_then(parse_color)
+                .unwrap_or([230, 230, 230]);
+            let caption = format!("{} {} [{}]", labels.figure, i + 1, image_source(i));
+            layout.figure(embedded, color, &caption, labels.not_embedded);
+        }
+    }
+
+    // Location
+    layout.heading(labels.location);
+    layout.field(labels.location, &dossier.location);
+    let region = [&dossier.province, &dossier.city, &dossier.district]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" / ");
+    if !region.is_empty() {
+        layout.field(labels.region, &region);
+    }
+    layout.field(labels.map, &map_url(dossier));
+
+    // Sources
+    layout.heading(labels.sources);
+    let mut entry = format!("[1] {} #{}", labels.catalog_entry, dossier.id);
+    if let Some(author) = &dossier.author {
+        let by = if dossier.contribution_id.is_some() {
+            labels.contributed_by
+        } else {
+            labels.entered_by
+        };
+        entry.push_str(&format!(", {}: {}", by, author));
+    }
+    layout.paragraph(&entry, 9.0);
+    for (i, image) in dossier.images.iter().enumerate() {
+        layout.paragraph(
+            &format!(
+                "[{}] {}: {}",
+                image_source(i),
+                labels.image_source,
+                image.url
+            ),
+            9.0,
+        );
+    }
+    for (i, post) in dossier.posts.iter().enumerate() {
+        layout.paragraph(
+            &format!(
+                "[{}] {}: {}, {}, {} (#{})",
+                dossier.images.len() + 2 + i,
+                labels.community_post,
+                post.title,
+                post.author,
+                post.created_at.format("%Y-%m-%d"),
+                post.id
+            ),
+            9.0,
+        );
+    }
+    layout.finish()
+}
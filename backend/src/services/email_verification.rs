@@ -0,0 +1,130 @@
+// src/services/email_verification.rs
+
+//! Confirming that a user owns the email address on their account.
+//!
+//! Setting an address (at registration or from the profile) emails a
+//! single-use token; `POST /api/auth/verify-email` with that token marks the
+//! address verified. Only the newest token of a user is valid, and only while
+//! the account still has the address it was sent to.
+
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+    config::{EMAIL_VERIFICATION_TTL_HOURS, MailConfig},
+    error::AppError,
+    utils::{
+        hash::{new_token, token_hash},
+        mailer::{self, Email},
+    },
+};
+
+/// Replaces any pending verification of the user with a new one and queues the email.
+/// Run it in the transaction that sets the address.
+pub async fn start(
+    conn: &mut PgConnection,
+    mail: &MailConfig,
+    user_id: i64,
+    email: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM email_verifications WHERE user_id = $1", user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let token = new_token();
+    sqlx::query!(
+        r#"
+        INSERT INTO email_verifications (token_hash, user_id, email, expires_at)
+        VALUES ($1, $2, $3, CURRENT_TIMESTAMP + make_interval(hours => $4))
+        "#,
+        token_hash(&token),
+        user_id,
+        email,
+        EMAIL_VERIFICATION_TTL_HOURS
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    mailer::queue(&mut *conn, &message(mail, email, &token)).await?;
+    Ok(())
+}
+
+fn message(mail: &MailConfig, to: &str, token: &str) -> Email {
+    let mut body = String::from("Please confirm your email address for Ancient Arch.\n\n");
+    if let Some(url) = &mail.verification_url {
+        body.push_str(&format!("Open this link to confirm it: {}?token={}\n\n", url, token));
+    }
+    body.push_str(&format!(
+        "Verification code: {}\n\nThe code expires in {} hours. If you did not sign up, ignore this email.\n",
+        token, EMAIL_VERIFICATION_TTL_HOURS
+    ));
+
+    Email {
+        to: to.to_string(),
+        subject: "Confirm your email address".to_string(),
+        body,
+    }
+}
+
+/// Uses up `token` and marks its address verified. Returns the address.
+pub async fn confirm(pool: &PgPool, token: &str) -> Result<String, AppError> {
+    let invalid = || AppError::BadRequest("Invalid or expired verification token".to_string());
+    let mut tx = pool.begin().await?;
+
+    let pending = sqlx::query!(
+        r#"
+        DELETE FROM email_verifications
+        WHERE token_hash = $1 AND expires_at > CURRENT_TIMESTAMP
+        RETURNING user_id, email
+        "#,
+        token_hash(token)
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(invalid)?;
+
+    // The address may have been changed since the email was sent.
+    let updated = sqlx::query!(
+        r#"
+        UPDATE users SET email_verified_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND email = $2 AND email_verified_at IS NULL
+        "#,
+        pending.user_id,
+        pending.email
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| match e.as_database_error().and_then(|d| d.constraint()) {
+        Some("idx_users_verified_email") => {
+            AppError::Conflict("This email address is already in use".to_string())
+        }
+        _ => AppError::from(e),
+    })?
+    .rows_affected();
+    if updated == 0 {
+        return Err(invalid());
+    }
+
+    tx.commit().await?;
+    Ok(pending.email)
+}
+
+/// Whether an account other than `user_id` has already verified `email`.
+pub async fn is_taken(
+    pool: &PgPool,
+    email: &str,
+    user_id: Option<i64>,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM users
+            WHERE lower(email) = lower($1) AND email_verified_at IS NOT NULL
+              AND id IS DISTINCT FROM $2
+        ) as "taken!"
+        "#,
+        email,
+        user_id
+    )
+    .fetch_one(pool)
+    .await
+}
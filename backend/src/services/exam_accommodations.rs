@@ -0,0 +1,64 @@
+// src/services/exam_accommodations.rs
+
+//! Exam accommodations granted by admins, honoured when a qualification exam
+//! is issued: the deadline in its token is extended and the paper shortened.
+
+use sqlx::PgExecutor;
+
+use crate::models::exam_record::ExamAccommodations;
+
+/// The user's accommodations, if any were granted.
+pub async fn find<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+) -> Result<Option<ExamAccommodations>, sqlx::Error> {
+    sqlx::query_as!(
+        ExamAccommodations,
+        r#"
+        SELECT time_multiplier, large_labels, question_count
+        FROM exam_accommodations
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+/// Grants `accommodations` to the user, replacing any earlier grant.
+pub async fn set<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+    accommodations: &ExamAccommodations,
+    granted_by: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO exam_accommodations
+            (user_id, time_multiplier, large_labels, question_count, granted_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (user_id) DO UPDATE SET
+            time_multiplier = EXCLUDED.time_multiplier,
+            large_labels = EXCLUDED.large_labels,
+            question_count = EXCLUDED.question_count,
+            granted_by = EXCLUDED.granted_by,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+        user_id,
+        accommodations.time_multiplier,
+        accommodations.large_labels,
+        accommodations.question_count,
+        granted_by
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Withdraws the user's accommodations. Returns false if there were none.
+pub async fn clear<'e, E: PgExecutor<'e>>(executor: E, user_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!("DELETE FROM exam_accommodations WHERE user_id = $1", user_id)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
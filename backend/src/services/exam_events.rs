@@ -0,0 +1,60 @@
+// src/services/exam_events.rs
+
+//! Exam events: timed competitions scheduled by admins. While one is running
+//! the question bank is locked, so every participant sees the same questions,
+//! and the exam leaderboard leaves out scores submitted since it started;
+//! they appear once it ends.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Method, Request},
+    middleware::Next,
+    response::Response,
+};
+use sqlx::{PgExecutor, PgPool};
+
+use crate::{error::AppError, models::exam_event::ExamEvent};
+
+/// The running event ending last, if any.
+pub async fn running<'e, E: PgExecutor<'e>>(
+    executor: E,
+) -> Result<Option<ExamEvent>, sqlx::Error> {
+    sqlx::query_as!(
+        ExamEvent,
+        r#"
+        SELECT id, title, starts_at, ends_at, created_by, created_at
+        FROM exam_events
+        WHERE starts_at <= NOW() AND ends_at > NOW()
+        ORDER BY ends_at DESC
+        LIMIT 1
+        "#
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+/// Refuses with 409 while an exam event is running.
+pub async fn ensure_bank_unlocked<'e, E: PgExecutor<'e>>(executor: E) -> Result<(), AppError> {
+    match running(executor).await? {
+        Some(event) => Err(AppError::Conflict(format!(
+            "The question bank is locked until exam event '{}' ends at {}",
+            event.title,
+            event.ends_at.to_rfc3339()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Lets reads of the question bank through, and refuses changes while an
+/// exam event is running.
+pub async fn lock_question_bank(
+    State(pool): State<PgPool>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    if req.method() != Method::GET {
+        ensure_bank_unlocked(&pool).await?;
+    }
+    Ok(next.run(req).await)
+}
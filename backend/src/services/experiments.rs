@@ -0,0 +1,76 @@
+// src/services/experiments.rs
+
+//! A/B experiment assignment.
+//!
+//! A user's variant is derived from a hash of the experiment key and user id,
+//! so it is stable across requests and servers without storing anything.
+//! Serving an assignment records an exposure, which is what variants are
+//! compared on.
+
+use std::collections::BTreeMap;
+
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+/// A running experiment. Users are split evenly across `variants`;
+/// the first variant is the control.
+#[derive(Debug)]
+pub struct Experiment {
+    pub key: &'static str,
+    pub variants: &'static [&'static str],
+}
+
+/// Experiments currently running. Changing an experiment's variants reshuffles
+/// its users, so start a new key instead of editing a live one.
+pub const EXPERIMENTS: &[Experiment] = &[
+    Experiment {
+        key: "discover_ranking",
+        variants: &["control", "affinity_boost"],
+    },
+    Experiment {
+        key: "onboarding_flow",
+        variants: &["control", "guided_tour"],
+    },
+];
+
+/// Returns the variant of `experiment` for `user_id`.
+pub fn assign(experiment: &Experiment, user_id: i64) -> &'static str {
+    let digest = Sha256::digest(format!("{}:{}", experiment.key, user_id).as_bytes());
+    let bucket = u64::from_be_bytes(digest[..8].try_into().expect("digest has 32 bytes"));
+    experiment.variants[(bucket % experiment.variants.len() as u64) as usize]
+}
+
+/// Returns the user's variant in every running experiment, keyed by experiment.
+pub fn assignments(user_id: i64) -> BTreeMap<String, String> {
+    EXPERIMENTS
+        .iter()
+        .map(|e| (e.key.to_string(), assign(e, user_id).to_string()))
+        .collect()
+}
+
+/// Records that the user was shown the given assignments.
+///
+/// Best effort, like the security log: a failure is logged and never fails
+/// the request that served the assignments.
+pub async fn record_exposures(pool: &PgPool, user_id: i64, assignments: &BTreeMap<String, String>) {
+    let keys: Vec<String> = assignments.keys().cloned().collect();
+    let variants: Vec<String> = assignments.values().cloned().collect();
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO experiment_exposures (user_id, experiment_key, variant)
+        SELECT $1, * FROM UNNEST($2::VARCHAR[], $3::VARCHAR[])
+        ON CONFLICT (user_id, experiment_key) DO UPDATE
+        SET variant = EXCLUDED.variant, last_exposed_at = NOW()
+        "#,
+        user_id,
+        &keys,
+        &variants
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        tracing::warn!("Failed to record experiment exposures for user {}: {:?}", user_id, e);
+    }
+}
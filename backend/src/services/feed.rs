@@ -0,0 +1,306 @@
+// src/services/feed.rs
+
+//! Discover feed: candidate gathering plus a pluggable scoring step.
+//!
+//! Candidates come from three buckets (trending posts, newly approved
+//! architectures, unanswered posts). A [`FeedScorer`] ranks them against the
+//! viewer's [`UserAffinity`]; swap the scorer in `AppState` to change ranking.
+
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::sync::LazyLock;
+
+static HTML_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+
+/// Max characters of body text included with each item.
+const SNIPPET_CHARS: usize = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedItemKind {
+    Post,
+    Architecture,
+}
+
+/// Which bucket an item was drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedReason {
+    Trending,
+    NewArchitecture,
+    Unanswered,
+}
+
+/// An item eligible for the feed, with the raw signals scorers may use.
+#[derive(Debug, Clone)]
+pub struct FeedCandidate {
+    pub kind: FeedItemKind,
+    pub reason: FeedReason,
+    pub id: i64,
+    pub title: String,
+    pub body: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Post author; None for architectures.
+    pub author_id: Option<i64>,
+    /// Architecture category; None for posts.
+    pub category: Option<String>,
+    pub likes: i64,
+    pub comments: i64,
+    pub favorites: i64,
+}
+
+/// What we know about the viewer's tastes. Empty for anonymous users.
+#[derive(Debug, Default)]
+pub struct UserAffinity {
+    pub user_id: Option<i64>,
+    /// Author id -> number of the viewer's likes/favorites/comments on their posts.
+    pub authors: HashMap<i64, i64>,
+    /// Architecture categories the viewer has contributed to.
+    pub categories: HashSet<String>,
+    /// Posts the viewer already liked; not worth recommending again.
+    pub seen_posts: HashSet<i64>,
+}
+
+/// Ranks feed candidates. Higher is better.
+pub trait FeedScorer: Send + Sync {
+    fn score(&self, item: &FeedCandidate, affinity: &UserAffinity) -> f64;
+}
+
+/// Hand-tuned scorer: bucket weight decayed by age, plus popularity and affinity boosts.
+#[derive(Debug, Default)]
+pub struct HeuristicScorer;
+
+impl FeedScorer for HeuristicScorer {
+    fn score(&self, item: &FeedCandidate, affinity: &UserAffinity) -> f64 {
+        let base = match item.reason {
+            FeedReason::Trending => 1.0,
+            FeedReason::NewArchitecture => 0.9,
+            FeedReason::Unanswered => 0.7,
+        };
+
+        let age_hours = (chrono::Utc::now() - item.created_at).num_minutes().max(0) as f64 / 60.0;
+        let recency = (-age_hours / 72.0).exp();
+
+        // Same weights as the community "hot" sort.
+        let engagement = (item.likes * 5 + item.comments * 3 + item.favorites * 10) as f64;
+        let popularity = 0.3 * (1.0 + engagement).ln();
+
+        let author_boost = item
+            .author_id
+            .and_then(|a| affinity.authors.get(&a))
+            .map_or(0.0, |&n| 0.5 * (1.0 + n as f64).ln());
+        let category_boost = match &item.category {
+            Some(c) if affinity.categories.contains(c) => 0.6,
+            _ => 0.0,
+        };
+
+        base * recency + popularity + author_boost + category_boost
+    }
+}
+
+/// A ranked feed entry as returned to clients.
+#[derive(Debug, Serialize)]
+pub struct FeedItem {
+    pub kind: FeedItemKind,
+    pub reason: FeedReason,
+    pub id: i64,
+    pub title: String,
+    pub snippet: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub score: f64,
+}
+
+/// Loads the viewer's interaction history.
+pub async fn load_affinity(pool: &PgPool, user_id: Option<i64>) -> Result<UserAffinity, sqlx::Error> {
+    let Some(uid) = user_id else {
+        return Ok(UserAffinity::default());
+    };
+
+    let authors = sqlx::query!(
+        r#"
+        SELECT p.user_id, COUNT(*) as "weight!"
+        FROM (
+            SELECT post_id FROM post_likes WHERE user_id = $1
+            UNION ALL
+            SELECT post_id FROM post_favorites WHERE user_id = $1
+            UNION ALL
+            SELECT post_id FROM comments WHERE user_id = $1
+        ) i
+        JOIN posts p ON p.id = i.post_id
+        WHERE p.user_id <> $1
+        GROUP BY p.user_id
+        "#,
+        uid
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| (r.user_id, r.weight))
+    .collect();
+
+    let categories = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT data->>'category' as "category!"
+        FROM contributions
+        WHERE user_id = $1 AND type = 'architecture' AND data->>'category' IS NOT NULL
+        "#,
+        uid
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    let seen_posts = sqlx::query_scalar!("SELECT post_id FROM post_likes WHERE user_id = $1", uid)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+
+    Ok(UserAffinity {
+        user_id: Some(uid),
+        authors,
+        categories,
+        seen_posts,
+    })
+}
+
+/// Gathers candidates from every bucket.
+pub async fn load_candidates(pool: &PgPool) -> Result<Vec<FeedCandidate>, sqlx::Error> {
+    let mut out = Vec::new();
+
+    let trending = sqlx::query!(
+        r#"
+        SELECT id, user_id, title, content, created_at as "created_at!",
+               likes_count, comments_count, favorites_count
+        FROM posts
+        WHERE deleted_at IS NULL AND created_at >= NOW() - INTERVAL '14 days'
+        ORDER BY (
+            (likes_count * 5 + comments_count * 3 + favorites_count * 10)::FLOAT /
+            POW(EXTRACT(EPOCH FROM (NOW() - created_at)) / 3600 + 2, 1.5)
+        ) DESC
+        LIMIT 50
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    out.extend(trending.into_iter().map(|p| FeedCandidate {
+        kind: FeedItemKind::Post,
+        reason: FeedReason::Trending,
+        id: p.id,
+        title: p.title,
+        body: p.content,
+        created_at: p.created_at,
+        author_id: Some(p.user_id),
+        category: None,
+        likes: p.likes_count as i64,
+        comments: p.comments_count as i64,
+        favorites: p.favorites_count as i64,
+    }));
+
+    let architectures = sqlx::query!(
+        r#"
+        SELECT a.id, a.name, a.category, a.description,
+               COALESCE(c.reviewed_at, a.created_at) as "approved_at!"
+        FROM architectures a
+        LEFT JOIN contributions c ON c.id = a.contribution_id
+        ORDER BY COALESCE(c.reviewed_at, a.created_at) DESC
+        LIMIT 30
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    out.extend(architectures.into_iter().map(|a| FeedCandidate {
+        kind: FeedItemKind::Architecture,
+        reason: FeedReason::NewArchitecture,
+        id: a.id,
+        title: a.name,
+        body: a.description,
+        created_at: a.approved_at,
+        author_id: None,
+        category: Some(a.category),
+        likes: 0,
+        comments: 0,
+        favorites: 0,
+    }));
+
+    let unanswered = sqlx::query!(
+        r#"
+        SELECT id, user_id, title, content, created_at as "created_at!",
+               likes_count, favorites_count
+        FROM posts
+        WHERE deleted_at IS NULL AND comments_count = 0
+          AND created_at >= NOW() - INTERVAL '30 days'
+        ORDER BY created_at DESC
+        LIMIT 30
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    out.extend(unanswered.into_iter().map(|p| FeedCandidate {
+        kind: FeedItemKind::Post,
+        reason: FeedReason::Unanswered,
+        id: p.id,
+        title: p.title,
+        body: p.content,
+        created_at: p.created_at,
+        author_id: Some(p.user_id),
+        category: None,
+        likes: p.likes_count as i64,
+        comments: 0,
+        favorites: p.favorites_count as i64,
+    }));
+
+    Ok(out)
+}
+
+/// Scores, filters and de-duplicates candidates into the final feed.
+pub fn rank(
+    candidates: Vec<FeedCandidate>,
+    affinity: &UserAffinity,
+    scorer: &dyn FeedScorer,
+    limit: usize,
+) -> Vec<FeedItem> {
+    let mut scored: Vec<(f64, FeedCandidate)> = candidates
+        .into_iter()
+        .filter(|c| match c.kind {
+            FeedItemKind::Post => {
+                c.author_id != affinity.user_id && !affinity.seen_posts.contains(&c.id)
+            }
+            FeedItemKind::Architecture => true,
+        })
+        .map(|c| (scorer.score(&c, affinity), c))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    // A post can be both trending and unanswered; keep its best-scoring entry.
+    let mut seen = HashSet::new();
+    scored
+        .into_iter()
+        .filter(|(_, c)| seen.insert((c.kind, c.id)))
+        .take(limit)
+        .map(|(score, c)| FeedItem {
+            kind: c.kind,
+            reason: c.reason,
+            id: c.id,
+            snippet: snippet(&c.body),
+            title: c.title,
+            created_at: c.created_at,
+            score,
+        })
+        .collect()
+}
+
+fn snippet(body: &str) -> String {
+    let text = HTML_TAG.replace_all(body, " ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= SNIPPET_CHARS {
+        return text;
+    }
+    let mut cut: String = text.chars().take(SNIPPET_CHARS).collect();
+    cut.push('…');
+    cut
+}
@@ -0,0 +1,97 @@
+// src/services/image_links.rs
+
+//! Dead-link checks for architecture images.
+//!
+//! An admin queues a `check_image_links` job, which probes every `cover_img`
+//! and `carousel_imgs` URL (a few at a time) and replaces the rows of
+//! `architecture_image_checks` for the architectures it scanned.
+
+use std::{collections::HashMap, sync::Arc};
+
+use reqwest::StatusCode;
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::{
+    config::IMAGE_LINK_CHECK_CONCURRENCY,
+    utils::http_client::{self, OutboundError, OutboundPolicy},
+};
+
+/// Job kind that checks architecture image URLs.
+pub const CHECK_IMAGE_LINKS_JOB: &str = "check_image_links";
+
+/// The outcome of probing one URL, as stored in `architecture_image_checks`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkStatus {
+    /// 'ok', 'broken', 'unreachable' or 'invalid'.
+    pub status: &'static str,
+    pub http_status: Option<i32>,
+    pub error: Option<String>,
+}
+
+/// Probes one URL with HEAD, falling back to GET for servers that refuse HEAD.
+pub async fn probe(url: &str) -> LinkStatus {
+    let policy = OutboundPolicy {
+        // Only the status matters if the GET fallback is used.
+        max_body_bytes: 1024,
+        user_agent: "ancient-arch-linkcheck/1.0",
+        ..OutboundPolicy::default()
+    };
+
+    let mut result = http_client::head(url, &policy).await;
+    if let Ok(resp) = &result
+        && matches!(
+            resp.status,
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+        )
+    {
+        result = http_client::get(url, &policy).await;
+    }
+
+    match result {
+        Ok(resp) if resp.status.is_success() => LinkStatus {
+            status: "ok",
+            http_status: Some(resp.status.as_u16() as i32),
+            error: None,
+        },
+        Ok(resp) => LinkStatus {
+            status: "broken",
+            http_status: Some(resp.status.as_u16() as i32),
+            error: Some(format!("HTTP {}", resp.status)),
+        },
+        Err(OutboundError::Blocked(reason)) => LinkStatus {
+            status: "invalid",
+            http_status: None,
+            error: Some(reason),
+        },
+        Err(OutboundError::Network(reason)) => LinkStatus {
+            status: "unreachable",
+            http_status: None,
+            error: Some(reason),
+        },
+    }
+}
+
+/// Probes each URL once, at most `IMAGE_LINK_CHECK_CONCURRENCY` at a time.
+pub async fn probe_all(urls: impl IntoIterator<Item = String>) -> HashMap<String, LinkStatus> {
+    let permits = Arc::new(Semaphore::new(IMAGE_LINK_CHECK_CONCURRENCY));
+    let mut probes = JoinSet::new();
+    for url in urls {
+        let permits = permits.clone();
+        probes.spawn(async move {
+            let _permit = permits.acquire_owned().await.expect("semaphore is never closed");
+            let status = probe(&url).await;
+            (url, status)
+        });
+    }
+
+    let mut results = HashMap::new();
+    while let Some(joined) = probes.join_next().await {
+        match joined {
+            Ok((url, status)) => {
+                results.insert(url, status);
+            }
+            Err(e) => tracing::error!("Image link probe failed: {}", e),
+        }
+    }
+    results
+}
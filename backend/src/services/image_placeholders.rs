@@ -0,0 +1,163 @@
+// src/services/image_placeholders.rs
+
+//! Dominant colors and BlurHashes for cover and post images.
+//!
+//! Saving an architecture or a post queues an `image_placeholder` job per new
+//! image URL. The job downloads the image and stores its placeholder in
+//! `image_placeholders`; architecture and post responses then carry it so
+//! clients can paint something before the image loads.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use sqlx::PgPool;
+
+use crate::{
+    config::{IMAGE_PLACEHOLDER_MAX_BYTES, IMAGE_PLACEHOLDER_MAX_IMAGES},
+    jobs::queue,
+    models::post::ImagePlaceholder,
+    utils::{
+        http_client::{self, OutboundError, OutboundPolicy},
+        image_preview,
+    },
+};
+
+/// Job kind that computes the placeholder of one image.
+pub const PLACEHOLDER_JOB: &str = "image_placeholder";
+
+static IMG_SRC_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)<img\s[^>]*?src="([^"]+)""#).unwrap());
+
+/// The placeholder computed from an image.
+#[derive(Debug, PartialEq)]
+pub struct Placeholder {
+    pub dominant_color: String,
+    pub blurhash: String,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Why an image did not produce a placeholder.
+#[derive(Debug)]
+pub enum PlaceholderError {
+    /// Not allowed, not an image, or not a format we decode; retrying will not help.
+    Rejected(String),
+    /// Network trouble; the job is retried.
+    Transient(String),
+}
+
+/// Finds the distinct http(s) `<img>` sources in sanitized HTML, in order of appearance.
+pub fn image_urls(html: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for c in IMG_SRC_RE.captures_iter(html) {
+        let url = c[1].replace("&amp;", "&");
+        if !(url.starts_with("http://") || url.starts_with("https://")) || urls.contains(&url) {
+            continue;
+        }
+        urls.push(url);
+        if urls.len() == IMAGE_PLACEHOLDER_MAX_IMAGES {
+            break;
+        }
+    }
+    urls
+}
+
+/// Queues the placeholder for a new cover image. A failure here must not fail the save.
+pub async fn queue_cover(pool: &PgPool, architecture_id: i64, cover_img: String) {
+    if let Err(e) = queue_placeholders(pool, &[cover_img]).await {
+        tracing::warn!(
+            "Failed to queue cover placeholder for architecture {}: {:?}",
+            architecture_id,
+            e
+        );
+    }
+}
+
+/// Queues a job for every URL that has no placeholder yet. Failed images are
+/// tried again, in case they have been fixed since.
+pub async fn queue_placeholders(pool: &PgPool, urls: &[String]) -> Result<(), sqlx::Error> {
+    for url in urls {
+        let mut tx = pool.begin().await?;
+
+        let claimed = sqlx::query!(
+            r#"
+            INSERT INTO image_placeholders (url) VALUES ($1)
+            ON CONFLICT (url) DO UPDATE SET status = 'pending'
+            WHERE image_placeholders.status = 'failed'
+            RETURNING url
+            "#,
+            url
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if claimed.is_some() {
+            queue::enqueue(&mut *tx, PLACEHOLDER_JOB, serde_json::json!({ "url": url })).await?;
+        }
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+/// Returns the placeholders computed so far for the images in `html`.
+pub async fn placeholders_for(
+    pool: &PgPool,
+    html: &str,
+) -> Result<Vec<ImagePlaceholder>, sqlx::Error> {
+    let urls = image_urls(html);
+    if urls.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut placeholders = sqlx::query_as!(
+        ImagePlaceholder,
+        r#"
+        SELECT url, dominant_color as "dominant_color!", blurhash as "blurhash!",
+               width as "width!", height as "height!"
+        FROM image_placeholders
+        WHERE url = ANY($1) AND status = 'ok'
+        "#,
+        &urls
+    )
+    .fetch_all(pool)
+    .await?;
+
+    placeholders.sort_by_key(|p| urls.iter().position(|u| *u == p.url));
+    Ok(placeholders)
+}
+
+/// Downloads an image and computes its placeholder.
+pub async fn fetch_placeholder(url: &str) -> Result<Placeholder, PlaceholderError> {
+    let policy = OutboundPolicy {
+        // One byte more than allowed, to tell a complete image from a truncated one.
+        max_body_bytes: IMAGE_PLACEHOLDER_MAX_BYTES + 1,
+        user_agent: "ancient-arch-images/1.0",
+        ..OutboundPolicy::default()
+    };
+
+    let resp = http_client::get(url, &policy).await.map_err(|e| match e {
+        OutboundError::Blocked(reason) => PlaceholderError::Rejected(reason),
+        OutboundError::Network(reason) => PlaceholderError::Transient(reason),
+    })?;
+
+    if !resp.status.is_success() {
+        return Err(PlaceholderError::Rejected(format!("HTTP {}", resp.status)));
+    }
+    if resp.body.len() > IMAGE_PLACEHOLDER_MAX_BYTES {
+        return Err(PlaceholderError::Rejected("Image too large".to_string()));
+    }
+
+    placeholder(&resp.body)
+        .ok_or_else(|| PlaceholderError::Rejected("Unsupported or damaged image".to_string()))
+}
+
+/// Computes the placeholder of an encoded JPEG or PNG.
+pub fn placeholder(bytes: &[u8]) -> Option<Placeholder> {
+    let preview = image_preview::decode(bytes)?;
+    Some(Placeholder {
+        dominant_color: image_preview::dominant_color(&preview),
+        blurhash: image_preview::blurhash(&preview),
+        width: preview.width as i32,
+        height: preview.height as i32,
+    })
+}
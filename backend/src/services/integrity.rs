@@ -0,0 +1,306 @@
+// src/services/integrity.rs
+
+//! Invariants the schema cannot enforce (`backend verify-integrity`,
+//! `GET /api/admin/system/integrity`).
+//!
+//! Denormalized counters on posts, comment thread roots and the like are kept
+//! in step by the handlers that change them; a bug or a manual edit can leave
+//! them wrong without any constraint noticing. Each [`Check`] finds the rows
+//! that break one invariant and, where the right value can be derived,
+//! repairs them.
+
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+    config::INTEGRITY_SAMPLE_SIZE,
+    models::integrity::{IntegrityCheck, IntegrityReport},
+    services::account_deletion,
+};
+
+/// One invariant. Checks run, and repair, in this order: likes are removed
+/// before counters are recounted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    OrphanedLikes,
+    PostLikesCount,
+    PostFavoritesCount,
+    PostCommentsCount,
+    CommentThreads,
+    ContributionAuthors,
+}
+
+impl Check {
+    pub const ALL: [Check; 6] = [
+        Check::OrphanedLikes,
+        Check::PostLikesCount,
+        Check::PostFavoritesCount,
+        Check::PostCommentsCount,
+        Check::CommentThreads,
+        Check::ContributionAuthors,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Check::OrphanedLikes => "orphaned_likes",
+            Check::PostLikesCount => "post_likes_count",
+            Check::PostFavoritesCount => "post_favorites_count",
+            Check::PostCommentsCount => "post_comments_count",
+            Check::CommentThreads => "comment_threads",
+            Check::ContributionAuthors => "contribution_authors",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Check::OrphanedLikes => {
+                "Posts liked or favorited by the ghost user, which cannot sign in, so the \
+                 likes were left behind by a deleted account (post IDs)"
+            }
+            Check::PostLikesCount => "Posts whose likes_count is not their number of likes",
+            Check::PostFavoritesCount => {
+                "Posts whose favorites_count is not their number of favorites"
+            }
+            Check::PostCommentsCount => {
+                "Posts whose comments_count is not their number of undeleted comments"
+            }
+            Check::CommentThreads => {
+                "Comments whose root_id is not the top comment of their parent chain, or whose \
+                 chain leaves the post or loops (comment IDs; only wrong roots can be repaired)"
+            }
+            Check::ContributionAuthors => {
+                "Contributions by users that no longer exist (contribution IDs; handed to the \
+                 ghost user)"
+            }
+        }
+    }
+
+    /// IDs of the rows breaking the invariant, in ascending order.
+    async fn find(self, conn: &mut PgConnection) -> Result<Vec<i64>, sqlx::Error> {
+        match self {
+            Check::OrphanedLikes => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT post_id as "post_id!" FROM post_likes
+                    WHERE user_id = (SELECT id FROM users WHERE username = 'ghost')
+                    UNION
+                    SELECT post_id FROM post_favorites
+                    WHERE user_id = (SELECT id FROM users WHERE username = 'ghost')
+                    ORDER BY 1
+                    "#
+                )
+                .fetch_all(conn)
+                .await
+            }
+            Check::PostLikesCount => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT p.id FROM posts p
+                    WHERE p.likes_count <> (SELECT COUNT(*) FROM post_likes WHERE post_id = p.id)
+                    ORDER BY p.id
+                    "#
+                )
+                .fetch_all(conn)
+                .await
+            }
+            Check::PostFavoritesCount => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT p.id FROM posts p
+                    WHERE p.favorites_count
+                          <> (SELECT COUNT(*) FROM post_favorites WHERE post_id = p.id)
+                    ORDER BY p.id
+                    "#
+                )
+                .fetch_all(conn)
+                .await
+            }
+            Check::PostCommentsCount => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT p.id FROM posts p
+                    WHERE p.comments_count <> (
+                        SELECT COUNT(*) FROM comments
+                        WHERE post_id = p.id AND deleted_at IS NULL
+                    )
+                    ORDER BY p.id
+                    "#
+                )
+                .fetch_all(conn)
+                .await
+            }
+            Check::CommentThreads => {
+                // Walks every chain down from the top comments of each post;
+                // comments not reached have a parent on another post or in a loop.
+                sqlx::query_scalar!(
+                    r#"
+                    WITH RECURSIVE chain (id, post_id, root) AS (
+                        SELECT id, post_id, id FROM comments WHERE parent_id IS NULL
+                        UNION ALL
+                        SELECT c.id, c.post_id, chain.root
+                        FROM comments c
+                        JOIN chain ON c.parent_id = chain.id AND c.post_id = chain.post_id
+                    )
+                    SELECT c.id as "id!" FROM comments c
+                    LEFT JOIN chain ON chain.id = c.id
+                    WHERE chain.id IS NULL OR c.root_id IS DISTINCT FROM NULLIF(chain.root, c.id)
+                    ORDER BY c.id
+                    "#
+                )
+                .fetch_all(conn)
+                .await
+            }
+            Check::ContributionAuthors => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT c.id FROM contributions c
+                    LEFT JOIN users u ON u.id = c.user_id
+                    WHERE u.id IS NULL
+                    ORDER BY c.id
+                    "#
+                )
+                .fetch_all(conn)
+                .await
+            }
+        }
+    }
+
+    /// Fixes what can be derived; the rest is left for `find` to report again.
+    async fn repair(self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+        match self {
+            Check::OrphanedLikes => {
+                sqlx::query!(
+                    "DELETE FROM post_likes WHERE user_id = (SELECT id FROM users WHERE username = 'ghost')"
+                )
+                .execute(&mut *conn)
+                .await?;
+                sqlx::query!(
+                    "DELETE FROM post_favorites WHERE user_id = (SELECT id FROM users WHERE username = 'ghost')"
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+            Check::PostLikesCount => {
+                sqlx::query!(
+                    r#"
+                    UPDATE posts p
+                    SET likes_count = (SELECT COUNT(*) FROM post_likes WHERE post_id = p.id)
+                    WHERE p.likes_count <> (SELECT COUNT(*) FROM post_likes WHERE post_id = p.id)
+                    "#
+                )
+                .execute(conn)
+                .await?;
+            }
+            Check::PostFavoritesCount => {
+                sqlx::query!(
+                    r#"
+                    UPDATE posts p
+                    SET favorites_count = (SELECT COUNT(*) FROM post_favorites WHERE post_id = p.id)
+                    WHERE p.favorites_count
+                          <> (SELECT COUNT(*) FROM post_favorites WHERE post_id = p.id)
+                    "#
+                )
+                .execute(conn)
+                .await?;
+            }
+            Check::PostCommentsCount => {
+                sqlx::query!(
+                    r#"
+                    UPDATE posts p
+                    SET comments_count = c.n
+                    FROM (
+                        SELECT p2.id, COUNT(c.id) as n
+                        FROM posts p2
+                        LEFT JOIN comments c ON c.post_id = p2.id AND c.deleted_at IS NULL
+                        GROUP BY p2.id
+                    ) c
+                    WHERE c.id = p.id AND p.comments_count <> c.n
+                    "#
+                )
+                .execute(conn)
+                .await?;
+            }
+            Check::CommentThreads => {
+                sqlx::query!(
+                    r#"
+                    WITH RECURSIVE chain (id, post_id, root) AS (
+                        SELECT id, post_id, id FROM comments WHERE parent_id IS NULL
+                        UNION ALL
+                        SELECT c.id, c.post_id, chain.root
+                        FROM comments c
+                        JOIN chain ON c.parent_id = chain.id AND c.post_id = chain.post_id
+                    )
+                    UPDATE comments c
+                    SET root_id = NULLIF(chain.root, c.id)
+                    FROM chain
+                    WHERE chain.id = c.id AND c.root_id IS DISTINCT FROM NULLIF(chain.root, c.id)
+                    "#
+                )
+                .execute(conn)
+                .await?;
+            }
+            Check::ContributionAuthors => {
+                let Some(ghost_id) = account_deletion::ghost_id(&mut *conn).await? else {
+                    return Ok(());
+                };
+                sqlx::query!(
+                    r#"
+                    UPDATE contributions c SET user_id = $1
+                    WHERE NOT EXISTS (SELECT 1 FROM users WHERE id = c.user_id)
+                    "#,
+                    ghost_id
+                )
+                .execute(conn)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs every check without changing anything.
+pub async fn verify(pool: &PgPool) -> Result<IntegrityReport, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let mut checks = Vec::with_capacity(Check::ALL.len());
+    for check in Check::ALL {
+        let ids = check.find(&mut conn).await?;
+        checks.push(outcome(check, &ids, None));
+    }
+    Ok(IntegrityReport { repaired: false, checked_at: chrono::Utc::now(), checks })
+}
+
+/// Runs every check and repairs what it can, in one transaction.
+/// Writes to the tables involved wait until it is done, so recounts are exact.
+pub async fn repair(pool: &PgPool) -> Result<IntegrityReport, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        r#"
+        LOCK TABLE posts, comments, post_likes, post_favorites, contributions
+        IN SHARE ROW EXCLUSIVE MODE
+        "#
+    )
+    .execute(&mut *tx)
+    .await?;
+    let mut checks = Vec::with_capacity(Check::ALL.len());
+    for check in Check::ALL {
+        let ids = check.find(&mut tx).await?;
+        let remaining = if ids.is_empty() {
+            0
+        } else {
+            check.repair(&mut tx).await?;
+            check.find(&mut tx).await?.len() as i64
+        };
+        checks.push(outcome(check, &ids, Some(remaining)));
+    }
+    tx.commit().await?;
+    Ok(IntegrityReport { repaired: true, checked_at: chrono::Utc::now(), checks })
+}
+
+fn outcome(check: Check, ids: &[i64], remaining: Option<i64>) -> IntegrityCheck {
+    IntegrityCheck {
+        name: check.name(),
+        description: check.description(),
+        violations: ids.len() as i64,
+        sample_ids: ids.iter().take(INTEGRITY_SAMPLE_SIZE).copied().collect(),
+        remaining,
+    }
+}
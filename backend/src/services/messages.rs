@@ -0,0 +1,61 @@
+// src/services/messages.rs
+
+//! Private messages. Each pair of users shares one conversation, created by
+//! the first message either of them sends.
+
+use sqlx::PgConnection;
+
+use crate::models::message::DirectMessage;
+
+/// The pair of a conversation as stored: lower user ID first.
+fn pair(a: i64, b: i64) -> (i64, i64) {
+    (a.min(b), a.max(b))
+}
+
+/// The conversation between two users, if they have one.
+pub async fn find(conn: &mut PgConnection, a: i64, b: i64) -> Result<Option<i64>, sqlx::Error> {
+    let (low, high) = pair(a, b);
+    sqlx::query_scalar!(
+        "SELECT id FROM conversations WHERE user_low_id = $1 AND user_high_id = $2",
+        low,
+        high
+    )
+    .fetch_optional(&mut *conn)
+    .await
+}
+
+/// Sends a message from `sender_id` to `recipient_id`, opening their
+/// conversation if needed. Checks are left to the caller.
+pub async fn send(
+    conn: &mut PgConnection,
+    sender_id: i64,
+    recipient_id: i64,
+    body: &str,
+) -> Result<DirectMessage, sqlx::Error> {
+    let (low, high) = pair(sender_id, recipient_id);
+    let conversation_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO conversations (user_low_id, user_high_id) VALUES ($1, $2)
+        ON CONFLICT (user_low_id, user_high_id) DO UPDATE SET last_message_at = NOW()
+        RETURNING id
+        "#,
+        low,
+        high
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query_as!(
+        DirectMessage,
+        r#"
+        INSERT INTO messages (conversation_id, sender_id, body)
+        VALUES ($1, $2, $3)
+        RETURNING id, conversation_id, sender_id, body, created_at, read_at
+        "#,
+        conversation_id,
+        sender_id,
+        body
+    )
+    .fetch_one(&mut *conn)
+    .await
+}
@@ -0,0 +1,51 @@
+// src/services/mod.rs
+
+//! Business logic shared between handlers and background jobs.
+
+pub mod account_deletion;
+pub mod api_usage;
+pub mod audit_log;
+pub mod auto_quiz;
+pub mod bans;
+pub mod blocks;
+pub mod broadcasts;
+pub mod catalog_export;
+pub mod certificates;
+pub mod comment_throttle;
+pub mod competitions;
+pub mod contribution_attachments;
+pub mod contribution_messages;
+pub mod contribution_review;
+pub mod data_exports;
+pub mod dossiers;
+pub mod email_verification;
+pub mod exam_accommodations;
+pub mod exam_events;
+pub mod experiments;
+pub mod feed;
+pub mod image_links;
+pub mod image_placeholders;
+pub mod integrity;
+pub mod messages;
+pub mod notifications;
+pub mod oauth;
+pub mod paper_snapshots;
+pub mod password_reset;
+pub mod question_duplicates;
+pub mod question_import;
+pub mod question_selection;
+pub mod quotas;
+pub mod redirects;
+pub mod references;
+pub mod security_events;
+pub mod segments;
+pub mod series;
+pub mod sessions;
+pub mod slugs;
+pub mod spam;
+pub mod tags;
+pub mod terms;
+pub mod token_revocation;
+pub mod two_factor;
+pub mod unfurl;
+pub mod user_settings;
@@ -0,0 +1,167 @@
+// src/services/notifications.rs
+
+//! In-app notifications. Callers create them inside the transaction of the
+//! change they report, so a rolled-back change never notifies anyone.
+
+use sqlx::{PgConnection, PgExecutor};
+
+/// An admin granted the user verified status. Payload: `{"reason"}`.
+pub const VERIFICATION_GRANTED: &str = "verification_granted";
+/// An admin revoked the user's verified status. Payload: `{"reason"}`.
+pub const VERIFICATION_REVOKED: &str = "verification_revoked";
+/// A moderator banned the user. Payload: `{"banned_until", "reason"}`, with
+/// `banned_until` null for an indefinite ban.
+pub const ACCOUNT_BANNED: &str = "account_banned";
+/// A moderator lifted the user's ban. Payload: `{"reason"}`.
+pub const ACCOUNT_UNBANNED: &str = "account_unbanned";
+/// New comments on the user's post. Payload: `{"post_id", "count", "comment_id"}`,
+/// where `comment_id` is the latest of the `count` comments.
+pub const POST_COMMENTS: &str = "post_comments";
+/// New likes on the user's post, batched like comments. Payload:
+/// `{"post_id", "count", "user_id", "user_ids"}`, where `user_id` liked last
+/// and `user_ids` are the `count` distinct likers.
+pub const POST_LIKES: &str = "post_likes";
+/// A reply to the user's comment. Payload: `{"post_id", "comment_id", "parent_id"}`,
+/// where `comment_id` is the reply.
+pub const COMMENT_REPLY: &str = "comment_reply";
+/// An admin decided on the user's contribution. Payload: `{"contribution_id", "status"}`.
+pub const CONTRIBUTION_REVIEWED: &str = "contribution_reviewed";
+/// A new message in the review thread of a contribution, for the contributor
+/// or the reviewers taking part. Payload: `{"contribution_id", "message_id"}`.
+pub const CONTRIBUTION_MESSAGE: &str = "contribution_message";
+/// Daily summary of pending admin work, sent to admins. Payload: the `AdminDigest`.
+pub const ADMIN_DIGEST: &str = "admin_digest";
+/// The user's data export can be downloaded. Payload: `{"export_id"}`.
+pub const DATA_EXPORT_READY: &str = "data_export_ready";
+/// An announcement from the admins. Payload: `{"broadcast_id", "title", "message"}`.
+pub const BROADCAST: &str = "broadcast";
+
+/// Adds a notification for `user_id`.
+pub async fn notify<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+    kind: &str,
+    payload: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO notifications (user_id, kind, payload) VALUES ($1, $2, $3)",
+        user_id,
+        kind,
+        payload
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Adds the same notification for each of `user_ids`.
+pub async fn notify_many<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_ids: &[i64],
+    kind: &str,
+    payload: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "INSERT INTO notifications (user_id, kind, payload) SELECT unnest($1::BIGINT[]), $2, $3",
+        user_ids,
+        kind,
+        payload
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Tells the author of `post_id` about a new comment.
+///
+/// While the last notification about the post is unread and less than
+/// `window_secs` old, the comment is counted into it instead, so a busy thread
+/// shows up as one "12 new comments" entry rather than twelve.
+/// Callers should hold the post's row lock so concurrent comments don't both
+/// start a new notification.
+pub async fn notify_comment(
+    conn: &mut PgConnection,
+    user_id: i64,
+    post_id: i64,
+    comment_id: i64,
+    window_secs: u64,
+) -> Result<(), sqlx::Error> {
+    notify_batched(conn, user_id, POST_COMMENTS, post_id, ("comment_id", comment_id), false, window_secs)
+        .await
+}
+
+/// Tells the author of `post_id` that `liker_id` liked it, batched the same
+/// way as `notify_comment`. Each liker counts once per batch, so unliking and
+/// liking again adds nothing. Callers should hold the post's row lock.
+pub async fn notify_like(
+    conn: &mut PgConnection,
+    user_id: i64,
+    post_id: i64,
+    liker_id: i64,
+    window_secs: u64,
+) -> Result<(), sqlx::Error> {
+    notify_batched(conn, user_id, POST_LIKES, post_id, ("user_id", liker_id), true, window_secs)
+        .await
+}
+
+/// Counts an event on `post_id` into the user's last unread `kind`
+/// notification about it if younger than `window_secs`, or starts a new one.
+/// `latest` names the newest event's ID in the payload. With `distinct`, the
+/// IDs are also listed under the key's plural, and an ID already listed is
+/// not counted again.
+async fn notify_batched(
+    conn: &mut PgConnection,
+    user_id: i64,
+    kind: &str,
+    post_id: i64,
+    latest: (&str, i64),
+    distinct: bool,
+    window_secs: u64,
+) -> Result<(), sqlx::Error> {
+    let (latest_key, latest_id) = latest;
+    let list_key = format!("{}s", latest_key);
+    let batched = sqlx::query_scalar!(
+        r#"
+        WITH batch AS (
+            SELECT id, payload FROM notifications
+            WHERE user_id = $1 AND kind = $2 AND payload->>'post_id' = $3::BIGINT::TEXT
+              AND read_at IS NULL
+              AND created_at > NOW() - make_interval(secs => $6)
+            ORDER BY created_at DESC
+            LIMIT 1
+        ),
+        counted AS (
+            UPDATE notifications n
+            SET payload = n.payload
+                || jsonb_build_object('count', (n.payload->>'count')::INT + 1, $4::TEXT, $5::BIGINT)
+                || CASE WHEN $7 THEN jsonb_build_object(
+                       $8::TEXT, COALESCE(n.payload->$8, '[]'::jsonb) || to_jsonb($5::BIGINT)
+                   ) ELSE '{}'::jsonb END
+            FROM batch
+            WHERE n.id = batch.id
+              AND NOT ($7 AND COALESCE(batch.payload->$8 @> to_jsonb($5::BIGINT), FALSE))
+        )
+        SELECT EXISTS (SELECT 1 FROM batch) as "batched!"
+        "#,
+        user_id,
+        kind,
+        post_id,
+        latest_key,
+        latest_id,
+        window_secs as f64,
+        distinct,
+        list_key
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if !batched {
+        let mut payload = serde_json::json!({"post_id": post_id, "count": 1});
+        payload[latest_key] = latest_id.into();
+        if distinct {
+            payload[list_key] = serde_json::json!([latest_id]);
+        }
+        notify(&mut *conn, user_id, kind, payload).await?;
+    }
+    Ok(())
+}
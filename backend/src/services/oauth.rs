@@ -0,0 +1,309 @@
+// src/services/oauth.rs
+
+//! Signing in with GitHub, Google or WeChat accounts.
+//!
+//! The web client asks for an authorize URL, sends the user to the provider,
+//! and posts the `code` and `state` it gets back to the callback endpoint. The
+//! state is stored single-use for `OAUTH_STATE_TTL_MINUTES`; when a signed-in
+//! user started the flow, the provider account is linked to them instead of
+//! signing in.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use sqlx::{PgExecutor, PgPool};
+
+use crate::{
+    config::{OAUTH_STATE_TTL_MINUTES, OAuthConfig, OAuthProviderConfig},
+    error::AppError,
+    utils::hash::{new_token, token_hash},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Provider {
+    Github,
+    Google,
+    Wechat,
+}
+
+impl Provider {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "github" => Some(Provider::Github),
+            "google" => Some(Provider::Google),
+            "wechat" => Some(Provider::Wechat),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Provider::Github => "github",
+            Provider::Google => "google",
+            Provider::Wechat => "wechat",
+        }
+    }
+
+    /// The provider's settings, if it is enabled.
+    pub fn config(self, oauth: &OAuthConfig) -> Option<&OAuthProviderConfig> {
+        match self {
+            Provider::Github => oauth.github.as_ref(),
+            Provider::Google => oauth.google.as_ref(),
+            Provider::Wechat => oauth.wechat.as_ref(),
+        }
+    }
+
+    /// The real authorize, token and user info endpoints.
+    fn endpoints(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Provider::Github => (
+                "https://github.com/login/oauth/authorize",
+                "https://github.com/login/oauth/access_token",
+                "https://api.github.com/user",
+            ),
+            Provider::Google => (
+                "https://accounts.google.com/o/oauth2/v2/auth",
+                "https://oauth2.googleapis.com/token",
+                "https://openidconnect.googleapis.com/v1/userinfo",
+            ),
+            Provider::Wechat => (
+                "https://open.weixin.qq.com/connect/qrconnect",
+                "https://api.weixin.qq.com/sns/oauth2/access_token",
+                "https://api.weixin.qq.com/sns/userinfo",
+            ),
+        }
+    }
+}
+
+/// A provider account, as reported by the provider.
+#[derive(Debug)]
+pub struct Identity {
+    /// Stable ID of the account at the provider.
+    pub subject: String,
+    /// Login or display name, to derive a username from.
+    pub name: Option<String>,
+}
+
+/// Starts a sign-in (or, with `link_user_id`, a link) and returns the URL to
+/// send the user to.
+pub async fn begin(
+    pool: &PgPool,
+    provider: Provider,
+    settings: &OAuthProviderConfig,
+    link_user_id: Option<i64>,
+) -> Result<String, AppError> {
+    let state = new_token();
+    sqlx::query!(
+        r#"
+        INSERT INTO oauth_states (state_hash, provider, link_user_id, expires_at)
+        VALUES ($1, $2, $3, CURRENT_TIMESTAMP + make_interval(mins => $4))
+        "#,
+        token_hash(&state),
+        provider.as_str(),
+        link_user_id,
+        OAUTH_STATE_TTL_MINUTES
+    )
+    .execute(pool)
+    .await?;
+
+    let (default_url, _, _) = provider.endpoints();
+    let base = settings.authorize_url.as_deref().unwrap_or(default_url);
+    let mut params = vec![
+        ("redirect_uri", settings.redirect_uri.as_str()),
+        ("response_type", "code"),
+        ("state", state.as_str()),
+    ];
+    match provider {
+        Provider::Github => {
+            params.extend([("client_id", settings.client_id.as_str()), ("scope", "read:user")])
+        }
+        Provider::Google => params.extend([
+            ("client_id", settings.client_id.as_str()),
+            ("scope", "openid profile"),
+        ]),
+        Provider::Wechat => {
+            params.extend([("appid", settings.client_id.as_str()), ("scope", "snsapi_login")])
+        }
+    }
+    let mut url = url::Url::parse_with_params(base, &params)
+        .map_err(|e| AppError::InternalServerError(format!("Bad OAuth authorize URL: {}", e)))?;
+    if provider == Provider::Wechat {
+        url.set_fragment(Some("wechat_redirect"));
+    }
+
+    Ok(url.into())
+}
+
+/// Uses up `state` and trades `code` for the provider account. Returns the
+/// user who started a link, if any, and the account.
+pub async fn complete(
+    pool: &PgPool,
+    provider: Provider,
+    settings: &OAuthProviderConfig,
+    code: &str,
+    state: &str,
+) -> Result<(Option<i64>, Identity), AppError> {
+    let pending = sqlx::query!(
+        r#"
+        DELETE FROM oauth_states
+        WHERE state_hash = $1 AND provider = $2 AND expires_at > CURRENT_TIMESTAMP
+        RETURNING link_user_id
+        "#,
+        token_hash(state),
+        provider.as_str()
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::BadRequest("Invalid or expired OAuth state".to_string()))?;
+
+    let identity = fetch_identity(provider, settings, code).await.map_err(|e| {
+        tracing::warn!("OAuth sign-in with {} failed: {}", provider.as_str(), e);
+        AppError::AuthError(format!("Could not sign in with {}", provider.as_str()))
+    })?;
+
+    Ok((pending.link_user_id, identity))
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    /// WeChat only.
+    openid: Option<String>,
+    error: Option<String>,
+    errmsg: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UserInfo {
+    /// GitHub
+    id: Option<i64>,
+    login: Option<String>,
+    /// Google
+    sub: Option<String>,
+    name: Option<String>,
+    /// WeChat
+    unionid: Option<String>,
+    nickname: Option<String>,
+}
+
+async fn fetch_identity(
+    provider: Provider,
+    settings: &OAuthProviderConfig,
+    code: &str,
+) -> Result<Identity, Box<dyn std::error::Error + Send + Sync>> {
+    let (_, default_token_url, default_userinfo_url) = provider.endpoints();
+    let token_url = settings.token_url.as_deref().unwrap_or(default_token_url);
+    let userinfo_url = settings.userinfo_url.as_deref().unwrap_or(default_userinfo_url);
+    // The endpoints come from configuration, not users, like the mailer's.
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .user_agent("ancient-arch")
+        .build()?;
+
+    let token: TokenResponse = match provider {
+        Provider::Github | Provider::Google => {
+            client
+                .post(token_url)
+                .header(reqwest::header::ACCEPT, "application/json")
+                .form(&[
+                    ("client_id", settings.client_id.as_str()),
+                    ("client_secret", settings.client_secret.as_str()),
+                    ("code", code),
+                    ("redirect_uri", settings.redirect_uri.as_str()),
+                    ("grant_type", "authorization_code"),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?
+        }
+        Provider::Wechat => {
+            client
+                .get(token_url)
+                .query(&[
+                    ("appid", settings.client_id.as_str()),
+                    ("secret", settings.client_secret.as_str()),
+                    ("code", code),
+                    ("grant_type", "authorization_code"),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?
+        }
+    };
+    // Providers report a bad code with a 200 and an error field.
+    let Some(access_token) = token.access_token else {
+        let reason = token.error.or(token.errmsg).unwrap_or_default();
+        return Err(format!("token exchange rejected: {}", reason).into());
+    };
+
+    let info: UserInfo = match provider {
+        Provider::Github | Provider::Google => client.get(userinfo_url).bearer_auth(&access_token),
+        Provider::Wechat => client.get(userinfo_url).query(&[
+            ("access_token", access_token.as_str()),
+            ("openid", token.openid.as_deref().unwrap_or_default()),
+        ]),
+    }
+    .send()
+    .await?
+    .error_for_status()?
+    .json()
+    .await?;
+
+    let (subject, name) = match provider {
+        Provider::Github => (info.id.map(|id| id.to_string()), info.login),
+        Provider::Google => (info.sub, info.name),
+        Provider::Wechat => (info.unionid.or(token.openid), info.nickname),
+    };
+    let subject = subject.ok_or("no account ID in user info")?;
+    Ok(Identity { subject, name })
+}
+
+/// The user a provider account signs in as, if it is linked.
+pub async fn find_user(
+    pool: &PgPool,
+    provider: Provider,
+    subject: &str,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT user_id FROM oauth_identities WHERE provider = $1 AND subject = $2",
+        provider.as_str(),
+        subject
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Links a provider account to `user_id`. Fails with 409 if the account is
+/// linked to someone else, or the user already has another account there.
+pub async fn link<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+    provider: Provider,
+    subject: &str,
+) -> Result<(), AppError> {
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO oauth_identities (provider, subject, user_id)
+        VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+        "#,
+        provider.as_str(),
+        subject,
+        user_id
+    )
+    .execute(executor)
+    .await?
+    .rows_affected();
+
+    if inserted == 0 {
+        return Err(AppError::Conflict(format!(
+            "This {} account or user is already linked",
+            provider.as_str()
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,189 @@
+// src/services/paper_snapshots.rs
+
+//! Frozen copies of the questions on a paper.
+//!
+//! Practice papers and qualification exams are snapshotted when they are
+//! served, and competitions when they are created; each is scored against
+//! that snapshot, so editing or deleting a question later changes neither the
+//! scores nor what the paper showed. Snapshots keep the source text;
+//! translations are applied when serving.
+//!
+//! A practice paper is submitted with a signed token naming its snapshot.
+//! Snapshots of papers never submitted are purged once their token expires.
+//! Papers that shuffle options keep the order shown with the snapshot, as
+//! anything in a token can be read by the examinee.
+
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgExecutor, types::Json};
+
+use crate::{config::PRACTICE_PAPER_TTL_SECS, models::question::Question};
+
+/// Claims of a practice paper token.
+#[derive(Debug, Serialize, Deserialize)]
+struct PaperClaims {
+    /// The snapshot the paper was served from.
+    sid: i64,
+    exp: usize,
+}
+
+/// Signs a token for submitting the practice paper frozen in `snapshot_id`.
+pub fn sign(snapshot_id: i64, secret: &str) -> Result<String, jsonwebtoken::errors::Error> {
+    let claims = PaperClaims {
+        sid: snapshot_id,
+        exp: chrono::Utc::now().timestamp() as usize + PRACTICE_PAPER_TTL_SECS as usize,
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+}
+
+/// The snapshot a practice paper token was signed for, or None if it is
+/// invalid or expired.
+pub fn verify(token: &str, secret: &str) -> Option<i64> {
+    decode::<PaperClaims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &Validation::default(),
+    )
+    .ok()
+    .map(|data| data.claims.sid)
+}
+
+/// Snapshots the questions in the order given, leaving out ids that do not
+/// exist. Returns the snapshot id and the questions as frozen.
+pub async fn take(
+    conn: &mut PgConnection,
+    question_ids: &[i64],
+) -> Result<(i64, Vec<Question>), sqlx::Error> {
+    let questions = sqlx::query_as!(
+        Question,
+        r#"
+        SELECT
+            q.id, q.type as "question_type", q.content,
+            q.options as "options: Json<Vec<String>>",
+            q.option_images as "option_images: Json<Vec<Option<String>>>",
+            q.answer, q.analysis, q.created_at, q.contribution_id, q.created_by
+        FROM UNNEST($1::BIGINT[]) WITH ORDINALITY AS p(id, position)
+        JOIN questions q ON q.id = p.id
+        ORDER BY p.position
+        "#,
+        question_ids
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let id = sqlx::query_scalar!(
+        "INSERT INTO paper_snapshots (questions) VALUES ($1) RETURNING id",
+        Json(&questions) as _
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok((id, questions))
+}
+
+/// The questions of a snapshot, as frozen.
+pub async fn load<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: i64,
+) -> Result<Vec<Question>, sqlx::Error> {
+    let questions = sqlx::query_scalar!(
+        r#"SELECT questions as "questions: Json<Vec<Question>>" FROM paper_snapshots WHERE id = $1"#,
+        id
+    )
+    .fetch_one(executor)
+    .await?;
+    Ok(questions.0)
+}
+
+/// Records the option order each question was shown in, by question id,
+/// where `order[shown] = original` option index.
+pub async fn set_option_orders<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: i64,
+    orders: &HashMap<i64, Vec<u8>>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE paper_snapshots SET option_orders = $2 WHERE id = $1",
+        id,
+        Json(orders) as _
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// The option orders recorded for a snapshot; empty when options were shown
+/// as stored.
+pub async fn option_orders<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: i64,
+) -> Result<HashMap<i64, Vec<u8>>, sqlx::Error> {
+    let orders = sqlx::query_scalar!(
+        r#"SELECT option_orders as "option_orders: Json<HashMap<i64, Vec<u8>>>" FROM paper_snapshots WHERE id = $1"#,
+        id
+    )
+    .fetch_one(executor)
+    .await?;
+    Ok(orders.map(|o| o.0).unwrap_or_default())
+}
+
+/// Swaps in the `locale` translation of each question where there is one.
+/// Translated options are used only while they still line up with the
+/// frozen ones.
+pub async fn translate<'e, E: PgExecutor<'e>>(
+    executor: E,
+    questions: &mut [Question],
+    locale: &str,
+) -> Result<(), sqlx::Error> {
+    let ids: Vec<i64> = questions.iter().map(|q| q.id).collect();
+    let translations = sqlx::query!(
+        r#"
+        SELECT question_id, content, options as "options: Json<Vec<String>>", analysis
+        FROM question_translations
+        WHERE locale = $1 AND question_id = ANY($2)
+        "#,
+        locale,
+        &ids
+    )
+    .fetch_all(executor)
+    .await?;
+
+    for t in translations {
+        let Some(q) = questions.iter_mut().find(|q| q.id == t.question_id) else {
+            continue;
+        };
+        if let Some(content) = t.content {
+            q.content = content;
+        }
+        if let Some(options) = t.options.filter(|o| o.len() == q.options.len()) {
+            q.options = options;
+        }
+        if t.analysis.is_some() {
+            q.analysis = t.analysis;
+        }
+    }
+    Ok(())
+}
+
+/// Deletes snapshots older than any paper token that nothing refers to:
+/// papers served but never submitted, or exams that earned no certificate.
+pub async fn purge<'e, E: PgExecutor<'e>>(executor: E) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM paper_snapshots s
+        WHERE s.created_at < CURRENT_TIMESTAMP - make_interval(secs => $1)
+          AND NOT EXISTS (SELECT 1 FROM quiz_attempts a WHERE a.paper_snapshot_id = s.id)
+          AND NOT EXISTS (SELECT 1 FROM competitions c WHERE c.paper_snapshot_id = s.id)
+          AND NOT EXISTS (SELECT 1 FROM certificates c WHERE c.paper_snapshot_id = s.id)
+        "#,
+        PRACTICE_PAPER_TTL_SECS as f64
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected())
+}
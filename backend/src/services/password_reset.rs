@@ -0,0 +1,123 @@
+// src/services/password_reset.rs
+
+//! Recovering an account through its verified email address.
+//!
+//! `POST /api/auth/forgot-password` emails a single-use token that expires
+//! after `PASSWORD_RESET_TTL_MINUTES`; `POST /api/auth/reset-password` trades
+//! it for a new password. Only the newest token of a user is valid, and a
+//! reset signs the account out everywhere.
+
+use sqlx::PgPool;
+
+use crate::{
+    config::{MailConfig, PASSWORD_RESET_MIN_INTERVAL_SECS, PASSWORD_RESET_TTL_MINUTES},
+    error::AppError,
+    services::sessions,
+    utils::{
+        hash::{hash_password, new_token, token_hash},
+        mailer::{self, Email},
+    },
+};
+
+/// Emails a reset link if `email` is the verified address of an account.
+/// Returns the account it was sent to, if any; never tell the client.
+pub async fn request(
+    pool: &PgPool,
+    mail: &MailConfig,
+    email: &str,
+) -> Result<Option<i64>, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let Some(user) = sqlx::query!(
+        r#"
+        SELECT id, email as "email!" FROM users
+        WHERE lower(email) = lower($1) AND email_verified_at IS NOT NULL
+        FOR UPDATE
+        "#,
+        email
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    // Repeated requests within the interval do not send more mail.
+    let recent = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM password_resets
+            WHERE user_id = $1 AND created_at > CURRENT_TIMESTAMP - make_interval(secs => $2)
+        ) as "recent!"
+        "#,
+        user.id,
+        PASSWORD_RESET_MIN_INTERVAL_SECS as f64
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if recent {
+        return Ok(None);
+    }
+
+    sqlx::query!("DELETE FROM password_resets WHERE user_id = $1", user.id)
+        .execute(&mut *tx)
+        .await?;
+    let token = new_token();
+    sqlx::query!(
+        r#"
+        INSERT INTO password_resets (token_hash, user_id, expires_at)
+        VALUES ($1, $2, CURRENT_TIMESTAMP + make_interval(mins => $3))
+        "#,
+        token_hash(&token),
+        user.id,
+        PASSWORD_RESET_TTL_MINUTES
+    )
+    .execute(&mut *tx)
+    .await?;
+    mailer::queue(&mut *tx, &message(mail, &user.email, &token)).await?;
+    tx.commit().await?;
+    Ok(Some(user.id))
+}
+
+fn message(mail: &MailConfig, to: &str, token: &str) -> Email {
+    let mut body = String::from("Someone asked to reset the password of your Ancient Arch account.\n\n");
+    if let Some(url) = &mail.password_reset_url {
+        body.push_str(&format!("Open this link to choose a new one: {}?token={}\n\n", url, token));
+    }
+    body.push_str(&format!(
+        "Reset code: {}\n\nThe code expires in {} minutes. If you did not ask for this, ignore this email; your password is unchanged.\n",
+        token, PASSWORD_RESET_TTL_MINUTES
+    ));
+
+    Email {
+        to: to.to_string(),
+        subject: "Reset your password".to_string(),
+        body,
+    }
+}
+
+/// Uses up `token`, sets the user's password and revokes every session the
+/// user had. Returns the user ID.
+pub async fn reset(pool: &PgPool, token: &str, password: &str) -> Result<i64, AppError> {
+    let hashed = hash_password(password)?;
+    let mut tx = pool.begin().await?;
+
+    let user_id = sqlx::query_scalar!(
+        r#"
+        DELETE FROM password_resets
+        WHERE token_hash = $1 AND expires_at > CURRENT_TIMESTAMP
+        RETURNING user_id
+        "#,
+        token_hash(token)
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::BadRequest("Invalid or expired reset token".to_string()))?;
+
+    sqlx::query!("UPDATE users SET password = $1 WHERE id = $2", hashed, user_id)
+        .execute(&mut *tx)
+        .await?;
+    sessions::revoke_all(&mut *tx, user_id).await?;
+    tx.commit().await?;
+    Ok(user_id)
+}
@@ -0,0 +1,175 @@
+// src/services/question_duplicates.rs
+
+//! Finding and merging near-identical questions.
+//!
+//! Contributors often resubmit a question already in the bank with small
+//! wording changes. Pairs are found by trigram similarity of the content and
+//! grouped transitively: if A looks like B and B like C, all three form one cluster.
+
+use std::collections::HashMap;
+
+use sqlx::{PgConnection, PgPool};
+
+use crate::models::question::{DuplicateCluster, DuplicateQuestion};
+
+/// Clusters of questions whose content is at least `threshold` similar (0-1),
+/// most similar first.
+pub async fn find_clusters(
+    pool: &PgPool,
+    threshold: f32,
+) -> Result<Vec<DuplicateCluster>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // `%` can use the trigram index; it compares against this setting.
+    sqlx::query!(
+        "SELECT set_config('pg_trgm.similarity_threshold', $1, true)",
+        threshold.to_string()
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let pairs = sqlx::query!(
+        r#"
+        SELECT a.id as "a!", b.id as "b!", similarity(a.content, b.content) as "similarity!"
+        FROM questions a
+        JOIN questions b ON a.content % b.content AND a.id < b.id
+        "#
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    let mut parent: HashMap<i64, i64> = HashMap::new();
+    for pair in &pairs {
+        let (a, b) = (find(&mut parent, pair.a), find(&mut parent, pair.b));
+        if a != b {
+            parent.insert(a.max(b), a.min(b));
+        }
+    }
+
+    let mut similarity: HashMap<i64, f32> = HashMap::new();
+    for pair in &pairs {
+        let root = find(&mut parent, pair.a);
+        let best = similarity.entry(root).or_default();
+        *best = best.max(pair.similarity);
+    }
+
+    let ids: Vec<i64> = parent.keys().copied().collect();
+    let questions = sqlx::query_as!(
+        DuplicateQuestion,
+        r#"
+        SELECT id, type as question_type, content, answer, created_at,
+               contribution_id, created_by, exposure_count
+        FROM questions
+        WHERE id = ANY($1)
+        ORDER BY created_at, id
+        "#,
+        &ids
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut members: HashMap<i64, Vec<DuplicateQuestion>> = HashMap::new();
+    for question in questions {
+        let root = find(&mut parent, question.id);
+        members.entry(root).or_default().push(question);
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = members
+        .into_iter()
+        // A question deleted since the pairs were read can leave a cluster of one.
+        .filter(|(_, questions)| questions.len() > 1)
+        .map(|(root, questions)| DuplicateCluster {
+            similarity: similarity.get(&root).copied().unwrap_or_default(),
+            questions,
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then_with(|| a.questions[0].id.cmp(&b.questions[0].id))
+    });
+
+    Ok(clusters)
+}
+
+/// The representative of `id`'s cluster (its lowest ID), compressing the path on the way.
+fn find(parent: &mut HashMap<i64, i64>, id: i64) -> i64 {
+    let mut root = *parent.entry(id).or_insert(id);
+    while let Some(&next) = parent.get(&root)
+        && next != root
+    {
+        root = next;
+    }
+    parent.insert(id, root);
+    root
+}
+
+/// Folds `duplicate_ids` into `keep_id` and deletes them.
+///
+/// Flags and practice answers move to the kept question unless it already has
+/// an equivalent one (the same user's open flag, or an answer in the same
+/// attempt); those are dropped with the duplicate. Exposure counts are added up.
+pub async fn merge(
+    conn: &mut PgConnection,
+    keep_id: i64,
+    duplicate_ids: &[i64],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE question_flags f SET question_id = $1
+        WHERE f.question_id = ANY($2)
+          AND (f.status <> 'open' OR (
+              NOT EXISTS (
+                  SELECT 1 FROM question_flags k
+                  WHERE k.question_id = $1 AND k.user_id = f.user_id AND k.status = 'open'
+              )
+              AND f.id = (
+                  SELECT MIN(g.id) FROM question_flags g
+                  WHERE g.question_id = ANY($2) AND g.user_id = f.user_id AND g.status = 'open'
+              )
+          ))
+        "#,
+        keep_id,
+        duplicate_ids
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE quiz_answers a SET question_id = $1
+        WHERE a.question_id = ANY($2)
+          AND NOT EXISTS (
+              SELECT 1 FROM quiz_answers k WHERE k.attempt_id = a.attempt_id AND k.question_id = $1
+          )
+          AND a.question_id = (
+              SELECT MIN(g.question_id) FROM quiz_answers g
+              WHERE g.attempt_id = a.attempt_id AND g.question_id = ANY($2)
+          )
+        "#,
+        keep_id,
+        duplicate_ids
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE questions SET exposure_count = exposure_count + (
+            SELECT COALESCE(SUM(exposure_count), 0) FROM questions WHERE id = ANY($2)
+        )::BIGINT
+        WHERE id = $1
+        "#,
+        keep_id,
+        duplicate_ids
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!("DELETE FROM questions WHERE id = ANY($1)", duplicate_ids)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+// src/services/question_import.rs
+
+//! Loading questions into the bank from a JSON file (`backend import-questions <file>`).
+//!
+//! The file is a JSON array. Each entry is either a question in the shape of
+//! `POST /api/admin/questions`, or a question contribution as submitted to
+//! `POST /api/contributions` (`{"type": "question", "data": {...}}`), so
+//! exported contributions can be loaded as they are.
+
+use serde::Deserialize;
+use sqlx::PgPool;
+use validator::Validate;
+
+use crate::{
+    error::AppError, handlers::qualification::normalize_answer,
+    models::question::CreateQuestionRequest, utils::html::clean_html,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ImportEntry {
+    Question(CreateQuestionRequest),
+    Contribution {
+        r#type: String,
+        data: serde_json::Value,
+    },
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Questions already in the bank with the same type and content.
+    pub duplicates: usize,
+    /// Contributions of other types (e.g. architectures).
+    pub skipped: usize,
+}
+
+/// Validates every entry, then inserts the new questions in one transaction.
+/// Nothing is imported if any entry is invalid, and re-running an import adds nothing.
+/// Answer keys are stored as sorted option letters, so "A, C" becomes "AC".
+pub async fn import_questions(pool: &PgPool, json: &str) -> Result<ImportSummary, AppError> {
+    let entries: Vec<ImportEntry> = serde_json::from_str(json)
+        .map_err(|e| AppError::BadRequest(format!("Invalid import file: {}", e)))?;
+
+    let mut summary = ImportSummary::default();
+    let mut questions = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        let invalid = |msg: String| AppError::BadRequest(format!("Entry {}: {}", index + 1, msg));
+        let question = match entry {
+            ImportEntry::Question(q) => q,
+            ImportEntry::Contribution { r#type, data } if r#type == "question" => {
+                serde_json::from_value(data).map_err(|e| invalid(e.to_string()))?
+            }
+            ImportEntry::Contribution { .. } => {
+                summary.skipped += 1;
+                continue;
+            }
+        };
+        question.validate().map_err(|e| invalid(e.to_string()))?;
+        questions.push(question);
+    }
+
+    let mut tx = pool.begin().await?;
+    for q in questions {
+        let options = serde_json::to_value(&q.options).unwrap_or_default();
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO questions (type, content, options, answer, analysis)
+            SELECT $1, $2, $3, $4, $5
+            WHERE NOT EXISTS (SELECT 1 FROM questions WHERE type = $1 AND content = $2)
+            "#,
+            q.question_type,
+            clean_html(&q.content),
+            options,
+            normalize_answer(&q.answer, None),
+            q.analysis.as_deref().map(clean_html)
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if inserted == 0 {
+            summary.duplicates += 1;
+        } else {
+            summary.imported += 1;
+        }
+    }
+    tx.commit().await?;
+
+    Ok(summary)
+}
@@ -0,0 +1,68 @@
+// src/services/question_selection.rs
+
+use sqlx::PgPool;
+
+use crate::{config::QUESTION_CANDIDATE_FACTOR, models::question::Question};
+
+/// Picks `count` questions for a paper, optionally restricted to one type.
+///
+/// Instead of `ORDER BY RANDOM()` over the whole bank, this reads a window of
+/// candidates around a random pivot on the indexed `random_key`, then serves the
+/// least-exposed ones. The chosen rows get their `exposure_count` bumped in the
+/// same statement. Questions suspended by user flags are skipped.
+///
+/// Text is served in `locale` where translated, field by field; translated
+/// options that no longer match the source's count are ignored.
+pub async fn pick_questions(
+    pool: &PgPool,
+    question_type: Option<&str>,
+    count: i64,
+    locale: &str,
+) -> Result<Vec<Question>, sqlx::Error> {
+    let window = count * QUESTION_CANDIDATE_FACTOR;
+
+    sqlx::query_as!(
+        Question,
+        r#"
+        WITH pivot AS (SELECT random() AS r),
+        candidates AS (
+            (SELECT q.id FROM questions q, pivot
+             WHERE ($1::TEXT IS NULL OR q.type = $1) AND q.suspended_at IS NULL
+               AND q.random_key >= pivot.r
+             ORDER BY q.random_key
+             LIMIT $2)
+            UNION
+            (SELECT q.id FROM questions q, pivot
+             WHERE ($1::TEXT IS NULL OR q.type = $1) AND q.suspended_at IS NULL
+               AND q.random_key < pivot.r
+             ORDER BY q.random_key DESC
+             LIMIT $2)
+        ),
+        picked AS (
+            SELECT q.id FROM questions q
+            JOIN candidates c ON c.id = q.id
+            ORDER BY q.exposure_count, random()
+            LIMIT $3
+        )
+        UPDATE questions q
+        SET exposure_count = q.exposure_count + 1
+        FROM picked
+        LEFT JOIN question_translations t ON t.question_id = picked.id AND t.locale = $4
+        WHERE q.id = picked.id
+        RETURNING
+            q.id, q.type as "question_type",
+            COALESCE(t.content, q.content) as "content!",
+            (CASE WHEN jsonb_array_length(t.options) = jsonb_array_length(q.options)
+                  THEN t.options ELSE q.options END) as "options!: sqlx::types::Json<Vec<String>>",
+            q.option_images as "option_images: sqlx::types::Json<Vec<Option<String>>>",
+            q.answer, COALESCE(t.analysis, q.analysis) as analysis,
+            q.created_at, q.contribution_id, q.created_by
+        "#,
+        question_type,
+        window,
+        count,
+        locale
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,114 @@
+// src/services/quotas.rs
+
+//! Per-user limits on posts, embedded images and favorites, set by the
+//! `QUOTA_*` variables. Admins are exempt.
+//!
+//! Checks lock the user's row, so concurrent requests of one user cannot both
+//! slip under a limit. Run them in the transaction that adds the content.
+
+use sqlx::PgConnection;
+
+use crate::{
+    config::QuotaConfig,
+    error::AppError,
+    models::quota::{QuotaStatus, QuotaUsage},
+};
+
+/// Current usage of every quota of the user.
+pub async fn status(
+    conn: &mut PgConnection,
+    quotas: &QuotaConfig,
+    user_id: i64,
+) -> Result<QuotaStatus, sqlx::Error> {
+    let usage = sqlx::query!(
+        r#"
+        SELECT
+            (u.role = 'admin') as "exempt!",
+            (SELECT COUNT(*) FROM posts
+             WHERE user_id = u.id AND created_at > CURRENT_TIMESTAMP - INTERVAL '1 day') as "posts!",
+            (SELECT COALESCE(SUM(image_count), 0) FROM posts
+             WHERE user_id = u.id AND deleted_at IS NULL) as "images!",
+            (SELECT COUNT(*) FROM post_favorites WHERE user_id = u.id) as "favorites!"
+        FROM users u
+        WHERE u.id = $1
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let limit = |limit: Option<u32>| limit.filter(|_| !usage.exempt).map(i64::from);
+    Ok(QuotaStatus {
+        posts_per_day: QuotaUsage { used: usage.posts, limit: limit(quotas.posts_per_day) },
+        images: QuotaUsage { used: usage.images, limit: limit(quotas.images_per_user) },
+        favorites: QuotaUsage { used: usage.favorites, limit: limit(quotas.favorites_per_user) },
+    })
+}
+
+/// Fails if a new post with `images` embedded images would exceed a quota.
+pub async fn check_post(
+    conn: &mut PgConnection,
+    quotas: &QuotaConfig,
+    user_id: i64,
+    images: i64,
+) -> Result<(), AppError> {
+    lock_user(conn, user_id).await?;
+    let status = status(conn, quotas, user_id).await?;
+    if status.posts_per_day.remaining() == Some(0) {
+        return Err(exceeded("Daily post limit reached", &status.posts_per_day));
+    }
+    if let Some(remaining) = status.images.remaining()
+        && images > remaining
+    {
+        return Err(exceeded("Image limit reached", &status.images));
+    }
+    Ok(())
+}
+
+/// Fails if `images` more embedded images, added by editing a post, would
+/// exceed the image quota.
+pub async fn check_images(
+    conn: &mut PgConnection,
+    quotas: &QuotaConfig,
+    user_id: i64,
+    images: i64,
+) -> Result<(), AppError> {
+    lock_user(conn, user_id).await?;
+    let status = status(conn, quotas, user_id).await?;
+    if let Some(remaining) = status.images.remaining()
+        && images > remaining
+    {
+        return Err(exceeded("Image limit reached", &status.images));
+    }
+    Ok(())
+}
+
+/// Fails if the user cannot favorite another post.
+pub async fn check_favorite(
+    conn: &mut PgConnection,
+    quotas: &QuotaConfig,
+    user_id: i64,
+) -> Result<(), AppError> {
+    lock_user(conn, user_id).await?;
+    let status = status(conn, quotas, user_id).await?;
+    if status.favorites.remaining() == Some(0) {
+        return Err(exceeded("Favorite limit reached", &status.favorites));
+    }
+    Ok(())
+}
+
+async fn lock_user(conn: &mut PgConnection, user_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", user_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+fn exceeded(what: &str, usage: &QuotaUsage) -> AppError {
+    AppError::QuotaExceeded(format!(
+        "{} ({} of {})",
+        what,
+        usage.used,
+        usage.limit.unwrap_or_default()
+    ))
+}
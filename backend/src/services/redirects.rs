@@ -0,0 +1,68 @@
+// src/services/redirects.rs
+
+//! Where architectures went after being merged into others, so lookups by an
+//! old ID or slug can send clients to the architecture that replaced it.
+
+use sqlx::{PgConnection, PgPool};
+
+/// The architecture that replaced `id`, if it was merged away.
+pub async fn resolve_architecture(pool: &PgPool, id: i64) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT to_id FROM architecture_redirects WHERE from_id = $1",
+        id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// The architecture that replaced the one with `slug`, if it was merged away.
+pub async fn resolve_architecture_slug(
+    pool: &PgPool,
+    slug: &str,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT to_id FROM architecture_redirects WHERE from_slug = $1",
+        slug
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Sends lookups of `from_id` (and its slug), and of everything merged into
+/// it before, to `to_id`. Run in the transaction that deletes `from_id`.
+pub async fn add_architecture(
+    conn: &mut PgConnection,
+    from_id: i64,
+    from_slug: Option<&str>,
+    to_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE architecture_redirects SET to_id = $2 WHERE to_id = $1",
+        from_id,
+        to_id
+    )
+    .execute(&mut *conn)
+    .await?;
+    // A slug freed by an earlier merge and given out again now means this one
+    if let Some(slug) = from_slug {
+        sqlx::query!(
+            "UPDATE architecture_redirects SET from_slug = NULL WHERE from_slug = $1",
+            slug
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query!(
+        r#"
+        INSERT INTO architecture_redirects (from_id, from_slug, to_id) VALUES ($1, $2, $3)
+        ON CONFLICT (from_id) DO UPDATE
+            SET from_slug = EXCLUDED.from_slug, to_id = EXCLUDED.to_id, created_at = NOW()
+        "#,
+        from_id,
+        from_slug,
+        to_id
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,132 @@
+// src/services/references.rs
+
+//! Cross-references from posts to architectures and glossary terms.
+//!
+//! Authors write `[[arch:123]]` or `[[term:dougong]]` in a post. References are
+//! resolved when the post is created or edited; unknown ids and slugs are ignored. Post
+//! detail lists the linked entities, and architecture pages count the posts that
+//! mention them.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use sqlx::{PgConnection, PgPool};
+
+use crate::models::post::EntityLink;
+
+static REFERENCE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\[(arch|term):([A-Za-z0-9_-]{1,100})\]\]").unwrap());
+
+/// References found in post content, deduplicated.
+#[derive(Debug, Default, PartialEq)]
+pub struct References {
+    pub architecture_ids: Vec<i64>,
+    pub term_slugs: Vec<String>,
+}
+
+/// Finds the `[[arch:ID]]` and `[[term:SLUG]]` markers in post content, in order of appearance.
+pub fn extract_references(content: &str) -> References {
+    let mut refs = References::default();
+    for caps in REFERENCE_RE.captures_iter(content) {
+        let target = &caps[2];
+        match &caps[1] {
+            "arch" => {
+                if let Ok(id) = target.parse::<i64>()
+                    && !refs.architecture_ids.contains(&id)
+                {
+                    refs.architecture_ids.push(id);
+                }
+            }
+            _ => {
+                let slug = target.to_lowercase();
+                if !refs.term_slugs.contains(&slug) {
+                    refs.term_slugs.push(slug);
+                }
+            }
+        }
+    }
+    refs
+}
+
+/// Stores the references that resolve to existing entities.
+pub async fn store(
+    conn: &mut PgConnection,
+    post_id: i64,
+    refs: &References,
+) -> Result<(), sqlx::Error> {
+    if !refs.architecture_ids.is_empty() {
+        sqlx::query!(
+            r#"
+            INSERT INTO post_references (post_id, entity_type, entity_id)
+            SELECT $1, 'architecture', id FROM architectures WHERE id = ANY($2)
+            ON CONFLICT DO NOTHING
+            "#,
+            post_id,
+            &refs.architecture_ids
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    if !refs.term_slugs.is_empty() {
+        sqlx::query!(
+            r#"
+            INSERT INTO post_references (post_id, entity_type, entity_id)
+            SELECT $1, 'term', id FROM glossary_terms WHERE slug = ANY($2)
+            ON CONFLICT DO NOTHING
+            "#,
+            post_id,
+            &refs.term_slugs
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Replaces the stored references of an edited post.
+pub async fn replace(
+    conn: &mut PgConnection,
+    post_id: i64,
+    refs: &References,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM post_references WHERE post_id = $1", post_id)
+        .execute(&mut *conn)
+        .await?;
+    store(conn, post_id, refs).await
+}
+
+/// The entities a post links to. References to since-deleted entities are dropped.
+pub async fn links_for_post(pool: &PgPool, post_id: i64) -> Result<Vec<EntityLink>, sqlx::Error> {
+    sqlx::query_as!(
+        EntityLink,
+        r#"
+        SELECT r.entity_type as kind, r.entity_id as id, g.slug as "slug?",
+               COALESCE(a.name, g.term) as "title!"
+        FROM post_references r
+        LEFT JOIN architectures a ON r.entity_type = 'architecture' AND a.id = r.entity_id
+        LEFT JOIN glossary_terms g ON r.entity_type = 'term' AND g.id = r.entity_id
+        WHERE r.post_id = $1 AND (a.id IS NOT NULL OR g.id IS NOT NULL)
+        ORDER BY r.entity_type, r.entity_id
+        "#,
+        post_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// How many visible posts mention an architecture.
+pub async fn architecture_mentions(pool: &PgPool, architecture_id: i64) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM post_references r
+        JOIN posts p ON p.id = r.post_id AND p.deleted_at IS NULL
+        WHERE r.entity_type = 'architecture' AND r.entity_id = $1
+        "#,
+        architecture_id
+    )
+    .fetch_one(pool)
+    .await
+}
@@ -0,0 +1,97 @@
+// src/services/security_events.rs
+
+use sqlx::PgPool;
+
+use crate::{
+    config::SESSION_USER_AGENT_MAX_CHARS,
+    models::security_event::SecurityEvent,
+    services::sessions::device_label,
+};
+
+/// A login attempt with an unknown username or a wrong password.
+pub const LOGIN_FAILED: &str = "login_failed";
+/// A request rejected by the rate limiter.
+pub const RATE_LIMITED: &str = "rate_limited";
+/// A user reported a post or comment.
+pub const CONTENT_REPORTED: &str = "content_reported";
+/// A registration or post that failed the bot checks in `services::spam`.
+pub const SPAM_SUSPECTED: &str = "spam_suspected";
+/// A successful sign-in, with a password or an OAuth provider.
+pub const LOGIN: &str = "login";
+/// An account was created.
+pub const REGISTERED: &str = "registered";
+/// The password was changed, e.g. through a reset link.
+pub const PASSWORD_CHANGED: &str = "password_changed";
+
+/// Kinds counted by the admin abuse dashboard.
+pub const ABUSE_KINDS: &[&str] = &[LOGIN_FAILED, RATE_LIMITED, CONTENT_REPORTED, SPAM_SUSPECTED];
+/// Kinds users see about their own account.
+pub const ACCOUNT_KINDS: &[&str] = &[LOGIN, LOGIN_FAILED, REGISTERED, PASSWORD_CHANGED];
+
+/// Appends an event to the security log.
+///
+/// Recording is best effort: a failure is logged and never fails the request
+/// that triggered it.
+pub async fn record(
+    pool: &PgPool,
+    kind: &str,
+    user_id: Option<i64>,
+    ip: Option<&str>,
+    user_agent: Option<&str>,
+    path: &str,
+) {
+    let user_agent: Option<String> =
+        user_agent.map(|ua| ua.chars().take(SESSION_USER_AGENT_MAX_CHARS).collect());
+    let result = sqlx::query!(
+        "INSERT INTO security_events (kind, user_id, ip, user_agent, path) VALUES ($1, $2, $3, $4, $5)",
+        kind,
+        user_id,
+        ip,
+        user_agent,
+        path
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        tracing::warn!("Failed to record security event '{}': {:?}", kind, e);
+    }
+}
+
+/// A user's account events, newest first, before `cursor` if given.
+pub async fn list_for_user(
+    pool: &PgPool,
+    user_id: i64,
+    cursor: Option<chrono::DateTime<chrono::Utc>>,
+    limit: i64,
+) -> Result<Vec<SecurityEvent>, sqlx::Error> {
+    let kinds: Vec<String> = ACCOUNT_KINDS.iter().map(|k| k.to_string()).collect();
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, kind, ip, user_agent, created_at
+        FROM security_events
+        WHERE user_id = $1 AND kind = ANY($2)
+          AND ($3::TIMESTAMPTZ IS NULL OR created_at < $3)
+        ORDER BY created_at DESC, id DESC
+        LIMIT $4
+        "#,
+        user_id,
+        &kinds,
+        cursor,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| SecurityEvent {
+            id: row.id,
+            kind: row.kind,
+            device: device_label(row.user_agent.as_deref()),
+            ip: row.ip,
+            user_agent: row.user_agent,
+            created_at: row.created_at,
+        })
+        .collect())
+}
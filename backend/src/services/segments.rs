@@ -0,0 +1,136 @@
+// src/services/segments.rs
+
+//! Groups of users picked by a filter, for admins to preview, export and
+//! send broadcasts to.
+//!
+//! Every condition of a [`UserFilter`] is optional and all given ones must
+//! hold. Matching is done by the `segment_user_ids` SQL function, so a
+//! preview counts exactly the users a broadcast with the same filter reaches.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgExecutor;
+use validator::Validate;
+
+/// Conditions on users. As query parameters, e.g.
+/// `?registered_before=2025-01-01T00:00:00Z&min_posts=3&is_verified=true&active_within_days=30`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Validate)]
+pub struct UserFilter {
+    /// Registered before this time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registered_before: Option<DateTime<Utc>>,
+    /// Has at least this many posts that are not deleted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 0))]
+    pub min_posts: Option<i64>,
+    /// Verified status is this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_verified: Option<bool>,
+    /// Signed in within this many days.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1, max = 3650))]
+    pub active_within_days: Option<i32>,
+}
+
+/// A user in a segment, as previewed and exported.
+#[derive(Debug, Serialize)]
+pub struct SegmentUser {
+    pub id: i64,
+    pub username: String,
+    pub is_verified: bool,
+    pub posts: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub last_active_at: Option<DateTime<Utc>>,
+}
+
+/// Number of users matching `filter`.
+pub async fn count<'e, E: PgExecutor<'e>>(
+    executor: E,
+    filter: &UserFilter,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM segment_user_ids($1, $2, $3, $4)"#,
+        filter.registered_before,
+        filter.min_posts,
+        filter.is_verified,
+        filter.active_within_days
+    )
+    .fetch_one(executor)
+    .await
+}
+
+/// Up to `limit` matching users, picked at random.
+pub async fn sample<'e, E: PgExecutor<'e>>(
+    executor: E,
+    filter: &UserFilter,
+    limit: i64,
+) -> Result<Vec<SegmentUser>, sqlx::Error> {
+    sqlx::query_as!(
+        SegmentUser,
+        r#"
+        SELECT u.id, u.username, u.is_verified, u.created_at, u.last_active_at,
+               (SELECT COUNT(*) FROM posts p
+                WHERE p.user_id = u.id AND p.deleted_at IS NULL) as "posts!"
+        FROM segment_user_ids($1, $2, $3, $4) s
+        JOIN users u ON u.id = s.user_id
+        ORDER BY random()
+        LIMIT $5
+        "#,
+        filter.registered_before,
+        filter.min_posts,
+        filter.is_verified,
+        filter.active_within_days,
+        limit
+    )
+    .fetch_all(executor)
+    .await
+}
+
+/// Every matching user, by ID.
+pub async fn all<'e, E: PgExecutor<'e>>(
+    executor: E,
+    filter: &UserFilter,
+) -> Result<Vec<SegmentUser>, sqlx::Error> {
+    sqlx::query_as!(
+        SegmentUser,
+        r#"
+        SELECT u.id, u.username, u.is_verified, u.created_at, u.last_active_at,
+               (SELECT COUNT(*) FROM posts p
+                WHERE p.user_id = u.id AND p.deleted_at IS NULL) as "posts!"
+        FROM segment_user_ids($1, $2, $3, $4) s
+        JOIN users u ON u.id = s.user_id
+        ORDER BY u.id
+        "#,
+        filter.registered_before,
+        filter.min_posts,
+        filter.is_verified,
+        filter.active_within_days
+    )
+    .fetch_all(executor)
+    .await
+}
+
+/// IDs of the next `limit` matching users after `after_id`, in ID order.
+pub async fn ids_after<'e, E: PgExecutor<'e>>(
+    executor: E,
+    filter: &UserFilter,
+    after_id: i64,
+    limit: i64,
+) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT user_id as "user_id!" FROM segment_user_ids($1, $2, $3, $4)
+        WHERE user_id > $5
+        ORDER BY user_id
+        LIMIT $6
+        "#,
+        filter.registered_before,
+        filter.min_posts,
+        filter.is_verified,
+        filter.active_within_days,
+        after_id,
+        limit
+    )
+    .fetch_all(executor)
+    .await
+}
@@ -0,0 +1,53 @@
+// src/services/series.rs
+
+use sqlx::PgPool;
+
+use crate::models::series::{SeriesLink, SeriesNav};
+
+/// Navigation for the series a post belongs to, if any. Deleted posts are skipped,
+/// so `previous` and `next` always point at readable parts.
+pub async fn nav_for_post(pool: &PgPool, post_id: i64) -> Result<Option<SeriesNav>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        WITH parts AS (
+            SELECT
+                sp.series_id, sp.post_id,
+                ROW_NUMBER() OVER w as part,
+                COUNT(*) OVER (PARTITION BY sp.series_id) as total,
+                LAG(sp.post_id) OVER w as prev_id,
+                LAG(p.title) OVER w as prev_title,
+                LEAD(sp.post_id) OVER w as next_id,
+                LEAD(p.title) OVER w as next_title
+            FROM series_posts sp
+            JOIN posts p ON p.id = sp.post_id AND p.deleted_at IS NULL
+            WHERE sp.series_id = (SELECT series_id FROM series_posts WHERE post_id = $1)
+            WINDOW w AS (PARTITION BY sp.series_id ORDER BY sp.position, sp.post_id)
+        )
+        SELECT
+            s.id, s.title,
+            parts.part as "part!", parts.total as "total!",
+            parts.prev_id, parts.prev_title, parts.next_id, parts.next_title
+        FROM parts
+        JOIN series s ON s.id = parts.series_id
+        WHERE parts.post_id = $1
+        "#,
+        post_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| SeriesNav {
+        id: r.id,
+        title: r.title,
+        part: r.part,
+        total: r.total,
+        previous: r
+            .prev_id
+            .zip(r.prev_title)
+            .map(|(post_id, title)| SeriesLink { post_id, title }),
+        next: r
+            .next_id
+            .zip(r.next_title)
+            .map(|(post_id, title)| SeriesLink { post_id, title }),
+    }))
+}
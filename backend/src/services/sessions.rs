@@ -0,0 +1,191 @@
+// src/services/sessions.rs
+
+//! Signed-in devices, so users can see where they are logged in and sign out
+//! one of them.
+//!
+//! A session is recorded for every token issued at sign-in, by the token's
+//! hash like the revocation list. Revoking a session puts its token on that
+//! list. Tokens issued before sessions were recorded have none.
+
+use sqlx::{PgExecutor, PgPool};
+
+use crate::{
+    config::{SESSION_TOUCH_INTERVAL_SECS, SESSION_USER_AGENT_MAX_CHARS},
+    models::session::Session,
+    utils::hash::token_hash,
+};
+
+/// Browsers and systems recognised in user agents, most specific first.
+const BROWSERS: [(&str, &str); 5] = [
+    ("MicroMessenger", "WeChat"),
+    ("Edg/", "Edge"),
+    ("Firefox/", "Firefox"),
+    ("Chrome/", "Chrome"),
+    ("Safari/", "Safari"),
+];
+const SYSTEMS: [(&str, &str); 6] = [
+    ("iPhone", "iOS"),
+    ("iPad", "iPadOS"),
+    ("Android", "Android"),
+    ("Windows", "Windows"),
+    ("Mac OS X", "macOS"),
+    ("Linux", "Linux"),
+];
+
+/// A short name for the device behind a user agent, e.g. "Chrome on Android".
+pub fn device_label(user_agent: Option<&str>) -> String {
+    let ua = user_agent.unwrap_or_default();
+    let find = |names: &[(&str, &'static str)]| {
+        names.iter().find(|(marker, _)| ua.contains(marker)).map(|(_, name)| *name)
+    };
+    match (find(&BROWSERS), find(&SYSTEMS)) {
+        (Some(browser), Some(system)) => format!("{} on {}", browser, system),
+        (Some(name), None) | (None, Some(name)) => name.to_string(),
+        (None, None) => "Unknown device".to_string(),
+    }
+}
+
+/// Records a token just issued to `user_id`, valid for `expires_in_secs`.
+pub async fn record<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+    token: &str,
+    expires_in_secs: u64,
+    ip: Option<&str>,
+    user_agent: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let user_agent: Option<String> =
+        user_agent.map(|ua| ua.chars().take(SESSION_USER_AGENT_MAX_CHARS).collect());
+    sqlx::query!(
+        r#"
+        INSERT INTO sessions (user_id, token_hash, ip, user_agent, expires_at)
+        VALUES ($1, $2, $3, $4, CURRENT_TIMESTAMP + make_interval(secs => $5))
+        "#,
+        user_id,
+        token_hash(token),
+        ip,
+        user_agent,
+        expires_in_secs as f64
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Notes that `token` was just used; written at most once a minute.
+pub async fn touch(pool: &PgPool, token: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE sessions SET last_used_at = CURRENT_TIMESTAMP
+        WHERE token_hash = $1 AND last_used_at < CURRENT_TIMESTAMP - make_interval(secs => $2)
+        "#,
+        token_hash(token),
+        SESSION_TOUCH_INTERVAL_SECS as f64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// The user's sessions that have not expired, most recently used first.
+/// `current_token` marks the one the request was made with.
+pub async fn list(
+    pool: &PgPool,
+    user_id: i64,
+    current_token: &str,
+) -> Result<Vec<Session>, sqlx::Error> {
+    let current_hash = token_hash(current_token);
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, token_hash, ip, user_agent, created_at, last_used_at, expires_at
+        FROM sessions
+        WHERE user_id = $1 AND expires_at > CURRENT_TIMESTAMP
+        ORDER BY last_used_at DESC, id DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Session {
+            id: row.id,
+            device: device_label(row.user_agent.as_deref()),
+            current: row.token_hash == current_hash,
+            user_agent: row.user_agent,
+            ip: row.ip,
+            created_at: row.created_at,
+            last_used_at: row.last_used_at,
+            expires_at: row.expires_at,
+        })
+        .collect())
+}
+
+/// Signs out one of the user's sessions: its token is revoked until it
+/// expires. Returns false if the user has no such session.
+pub async fn revoke(pool: &PgPool, user_id: i64, session_id: i64) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let session = sqlx::query!(
+        "DELETE FROM sessions WHERE id = $1 AND user_id = $2 RETURNING token_hash, expires_at",
+        session_id,
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(session) = session else {
+        return Ok(false);
+    };
+    sqlx::query!(
+        r#"
+        INSERT INTO revoked_tokens (token_hash, user_id, expires_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (token_hash) DO NOTHING
+        "#,
+        session.token_hash,
+        user_id,
+        session.expires_at
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Signs out all of the user's sessions, e.g. after the password was reset.
+/// Run in the transaction that changes the credentials.
+pub async fn revoke_all<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        WITH ended AS (
+            DELETE FROM sessions WHERE user_id = $1
+            RETURNING token_hash, expires_at
+        )
+        INSERT INTO revoked_tokens (token_hash, user_id, expires_at)
+        SELECT token_hash, $1, expires_at FROM ended
+        WHERE expires_at > CURRENT_TIMESTAMP
+        ON CONFLICT (token_hash) DO NOTHING
+        "#,
+        user_id
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Drops the session of `token`, once it has been revoked by logging out.
+pub async fn forget<'e, E: PgExecutor<'e>>(executor: E, token: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM sessions WHERE token_hash = $1", token_hash(token))
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
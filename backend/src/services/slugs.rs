@@ -0,0 +1,89 @@
+// src/services/slugs.rs
+
+//! URL slugs for architectures and posts, so frontends can link to
+//! `/architectures/yingxianmuta` rather than a bare ID.
+//!
+//! A slug is the name or title in pinyin (see `slugify`), cut to
+//! `SLUG_MAX_LEN`, with `-2`, `-3`, ... appended when taken. Slugs never
+//! change once given, so links stay valid when the name is edited. Slugs are
+//! never all digits, so a path segment is an ID exactly when it parses as one.
+
+use sqlx::PgConnection;
+
+use crate::{config::SLUG_MAX_LEN, utils::pinyin::slugify};
+
+/// How a GET endpoint was asked for an entity.
+pub enum Lookup {
+    Id(i64),
+    Slug(String),
+}
+
+impl Lookup {
+    pub fn parse(key: &str) -> Self {
+        match key.parse() {
+            Ok(id) => Lookup::Id(id),
+            Err(_) => Lookup::Slug(key.to_lowercase()),
+        }
+    }
+}
+
+/// The slug before collision handling; `fallback` stands in for names that
+/// leave nothing usable, and prefixes names that are only digits.
+fn base(text: &str, fallback: &str) -> String {
+    let slug = slugify(text);
+    let slug = slug[..slug.len().min(SLUG_MAX_LEN)].trim_end_matches('-');
+    if slug.is_empty() {
+        fallback.to_string()
+    } else if slug.bytes().all(|b| b.is_ascii_digit()) {
+        format!("{}-{}", fallback, slug)
+    } else {
+        slug.to_string()
+    }
+}
+
+/// `base`, or the first of `base-2`, `base-3`, ... not in `taken`.
+fn first_free(base: String, taken: &[String]) -> String {
+    if !taken.contains(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("some suffix is free")
+}
+
+/// Serializes slug picks for the same base until the transaction ends, so
+/// concurrent inserts don't pick the same free slug.
+async fn lock(conn: &mut PgConnection, base: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!("SELECT pg_advisory_xact_lock(hashtext('slug:' || $1))", base)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// A free slug for an architecture named `name`. Run in the transaction
+/// that stores it.
+pub async fn for_architecture(conn: &mut PgConnection, name: &str) -> Result<String, sqlx::Error> {
+    let base = base(name, "architecture");
+    lock(conn, &base).await?;
+    let taken = sqlx::query_scalar!(
+        r#"SELECT slug as "slug!" FROM architectures WHERE slug = $1 OR slug LIKE $1 || '-%'"#,
+        base
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(first_free(base, &taken))
+}
+
+/// A free slug for a post titled `title`. Run in the transaction that stores it.
+pub async fn for_post(conn: &mut PgConnection, title: &str) -> Result<String, sqlx::Error> {
+    let base = base(title, "post");
+    lock(conn, &base).await?;
+    let taken = sqlx::query_scalar!(
+        r#"SELECT slug as "slug!" FROM posts WHERE slug = $1 OR slug LIKE $1 || '-%'"#,
+        base
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(first_free(base, &taken))
+}
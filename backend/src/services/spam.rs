@@ -0,0 +1,120 @@
+// src/services/spam.rs
+
+//! Cheap checks that catch simple bots on the registration and post forms,
+//! configured by the `SPAM_*` variables.
+//!
+//! A honeypot field is hidden from people by the web client, so anything in
+//! it came from a script. Timing uses a signed `form_token` fetched when the
+//! form is shown: a form sent back faster than a person could fill it in, or
+//! without a valid token, is suspect. Suspect submissions are rejected or
+//! accepted and flagged as `spam_suspected` security events.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::PgPool;
+
+use crate::{
+    config::{FORM_TOKEN_TTL_HOURS, SpamAction, SpamConfig},
+    error::AppError,
+    models::spam::FormTraps,
+    services::security_events,
+    utils::audit_stream,
+};
+
+fn signature(jwt_secret: &str, issued_at: i64) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(jwt_secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("form-token:{}", issued_at).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// A token recording that a form was shown at `issued_at` (Unix seconds).
+pub fn form_token(jwt_secret: &str, issued_at: i64) -> String {
+    format!("{}.{}", issued_at, signature(jwt_secret, issued_at))
+}
+
+/// Why a submission looks automated, if it does.
+pub fn verdict(
+    spam: &SpamConfig,
+    jwt_secret: &str,
+    traps: &FormTraps,
+    now: i64,
+) -> Option<&'static str> {
+    if let Some(field) = &spam.honeypot_field
+        && traps
+            .other
+            .get(field)
+            .is_some_and(|value| !value.is_null() && value.as_str() != Some(""))
+    {
+        return Some("honeypot");
+    }
+
+    if spam.min_form_secs > 0 {
+        let Some(token) = &traps.form_token else {
+            return Some("missing_form_token");
+        };
+        let issued_at = token
+            .split_once('.')
+            .and_then(|(issued_at, mac)| {
+                let issued_at = issued_at.parse::<i64>().ok()?;
+                (mac == signature(jwt_secret, issued_at)).then_some(issued_at)
+            });
+        let Some(issued_at) = issued_at else {
+            return Some("bad_form_token");
+        };
+        if now - issued_at < spam.min_form_secs as i64 {
+            return Some("too_fast");
+        }
+        if now - issued_at > FORM_TOKEN_TTL_HOURS * 3600 {
+            return Some("expired_form_token");
+        }
+    }
+    None
+}
+
+/// Checks a submission before it is stored. Suspect ones are refused with 400
+/// under `SPAM_ACTION=reject`; under `flag` the reason is returned, to be
+/// passed to [`flag`] once the content exists.
+pub async fn screen(
+    pool: &PgPool,
+    spam: &SpamConfig,
+    jwt_secret: &str,
+    traps: &FormTraps,
+    user_id: Option<i64>,
+    ip: Option<&str>,
+    path: &str,
+) -> Result<Option<&'static str>, AppError> {
+    let Some(reason) = verdict(spam, jwt_secret, traps, chrono::Utc::now().timestamp()) else {
+        return Ok(None);
+    };
+    if spam.action == SpamAction::Flag {
+        return Ok(Some(reason));
+    }
+
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "spam_rejected",
+        user_id,
+        ip,
+        path,
+        reason
+    );
+    security_events::record(pool, security_events::SPAM_SUSPECTED, user_id, ip, None, path).await;
+    Err(AppError::BadRequest(
+        "This submission looks automated. Please reload the page and try again.".to_string(),
+    ))
+}
+
+/// Records accepted content from a suspect submission for moderators.
+pub async fn flag(pool: &PgPool, reason: &str, user_id: i64, ip: Option<&str>, path: &str) {
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "spam_flagged",
+        user_id,
+        ip,
+        path,
+        reason
+    );
+    security_events::record(pool, security_events::SPAM_SUSPECTED, Some(user_id), ip, None, path)
+        .await;
+}
@@ -0,0 +1,231 @@
+// src/services/tags.rs
+
+//! Tags on posts, the trending list, and topic pages.
+//!
+//! Tags are told apart by slug, so "Dougong" and "dougong" are one tag; the
+//! name kept is the spelling it was first used with. The trending list is a
+//! snapshot the `trending_tags` job rewrites: posts of the last week weighted
+//! by age, so a burst of new posts beats a steady trickle of old ones.
+
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+    config::{TRENDING_TAGS_HALF_LIFE_HOURS, TRENDING_TAGS_LIMIT, TRENDING_TAGS_WINDOW_DAYS},
+    models::{
+        post::Post,
+        tag::{RelatedArchitecture, Tag, TrendingTag},
+    },
+    utils::pinyin::slugify,
+};
+
+/// The tags named in a new post, deduplicated by slug in order of appearance.
+pub fn normalize(names: &[String]) -> Vec<Tag> {
+    let mut tags: Vec<Tag> = Vec::new();
+    for name in names {
+        let name = name.trim();
+        let slug = slugify(name);
+        if !slug.is_empty() && !tags.iter().any(|t| t.slug == slug) {
+            tags.push(Tag {
+                slug,
+                name: name.to_string(),
+            });
+        }
+    }
+    tags
+}
+
+/// Puts `tags` on a post, creating the ones not used before.
+pub async fn store(conn: &mut PgConnection, post_id: i64, tags: &[Tag]) -> Result<(), sqlx::Error> {
+    if tags.is_empty() {
+        return Ok(());
+    }
+    let slugs: Vec<String> = tags.iter().map(|t| t.slug.clone()).collect();
+    let names: Vec<String> = tags.iter().map(|t| t.name.clone()).collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO tags (slug, name)
+        SELECT * FROM UNNEST($1::TEXT[], $2::TEXT[])
+        ON CONFLICT (slug) DO NOTHING
+        "#,
+        &slugs,
+        &names
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO post_tags (post_id, tag_id)
+        SELECT $1, id FROM tags WHERE slug = ANY($2)
+        ON CONFLICT DO NOTHING
+        "#,
+        post_id,
+        &slugs
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// The tags on a post, by name.
+pub async fn for_post(pool: &PgPool, post_id: i64) -> Result<Vec<Tag>, sqlx::Error> {
+    sqlx::query_as!(
+        Tag,
+        r#"
+        SELECT t.slug, t.name
+        FROM post_tags pt
+        JOIN tags t ON t.id = pt.tag_id
+        WHERE pt.post_id = $1
+        ORDER BY t.name
+        "#,
+        post_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Rewrites the trending list from the posts of the last week.
+/// Returns the number of tags on it.
+pub async fn refresh_trending(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!("DELETE FROM trending_tags").execute(&mut *tx).await?;
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO trending_tags (tag_id, posts, authors, score)
+        SELECT pt.tag_id, COUNT(*), COUNT(DISTINCT p.user_id),
+               SUM(POWER(0.5, EXTRACT(EPOCH FROM (NOW() - p.created_at))::FLOAT8 / 3600 / $2::FLOAT8))
+        FROM post_tags pt
+        JOIN posts p ON p.id = pt.post_id
+        WHERE p.deleted_at IS NULL
+          AND p.created_at >= NOW() - make_interval(days => $1)
+        GROUP BY pt.tag_id
+        ORDER BY 4 DESC, pt.tag_id
+        LIMIT $3
+        "#,
+        TRENDING_TAGS_WINDOW_DAYS,
+        TRENDING_TAGS_HALF_LIFE_HOURS,
+        TRENDING_TAGS_LIMIT
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(result.rows_affected())
+}
+
+/// The trending list as last computed, hottest first.
+pub async fn trending(pool: &PgPool) -> Result<Vec<TrendingTag>, sqlx::Error> {
+    sqlx::query_as!(
+        TrendingTag,
+        r#"
+        SELECT t.slug, t.name, tt.posts, tt.authors, tt.score, tt.computed_at
+        FROM trending_tags tt
+        JOIN tags t ON t.id = tt.tag_id
+        ORDER BY tt.score DESC, t.id
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// The ID and tag with `slug`, if it exists.
+pub async fn find(pool: &PgPool, slug: &str) -> Result<Option<(i64, Tag)>, sqlx::Error> {
+    let row = sqlx::query!("SELECT id, slug, name FROM tags WHERE slug = $1", slug)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(|r| {
+        (
+            r.id,
+            Tag {
+                slug: r.slug,
+                name: r.name,
+            },
+        )
+    }))
+}
+
+/// Number of visible posts with the tag.
+pub async fn post_count(pool: &PgPool, tag_id: i64) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM post_tags pt
+        JOIN posts p ON p.id = pt.post_id AND p.deleted_at IS NULL
+        WHERE pt.tag_id = $1
+        "#,
+        tag_id
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Visible posts with the tag, newest first, created before `cursor`.
+pub async fn posts(
+    pool: &PgPool,
+    tag_id: i64,
+    cursor: Option<chrono::DateTime<chrono::Utc>>,
+    limit: i64,
+) -> Result<Vec<Post>, sqlx::Error> {
+    sqlx::query_as!(
+        Post,
+        r#"
+        SELECT
+            p.id, p.user_id, p.slug, p.title, p.content, p.excerpt, p.reading_time_minutes,
+            p.created_at, p.updated_at, p.edited_at, p.deleted_at,
+            p.likes_count, p.comments_count, p.favorites_count,
+            FALSE as "is_liked!", FALSE as "is_favorited!"
+        FROM post_tags pt
+        JOIN posts p ON p.id = pt.post_id
+        WHERE pt.tag_id = $1 AND p.deleted_at IS NULL
+          AND ($2::TIMESTAMPTZ IS NULL OR p.created_at < $2)
+        ORDER BY p.created_at DESC
+        LIMIT $3
+        "#,
+        tag_id,
+        cursor,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Architectures whose name, category or dynasty is the tag's name, or that
+/// posts with the tag reference; named ones first, then by mentions.
+/// Names are in `locale` where translated.
+pub async fn related_architectures(
+    pool: &PgPool,
+    tag_id: i64,
+    tag_name: &str,
+    locale: &str,
+    limit: i64,
+) -> Result<Vec<RelatedArchitecture>, sqlx::Error> {
+    sqlx::query_as!(
+        RelatedArchitecture,
+        r#"
+        WITH mentions AS (
+            SELECT r.entity_id as architecture_id, COUNT(*) as mentions
+            FROM post_tags pt
+            JOIN posts p ON p.id = pt.post_id AND p.deleted_at IS NULL
+            JOIN post_references r ON r.post_id = pt.post_id AND r.entity_type = 'architecture'
+            WHERE pt.tag_id = $1
+            GROUP BY r.entity_id
+        )
+        SELECT a.id, COALESCE(t.name, a.name) as "name!", a.category,
+               COALESCE(t.dynasty, a.dynasty) as "dynasty!", a.cover_img,
+               COALESCE(m.mentions, 0) as "mentions!"
+        FROM architectures a
+        LEFT JOIN mentions m ON m.architecture_id = a.id
+        LEFT JOIN architecture_translations t ON t.architecture_id = a.id AND t.locale = $3
+        WHERE m.architecture_id IS NOT NULL
+           OR a.name = $2 OR a.category = $2 OR a.dynasty = $2
+        ORDER BY (a.name = $2) DESC, COALESCE(m.mentions, 0) DESC, a.id
+        LIMIT $4
+        "#,
+        tag_id,
+        tag_name,
+        locale,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,91 @@
+// src/services/terms.rs
+
+//! Terms of service acceptance.
+//!
+//! Until the first version is published nobody is asked to accept anything.
+//! After that, registration requires the current version and users who have
+//! not accepted it cannot post or comment.
+
+use sqlx::{PgExecutor, PgPool};
+
+use crate::{error::AppError, models::terms::TermsVersion};
+
+/// The most recently published terms, if any.
+pub async fn current(pool: &PgPool) -> Result<Option<TermsVersion>, sqlx::Error> {
+    sqlx::query_as!(
+        TermsVersion,
+        r#"
+        SELECT id, version, content, published_at
+        FROM terms_versions
+        ORDER BY published_at DESC, id DESC
+        LIMIT 1
+        "#
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Checks that the version a client claims to accept is the current one.
+pub fn check_version(current: &TermsVersion, accepted: Option<&str>) -> Result<(), AppError> {
+    if accepted == Some(current.version.as_str()) {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(format!(
+            "You must accept the current terms of service (version {}).",
+            current.version
+        )))
+    }
+}
+
+/// Records that a user accepted a version. Accepting twice keeps the first timestamp.
+pub async fn record_acceptance<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+    terms_version_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO terms_acceptances (user_id, terms_version_id)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id, terms_version_id) DO NOTHING
+        "#,
+        user_id,
+        terms_version_id
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Whether the user has accepted the current terms. True while none are published.
+pub async fn has_accepted_current(pool: &PgPool, user_id: i64) -> Result<bool, sqlx::Error> {
+    let Some(current) = current(pool).await? else {
+        return Ok(true);
+    };
+
+    let accepted = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM terms_acceptances WHERE user_id = $1 AND terms_version_id = $2
+        ) as "accepted!"
+        "#,
+        user_id,
+        current.id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(accepted)
+}
+
+/// Rejects users who still have to accept the current terms.
+pub async fn ensure_accepted(pool: &PgPool, user_id: i64) -> Result<(), AppError> {
+    if has_accepted_current(pool, user_id).await? {
+        Ok(())
+    } else {
+        Err(AppError::AuthError(
+            "You must accept the current terms of service to perform this action.".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,45 @@
+// src/services/token_revocation.rs
+
+//! Server-side revocation of JWTs that have not expired yet.
+//!
+//! Tokens are stateless, so logging out only means something if the server
+//! remembers the token until it would have expired. The list holds SHA-256
+//! hashes rather than tokens, and every authenticated request is checked
+//! against it.
+
+use sqlx::{PgExecutor, PgPool};
+
+use crate::utils::{hash::token_hash, jwt::Claims};
+
+/// Invalidates `token` until its expiry. Revoking a token twice is not an error.
+pub async fn revoke<'e, E: PgExecutor<'e>>(
+    executor: E,
+    token: &str,
+    claims: &Claims,
+    user_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO revoked_tokens (token_hash, user_id, expires_at)
+        VALUES ($1, $2, to_timestamp($3))
+        ON CONFLICT (token_hash) DO NOTHING
+        "#,
+        token_hash(token),
+        user_id,
+        claims.exp as f64
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Whether `token` has been revoked.
+pub async fn is_revoked(pool: &PgPool, token: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE token_hash = $1) as "revoked!""#,
+        token_hash(token)
+    )
+    .fetch_one(pool)
+    .await
+}
@@ -0,0 +1,116 @@
+// src/services/two_factor.rs
+
+//! Opt-in two-factor authentication with an authenticator app.
+//!
+//! Enrolling stores a new secret; it takes effect once a first code from the
+//! app is verified. After that, a code at login (or from
+//! `POST /api/auth/2fa/verify`) gives a token marked as having passed the
+//! second factor, which admins with 2FA enabled need for the admin API.
+
+use sqlx::{PgExecutor, PgPool};
+
+use crate::{error::AppError, utils::totp};
+
+/// A started enrollment, for the user to add to their app.
+#[derive(Debug)]
+pub struct Enrollment {
+    /// Base32 secret, for typing in by hand.
+    pub secret: String,
+    /// `otpauth://` URI, for a QR code.
+    pub uri: String,
+}
+
+/// Whether the user has two-factor authentication turned on.
+pub async fn is_enabled<'e, E: PgExecutor<'e>>(executor: E, user_id: i64) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT totp_enabled_at IS NOT NULL as "enabled!" FROM users WHERE id = $1"#,
+        user_id
+    )
+    .fetch_optional(executor)
+    .await
+    .map(|enabled| enabled.unwrap_or(false))
+}
+
+/// Gives the user a new secret, replacing any enrollment not yet verified.
+/// Fails with 409 once two-factor authentication is on.
+pub async fn enroll(
+    pool: &PgPool,
+    jwt_secret: &str,
+    user_id: i64,
+    username: &str,
+) -> Result<Enrollment, AppError> {
+    let secret = totp::new_secret();
+    let updated = sqlx::query!(
+        r#"
+        UPDATE users SET totp_secret = $2, totp_last_step = NULL
+        WHERE id = $1 AND totp_enabled_at IS NULL
+        "#,
+        user_id,
+        totp::encrypt_secret(jwt_secret, &secret)
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if updated == 0 {
+        return Err(AppError::Conflict(
+            "Two-factor authentication is already enabled".to_string(),
+        ));
+    }
+
+    Ok(Enrollment {
+        secret: totp::base32_encode(&secret),
+        uri: totp::provisioning_uri(username, &secret),
+    })
+}
+
+/// Checks a code from the user's app, turning two-factor authentication on
+/// if this is the first one. Each code is accepted once. Returns whether it
+/// was just turned on.
+pub async fn verify(
+    pool: &PgPool,
+    jwt_secret: &str,
+    user_id: i64,
+    code: &str,
+) -> Result<bool, AppError> {
+    let mut tx = pool.begin().await?;
+    let user = sqlx::query!(
+        r#"
+        SELECT totp_secret, totp_enabled_at IS NOT NULL as "enabled!", totp_last_step
+        FROM users WHERE id = $1
+        FOR UPDATE
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    let Some(stored) = user.totp_secret else {
+        return Err(AppError::BadRequest(
+            "Two-factor authentication is not set up".to_string(),
+        ));
+    };
+    let secret = totp::decrypt_secret(jwt_secret, &stored).ok_or_else(|| {
+        AppError::InternalServerError("Two-factor secret cannot be decrypted".to_string())
+    })?;
+
+    let step = totp::verify(&secret, code, chrono::Utc::now().timestamp())
+        .filter(|step| user.totp_last_step.is_none_or(|last| *step > last))
+        .ok_or(AppError::AuthError("Invalid two-factor code".to_string()))?;
+
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET totp_last_step = $2, totp_enabled_at = COALESCE(totp_enabled_at, CURRENT_TIMESTAMP)
+        WHERE id = $1
+        "#,
+        user_id,
+        step
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(!user.enabled)
+}
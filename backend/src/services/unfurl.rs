@@ -0,0 +1,217 @@
+// src/services/unfurl.rs
+
+//! Link previews ("unfurling") for URLs in posts.
+//!
+//! Creating a post queues an `unfurl_link` job per new URL. The job fetches the
+//! page and stores its title/description/thumbnail in `link_previews`; post
+//! detail responses then attach the cached cards.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use sqlx::PgPool;
+use url::Url;
+
+use crate::{
+    config::{UNFURL_ALLOWED_DOMAINS, UNFURL_DENIED_DOMAINS, UNFURL_MAX_LINKS, UNFURL_REFRESH_DAYS},
+    jobs::queue,
+    models::post::LinkCard,
+    utils::http_client::{self, OutboundError, OutboundPolicy},
+};
+
+/// Job kind that fetches one link preview.
+pub const UNFURL_JOB: &str = "unfurl_link";
+
+/// Metadata lives in `<head>`, so there is no need to read more than this.
+const MAX_BODY_BYTES: usize = 512 * 1024;
+
+static URL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"https?://[^\s<>"'`]+"#).unwrap());
+static TITLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap());
+static META_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?is)<meta\s[^>]*>").unwrap());
+static ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)([a-z:_-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap()
+});
+
+/// Metadata scraped from a page.
+#[derive(Debug, Default, PartialEq)]
+pub struct LinkPreview {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+}
+
+/// Why a fetch did not produce a preview.
+#[derive(Debug)]
+pub enum UnfurlError {
+    /// The URL is not allowed or not a page; retrying will not help.
+    Rejected(String),
+    /// Network trouble; the job is retried.
+    Transient(String),
+}
+
+/// Finds the distinct http(s) URLs in sanitized post content, in order of appearance.
+pub fn extract_urls(content: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for m in URL_RE.find_iter(content) {
+        // Content is stored HTML-escaped, and trailing punctuation is rarely part of the link.
+        let raw = m.as_str().replace("&amp;", "&");
+        let trimmed = raw.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '&']);
+        let Ok(url) = Url::parse(trimmed) else { continue };
+        let url = url.to_string();
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+        if urls.len() == UNFURL_MAX_LINKS {
+            break;
+        }
+    }
+    urls
+}
+
+/// Queues a fetch for every URL that has no fresh preview yet.
+pub async fn queue_previews(pool: &PgPool, urls: &[String]) -> Result<(), sqlx::Error> {
+    for url in urls {
+        let mut tx = pool.begin().await?;
+
+        let claimed = sqlx::query!(
+            r#"
+            INSERT INTO link_previews (url) VALUES ($1)
+            ON CONFLICT (url) DO UPDATE SET status = 'pending'
+            WHERE link_previews.status <> 'pending'
+              AND link_previews.fetched_at < NOW() - make_interval(days => $2)
+            RETURNING url
+            "#,
+            url,
+            UNFURL_REFRESH_DAYS
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if claimed.is_some() {
+            queue::enqueue(&mut *tx, UNFURL_JOB, serde_json::json!({ "url": url })).await?;
+        }
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+/// Returns the successfully fetched previews for the links in `content`.
+pub async fn cards_for(pool: &PgPool, content: &str) -> Result<Vec<LinkCard>, sqlx::Error> {
+    let urls = extract_urls(content);
+    if urls.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut cards = sqlx::query_as!(
+        LinkCard,
+        r#"
+        SELECT url, title, description, image_url
+        FROM link_previews
+        WHERE url = ANY($1) AND status = 'ok'
+        "#,
+        &urls
+    )
+    .fetch_all(pool)
+    .await?;
+
+    cards.sort_by_key(|c| urls.iter().position(|u| *u == c.url));
+    Ok(cards)
+}
+
+/// Fetches a page and extracts its preview metadata.
+pub async fn fetch_preview(url: &str) -> Result<LinkPreview, UnfurlError> {
+    let policy = OutboundPolicy {
+        allowed_domains: UNFURL_ALLOWED_DOMAINS,
+        denied_domains: UNFURL_DENIED_DOMAINS,
+        max_body_bytes: MAX_BODY_BYTES,
+        user_agent: "ancient-arch-unfurl/1.0",
+        ..OutboundPolicy::default()
+    };
+
+    let resp = http_client::get(url, &policy).await.map_err(|e| match e {
+        OutboundError::Blocked(reason) => UnfurlError::Rejected(reason),
+        OutboundError::Network(reason) => UnfurlError::Transient(reason),
+    })?;
+
+    if !resp.status.is_success() {
+        return Err(UnfurlError::Rejected(format!("HTTP {}", resp.status)));
+    }
+    if !resp.content_type.is_some_and(|v| v.contains("text/html")) {
+        return Err(UnfurlError::Rejected("Not an HTML page".to_string()));
+    }
+
+    Ok(parse_preview(&String::from_utf8_lossy(&resp.body), &resp.url))
+}
+
+/// Extracts Open Graph / standard metadata from an HTML document.
+pub fn parse_preview(html: &str, page_url: &Url) -> LinkPreview {
+    let mut preview = LinkPreview::default();
+    let mut meta_description = None;
+    let mut meta_title = None;
+
+    for tag in META_RE.find_iter(html) {
+        let mut key = None;
+        let mut content = None;
+        for attr in ATTR_RE.captures_iter(tag.as_str()) {
+            let name = attr[1].to_ascii_lowercase();
+            let value = attr.get(2).or(attr.get(3)).map_or("", |m| m.as_str());
+            match name.as_str() {
+                "property" | "name" => key = Some(value.to_ascii_lowercase()),
+                "content" => content = Some(decode_entities(value)),
+                _ => {}
+            }
+        }
+        let (Some(key), Some(content)) = (key, content) else { continue };
+        if content.is_empty() {
+            continue;
+        }
+        match key.as_str() {
+            "og:title" => preview.title = Some(content),
+            "twitter:title" => meta_title = meta_title.or(Some(content)),
+            "og:description" => preview.description = Some(content),
+            "description" | "twitter:description" => {
+                meta_description = meta_description.or(Some(content))
+            }
+            "og:image" | "twitter:image" if preview.image_url.is_none() => {
+                preview.image_url = page_url
+                    .join(&content)
+                    .ok()
+                    .filter(|u| u.scheme() == "http" || u.scheme() == "https")
+                    .map(|u| u.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    let page_title = TITLE_RE
+        .captures(html)
+        .map(|c| decode_entities(c[1].trim()))
+        .filter(|t| !t.is_empty());
+
+    preview.title = preview.title.or(meta_title).or(page_title).map(|t| truncate(&t, 200));
+    preview.description = preview
+        .description
+        .or(meta_description)
+        .map(|d| truncate(&d, 500));
+    preview
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    let s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    match s.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}…", &s[..idx]),
+        None => s,
+    }
+}
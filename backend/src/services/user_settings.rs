@@ -0,0 +1,67 @@
+// src/services/user_settings.rs
+
+//! Per-user preferences. The language lives in `users.locale`, where
+//! `PreferredLocale` reads it; everything else in `users.user_settings`.
+
+use sqlx::{PgExecutor, PgPool};
+
+use crate::models::user::{PostSort, UserSettings};
+
+/// Parses the stored settings. A document that no longer parses (say, after a
+/// key was retired) falls back to the defaults rather than failing requests.
+fn parse(user_id: i64, stored: serde_json::Value) -> UserSettings {
+    serde_json::from_value(stored).unwrap_or_else(|e| {
+        tracing::warn!(
+            "Settings of user {} not readable, using defaults: {}",
+            user_id,
+            e
+        );
+        UserSettings::default()
+    })
+}
+
+/// The settings of a user, or None if the user does not exist.
+pub async fn load(pool: &PgPool, user_id: i64) -> Result<Option<UserSettings>, sqlx::Error> {
+    let row = sqlx::query!(
+        "SELECT locale, user_settings FROM users WHERE id = $1",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|row| UserSettings {
+        language: row.locale,
+        ..parse(user_id, row.user_settings)
+    }))
+}
+
+/// Replaces the settings of a user. `settings.language` must already be canonical.
+pub async fn save<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: i64,
+    settings: &UserSettings,
+) -> Result<(), sqlx::Error> {
+    let stored = serde_json::json!({
+        "email_notifications": settings.email_notifications,
+        "default_post_sort": settings.default_post_sort,
+    });
+    sqlx::query!(
+        "UPDATE users SET locale = $1, user_settings = $2 WHERE id = $3",
+        settings.language,
+        stored,
+        user_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// The post order a user picked, for list requests that give none.
+pub async fn default_post_sort(pool: &PgPool, user_id: i64) -> Result<PostSort, sqlx::Error> {
+    let stored = sqlx::query_scalar!("SELECT user_settings FROM users WHERE id = $1", user_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(stored.map_or(PostSort::default(), |stored| {
+        parse(user_id, stored).default_post_sort
+    }))
+}
@@ -1,4 +1,15 @@
-use crate::config::Config;
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    config::{ACTIVITY_HEATMAP_CACHE_SECS, Config, PUBLIC_STATS_CACHE_SECS, USAGE_SAMPLE_EVERY},
+    models::stats::{ActivityHeatmap, PublicStats},
+    services::feed::{FeedScorer, HeuristicScorer},
+    storage::{self, Storage},
+    utils::{
+        cache::TtlCache, log_filter::LogFilter, presence::Presence, role_cache::RoleCache,
+        usage_sampler::UsageSampler,
+    },
+};
 use axum::extract::FromRef;
 use sqlx::PgPool;
 
@@ -6,6 +17,39 @@ use sqlx::PgPool;
 pub struct AppState {
     pub pool: PgPool,
     pub config: Config,
+    /// Aggregates for `GET /api/stats/public`, keyed by `()` as there is a single entry.
+    pub public_stats: TtlCache<(), PublicStats>,
+    /// Per-user `GET /api/profile/activity-heatmap` responses, keyed by user ID.
+    pub activity_heatmaps: TtlCache<i64, ActivityHeatmap>,
+    /// Ranking strategy for `GET /api/feed/discover`.
+    pub feed_scorer: Arc<dyn FeedScorer>,
+    /// Last-seen times of authenticated users, fed by the auth middlewares.
+    pub presence: Presence,
+    /// Blob storage backend chosen by `Config::storage`.
+    pub storage: Arc<dyn Storage>,
+    /// Runtime log filter control; detached unless `main` installs one.
+    pub log_filter: LogFilter,
+    /// Current user roles for `admin_middleware`.
+    pub roles: RoleCache,
+    /// Picks the authenticated requests recorded as API usage.
+    pub usage: UsageSampler,
+}
+
+impl AppState {
+    pub fn new(pool: PgPool, config: Config) -> Self {
+        Self {
+            storage: storage::from_config(&config),
+            log_filter: LogFilter::detached(&config.rust_log),
+            pool,
+            config,
+            public_stats: TtlCache::new(Duration::from_secs(PUBLIC_STATS_CACHE_SECS)),
+            activity_heatmaps: TtlCache::new(Duration::from_secs(ACTIVITY_HEATMAP_CACHE_SECS)),
+            feed_scorer: Arc::new(HeuristicScorer),
+            presence: Presence::new(),
+            roles: RoleCache::new(),
+            usage: UsageSampler::new(USAGE_SAMPLE_EVERY),
+        }
+    }
 }
 
 impl FromRef<AppState> for PgPool {
@@ -19,3 +63,33 @@ impl FromRef<AppState> for Config {
         state.config.clone()
     }
 }
+
+impl FromRef<AppState> for Arc<dyn Storage> {
+    fn from_ref(state: &AppState) -> Self {
+        state.storage.clone()
+    }
+}
+
+impl FromRef<AppState> for Presence {
+    fn from_ref(state: &AppState) -> Self {
+        state.presence.clone()
+    }
+}
+
+impl FromRef<AppState> for LogFilter {
+    fn from_ref(state: &AppState) -> Self {
+        state.log_filter.clone()
+    }
+}
+
+impl FromRef<AppState> for RoleCache {
+    fn from_ref(state: &AppState) -> Self {
+        state.roles.clone()
+    }
+}
+
+impl FromRef<AppState> for UsageSampler {
+    fn from_ref(state: &AppState) -> Self {
+        state.usage.clone()
+    }
+}
@@ -0,0 +1,86 @@
+// src/utils/audit_stream.rs
+
+//! Business events (logins, reviews, deletions) in a log of their own.
+//!
+//! Events logged with `target: audit_stream::TARGET` are kept out of the
+//! application log and its adjustable level, and written by `AuditLayer` as
+//! one JSON object per line, to daily files kept for a configured number of days:
+//!
+//! ```ignore
+//! tracing::info!(target: audit_stream::TARGET, action = "post_deleted", actor_id, post_id);
+//! ```
+
+use std::{fmt, io::Write};
+
+use tracing::{
+    Event, Metadata, Subscriber,
+    field::{Field, Visit},
+};
+use tracing_subscriber::{
+    fmt::MakeWriter,
+    layer::{Context, Layer},
+};
+
+/// The tracing target of audit events.
+pub const TARGET: &str = "audit";
+
+/// Whether `metadata` belongs to an audit event.
+pub fn is_audit(metadata: &Metadata<'_>) -> bool {
+    metadata.target() == TARGET
+}
+
+/// Writes every event it sees as a JSON line: its fields plus `time`.
+/// Pair it with a `filter_fn(is_audit)` filter.
+pub struct AuditLayer<W> {
+    make_writer: W,
+}
+
+impl<W> AuditLayer<W> {
+    pub fn new(make_writer: W) -> Self {
+        Self { make_writer }
+    }
+}
+
+impl<S, W> Layer<S> for AuditLayer<W>
+where
+    S: Subscriber,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = serde_json::Map::new();
+        fields.insert("time".to_string(), chrono::Utc::now().to_rfc3339().into());
+        event.record(&mut JsonVisitor(&mut fields));
+
+        let mut line = serde_json::Value::Object(fields).to_string();
+        line.push('\n');
+        if let Err(e) = self.make_writer.make_writer().write_all(line.as_bytes()) {
+            // Logging through tracing here would come back to this layer
+            eprintln!("Failed to write audit event: {}", e);
+        }
+    }
+}
+
+struct JsonVisitor<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
@@ -0,0 +1,87 @@
+// src/utils/client_ip.rs
+
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+
+use axum::{
+    extract::{ConnectInfo, FromRef, FromRequestParts},
+    http::{Extensions, HeaderMap, request::Parts},
+};
+use ipnet::IpNet;
+
+use crate::config::Config;
+
+/// Best-effort client address of the request.
+///
+/// Forwarding headers are only believed when the socket peer is one of the
+/// trusted proxies (`Config::trusted_proxies`); anyone else could write them.
+/// Otherwise the socket peer is used when the server was started with connect
+/// info.
+#[derive(Debug, Clone)]
+pub struct ClientIp(pub Option<String>);
+
+/// Reads the client address a trusted proxy passed on.
+///
+/// `X-Real-IP` wins, as the proxy overwrites it with the address it saw. When
+/// that is missing or itself a trusted proxy, the rightmost `X-Forwarded-For`
+/// hop that is not a trusted proxy is used: hops to its left were written by
+/// the client.
+fn from_headers(headers: &HeaderMap, trusted: &[IpNet]) -> Option<IpAddr> {
+    let is_trusted = |ip: &IpAddr| trusted.iter().any(|net| net.contains(ip));
+    let parse = |v: &str| v.trim().parse::<IpAddr>().ok();
+
+    let real_ip = headers
+        .get("x-real-ip")
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse)
+        .filter(|ip| !is_trusted(ip));
+
+    real_ip.or_else(|| {
+        headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .rev()
+            .map_while(parse)
+            .find(|ip| !is_trusted(ip))
+    })
+}
+
+/// Reads the client address from proxy headers when the socket peer is a
+/// trusted proxy, else the socket peer itself.
+pub fn from_request(
+    headers: &HeaderMap,
+    extensions: &Extensions,
+    trusted: &[IpNet],
+) -> Option<String> {
+    let peer = extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_canonical())?;
+
+    let client = if trusted.iter().any(|net| net.contains(&peer)) {
+        from_headers(headers, trusted).unwrap_or(peer)
+    } else {
+        peer
+    };
+    Some(client.to_string())
+}
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+    Config: FromRef<S>,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let config = Config::from_ref(state);
+        Ok(ClientIp(from_request(
+            &parts.headers,
+            &parts.extensions,
+            &config.trusted_proxies,
+        )))
+    }
+}
@@ -0,0 +1,198 @@
+// src/utils/error_report.rs
+
+//! Turning handler panics into clean 500s, and reporting them.
+//!
+//! `CatchPanicLayer` stops a panicking handler from tearing down the connection.
+//! The panic hook installed by `install_panic_hook` records where the panic
+//! happened and a backtrace; the layer's handler then logs both and, when
+//! `SENTRY_DSN` is set, sends them to Sentry (or anything speaking its store API).
+
+use std::{any::Any, backtrace::Backtrace, cell::RefCell};
+
+use axum::response::{IntoResponse, Response};
+use rand::RngCore;
+use serde_json::json;
+use url::Url;
+
+use crate::error::AppError;
+
+thread_local! {
+    /// The last panic on this thread, left by the hook for the catch handler.
+    static LAST_PANIC: RefCell<Option<CapturedPanic>> = const { RefCell::new(None) };
+}
+
+/// What the panic hook saw.
+#[derive(Debug, Clone)]
+struct CapturedPanic {
+    location: Option<String>,
+    backtrace: String,
+}
+
+/// Records the location and a backtrace of every panic, then runs the previous hook.
+/// Call once at startup.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let captured = CapturedPanic {
+            location: info.location().map(|l| format!("{}:{}", l.file(), l.line())),
+            backtrace: Backtrace::force_capture().to_string(),
+        };
+        LAST_PANIC.with(|last| *last.borrow_mut() = Some(captured));
+        previous(info);
+    }));
+}
+
+/// A Sentry DSN split into the store endpoint and the public key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SentryDsn {
+    pub store_url: String,
+    pub public_key: String,
+}
+
+impl SentryDsn {
+    /// Parses `https://<key>@<host>[:port]/<project_id>`.
+    pub fn parse(dsn: &str) -> Option<Self> {
+        let url = Url::parse(dsn).ok()?;
+        let public_key = url.username();
+        let project_id = url.path().trim_matches('/');
+        if public_key.is_empty() || project_id.is_empty() {
+            return None;
+        }
+        let host = url.host_str()?;
+        let port = url.port().map(|p| format!(":{}", p)).unwrap_or_default();
+
+        Some(Self {
+            store_url: format!("{}://{}{}/api/{}/store/", url.scheme(), host, port, project_id),
+            public_key: public_key.to_string(),
+        })
+    }
+}
+
+/// Sends captured panics to an error tracker. Without a DSN it only logs.
+/// Cloning is cheap.
+#[derive(Clone)]
+pub struct ErrorReporter {
+    dsn: Option<SentryDsn>,
+    client: reqwest::Client,
+}
+
+impl ErrorReporter {
+    /// A reporter for `dsn`; an unparsable DSN is logged and reporting stays off.
+    pub fn new(dsn: Option<&str>) -> Self {
+        let dsn = dsn.and_then(|raw| {
+            let parsed = SentryDsn::parse(raw);
+            if parsed.is_none() {
+                tracing::warn!("Ignoring invalid SENTRY_DSN");
+            }
+            parsed
+        });
+        Self {
+            dsn,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Delivers an event in the background; delivery failures are only logged.
+    fn send(&self, event: serde_json::Value) {
+        let Some(dsn) = self.dsn.clone() else { return };
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            let auth = format!(
+                "Sentry sentry_version=7, sentry_client=ancient-arch/{}, sentry_key={}",
+                env!("CARGO_PKG_VERSION"),
+                dsn.public_key
+            );
+            let result = client
+                .post(&dsn.store_url)
+                .header("X-Sentry-Auth", auth)
+                .json(&event)
+                .send()
+                .await
+                .and_then(|res| res.error_for_status());
+            if let Err(e) = result {
+                tracing::warn!("Failed to report panic: {}", e);
+            }
+        });
+    }
+
+    /// Handler for `CatchPanicLayer::custom`: logs and reports the panic, then
+    /// answers with the usual JSON 500.
+    pub fn handle_panic(&self, payload: Box<dyn Any + Send + 'static>) -> Response {
+        let message = if let Some(s) = payload.downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "Box<dyn Any>".to_string()
+        };
+        let captured = LAST_PANIC.with(|last| last.borrow_mut().take());
+
+        match &captured {
+            Some(c) => tracing::error!(
+                "Handler panicked at {}: {}\n{}",
+                c.location.as_deref().unwrap_or("<unknown>"),
+                message,
+                c.backtrace
+            ),
+            None => tracing::error!("Handler panicked: {}", message),
+        }
+        self.send(panic_event(&message, captured.as_ref()));
+
+        AppError::InternalServerError(format!("panic: {}", message)).into_response()
+    }
+}
+
+/// Builds a Sentry store event for a panic.
+fn panic_event(message: &str, captured: Option<&CapturedPanic>) -> serde_json::Value {
+    let mut event_id = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut event_id);
+
+    let frames = captured
+        .map(|c| stack_frames(&c.backtrace))
+        .unwrap_or_default();
+
+    json!({
+        "event_id": hex::encode(event_id),
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "platform": "rust",
+        "level": "fatal",
+        "logger": "panic",
+        "release": concat!("backend@", env!("CARGO_PKG_VERSION")),
+        "exception": {
+            "values": [{
+                "type": "panic",
+                "value": message,
+                "stacktrace": { "frames": frames },
+            }]
+        },
+        "extra": {
+            "location": captured.and_then(|c| c.location.clone()),
+            "backtrace": captured.map(|c| c.backtrace.clone()),
+        },
+    })
+}
+
+/// Parses `std::backtrace` output into Sentry frames, oldest call first.
+fn stack_frames(backtrace: &str) -> Vec<serde_json::Value> {
+    let mut frames: Vec<serde_json::Value> = Vec::new();
+    for line in backtrace.lines().map(str::trim) {
+        if let Some((index, function)) = line.split_once(": ")
+            && index.chars().all(|c| c.is_ascii_digit())
+        {
+            frames.push(json!({ "function": function }));
+        } else if let Some(location) = line.strip_prefix("at ")
+            && let Some(frame) = frames.last_mut()
+        {
+            // "at /path/to/file.rs:12:5"
+            let mut parts = location.rsplitn(3, ':');
+            let _column = parts.next();
+            let line_no = parts.next().and_then(|l| l.parse::<u32>().ok());
+            if let (Some(lineno), Some(filename)) = (line_no, parts.next()) {
+                frame["filename"] = json!(filename);
+                frame["lineno"] = json!(lineno);
+            }
+        }
+    }
+    frames.reverse();
+    frames
+}
@@ -5,6 +5,7 @@ use argon2::{
     Argon2,
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
 };
+use sha2::{Digest, Sha256};
 
 /// Hashes a password using Argon2 algorithm.
 ///
@@ -39,3 +40,13 @@ pub fn verify_password(password: &str, password_hash: &str) -> Result<bool, AppE
         Err(_) => Ok(false),
     }
 }
+
+/// A random single-use token to hand out (in an email link, for instance).
+pub fn new_token() -> String {
+    hex::encode(rand::random::<[u8; 32]>())
+}
+
+/// SHA-256 of a token, as stored instead of the token itself.
+pub fn token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
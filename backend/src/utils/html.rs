@@ -1,4 +1,17 @@
+use std::sync::LazyLock;
+
 use ammonia;
+use pulldown_cmark::{Options, Parser, html::push_html};
+
+use regex::Regex;
+
+use crate::config::{EXCERPT_CHARS, READING_CJK_CHARS_PER_MINUTE, READING_WORDS_PER_MINUTE};
+
+/// Ends of elements whose text is separate from what follows, like table
+/// cells, which rendered Markdown writes without whitespace between them.
+static BLOCK_END_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)</(p|div|li|td|th|h[1-6]|blockquote|pre)>|<br\s*/?>").unwrap()
+});
 
 /// Clean HTML content using the ammonia library.
 /// 
@@ -14,3 +27,60 @@ use ammonia;
 pub fn clean_html(input: &str) -> String {
     ammonia::clean(input)
 }
+
+/// Renders Markdown to sanitized HTML. Tables and ~~strikethrough~~ are
+/// supported; HTML written in the Markdown goes through `clean_html` with
+/// the rest.
+pub fn render_markdown(markdown: &str) -> String {
+    let mut html = String::new();
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    push_html(&mut html, Parser::new_ext(markdown, options));
+    clean_html(html.trim_end())
+}
+
+/// The text of sanitized HTML, with tags removed and whitespace collapsed.
+/// The result is plain text, not HTML.
+pub fn plain_text(html: &str) -> String {
+    let spaced = BLOCK_END_RE.replace_all(html, "$0 ");
+    let text = ammonia::Builder::empty().clean(&spaced).to_string();
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The start of the plain text, cut to `EXCERPT_CHARS` characters with an ellipsis.
+pub fn excerpt(html: &str) -> String {
+    let text = plain_text(html);
+    match text.char_indices().nth(EXCERPT_CHARS) {
+        Some((cut, _)) => format!("{}…", text[..cut].trim_end()),
+        None => text,
+    }
+}
+
+/// Number of `<img>` elements in sanitized HTML.
+pub fn image_count(html: &str) -> i32 {
+    html.match_indices("<img")
+        .filter(|(i, _)| matches!(html[i + 4..].chars().next(), Some(' ' | '>' | '/')))
+        .count() as i32
+}
+
+/// Estimated minutes to read, at least 1. Chinese and Japanese characters are
+/// counted one by one, other text by words.
+pub fn reading_time_minutes(html: &str) -> i32 {
+    let text = plain_text(html);
+    let is_cjk = |c: char| {
+        matches!(c, '\u{3040}'..='\u{30ff}' | '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{f900}'..='\u{faff}')
+    };
+    let cjk_chars = text.chars().filter(|c| is_cjk(*c)).count();
+    let words = text
+        .split(|c: char| c.is_whitespace() || is_cjk(c))
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .count();
+
+    let minutes = cjk_chars as f64 / READING_CJK_CHARS_PER_MINUTE as f64
+        + words as f64 / READING_WORDS_PER_MINUTE as f64;
+    (minutes.ceil() as i32).max(1)
+}
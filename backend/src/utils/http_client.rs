@@ -0,0 +1,265 @@
+// src/utils/http_client.rs
+
+//! Hardened client for every outbound HTTP request (link previews, webhooks, ...).
+//!
+//! URLs usually come from users, so each request and every redirect hop is
+//! checked before connecting: only http(s), no credentials, host not denied,
+//! and every resolved address public. The connection is pinned to the checked
+//! addresses so a second DNS answer cannot point it at an internal service.
+//! Proxy settings from the environment are ignored, since a proxy would
+//! connect to the host itself and skip the pinning.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use reqwest::{Method, header};
+use url::Url;
+
+/// Hosts no integration may reach, whatever its own policy says.
+/// Suffix match, so subdomains are covered.
+const ALWAYS_DENIED_HOSTS: [&str; 3] = ["localhost", "local", "internal"];
+
+/// Per-integration limits for outbound requests.
+#[derive(Debug, Clone)]
+pub struct OutboundPolicy {
+    /// When non-empty, only these hosts (and their subdomains) may be contacted.
+    pub allowed_domains: &'static [&'static str],
+    /// Extra hosts to refuse on top of the built-in list.
+    pub denied_domains: &'static [&'static str],
+    pub timeout: Duration,
+    /// Redirects followed for GET and HEAD requests. Other methods never follow redirects.
+    pub max_redirects: usize,
+    /// The body is truncated after this many bytes.
+    pub max_body_bytes: usize,
+    pub user_agent: &'static str,
+}
+
+impl Default for OutboundPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_domains: &[],
+            denied_domains: &[],
+            timeout: Duration::from_secs(5),
+            max_redirects: 3,
+            max_body_bytes: 1024 * 1024,
+            user_agent: "ancient-arch/1.0",
+        }
+    }
+}
+
+/// A completed response with its (possibly truncated) body.
+#[derive(Debug)]
+pub struct OutboundResponse {
+    /// The URL that produced this response, after redirects.
+    pub url: Url,
+    pub status: reqwest::StatusCode,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum OutboundError {
+    /// The URL or a redirect target is not allowed; retrying will not help.
+    Blocked(String),
+    /// Connection, DNS or timeout failure; may succeed later.
+    Network(String),
+}
+
+impl std::fmt::Display for OutboundError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutboundError::Blocked(reason) => write!(f, "blocked: {}", reason),
+            OutboundError::Network(reason) => write!(f, "network error: {}", reason),
+        }
+    }
+}
+
+/// Sends a GET request, following redirects up to the policy limit.
+pub async fn get(url: &str, policy: &OutboundPolicy) -> Result<OutboundResponse, OutboundError> {
+    send(Method::GET, url, None, policy).await
+}
+
+/// Sends a HEAD request, following redirects up to the policy limit.
+pub async fn head(url: &str, policy: &OutboundPolicy) -> Result<OutboundResponse, OutboundError> {
+    send(Method::HEAD, url, None, policy).await
+}
+
+/// Sends a JSON POST request. Redirects are returned, not followed.
+pub async fn post_json(
+    url: &str,
+    body: &serde_json::Value,
+    policy: &OutboundPolicy,
+) -> Result<OutboundResponse, OutboundError> {
+    send(Method::POST, url, Some(body), policy).await
+}
+
+async fn send(
+    method: Method,
+    url: &str,
+    json: Option<&serde_json::Value>,
+    policy: &OutboundPolicy,
+) -> Result<OutboundResponse, OutboundError> {
+    let mut current =
+        Url::parse(url).map_err(|e| OutboundError::Blocked(format!("Invalid URL: {}", e)))?;
+    let follows_redirects = method == Method::GET || method == Method::HEAD;
+    let max_redirects = if follows_redirects { policy.max_redirects } else { 0 };
+
+    for _ in 0..=max_redirects {
+        let (host, addrs) = resolve_public(&current, policy).await?;
+
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .no_proxy()
+            .timeout(policy.timeout)
+            .resolve_to_addrs(&host, &addrs)
+            .user_agent(policy.user_agent)
+            .build()
+            .map_err(|e| OutboundError::Network(e.to_string()))?;
+
+        let mut request = client.request(method.clone(), current.clone());
+        if let Some(json) = json {
+            request = request.json(json);
+        }
+        let mut resp = request
+            .send()
+            .await
+            .map_err(|e| OutboundError::Network(e.to_string()))?;
+
+        if resp.status().is_redirection() && follows_redirects {
+            let location = resp
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| OutboundError::Blocked("Redirect without location".to_string()))?;
+            current = current
+                .join(location)
+                .map_err(|e| OutboundError::Blocked(format!("Invalid redirect: {}", e)))?;
+            continue;
+        }
+
+        let content_type = resp
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+
+        let mut body = Vec::new();
+        while let Some(chunk) = resp
+            .chunk()
+            .await
+            .map_err(|e| OutboundError::Network(e.to_string()))?
+        {
+            body.extend_from_slice(&chunk);
+            if body.len() >= policy.max_body_bytes {
+                body.truncate(policy.max_body_bytes);
+                break;
+            }
+        }
+
+        return Ok(OutboundResponse {
+            url: current,
+            status: resp.status(),
+            content_type,
+            body,
+        });
+    }
+
+    Err(OutboundError::Blocked("Too many redirects".to_string()))
+}
+
+/// Checks that a URL may be contacted and resolves it to public addresses only.
+async fn resolve_public(
+    url: &Url,
+    policy: &OutboundPolicy,
+) -> Result<(String, Vec<SocketAddr>), OutboundError> {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(OutboundError::Blocked(format!(
+            "Scheme '{}' not allowed",
+            url.scheme()
+        )));
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(OutboundError::Blocked("Credentials in URL not allowed".to_string()));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| OutboundError::Blocked("URL has no host".to_string()))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase();
+
+    let matches = |domain: &&str| host == *domain || host.ends_with(&format!(".{}", domain));
+    if ALWAYS_DENIED_HOSTS.iter().any(matches) || policy.denied_domains.iter().any(matches) {
+        return Err(OutboundError::Blocked(format!("Host '{}' is denied", host)));
+    }
+    if !policy.allowed_domains.is_empty() && !policy.allowed_domains.iter().any(matches) {
+        return Err(OutboundError::Blocked(format!("Host '{}' is not allowed", host)));
+    }
+
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+        .await
+        .map_err(|e| OutboundError::Network(format!("DNS lookup failed: {}", e)))?
+        .collect();
+
+    if addrs.is_empty() {
+        return Err(OutboundError::Blocked("Host has no addresses".to_string()));
+    }
+    if let Some(addr) = addrs.iter().find(|a| !is_public_ip(a.ip())) {
+        return Err(OutboundError::Blocked(format!(
+            "Non-public address {}",
+            addr.ip()
+        )));
+    }
+
+    Ok((host, addrs))
+}
+
+/// False for loopback, private, link-local, CGNAT, multicast and other reserved
+/// ranges, including IPv6 addresses that carry a non-public IPv4 address.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_public_ipv4(v4),
+        IpAddr::V6(v6) => {
+            if let Some(v4) = embedded_ipv4(v6) {
+                return is_public_ipv4(v4);
+            }
+            let [first, second, ..] = v6.segments();
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00 // unique local fc00::/7
+                || (first & 0xffc0) == 0xfe80 // link-local fe80::/10
+                || (first == 0x64 && second == 0xff9b)) // local-use NAT64 64:ff9b:1::/48
+        }
+    }
+}
+
+/// The IPv4 address an IPv6 address routes to: IPv4-mapped (::ffff:0:0/96),
+/// IPv4-compatible (::/96), NAT64 (64:ff9b::/96) and 6to4 (2002::/16).
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let s = ip.segments();
+    let from = |hi: u16, lo: u16| Ipv4Addr::from((u32::from(hi) << 16) | u32::from(lo));
+    match s {
+        [0, 0, 0, 0, 0, 0xffff | 0, ..] | [0x64, 0xff9b, 0, 0, 0, 0, ..] => Some(from(s[6], s[7])),
+        [0x2002, ..] => Some(from(s[1], s[2])),
+        _ => None,
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b)) // CGNAT 100.64.0.0/10
+        || (a == 198 && (18..20).contains(&b)) // benchmarking 198.18.0.0/15
+        || a >= 240)
+}
@@ -0,0 +1,166 @@
+// src/utils/image_preview.rs
+
+//! Placeholders for images: a dominant color and a [BlurHash](https://blurha.sh),
+//! computed from a tiny preview of the image.
+//!
+//! JPEGs and PNGs are decoded with the `image` crate and averaged down to a
+//! grid of at most `PREVIEW_SIDE` cells a side; the hash itself comes from the
+//! `blurhash` crate. Other formats yield no preview.
+
+use std::{collections::HashMap, io::Cursor};
+
+use image::{ImageFormat, ImageReader};
+
+/// Longest side of the preview grid placeholders are computed from.
+const PREVIEW_SIDE: usize = 32;
+/// Images with more pixels than this are not decoded.
+const MAX_PIXELS: u64 = 50_000_000;
+
+/// A downscaled image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preview {
+    /// Size of the original image, in pixels.
+    pub width: u32,
+    pub height: u32,
+    /// Size of the preview grid.
+    pub grid_width: usize,
+    pub grid_height: usize,
+    /// Row-major RGB, `grid_width * grid_height` of them.
+    pub pixels: Vec<[u8; 3]>,
+}
+
+/// Decodes a JPEG or PNG into a preview, or None if the format is not supported
+/// or the data is damaged.
+pub fn decode(bytes: &[u8]) -> Option<Preview> {
+    let format = image::guess_format(bytes).ok()?;
+    if !matches!(format, ImageFormat::Jpeg | ImageFormat::Png) {
+        return None;
+    }
+    let reader = || ImageReader::with_format(Cursor::new(bytes), format);
+
+    let (width, height) = reader().into_dimensions().ok()?;
+    if width == 0 || height == 0 || u64::from(width) * u64::from(height) > MAX_PIXELS {
+        return None;
+    }
+    let image = reader().decode().ok()?.into_rgba8();
+
+    let mut scaler = Downscaler::new(width as usize, height as usize);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let [r, g, b, alpha] = pixel.0;
+        scaler.add(x as usize, y as usize, [r, g, b], f64::from(alpha) / 255.0);
+    }
+    Some(scaler.finish(width, height))
+}
+
+/// The most common color of the preview, as `#rrggbb`.
+///
+/// Colors are grouped by their top four bits per channel; the average of the
+/// largest group is returned, so a photo of a red hall against a blue sky gives
+/// the sky's blue rather than a muddy mix.
+pub fn dominant_color(preview: &Preview) -> String {
+    let mut groups: HashMap<u16, (u32, [u32; 3])> = HashMap::new();
+    for [r, g, b] in &preview.pixels {
+        let key = (u16::from(r >> 4) << 8) | (u16::from(g >> 4) << 4) | u16::from(b >> 4);
+        let (count, sum) = groups.entry(key).or_default();
+        *count += 1;
+        sum[0] += u32::from(*r);
+        sum[1] += u32::from(*g);
+        sum[2] += u32::from(*b);
+    }
+
+    let (count, sum) = groups
+        .into_iter()
+        .max_by_key(|(key, (count, _))| (*count, std::cmp::Reverse(*key)))
+        .map(|(_, group)| group)
+        .unwrap_or((1, [255, 255, 255]));
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        sum[0] / count,
+        sum[1] / count,
+        sum[2] / count
+    )
+}
+
+/// The BlurHash of the preview, with 4x3 components (3x4 for portrait images).
+pub fn blurhash(preview: &Preview) -> String {
+    let (cx, cy) = if preview.grid_width >= preview.grid_height {
+        (4, 3)
+    } else {
+        (3, 4)
+    };
+    let rgba: Vec<u8> = preview
+        .pixels
+        .iter()
+        .flat_map(|[r, g, b]| [*r, *g, *b, 255])
+        .collect();
+    blurhash::encode(
+        cx,
+        cy,
+        preview.grid_width as u32,
+        preview.grid_height as u32,
+        &rgba,
+    )
+    .expect("component counts are within 1-9")
+}
+
+/// Averages pixels of an image into a grid of at most `PREVIEW_SIDE` per side.
+struct Downscaler {
+    src_width: usize,
+    src_height: usize,
+    grid_width: usize,
+    grid_height: usize,
+    /// Weighted RGB sums and the total weight per cell.
+    cells: Vec<[f64; 4]>,
+}
+
+impl Downscaler {
+    fn new(src_width: usize, src_height: usize) -> Self {
+        let side = src_width.max(src_height).min(PREVIEW_SIDE);
+        let grid_width = (src_width * side)
+            .div_ceil(src_width.max(src_height))
+            .max(1);
+        let grid_height = (src_height * side)
+            .div_ceil(src_width.max(src_height))
+            .max(1);
+        Downscaler {
+            src_width,
+            src_height,
+            grid_width,
+            grid_height,
+            cells: vec![[0.0; 4]; grid_width * grid_height],
+        }
+    }
+
+    /// Adds the pixel at (x, y); transparent pixels have less weight.
+    fn add(&mut self, x: usize, y: usize, rgb: [u8; 3], weight: f64) {
+        let gx = x * self.grid_width / self.src_width;
+        let gy = y * self.grid_height / self.src_height;
+        let cell = &mut self.cells[gy * self.grid_width + gx];
+        for c in 0..3 {
+            cell[c] += f64::from(rgb[c]) * weight;
+        }
+        cell[3] += weight;
+    }
+
+    /// The preview; cells without any opaque pixel are white.
+    fn finish(self, width: u32, height: u32) -> Preview {
+        let pixels = self
+            .cells
+            .iter()
+            .map(|cell| {
+                if cell[3] == 0.0 {
+                    [255, 255, 255]
+                } else {
+                    [0, 1, 2].map(|c| (cell[c] / cell[3]).round().clamp(0.0, 255.0) as u8)
+                }
+            })
+            .collect();
+        Preview {
+            width,
+            height,
+            grid_width: self.grid_width,
+            grid_height: self.grid_height,
+            pixels,
+        }
+    }
+}
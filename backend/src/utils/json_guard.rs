@@ -0,0 +1,125 @@
+// src/utils/json_guard.rs
+
+//! Limits on the shape of JSON request bodies.
+//!
+//! serde happily parses a 2MB body of nested arrays, and validation only runs
+//! after that. This middleware refuses oversized bodies (413) and bodies nested
+//! too deeply or with overlong arrays (400) before any handler parses them.
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::Request,
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    config::{MAX_JSON_ARRAY_LEN, MAX_JSON_BODY_BYTES, MAX_JSON_DEPTH},
+    error::AppError,
+};
+
+/// Why a JSON body was refused.
+#[derive(Debug, PartialEq)]
+pub enum JsonShapeError {
+    TooDeep,
+    ArrayTooLong,
+}
+
+/// Checks nesting depth and array lengths with a single scan, without parsing.
+/// Malformed JSON is let through for the extractor to report.
+pub fn check_shape(body: &[u8], max_depth: usize, max_array_len: usize) -> Result<(), JsonShapeError> {
+    // One entry per open container: the number of commas so far for arrays, None for objects.
+    let mut open: Vec<Option<usize>> = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for &byte in body {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                if open.len() >= max_depth {
+                    return Err(JsonShapeError::TooDeep);
+                }
+                open.push((byte == b'[').then_some(0));
+            }
+            b']' | b'}' => {
+                open.pop();
+            }
+            b',' => {
+                if let Some(Some(commas)) = open.last_mut() {
+                    *commas += 1;
+                    if *commas >= max_array_len {
+                        return Err(JsonShapeError::ArrayTooLong);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// JSON Guard Middleware.
+///
+/// Buffers JSON bodies up to `MAX_JSON_BODY_BYTES` and checks their shape.
+/// Other content types pass through; extractors apply their own limits.
+pub async fn json_guard_middleware(req: Request, next: Next) -> Response {
+    let is_json = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<mime::Mime>().ok())
+        .is_some_and(|m| {
+            m.type_() == mime::APPLICATION
+                && (m.subtype() == mime::JSON || m.suffix() == Some(mime::JSON))
+        });
+    if !is_json {
+        return next.run(req).await;
+    }
+
+    let too_large = || {
+        AppError::PayloadTooLarge(format!(
+            "Request body exceeds {} KB",
+            MAX_JSON_BODY_BYTES / 1024
+        ))
+        .into_response()
+    };
+
+    let declared = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    if declared.is_some_and(|len| len > MAX_JSON_BODY_BYTES) {
+        return too_large();
+    }
+
+    let (parts, body) = req.into_parts();
+    let Ok(bytes) = to_bytes(body, MAX_JSON_BODY_BYTES).await else {
+        return too_large();
+    };
+
+    if let Err(e) = check_shape(&bytes, MAX_JSON_DEPTH, MAX_JSON_ARRAY_LEN) {
+        let message = match e {
+            JsonShapeError::TooDeep => {
+                format!("JSON nested deeper than {} levels", MAX_JSON_DEPTH)
+            }
+            JsonShapeError::ArrayTooLong => {
+                format!("JSON array longer than {} items", MAX_JSON_ARRAY_LEN)
+            }
+        };
+        return AppError::BadRequest(message).into_response();
+    }
+
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
@@ -1,30 +1,126 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use axum::{
+    Json,
     body::Body,
     extract::{FromRef, FromRequestParts, State},
-    http::{Request, StatusCode, header, request::Parts},
+    http::{Method, Request, StatusCode, header, request::Parts},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 
-use crate::{config::Config, error::AppError};
+use crate::{
+    config::Config,
+    error::AppError,
+    services::{api_usage, bans, sessions, token_revocation, two_factor},
+    utils::{
+        permissions::{self, Permission, Role},
+        presence::Presence,
+        role_cache::RoleCache,
+        usage_sampler::UsageSampler,
+    },
+};
 
 /// JWT Claims structure.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Claims {
     /// Subject - Stores the User ID (as string).
     pub sub: String,
-    /// User's role (e.g., 'user', 'admin').
+    /// User's role (e.g., 'user', 'moderator', 'admin').
     pub role: String,
     /// Expiration time as Unix timestamp.
     pub exp: usize,
+    /// Random token ID, so tokens issued in the same second differ and can be
+    /// revoked one at a time. Missing from tokens issued before logout existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// Whether the user passed two-factor authentication when the token was
+    /// issued. Admins with 2FA enabled need such a token for the admin API.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mfa: bool,
+}
+
+/// The authenticated caller, for routes behind `auth_middleware`.
+///
+/// Parses the token subject once and checks that the user still exists, so a
+/// malformed or stale token is rejected with 401 instead of acting as user 0.
+/// `role` is read from the database, not the token.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: i64,
+    pub role: String,
 }
 
-/// A custom extractor that only allows verified users or admins.
+impl AuthUser {
+    /// Whether the user's role grants `permission`.
+    pub fn can(&self, permission: Permission) -> bool {
+        permissions::can(&self.role, permission)
+    }
+
+    /// Resolves token claims to an existing user.
+    async fn from_claims(claims: &Claims, pool: &PgPool) -> Result<Self, AppError> {
+        let id = subject_id(claims)
+            .ok_or(AppError::AuthError("Invalid token subject".to_string()))?;
+
+        let role = sqlx::query_scalar!("SELECT role FROM users WHERE id = $1", id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(AppError::AuthError("User no longer exists".to_string()))?;
+
+        Ok(AuthUser { id, role })
+    }
+}
+
+impl<S> FromRequestParts<S> for AuthUser
+where
+    PgPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let claims = parts
+            .extensions
+            .get::<Claims>()
+            .ok_or(AppError::AuthError("Missing or invalid token".to_string()))?;
+
+        AuthUser::from_claims(claims, &PgPool::from_ref(state)).await
+    }
+}
+
+/// The caller on routes behind `optional_auth_middleware`. Anonymous requests,
+/// and tokens that no longer resolve to a user, give `None`.
+pub struct OptionalAuthUser(pub Option<AuthUser>);
+
+impl<S> FromRequestParts<S> for OptionalAuthUser
+where
+    PgPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Some(claims) = parts.extensions.get::<Claims>() else {
+            return Ok(OptionalAuthUser(None));
+        };
+
+        match AuthUser::from_claims(claims, &PgPool::from_ref(state)).await {
+            Ok(user) => Ok(OptionalAuthUser(Some(user))),
+            Err(AppError::AuthError(_)) => Ok(OptionalAuthUser(None)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// The user ID in the token subject, if it is one.
+pub fn subject_id(claims: &Claims) -> Option<i64> {
+    claims.sub.parse::<i64>().ok().filter(|id| *id > 0)
+}
+
+/// A custom extractor that only allows verified users or staff.
 pub struct VerifiedUser {
     pub id: i64,
 }
@@ -43,18 +139,23 @@ where
         let config = Config::from_ref(state);
 
         // 2. Extract and verify Token
-        let claims = extract_claims_from_header(&parts.headers, &config.jwt_secret)
+        let token = bearer_token(&parts.headers)
             .ok_or(AppError::AuthError("Missing or invalid token".to_string()))?;
+        let claims = verify_jwt(token, &config.jwt_secret)?;
+        if token_revocation::is_revoked(&pool, token).await? {
+            return Err(AppError::AuthError("Token has been revoked".to_string()));
+        }
 
-        let user_id = claims.sub.parse::<i64>().unwrap_or(0);
+        let user_id = subject_id(&claims)
+            .ok_or(AppError::AuthError("Invalid token subject".to_string()))?;
 
         // 3. Check DB status
         let user = sqlx::query!("SELECT is_verified, role FROM users WHERE id = $1", user_id)
             .fetch_optional(&pool)
             .await?
-            .ok_or(AppError::NotFound("User not found".to_string()))?;
+            .ok_or(AppError::AuthError("User no longer exists".to_string()))?;
 
-        if user.is_verified || user.role == "admin" {
+        if user.is_verified || Role::parse(&user.role).is_some_and(Role::is_staff) {
             Ok(VerifiedUser { id: user_id })
         } else {
             Err(AppError::AuthError(
@@ -64,13 +165,15 @@ where
     }
 }
 
-/// Signs a new JWT for the user.
+/// Signs a new JWT for the user. `mfa` marks a sign-in that passed two-factor
+/// authentication.
 pub fn sign_jwt(
     id: i64,
     _username: &str,
     role: &str,
     secret: &str,
     expiration_seconds: u64,
+    mfa: bool,
 ) -> Result<String, AppError> {
     let expiration = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -82,6 +185,8 @@ pub fn sign_jwt(
         sub: id.to_string(),
         role: role.to_owned(),
         exp: expiration,
+        jti: Some(hex::encode(rand::random::<[u8; 16]>())),
+        mfa,
     };
 
     encode(
@@ -92,13 +197,18 @@ pub fn sign_jwt(
     .map_err(|e| AppError::InternalServerError(e.to_string()))
 }
 
-/// Helper to extract and verify JWT from Authorization header.
-pub fn extract_claims_from_header(headers: &header::HeaderMap, secret: &str) -> Option<Claims> {
+/// The token in a `Bearer` Authorization header.
+pub fn bearer_token(headers: &header::HeaderMap) -> Option<&str> {
     headers
         .get(header::AUTHORIZATION)
         .and_then(|value| value.to_str().ok())
-        .filter(|auth| auth.starts_with("Bearer "))
-        .and_then(|auth| verify_jwt(&auth[7..], secret).ok())
+        .and_then(|auth| auth.strip_prefix("Bearer "))
+}
+
+/// Helper to extract and verify JWT from Authorization header.
+/// Does not consult the revocation list; the auth middlewares do.
+pub fn extract_claims_from_header(headers: &header::HeaderMap, secret: &str) -> Option<Claims> {
+    bearer_token(headers).and_then(|token| verify_jwt(token, secret).ok())
 }
 
 /// Verifies and decodes a JWT string.
@@ -113,42 +223,157 @@ pub fn verify_jwt(token: &str, secret: &str) -> Result<Claims, AppError> {
     Ok(token_data.claims)
 }
 
+/// Whether the token was revoked, e.g. by logging out.
+async fn check_revoked(pool: &PgPool, token: &str) -> Result<bool, StatusCode> {
+    token_revocation::is_revoked(pool, token).await.map_err(|e| {
+        tracing::error!("Failed to check token revocation: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Notes the use of the token's session. Best effort: a failure is logged.
+async fn touch_session(pool: &PgPool, token: &str) {
+    if let Err(e) = sessions::touch(pool, token).await {
+        tracing::warn!("Failed to note session use: {}", e);
+    }
+}
+
+/// A 403 for writes (anything but GET, HEAD and OPTIONS) by a banned user,
+/// saying why and until when. Banned users can still read.
+async fn refuse_banned_write(
+    pool: &PgPool,
+    method: &Method,
+    user_id: i64,
+) -> Result<Option<Response>, StatusCode> {
+    if method.is_safe() {
+        return Ok(None);
+    }
+    let ban = bans::active(pool, user_id).await.map_err(|e| {
+        tracing::error!("Failed to check for a ban: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(ban.map(|ban| {
+        let body = Json(serde_json::json!({
+            "error": "Your account is banned",
+            "code": "banned",
+            "banned_until": ban.banned_until,
+            "reason": ban.reason,
+        }));
+        (StatusCode::FORBIDDEN, body).into_response()
+    }))
+}
+
+/// Counts a request towards the user's API usage. Best effort: a failure is logged.
+async fn note_usage(pool: &PgPool, usage: &UsageSampler, user_id: i64) {
+    if usage.hit(user_id)
+        && let Err(e) = api_usage::record(pool, user_id, usage.every().into()).await
+    {
+        tracing::warn!("Failed to record API usage: {}", e);
+    }
+}
+
 /// Mandatory Authentication Middleware.
+/// Revoked tokens are rejected. Each authenticated request also counts as a
+/// presence heartbeat, as a use of the token's session and towards the user's
+/// API usage. Banned users may only read.
 pub async fn auth_middleware(
     State(config): State<Config>,
+    State(pool): State<PgPool>,
+    State(presence): State<Presence>,
+    State(usage): State<UsageSampler>,
     mut req: Request<Body>,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    if let Some(claims) = extract_claims_from_header(req.headers(), &config.jwt_secret) {
-        req.extensions_mut().insert(claims);
-        Ok(next.run(req).await)
-    } else {
-        Err(StatusCode::UNAUTHORIZED)
+    let token = bearer_token(req.headers()).ok_or(StatusCode::UNAUTHORIZED)?;
+    let claims = verify_jwt(token, &config.jwt_secret).map_err(|_| StatusCode::UNAUTHORIZED)?;
+    let user_id = subject_id(&claims).ok_or(StatusCode::UNAUTHORIZED)?;
+    if check_revoked(&pool, token).await? {
+        return Err(StatusCode::UNAUTHORIZED);
     }
+
+    presence.touch(user_id);
+    touch_session(&pool, token).await;
+    note_usage(&pool, &usage, user_id).await;
+    if let Some(refusal) = refuse_banned_write(&pool, req.method(), user_id).await? {
+        return Ok(refusal);
+    }
+    req.extensions_mut().insert(claims);
+    Ok(next.run(req).await)
 }
 
 /// Optional Authentication Middleware.
+/// Requests with a revoked token are treated as anonymous; banned users may
+/// only read, as with `auth_middleware`.
 pub async fn optional_auth_middleware(
     State(config): State<Config>,
+    State(pool): State<PgPool>,
+    State(presence): State<Presence>,
+    State(usage): State<UsageSampler>,
     mut req: Request<Body>,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    if let Some(claims) = extract_claims_from_header(req.headers(), &config.jwt_secret) {
+    if let Some(token) = bearer_token(req.headers())
+        && let Ok(claims) = verify_jwt(token, &config.jwt_secret)
+        && let Some(user_id) = subject_id(&claims)
+        && !check_revoked(&pool, token).await?
+    {
+        presence.touch(user_id);
+        touch_session(&pool, token).await;
+        note_usage(&pool, &usage, user_id).await;
+        if let Some(refusal) = refuse_banned_write(&pool, req.method(), user_id).await? {
+            return Ok(refusal);
+        }
         req.extensions_mut().insert(claims);
     }
     Ok(next.run(req).await)
 }
 
 /// Admin Authorization Middleware (Must follow auth_middleware).
-pub async fn admin_middleware(req: Request<Body>, next: Next) -> Result<Response, StatusCode> {
+/// Lets staff through and records their [`Role`] for `permissions::guard`.
+/// Unless `revalidate_admin_role` is off, the role is the current one from the
+/// database rather than the one the token was issued with. Staff who turned on
+/// two-factor authentication are refused with a token issued without it.
+pub async fn admin_middleware(
+    State(config): State<Config>,
+    State(pool): State<PgPool>,
+    State(roles): State<RoleCache>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Result<Response, StatusCode> {
     let claims = req
         .extensions()
         .get::<Claims>()
         .ok_or(StatusCode::UNAUTHORIZED)?;
 
-    if claims.role != "admin" {
-        return Err(StatusCode::FORBIDDEN);
+    let role = if config.revalidate_admin_role {
+        let user_id = subject_id(claims).ok_or(StatusCode::UNAUTHORIZED)?;
+        roles
+            .role(&pool, user_id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to look up role: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+            .ok_or(StatusCode::UNAUTHORIZED)?
+    } else {
+        claims.role.clone()
+    };
+
+    let role = Role::parse(&role)
+        .filter(|role| role.is_staff())
+        .ok_or(StatusCode::FORBIDDEN)?;
+
+    if !claims.mfa {
+        let user_id = subject_id(claims).ok_or(StatusCode::UNAUTHORIZED)?;
+        let enrolled = two_factor::is_enabled(&pool, user_id).await.map_err(|e| {
+            tracing::error!("Failed to look up two-factor status: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        if enrolled {
+            return Err(StatusCode::FORBIDDEN);
+        }
     }
 
+    req.extensions_mut().insert(role);
     Ok(next.run(req).await)
 }
@@ -0,0 +1,50 @@
+// src/utils/lite.rs
+
+//! Low-bandwidth responses for list endpoints.
+//!
+//! A client sending `X-Lite: 1` gets summaries instead of full rows: long
+//! HTML fields become a short plain-text `summary` and image galleries are
+//! left out. Responses say `Vary: X-Lite` so caches keep the two apart.
+
+use std::convert::Infallible;
+
+use axum::{
+    Json,
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+pub const LITE_HEADER: &str = "x-lite";
+
+/// Whether the client asked for lite responses (`X-Lite: 1` or `true`).
+pub struct Lite(pub bool);
+
+impl<S: Send + Sync> FromRequestParts<S> for Lite {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let enabled = parts
+            .headers
+            .get(LITE_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.trim() == "1" || v.trim().eq_ignore_ascii_case("true"));
+        Ok(Lite(enabled))
+    }
+}
+
+/// Serializes `items` as they are, or converted to their summary type `L`.
+pub fn respond<T, L>(lite: bool, items: Vec<T>) -> Response
+where
+    T: Serialize,
+    L: Serialize + From<T>,
+{
+    let vary = [(header::VARY, LITE_HEADER)];
+    if lite {
+        let summaries: Vec<L> = items.into_iter().map(L::from).collect();
+        (vary, Json(summaries)).into_response()
+    } else {
+        (vary, Json(items)).into_response()
+    }
+}
@@ -0,0 +1,98 @@
+// src/utils/locale.rs
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{HeaderMap, header, request::Parts},
+};
+use sqlx::PgPool;
+
+use crate::{config::Config, error::AppError, utils::jwt::{extract_claims_from_header, subject_id}};
+
+/// Locale the source content is written in.
+pub const DEFAULT_LOCALE: &str = "zh-CN";
+
+/// Locales users may pick and translations may be stored in.
+pub const SUPPORTED_LOCALES: [&str; 5] = ["zh-CN", "zh-TW", "en", "ja", "ko"];
+
+/// Returns the canonical spelling of a supported locale ("EN" -> "en").
+pub fn canonical_locale(tag: &str) -> Option<&'static str> {
+    SUPPORTED_LOCALES
+        .iter()
+        .find(|l| l.eq_ignore_ascii_case(tag.trim()))
+        .copied()
+}
+
+/// Maps a language tag to the closest supported locale, falling back to the
+/// primary subtag ("en-US" -> "en", "zh-HK" -> "zh-TW", "zh" -> "zh-CN").
+fn closest_locale(tag: &str) -> Option<&'static str> {
+    if let Some(exact) = canonical_locale(tag) {
+        return Some(exact);
+    }
+    let lower = tag.trim().to_ascii_lowercase();
+    match lower.as_str() {
+        "zh-hk" | "zh-mo" | "zh-hant" => return Some("zh-TW"),
+        "zh-sg" | "zh-hans" => return Some("zh-CN"),
+        _ => {}
+    }
+    let primary = lower.split('-').next()?;
+    SUPPORTED_LOCALES
+        .iter()
+        .find(|l| l.split('-').next().is_some_and(|p| p.eq_ignore_ascii_case(primary)))
+        .copied()
+}
+
+/// Picks the best supported locale from an `Accept-Language` header, honouring q-values.
+pub fn from_accept_language(headers: &HeaderMap) -> Option<&'static str> {
+    let value = headers.get(header::ACCEPT_LANGUAGE)?.to_str().ok()?;
+
+    let mut ranges: Vec<(f32, &str)> = value
+        .split(',')
+        .filter_map(|part| {
+            let mut pieces = part.split(';');
+            let tag = pieces.next()?.trim();
+            let q = pieces
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.parse().ok())
+                .unwrap_or(1.0);
+            (!tag.is_empty() && tag != "*" && q > 0.0).then_some((q, tag))
+        })
+        .collect();
+    // Stable sort keeps header order among equal weights.
+    ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    ranges.into_iter().find_map(|(_, tag)| closest_locale(tag))
+}
+
+/// The locale to serve content in: the logged-in user's saved preference,
+/// else `Accept-Language`, else [`DEFAULT_LOCALE`].
+pub struct PreferredLocale(pub String);
+
+impl<S> FromRequestParts<S> for PreferredLocale
+where
+    PgPool: FromRef<S>,
+    Config: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let config = Config::from_ref(state);
+
+        if let Some(user_id) = extract_claims_from_header(&parts.headers, &config.jwt_secret)
+            .as_ref()
+            .and_then(subject_id)
+        {
+            let pool = PgPool::from_ref(state);
+            let saved = sqlx::query_scalar!("SELECT locale FROM users WHERE id = $1", user_id)
+                .fetch_optional(&pool)
+                .await?
+                .flatten();
+            if let Some(locale) = saved {
+                return Ok(PreferredLocale(locale));
+            }
+        }
+
+        let locale = from_accept_language(&parts.headers).unwrap_or(DEFAULT_LOCALE);
+        Ok(PreferredLocale(locale.to_string()))
+    }
+}
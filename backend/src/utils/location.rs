@@ -0,0 +1,112 @@
+// src/utils/location.rs
+
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+/// Longest province, city or district name kept; the `architectures` columns
+/// are VARCHAR(50).
+const MAX_PART_CHARS: usize = 50;
+
+/// Province-level municipalities, which act as their own city.
+const MUNICIPALITIES: [&str; 4] = ["北京", "天津", "上海", "重庆"];
+
+static CN_LOCATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?P<province>(?:北京|天津|上海|重庆)市?|[^省]+?省|.+?自治区|.+?特别行政区)?(?P<city>.+?(?:自治州|地区|盟|市))?(?P<district>.+?(?:区|县|旗|市))?",
+    )
+    .unwrap()
+});
+
+/// Structured form of a free-text `location`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct ParsedLocation {
+    pub province: Option<String>,
+    pub city: Option<String>,
+    pub district: Option<String>,
+}
+
+impl ParsedLocation {
+    pub fn is_empty(&self) -> bool {
+        self.province.is_none() && self.city.is_none() && self.district.is_none()
+    }
+}
+
+/// Best-effort split of a location string into province/city/district.
+///
+/// Handles Chinese addresses ("山西省大同市云冈区") and comma-separated
+/// English ones ("Yungang District, Datong, Shanxi"). Parts longer than
+/// `MAX_PART_CHARS` are not names and are left out. Returns an empty
+/// `ParsedLocation` when nothing could be recognised.
+pub fn parse_location(input: &str) -> ParsedLocation {
+    let input = input.trim();
+    let mut parsed = if input.is_ascii() {
+        parse_english(input)
+    } else {
+        parse_chinese(input)
+    };
+    for part in [&mut parsed.province, &mut parsed.city, &mut parsed.district] {
+        if part.as_ref().is_some_and(|p| p.chars().count() > MAX_PART_CHARS) {
+            *part = None;
+        }
+    }
+    parsed
+}
+
+fn parse_chinese(input: &str) -> ParsedLocation {
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let Some(caps) = CN_LOCATION.captures(&compact) else {
+        return ParsedLocation::default();
+    };
+    let field = |name: &str| caps.name(name).map(|m| m.as_str().to_string());
+
+    let mut parsed = ParsedLocation {
+        province: field("province"),
+        city: field("city"),
+        district: field("district"),
+    };
+
+    if let Some(p) = &parsed.province
+        && MUNICIPALITIES.contains(&p.trim_end_matches('市'))
+    {
+        let full = format!("{}市", p.trim_end_matches('市'));
+        parsed.province = Some(full.clone());
+        if parsed.city.is_none() {
+            parsed.city = Some(full);
+        }
+    }
+
+    parsed
+}
+
+fn parse_english(input: &str) -> ParsedLocation {
+    let mut parts: Vec<String> = input
+        .split(',')
+        .map(|p| strip_english_suffix(p.trim()))
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    if parts
+        .last()
+        .is_some_and(|p| p.eq_ignore_ascii_case("china") || p.eq_ignore_ascii_case("prc"))
+    {
+        parts.pop();
+    }
+
+    // Most specific first: "District, City, Province".
+    let mut rev = parts.into_iter().rev();
+    ParsedLocation {
+        province: rev.next(),
+        city: rev.next(),
+        district: rev.next(),
+    }
+}
+
+fn strip_english_suffix(part: &str) -> String {
+    for suffix in [" Province", " City", " District", " County"] {
+        if part.len() > suffix.len() && part[part.len() - suffix.len()..].eq_ignore_ascii_case(suffix) {
+            return part[..part.len() - suffix.len()].trim().to_string();
+        }
+    }
+    part.to_string()
+}
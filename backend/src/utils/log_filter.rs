@@ -0,0 +1,66 @@
+// src/utils/log_filter.rs
+
+use std::sync::{Arc, RwLock};
+
+use tracing_subscriber::{EnvFilter, Registry, reload};
+
+/// Reload handle for the global `EnvFilter` installed in `main`.
+pub type FilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Runtime control over the log filter, so debug logging can be switched on
+/// without a restart. Cloning is cheap and clones control the same filter.
+///
+/// A detached control (what `AppState::new` and tests get) has no subscriber
+/// to reload and rejects changes.
+#[derive(Clone)]
+pub struct LogFilter {
+    handle: Option<FilterHandle>,
+    current: Arc<RwLock<String>>,
+}
+
+impl LogFilter {
+    /// Controls the filter behind `handle`, which was built from `initial`.
+    pub fn new(handle: FilterHandle, initial: &str) -> Self {
+        Self {
+            handle: Some(handle),
+            current: Arc::new(RwLock::new(initial.to_string())),
+        }
+    }
+
+    /// A control with nothing to reload, reporting `initial` as current.
+    pub fn detached(initial: &str) -> Self {
+        Self {
+            handle: None,
+            current: Arc::new(RwLock::new(initial.to_string())),
+        }
+    }
+
+    /// The directives currently in effect, e.g. "info,backend=debug".
+    pub fn current(&self) -> String {
+        self.current.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replaces the filter. Fails on invalid directives or when detached.
+    pub fn set(&self, directives: &str) -> Result<(), LogFilterError> {
+        let filter =
+            EnvFilter::try_new(directives).map_err(|e| LogFilterError::Invalid(e.to_string()))?;
+        let handle = self.handle.as_ref().ok_or(LogFilterError::Detached)?;
+        handle
+            .reload(filter)
+            .map_err(|e| LogFilterError::Reload(e.to_string()))?;
+
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = directives.to_string();
+        Ok(())
+    }
+}
+
+/// Why the filter could not be changed.
+#[derive(Debug)]
+pub enum LogFilterError {
+    /// The directives do not parse.
+    Invalid(String),
+    /// No reloadable subscriber is installed.
+    Detached,
+    /// The subscriber is gone.
+    Reload(String),
+}
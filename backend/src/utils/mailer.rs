@@ -0,0 +1,122 @@
+// src/utils/mailer.rs
+
+//! Outgoing email.
+//!
+//! Mail is never sent from a request: handlers `queue` a message in the same
+//! transaction as the change it is about, and the job queue hands it to the
+//! [`Mailer`] chosen by `MAIL_TRANSPORT`, retrying failed deliveries.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::PgExecutor;
+
+use crate::{
+    config::{MailConfig, MailTransport},
+    jobs::queue,
+};
+
+/// Job kind that delivers one email.
+pub const SEND_EMAIL_JOB: &str = "send_email";
+
+/// A plain-text message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug)]
+pub struct MailError(pub String);
+
+impl std::fmt::Display for MailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "mail error: {}", self.0)
+    }
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &Email) -> Result<(), MailError>;
+}
+
+/// Writes messages to the application log instead of sending them; for development.
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        tracing::info!(
+            "Email to {} (not sent, MAIL_TRANSPORT=log): {}\n{}",
+            email.to,
+            email.subject,
+            email.body
+        );
+        Ok(())
+    }
+}
+
+/// Posts messages as JSON (`from`, `to`, `subject`, `text`) to a mail relay's HTTP API.
+///
+/// The endpoint comes from configuration rather than users, so unlike
+/// `utils::http_client` it may be on the internal network.
+pub struct HttpMailer {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+    from: String,
+}
+
+impl HttpMailer {
+    pub fn new(url: &str, api_key: Option<&str>, from: &str) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .expect("Failed to build mail HTTP client"),
+            url: url.to_string(),
+            api_key: api_key.map(str::to_string),
+            from: from.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for HttpMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        let mut request = self.client.post(&self.url).json(&serde_json::json!({
+            "from": self.from,
+            "to": email.to,
+            "subject": email.subject,
+            "text": email.body,
+        }));
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let resp = request.send().await.map_err(|e| MailError(e.to_string()))?;
+        if !resp.status().is_success() {
+            return Err(MailError(format!("relay returned HTTP {}", resp.status())));
+        }
+        Ok(())
+    }
+}
+
+/// Builds the transport selected in the configuration.
+pub fn from_config(config: &MailConfig) -> Arc<dyn Mailer> {
+    match &config.transport {
+        MailTransport::Log => Arc::new(LogMailer),
+        MailTransport::Http { url, api_key } => {
+            Arc::new(HttpMailer::new(url, api_key.as_deref(), &config.from))
+        }
+    }
+}
+
+/// Queues `email` for delivery. Pass a transaction to send it only if the
+/// surrounding change commits.
+pub async fn queue<'e, E: PgExecutor<'e>>(executor: E, email: &Email) -> Result<i64, sqlx::Error> {
+    let payload = serde_json::to_value(email).unwrap_or_default();
+    queue::enqueue(executor, SEND_EMAIL_JOB, payload).await
+}
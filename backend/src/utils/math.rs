@@ -0,0 +1,302 @@
+// src/utils/math.rs
+
+//! Server-side rendering of question options with inline math.
+//!
+//! Options are plain text in which `$...$` marks TeX-style math, e.g.
+//! `$\frac{1}{2}$ of the bay width` or `$3 \times 4$ dou`. [`render`] turns
+//! an option into HTML: text is escaped and math becomes MathML built from a
+//! small subset of TeX (fractions, roots, sub- and superscripts, Greek letters
+//! and the usual relations). Every element is produced here from escaped text,
+//! so the output is safe to insert as-is; unsupported commands are shown
+//! verbatim instead of failing. `\$` is a literal dollar sign, and a `$`
+//! without a closing one is kept as text.
+
+/// How deeply braces, fractions and roots may nest before the rest is shown
+/// as text. Options are short; this only guards against pathological input.
+const MAX_DEPTH: usize = 16;
+
+/// Renders an option's text as safe HTML.
+pub fn render(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(start) = find_dollar(rest) {
+        let Some(len) = find_dollar(&rest[start + 1..]) else {
+            break;
+        };
+        push_text(&mut out, &rest[..start]);
+        let math = &rest[start + 1..start + 1 + len];
+        out.push_str("<math>");
+        let mut parser = Parser {
+            chars: math.chars().collect(),
+            pos: 0,
+        };
+        parser.row(&mut out, 0, false);
+        out.push_str("</math>");
+        rest = &rest[start + 2 + len..];
+    }
+    push_text(&mut out, rest);
+    out
+}
+
+/// Byte offset of the first `$` not escaped as `\$`.
+fn find_dollar(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'$' => return Some(i),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Appends text outside math, escaped, with `\$` unescaped to `$`.
+fn push_text(out: &mut String, text: &str) {
+    escape_into(out, &text.replace("\\$", "$"));
+}
+
+fn escape_into(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+fn element(out: &mut String, tag: &str, text: &str) {
+    out.push('<');
+    out.push_str(tag);
+    out.push('>');
+    escape_into(out, text);
+    out.push_str("</");
+    out.push_str(tag);
+    out.push('>');
+}
+
+/// What a TeX command stands for.
+enum Symbol {
+    /// An identifier, e.g. a Greek letter.
+    Identifier(&'static str),
+    /// An operator or relation.
+    Operator(&'static str),
+}
+
+fn symbol(name: &str) -> Option<Symbol> {
+    use Symbol::{Identifier, Operator};
+    Some(match name {
+        "alpha" => Identifier("α"),
+        "beta" => Identifier("β"),
+        "gamma" => Identifier("γ"),
+        "delta" => Identifier("δ"),
+        "epsilon" => Identifier("ε"),
+        "theta" => Identifier("θ"),
+        "lambda" => Identifier("λ"),
+        "mu" => Identifier("μ"),
+        "pi" => Identifier("π"),
+        "rho" => Identifier("ρ"),
+        "sigma" => Identifier("σ"),
+        "phi" => Identifier("φ"),
+        "omega" => Identifier("ω"),
+        "Delta" => Identifier("Δ"),
+        "Theta" => Identifier("Θ"),
+        "Pi" => Identifier("Π"),
+        "Sigma" => Identifier("Σ"),
+        "Omega" => Identifier("Ω"),
+        "infty" => Identifier("∞"),
+        "circ" | "degree" => Operator("°"),
+        "times" => Operator("×"),
+        "div" => Operator("÷"),
+        "cdot" => Operator("⋅"),
+        "pm" => Operator("±"),
+        "le" | "leq" => Operator("≤"),
+        "ge" | "geq" => Operator("≥"),
+        "ne" | "neq" => Operator("≠"),
+        "approx" => Operator("≈"),
+        "sim" => Operator("∼"),
+        "cong" => Operator("≅"),
+        "propto" => Operator("∝"),
+        "angle" => Operator("∠"),
+        "perp" => Operator("⊥"),
+        "parallel" => Operator("∥"),
+        "triangle" => Operator("△"),
+        "%" => Operator("%"),
+        "$" => Operator("$"),
+        "," | ";" | " " => Operator("\u{2009}"),
+        _ => return None,
+    })
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Renders items up to the end, or up to the closing brace in a group.
+    fn row(&mut self, out: &mut String, depth: usize, in_group: bool) {
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                None => return,
+                Some('}') if in_group => {
+                    self.pos += 1;
+                    return;
+                }
+                Some(_) => self.item(out, depth),
+            }
+        }
+    }
+
+    /// An atom with its subscript and superscript, if any.
+    fn item(&mut self, out: &mut String, depth: usize) {
+        let mut base = String::new();
+        self.atom(&mut base, depth);
+
+        let mut sub = None;
+        let mut sup = None;
+        loop {
+            self.skip_spaces();
+            let slot = match self.peek() {
+                Some('_') if sub.is_none() => &mut sub,
+                Some('^') if sup.is_none() => &mut sup,
+                _ => break,
+            };
+            self.pos += 1;
+            let mut script = String::new();
+            self.skip_spaces();
+            self.atom(&mut script, depth);
+            *slot = Some(script);
+        }
+
+        match (sub, sup) {
+            (None, None) => out.push_str(&base),
+            (Some(sub), None) => out.push_str(&format!("<msub>{base}{sub}</msub>")),
+            (None, Some(sup)) => out.push_str(&format!("<msup>{base}{sup}</msup>")),
+            (Some(sub), Some(sup)) => out.push_str(&format!("<msubsup>{base}{sub}{sup}</msubsup>")),
+        }
+    }
+
+    /// A single element: a group, number, letter, command or operator.
+    fn atom(&mut self, out: &mut String, depth: usize) {
+        let Some(c) = self.peek() else {
+            out.push_str("<mrow></mrow>");
+            return;
+        };
+        self.pos += 1;
+
+        match c {
+            '{' if depth < MAX_DEPTH => {
+                out.push_str("<mrow>");
+                self.row(out, depth + 1, true);
+                out.push_str("</mrow>");
+            }
+            '0'..='9' | '.' => {
+                let mut number = c.to_string();
+                while let Some(d) = self.peek().filter(|d| d.is_ascii_digit() || *d == '.') {
+                    number.push(d);
+                    self.pos += 1;
+                }
+                element(out, "mn", &number);
+            }
+            '\\' => self.command(out, depth),
+            c if c.is_alphabetic() => element(out, "mi", &c.to_string()),
+            c => element(out, "mo", &c.to_string()),
+        }
+    }
+
+    /// The command after a backslash.
+    fn command(&mut self, out: &mut String, depth: usize) {
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+            name.push(c);
+            self.pos += 1;
+        }
+        if name.is_empty()
+            && let Some(c) = self.peek()
+        {
+            name.push(c);
+            self.pos += 1;
+        }
+
+        match name.as_str() {
+            "frac" if depth < MAX_DEPTH => {
+                out.push_str("<mfrac>");
+                self.argument(out, depth + 1);
+                self.argument(out, depth + 1);
+                out.push_str("</mfrac>");
+            }
+            "sqrt" if depth < MAX_DEPTH => {
+                self.skip_spaces();
+                if self.peek() == Some('[') {
+                    self.pos += 1;
+                    let mut index = String::new();
+                    while let Some(c) = self.peek().filter(|c| *c != ']') {
+                        index.push(c);
+                        self.pos += 1;
+                    }
+                    self.pos += 1;
+                    let mut radicand = String::new();
+                    self.argument(&mut radicand, depth + 1);
+                    out.push_str("<mroot>");
+                    out.push_str(&radicand);
+                    element(out, "mn", index.trim());
+                    out.push_str("</mroot>");
+                } else {
+                    out.push_str("<msqrt>");
+                    self.argument(out, depth + 1);
+                    out.push_str("</msqrt>");
+                }
+            }
+            "text" | "mathrm" => {
+                self.skip_spaces();
+                if self.peek() == Some('{') {
+                    self.pos += 1;
+                    let mut text = String::new();
+                    while let Some(c) = self.peek().filter(|c| *c != '}') {
+                        text.push(c);
+                        self.pos += 1;
+                    }
+                    self.pos += 1;
+                    element(out, "mtext", &text);
+                } else {
+                    element(out, "mtext", &format!("\\{name}"));
+                }
+            }
+            _ => match symbol(&name) {
+                Some(Symbol::Identifier(s)) => element(out, "mi", s),
+                Some(Symbol::Operator(s)) => element(out, "mo", s),
+                None => element(out, "mtext", &format!("\\{name}")),
+            },
+        }
+    }
+
+    /// A command's argument, always wrapped in one element.
+    fn argument(&mut self, out: &mut String, depth: usize) {
+        self.skip_spaces();
+        if self.peek() == Some('{') {
+            self.atom(out, depth);
+        } else {
+            out.push_str("<mrow>");
+            self.atom(out, depth);
+            out.push_str("</mrow>");
+        }
+    }
+}
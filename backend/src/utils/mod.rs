@@ -1,5 +1,29 @@
 // src/utils/mod.rs
 
+pub mod audit_stream;
+pub mod cache;
+pub mod client_ip;
+pub mod error_report;
 pub mod hash;
+pub mod http_client;
+pub mod image_preview;
+pub mod json_guard;
 pub mod jwt;
+pub mod lite;
+pub mod locale;
+pub mod location;
+pub mod log_filter;
+pub mod mailer;
+pub mod math;
 pub mod html;
+pub mod pdf;
+pub mod permissions;
+pub mod pinyin;
+pub mod presence;
+pub mod rate_limit;
+pub mod role_cache;
+pub mod text_length;
+pub mod timeout;
+pub mod totp;
+pub mod units;
+pub mod usage_sampler;
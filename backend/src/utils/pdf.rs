@@ -0,0 +1,414 @@
+// src/utils/pdf.rs
+
+//! PDF documents, written with `printpdf`.
+//!
+//! Text is set in the TrueType font from `PDF_FONT_PATH`, embedded in every
+//! document so Chinese and other non-Latin text renders in any reader. Without
+//! one the built-in Helvetica is used, which only covers Latin text. JPEGs are
+//! decoded and embedded as RGB pixels; other image formats are not supported.
+
+use std::{path::Path, sync::Arc};
+
+use image::ImageFormat;
+use printpdf::{
+    BuiltinFont, Color, ColorBits, ColorSpace, Image, ImageTransform, ImageXObject,
+    IndirectFontRef, Mm, PdfLayerReference, Pt, Px, Rect, Rgb, TextRenderingMode,
+    path::PaintMode,
+};
+
+pub use printpdf::Error as PdfError;
+
+/// A4 landscape, in points.
+pub const PAGE_WIDTH: f32 = 842.0;
+pub const PAGE_HEIGHT: f32 = 595.0;
+
+/// A TrueType or OpenType font to embed in documents.
+#[derive(Clone)]
+pub struct PdfFont(Arc<[u8]>);
+
+impl PdfFont {
+    /// Reads and checks the font file at `path`.
+    pub fn load(path: &Path) -> Result<Self, PdfError> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, PdfError> {
+        printpdf::PdfDocument::empty("").add_external_font(data.as_slice())?;
+        Ok(Self(data.into()))
+    }
+}
+
+impl std::fmt::Debug for PdfFont {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PdfFont({} bytes)", self.0.len())
+    }
+}
+
+/// Estimated width of `text` in points. Latin glyphs are approximated by
+/// Helvetica's average width, CJK characters are full width.
+pub fn text_width(text: &str, size: f32, bold: bool) -> f32 {
+    let ascii = if bold { 0.58 } else { 0.52 };
+    text.chars()
+        .map(|c| if c.is_ascii() { ascii } else { 1.0 })
+        .sum::<f32>()
+        * size
+}
+
+/// Breaks `text` into lines no wider than `max_width`: between words, and
+/// anywhere between CJK characters. Words longer than a line are split.
+pub fn wrap(text: &str, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for token in tokens(text) {
+        let candidate = if line.is_empty() {
+            token.trim_start().to_string()
+        } else {
+            format!("{}{}", line, token)
+        };
+        if text_width(&candidate, size, false) <= max_width {
+            line = candidate;
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in token.trim_start().chars() {
+            if !line.is_empty() && text_width(&format!("{}{}", line, c), size, false) > max_width {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push(c);
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Splits text into words (with their leading space) and single CJK characters.
+fn tokens(text: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !current.trim().is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            current = " ".to_string();
+        } else if c.is_ascii() {
+            current.push(c);
+        } else {
+            if !current.trim().is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            current.push(c);
+            tokens.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.trim().is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// A JPEG to embed, added to a document with [`PdfDocument::add_jpeg`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageId {
+    index: usize,
+    pub width: u32,
+    pub height: u32,
+}
+
+struct Bitmap {
+    width: u32,
+    height: u32,
+    rgb: Vec<u8>,
+}
+
+/// What a page draws, in points from its lower-left corner.
+#[derive(Debug)]
+enum Op {
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        bold: bool,
+        text: String,
+    },
+    Rect {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        line_width: f32,
+    },
+    FillRect {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        rgb: [u8; 3],
+    },
+    Image {
+        image: ImageId,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+    },
+}
+
+/// Collects what to draw on one page.
+#[derive(Debug)]
+pub struct PdfPage {
+    width: f32,
+    height: f32,
+    ops: Vec<Op>,
+}
+
+impl Default for PdfPage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfPage {
+    /// An A4 landscape page.
+    pub fn new() -> Self {
+        Self::with_size(PAGE_WIDTH, PAGE_HEIGHT)
+    }
+
+    /// An A4 portrait page.
+    pub fn portrait() -> Self {
+        Self::with_size(PAGE_HEIGHT, PAGE_WIDTH)
+    }
+
+    pub fn with_size(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            ops: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    /// Draws text with its baseline starting at (x, y). With an embedded font,
+    /// bold is simulated by also stroking the glyph outlines.
+    pub fn text(&mut self, x: f32, y: f32, size: f32, bold: bool, text: &str) -> &mut Self {
+        let text = text.chars().filter(|c| !c.is_control()).collect();
+        self.ops.push(Op::Text {
+            x,
+            y,
+            size,
+            bold,
+            text,
+        });
+        self
+    }
+
+    /// Draws text horizontally centered on the page.
+    pub fn centered_text(&mut self, y: f32, size: f32, bold: bool, text: &str) -> &mut Self {
+        let width = text_width(text, size, bold);
+        self.text((self.width - width) / 2.0, y, size, bold, text)
+    }
+
+    /// Strokes a rectangle with its lower-left corner at (x, y).
+    pub fn rect(&mut self, x: f32, y: f32, w: f32, h: f32, line_width: f32) -> &mut Self {
+        self.ops.push(Op::Rect {
+            x,
+            y,
+            w,
+            h,
+            line_width,
+        });
+        self
+    }
+
+    /// Fills a rectangle with an RGB color.
+    pub fn fill_rect(&mut self, x: f32, y: f32, w: f32, h: f32, rgb: [u8; 3]) -> &mut Self {
+        self.ops.push(Op::FillRect { x, y, w, h, rgb });
+        self
+    }
+
+    /// Draws an image of the document scaled to w x h, lower-left corner at (x, y).
+    pub fn image(&mut self, image: ImageId, x: f32, y: f32, w: f32, h: f32) -> &mut Self {
+        self.ops.push(Op::Image { image, x, y, w, h });
+        self
+    }
+
+    /// Serializes the page alone into a complete PDF document.
+    pub fn finish(self, font: Option<&PdfFont>) -> Result<Vec<u8>, PdfError> {
+        let mut doc = PdfDocument::new(font);
+        doc.push(self);
+        doc.finish()
+    }
+}
+
+/// A document of one or more pages sharing a font and images.
+pub struct PdfDocument {
+    font: Option<PdfFont>,
+    pages: Vec<PdfPage>,
+    images: Vec<Bitmap>,
+}
+
+/// The fonts text is drawn in; regular and bold are the same embedded font.
+struct Fonts {
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    embedded: bool,
+}
+
+impl PdfDocument {
+    /// A document embedding `font`, or using Helvetica when there is none.
+    pub fn new(font: Option<&PdfFont>) -> Self {
+        Self {
+            font: font.cloned(),
+            pages: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, page: PdfPage) {
+        self.pages.push(page);
+    }
+
+    /// Adds a JPEG for pages to draw, or None if it cannot be decoded.
+    pub fn add_jpeg(&mut self, data: Vec<u8>) -> Option<ImageId> {
+        let decoded = image::load_from_memory_with_format(&data, ImageFormat::Jpeg)
+            .ok()?
+            .into_rgb8();
+        let (width, height) = decoded.dimensions();
+        self.images.push(Bitmap {
+            width,
+            height,
+            rgb: decoded.into_raw(),
+        });
+        Some(ImageId {
+            index: self.images.len() - 1,
+            width,
+            height,
+        })
+    }
+
+    /// Serializes the document.
+    pub fn finish(self) -> Result<Vec<u8>, PdfError> {
+        let mut pages = self.pages.into_iter();
+        let first = pages.next().unwrap_or_default();
+        let (doc, page, layer) = printpdf::PdfDocument::new(
+            "",
+            Mm::from(Pt(first.width)),
+            Mm::from(Pt(first.height)),
+            "",
+        );
+        let fonts = match &self.font {
+            Some(font) => {
+                let embedded = doc.add_external_font(&*font.0)?;
+                Fonts {
+                    regular: embedded.clone(),
+                    bold: embedded,
+                    embedded: true,
+                }
+            }
+            None => Fonts {
+                regular: doc.add_builtin_font(BuiltinFont::Helvetica)?,
+                bold: doc.add_builtin_font(BuiltinFont::HelveticaBold)?,
+                embedded: false,
+            },
+        };
+
+        draw(&doc.get_page(page).get_layer(layer), &first, &fonts, &self.images);
+        for next in pages {
+            let (page, layer) =
+                doc.add_page(Mm::from(Pt(next.width)), Mm::from(Pt(next.height)), "");
+            draw(&doc.get_page(page).get_layer(layer), &next, &fonts, &self.images);
+        }
+        doc.save_to_bytes()
+    }
+}
+
+fn mm(points: f32) -> Mm {
+    Mm::from(Pt(points))
+}
+
+fn draw(layer: &PdfLayerReference, page: &PdfPage, fonts: &Fonts, images: &[Bitmap]) {
+    for op in &page.ops {
+        match op {
+            Op::Text {
+                x,
+                y,
+                size,
+                bold,
+                text,
+            } => {
+                if *bold && fonts.embedded {
+                    layer.save_graphics_state();
+                    layer.set_text_rendering_mode(TextRenderingMode::FillStroke);
+                    layer.set_outline_thickness(size / 30.0);
+                    layer.use_text(text.as_str(), *size, mm(*x), mm(*y), &fonts.bold);
+                    layer.restore_graphics_state();
+                } else {
+                    let font = if *bold { &fonts.bold } else { &fonts.regular };
+                    layer.use_text(text.as_str(), *size, mm(*x), mm(*y), font);
+                }
+            }
+            Op::Rect {
+                x,
+                y,
+                w,
+                h,
+                line_width,
+            } => {
+                layer.save_graphics_state();
+                layer.set_outline_thickness(*line_width);
+                layer.add_rect(
+                    Rect::new(mm(*x), mm(*y), mm(x + w), mm(y + h)).with_mode(PaintMode::Stroke),
+                );
+                layer.restore_graphics_state();
+            }
+            Op::FillRect { x, y, w, h, rgb } => {
+                let [r, g, b] = rgb.map(|c| f32::from(c) / 255.0);
+                layer.save_graphics_state();
+                layer.set_fill_color(Color::Rgb(Rgb::new(r, g, b, None)));
+                layer.add_rect(
+                    Rect::new(mm(*x), mm(*y), mm(x + w), mm(y + h)).with_mode(PaintMode::Fill),
+                );
+                layer.restore_graphics_state();
+            }
+            Op::Image { image, x, y, w, h } => {
+                let Some(bitmap) = images.get(image.index) else {
+                    continue;
+                };
+                let xobject = ImageXObject {
+                    width: Px(bitmap.width as usize),
+                    height: Px(bitmap.height as usize),
+                    color_space: ColorSpace::Rgb,
+                    bits_per_component: ColorBits::Bit8,
+                    interpolate: true,
+                    image_data: bitmap.rgb.clone(),
+                    image_filter: None,
+                    smask: None,
+                    clipping_bbox: None,
+                };
+                // At 72 dpi one pixel is one point.
+                Image::from(xobject).add_to_layer(
+                    layer.clone(),
+                    ImageTransform {
+                        translate_x: Some(mm(*x)),
+                        translate_y: Some(mm(*y)),
+                        scale_x: Some(w / bitmap.width as f32),
+                        scale_y: Some(h / bitmap.height as f32),
+                        dpi: Some(72.0),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    }
+}
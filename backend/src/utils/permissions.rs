@@ -0,0 +1,115 @@
+// src/utils/permissions.rs
+
+//! Roles and what each may do.
+//!
+//! `users.role` names a [`Role`]; every role but `user` is staff and reaches
+//! the admin API through `admin_middleware`, where each group of routes is
+//! then [`guard`]ed by the [`Permission`] it needs. Handlers that also serve
+//! regular users check [`Role::can`] themselves. Unknown roles have no
+//! permissions.
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+};
+
+use crate::state::AppState;
+
+/// Something only some staff may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Accounts: roles, verification, exam accommodations, segments and broadcasts.
+    ManageUsers,
+    /// Deleting anyone's posts and comments, and looking into reports.
+    ModerateContent,
+    /// The catalog: architectures, glossary terms and their contributions.
+    ManageArchitectures,
+    /// The question bank, its flags, duplicates and contributions.
+    ManageQuestions,
+    /// Site settings: terms, exam events, experiments, the audit log and log levels.
+    ManageSite,
+}
+
+/// A user's role, as stored in `users.role`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    User,
+    Moderator,
+    Editor,
+    Admin,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::User, Role::Moderator, Role::Editor, Role::Admin];
+
+    /// The role named `name`, if there is one.
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|role| role.as_str() == name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Moderator => "moderator",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn permissions(self) -> &'static [Permission] {
+        match self {
+            Role::User => &[],
+            Role::Moderator => &[Permission::ModerateContent],
+            Role::Editor => &[Permission::ManageArchitectures],
+            Role::Admin => &[
+                Permission::ManageUsers,
+                Permission::ModerateContent,
+                Permission::ManageArchitectures,
+                Permission::ManageQuestions,
+                Permission::ManageSite,
+            ],
+        }
+    }
+
+    pub fn can(self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+
+    /// Whether the role has any permission, and so may use the admin API.
+    pub fn is_staff(self) -> bool {
+        !self.permissions().is_empty()
+    }
+}
+
+/// Whether users with the role named `role` may do `permission`.
+pub fn can(role: &str, permission: Permission) -> bool {
+    Role::parse(role).is_some_and(|role| role.can(permission))
+}
+
+/// Refuses callers whose role lacks `permission` with 403.
+/// Must follow `admin_middleware`, which records the caller's role.
+async fn require(
+    permission: Permission,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let role = req
+        .extensions()
+        .get::<Role>()
+        .copied()
+        .ok_or(StatusCode::FORBIDDEN)?;
+    if !role.can(permission) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(next.run(req).await)
+}
+
+/// Restricts `routes` to staff with `permission`.
+pub fn guard(routes: Router<AppState>, permission: Permission) -> Router<AppState> {
+    routes.route_layer(middleware::from_fn(move |req: Request<Body>, next: Next| {
+        require(permission, req, next)
+    }))
+}
@@ -0,0 +1,91 @@
+// src/utils/pinyin.rs
+
+//! Pinyin keys for Chinese search.
+//!
+//! Han characters are transliterated with `deunicode` (toneless pinyin,
+//! one syllable per character); ASCII words are kept as-is, lower-cased.
+//!
+//! `deunicode` knows one reading per character, so polyphonic characters
+//! can come out wrong (重庆 gives "zhong qing", not "chong qing"). Fuzzy
+//! matching hides most of this in search; a glossary slug that matters can
+//! be given explicitly.
+
+/// Splits text into lower-case pinyin syllables / ASCII words.
+fn tokens(text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut word = String::new();
+
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            word.push(c.to_ascii_lowercase());
+            continue;
+        }
+        if !word.is_empty() {
+            out.push(std::mem::take(&mut word));
+        }
+        if c.is_ascii() {
+            continue;
+        }
+        if let Some(latin) = deunicode::deunicode_char(c) {
+            let syllable: String = latin
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .map(|c| c.to_ascii_lowercase())
+                .collect();
+            if !syllable.is_empty() {
+                out.push(syllable);
+            }
+        }
+    }
+    if !word.is_empty() {
+        out.push(word);
+    }
+    out
+}
+
+/// Search keys stored alongside a name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PinyinKeys {
+    /// Space-separated syllables, e.g. "ying xian mu ta".
+    pub full: String,
+    /// First letter of each syllable, e.g. "yxmt".
+    pub initials: String,
+}
+
+pub fn pinyin_keys(text: &str) -> PinyinKeys {
+    let tokens = tokens(text);
+    PinyinKeys {
+        initials: tokens.iter().filter_map(|t| t.chars().next()).collect(),
+        full: tokens.join(" "),
+    }
+}
+
+/// Normalizes a search query to compact pinyin ("Yingxian Muta" and "应县木塔"
+/// both become "yingxianmuta"). Returns `None` if nothing searchable is left.
+pub fn normalize_query(query: &str) -> Option<String> {
+    let compact = tokens(query).concat();
+    (!compact.is_empty()).then_some(compact)
+}
+
+/// URL-friendly slug: pinyin syllables run together, words separated by '-'.
+/// "斗拱" -> "dougong", "Bracket set" -> "bracket-set".
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if c.is_ascii() {
+            if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        } else if let Some(latin) = deunicode::deunicode_char(c) {
+            slug.extend(
+                latin
+                    .chars()
+                    .filter(|c| c.is_ascii_alphanumeric())
+                    .map(|c| c.to_ascii_lowercase()),
+            );
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
@@ -0,0 +1,47 @@
+// src/utils/presence.rs
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+/// In-process record of when each authenticated user was last seen.
+///
+/// Every authenticated request counts as a heartbeat, so the numbers are
+/// "soft" real time: a user is online if they made a request within the window.
+/// Cloning is cheap and clones share the same map.
+#[derive(Debug, Clone, Default)]
+pub struct Presence {
+    last_seen: Arc<RwLock<HashMap<i64, Instant>>>,
+}
+
+impl Presence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the user as active now.
+    pub fn touch(&self, user_id: i64) {
+        let mut last_seen = self.last_seen.write().unwrap_or_else(|e| e.into_inner());
+        last_seen.insert(user_id, Instant::now());
+    }
+
+    /// Users seen within `window`, most recent first, with how long ago they were seen.
+    /// Stale entries are dropped as a side effect.
+    pub fn online(&self, window: Duration) -> Vec<(i64, Duration)> {
+        let mut last_seen = self.last_seen.write().unwrap_or_else(|e| e.into_inner());
+        last_seen.retain(|_, seen| seen.elapsed() < window);
+
+        let mut users: Vec<(i64, Duration)> = last_seen
+            .iter()
+            .map(|(id, seen)| (*id, seen.elapsed()))
+            .collect();
+        users.sort_by_key(|(_, ago)| *ago);
+        users
+    }
+
+    pub fn online_count(&self, window: Duration) -> usize {
+        self.online(window).len()
+    }
+}
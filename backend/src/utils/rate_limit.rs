@@ -0,0 +1,189 @@
+// src/utils/rate_limit.rs
+
+//! Request rate limits per route group, on top of `tower_governor`.
+//!
+//! Each limited group keeps a token bucket per client: `burst` requests at
+//! once, refilled at the configured rate. Clients are told apart by address,
+//! or by the user of a valid bearer token when keyed by user. Rejections
+//! answer 429 with `Retry-After` and are recorded as `rate_limited` security
+//! events, at most once a minute per client so a flood does not become one.
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Router,
+    extract::Request,
+    http::{self, Method},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use ipnet::IpNet;
+use sqlx::PgPool;
+use tower_governor::{
+    GovernorError, GovernorLayer, governor::GovernorConfigBuilder, key_extractor::KeyExtractor,
+};
+
+use crate::{
+    config::{Config, RateLimit, RateLimitKey},
+    error::AppError,
+    services::security_events,
+    state::AppState,
+    utils::{client_ip, jwt},
+};
+
+/// How often the buckets of clients that have gone quiet are dropped.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Whose budget a request counts against.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Client {
+    User(i64),
+    /// The client address; `None` when neither proxy headers nor the socket
+    /// peer give one, so all such requests share a bucket.
+    Ip(Option<String>),
+}
+
+/// Picks the [`Client`] of a request. Keyed by user when given the JWT secret.
+#[derive(Debug, Clone)]
+pub struct ClientKey {
+    jwt_secret: Option<Arc<str>>,
+    trusted_proxies: Arc<[IpNet]>,
+}
+
+impl ClientKey {
+    pub fn new(strategy: RateLimitKey, config: &Config) -> Self {
+        let jwt_secret =
+            (strategy == RateLimitKey::User).then(|| Arc::from(config.jwt_secret.as_str()));
+        Self {
+            jwt_secret,
+            trusted_proxies: config.trusted_proxies.as_slice().into(),
+        }
+    }
+
+    fn ip<T>(&self, req: &http::Request<T>) -> Option<String> {
+        client_ip::from_request(req.headers(), req.extensions(), &self.trusted_proxies)
+    }
+
+    fn client<T>(&self, req: &http::Request<T>) -> Client {
+        let user = self.jwt_secret.as_deref().and_then(|secret| {
+            jwt::extract_claims_from_header(req.headers(), secret)
+                .and_then(|claims| jwt::subject_id(&claims))
+        });
+        match user {
+            Some(id) => Client::User(id),
+            None => Client::Ip(self.ip(req)),
+        }
+    }
+}
+
+impl KeyExtractor for ClientKey {
+    type Key = Client;
+
+    fn extract<T>(&self, req: &http::Request<T>) -> Result<Self::Key, GovernorError> {
+        Ok(self.client(req))
+    }
+}
+
+/// Marks responses the limiter produced, for [`limit`] to record.
+#[derive(Debug, Clone, Copy)]
+struct Rejected;
+
+fn too_many_requests(error: GovernorError) -> Response {
+    let GovernorError::TooManyRequests { wait_time, .. } = error else {
+        return Response::from(error);
+    };
+    let mut response = AppError::RateLimited {
+        message: "Too many requests. Please slow down.".to_string(),
+        retry_after_secs: wait_time.max(1),
+    }
+    .into_response();
+    response.extensions_mut().insert(Rejected);
+    response
+}
+
+/// Limits `routes` to `limit` per client, counting only requests with one of
+/// `methods` (all when `None`). Without a limit the routes are left as they are.
+pub fn limit(
+    routes: Router<AppState>,
+    limit: Option<RateLimit>,
+    methods: Option<&[Method]>,
+    key: ClientKey,
+    pool: PgPool,
+) -> Router<AppState> {
+    let Some(limit) = limit else {
+        return routes;
+    };
+
+    let mut builder = GovernorConfigBuilder::default();
+    builder
+        .period(Duration::from_secs(60) / limit.per_minute)
+        .burst_size(limit.burst);
+    if let Some(methods) = methods {
+        builder.methods(methods.to_vec());
+    }
+    let config = builder
+        .key_extractor(key.clone())
+        .finish()
+        .expect("rate limits are positive");
+    let reports = GovernorConfigBuilder::default()
+        .period(Duration::from_secs(60))
+        .burst_size(1)
+        .key_extractor(key.clone())
+        .finish()
+        .expect("report limit is positive");
+
+    // The limiters live as long as the router does
+    let (limiter, report_limiter) = (
+        Arc::downgrade(config.limiter()),
+        Arc::downgrade(reports.limiter()),
+    );
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PRUNE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let (Some(limiter), Some(report_limiter)) = (limiter.upgrade(), report_limiter.upgrade())
+            else {
+                break;
+            };
+            limiter.retain_recent();
+            report_limiter.retain_recent();
+        }
+    });
+
+    let reports = Arc::new(reports);
+    routes
+        .layer(GovernorLayer::new(config).error_handler(too_many_requests))
+        .layer(middleware::from_fn(move |req: Request, next: Next| {
+            let (key, pool, reports) = (key.clone(), pool.clone(), reports.clone());
+            async move {
+                let client = key.client(&req);
+                let ip = key.ip(&req);
+                let path = req.uri().path().to_string();
+                let user_agent = req
+                    .headers()
+                    .get(http::header::USER_AGENT)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+
+                let response = next.run(req).await;
+                if response.extensions().get::<Rejected>().is_some()
+                    && reports.limiter().check_key(&client).is_ok()
+                {
+                    let user_id = match client {
+                        Client::User(id) => Some(id),
+                        Client::Ip(_) => None,
+                    };
+                    security_events::record(
+                        &pool,
+                        security_events::RATE_LIMITED,
+                        user_id,
+                        ip.as_deref(),
+                        user_agent.as_deref(),
+                        &path,
+                    )
+                    .await;
+                }
+                response
+            }
+        }))
+}
@@ -0,0 +1,50 @@
+// src/utils/role_cache.rs
+
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::{config::ADMIN_ROLE_CACHE_SECS, utils::cache::TtlCache};
+
+/// Users' current roles as stored in the database, cached for a few seconds.
+///
+/// Tokens carry the role they were issued with; `admin_middleware` checks it
+/// here instead, so a demoted or deleted admin loses access without waiting for
+/// their token to expire. `None` means the user no longer exists.
+/// Cloning is cheap and clones share the same entries.
+#[derive(Debug, Clone)]
+pub struct RoleCache {
+    roles: TtlCache<i64, Option<String>>,
+}
+
+impl Default for RoleCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RoleCache {
+    pub fn new() -> Self {
+        Self {
+            roles: TtlCache::new(Duration::from_secs(ADMIN_ROLE_CACHE_SECS)),
+        }
+    }
+
+    /// The user's current role, from the cache or the database.
+    pub async fn role(&self, pool: &PgPool, user_id: i64) -> Result<Option<String>, sqlx::Error> {
+        if let Some(role) = self.roles.get(&user_id) {
+            return Ok(role);
+        }
+
+        let role = sqlx::query_scalar!("SELECT role FROM users WHERE id = $1", user_id)
+            .fetch_optional(pool)
+            .await?;
+        self.roles.insert(user_id, role.clone());
+        Ok(role)
+    }
+
+    /// Forgets the cached role, so a change made through the API applies at once.
+    pub fn invalidate(&self, user_id: i64) {
+        self.roles.invalidate(&user_id);
+    }
+}
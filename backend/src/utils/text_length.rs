@@ -0,0 +1,139 @@
+// src/utils/text_length.rs
+
+//! Length validation that counts characters the way users see them.
+//!
+//! validator's `length` counts Unicode scalar values, so an emoji family
+//! (👨‍👩‍👧) or a letter written with a combining accent counts as several
+//! characters. The validators here count grapheme clusters instead, against
+//! the `*_LENGTH` limits in `config`.
+
+use std::borrow::Cow;
+
+use unicode_normalization::char::is_combining_mark;
+use validator::ValidationError;
+
+use crate::config::{
+    ADMIN_REASON_LENGTH, ARCH_DESCRIPTION_LENGTH, ARCH_LABEL_LENGTH, ARCH_LOCATION_LENGTH,
+    ARCH_NAME_LENGTH, COMMENT_LENGTH, COMPETITION_DESCRIPTION_LENGTH, COMPETITION_TITLE_LENGTH,
+    CONTRIBUTION_MESSAGE_LENGTH, DIRECT_MESSAGE_LENGTH,
+    EXAM_EVENT_TITLE_LENGTH,
+    FLAG_COMMENT_LENGTH,
+    GLOSSARY_DEFINITION_LENGTH, GLOSSARY_TERM_LENGTH, LengthLimit, POST_CONTENT_LENGTH,
+    POST_TITLE_LENGTH, PROFILE_BIO_LENGTH, PROFILE_LOCATION_LENGTH, QUESTION_ANALYSIS_LENGTH,
+    QUESTION_ANSWER_LENGTH, QUESTION_CONTENT_LENGTH, QUESTION_OPTION_LENGTH,
+    SERIES_DESCRIPTION_LENGTH, SERIES_TITLE_LENGTH, TAG_NAME_LENGTH, TERMS_CONTENT_LENGTH,
+    USERNAME_LENGTH,
+};
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+/// Characters that never start a grapheme cluster of their own.
+fn extends_previous(c: char) -> bool {
+    is_combining_mark(c)
+        || matches!(c,
+            ZERO_WIDTH_JOINER
+            // Variation selectors (text/emoji presentation)
+            | '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}'
+            // Emoji skin tone modifiers
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            // Emoji tag sequences (subdivision flags)
+            | '\u{E0020}'..='\u{E007F}'
+            // Hangul medial vowels and final consonants
+            | '\u{1160}'..='\u{11FF}'
+        )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c, '\u{1F1E6}'..='\u{1F1FF}')
+}
+
+/// The number of grapheme clusters in `s`.
+///
+/// Covers combining marks, emoji ZWJ sequences, modifiers and flags, and
+/// conjoining Hangul; CJK text counts one per character, as it does for users.
+pub fn grapheme_count(s: &str) -> usize {
+    let mut count = 0;
+    let mut previous: Option<char> = None;
+    // Regional indicators pair up into flags; odd ones start a new flag.
+    let mut open_flag = false;
+
+    for c in s.chars() {
+        let joined = match previous {
+            None => false,
+            Some(ZERO_WIDTH_JOINER) => true,
+            Some('\r') => c == '\n',
+            Some(_) if is_regional_indicator(c) => open_flag,
+            Some(_) => extends_previous(c),
+        };
+        if is_regional_indicator(c) {
+            open_flag = !joined;
+        } else if !extends_previous(c) {
+            open_flag = false;
+        }
+        if !joined {
+            count += 1;
+        }
+        previous = Some(c);
+    }
+    count
+}
+
+/// Checks that `value` is within `limit`, reporting a `length` error like validator's own.
+pub fn check(value: &str, limit: LengthLimit) -> Result<(), ValidationError> {
+    let count = grapheme_count(value);
+    if (limit.min..=limit.max).contains(&count) {
+        return Ok(());
+    }
+
+    let message = if limit.min == 0 {
+        format!("Must be at most {} characters.", limit.max)
+    } else {
+        format!("Must be between {} and {} characters.", limit.min, limit.max)
+    };
+    let mut error = ValidationError::new("length").with_message(Cow::from(message));
+    error.add_param(Cow::from("min"), &limit.min);
+    error.add_param(Cow::from("max"), &limit.max);
+    error.add_param(Cow::from("actual"), &count);
+    Err(error)
+}
+
+/// Defines one validator function per limit, for `#[validate(custom(function = ...))]`.
+macro_rules! length_validators {
+    ($($name:ident => $limit:ident),* $(,)?) => {
+        $(
+            pub fn $name(value: &str) -> Result<(), ValidationError> {
+                check(value, $limit)
+            }
+        )*
+    };
+}
+
+length_validators! {
+    username => USERNAME_LENGTH,
+    profile_bio => PROFILE_BIO_LENGTH,
+    profile_location => PROFILE_LOCATION_LENGTH,
+    post_title => POST_TITLE_LENGTH,
+    post_content => POST_CONTENT_LENGTH,
+    tag_name => TAG_NAME_LENGTH,
+    comment => COMMENT_LENGTH,
+    series_title => SERIES_TITLE_LENGTH,
+    series_description => SERIES_DESCRIPTION_LENGTH,
+    arch_name => ARCH_NAME_LENGTH,
+    arch_label => ARCH_LABEL_LENGTH,
+    arch_location => ARCH_LOCATION_LENGTH,
+    arch_description => ARCH_DESCRIPTION_LENGTH,
+    question_content => QUESTION_CONTENT_LENGTH,
+    question_option => QUESTION_OPTION_LENGTH,
+    question_answer => QUESTION_ANSWER_LENGTH,
+    question_analysis => QUESTION_ANALYSIS_LENGTH,
+    flag_comment => FLAG_COMMENT_LENGTH,
+    glossary_term => GLOSSARY_TERM_LENGTH,
+    glossary_definition => GLOSSARY_DEFINITION_LENGTH,
+    terms_content => TERMS_CONTENT_LENGTH,
+    admin_reason => ADMIN_REASON_LENGTH,
+    contribution_message => CONTRIBUTION_MESSAGE_LENGTH,
+    direct_message => DIRECT_MESSAGE_LENGTH,
+    exam_event_title => EXAM_EVENT_TITLE_LENGTH,
+    competition_title => COMPETITION_TITLE_LENGTH,
+    competition_description => COMPETITION_DESCRIPTION_LENGTH,
+}
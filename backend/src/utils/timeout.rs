@@ -0,0 +1,33 @@
+// src/utils/timeout.rs
+
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::error::AppError;
+
+/// Request Budget Middleware.
+///
+/// Answers 504 once the handler has run longer than the budget in state.
+/// The handler future is dropped, as it is when the client disconnects,
+/// which abandons its in-flight queries; `STATEMENT_TIMEOUT_SECS` makes
+/// Postgres cancel them too.
+pub async fn timeout_middleware(
+    State(budget): State<Duration>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let path = req.uri().path().to_string();
+    match tokio::time::timeout(budget, next.run(req)).await {
+        Ok(resp) => resp,
+        Err(_) => {
+            tracing::warn!("Request to {} exceeded its {:?} budget", path, budget);
+            AppError::Timeout("The request took too long. Please try again.".to_string())
+                .into_response()
+        }
+    }
+}
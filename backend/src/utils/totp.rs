@@ -0,0 +1,122 @@
+// src/utils/totp.rs
+
+//! Time-based one-time passwords (RFC 6238), as shown by authenticator apps.
+//!
+//! Codes are HMAC-SHA1 of the 30-second time step, truncated to six digits.
+//! Secrets are stored encrypted with AES-256-GCM under a key derived from
+//! `JWT_SECRET`, so a database dump alone does not reveal them; rotating the
+//! JWT secret therefore disables everyone's two-factor enrollment.
+
+use hmac::{Hmac, Mac};
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use sha1::Sha1;
+use sha2::Sha256;
+
+use crate::config::{TOTP_DIGITS, TOTP_ISSUER, TOTP_SKEW_STEPS, TOTP_STEP_SECS};
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// A new random 160-bit secret.
+pub fn new_secret() -> Vec<u8> {
+    rand::random::<[u8; 20]>().to_vec()
+}
+
+/// RFC 4648 base32 without padding, the form authenticator apps take secrets in.
+pub fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+/// The `otpauth://` URI to show as a QR code.
+pub fn provisioning_uri(account: &str, secret: &[u8]) -> String {
+    let label = format!("{}:{}", TOTP_ISSUER, account);
+    let mut url = url::Url::parse("otpauth://totp/").expect("static URL");
+    url.path_segments_mut().expect("static URL").push(&label);
+    url.query_pairs_mut()
+        .append_pair("secret", &base32_encode(secret))
+        .append_pair("issuer", TOTP_ISSUER)
+        .append_pair("digits", &TOTP_DIGITS.to_string())
+        .append_pair("period", &TOTP_STEP_SECS.to_string());
+    url.into()
+}
+
+/// The code for time step `step`.
+pub fn code_at(secret: &[u8], step: i64) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let value = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        value % 10u32.pow(TOTP_DIGITS),
+        width = TOTP_DIGITS as usize
+    )
+}
+
+/// The time step a Unix timestamp falls in.
+pub fn step_at(unix_secs: i64) -> i64 {
+    unix_secs.div_euclid(TOTP_STEP_SECS)
+}
+
+/// The step `code` is valid for, allowing `TOTP_SKEW_STEPS` of clock drift
+/// either way, if it is valid now.
+pub fn verify(secret: &[u8], code: &str, unix_secs: i64) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != TOTP_DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let now = step_at(unix_secs);
+    (now - TOTP_SKEW_STEPS..=now + TOTP_SKEW_STEPS).find(|&step| code_at(secret, step) == code)
+}
+
+fn cipher(jwt_secret: &str) -> LessSafeKey {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(jwt_secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(b"ancient-arch totp secrets");
+    let key = mac.finalize().into_bytes();
+    LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &key).expect("key is 256 bits"))
+}
+
+/// Encrypts a secret for storage: a random nonce followed by the sealed secret.
+pub fn encrypt_secret(jwt_secret: &str, secret: &[u8]) -> Vec<u8> {
+    let nonce = rand::random::<[u8; NONCE_LEN]>();
+    let mut sealed = secret.to_vec();
+    cipher(jwt_secret)
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut sealed)
+        .expect("secret is small enough to seal");
+
+    let mut stored = nonce.to_vec();
+    stored.append(&mut sealed);
+    stored
+}
+
+/// Decrypts a stored secret. `None` if it was encrypted under another key.
+pub fn decrypt_secret(jwt_secret: &str, stored: &[u8]) -> Option<Vec<u8>> {
+    if stored.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, sealed) = stored.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+    let mut buffer = sealed.to_vec();
+    let secret = cipher(jwt_secret).open_in_place(nonce, Aad::empty(), &mut buffer).ok()?;
+    Some(secret.to_vec())
+}
@@ -0,0 +1,87 @@
+// src/utils/units.rs
+
+//! Units for architecture dimensions.
+//!
+//! Dimensions are stored in metres and square metres. Admins and contributors
+//! may give them as a plain number in those units, or as text with a unit such
+//! as `"32.5 m"`, `"120 ft"`, `"10 丈"`, `"1200 m²"` or `"2 亩"`. Traditional
+//! units follow the modern market system: 1 尺 = 1/3 m, 1 丈 = 10 尺 and
+//! 1 亩 = 2000/3 m².
+
+use serde::{Deserialize, Serialize};
+
+const METRES_PER_FOOT: f64 = 0.3048;
+const SQUARE_METRES_PER_SQUARE_FOOT: f64 = METRES_PER_FOOT * METRES_PER_FOOT;
+
+/// A length or area as given in a request: a number in the base unit, or text
+/// with a unit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Measure {
+    Number(f64),
+    Text(String),
+}
+
+impl Measure {
+    /// The length in metres, or None for an unknown unit or a value that is
+    /// not a positive finite number.
+    pub fn metres(&self) -> Option<f64> {
+        self.convert(length_factor)
+    }
+
+    /// The area in square metres, on the same terms as [`Measure::metres`].
+    pub fn square_metres(&self) -> Option<f64> {
+        self.convert(area_factor)
+    }
+
+    fn convert(&self, factor: fn(&str) -> Option<f64>) -> Option<f64> {
+        let value = match self {
+            Measure::Number(n) => *n,
+            Measure::Text(text) => {
+                let text = text.trim();
+                let split = text
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                    .unwrap_or(text.len());
+                let (number, unit) = text.split_at(split);
+                number.parse::<f64>().ok()? * factor(&unit.trim().to_lowercase())?
+            }
+        };
+        (value.is_finite() && value > 0.0).then_some(value)
+    }
+}
+
+/// Metres per unit of length.
+fn length_factor(unit: &str) -> Option<f64> {
+    Some(match unit {
+        "" | "m" | "米" | "metre" | "metres" | "meter" | "meters" => 1.0,
+        "cm" | "厘米" => 0.01,
+        "ft" | "foot" | "feet" => METRES_PER_FOOT,
+        "尺" | "chi" => 1.0 / 3.0,
+        "丈" | "zhang" => 10.0 / 3.0,
+        _ => return None,
+    })
+}
+
+/// Square metres per unit of area.
+fn area_factor(unit: &str) -> Option<f64> {
+    Some(match unit {
+        "" | "m2" | "m²" | "㎡" | "sqm" | "平方米" => 1.0,
+        "ft2" | "ft²" | "sqft" | "sq ft" => SQUARE_METRES_PER_SQUARE_FOOT,
+        "亩" | "mu" => 2000.0 / 3.0,
+        "ha" | "公顷" => 10_000.0,
+        _ => return None,
+    })
+}
+
+pub fn metres_to_feet(metres: f64) -> f64 {
+    metres / METRES_PER_FOOT
+}
+
+pub fn square_metres_to_square_feet(square_metres: f64) -> f64 {
+    square_metres / SQUARE_METRES_PER_SQUARE_FOOT
+}
+
+/// Rounds a converted value for display, to two decimal places.
+pub fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
@@ -0,0 +1,45 @@
+// src/utils/usage_sampler.rs
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Decides which authenticated requests are written to `api_usage_daily`.
+///
+/// Counting every request would mean a database write per request, so each
+/// user's requests are counted in memory and every Nth one is recorded as N.
+/// Requests since a user's last recorded one are lost on restart.
+/// Cloning is cheap and clones share the same counts.
+#[derive(Debug, Clone)]
+pub struct UsageSampler {
+    every: u32,
+    counts: Arc<Mutex<HashMap<i64, u32>>>,
+}
+
+impl UsageSampler {
+    /// Records every `every`th request of each user.
+    pub fn new(every: u32) -> Self {
+        Self {
+            every: every.max(1),
+            counts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// How many requests each recorded one stands for.
+    pub fn every(&self) -> u32 {
+        self.every
+    }
+
+    /// Counts a request by the user; true if this one should be recorded.
+    pub fn hit(&self, user_id: i64) -> bool {
+        let mut counts = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        let count = counts.entry(user_id).or_insert(0);
+        *count += 1;
+        if *count < self.every {
+            return false;
+        }
+        counts.remove(&user_id);
+        true
+    }
+}
@@ -0,0 +1,4608 @@
+// tests/admin_tests.rs
+
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig,
+        RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    state::AppState,
+    services::question_selection,
+    utils::totp,
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+use std::net::SocketAddr;
+
+async fn spawn_app() -> (String, PgPool) {
+    spawn_app_with(AutoApprovalConfig::default()).await
+}
+
+async fn spawn_app_with(auto_approval: AutoApprovalConfig) -> (String, PgPool) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: "admin_test_secret".to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval,
+        pdf_font: None,
+        // As behind nginx, with an inner proxy hop on the private network
+        trusted_proxies: vec!["127.0.0.1/32".parse().unwrap(), "10.0.0.0/8".parse().unwrap()],
+    };
+
+    let state = AppState::new(pool.clone(), config);
+    let app = routes::create_router(state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+
+    (address, pool)
+}
+
+/// Registers a fresh user, optionally promotes/verifies them in the DB, and returns (id, token).
+async fn create_user(
+    client: &reqwest::Client,
+    address: &str,
+    pool: &PgPool,
+    prefix: &str,
+    role: &str,
+) -> (i64, String) {
+    let username = format!("{}_{}", prefix, &uuid::Uuid::new_v4().to_string()[..8]);
+    let password = "password123";
+
+    let user: serde_json::Value = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": password}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = user["id"].as_i64().unwrap();
+
+    sqlx::query!(
+        "UPDATE users SET role = $1, is_verified = TRUE WHERE id = $2",
+        role,
+        id
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": password}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    (id, login["token"].as_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn test_delete_user_dry_run() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let (victim_id, victim_token) = create_user(&client, &address, &pool, "vic", "user").await;
+
+    let post: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .header("Authorization", format!("Bearer {}", victim_token))
+        .json(&serde_json::json!({"title": "Doomed", "content": "Soon to be ghostly"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let post_id = post["id"].as_i64().unwrap();
+
+    client
+        .post(format!("{}/api/posts/{}/like", address, post_id))
+        .header("Authorization", format!("Bearer {}", victim_token))
+        .send()
+        .await
+        .unwrap();
+
+    // Act 1: Dry run reports the impact without deleting anything
+    let dry_resp = client
+        .delete(format!("{}/api/admin/users/{}?dry_run=true", address, victim_id))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(dry_resp.status().as_u16(), 200);
+    let impact: serde_json::Value = dry_resp.json().await.unwrap();
+    assert_eq!(impact["user_id"], victim_id);
+    assert_eq!(impact["posts_to_transfer"], 1);
+    assert_eq!(impact["comments_to_transfer"], 0);
+    assert_eq!(impact["likes_to_delete"], 1);
+
+    let still_there = sqlx::query!("SELECT id FROM users WHERE id = $1", victim_id)
+        .fetch_optional(&pool)
+        .await
+        .unwrap();
+    assert!(still_there.is_some(), "Dry run must not delete the user");
+
+    // Act 2: Real deletion
+    let del_resp = client
+        .delete(format!("{}/api/admin/users/{}", address, victim_id))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(del_resp.status().as_u16(), 204);
+
+    // Assert: The post survives under the ghost account
+    let owner = sqlx::query!(
+        "SELECT u.username FROM posts p JOIN users u ON p.user_id = u.id WHERE p.id = $1",
+        post_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(owner.username, "ghost");
+}
+
+#[tokio::test]
+async fn test_demoted_admin_loses_access_immediately() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let (rogue_id, rogue_token) = create_user(&client, &address, &pool, "rog", "admin").await;
+    let (quiet_id, quiet_token) = create_user(&client, &address, &pool, "qui", "admin").await;
+
+    let status = |token: String| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            client
+                .get(format!("{}/api/admin/users", address))
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        }
+    };
+    assert_eq!(status(rogue_token.clone()).await, 200);
+
+    // Act 1: Demote through the admin API while the old token is still valid
+    let resp = client
+        .put(format!("{}/api/admin/users/{}", address, rogue_id))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({ "role": "user" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // Assert: The token still says admin, the database doesn't
+    assert_eq!(status(rogue_token).await, 403);
+
+    // Act 2: Demote directly in the database
+    sqlx::query!("UPDATE users SET role = 'user' WHERE id = $1", quiet_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status(quiet_token).await, 403);
+    assert_eq!(status(admin_token).await, 200);
+}
+
+#[tokio::test]
+async fn test_staff_role_permissions() {
+    // Arrange: One of each role, and a post with a comment to moderate
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let (_, moderator_token) = create_user(&client, &address, &pool, "mod", "moderator").await;
+    let (_, editor_token) = create_user(&client, &address, &pool, "edi", "editor").await;
+    let (author_id, author_token) = create_user(&client, &address, &pool, "aut", "user").await;
+
+    let post: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .bearer_auth(&author_token)
+        .json(&serde_json::json!({"title": "Moderated", "content": "Under review"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let post_id = post["id"].as_i64().unwrap();
+    let comment: serde_json::Value = client
+        .post(format!("{}/api/posts/{}/comments", address, post_id))
+        .bearer_auth(&author_token)
+        .json(&serde_json::json!({"content": "Off topic"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let comment_url = format!("{}/api/posts/{}/comments/{}", address, post_id, comment["id"]);
+
+    let status = |method: reqwest::Method, path: &str, token: &str| {
+        client
+            .request(method, format!("{}/api/admin{}", address, path))
+            .bearer_auth(token)
+            .send()
+    };
+
+    // Act & Assert 1: Each role reaches its own admin routes only
+    for (path, allowed) in [
+        ("/users", [true, false, false]),
+        (&format!("/users/{}/overview", author_id), [true, true, false]),
+        ("/abuse", [true, true, false]),
+        ("/architectures", [true, false, true]),
+        ("/contributions", [true, false, true]),
+        ("/questions", [true, false, false]),
+        ("/audit-logs", [true, false, false]),
+        ("/docs/openapi.json", [true, true, true]),
+    ] {
+        for (token, allowed) in [&admin_token, &moderator_token, &editor_token].into_iter().zip(allowed) {
+            let code = status(reqwest::Method::GET, path, token).await.unwrap().status().as_u16();
+            assert_eq!(code == 200, allowed, "GET {} answered {}", path, code);
+            if !allowed {
+                assert_eq!(code, 403);
+            }
+        }
+    }
+    let code = status(reqwest::Method::GET, "/docs/openapi.json", &author_token).await.unwrap().status();
+    assert_eq!(code.as_u16(), 403);
+
+    // Act & Assert 2: Editors cannot moderate; moderators can
+    let res = client.delete(&comment_url).bearer_auth(&editor_token).send().await.unwrap();
+    assert_eq!(res.status().as_u16(), 401);
+    let res = client.delete(&comment_url).bearer_auth(&moderator_token).send().await.unwrap();
+    assert_eq!(res.status().as_u16(), 204);
+    let res = client.delete(&comment_url).bearer_auth(&author_token).send().await.unwrap();
+    assert_eq!(res.status().as_u16(), 404);
+    let comments_count: i32 = sqlx::query_scalar("SELECT comments_count FROM posts WHERE id = $1")
+        .bind(post_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(comments_count, 0);
+
+    let res = client
+        .delete(format!("{}/api/posts/{}", address, post_id))
+        .bearer_auth(&moderator_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 204);
+
+    // Act & Assert 3: Only known roles can be given
+    let res = client
+        .put(format!("{}/api/admin/users/{}", address, author_id))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({ "role": "superuser" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 400);
+    let res = client
+        .put(format!("{}/api/admin/users/{}", address, author_id))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({ "role": "editor" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+    let code = status(reqwest::Method::GET, "/architectures", &author_token).await.unwrap().status();
+    assert_eq!(code.as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_admin_two_factor() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (admin_id, plain_token) = create_user(&client, &address, &pool, "tfa", "admin").await;
+    let username = sqlx::query_scalar!("SELECT username FROM users WHERE id = $1", admin_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let status = |token: String| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            client
+                .get(format!("{}/api/admin/users", address))
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        }
+    };
+    let post = |path: &str, token: &str, body: serde_json::Value| {
+        client
+            .post(format!("{}/api/auth/2fa/{}", address, path))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+    };
+    let login = |code: Option<String>| {
+        client
+            .post(format!("{}/api/auth/login", address))
+            .json(&serde_json::json!({
+                "username": username, "password": "password123", "totp_code": code
+            }))
+            .send()
+    };
+
+    // Act 1: Enroll; 2FA is not on until a code is verified
+    let enrollment: serde_json::Value =
+        post("enroll", &plain_token, serde_json::json!({})).await.unwrap().json().await.unwrap();
+    assert!(enrollment["otpauth_uri"].as_str().unwrap().starts_with("otpauth://totp/"));
+    assert_eq!(enrollment["secret"].as_str().unwrap().len(), 32);
+    assert_eq!(status(plain_token.clone()).await, 200);
+
+    // The stored secret is encrypted
+    let stored = sqlx::query_scalar!("SELECT totp_secret FROM users WHERE id = $1", admin_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let secret = totp::decrypt_secret("admin_test_secret", &stored).unwrap();
+    assert_eq!(totp::base32_encode(&secret), enrollment["secret"]);
+    assert!(totp::decrypt_secret("another_secret", &stored).is_none());
+    let step = totp::step_at(chrono::Utc::now().timestamp());
+
+    // Act 2: Verify a wrong code, then the current one
+    let wrong = totp::code_at(&secret, step + 5);
+    let res = post("verify", &plain_token, serde_json::json!({ "code": wrong })).await.unwrap();
+    assert_eq!(res.status().as_u16(), 401);
+    let res = post("verify", &plain_token, serde_json::json!({ "code": totp::code_at(&secret, step) }))
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+    let verified: serde_json::Value = res.json().await.unwrap();
+
+    // Assert 2: Tokens issued without the second factor no longer open the admin API
+    assert_eq!(verified["mfa"], true);
+    assert_eq!(status(plain_token.clone()).await, 403);
+    assert_eq!(status(verified["token"].as_str().unwrap().to_string()).await, 200);
+    let res = post("enroll", &plain_token, serde_json::json!({})).await.unwrap();
+    assert_eq!(res.status().as_u16(), 409);
+
+    // Act & Assert 3: Logging in needs a code now; codes are single-use
+    let without = login(None).await.unwrap();
+    assert_eq!(without.status().as_u16(), 401);
+    let body: serde_json::Value = without.json().await.unwrap();
+    assert_eq!(body["error"], "Two-factor code required");
+    let replayed = login(Some(totp::code_at(&secret, step))).await.unwrap();
+    assert_eq!(replayed.status().as_u16(), 401);
+    let with: serde_json::Value =
+        login(Some(totp::code_at(&secret, step + 1))).await.unwrap().json().await.unwrap();
+    assert_eq!(with["mfa"], true);
+    assert_eq!(status(with["token"].as_str().unwrap().to_string()).await, 200);
+}
+#[tokio::test]
+async fn test_set_user_verification() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let (admin_id, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let (user_id, user_token) = create_user(&client, &address, &pool, "ver", "user").await;
+
+    let set = |is_verified: bool, reason: &str| {
+        client
+            .put(format!("{}/api/admin/users/{}/verification", address, user_id))
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .json(&serde_json::json!({ "is_verified": is_verified, "reason": reason }))
+            .send()
+    };
+
+    // A reason is required
+    assert_eq!(set(false, "").await.unwrap().status().as_u16(), 400);
+
+    // Act: Revoke
+    let resp = set(false, "Certificate expired").await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["changed"], true);
+
+    // Assert: Stored, audited with the reason, and the user is told why
+    let verified = sqlx::query_scalar!("SELECT is_verified FROM users WHERE id = $1", user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(!verified);
+
+    let log: Vec<serde_json::Value> = client
+        .get(format!(
+            "{}/api/admin/audit-logs?entity_type=user&entity_id={}",
+            address, user_id
+        ))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0]["admin_id"], admin_id);
+    assert_eq!(log[0]["fields"], serde_json::json!(["is_verified"]));
+    assert_eq!(log[0]["reason"], "Certificate expired");
+
+    let inbox: serde_json::Value = client
+        .get(format!("{}/api/notifications", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(inbox["unread"], 1);
+    assert_eq!(inbox["notifications"][0]["kind"], "verification_revoked");
+    assert_eq!(inbox["notifications"][0]["payload"]["reason"], "Certificate expired");
+
+    // Act: Revoking again is a no-op
+    let body: serde_json::Value = set(false, "Again").await.unwrap().json().await.unwrap();
+    assert_eq!(body["changed"], false);
+
+    // Act: Grant back
+    let body: serde_json::Value = set(true, "Renewed").await.unwrap().json().await.unwrap();
+    assert_eq!(body["changed"], true);
+
+    // Assert: Two notifications, and reading them all clears the count
+    let resp: serde_json::Value = client
+        .post(format!("{}/api/notifications/read", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(resp["updated"], 2);
+
+    let inbox: serde_json::Value = client
+        .get(format!("{}/api/notifications", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(inbox["unread"], 0);
+    assert_eq!(inbox["notifications"][0]["kind"], "verification_granted");
+
+    let resp = client
+        .put(format!("{}/api/admin/users/{}/verification", address, i64::MAX))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({ "is_verified": true, "reason": "x" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_exam_accommodations() {
+    // Arrange: Enough questions for a shortened paper
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let (user_id, user_token) = create_user(&client, &address, &pool, "acc", "user").await;
+    let (_, other_token) = create_user(&client, &address, &pool, "oth", "user").await;
+    for i in 0..5 {
+        sqlx::query("INSERT INTO questions (type, content, options, answer) VALUES ('single', $1, $2, 'A')")
+            .bind(format!("Accommodated {}", i))
+            .bind(serde_json::json!(["A", "B"]))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    let url = format!("{}/api/admin/users/{}/exam-accommodations", address, user_id);
+    let exam = || async {
+        let body: serde_json::Value = client
+            .get(format!("{}/api/auth/qualification", address))
+            .bearer_auth(&user_token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        body
+    };
+
+    // Act & Assert 1: Out of range values and a missing reason are rejected
+    for body in [
+        serde_json::json!({"time_multiplier": 4.0, "reason": "x"}),
+        serde_json::json!({"time_multiplier": 1.5, "question_count": 50, "reason": "x"}),
+        serde_json::json!({"time_multiplier": 1.5, "reason": ""}),
+    ] {
+        let res = client.put(&url).bearer_auth(&admin_token).json(&body).send().await.unwrap();
+        assert_eq!(res.status().as_u16(), 400);
+    }
+
+    // Act 2: Grant extended time, larger labels and a shorter paper
+    let res = client
+        .put(&url)
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({
+            "time_multiplier": 1.5, "large_labels": true, "question_count": 5, "reason": "Medical note"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+
+    // Assert 2: Shown on the profile and honoured by the exam
+    let granted = serde_json::json!({"time_multiplier": 1.5, "large_labels": true, "question_count": 5});
+    let me: serde_json::Value = client
+        .get(format!("{}/api/profile/me", address))
+        .bearer_auth(&user_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["exam_accommodations"], granted);
+
+    let paper = exam().await;
+    assert_eq!(paper["questions"].as_array().unwrap().len(), 5);
+    assert_eq!(paper["expires_in"], 1350);
+    assert_eq!(paper["accommodations"], granted);
+
+    // Act & Assert 3: The extended token cannot be handed to someone else
+    let res = client
+        .post(format!("{}/api/auth/qualification/submit", address))
+        .bearer_auth(&other_token)
+        .json(&serde_json::json!({"exam_token": paper["exam_token"], "answers": {}}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 400);
+
+    // Act & Assert 4: Withdrawn, the standard exam is back; both changes are audited
+    let res = client.delete(&url).bearer_auth(&admin_token).send().await.unwrap();
+    assert_eq!(res.status().as_u16(), 204);
+    let res = client.delete(&url).bearer_auth(&admin_token).send().await.unwrap();
+    assert_eq!(res.status().as_u16(), 404);
+
+    let paper = exam().await;
+    assert_eq!(paper["expires_in"], 900);
+    assert_eq!(paper["accommodations"]["question_count"], serde_json::Value::Null);
+
+    let log: Vec<serde_json::Value> = client
+        .get(format!(
+            "{}/api/admin/audit-logs?entity_type=user&entity_id={}",
+            address, user_id
+        ))
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[1]["fields"], serde_json::json!(["exam_accommodations"]));
+    assert_eq!(log[1]["reason"], "Medical note");
+}
+
+#[tokio::test]
+async fn test_contributor_leaderboard() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let (busy_id, _) = create_user(&client, &address, &pool, "bsy", "user").await;
+    let (steady_id, _) = create_user(&client, &address, &pool, "sty", "user").await;
+    let (admin_id, _) = create_user(&client, &address, &pool, "adm", "admin").await;
+
+    // (user, days since submission and approval, status, type)
+    let contributions = [
+        (busy_id, 1, "approved", "question"),
+        (busy_id, 2, "approved", "question"),
+        (busy_id, 3, "approved", "architecture"),
+        (busy_id, 4, "rejected", "question"),
+        (steady_id, 1, "approved", "question"),
+        (steady_id, 60, "approved", "question"),
+        (admin_id, 1, "approved", "question"),
+    ];
+    for (user_id, days, status, kind) in contributions {
+        sqlx::query!(
+            r#"
+            INSERT INTO contributions (user_id, type, data, status, created_at, reviewed_at)
+            VALUES ($1, $2, '{}', $3, NOW() - make_interval(days => $4), NOW() - make_interval(days => $4))
+            "#,
+            user_id,
+            kind,
+            status,
+            days
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    let usernames = sqlx::query!(
+        "SELECT id, username FROM users WHERE id = ANY($1)",
+        &[busy_id, steady_id, admin_id]
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    let name = |id: i64| usernames.iter().find(|u| u.id == id).unwrap().username.clone();
+
+    let leaderboard = |window: &'static str| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            let resp = client
+                .get(format!(
+                    "{}/api/contributions/leaderboard?window={}&limit=50",
+                    address, window
+                ))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(resp.status().as_u16(), 200);
+            let body: serde_json::Value = resp.json().await.unwrap();
+            body["entries"].as_array().unwrap().clone()
+        }
+    };
+    let find = |entries: &[serde_json::Value], id: i64| {
+        entries.iter().find(|e| e["username"] == name(id)).cloned()
+    };
+
+    // Act & Assert: Last 7 days, no auth needed
+    let week = leaderboard("7d").await;
+    let busy = find(&week, busy_id).unwrap();
+    let steady = find(&week, steady_id).unwrap();
+    assert_eq!(busy["approved"], 3);
+    assert_eq!(busy["architectures"], 1);
+    assert_eq!(busy["questions"], 2);
+    assert_eq!(steady["approved"], 1);
+    assert!(busy["rank"].as_i64().unwrap() < steady["rank"].as_i64().unwrap());
+    assert!(find(&week, admin_id).is_none());
+
+    // Older approvals count in the wider window
+    let all = leaderboard("all").await;
+    assert_eq!(find(&all, steady_id).unwrap()["approved"], 2);
+
+    let resp = client
+        .get(format!("{}/api/contributions/leaderboard?window=1y", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_contribution_review_metrics() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let (admin_id, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let (user_id, _) = create_user(&client, &address, &pool, "con", "user").await;
+
+    let question = serde_json::json!({
+        "question_type": "single",
+        "content": format!("Metrics {}", uuid::Uuid::new_v4()),
+        "options": ["A", "B"],
+        "answer": "A"
+    });
+    // Submitted 1, 3 and 5 days ago; the last one stays pending
+    let mut ids = Vec::new();
+    for days in [1, 3, 5] {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO contributions (user_id, type, data, created_at)
+            VALUES ($1, 'question', $2, NOW() - make_interval(days => $3))
+            RETURNING id
+            "#,
+            user_id,
+            question,
+            days
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        ids.push(id);
+    }
+
+    // Act: Approve one, reject one
+    for (id, status) in [(ids[0], "approved"), (ids[1], "rejected")] {
+        let resp = client
+            .put(format!("{}/api/admin/contributions/{}/review", address, id))
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .json(&serde_json::json!({ "status": status }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+
+    let resp = client
+        .get(format!("{}/api/admin/contributions/metrics?window=7d", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let metrics: serde_json::Value = resp.json().await.unwrap();
+
+    // Assert: The reviewer is recorded, with their own approval rate and timing
+    let reviewed_by = sqlx::query_scalar!("SELECT reviewed_by FROM contributions WHERE id = $1", ids[0])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(reviewed_by, Some(admin_id));
+
+    let mine = metrics["reviewers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["admin_id"] == admin_id)
+        .unwrap();
+    assert_eq!(mine["reviewed"], 2);
+    assert_eq!(mine["approved"], 1);
+    assert_eq!(mine["approval_rate"], 50.0);
+    // Median of one and three days
+    let median = mine["median_review_secs"].as_f64().unwrap();
+    assert!((median - 2.0 * 86400.0).abs() < 60.0);
+
+    assert!(metrics["reviewed"].as_i64().unwrap() >= 2);
+    assert!(metrics["median_review_secs"].is_number());
+
+    // The pending one is in the 3-7 day bucket
+    let backlog = &metrics["backlog"];
+    assert!(backlog["pending"].as_i64().unwrap() >= 1);
+    let bucket = backlog["buckets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|b| b["label"] == "3-7d")
+        .unwrap();
+    assert!(bucket["count"].as_i64().unwrap() >= 1);
+
+    let resp = client
+        .get(format!("{}/api/admin/contributions/metrics?window=1y", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_list_questions_by_source() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let (admin_id, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let (contributor_id, contributor_token) =
+        create_user(&client, &address, &pool, "con", "user").await;
+
+    let question = serde_json::json!({
+        "question_type": "single",
+        "content": format!("Provenance {}", uuid::Uuid::new_v4()),
+        "options": ["A", "B"],
+        "answer": "A",
+        "analysis": "None"
+    });
+
+    let direct: serde_json::Value = client
+        .post(format!("{}/api/admin/questions", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&question)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let direct_id = direct["id"].as_i64().unwrap();
+
+    let contrib: serde_json::Value = client
+        .post(format!("{}/api/contributions", address))
+        .header("Authorization", format!("Bearer {}", contributor_token))
+        .json(&serde_json::json!({"type": "question", "data": question}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let contrib_id = contrib["id"].as_i64().unwrap();
+
+    let review = client
+        .put(format!("{}/api/admin/contributions/{}/review", address, contrib_id))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({"status": "approved"}))
+        .send()
+        .await
+        .unwrap();
+    assert!(review.status().is_success());
+
+    // Act: Filter by the contribution it came from
+    let crowd: Vec<serde_json::Value> = client
+        .get(format!(
+            "{}/api/admin/questions?source=contribution&contribution_id={}",
+            address, contrib_id
+        ))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(crowd.len(), 1);
+    assert_eq!(crowd[0]["created_by"], contributor_id);
+
+    let mine: Vec<serde_json::Value> = client
+        .get(format!(
+            "{}/api/admin/questions?source=admin&created_by={}",
+            address, admin_id
+        ))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(mine.len(), 1);
+    assert_eq!(mine[0]["id"], direct_id);
+    assert!(mine[0]["contribution_id"].is_null());
+
+    let past_end: Vec<serde_json::Value> = client
+        .get(format!(
+            "{}/api/admin/questions?source=admin&created_by={}&limit=1&offset=1",
+            address, admin_id
+        ))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(past_end.is_empty());
+
+    let bad = client
+        .get(format!("{}/api/admin/questions?source=robots", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(bad.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_merge_duplicate_questions() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let (flagger_id, _) = create_user(&client, &address, &pool, "flg", "user").await;
+
+    let tag = uuid::Uuid::new_v4();
+    let mut ids = Vec::new();
+    for content in [
+        format!("Which dynasty built {}?", tag),
+        format!("Which dynasty built {} ?!", tag),
+        format!("{} was restored in which year?", uuid::Uuid::new_v4()),
+    ] {
+        let created: serde_json::Value = client
+            .post(format!("{}/api/admin/questions", address))
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .json(&serde_json::json!({
+                "question_type": "single",
+                "content": content,
+                "options": ["Ming", "Qing"],
+                "answer": "Ming"
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        ids.push(created["id"].as_i64().unwrap());
+    }
+    let (original, copy, unrelated) = (ids[0], ids[1], ids[2]);
+
+    sqlx::query!(
+        "INSERT INTO question_flags (question_id, user_id, reason) VALUES ($1, $2, 'typo')",
+        copy,
+        flagger_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Act 1: Find duplicates
+    let clusters: Vec<serde_json::Value> = client
+        .get(format!("{}/api/admin/questions/duplicates", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Assert: The two near-identical questions form one cluster, oldest first
+    let cluster = clusters
+        .iter()
+        .find(|c| c["questions"][0]["id"] == original)
+        .expect("Cluster not found");
+    let members: Vec<i64> = cluster["questions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|q| q["id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(members, vec![original, copy]);
+    assert!(cluster["similarity"].as_f64().unwrap() >= 0.8);
+    assert!(!clusters.iter().any(|c| c["questions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|q| q["id"] == unrelated)));
+
+    let bad = client
+        .get(format!("{}/api/admin/questions/duplicates?threshold=0.1", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(bad.status().as_u16(), 400);
+
+    // Act 2: A merge naming a question twice is refused
+    let resp = client
+        .post(format!("{}/api/admin/questions/duplicates/merge", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "merges": [{ "keep_id": original, "duplicate_ids": [copy, original] }]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+
+    // Act 3: Preview the merge, then merge the copy into the original
+    let preview: serde_json::Value = client
+        .post(format!("{}/api/admin/questions/duplicates/merge?dry_run=true", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "merges": [{ "keep_id": original, "duplicate_ids": [copy] }]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(preview["merged"], 1);
+    assert_eq!(preview["dry_run"], true);
+    let untouched = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM questions WHERE id = ANY($1)"#,
+        &ids
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(untouched, 3);
+
+    let resp = client
+        .post(format!("{}/api/admin/questions/duplicates/merge", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "merges": [{ "keep_id": original, "duplicate_ids": [copy] }]
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.json::<serde_json::Value>().await.unwrap()["merged"], 1);
+
+    // Assert: The copy is gone and its flag moved over
+    let remaining = sqlx::query_scalar!(
+        "SELECT id FROM questions WHERE id = ANY($1) ORDER BY id",
+        &ids
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(remaining, vec![original, unrelated]);
+    let flagged = sqlx::query_scalar!(
+        "SELECT question_id FROM question_flags WHERE user_id = $1",
+        flagger_id
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(flagged, vec![original]);
+
+    // Act 4: Preview a bulk delete, then delete the rest
+    let preview: serde_json::Value = client
+        .post(format!("{}/api/admin/questions/bulk-delete?dry_run=true", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({ "ids": [original, unrelated, copy] }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(preview["deleted"], 2);
+    assert_eq!(preview["dry_run"], true);
+
+    let resp: serde_json::Value = client
+        .post(format!("{}/api/admin/questions/bulk-delete", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({ "ids": [original, unrelated, copy] }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(resp["deleted"], 2);
+    assert_eq!(resp["dry_run"], false);
+}
+
+#[tokio::test]
+async fn test_import_questions_from_file_format() {
+    // Arrange
+    let (_, pool) = spawn_app().await;
+    let tag = uuid::Uuid::new_v4();
+    let file = serde_json::json!([
+        {
+            "question_type": "single",
+            "content": format!("Import {} single", tag),
+            "options": ["A", "B"],
+            "answer": "A"
+        },
+        {
+            "type": "question",
+            "data": {
+                "question_type": "multiple",
+                "content": format!("Import {} <script>alert(1)</script>contributed", tag),
+                "options": ["A", "B", "C"],
+                "answer": "A,C",
+                "analysis": "Both"
+            }
+        },
+        { "type": "architecture", "data": { "name": "Skipped" } }
+    ])
+    .to_string();
+
+    // Act 1: An invalid entry aborts the whole import
+    let broken = file.replace("\"single\"", "\"essay\"");
+    let err = backend::services::question_import::import_questions(&pool, &broken)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Entry 1"));
+
+    // Act 2: Import, then import again
+    let first = backend::services::question_import::import_questions(&pool, &file)
+        .await
+        .unwrap();
+    let second = backend::services::question_import::import_questions(&pool, &file)
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!((first.imported, first.duplicates, first.skipped), (2, 0, 1));
+    assert_eq!((second.imported, second.duplicates, second.skipped), (0, 2, 1));
+
+    let stored = sqlx::query!(
+        "SELECT type, content, answer, created_by FROM questions WHERE content LIKE $1 ORDER BY type",
+        format!("Import {}%", tag)
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(stored.len(), 2);
+    assert_eq!(stored[0].r#type, "multiple");
+    assert!(!stored[0].content.contains("<script>"), "Content must be sanitized");
+    assert_eq!(stored[0].answer, "AC");
+    assert!(stored[1].created_by.is_none());
+}
+
+#[tokio::test]
+async fn test_image_link_report() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+
+    let created: serde_json::Value = client
+        .post(format!("{}/api/admin/architectures", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "category": "Bridge",
+            "name": "Zhaozhou Bridge",
+            "dynasty": "Sui",
+            "location": "河北省石家庄市赵县",
+            "description": "Stone arch",
+            "cover_img": "http://127.0.0.1:1/cover.jpg",
+            "carousel_imgs": ["http://localhost/a.jpg", "http://127.0.0.1:1/cover.jpg"]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let arch_id = created["id"].as_i64().unwrap();
+
+    // Act 1: Queue a check of this architecture
+    let resp = client
+        .post(format!(
+            "{}/api/admin/architectures/link-report?architecture_id={}",
+            address, arch_id
+        ))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 202);
+    let job_id = resp.json::<serde_json::Value>().await.unwrap()["job_id"]
+        .as_i64()
+        .unwrap();
+
+    let payload = sqlx::query_scalar!("SELECT payload FROM jobs WHERE id = $1", job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(payload["architecture_id"], arch_id);
+
+    // Act 2: Run it; loopback targets are flagged without a request
+    backend::jobs::check_image_links::run(&pool, &payload).await.unwrap();
+    sqlx::query!("UPDATE jobs SET status = 'done' WHERE id = $1", job_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Assert
+    let report: serde_json::Value = client
+        .get(format!("{}/api/admin/architectures/link-report", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(report["dead"].as_i64().unwrap() >= 3);
+    let mut mine: Vec<(String, String, String)> = report["links"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|l| l["architecture_id"] == arch_id)
+        .map(|l| {
+            (
+                l["field"].as_str().unwrap().to_string(),
+                l["url"].as_str().unwrap().to_string(),
+                l["status"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    mine.sort();
+    assert_eq!(
+        mine,
+        vec![
+            ("carousel".into(), "http://127.0.0.1:1/cover.jpg".into(), "invalid".into()),
+            ("carousel".into(), "http://localhost/a.jpg".into(), "invalid".into()),
+            ("cover".into(), "http://127.0.0.1:1/cover.jpg".into(), "invalid".into()),
+        ]
+    );
+
+    let missing = client
+        .post(format!(
+            "{}/api/admin/architectures/link-report?architecture_id=0",
+            address
+        ))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_structured_location_filter_and_backfill() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+
+    let tag = uuid::Uuid::new_v4().to_string()[..8].to_string();
+    let created: serde_json::Value = client
+        .post(format!("{}/api/admin/architectures", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "category": "Temple",
+            "name": format!("Yungang {}", tag),
+            "dynasty": "Northern Wei",
+            "location": "山西省大同市云冈区",
+            "description": "Grottoes",
+            "cover_img": "http://example.com/a.jpg",
+            "carousel_imgs": []
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let arch_id = created["id"].as_i64().unwrap();
+
+    // Act 1: Filter the public list by the parsed hierarchy
+    let list: Vec<serde_json::Value> = client
+        .get(format!(
+            "{}/api/architectures?province=山西省&city=大同市&q=Yungang {}",
+            address, tag
+        ))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0]["district"], "云冈区");
+    assert_eq!(list[0]["location"], "山西省大同市云冈区");
+    assert!(list[0].get("created_by").is_none(), "Provenance is admin-only");
+    assert!(list[0].get("contribution_id").is_none());
+
+    let admin_list: Vec<serde_json::Value> = client
+        .get(format!("{}/api/admin/architectures?source=admin&limit=1", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(admin_list.len(), 1);
+    assert_eq!(admin_list[0]["id"], arch_id);
+    assert!(admin_list[0]["created_by"].is_i64());
+    assert!(admin_list[0]["contribution_id"].is_null());
+
+    // Act 2: Clear the structured fields and backfill them (dry run first)
+    sqlx::query!(
+        "UPDATE architectures SET province = NULL, city = NULL, district = NULL, location = 'Yingxian County, Shuozhou, Shanxi Province' WHERE id = $1",
+        arch_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let dry: serde_json::Value = client
+        .post(format!(
+            "{}/api/admin/architectures/locations/backfill?dry_run=true",
+            address
+        ))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(dry["updated"].as_u64().unwrap() >= 1);
+    let untouched = sqlx::query!("SELECT province FROM architectures WHERE id = $1", arch_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(untouched.province.is_none(), "Dry run must not write");
+
+    client
+        .post(format!("{}/api/admin/architectures/locations/backfill", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+
+    // Assert: English locations are split most-specific first
+    let filled = sqlx::query!(
+        "SELECT province, city, district FROM architectures WHERE id = $1",
+        arch_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(filled.province.as_deref(), Some("Shanxi"));
+    assert_eq!(filled.city.as_deref(), Some("Shuozhou"));
+    assert_eq!(filled.district.as_deref(), Some("Yingxian"));
+
+    // Act 3: A long location without separators is not a province
+    let long_location = "Yungang".repeat(10);
+    let res = client
+        .post(format!("{}/api/admin/architectures", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "category": "Temple",
+            "name": format!("Long {}", tag),
+            "dynasty": "Northern Wei",
+            "location": long_location,
+            "description": "Grottoes",
+            "cover_img": "http://example.com/a.jpg",
+            "carousel_imgs": []
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success(), "{}", res.status());
+    let long_id = res.json::<serde_json::Value>().await.unwrap()["id"]
+        .as_i64()
+        .unwrap();
+    let unparsed = sqlx::query!("SELECT province FROM architectures WHERE id = $1", long_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(unparsed.province.is_none());
+
+    let backfill = client
+        .post(format!("{}/api/admin/architectures/locations/backfill", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(backfill.status(), 200);
+    let report: serde_json::Value = backfill.json().await.unwrap();
+    assert!(
+        report["unparsed"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|u| u["id"] == long_id)
+    );
+}
+
+#[tokio::test]
+async fn test_pinyin_search() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+
+    let tag = uuid::Uuid::new_v4().to_string()[..8].to_string();
+    let created: serde_json::Value = client
+        .post(format!("{}/api/admin/architectures", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "category": "Pagoda",
+            "name": format!("应县木塔{}", tag),
+            "dynasty": "Liao",
+            "location": "山西省朔州市应县",
+            "description": "Wooden pagoda",
+            "cover_img": "http://example.com/a.jpg",
+            "carousel_imgs": []
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let arch_id = created["id"].as_i64().unwrap();
+
+    let term: serde_json::Value = client
+        .post(format!("{}/api/admin/glossary", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "slug": format!("dougong-{}", tag),
+            "term": "斗拱",
+            "definition": "Interlocking wooden brackets"
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let slug = term["slug"].as_str().unwrap().to_string();
+
+    // Act & Assert: Full pinyin, partial pinyin and initials all find the architecture
+    for q in ["yingxian muta", "xianmu", "yxmt", "yingxan muta"] {
+        let list: Vec<serde_json::Value> = client
+            .get(format!("{}/api/architectures", address))
+            .query(&[("q", q)])
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(
+            list.iter().any(|a| a["id"] == arch_id),
+            "'{}' should match 应县木塔",
+            q
+        );
+    }
+
+    let terms: Vec<serde_json::Value> = client
+        .get(format!("{}/api/glossary", address))
+        .query(&[("q", "dougong")])
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(terms.iter().any(|t| t["slug"] == slug.as_str()));
+
+    let detail: serde_json::Value = client
+        .get(format!("{}/api/glossary/{}", address, slug))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["term"], "斗拱");
+    assert_eq!(detail["term_pinyin"], "dou gong");
+
+    // Act & Assert: A misspelling still finds the term
+    let terms: Vec<serde_json::Value> = client
+        .get(format!("{}/api/glossary", address))
+        .query(&[("q", "dougon")])
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(terms.iter().any(|t| t["slug"] == slug.as_str()));
+
+    // Act & Assert: A slug that slugifies to nothing is rejected on update
+    let term_id = term["id"].as_i64().unwrap();
+    let res = client
+        .put(format!("{}/api/admin/glossary/{}", address, term_id))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({ "slug": "!!!" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+
+    // Act & Assert: Slugs derived from long terms are cut to fit
+    let long: serde_json::Value = client
+        .post(format!("{}/api/admin/glossary", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "term": format!("{}{}", tag, "斗拱".repeat(20)),
+            "definition": "Many brackets"
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let long_slug = long["slug"].as_str().unwrap();
+    assert!(long_slug.len() <= 100 && long_slug.starts_with(&format!("{}dougong", tag)));
+}
+
+#[tokio::test]
+async fn test_translated_architecture_by_locale() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let (_, user_token) = create_user(&client, &address, &pool, "loc", "user").await;
+
+    let created: serde_json::Value = client
+        .post(format!("{}/api/admin/architectures", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "category": "Pagoda",
+            "name": "应县木塔",
+            "dynasty": "辽",
+            "location": "山西省朔州市应县",
+            "description": "现存最古老的木塔",
+            "cover_img": "http://example.com/a.jpg",
+            "carousel_imgs": []
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let arch_id = created["id"].as_i64().unwrap();
+
+    let put = client
+        .put(format!("{}/api/admin/architectures/{}/translations/en", address, arch_id))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({"name": "Yingxian Wooden Pagoda", "dynasty": "Liao"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(put.status().as_u16(), 200);
+
+    // Act 1: Anonymous visitor asking for English
+    let resp = client
+        .get(format!("{}/api/architectures/{}", address, arch_id))
+        .header("Accept-Language", "en-US,en;q=0.9,zh;q=0.5")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["content-language"], "en");
+    let en: serde_json::Value = resp.json().await.unwrap();
+
+    // Assert: Translated fields win, missing ones fall back to the source
+    assert_eq!(en["name"], "Yingxian Wooden Pagoda");
+    assert_eq!(en["dynasty"], "Liao");
+    assert_eq!(en["description"], "现存最古老的木塔");
+
+    // Act 2: A saved preference beats Accept-Language
+    let saved = client
+        .put(format!("{}/api/profile/locale", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .json(&serde_json::json!({"locale": "zh-cn"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(saved.status().as_u16(), 200);
+
+    let zh: serde_json::Value = client
+        .get(format!("{}/api/architectures/{}", address, arch_id))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .header("Accept-Language", "en")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(zh["name"], "应县木塔");
+
+    let me: serde_json::Value = client
+        .get(format!("{}/api/profile/me", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["locale"], "zh-CN");
+
+    let bad = client
+        .put(format!("{}/api/profile/locale", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .json(&serde_json::json!({"locale": "klingon"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(bad.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_translated_questions_in_papers() {
+    // Arrange: A question of a type of its own, so selection can only pick it
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let question_type = format!("loc_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let question_id: i64 = sqlx::query_scalar(
+        "INSERT INTO questions (type, content, options, answer, analysis) VALUES ($1, '哪个朝代？', $2, 'B', '原文解析') RETURNING id",
+    )
+    .bind(&question_type)
+    .bind(serde_json::json!(["唐", "辽", "宋"]))
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let translations_url = format!("{}/api/admin/questions/{}/translations", address, question_id);
+
+    // Act & Assert 1: Translations must keep the option count and a target locale
+    let mismatched = client
+        .put(format!("{}/en", translations_url))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"options": ["Tang", "Liao"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(mismatched.status().as_u16(), 400);
+    let source_locale = client
+        .put(format!("{}/zh-CN", translations_url))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"content": "?"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(source_locale.status().as_u16(), 400);
+
+    let put = client
+        .put(format!("{}/EN", translations_url))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"content": "Which dynasty?", "options": ["Tang", "Liao", "Song"]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(put.status().as_u16(), 200);
+    let listed: Vec<serde_json::Value> = client
+        .get(&translations_url)
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["locale"], "en");
+    assert_eq!(listed[0]["analysis"], serde_json::Value::Null);
+
+    // Act & Assert 2: Papers use the translation field by field
+    let en = question_selection::pick_questions(&pool, Some(&question_type), 1, "en")
+        .await
+        .unwrap();
+    assert_eq!(en[0].content, "Which dynasty?");
+    assert_eq!(en[0].options.0, ["Tang", "Liao", "Song"]);
+    assert_eq!(en[0].analysis.as_deref(), Some("原文解析"));
+    assert_eq!(en[0].answer, "B");
+    let ja = question_selection::pick_questions(&pool, Some(&question_type), 1, "ja")
+        .await
+        .unwrap();
+    assert_eq!(ja[0].content, "哪个朝代？");
+
+    // Options that no longer match the source fall back to it
+    sqlx::query("UPDATE questions SET options = $2 WHERE id = $1")
+        .bind(question_id)
+        .bind(serde_json::json!(["唐", "辽", "宋", "元"]))
+        .execute(&pool)
+        .await
+        .unwrap();
+    let en = question_selection::pick_questions(&pool, Some(&question_type), 1, "en")
+        .await
+        .unwrap();
+    assert_eq!(en[0].options.0, ["唐", "辽", "宋", "元"]);
+
+    let paper = client
+        .get(format!("{}/api/quiz/generate", address))
+        .header("Accept-Language", "en-GB")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(paper.headers()["content-language"], "en");
+
+    // Act & Assert 3: Deleting the translation
+    let delete = |locale: &'static str| {
+        client
+            .delete(format!("{}/{}", translations_url, locale))
+            .bearer_auth(&admin_token)
+            .send()
+    };
+    assert_eq!(delete("en").await.unwrap().status().as_u16(), 204);
+    assert_eq!(delete("en").await.unwrap().status().as_u16(), 404);
+
+    sqlx::query("DELETE FROM questions WHERE id = $1")
+        .bind(question_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_abuse_summary_counts_failed_logins() {
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let (_, admin_token) = create_user(&client, &address, &pool, "abuse_admin", "admin").await;
+    let (victim_id, _) = create_user(&client, &address, &pool, "abuse_victim", "user").await;
+    let victim = sqlx::query_scalar!("SELECT username FROM users WHERE id = $1", victim_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let n = uuid::Uuid::new_v4().as_bytes()[0];
+    let ip = format!("198.51.100.{}", n);
+
+    let summary_url = format!("{}/api/admin/abuse?window=1h&limit=100", address);
+    let before: serde_json::Value = client
+        .get(&summary_url)
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    for _ in 0..3 {
+        let res = client
+            .post(format!("{}/api/auth/login", address))
+            .header("X-Forwarded-For", format!("{}, 10.0.0.1", ip))
+            .json(&serde_json::json!({"username": victim, "password": "wrong-password"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 401);
+    }
+
+    let after: serde_json::Value = client
+        .get(&summary_url)
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(after["window"], "1h");
+    assert!(
+        after["failed_logins"].as_i64().unwrap() >= before["failed_logins"].as_i64().unwrap() + 3
+    );
+    let top_ip = after["top_ips"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|o| o["ip"] == ip.as_str())
+        .expect("offending IP should be listed");
+    assert!(top_ip["events"].as_i64().unwrap() >= 3);
+    let top_user = after["top_users"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|o| o["user_id"] == victim_id)
+        .expect("targeted user should be listed");
+    assert_eq!(top_user["events"], 3);
+    assert_eq!(top_user["username"], victim.as_str());
+
+    // Unknown windows are rejected
+    let res = client
+        .get(format!("{}/api/admin/abuse?window=1y", address))
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn test_online_user_count() {
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let (_, admin_token) = create_user(&client, &address, &pool, "online_admin", "admin").await;
+    let (user_id, user_token) = create_user(&client, &address, &pool, "online_user", "user").await;
+
+    // Nobody has made an authenticated request against this instance yet
+    let stats: serde_json::Value = client
+        .get(format!("{}/api/stats/online", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stats["online_users"], 0);
+
+    // Any authenticated request is a heartbeat
+    let res = client
+        .get(format!("{}/api/profile/me", address))
+        .bearer_auth(&user_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    let stats: serde_json::Value = client
+        .get(format!("{}/api/stats/online", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stats["online_users"], 1);
+    assert_eq!(stats["window_secs"], 300);
+
+    // The admin listing includes the admin's own request
+    let online: serde_json::Value = client
+        .get(format!("{}/api/admin/online", address))
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(online["online_users"], 2);
+    let users = online["users"].as_array().unwrap();
+    assert!(users.iter().any(|u| u["id"] == user_id));
+}
+
+#[tokio::test]
+async fn test_pinned_posts_on_architecture() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "pin_adm", "admin").await;
+    let (_, user_token) = create_user(&client, &address, &pool, "pin_user", "user").await;
+
+    let arch: serde_json::Value = client
+        .post(format!("{}/api/admin/architectures", address))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({
+            "category": "Bridge",
+            "name": "赵州桥",
+            "dynasty": "隋",
+            "location": "河北省石家庄市赵县",
+            "description": "现存最古老的石拱桥",
+            "cover_img": "http://example.com/b.jpg",
+            "carousel_imgs": []
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let arch_id = arch["id"].as_i64().unwrap();
+
+    let mut post_ids = Vec::new();
+    for title in ["赵州桥讨论帖", "参观攻略"] {
+        let post: serde_json::Value = client
+            .post(format!("{}/api/posts", address))
+            .bearer_auth(&user_token)
+            .json(&serde_json::json!({"title": title, "content": "..."}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        post_ids.push(post["id"].as_i64().unwrap());
+    }
+    let pins_url = format!("{}/api/admin/architectures/{}/pins", address, arch_id);
+
+    // Act 1: Pin both slots
+    let res = client
+        .put(&pins_url)
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"discussion_post_id": post_ids[0], "tips_post_id": post_ids[1]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    let detail: serde_json::Value = client
+        .get(format!("{}/api/architectures/{}", address, arch_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["name"], "赵州桥");
+    assert_eq!(detail["pinned_posts"]["discussion"]["id"], post_ids[0]);
+    assert_eq!(detail["pinned_posts"]["visiting_tips"]["title"], "参观攻略");
+
+    // Act 2: Invalid pins are rejected
+    let same = client
+        .put(&pins_url)
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"discussion_post_id": post_ids[0], "tips_post_id": post_ids[0]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(same.status(), 400);
+    let missing = client
+        .put(&pins_url)
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"discussion_post_id": -1}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), 400);
+
+    // Act 3: A soft-deleted pinned post disappears from the detail
+    let del = client
+        .delete(format!("{}/api/posts/{}", address, post_ids[1]))
+        .bearer_auth(&user_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(del.status(), 204);
+
+    let detail: serde_json::Value = client
+        .get(format!("{}/api/architectures/{}", address, arch_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["pinned_posts"]["discussion"]["id"], post_ids[0]);
+    assert!(detail["pinned_posts"]["visiting_tips"].is_null());
+}
+
+#[tokio::test]
+async fn test_user_overview() {
+    // Arrange: A user with a post, a deleted post, a comment and a like
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "ovadm", "admin").await;
+    let (user_id, user_token) = create_user(&client, &address, &pool, "ovusr", "user").await;
+
+    let mut post_ids = Vec::new();
+    for title in ["Kept", "Removed"] {
+        let post: serde_json::Value = client
+            .post(format!("{}/api/posts", address))
+            .header("Authorization", format!("Bearer {}", user_token))
+            .json(&serde_json::json!({"title": title, "content": "Body"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        post_ids.push(post["id"].as_i64().unwrap());
+    }
+    client
+        .post(format!("{}/api/posts/{}/comments", address, post_ids[0]))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .json(&serde_json::json!({"content": "First!"}))
+        .send()
+        .await
+        .unwrap();
+    client
+        .post(format!("{}/api/posts/{}/like", address, post_ids[0]))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .send()
+        .await
+        .unwrap();
+    client
+        .delete(format!("{}/api/posts/{}", address, post_ids[1]))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .send()
+        .await
+        .unwrap();
+
+    // Act
+    let forbidden = client
+        .get(format!("{}/api/admin/users/{}/overview", address, user_id))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .send()
+        .await
+        .unwrap();
+    let overview: serde_json::Value = client
+        .get(format!("{}/api/admin/users/{}/overview?limit=1", address, user_id))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(forbidden.status().as_u16(), 403);
+    assert_eq!(overview["user"]["id"], user_id);
+    assert!(overview["user"].get("password").is_none());
+    assert_eq!(overview["counts"]["posts"], 2, "Soft-deleted posts are included");
+    assert_eq!(overview["counts"]["comments"], 1);
+    assert_eq!(overview["counts"]["likes"], 1);
+    assert_eq!(overview["counts"]["contributions"], 0);
+    assert_eq!(overview["posts"].as_array().unwrap().len(), 1, "Lists honour the limit");
+    assert_eq!(overview["comments"][0]["content"], "First!");
+    assert_eq!(overview["likes"][0]["post_id"], post_ids[0]);
+    assert_eq!(overview["likes"][0]["title"], "Kept");
+
+    let missing = client
+        .get(format!("{}/api/admin/users/{}/overview", address, i64::MAX))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_question_flags_suspend_and_resolve() {
+    // Arrange: A question and three test-takers
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "flagadm", "admin").await;
+
+    let question: serde_json::Value = client
+        .post(format!("{}/api/admin/questions", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "question_type": "single",
+            "content": format!("Flag me {}", uuid::Uuid::new_v4()),
+            "options": ["A", "B"],
+            "answer": "A"
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let question_id = question["id"].as_i64().unwrap();
+    let flag_url = format!("{}/api/questions/{}/flag", address, question_id);
+
+    let mut tokens = Vec::new();
+    for _ in 0..3 {
+        tokens.push(create_user(&client, &address, &pool, "flagger", "user").await.1);
+    }
+
+    // Act 1: Invalid reason, then the first flag and a repeat
+    let invalid = client
+        .post(&flag_url)
+        .header("Authorization", format!("Bearer {}", tokens[0]))
+        .json(&serde_json::json!({"reason": "boring"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(invalid.status().as_u16(), 400);
+
+    let first = client
+        .post(&flag_url)
+        .header("Authorization", format!("Bearer {}", tokens[0]))
+        .json(&serde_json::json!({"reason": "wrong_answer", "comment": "It is B"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(first.status().as_u16(), 201);
+    let repeat = client
+        .post(&flag_url)
+        .header("Authorization", format!("Bearer {}", tokens[0]))
+        .json(&serde_json::json!({"reason": "ambiguous"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(repeat.status().as_u16(), 409);
+
+    let suspended_at = || async {
+        sqlx::query_scalar!("SELECT suspended_at FROM questions WHERE id = $1", question_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+    };
+    assert!(suspended_at().await.is_none(), "One flag does not suspend");
+
+    // Act 2: Two more users flag it
+    for token in &tokens[1..] {
+        client
+            .post(&flag_url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&serde_json::json!({"reason": "ambiguous"}))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    // Assert: Suspended and queued for triage
+    assert!(suspended_at().await.is_some());
+    let queue: Vec<serde_json::Value> = client
+        .get(format!("{}/api/admin/questions/flagged", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entry = queue.iter().find(|q| q["question_id"] == question_id).unwrap();
+    assert_eq!(entry["flags"].as_array().unwrap().len(), 3);
+    assert_eq!(entry["flags"][0]["comment"], "It is B");
+
+    // Act 3: Admin dismisses the flags
+    let resolved: serde_json::Value = client
+        .put(format!("{}/api/admin/questions/{}/flags", address, question_id))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({"status": "dismissed"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Assert: Back in papers, out of the queue, and users may flag again
+    assert_eq!(resolved["closed"], 3);
+    assert!(suspended_at().await.is_none());
+    let queue: Vec<serde_json::Value> = client
+        .get(format!("{}/api/admin/questions/flagged", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(!queue.iter().any(|q| q["question_id"] == question_id));
+    let again = client
+        .post(&flag_url)
+        .header("Authorization", format!("Bearer {}", tokens[0]))
+        .json(&serde_json::json!({"reason": "typo"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(again.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn test_architecture_quiz_from_metadata() {
+    // Arrange: Two entries, so each has at least one distractor
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "aqadm", "admin").await;
+    let tag = &uuid::Uuid::new_v4().to_string()[..8];
+
+    let mut ids = Vec::new();
+    for (name, dynasty, location) in [
+        ("Foguang Temple", "Tang", "山西省忻州市五台县"),
+        ("Zhaozhou Bridge", "Sui", "河北省石家庄市赵县"),
+    ] {
+        let created: serde_json::Value = client
+            .post(format!("{}/api/admin/architectures", address))
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .json(&serde_json::json!({
+                "category": format!("Quizzable {}", tag),
+                "name": format!("{} {}", name, tag),
+                "dynasty": format!("{} {}", dynasty, tag),
+                "location": location,
+                "description": "...",
+                "cover_img": "http://example.com/a.jpg",
+                "carousel_imgs": []
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        ids.push(created["id"].as_i64().unwrap());
+    }
+
+    // Act
+    let quiz: serde_json::Value = client
+        .get(format!("{}/api/quiz/architecture/{}", address, ids[0]))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(quiz["architecture_id"], ids[0]);
+    assert_eq!(quiz["source"], "generated");
+    let questions = quiz["questions"].as_array().unwrap();
+    let dynasty = questions.iter().find(|q| q["kind"] == "dynasty").unwrap();
+    assert_eq!(dynasty["answer"], format!("Tang {}", tag));
+    assert!(dynasty["prompt"].as_str().unwrap().contains("Foguang Temple"));
+    let province = questions.iter().find(|q| q["kind"] == "province").unwrap();
+    assert_eq!(province["answer"], "山西省");
+    for q in questions {
+        let options: Vec<&str> = q["options"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|o| o.as_str().unwrap())
+            .collect();
+        assert!((2..=4).contains(&options.len()));
+        assert!(options.contains(&q["answer"].as_str().unwrap()));
+        let mut unique = options.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), options.len());
+    }
+
+    let missing = client
+        .get(format!("{}/api/quiz/architecture/{}", address, i64::MAX))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_audit_log_diff_for_admin_edits() {
+    // Arrange: An architecture and a question
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (admin_id, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let tag = &uuid::Uuid::new_v4().to_string()[..8];
+
+    let arch: serde_json::Value = client
+        .post(format!("{}/api/admin/architectures", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "category": "Temple",
+            "name": format!("佛光寺{}", tag),
+            "dynasty": "Tang",
+            "location": "山西省忻州市五台县",
+            "description": "East hall",
+            "cover_img": "http://example.com/a.jpg",
+            "carousel_imgs": []
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let arch_id = arch["id"].as_i64().unwrap();
+
+    let question: serde_json::Value = client
+        .post(format!("{}/api/admin/questions", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "question_type": "single",
+            "content": format!("Audit {}", tag),
+            "options": ["A", "B"],
+            "answer": "A",
+            "analysis": "None"
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let question_id = question["id"].as_i64().unwrap();
+
+    // Act: Edit both, once with a no-op
+    for (url, body) in [
+        (
+            format!("{}/api/admin/architectures/{}", address, arch_id),
+            serde_json::json!({"name": format!("佛光寺东大殿{}", tag), "dynasty": "唐"}),
+        ),
+        (
+            format!("{}/api/admin/architectures/{}", address, arch_id),
+            serde_json::json!({"dynasty": "唐"}),
+        ),
+        (
+            format!("{}/api/admin/questions/{}", address, question_id),
+            serde_json::json!({"answer": "B"}),
+        ),
+    ] {
+        let res = client
+            .put(url)
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+    }
+
+    // Assert: Only real changes are logged
+    let log: Vec<serde_json::Value> = client
+        .get(format!(
+            "{}/api/admin/audit-logs?action=update&entity_type=architecture&entity_id={}",
+            address, arch_id
+        ))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(log.len(), 1, "A no-op edit leaves no entry");
+    assert_eq!(log[0]["admin_id"], admin_id);
+    assert_eq!(log[0]["action"], "update");
+    assert_eq!(log[0]["fields"], serde_json::json!(["dynasty", "name"]));
+
+    let diff: serde_json::Value = client
+        .get(format!("{}/api/admin/audit-logs/{}/diff", address, log[0]["id"]))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(diff["entity_id"], arch_id);
+    assert_eq!(diff["changes"][0]["field"], "dynasty");
+    assert_eq!(diff["changes"][0]["before"], "Tang");
+    assert_eq!(diff["changes"][0]["after"], "唐");
+    assert_eq!(diff["changes"][1]["after"], format!("佛光寺东大殿{}", tag));
+
+    let log: Vec<serde_json::Value> = client
+        .get(format!(
+            "{}/api/admin/audit-logs?action=update&entity_type=question&entity_id={}",
+            address, question_id
+        ))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(log[0]["fields"], serde_json::json!(["answer"]));
+
+    let missing = client
+        .get(format!("{}/api/admin/audit-logs/0/diff", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), 404);
+}
+
+#[tokio::test]
+async fn test_audit_logs_cover_deletions_and_reviews() {
+    // Arrange: A fresh admin and a contributor
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (admin_id, admin_token) = create_user(&client, &address, &pool, "aud_adm", "admin").await;
+    let (_, contributor_token) = create_user(&client, &address, &pool, "aud_usr", "user").await;
+    let tag = &uuid::Uuid::new_v4().to_string()[..8];
+    let question = serde_json::json!({
+        "question_type": "single",
+        "content": format!("Audited {}", tag),
+        "options": ["A", "B"],
+        "answer": "A",
+        "analysis": "None"
+    });
+
+    // Act: Create and delete a question, and approve a contribution
+    let created: serde_json::Value = client
+        .post(format!("{}/api/admin/questions", address))
+        .bearer_auth(&admin_token)
+        .json(&question)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let question_id = created["id"].as_i64().unwrap();
+    let res = client
+        .delete(format!("{}/api/admin/questions/{}", address, question_id))
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 204);
+
+    let contrib: serde_json::Value = client
+        .post(format!("{}/api/contributions", address))
+        .bearer_auth(&contributor_token)
+        .json(&serde_json::json!({"type": "question", "data": question}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let contrib_id = contrib["id"].as_i64().unwrap();
+    let res = client
+        .put(format!("{}/api/admin/contributions/{}/review", address, contrib_id))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"status": "approved", "admin_comment": "Good"}))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+
+    let list = |query: String| {
+        let client = client.clone();
+        let url = format!("{}/api/admin/audit-logs?admin_id={}&{}", address, admin_id, query);
+        let token = admin_token.clone();
+        async move {
+            let log: Vec<serde_json::Value> =
+                client.get(url).bearer_auth(token).send().await.unwrap().json().await.unwrap();
+            log
+        }
+    };
+
+    // Assert: Everything this admin did is listed, newest first
+    let all = list(String::new()).await;
+    let actions: Vec<(&str, &str)> = all
+        .iter()
+        .map(|e| (e["action"].as_str().unwrap(), e["entity_type"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            ("review", "contribution"),
+            ("create", "question"),
+            ("delete", "question"),
+            ("create", "question"),
+        ]
+    );
+
+    // Assert: The deletion keeps the last values of the question
+    let deleted = list(format!("action=delete&entity_id={}", question_id)).await;
+    assert_eq!(deleted.len(), 1);
+    let diff: serde_json::Value = client
+        .get(format!("{}/api/admin/audit-logs/{}/diff", address, deleted[0]["id"]))
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let content = diff["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["field"] == "content")
+        .unwrap();
+    assert_eq!(content["before"], format!("Audited {}", tag));
+    assert_eq!(content["after"], serde_json::Value::Null);
+
+    // Assert: The review records the decision
+    let reviews = list(format!("entity_type=contribution&entity_id={}", contrib_id)).await;
+    assert_eq!(reviews.len(), 1);
+    assert_eq!(reviews[0]["fields"], serde_json::json!(["admin_comment", "status"]));
+
+    // Assert: Pages follow on from each other
+    let page = list("limit=2&offset=1".to_string()).await;
+    assert_eq!(page.len(), 2);
+    assert_eq!(page[0]["id"], all[1]["id"]);
+    assert_eq!(page[1]["id"], all[2]["id"]);
+
+    let future = list("since=2999-01-01T00:00:00Z".to_string()).await;
+    assert!(future.is_empty());
+}
+
+#[tokio::test]
+async fn test_post_cross_references_and_backlinks() {
+    // Arrange: An architecture and a glossary term to link to
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "ref_adm", "admin").await;
+    let (_, user_token) = create_user(&client, &address, &pool, "ref_user", "user").await;
+    let tag = &uuid::Uuid::new_v4().to_string()[..8];
+
+    let arch: serde_json::Value = client
+        .post(format!("{}/api/admin/architectures", address))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({
+            "category": "Temple",
+            "name": format!("南禅寺{}", tag),
+            "dynasty": "唐",
+            "location": "山西省忻州市五台县",
+            "description": "Main hall",
+            "cover_img": "http://example.com/c.jpg",
+            "carousel_imgs": []
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let arch_id = arch["id"].as_i64().unwrap();
+
+    let term: serde_json::Value = client
+        .post(format!("{}/api/admin/glossary", address))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({
+            "slug": format!("queti-{}", tag),
+            "term": "雀替",
+            "definition": "Bracket under a beam end"
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let slug = term["slug"].as_str().unwrap().to_string();
+
+    // Act: Two posts mention the architecture; one also a term and a missing entry
+    let mut post_ids = Vec::new();
+    for content in [
+        format!("See [[arch:{}]], note the [[term:{}]] and [[arch:0]].", arch_id, slug),
+        format!("Visited [[arch:{0}]] again, [[arch:{0}]]", arch_id),
+    ] {
+        let post: serde_json::Value = client
+            .post(format!("{}/api/posts", address))
+            .bearer_auth(&user_token)
+            .json(&serde_json::json!({"title": "Field notes", "content": content}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        post_ids.push(post["id"].as_i64().unwrap());
+    }
+
+    // Assert 1: Post detail resolves the references
+    let post: serde_json::Value = client
+        .get(format!("{}/api/posts/{}", address, post_ids[0]))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let refs = post["references"].as_array().unwrap();
+    assert_eq!(refs.len(), 2, "Unknown entities are ignored");
+    assert_eq!(refs[0]["kind"], "architecture");
+    assert_eq!(refs[0]["id"], arch_id);
+    assert_eq!(refs[0]["title"], format!("南禅寺{}", tag));
+    assert_eq!(refs[1]["kind"], "term");
+    assert_eq!(refs[1]["slug"], slug.as_str());
+    assert_eq!(refs[1]["title"], "雀替");
+
+    // Assert 2: The architecture counts visible mentions
+    let detail: serde_json::Value = client
+        .get(format!("{}/api/architectures/{}", address, arch_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["mentioned_in_posts"], 2);
+
+    client
+        .delete(format!("{}/api/posts/{}", address, post_ids[1]))
+        .bearer_auth(&user_token)
+        .send()
+        .await
+        .unwrap();
+    let detail: serde_json::Value = client
+        .get(format!("{}/api/architectures/{}", address, arch_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["mentioned_in_posts"], 1);
+}
+
+#[tokio::test]
+async fn test_admin_digest_job() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let (_, admin_token) = create_user(&client, &address, &pool, "dig", "admin").await;
+    let (user_id, _) = create_user(&client, &address, &pool, "dig", "user").await;
+    sqlx::query!(
+        "INSERT INTO contributions (user_id, type, data) VALUES ($1, 'question', '{}')",
+        user_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query!("DELETE FROM admin_digests").execute(&pool).await.unwrap();
+
+    let digests = || async {
+        let inbox: serde_json::Value = client
+            .get(format!("{}/api/notifications", address))
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        inbox["notifications"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|n| n["kind"] == "admin_digest")
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    // Act
+    backend::jobs::admin_digest::run(pool.clone(), None).await.unwrap();
+
+    // Assert: Every admin is sent the counts
+    let sent = digests().await;
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0]["payload"]["pending_contributions"].as_i64().unwrap() >= 1);
+    assert!(sent[0]["payload"]["oldest_pending_at"].is_string());
+
+    // Act & Assert: Not again until the next day
+    backend::jobs::admin_digest::run(pool.clone(), None).await.unwrap();
+    assert_eq!(digests().await.len(), 1);
+    let recorded = sqlx::query_scalar!(r#"SELECT COUNT(*) as "count!" FROM admin_digests"#)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(recorded, 1);
+}
+
+#[tokio::test]
+async fn test_api_documents_per_audience() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let (_, admin_token) = create_user(&client, &address, &pool, "doc", "admin").await;
+    let (_, user_token) = create_user(&client, &address, &pool, "doc", "user").await;
+
+    let fetch = |path: &str, token: Option<&String>| {
+        let mut req = client.get(format!("{}{}", address, path));
+        if let Some(token) = token {
+            req = req.bearer_auth(token);
+        }
+        req.send()
+    };
+    let paths = |doc: &serde_json::Value| -> Vec<String> {
+        doc["paths"].as_object().unwrap().keys().cloned().collect()
+    };
+
+    // Act & Assert: The public document only has read-only, anonymous endpoints
+    let public: serde_json::Value = fetch("/api/docs/openapi.json", None)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let public_paths = paths(&public);
+    assert!(public_paths.contains(&"/api/architectures/{id}".to_string()));
+    assert!(public_paths.iter().all(|p| !p.starts_with("/api/admin")));
+    assert!(!public_paths.contains(&"/api/profile/me".to_string()));
+    for item in public["paths"].as_object().unwrap().values() {
+        assert_eq!(item.as_object().unwrap().keys().collect::<Vec<_>>(), ["get"]);
+    }
+    assert_eq!(
+        public["paths"]["/api/architectures/{id}"]["get"]["parameters"][0]["name"],
+        "id"
+    );
+
+    // Act & Assert: The user document needs a login and adds account endpoints
+    assert_eq!(fetch("/api/docs/user.json", None).await.unwrap().status(), 401);
+    let user: serde_json::Value = fetch("/api/docs/user.json", Some(&user_token))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let user_paths = paths(&user);
+    assert!(user_paths.contains(&"/api/profile/me".to_string()));
+    assert!(user["paths"]["/api/posts"]["post"]["security"].is_array());
+    assert!(user_paths.iter().all(|p| !p.starts_with("/api/admin")));
+
+    // Act & Assert: Only admins get the admin document
+    assert_eq!(
+        fetch("/api/admin/docs/openapi.json", Some(&user_token))
+            .await
+            .unwrap()
+            .status(),
+        403
+    );
+    let admin: serde_json::Value = fetch("/api/admin/docs/openapi.json", Some(&admin_token))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let admin_paths = paths(&admin);
+    assert!(admin_paths.contains(&"/api/admin/users/{id}".to_string()));
+    assert!(admin["paths"]["/api/admin/users/{id}"]["put"].is_object());
+    assert!(admin["paths"]["/api/admin/users/{id}"]["delete"].is_object());
+    assert!(admin_paths.len() > user_paths.len());
+}
+
+#[tokio::test]
+async fn test_catalog_export_bundle() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (admin_id, admin_token) = create_user(&client, &address, &pool, "exp", "admin").await;
+    let (_, user_token) = create_user(&client, &address, &pool, "exp", "user").await;
+
+    let created: serde_json::Value = client
+        .post(format!("{}/api/admin/architectures", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "category": "Bridge",
+            "name": "赵州桥",
+            "dynasty": "隋",
+            "location": "河北省石家庄市赵县",
+            "description": "现存最早的敞肩石拱桥",
+            "cover_img": "http://example.com/cover.jpg",
+            "carousel_imgs": ["http://example.com/1.jpg"]
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let arch_id = created["id"].as_i64().unwrap();
+    client
+        .put(format!("{}/api/admin/architectures/{}/translations/en", address, arch_id))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({"name": "Zhaozhou Bridge"}))
+        .send()
+        .await
+        .unwrap();
+    sqlx::query!(
+        "INSERT INTO architecture_image_checks (architecture_id, field, url, status) VALUES ($1, 'cover', 'http://example.com/cover.jpg', 'ok')",
+        arch_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Act 1: Non-admins cannot export
+    let forbidden = client
+        .post(format!("{}/api/admin/architectures/export", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(forbidden.status().as_u16(), 403);
+
+    // Act 2: Queue an export and run its job
+    let queued = client
+        .post(format!("{}/api/admin/architectures/export", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(queued.status().as_u16(), 202);
+    let job_id = queued.json::<serde_json::Value>().await.unwrap()["job_id"]
+        .as_i64()
+        .unwrap();
+    // Run it here rather than draining the shared queue, then take it off the queue
+    let payload = sqlx::query_scalar!("DELETE FROM jobs WHERE id = $1 RETURNING payload", job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let storage = backend::storage::LocalStorage::new(
+        std::env::temp_dir().join("ancient-arch-test-storage"),
+        b"admin_test_secret",
+    );
+    backend::jobs::catalog_export::run(&pool, &storage, &payload)
+        .await
+        .expect("Export failed");
+
+    // Assert: The newest export is listed and its bundle can be downloaded
+    let list: serde_json::Value = client
+        .get(format!("{}/api/admin/architectures/export", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let latest = &list["exports"][0];
+    assert_eq!(latest["requested_by"], admin_id);
+    let version = latest["version"].as_i64().unwrap();
+
+    let bundle: serde_json::Value = client
+        .get(format!("{}{}", address, latest["download_url"].as_str().unwrap()))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(bundle["format"], 1);
+    assert_eq!(bundle["version"], version);
+    let architectures = bundle["architectures"].as_array().unwrap();
+    assert_eq!(latest["architecture_count"], architectures.len());
+
+    let entry = architectures.iter().find(|a| a["id"] == arch_id).unwrap();
+    assert_eq!(entry["name"], "赵州桥");
+    assert_eq!(entry["translations"]["en"]["name"], "Zhaozhou Bridge");
+    assert!(entry["translations"]["en"]["description"].is_null());
+    assert_eq!(entry["images"][0]["field"], "cover");
+    assert_eq!(entry["images"][0]["link_status"], "ok");
+    assert_eq!(entry["images"][1]["url"], "http://example.com/1.jpg");
+    assert!(entry["images"][1]["link_status"].is_null());
+}
+
+#[tokio::test]
+async fn test_sync_changes_since_cursor() {
+    // Arrange: Entries that exist before the cursor
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let insert_arch = |name: &'static str| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query_scalar!(
+                r#"
+                INSERT INTO architectures (category, name, dynasty, location, description, cover_img, carousel_imgs)
+                VALUES ('Temple', $1, '唐', '山西', '...', 'http://example.com/a.jpg', '[]')
+                RETURNING id
+                "#,
+                name
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+        }
+    };
+    let translated = insert_arch("南禅寺").await;
+    let untouched = insert_arch("天宁寺").await;
+    let old_question = sqlx::query_scalar!(
+        r#"INSERT INTO questions (type, content, options, answer) VALUES ('single', 'Sync?', '["A","B"]', 'A') RETURNING id"#
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let since = sqlx::query_scalar!(r#"SELECT CURRENT_TIMESTAMP as "now!""#)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    // Act: Create, change and delete after it
+    let created = insert_arch("佛光寺").await;
+    let short_lived = insert_arch("临时").await;
+    sqlx::query!("DELETE FROM architectures WHERE id = $1", short_lived)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query!(
+        "INSERT INTO architecture_translations (architecture_id, locale, name) VALUES ($1, 'en', 'Nanchan Temple')",
+        translated
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    // Bookkeeping and no-op edits are not changes
+    sqlx::query!(
+        "UPDATE architectures SET name_pinyin = 'x', name = name WHERE id = $1",
+        untouched
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query!("UPDATE questions SET exposure_count = exposure_count + 1 WHERE id = $1", old_question)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let term = sqlx::query_scalar!(
+        "INSERT INTO glossary_terms (slug, term, definition) VALUES ($1, '鸱吻', 'Ridge ornament') RETURNING id",
+        format!("chiwen-{}", &uuid::Uuid::new_v4().to_string()[..8])
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    sqlx::query!("DELETE FROM questions WHERE id = $1", old_question)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let res = client
+        .get(format!("{}/api/sync/changes", address))
+        .query(&[("since", since.to_rfc3339())])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+    let changes: serde_json::Value = res.json().await.unwrap();
+
+    // Assert
+    let ids = |kind: &str, list: &str| -> Vec<i64> {
+        changes[kind][list]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|id| id.as_i64().unwrap())
+            .collect()
+    };
+    assert!(ids("architectures", "created").contains(&created));
+    assert!(ids("architectures", "updated").contains(&translated));
+    assert!(ids("architectures", "deleted").contains(&short_lived));
+    assert!(!ids("architectures", "created").contains(&short_lived));
+    for list in ["created", "updated", "deleted"] {
+        assert!(!ids("architectures", list).contains(&untouched));
+    }
+    assert!(ids("glossary", "created").contains(&term));
+    assert!(ids("questions", "deleted").contains(&old_question));
+    assert!(!ids("questions", "updated").contains(&old_question));
+
+    let until = chrono::DateTime::parse_from_rfc3339(changes["until"].as_str().unwrap()).unwrap();
+    assert!(until < chrono::Utc::now());
+
+    // A missing or malformed cursor is rejected
+    let bad = client
+        .get(format!("{}/api/sync/changes?since=yesterday", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(bad.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_post_tombstones_and_retention() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (user_id, _) = create_user(&client, &address, &pool, "tomb", "user").await;
+    let post_id = sqlx::query_scalar!(
+        "INSERT INTO posts (user_id, title, content) VALUES ($1, 'Gone soon', '...') RETURNING id",
+        user_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let tombstone = |id: i64| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query_scalar!(
+                "SELECT deleted_at FROM tombstones WHERE entity_type = 'post' AND entity_id = $1",
+                id
+            )
+            .fetch_optional(&pool)
+            .await
+            .unwrap()
+        }
+    };
+
+    // Act & Assert 1: Edits leave no tombstone; hiding the post does
+    sqlx::query!("UPDATE posts SET title = 'Edited' WHERE id = $1", post_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(tombstone(post_id).await.is_none());
+    sqlx::query!("UPDATE posts SET deleted_at = NOW() WHERE id = $1", post_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(tombstone(post_id).await.is_some());
+
+    // Act & Assert 2: The retention job prunes only old tombstones
+    sqlx::query!(
+        "INSERT INTO tombstones (entity_type, entity_id, deleted_at) VALUES ('post', $1, NOW() - INTERVAL '91 days')",
+        -post_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    backend::jobs::retention::run(pool.clone()).await.unwrap();
+    assert!(tombstone(-post_id).await.is_none());
+    assert!(tombstone(post_id).await.is_some());
+
+    // Act & Assert 3: Cursors older than the retention must start over
+    let sync = |since: chrono::DateTime<chrono::Utc>| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            client
+                .get(format!("{}/api/sync/changes", address))
+                .query(&[("since", since.to_rfc3339())])
+                .send()
+                .await
+                .unwrap()
+                .json::<serde_json::Value>()
+                .await
+                .unwrap()
+        }
+    };
+    let stale = sync(chrono::Utc::now() - chrono::Duration::days(100)).await;
+    assert_eq!(stale["reset_required"], true);
+    let fresh = sync(chrono::Utc::now() - chrono::Duration::hours(1)).await;
+    assert_eq!(fresh["reset_required"], false);
+}
+
+#[tokio::test]
+async fn test_broadcast_to_segment() {
+    // Arrange: A verified user and one who is neither verified nor active
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "bc", "admin").await;
+    let (_, reader_token) = create_user(&client, &address, &pool, "bc", "user").await;
+    let (idle_id, idle_token) = create_user(&client, &address, &pool, "bc", "user").await;
+    sqlx::query!(
+        "UPDATE users SET is_verified = FALSE, last_active_at = NOW() - INTERVAL '60 days' WHERE id = $1",
+        idle_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let broadcast = |segment: &'static str| {
+        let client = client.clone();
+        let address = address.clone();
+        let admin_token = admin_token.clone();
+        let pool = pool.clone();
+        async move {
+            let res = client
+                .post(format!("{}/api/admin/notifications/broadcast", address))
+                .header("Authorization", format!("Bearer {}", admin_token))
+                .json(&serde_json::json!({
+                    "title": "Maintenance", "message": "Back soon", "segment": segment
+                }))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(res.status().as_u16(), 202);
+            let id = res.json::<serde_json::Value>().await.unwrap()["broadcast_id"]
+                .as_i64()
+                .unwrap();
+
+            // Deliver every batch here rather than through the shared queue
+            while sqlx::query_scalar!("SELECT finished_at FROM broadcasts WHERE id = $1", id)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+                .is_none()
+            {
+                backend::services::broadcasts::deliver_batch(&pool, id).await.unwrap();
+            }
+            sqlx::query!(
+                "DELETE FROM jobs WHERE kind = 'broadcast_notifications' AND payload->>'broadcast_id' = $1",
+                id.to_string()
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            id
+        }
+    };
+    let inbox = |token: String| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            let body: serde_json::Value = client
+                .get(format!("{}/api/notifications", address))
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            body["notifications"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|n| n["kind"] == "broadcast")
+                .cloned()
+                .collect::<Vec<_>>()
+        }
+    };
+
+    // Act 1
+    let verified_id = broadcast("verified").await;
+    let active_id = broadcast("active").await;
+
+    // Assert 1: Only users in each segment were notified, once
+    let received = inbox(reader_token.clone()).await;
+    let ids: Vec<i64> = received
+        .iter()
+        .map(|n| n["payload"]["broadcast_id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(ids.iter().filter(|id| **id == verified_id).count(), 1);
+    assert_eq!(ids.iter().filter(|id| **id == active_id).count(), 1);
+    assert_eq!(received[0]["payload"]["title"], "Maintenance");
+    assert!(inbox(idle_token).await.is_empty());
+
+    // Act 2: The reader reads the first broadcast
+    let notification_id = received
+        .iter()
+        .find(|n| n["payload"]["broadcast_id"] == verified_id)
+        .unwrap()["id"]
+        .as_i64()
+        .unwrap();
+    client
+        .post(format!("{}/api/notifications/read", address))
+        .header("Authorization", format!("Bearer {}", reader_token))
+        .json(&serde_json::json!({"ids": [notification_id]}))
+        .send()
+        .await
+        .unwrap();
+
+    // Assert 2: Delivery stats
+    let list: Vec<serde_json::Value> = client
+        .get(format!("{}/api/admin/notifications/broadcasts", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let stats = list.iter().find(|b| b["id"] == verified_id).unwrap();
+    assert_eq!(stats["segment"], "verified");
+    assert!(stats["recipients"].as_i64().unwrap() >= 2);
+    assert!(stats["delivered"].as_i64().unwrap() >= 2);
+    assert_eq!(stats["read"], 1);
+    assert!(!stats["finished_at"].is_null());
+
+    // Only admins may broadcast
+    let denied = client
+        .post(format!("{}/api/admin/notifications/broadcast", address))
+        .header("Authorization", format!("Bearer {}", reader_token))
+        .json(&serde_json::json!({"title": "Hi", "message": "Hi"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(denied.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn test_segment_preview_export_and_broadcast() {
+    // Arrange: A verified user with three posts, and one with none
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "seg", "admin").await;
+    let (poster_id, poster_token) = create_user(&client, &address, &pool, "seg", "user").await;
+    let (quiet_id, quiet_token) = create_user(&client, &address, &pool, "seg", "user").await;
+    // Only possible for accounts from before usernames were restricted
+    sqlx::query!("UPDATE users SET username = username || ',\"q\"' WHERE id = $1", quiet_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    for _ in 0..3 {
+        sqlx::query!(
+            "INSERT INTO posts (user_id, title, content) VALUES ($1, 'Segment', '...')",
+            poster_id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    let usernames: Vec<String> =
+        sqlx::query_scalar!("SELECT username FROM users WHERE id = ANY($1) ORDER BY id", &[poster_id, quiet_id])
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    let get = |path: String| {
+        client
+            .get(format!("{}/api/admin/segments/{}", address, path))
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .send()
+    };
+
+    // Act 1: Preview
+    let preview: serde_json::Value = get("preview?min_posts=3&is_verified=true&active_within_days=1".into())
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Assert 1: Every sampled user matches
+    assert!(preview["count"].as_i64().unwrap() >= 1);
+    let sample = preview["sample"].as_array().unwrap();
+    assert!(!sample.is_empty() && sample.len() <= 10);
+    assert!(sample.iter().all(|u| u["posts"].as_i64().unwrap() >= 3 && u["is_verified"] == true));
+    assert_eq!(get("preview?active_within_days=0".into()).await.unwrap().status().as_u16(), 400);
+
+    // Act & Assert 2: The export lists the same users, quoting where needed
+    let registered_before = chrono::Utc::now() + chrono::Duration::hours(1);
+    let res = get(format!(
+        "export?min_posts=3&registered_before={}",
+        registered_before.format("%Y-%m-%dT%H:%M:%SZ")
+    ))
+    .await
+    .unwrap();
+    assert_eq!(res.headers()["content-type"], "text/csv; charset=utf-8");
+    let csv = res.text().await.unwrap();
+    assert!(csv.starts_with("id,username,is_verified,posts,created_at,last_active_at\n"));
+    assert!(csv.contains(&format!("\n{},{},true,3,", poster_id, usernames[0])));
+    let everyone = get("export".into()).await.unwrap().text().await.unwrap();
+    assert!(everyone.contains(&format!("\n{},\"{}\",", quiet_id, usernames[1].replace('"', "\"\""))));
+    assert!(!csv.contains(&format!("\n{},", quiet_id)));
+
+    // Act 3: Broadcast to a custom filter
+    let res = client
+        .post(format!("{}/api/admin/notifications/broadcast", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({
+            "title": "Thanks", "message": "For posting", "filter": { "min_posts": 3 }
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 202);
+    let broadcast_id = res.json::<serde_json::Value>().await.unwrap()["broadcast_id"]
+        .as_i64()
+        .unwrap();
+    while sqlx::query_scalar!("SELECT finished_at FROM broadcasts WHERE id = $1", broadcast_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+        .is_none()
+    {
+        backend::services::broadcasts::deliver_batch(&pool, broadcast_id).await.unwrap();
+    }
+    sqlx::query!(
+        "DELETE FROM jobs WHERE kind = 'broadcast_notifications' AND payload->>'broadcast_id' = $1",
+        broadcast_id.to_string()
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Assert 3: Only the poster was notified
+    let received = |token: String| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            let body: serde_json::Value = client
+                .get(format!("{}/api/notifications", address))
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            body["notifications"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|n| n["payload"]["broadcast_id"] == broadcast_id)
+        }
+    };
+    assert!(received(poster_token).await);
+    assert!(!received(quiet_token).await);
+    let list: Vec<serde_json::Value> = client
+        .get(format!("{}/api/admin/notifications/broadcasts", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let stats = list.iter().find(|b| b["id"] == broadcast_id).unwrap();
+    assert_eq!(stats["segment"], "custom");
+    assert_eq!(stats["filter"], serde_json::json!({ "min_posts": 3 }));
+}
+
+#[tokio::test]
+async fn test_api_usage_and_anomalies() {
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act & Assert 1: Requests are counted in samples of ten
+    let (_, token) = create_user(&client, &address, &pool, "usage_user", "user").await;
+    for _ in 0..25 {
+        client
+            .get(format!("{}/api/profile/me", address))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap();
+    }
+    let usage: serde_json::Value = client
+        .get(format!("{}/api/profile/usage", address))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(usage["sample_every"], 10);
+    assert_eq!(usage["total"], 20);
+    assert_eq!(usage["days"][0]["date"], chrono::Utc::now().date_naive().to_string());
+
+    // Arrange 2: Usage histories on a day no other run is likely to use
+    let date = chrono::Utc::now().date_naive()
+        - chrono::Days::new(20 + u64::from(uuid::Uuid::new_v4().as_bytes()[0] % 50));
+    let mut users = Vec::new();
+    for (prefix, usual, on_the_day) in [
+        ("usage_spike", Some(100), 1500),
+        ("usage_steady", Some(1000), 1200),
+        ("usage_new", None, 1000),
+        ("usage_quiet", None, 500),
+    ] {
+        let (id, _) = create_user(&client, &address, &pool, prefix, "user").await;
+        sqlx::query("INSERT INTO api_usage_daily (user_id, day, requests) VALUES ($1, $2, $3)")
+            .bind(id)
+            .bind(date)
+            .bind(on_the_day as i64)
+            .execute(&pool)
+            .await
+            .unwrap();
+        if let Some(usual) = usual {
+            sqlx::query(
+                "INSERT INTO api_usage_daily (user_id, day, requests) SELECT $1, $2::DATE - n, $3 FROM generate_series(1, 14) n",
+            )
+            .bind(id)
+            .bind(date)
+            .bind(usual as i64)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        users.push(id);
+    }
+
+    // Act 2
+    let (_, moderator_token) = create_user(&client, &address, &pool, "usage_mod", "moderator").await;
+    let url = format!("{}/api/admin/usage/anomalies?date={}&limit=100", address, date);
+    let body: serde_json::Value = client
+        .get(&url)
+        .bearer_auth(&moderator_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Assert 2: The spike and the newcomer over the minimum are flagged
+    assert_eq!(body["baseline_days"], 14);
+    let flagged = |id: i64| {
+        body["anomalies"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|a| a["user_id"] == id)
+            .cloned()
+    };
+    let spike = flagged(users[0]).expect("spike is flagged");
+    assert_eq!(spike["requests"], 1500);
+    assert_eq!(spike["baseline"], 100.0);
+    assert_eq!(spike["ratio"], 15.0);
+    assert!(flagged(users[1]).is_none());
+    assert!(flagged(users[2]).expect("newcomer is flagged")["ratio"].is_null());
+    assert!(flagged(users[3]).is_none());
+
+    // Act & Assert 3: Not for regular users
+    let res = client.get(&url).bearer_auth(&token).send().await.unwrap();
+    assert_eq!(res.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn test_ban_user() {
+    // Arrange: A verified user with a post, and a moderator
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let (_, moderator_token) = create_user(&client, &address, &pool, "ban_mod", "moderator").await;
+    let (user_id, user_token) = create_user(&client, &address, &pool, "ban_user", "user").await;
+    let (admin_id, _) = create_user(&client, &address, &pool, "ban_admin", "admin").await;
+    let post: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .bearer_auth(&user_token)
+        .json(&serde_json::json!({"title": "Before the ban", "content": "..."}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let post_id = post["id"].as_i64().unwrap();
+
+    let set = |id: i64, body: serde_json::Value| {
+        client
+            .put(format!("{}/api/admin/users/{}/ban", address, id))
+            .bearer_auth(&moderator_token)
+            .json(&body)
+            .send()
+    };
+    let write = || {
+        client
+            .post(format!("{}/api/posts/{}/like", address, post_id))
+            .bearer_auth(&user_token)
+            .send()
+    };
+
+    // Act & Assert 1: Staff and past dates are refused
+    let res = set(admin_id, serde_json::json!({"banned": true, "reason": "No"})).await.unwrap();
+    assert_eq!(res.status().as_u16(), 400);
+    let past = chrono::Utc::now() - chrono::Duration::hours(1);
+    let res = set(user_id, serde_json::json!({"banned": true, "until": past, "reason": "No"})).await.unwrap();
+    assert_eq!(res.status().as_u16(), 400);
+
+    // Act 2: Ban for a day
+    let until = chrono::Utc::now() + chrono::Duration::days(1);
+    let res = set(user_id, serde_json::json!({"banned": true, "until": until, "reason": "Spamming"}))
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+
+    // Assert 2: Writes are refused with the reason, reads and content still work
+    let res = write().await.unwrap();
+    assert_eq!(res.status().as_u16(), 403);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "banned");
+    assert_eq!(body["reason"], "Spamming");
+    let me: serde_json::Value = client
+        .get(format!("{}/api/profile/me", address))
+        .bearer_auth(&user_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["ban"]["reason"], "Spamming");
+    let res = client.get(format!("{}/api/posts/{}", address, post_id)).send().await.unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+    let inbox: serde_json::Value = client
+        .get(format!("{}/api/notifications", address))
+        .bearer_auth(&user_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(inbox["notifications"][0]["kind"], "account_banned");
+
+    // Act & Assert 3: Lifting the ban restores writes
+    let res = set(user_id, serde_json::json!({"banned": false, "reason": "Appeal accepted"})).await.unwrap();
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert!(body["ban"].is_null());
+    assert_eq!(body["changed"], true);
+    assert_eq!(write().await.unwrap().status().as_u16(), 200);
+    let res = set(user_id, serde_json::json!({"banned": false, "reason": "Again"})).await.unwrap();
+    assert_eq!(res.json::<serde_json::Value>().await.unwrap()["changed"], false);
+
+    // Act & Assert 4: An expired ban no longer applies
+    sqlx::query("UPDATE users SET banned_until = NOW() - INTERVAL '1 minute', ban_reason = 'Old' WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(write().await.unwrap().status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_architecture_dimensions() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+
+    let category = format!("Pagoda-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let create = |name: &str, dims: serde_json::Value| {
+        let mut body = serde_json::json!({
+            "category": category,
+            "name": name,
+            "dynasty": "Liao",
+            "location": "山西省朔州市应县",
+            "description": "Pagoda",
+            "cover_img": "http://example.com/a.jpg",
+            "carousel_imgs": []
+        });
+        body.as_object_mut().unwrap().extend(dims.as_object().unwrap().clone());
+        client
+            .post(format!("{}/api/admin/architectures", address))
+            .bearer_auth(&admin_token)
+            .json(&body)
+            .send()
+    };
+
+    // Act & Assert 1: Units are converted to metric on input, bad values rejected
+    let res = create("Wooden", serde_json::json!({"height_m": "65.88 m", "footprint_m2": 1, "bays_wide": 5, "bays_deep": 5})).await.unwrap();
+    assert_eq!(res.status().as_u16(), 201);
+    let wooden = res.json::<serde_json::Value>().await.unwrap()["id"].as_i64().unwrap();
+    let res = create("Brick", serde_json::json!({"height_m": "30 丈"})).await.unwrap();
+    let brick = res.json::<serde_json::Value>().await.unwrap()["id"].as_i64().unwrap();
+    let res = create("Unmeasured", serde_json::json!({})).await.unwrap();
+    let unmeasured = res.json::<serde_json::Value>().await.unwrap()["id"].as_i64().unwrap();
+    for dims in [
+        serde_json::json!({"height_m": "12 furlongs"}),
+        serde_json::json!({"height_m": 5000}),
+        serde_json::json!({"footprint_m2": -3}),
+        serde_json::json!({"bays_wide": 0}),
+    ] {
+        let res = create("Bad", dims.clone()).await.unwrap();
+        assert_eq!(res.status().as_u16(), 400, "{} should be rejected", dims);
+    }
+
+    let res = client
+        .put(format!("{}/api/admin/architectures/{}", address, wooden))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"footprint_m2": "2 亩"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+    let detail: serde_json::Value = client
+        .get(format!("{}/api/architectures/{}", address, wooden))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["height_m"], 65.88);
+    assert!((detail["footprint_m2"].as_f64().unwrap() - 1333.33).abs() < 0.01);
+    assert_eq!(detail["bays_wide"], 5);
+
+    // Act & Assert 2: Tallest first, unmeasured last; filters by height
+    let list = |query: Vec<(&'static str, &'static str)>| {
+        client
+            .get(format!("{}/api/architectures", address))
+            .query(&[("category", category.as_str())])
+            .query(&query)
+            .send()
+    };
+    let ids = |list: Vec<serde_json::Value>| list.iter().map(|a| a["id"].as_i64().unwrap()).collect::<Vec<_>>();
+    let tallest: Vec<serde_json::Value> = list(vec![("sort", "height")]).await.unwrap().json().await.unwrap();
+    assert_eq!(ids(tallest), vec![brick, wooden, unmeasured]);
+    let shortest: Vec<serde_json::Value> = list(vec![("sort", "height"), ("order", "asc")]).await.unwrap().json().await.unwrap();
+    assert_eq!(ids(shortest), vec![wooden, brick, unmeasured]);
+    let tall: Vec<serde_json::Value> = list(vec![("min_height", "70")]).await.unwrap().json().await.unwrap();
+    assert_eq!(ids(tall), vec![brick]);
+    assert_eq!(list(vec![("sort", "age")]).await.unwrap().status().as_u16(), 400);
+
+    // Act & Assert 3: Comparison keeps the requested order and converts units
+    let compared: serde_json::Value = client
+        .get(format!("{}/api/architectures/compare", address))
+        .query(&[("ids", format!("{},{}", wooden, brick).as_str()), ("units", "imperial")])
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(compared["units"], "imperial");
+    assert_eq!(compared["architectures"][0]["id"], wooden);
+    assert_eq!(compared["architectures"][0]["height"], 216.14);
+    assert_eq!(compared["architectures"][0]["bays_deep"], 5);
+    assert_eq!(compared["architectures"][1]["height"], 328.08);
+    assert!(compared["architectures"][1]["footprint"].is_null());
+
+    for ids in [format!("{}", wooden), format!("{},{}", wooden, i64::MAX), "1,x".to_string()] {
+        let res = client
+            .get(format!("{}/api/architectures/compare", address))
+            .query(&[("ids", ids.as_str())])
+            .send()
+            .await
+            .unwrap();
+        assert!(matches!(res.status().as_u16(), 400 | 404), "{}", ids);
+    }
+}
+
+#[tokio::test]
+async fn test_integrity_verify_and_repair() {
+    // Arrange: A liked post with a reply thread
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let (_, moderator_token) = create_user(&client, &address, &pool, "mod", "moderator").await;
+    let (_, author_token) = create_user(&client, &address, &pool, "aut", "user").await;
+
+    let post: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .bearer_auth(&author_token)
+        .json(&serde_json::json!({"title": "Counted", "content": "Twice"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let post_id = post["id"].as_i64().unwrap();
+    // Two authors, as comments on one post are throttled per user
+    let comment = |token: &str, parent_id: Option<i64>| {
+        client
+            .post(format!("{}/api/posts/{}/comments", address, post_id))
+            .bearer_auth(token)
+            .json(&serde_json::json!({"content": "Thread", "parent_id": parent_id}))
+            .send()
+    };
+    let top: serde_json::Value = comment(&author_token, None).await.unwrap().json().await.unwrap();
+    let top_id = top["id"].as_i64().unwrap();
+    let reply: serde_json::Value =
+        comment(&admin_token, Some(top_id)).await.unwrap().json().await.unwrap();
+    let reply_id = reply["id"].as_i64().unwrap();
+    client
+        .post(format!("{}/api/posts/{}/like", address, post_id))
+        .bearer_auth(&author_token)
+        .send()
+        .await
+        .unwrap();
+
+    let run = |method: reqwest::Method, path: &'static str, token: String| {
+        client
+            .request(method, format!("{}/api/admin/system/integrity{}", address, path))
+            .bearer_auth(token)
+            .send()
+    };
+    let check = |report: &serde_json::Value, name: &str| {
+        report["checks"].as_array().unwrap().iter().find(|c| c["name"] == name).unwrap().clone()
+    };
+    let res = run(reqwest::Method::POST, "/repair", admin_token.clone()).await.unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+
+    // Break the counters, the thread and leave a like behind with the ghost user
+    sqlx::query("UPDATE posts SET likes_count = 7, comments_count = 0 WHERE id = $1")
+        .bind(post_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE comments SET root_id = NULL WHERE id = $1")
+        .bind(reply_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO post_likes (user_id, post_id) SELECT id, $1 FROM users WHERE username = 'ghost'")
+        .bind(post_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Act & Assert 1: Checking reports each problem and changes nothing
+    let res = run(reqwest::Method::GET, "", moderator_token).await.unwrap();
+    assert_eq!(res.status().as_u16(), 403);
+    let report: serde_json::Value =
+        run(reqwest::Method::GET, "", admin_token.clone()).await.unwrap().json().await.unwrap();
+    assert_eq!(report["repaired"], false);
+    for (name, id) in [
+        ("orphaned_likes", post_id),
+        ("post_likes_count", post_id),
+        ("post_comments_count", post_id),
+        ("comment_threads", reply_id),
+    ] {
+        let found = check(&report, name);
+        assert!(found["sample_ids"].as_array().unwrap().contains(&serde_json::json!(id)), "{}", name);
+        assert!(found["remaining"].is_null());
+    }
+    let likes: i32 = sqlx::query_scalar("SELECT likes_count FROM posts WHERE id = $1")
+        .bind(post_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(likes, 7);
+
+    // Act & Assert 2: Repairing derives the right values again
+    let report: serde_json::Value = run(reqwest::Method::POST, "/repair", admin_token)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(report["repaired"], true);
+    assert_eq!(check(&report, "comment_threads")["remaining"], 0);
+    let (likes, comments): (i32, i32) =
+        sqlx::query_as("SELECT likes_count, comments_count FROM posts WHERE id = $1")
+            .bind(post_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!((likes, comments), (1, 2));
+    let root: Option<i64> = sqlx::query_scalar("SELECT root_id FROM comments WHERE id = $1")
+        .bind(reply_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(root, Some(top_id));
+}
+
+#[tokio::test]
+async fn test_image_placeholders_on_covers_and_posts() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+    let (_, user_token) = create_user(&client, &address, &pool, "usr", "user").await;
+
+    let tag = uuid::Uuid::new_v4();
+    let cover = format!("https://img.example.com/{}/cover.jpg", tag);
+    let inline = format!("https://img.example.com/{}/inline.png", tag);
+    let private = format!("http://127.0.0.1:9/{}.png", tag);
+    let category = format!("Hall-{}", &tag.to_string()[..8]);
+
+    // Act 1: Create an architecture and a post with images
+    let res = client
+        .post(format!("{}/api/admin/architectures", address))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({
+            "category": category,
+            "name": "Placeholder Hall",
+            "dynasty": "Tang",
+            "location": "山西省忻州市五台县",
+            "description": "Hall",
+            "cover_img": cover,
+            "carousel_imgs": []
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 201);
+    let arch_id = res.json::<serde_json::Value>().await.unwrap()["id"].as_i64().unwrap();
+
+    let res = client
+        .post(format!("{}/api/posts", address))
+        .bearer_auth(&user_token)
+        .json(&serde_json::json!({
+            "title": "Photos",
+            "content": format!(r#"<p><img src="{}"> and <img src="{}"></p>"#, inline, private)
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 201);
+    let post_id = res.json::<serde_json::Value>().await.unwrap()["id"].as_i64().unwrap();
+
+    // Assert 1: A job was queued per image, and nothing is exposed yet
+    let mut queued = sqlx::query_scalar!(
+        r#"SELECT payload->>'url' as "url!" FROM jobs WHERE kind = 'image_placeholder' AND payload->>'url' LIKE '%' || $1 || '%'"#,
+        tag.to_string()
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    queued.sort();
+    let mut expected = vec![cover.clone(), inline.clone(), private.clone()];
+    expected.sort();
+    assert_eq!(queued, expected);
+
+    let detail: serde_json::Value = client
+        .get(format!("{}/api/architectures/{}", address, arch_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(detail["cover_color"].is_null());
+
+    // Act 2: Process the images; loopback targets are refused without a request
+    backend::jobs::image_placeholder::run(&pool, &serde_json::json!({"url": private}))
+        .await
+        .unwrap();
+    for url in [&cover, &inline] {
+        sqlx::query!(
+            r#"
+            UPDATE image_placeholders
+            SET status = 'ok', dominant_color = '#8a3324', blurhash = 'LEHV6nWB2yk8pyo0adR*.7kCMdnj',
+                width = 800, height = 600, processed_at = NOW()
+            WHERE url = $1
+            "#,
+            url
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    // Assert 2: Covers and post images carry their placeholders; failed ones are left out
+    let failed = sqlx::query_scalar!("SELECT status FROM image_placeholders WHERE url = $1", private)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(failed, "failed");
+
+    let detail: serde_json::Value = client
+        .get(format!("{}/api/architectures/{}", address, arch_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["cover_color"], "#8a3324");
+    assert_eq!(detail["cover_blurhash"], "LEHV6nWB2yk8pyo0adR*.7kCMdnj");
+
+    let list: serde_json::Value = client
+        .get(format!("{}/api/architectures?category={}", address, category))
+        .header("X-Lite", "1")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let summary = list
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["id"] == arch_id)
+        .expect("architecture listed");
+    assert_eq!(summary["cover_color"], "#8a3324");
+
+    let post: serde_json::Value = client
+        .get(format!("{}/api/posts/{}", address, post_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let images = post["images"].as_array().unwrap();
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0]["url"], inline.as_str());
+    assert_eq!(images[0]["width"], 800);
+
+    // Act & Assert 3: Re-saving the same cover does not queue it again
+    let res = client
+        .put(format!("{}/api/admin/architectures/{}", address, arch_id))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"cover_img": cover}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+    let count = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM jobs WHERE kind = 'image_placeholder' AND payload->>'url' = $1"#,
+        cover
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn test_contribution_attachments_for_reviewers() {
+    // Arrange: A pending question contribution
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "att_adm", "admin").await;
+    let (_, contributor_token) = create_user(&client, &address, &pool, "att_usr", "user").await;
+    let (_, other_token) = create_user(&client, &address, &pool, "att_oth", "user").await;
+    let tag = &uuid::Uuid::new_v4().to_string()[..8];
+    let contrib: serde_json::Value = client
+        .post(format!("{}/api/contributions", address))
+        .bearer_auth(&contributor_token)
+        .json(&serde_json::json!({"type": "question", "data": {
+            "question_type": "single",
+            "content": format!("Attached {}", tag),
+            "options": ["A", "B"],
+            "answer": "A",
+            "analysis": "See the scan"
+        }}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let contrib_id = contrib["id"].as_i64().unwrap();
+    let upload_url = format!("{}/api/contributions/{}/attachments", address, contrib_id);
+    let upload = |token: &str, filename: &str, body: Vec<u8>| {
+        client
+            .post(&upload_url)
+            .bearer_auth(token)
+            .query(&[("filename", filename)])
+            .header("Content-Type", "application/octet-stream")
+            .body(body)
+            .send()
+    };
+    let scan = b"%PDF-1.4\n% gazetteer page 12\n%%EOF\n".to_vec();
+
+    // Act 1: Attach a PDF scan; the type comes from the content
+    let res = upload(&contributor_token, "C:\\scans\\gazetteer.pdf", scan.clone())
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 201);
+    let attached: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(attached["filename"], "gazetteer.pdf");
+    assert_eq!(attached["content_type"], "application/pdf");
+    assert_eq!(attached["size_bytes"], scan.len());
+    assert!(attached.get("download_url").is_none());
+
+    // Assert: Only the author can attach, and only supported files
+    let gif = b"GIF89a\x01\x00\x01\x00".to_vec();
+    assert_eq!(upload(&contributor_token, "a.gif", gif).await.unwrap().status().as_u16(), 400);
+    assert_eq!(upload(&other_token, "x.pdf", scan.clone()).await.unwrap().status().as_u16(), 404);
+    let anonymous = client.post(&upload_url).body(scan.clone()).send().await.unwrap();
+    assert_eq!(anonymous.status().as_u16(), 401);
+    let mut oversized = b"\xFF\xD8\xFF".to_vec();
+    oversized.resize(10 * 1024 * 1024 + 1, 0);
+    assert_eq!(upload(&contributor_token, "big.jpg", oversized).await.unwrap().status().as_u16(), 413);
+
+    // At most five per contribution
+    for i in 0..4 {
+        let photo = b"\x89PNG\r\n\x1a\n rest of the image".to_vec();
+        let res = upload(&contributor_token, &format!("photo{}.png", i), photo).await.unwrap();
+        assert_eq!(res.status().as_u16(), 201);
+    }
+    let res = upload(&contributor_token, "sixth.pdf", scan.clone()).await.unwrap();
+    assert_eq!(res.status().as_u16(), 400);
+
+    // Act 2: Reviewers list them with links; contributors cannot
+    let list_url = format!("{}/api/admin/contributions/{}/attachments", address, contrib_id);
+    let res = client.get(&list_url).bearer_auth(&contributor_token).send().await.unwrap();
+    assert_eq!(res.status().as_u16(), 403);
+    let listed: Vec<serde_json::Value> = client
+        .get(&list_url)
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 5);
+    assert_eq!(listed[0]["filename"], "gazetteer.pdf");
+    assert_eq!(listed[1]["content_type"], "image/png");
+    let link = listed[0]["download_url"].as_str().unwrap();
+    let file = client.get(format!("{}{}", address, link)).send().await.unwrap();
+    assert_eq!(file.status().as_u16(), 200);
+    assert_eq!(file.headers()["content-type"], "application/pdf");
+    assert_eq!(file.bytes().await.unwrap().to_vec(), scan);
+
+    // Act 3: Reviewed contributions take no more attachments
+    let res = client
+        .put(format!("{}/api/admin/contributions/{}/review", address, contrib_id))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"status": "rejected", "admin_comment": "Already listed"}))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let res = upload(&contributor_token, "late.pdf", scan.clone()).await.unwrap();
+    assert_eq!(res.status().as_u16(), 400);
+
+    // Act 4: The retention job keeps them for 30 days after review, then deletes them
+    let storage: std::sync::Arc<dyn backend::storage::Storage> =
+        std::sync::Arc::new(backend::storage::LocalStorage::new(
+            std::env::temp_dir().join("ancient-arch-test-storage"),
+            b"admin_test_secret",
+        ));
+    let keys = sqlx::query_scalar!(
+        "SELECT storage_key FROM contribution_attachments WHERE contribution_id = $1",
+        contrib_id
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    backend::jobs::contribution_attachments::run(pool.clone(), storage.clone())
+        .await
+        .unwrap();
+    assert!(storage.get(&keys[0]).await.unwrap().is_some());
+
+    sqlx::query!(
+        "UPDATE contributions SET reviewed_at = NOW() - INTERVAL '31 days' WHERE id = $1",
+        contrib_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    backend::jobs::contribution_attachments::run(pool.clone(), storage.clone())
+        .await
+        .unwrap();
+    let remaining = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM contribution_attachments WHERE contribution_id = $1"#,
+        contrib_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(remaining, 0);
+    for key in keys {
+        assert!(storage.get(&key).await.unwrap().is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_contribution_review_thread() {
+    // Arrange: A pending question contribution
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (admin_id, admin_token) = create_user(&client, &address, &pool, "thr_adm", "admin").await;
+    let (contributor_id, contributor_token) =
+        create_user(&client, &address, &pool, "thr_usr", "user").await;
+    let (_, other_token) = create_user(&client, &address, &pool, "thr_oth", "user").await;
+    let tag = &uuid::Uuid::new_v4().to_string()[..8];
+    let contrib: serde_json::Value = client
+        .post(format!("{}/api/contributions", address))
+        .bearer_auth(&contributor_token)
+        .json(&serde_json::json!({"type": "question", "data": {
+            "question_type": "single",
+            "content": format!("Threaded {}", tag),
+            "options": ["A", "B"],
+            "answer": "A",
+            "analysis": "From the survey"
+        }}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let contrib_id = contrib["id"].as_i64().unwrap();
+    let admin_url = format!("{}/api/admin/contributions/{}/messages", address, contrib_id);
+    let own_url = format!("{}/api/contributions/{}/messages", address, contrib_id);
+    let send = |url: &str, token: &str, body: &str| {
+        client
+            .post(url)
+            .bearer_auth(token)
+            .json(&serde_json::json!({"body": body}))
+            .send()
+    };
+    let inbox = |token: String| {
+        let request = client.get(format!("{}/api/notifications", address)).bearer_auth(token);
+        async move {
+            let inbox: serde_json::Value = request.send().await.unwrap().json().await.unwrap();
+            inbox["notifications"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|n| n["kind"] == "contribution_message")
+                .cloned()
+                .collect::<Vec<_>>()
+        }
+    };
+
+    // Act 1: The reviewer asks for a source without rejecting
+    let res = send(&admin_url, &admin_token, "Which survey?").await.unwrap();
+    assert_eq!(res.status().as_u16(), 201);
+    let asked: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(asked["author_id"], admin_id);
+    assert_eq!(asked["from_reviewer"], true);
+
+    // Assert: The contribution stays pending and the contributor is notified
+    let status = sqlx::query_scalar!("SELECT status FROM contributions WHERE id = $1", contrib_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "pending");
+    let notices = inbox(contributor_token.clone()).await;
+    assert_eq!(notices.len(), 1);
+    assert_eq!(notices[0]["payload"]["contribution_id"], contrib_id);
+    assert_eq!(notices[0]["payload"]["message_id"], asked["id"]);
+
+    // Act 2: The contributor answers; the reviewer is notified
+    let res = send(&own_url, &contributor_token, "The 1934 survey").await.unwrap();
+    assert_eq!(res.status().as_u16(), 201);
+    assert_eq!(inbox(admin_token.clone()).await.len(), 1);
+
+    // Assert: Both sides see the thread in order
+    for (url, token) in [(&own_url, &contributor_token), (&admin_url, &admin_token)] {
+        let thread: Vec<serde_json::Value> =
+            client.get(url).bearer_auth(token).send().await.unwrap().json().await.unwrap();
+        assert_eq!(thread.len(), 2);
+        assert_eq!(thread[0]["body"], "Which survey?");
+        assert_eq!(thread[1]["author_id"], contributor_id);
+        assert_eq!(thread[1]["from_reviewer"], false);
+    }
+
+    // Others cannot read or write it, and empty messages are rejected
+    let res = client.get(&own_url).bearer_auth(&other_token).send().await.unwrap();
+    assert_eq!(res.status().as_u16(), 404);
+    assert_eq!(send(&own_url, &other_token, "Hi").await.unwrap().status().as_u16(), 404);
+    assert_eq!(send(&admin_url, &contributor_token, "Hi").await.unwrap().status().as_u16(), 403);
+    assert_eq!(send(&own_url, &contributor_token, "").await.unwrap().status().as_u16(), 400);
+
+    // Act 3: The review comment closes the thread
+    let res = client
+        .put(format!("{}/api/admin/contributions/{}/review", address, contrib_id))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"status": "approved", "admin_comment": "Thanks, approved"}))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let mine: Vec<serde_json::Value> = client
+        .get(format!("{}/api/profile/contributions", address))
+        .bearer_auth(&contributor_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let reviewed = mine.iter().find(|c| c["id"] == contrib_id).unwrap();
+    assert_eq!(reviewed["message_count"], 3);
+    assert_eq!(inbox(contributor_token.clone()).await.len(), 2);
+    let res = send(&own_url, &contributor_token, "One more thing").await.unwrap();
+    assert_eq!(res.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_auto_approval_for_trusted_contributors() {
+    // Arrange: Trusted users publish questions directly after two reviewed approvals
+    let (address, pool) = spawn_app_with(AutoApprovalConfig {
+        types: vec!["question".to_string()],
+        min_approved: 2,
+        max_rejected_percent: 10,
+    })
+    .await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "aa_adm", "admin").await;
+    let (user_id, user_token) = create_user(&client, &address, &pool, "aa_usr", "user").await;
+    let tag = &uuid::Uuid::new_v4().to_string()[..8];
+    let question = |n: u32| {
+        serde_json::json!({"type": "question", "data": {
+            "question_type": "single",
+            "content": format!("Auto {} {}", tag, n),
+            "options": ["A", "B"],
+            "answer": "A"
+        }})
+    };
+    let submit = |body: serde_json::Value| {
+        client
+            .post(format!("{}/api/contributions", address))
+            .bearer_auth(&user_token)
+            .json(&body)
+            .send()
+    };
+    let trust_level = || async {
+        let overview: serde_json::Value = client
+            .get(format!("{}/api/admin/users/{}/overview", address, user_id))
+            .bearer_auth(&admin_token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        overview["trust_level"].as_str().unwrap().to_string()
+    };
+    assert_eq!(trust_level().await, "new");
+
+    // Approvals from earlier days; one is not enough yet
+    let approve_past = |days: i32| {
+        sqlx::query!(
+            r#"
+            INSERT INTO contributions (user_id, type, data, status, created_at, reviewed_at)
+            VALUES ($1, 'question', '{}', 'approved', NOW() - make_interval(days => $2), NOW())
+            "#,
+            user_id,
+            days
+        )
+        .execute(&pool)
+    };
+    approve_past(3).await.unwrap();
+    assert_eq!(trust_level().await, "contributor");
+    approve_past(2).await.unwrap();
+    assert_eq!(trust_level().await, "trusted");
+
+    // Act 1: An architecture still waits for review; a question does not
+    let res = submit(serde_json::json!({"type": "architecture", "data": {
+        "category": "Temple", "name": format!("Auto {}", tag), "dynasty": "Tang",
+        "location": "Xi'an", "description": "A temple", "cover_img": "http://example.com/a.jpg",
+        "carousel_imgs": []
+    }}))
+    .await
+    .unwrap();
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["status"], "pending");
+    sqlx::query!("DELETE FROM contributions WHERE id = $1", body["id"].as_i64().unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let res = submit(question(1)).await.unwrap();
+    assert_eq!(res.status().as_u16(), 201);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["status"], "approved");
+    let contrib_id = body["id"].as_i64().unwrap();
+
+    // Assert: The question is live and listed for audit
+    let question_id = sqlx::query_scalar!(
+        "SELECT id FROM questions WHERE contribution_id = $1",
+        contrib_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let unaudited: Vec<serde_json::Value> = client
+        .get(format!("{}/api/admin/contributions/unaudited", address))
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entry = unaudited.iter().find(|c| c["id"] == contrib_id).unwrap();
+    assert_eq!(entry["published_id"], question_id);
+
+    // Act 2: Rolling it back deletes the question and tells the contributor why
+    let rollback_url = format!("{}/api/admin/contributions/{}/rollback", address, contrib_id);
+    let res = client
+        .post(&rollback_url)
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"reason": "Duplicate of an existing question"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+    let remaining = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) as "count!" FROM questions WHERE id = $1"#,
+        question_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(remaining, 0);
+    let thread: Vec<serde_json::Value> = client
+        .get(format!("{}/api/contributions/{}/messages", address, contrib_id))
+        .bearer_auth(&user_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(thread[0]["body"], "Duplicate of an existing question");
+    let inbox: serde_json::Value = client
+        .get(format!("{}/api/notifications", address))
+        .bearer_auth(&user_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let reviewed = inbox["notifications"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|n| n["kind"] == "contribution_reviewed")
+        .unwrap();
+    assert_eq!(reviewed["payload"]["contribution_id"], contrib_id);
+    assert_eq!(reviewed["payload"]["status"], "rejected");
+
+    // Assert: The rejection costs the trust, and a second audit finds nothing
+    assert_eq!(trust_level().await, "contributor");
+    let res = client
+        .post(&rollback_url)
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 404);
+    let res = client
+        .post(format!("{}/api/admin/contributions/{}/confirm", address, contrib_id))
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_revert_approved_contribution() {
+    // Arrange: An approved architecture contribution, edited since
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (admin_id, admin_token) = create_user(&client, &address, &pool, "rv_adm", "admin").await;
+    let (_, contributor_token) = create_user(&client, &address, &pool, "rv_usr", "user").await;
+    let tag = &uuid::Uuid::new_v4().to_string()[..8];
+    let contrib: serde_json::Value = client
+        .post(format!("{}/api/contributions", address))
+        .bearer_auth(&contributor_token)
+        .json(&serde_json::json!({"type": "architecture", "data": {
+            "category": "Temple", "name": format!("Revert {}", tag), "dynasty": "Tang",
+            "location": "Xi'an", "description": "A temple", "cover_img": "http://example.com/a.jpg",
+            "carousel_imgs": []
+        }}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let contrib_id = contrib["id"].as_i64().unwrap();
+    let res = client
+        .put(format!("{}/api/admin/contributions/{}/review", address, contrib_id))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"status": "approved"}))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+    let arch_id = sqlx::query_scalar!(
+        "SELECT id FROM architectures WHERE contribution_id = $1",
+        contrib_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let res = client
+        .put(format!("{}/api/admin/architectures/{}", address, arch_id))
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"description": "Edited after approval"}))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_success());
+
+    // Act
+    let revert_url = format!("{}/api/admin/contributions/{}/revert", address, contrib_id);
+    let res = client
+        .post(&revert_url)
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({"reason": "Not a historic building"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+
+    // Assert: The architecture is gone, the contribution rejected
+    let row = sqlx::query!(
+        r#"
+        SELECT c.status, (SELECT COUNT(*) FROM architectures WHERE id = $2) as "left!"
+        FROM contributions c WHERE c.id = $1
+        "#,
+        contrib_id,
+        arch_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(row.status, "rejected");
+    assert_eq!(row.left, 0);
+
+    // The audit log keeps the edited architecture and the reason
+    let log: Vec<serde_json::Value> = client
+        .get(format!("{}/api/admin/audit-logs?admin_id={}", address, admin_id))
+        .bearer_auth(&admin_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(log[0]["action"], "revert");
+    assert_eq!(log[0]["reason"], "Not a historic building");
+    assert_eq!(log[1]["action"], "delete");
+    assert_eq!(log[1]["entity_id"], arch_id);
+
+    // Only approved contributions can be reverted
+    let res = client
+        .post(&revert_url)
+        .bearer_auth(&admin_token)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_merged_architecture_redirects() {
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let no_redirects = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+
+    let tag = uuid::Uuid::new_v4().to_string()[..8].to_string();
+    let mut ids = Vec::new();
+    for name in ["Foguang", "Foguang Temple", "Foguang Si"] {
+        let created: serde_json::Value = client
+            .post(format!("{}/api/admin/architectures", address))
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .json(&serde_json::json!({
+                "category": "Temple",
+                "name": format!("{} {}", name, tag),
+                "dynasty": "Tang",
+                "location": "山西省忻州市五台县",
+                "description": "East hall",
+                "cover_img": "http://example.com/a.jpg",
+                "carousel_imgs": []
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        ids.push(created["id"].as_i64().unwrap());
+    }
+    let merge = |id: i64, keep_id: i64| {
+        client
+            .post(format!("{}/api/admin/architectures/{}/merge", address, id))
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .json(&serde_json::json!({ "keep_id": keep_id }))
+            .send()
+    };
+
+    // Merging into itself or into nothing is refused
+    assert_eq!(merge(ids[0], ids[0]).await.unwrap().status().as_u16(), 400);
+    assert_eq!(merge(ids[0], -1).await.unwrap().status().as_u16(), 404);
+
+    assert_eq!(merge(ids[0], ids[1]).await.unwrap().status().as_u16(), 204);
+
+    // The old ID answers 301 with where it went, and clients following it land there
+    let res = no_redirects
+        .get(format!("{}/api/architectures/{}", address, ids[0]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 301);
+    assert_eq!(
+        res.headers()["location"],
+        format!("/api/architectures/{}", ids[1]).as_str()
+    );
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["moved_to"], ids[1]);
+
+    // Merging the survivor again re-points the earlier redirect
+    assert_eq!(merge(ids[1], ids[2]).await.unwrap().status().as_u16(), 204);
+    let arch: serde_json::Value = client
+        .get(format!("{}/api/architectures/{}", address, ids[0]))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(arch["id"], ids[2]);
+
+    let action = sqlx::query_scalar!(
+        "SELECT action FROM audit_logs WHERE entity_type = 'architecture' AND entity_id = $1",
+        ids[0]
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert!(action.contains(&"merge".to_string()));
+
+    // IDs that never existed are still plain 404s
+    let res = client
+        .get(format!("{}/api/architectures/{}", address, i64::MAX))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_architecture_and_post_slugs() {
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let no_redirects = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let (_, admin_token) = create_user(&client, &address, &pool, "adm", "admin").await;
+
+    // Same names get numbered slugs, in pinyin
+    let tag = uuid::Uuid::new_v4().to_string()[..8].to_string();
+    let mut created = Vec::new();
+    for _ in 0..2 {
+        let arch: serde_json::Value = client
+            .post(format!("{}/api/admin/architectures", address))
+            .header("Authorization", format!("Bearer {}", admin_token))
+            .json(&serde_json::json!({
+                "category": "Pagoda",
+                "name": format!("应县木塔 {}", tag),
+                "dynasty": "Liao",
+                "location": "山西省朔州市应县",
+                "description": "Wooden pagoda",
+                "cover_img": "http://example.com/a.jpg",
+                "carousel_imgs": []
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        created.push(arch);
+    }
+    let slug = format!("yingxianmuta-{}", tag);
+    assert_eq!(created[0]["slug"], slug.as_str());
+    assert_eq!(created[1]["slug"], format!("{}-2", slug).as_str());
+
+    // Slugs work wherever IDs do, and show up in the response
+    let arch: serde_json::Value = client
+        .get(format!("{}/api/architectures/{}-2", address, slug))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(arch["id"], created[1]["id"]);
+    assert_eq!(arch["slug"], format!("{}-2", slug).as_str());
+
+    // The slug of a merged architecture redirects like its ID
+    let res = client
+        .post(format!(
+            "{}/api/admin/architectures/{}/merge",
+            address, created[1]["id"]
+        ))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({ "keep_id": created[0]["id"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 204);
+    let res = no_redirects
+        .get(format!("{}/api/architectures/{}-2", address, slug))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 301);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["moved_to"], created[0]["id"]);
+
+    // Titles that are only digits get a prefix, so they never read as IDs
+    let post: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({"title": tag.replace(|c: char| !c.is_ascii_digit(), "1"), "content": "Numbers"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let post_slug = post["slug"].as_str().unwrap().to_string();
+    assert!(post_slug.starts_with("post-"));
+    let fetched: serde_json::Value = client
+        .get(format!("{}/api/posts/{}", address, post_slug))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(fetched["id"], post["id"]);
+
+    let res = client
+        .get(format!("{}/api/posts/no-such-post-{}", address, tag))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 404);
+}
@@ -1,6 +1,13 @@
 // tests/api_tests.rs
 
-use backend::{config::Config, routes, state::AppState};
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig,
+        RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    state::AppState,
+};
 use sqlx::postgres::PgPoolOptions;
 use std::collections::HashMap;
 
@@ -32,9 +39,27 @@ async fn spawn_app() -> String {
         rust_log: "error".to_string(),
         admin_username: None,
         admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies: Vec::new(),
     };
 
-    let state = AppState { pool, config };
+    let state = AppState::new(pool, config);
 
     // 4. Create the router with the app state
     let app = routes::create_router(state);
@@ -63,7 +88,7 @@ async fn health_check_404() {
 
     // Act
     let response = client
-        .get(&format!("{}/random_path_that_does_not_exist", address))
+        .get(format!("{}/random_path_that_does_not_exist", address))
         .send()
         .await
         .expect("Failed to execute request");
@@ -82,7 +107,7 @@ async fn register_works() {
 
     // Act
     let response = client
-        .post(&format!("{}/api/auth/register", address))
+        .post(format!("{}/api/auth/register", address))
         .json(&serde_json::json!({
             "username": unique_name,
             "password": "password123"
@@ -95,6 +120,54 @@ async fn register_works() {
     assert_eq!(response.status().as_u16(), 201);
 }
 
+#[tokio::test]
+async fn register_rejects_look_alike_usernames() {
+    // Arrange
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let suffix = &uuid::Uuid::new_v4().to_string()[..8];
+    let name = format!("Look_{}", suffix);
+
+    let register = |username: String| {
+        client
+            .post(format!("{}/api/auth/register", address))
+            .json(&serde_json::json!({ "username": username, "password": "password123" }))
+            .send()
+    };
+    let login = |username: String| {
+        client
+            .post(format!("{}/api/auth/login", address))
+            .json(&serde_json::json!({ "username": username, "password": "password123" }))
+            .send()
+    };
+    let resp = register(name.clone()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    // The name is kept as typed
+    let user: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(user["username"], name);
+
+    // Act & Assert: Another case is the same user
+    let resp = register(name.to_lowercase()).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    // Logging in ignores case, fullwidth forms and zero-width characters
+    let fullwidth: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => char::from_u32(c as u32 - 0x21 + 0xFF01).unwrap(),
+            _ => c,
+        })
+        .collect();
+    for variant in [
+        name.to_uppercase(),
+        fullwidth,
+        format!("\u{200B}{}\u{FEFF}", name.to_lowercase()),
+    ] {
+        let resp = login(variant.clone()).await.unwrap();
+        assert_eq!(resp.status().as_u16(), 200, "login as {:?}", variant);
+    }
+}
+
 #[tokio::test]
 async fn register_fails_validation() {
     // Arrange
@@ -103,7 +176,7 @@ async fn register_fails_validation() {
 
     // Act: Send a username that is too short
     let response = client
-        .post(&format!("{}/api/auth/register", address))
+        .post(format!("{}/api/auth/register", address))
         .json(&serde_json::json!({
             "username": "yo",
             "password": "password123"
@@ -153,7 +226,7 @@ async fn test_qualification_flow() {
     let password = "password123";
 
     client
-        .post(&format!("{}/api/auth/register", address))
+        .post(format!("{}/api/auth/register", address))
         .json(&serde_json::json!({
             "username": username,
             "password": password
@@ -164,7 +237,7 @@ async fn test_qualification_flow() {
 
     // 2. Login to get token and check initial status
     let login_resp = client
-        .post(&format!("{}/api/auth/login", address))
+        .post(format!("{}/api/auth/login", address))
         .json(&serde_json::json!({
             "username": username,
             "password": password
@@ -184,7 +257,7 @@ async fn test_qualification_flow() {
 
     // 3. Fetch Exam
     let exam_resp = client
-        .get(&format!("{}/api/auth/qualification", address))
+        .get(format!("{}/api/auth/qualification", address))
         .header("Authorization", format!("Bearer {}", token))
         .send()
         .await
@@ -199,17 +272,47 @@ async fn test_qualification_flow() {
     let exam_token = exam_data["exam_token"]
         .as_str()
         .expect("Exam token not found");
-    assert!(questions.len() > 0);
+    assert!(!questions.is_empty());
 
-    // 4. Submit Answers (All 'A', which is correct per our seed)
+    // The token is readable by the examinee, so it must not carry the option order
+    let claims = jsonwebtoken::decode::<serde_json::Value>(
+        exam_token,
+        &jsonwebtoken::DecodingKey::from_secret(b"test_secret_for_integration_tests"),
+        &jsonwebtoken::Validation::default(),
+    )
+    .expect("Exam token rejected")
+    .claims;
+    let mut fields: Vec<&str> = claims.as_object().unwrap().keys().map(String::as_str).collect();
+    fields.sort_unstable();
+    assert_eq!(fields, ["exp", "qids", "sid", "sub"]);
+
+    // 4. Submit Answers. Options are shuffled per delivery, so translate each
+    // correct option from the answer key into the letter it is shown under now.
     let mut answers = HashMap::new();
     for q in questions {
         let id = q["id"].as_i64().unwrap();
-        answers.insert(id, "A".to_string());
+        let key = sqlx::query!("SELECT options, answer FROM questions WHERE id = $1", id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let shown = q["options"].as_array().unwrap();
+        let letters: String = key
+            .answer
+            .chars()
+            .map(|c| {
+                let correct = &key.options[(c as u8 - b'A') as usize];
+                let pos = shown
+                    .iter()
+                    .position(|opt| opt == correct)
+                    .expect("Correct option missing from delivered paper");
+                (b'A' + pos as u8) as char
+            })
+            .collect();
+        answers.insert(id, letters);
     }
 
     let submit_resp = client
-        .post(&format!("{}/api/auth/qualification/submit", address))
+        .post(format!("{}/api/auth/qualification/submit", address))
         .header("Authorization", format!("Bearer {}", token))
         .json(&serde_json::json!({
             "answers": answers,
@@ -225,7 +328,7 @@ async fn test_qualification_flow() {
 
     // 5. Login again to verify status updated
     let login_resp_2 = client
-        .post(&format!("{}/api/auth/login", address))
+        .post(format!("{}/api/auth/login", address))
         .json(&serde_json::json!({
             "username": username,
             "password": password
@@ -262,7 +365,7 @@ async fn test_community_flow() {
     let password = "password123";
 
     client
-        .post(&format!("{}/api/auth/register", address))
+        .post(format!("{}/api/auth/register", address))
         .json(&serde_json::json!({
             "username": username,
             "password": password
@@ -273,7 +376,7 @@ async fn test_community_flow() {
 
     // Login
     let login_resp = client
-        .post(&format!("{}/api/auth/login", address))
+        .post(format!("{}/api/auth/login", address))
         .json(&serde_json::json!({
             "username": username,
             "password": password
@@ -289,7 +392,7 @@ async fn test_community_flow() {
 
     // 2. Try to Post (Unverified) -> Should Fail
     let post_resp = client
-        .post(&format!("{}/api/posts", address))
+        .post(format!("{}/api/posts", address))
         .header("Authorization", format!("Bearer {}", token))
         .json(&serde_json::json!({
             "title": "My First Post",
@@ -313,7 +416,7 @@ async fn test_community_flow() {
 
     // 4. Try to Post Again (Verified) -> Should Success
     let post_resp = client
-        .post(&format!("{}/api/posts", address))
+        .post(format!("{}/api/posts", address))
         .header("Authorization", format!("Bearer {}", token))
         .json(&serde_json::json!({
             "title": "My First Post",
@@ -330,7 +433,7 @@ async fn test_community_flow() {
 
     // 5. List Posts
     let list_resp = client
-        .get(&format!("{}/api/posts", address))
+        .get(format!("{}/api/posts", address))
         .send()
         .await
         .expect("List request failed");
@@ -344,7 +447,7 @@ async fn test_community_flow() {
 
     // 6. Get Post Details
     let detail_resp = client
-        .get(&format!("{}/api/posts/{}", address, post_id))
+        .get(format!("{}/api/posts/{}", address, post_id))
         .send()
         .await
         .expect("Detail request failed");
@@ -353,7 +456,7 @@ async fn test_community_flow() {
 
     // 7. Delete Post
     let del_resp = client
-        .delete(&format!("{}/api/posts/{}", address, post_id))
+        .delete(format!("{}/api/posts/{}", address, post_id))
         .header("Authorization", format!("Bearer {}", token))
         .send()
         .await
@@ -363,7 +466,7 @@ async fn test_community_flow() {
 
     // 8. Verify Soft Delete (List should not contain it)
     let list_resp_2 = client
-        .get(&format!("{}/api/posts", address))
+        .get(format!("{}/api/posts", address))
         .send()
         .await
         .expect("List request failed");
@@ -374,7 +477,7 @@ async fn test_community_flow() {
 
     // 9. Verify Detail (Should be 404)
     let detail_resp_2 = client
-        .get(&format!("{}/api/posts/{}", address, post_id))
+        .get(format!("{}/api/posts/{}", address, post_id))
         .send()
         .await
         .expect("Detail request failed");
@@ -403,7 +506,7 @@ async fn test_community_pagination() {
     let password = "password123";
 
     client
-        .post(&format!("{}/api/auth/register", address))
+        .post(format!("{}/api/auth/register", address))
         .json(&serde_json::json!({"username": username, "password": password}))
         .send()
         .await
@@ -418,7 +521,7 @@ async fn test_community_pagination() {
     .expect("Verify failed");
 
     let login_resp = client
-        .post(&format!("{}/api/auth/login", address))
+        .post(format!("{}/api/auth/login", address))
         .json(&serde_json::json!({"username": username, "password": password}))
         .send()
         .await
@@ -432,7 +535,7 @@ async fn test_community_pagination() {
     // 2. Create 3 posts with small delays
     for i in 1..=3 {
         client
-            .post(&format!("{}/api/posts", address))
+            .post(format!("{}/api/posts", address))
             .header("Authorization", format!("Bearer {}", token))
             .json(&serde_json::json!({"title": format!("Post {}", i), "content": "Content"}))
             .send()
@@ -446,7 +549,7 @@ async fn test_community_pagination() {
     // 3. Fetch Page 1 (Limit 2)
     // Expected order: Post 3, Post 2
     let page1_resp = client
-        .get(&format!("{}/api/posts?limit=2", address))
+        .get(format!("{}/api/posts?limit=2", address))
         .send()
         .await
         .expect("List failed");
@@ -460,14 +563,14 @@ async fn test_community_pagination() {
     let cursor = page1[1]["created_at"].as_str().unwrap();
 
     let page2_resp = client
-        .get(&format!("{}/api/posts", address))
+        .get(format!("{}/api/posts", address))
         .query(&[("limit", "2"), ("cursor", cursor)])
         .send()
         .await
         .expect("List page 2 failed");
 
     let page2: Vec<serde_json::Value> = page2_resp.json().await.unwrap();
-    assert!(page2.len() >= 1, "Page 2 should contain at least one post");
+    assert!(!page2.is_empty(), "Page 2 should contain at least one post");
     // Since we sort by created_at DESC, and Post 1 is the oldest of our three,
     // it should be the first one after Post 2's cursor (if no other posts were made exactly at that time).
     assert_eq!(page2[0]["title"], "Post 1");
@@ -492,7 +595,7 @@ async fn test_interaction_flow() {
 
     for u in &[&user_a, &user_b] {
         client
-            .post(&format!("{}/api/auth/register", address))
+            .post(format!("{}/api/auth/register", address))
             .json(&serde_json::json!({"username": u, "password": password}))
             .send()
             .await
@@ -505,7 +608,7 @@ async fn test_interaction_flow() {
 
     // Login A
     let login_a = client
-        .post(&format!("{}/api/auth/login", address))
+        .post(format!("{}/api/auth/login", address))
         .json(&serde_json::json!({"username": user_a, "password": password}))
         .send()
         .await
@@ -517,7 +620,7 @@ async fn test_interaction_flow() {
 
     // Login B
     let login_b = client
-        .post(&format!("{}/api/auth/login", address))
+        .post(format!("{}/api/auth/login", address))
         .json(&serde_json::json!({"username": user_b, "password": password}))
         .send()
         .await
@@ -528,7 +631,7 @@ async fn test_interaction_flow() {
     let token_b = login_b["token"].as_str().unwrap();
 
     // 2. User A Creates Post
-    let post_resp = client.post(&format!("{}/api/posts", address))
+    let post_resp = client.post(format!("{}/api/posts", address))
         .header("Authorization", format!("Bearer {}", token_a))
         .json(&serde_json::json!({"title": "Interactions Test", "content": "Let's like and comment!"}))
         .send().await.unwrap();
@@ -538,7 +641,7 @@ async fn test_interaction_flow() {
 
     // 3. User B Likes Post
     let like_resp = client
-        .post(&format!("{}/api/posts/{}/like", address, post_id))
+        .post(format!("{}/api/posts/{}/like", address, post_id))
         .header("Authorization", format!("Bearer {}", token_b))
         .send()
         .await
@@ -551,7 +654,7 @@ async fn test_interaction_flow() {
 
     // Verify Like Count
     let p_detail = client
-        .get(&format!("{}/api/posts/{}", address, post_id))
+        .get(format!("{}/api/posts/{}", address, post_id))
         .header("Authorization", format!("Bearer {}", token_b))
         .send()
         .await
@@ -564,13 +667,13 @@ async fn test_interaction_flow() {
 
     // 4. User B Unlikes Post
     client
-        .post(&format!("{}/api/posts/{}/like", address, post_id))
+        .post(format!("{}/api/posts/{}/like", address, post_id))
         .header("Authorization", format!("Bearer {}", token_b))
         .send()
         .await
         .unwrap();
     let p_detail_2 = client
-        .get(&format!("{}/api/posts/{}", address, post_id))
+        .get(format!("{}/api/posts/{}", address, post_id))
         .send()
         .await
         .unwrap()
@@ -581,7 +684,7 @@ async fn test_interaction_flow() {
 
     // 5. User B Comments (Root)
     let c1_resp = client
-        .post(&format!("{}/api/posts/{}/comments", address, post_id))
+        .post(format!("{}/api/posts/{}/comments", address, post_id))
         .header("Authorization", format!("Bearer {}", token_b))
         .json(&serde_json::json!({"content": "This is root comment"}))
         .send()
@@ -593,7 +696,7 @@ async fn test_interaction_flow() {
 
     // 6. User A Replies to B (Level 2)
     let c2_resp = client
-        .post(&format!("{}/api/posts/{}/comments", address, post_id))
+        .post(format!("{}/api/posts/{}/comments", address, post_id))
         .header("Authorization", format!("Bearer {}", token_a))
         .json(&serde_json::json!({"content": "This is a reply", "parent_id": c1_id}))
         .send()
@@ -605,7 +708,7 @@ async fn test_interaction_flow() {
 
     // 7. Verify Comments and Counts
     let p_detail_3 = client
-        .get(&format!("{}/api/posts/{}", address, post_id))
+        .get(format!("{}/api/posts/{}", address, post_id))
         .send()
         .await
         .unwrap()
@@ -615,7 +718,7 @@ async fn test_interaction_flow() {
     assert_eq!(p_detail_3["comments_count"], 2);
 
     let comments_resp = client
-        .get(&format!("{}/api/posts/{}/comments", address, post_id))
+        .get(format!("{}/api/posts/{}/comments", address, post_id))
         .send()
         .await
         .unwrap();
@@ -650,7 +753,7 @@ async fn test_contribution_flow() {
 
     // Register User
     client
-        .post(&format!("{}/api/auth/register", address))
+        .post(format!("{}/api/auth/register", address))
         .json(&serde_json::json!({"username": user_name, "password": password}))
         .send()
         .await
@@ -663,7 +766,7 @@ async fn test_contribution_flow() {
     .await
     .unwrap();
     let login_user = client
-        .post(&format!("{}/api/auth/login", address))
+        .post(format!("{}/api/auth/login", address))
         .json(&serde_json::json!({"username": user_name, "password": password}))
         .send()
         .await
@@ -684,7 +787,7 @@ async fn test_contribution_flow() {
     .await
     .unwrap();
     let login_admin = client
-        .post(&format!("{}/api/auth/login", address))
+        .post(format!("{}/api/auth/login", address))
         .json(&serde_json::json!({"username": admin_name, "password": password}))
         .send()
         .await
@@ -694,7 +797,38 @@ async fn test_contribution_flow() {
         .unwrap();
     let admin_token = login_admin["token"].as_str().unwrap();
 
-    // 2. Submit valid architecture
+    // 2. Pre-check: invalid data is rejected, valid data is previewed without inserting
+    let invalid = client
+        .post(format!("{}/api/contributions/validate", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .json(&serde_json::json!({"type": "question", "data": {
+            "question_type": "single", "content": "Q", "options": [], "answer": "A"
+        }}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(invalid.status().as_u16(), 400);
+
+    let check = client
+        .post(format!("{}/api/contributions/validate", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .json(&serde_json::json!({"type": "question", "data": {
+            "question_type": "single",
+            "content": "Which dynasty?<script>alert(1)</script>",
+            "options": ["Ming", "Qing"],
+            "answer": "Ming"
+        }}))
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(check["type"], "question");
+    assert_eq!(check["cleaned"]["content"], "Which dynasty?");
+    assert_eq!(check["can_submit_today"], true);
+
+    // 3. Submit valid architecture
     let arch_payload = serde_json::json!({
         "type": "architecture",
         "data": {
@@ -709,7 +843,7 @@ async fn test_contribution_flow() {
     });
 
     let resp = client
-        .post(&format!("{}/api/contributions", address))
+        .post(format!("{}/api/contributions", address))
         .header("Authorization", format!("Bearer {}", user_token))
         .json(&arch_payload)
         .send()
@@ -720,9 +854,9 @@ async fn test_contribution_flow() {
         .as_i64()
         .unwrap();
 
-    // 3. Try to submit again same day -> Should Fail (409 Conflict)
+    // 4. Try to submit again same day -> Should Fail (409 Conflict)
     let resp_fail = client
-        .post(&format!("{}/api/contributions", address))
+        .post(format!("{}/api/contributions", address))
         .header("Authorization", format!("Bearer {}", user_token))
         .json(&arch_payload)
         .send()
@@ -730,9 +864,29 @@ async fn test_contribution_flow() {
         .unwrap();
     assert_eq!(resp_fail.status().as_u16(), 409);
 
-    // 4. Admin reviews and approves
+    // The pre-check now warns about the used quota and the pending duplicate
+    let recheck = client
+        .post(format!("{}/api/contributions/validate", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .json(&arch_payload)
+        .send()
+        .await
+        .unwrap()
+        .json::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(recheck["can_submit_today"], false);
+    assert!(
+        recheck["duplicates"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|d| d["source"] == "contribution" && d["id"] == contrib_id)
+    );
+
+    // 5. Admin reviews and approves
     let review_resp = client
-        .put(&format!(
+        .put(format!(
             "{}/api/admin/contributions/{}/review",
             address, contrib_id
         ))
@@ -746,9 +900,9 @@ async fn test_contribution_flow() {
         .unwrap();
     assert_eq!(review_resp.status().as_u16(), 200);
 
-    // 5. Verify it's in the real architectures table
+    // 6. Verify it's in the real architectures table
     let arch_check = client
-        .get(&format!("{}/api/architectures", address))
+        .get(format!("{}/api/architectures", address))
         .send()
         .await
         .unwrap()
@@ -763,3 +917,1626 @@ async fn test_contribution_flow() {
         "The approved architecture should be in the main list"
     );
 }
+
+#[tokio::test]
+async fn test_quiz_paper_tracks_exposure() {
+    // Arrange
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    sqlx::query!(
+        "INSERT INTO questions (type, content, options, answer) VALUES ('single', $1, $2, 'A')",
+        format!("Exposure {}", uuid::Uuid::new_v4()),
+        serde_json::json!(["A", "B"])
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Act
+    let paper: serde_json::Value = client
+        .get(format!("{}/api/quiz/generate", address))
+        .send()
+        .await
+        .expect("Generate failed")
+        .json()
+        .await
+        .unwrap();
+
+    // Assert: Every served question has been counted
+    let questions = paper["questions"].as_array().unwrap();
+    assert!(!questions.is_empty());
+    assert!(questions.iter().all(|q| q.get("answer").is_none()));
+    let ids: Vec<i64> = questions.iter().map(|q| q["id"].as_i64().unwrap()).collect();
+    let unexposed = sqlx::query!(
+        r#"SELECT COUNT(*) as "count!" FROM questions WHERE id = ANY($1) AND exposure_count = 0"#,
+        &ids
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(unexposed.count, 0);
+
+    // The paper was frozen as served, and the token names its snapshot
+    let snapshot_id = backend::services::paper_snapshots::verify(
+        paper["exam_token"].as_str().unwrap(),
+        "test_secret_for_integration_tests",
+    )
+    .expect("Paper token rejected");
+    let frozen = backend::services::paper_snapshots::load(&pool, snapshot_id)
+        .await
+        .unwrap();
+    assert_eq!(frozen.iter().map(|q| q.id).collect::<Vec<_>>(), ids);
+
+    // The maintenance job runs cleanly against the live bank
+    backend::jobs::question_sampling::run(pool.clone())
+        .await
+        .expect("Sampling job failed");
+}
+
+#[tokio::test]
+async fn test_public_stats_cached() {
+    // Arrange
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let first: serde_json::Value = client
+        .get(format!("{}/api/stats/public", address))
+        .send()
+        .await
+        .expect("Stats failed")
+        .json()
+        .await
+        .unwrap();
+    let second: serde_json::Value = client
+        .get(format!("{}/api/stats/public", address))
+        .send()
+        .await
+        .expect("Stats failed")
+        .json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(first["total_architectures"].is_i64());
+    assert!(first["verified_contributors"].is_i64());
+    assert!(first["questions_in_bank"].is_i64());
+    assert!(first["posts_this_week"].is_i64());
+    assert_eq!(
+        first["generated_at"], second["generated_at"],
+        "Second call should be served from cache"
+    );
+}
+
+#[tokio::test]
+async fn test_malformed_or_stale_token_subject_rejected() {
+    // Arrange
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&std::env::var("DATABASE_URL").unwrap())
+        .await
+        .unwrap();
+    let secret = "test_secret_for_integration_tests";
+
+    // A validly signed token whose subject is not a user ID
+    let forged = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &backend::utils::jwt::Claims {
+            sub: "not-a-number".to_string(),
+            role: "user".to_string(),
+            exp: 4_000_000_000,
+            jti: None,
+            mfa: false,
+        },
+        &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .unwrap();
+
+    let username = format!("st_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({ "username": username, "password": "password123" }))
+        .send()
+        .await
+        .expect("Register failed");
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({ "username": username, "password": "password123" }))
+        .send()
+        .await
+        .expect("Login failed")
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap().to_string();
+
+    // Act & Assert: malformed subject
+    let res = client
+        .get(format!("{}/api/profile/me", address))
+        .header("Authorization", format!("Bearer {}", forged))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 401);
+
+    // Optional-auth routes treat it as anonymous
+    let res = client
+        .get(format!("{}/api/feed/discover", address))
+        .header("Authorization", format!("Bearer {}", forged))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+
+    // Valid until the user is deleted
+    let res = client
+        .get(format!("{}/api/profile/me", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+
+    sqlx::query("DELETE FROM users WHERE username = $1")
+        .bind(&username)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let res = client
+        .get(format!("{}/api/profile/me", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 401);
+
+    let res = client
+        .get(format!("{}/api/feed/discover", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn test_logout_revokes_only_that_token() {
+    // Arrange: One user signed in twice within the same second
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let username = format!("lo_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({ "username": username, "password": "password123" }))
+        .send()
+        .await
+        .expect("Register failed");
+    let mut tokens = Vec::new();
+    for _ in 0..2 {
+        let login: serde_json::Value = client
+            .post(format!("{}/api/auth/login", address))
+            .json(&serde_json::json!({ "username": username, "password": "password123" }))
+            .send()
+            .await
+            .expect("Login failed")
+            .json()
+            .await
+            .unwrap();
+        tokens.push(login["token"].as_str().unwrap().to_string());
+    }
+    assert_ne!(tokens[0], tokens[1]);
+    let status = |path: &'static str, token: String| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            client
+                .get(format!("{}{}", address, path))
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        }
+    };
+
+    // Act
+    let logout = client
+        .post(format!("{}/api/auth/logout", address))
+        .header("Authorization", format!("Bearer {}", tokens[0]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(logout.status().as_u16(), 204);
+
+    // Assert: The revoked token is refused, optional-auth routes serve it anonymously
+    assert_eq!(status("/api/profile/me", tokens[0].clone()).await, 401);
+    assert_eq!(status("/api/feed/discover", tokens[0].clone()).await, 200);
+    let again = client
+        .post(format!("{}/api/auth/logout", address))
+        .header("Authorization", format!("Bearer {}", tokens[0]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(again.status().as_u16(), 401);
+
+    // The other session still works
+    assert_eq!(status("/api/profile/me", tokens[1].clone()).await, 200);
+}
+
+#[tokio::test]
+async fn test_json_body_guards() {
+    // Arrange
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let register = |body: String| {
+        client
+            .post(format!("{}/api/auth/register", address))
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+    };
+
+    // Act & Assert: Oversized body
+    let huge = format!(r#"{{"username": "{}", "password": "password123"}}"#, "a".repeat(300 * 1024));
+    let res = register(huge).await.unwrap();
+    assert_eq!(res.status().as_u16(), 413);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("exceeds"));
+
+    // Deep nesting
+    let deep = format!(r#"{{"username": {}0{}}}"#, "[".repeat(40), "]".repeat(40));
+    let res = register(deep).await.unwrap();
+    assert_eq!(res.status().as_u16(), 400);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("nested"));
+
+    // Media types are case-insensitive, so the guard can't be dodged by casing
+    let deep = format!(r#"{{"username": {}0{}}}"#, "[".repeat(40), "]".repeat(40));
+    let res = client
+        .post(format!("{}/api/auth/register", address))
+        .header("Content-Type", "Application/JSON; charset=UTF-8")
+        .body(deep)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 400);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("nested"));
+
+    // Overlong array, even in a field the handler ignores
+    let items = vec!["0"; 1001].join(",");
+    let res = register(format!(r#"{{"username": "yo", "pad": [{}]}}"#, items))
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 400);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert!(body["error"].as_str().unwrap().contains("array"));
+
+    // Brackets inside strings don't count; the request reaches validation
+    let res = register(format!(
+        r#"{{"username": "yo", "password": "{}", "pad": [{}]}}"#,
+        "[".repeat(100),
+        vec!["0"; 1000].join(",")
+    ))
+    .await
+    .unwrap();
+    assert_eq!(res.status().as_u16(), 400);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["error"], "Validation failed");
+}
+
+#[tokio::test]
+async fn test_lite_list_responses() {
+    // Arrange
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let tag = uuid::Uuid::new_v4().to_string()[..8].to_string();
+    let user: serde_json::Value = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": format!("lt_{}", tag), "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let user_id = user["id"].as_i64().unwrap();
+
+    let long_html = format!("<p>Tom &amp; <b>Jerry</b></p>\n<p>{}</p>", "Dougong ".repeat(100));
+    sqlx::query!(
+        "INSERT INTO posts (user_id, title, content) VALUES ($1, $2, $3)",
+        user_id,
+        format!("Lite {}", tag),
+        long_html
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        r#"
+        INSERT INTO architectures (category, name, dynasty, location, description, cover_img, carousel_imgs)
+        VALUES ($1, 'Lite Pagoda', 'Liao', 'Yingxian', $2, 'http://img/cover.jpg', '["http://img/1.jpg"]')
+        "#,
+        format!("Lite {}", tag),
+        long_html
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let get = |url: String, lite: bool| {
+        let mut req = client.get(url);
+        if lite {
+            req = req.header("X-Lite", "1");
+        }
+        async move {
+            let resp = req.send().await.unwrap();
+            assert_eq!(resp.status().as_u16(), 200);
+            assert_eq!(resp.headers()["vary"], "x-lite");
+            let body: Vec<serde_json::Value> = resp.json().await.unwrap();
+            assert_eq!(body.len(), 1);
+            body.into_iter().next().unwrap()
+        }
+    };
+    let posts_url = format!("{}/api/posts?q=Lite%20{}", address, tag);
+    let archs_url = format!("{}/api/architectures?category=Lite%20{}", address, tag);
+
+    // Act & Assert: Full rows by default
+    let post = get(posts_url.clone(), false).await;
+    assert_eq!(post["content"], long_html);
+    assert!(post.get("summary").is_none());
+    let arch = get(archs_url.clone(), false).await;
+    assert!(arch["carousel_imgs"].is_array());
+
+    // Lite: plain-text summaries, no heavy fields
+    let post = get(posts_url, true).await;
+    assert!(post.get("content").is_none());
+    let summary = post["summary"].as_str().unwrap();
+    assert!(summary.starts_with("Tom & Jerry Dougong Dougong"));
+    assert!(summary.ends_with('…'));
+    assert!(summary.chars().count() <= 141);
+    assert_eq!(post["user_id"], user_id);
+
+    let arch = get(archs_url, true).await;
+    assert!(arch.get("description").is_none());
+    assert!(arch.get("carousel_imgs").is_none());
+    assert_eq!(arch["name"], "Lite Pagoda");
+    assert_eq!(arch["cover_img"], "http://img/cover.jpg");
+    assert!(arch["summary"].as_str().unwrap().starts_with("Tom & Jerry"));
+}
+
+#[tokio::test]
+async fn test_post_excerpt_and_reading_time() {
+    // Arrange
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let username = format!("ex_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let user: serde_json::Value = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let user_id = user["id"].as_i64().unwrap();
+    sqlx::query!("UPDATE users SET is_verified = TRUE WHERE id = $1", user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap();
+
+    // 600 CJK characters plus 100 English words: 2 + 0.5 minutes
+    let content = format!("<p>{}</p><p>{}</p>", "斗拱".repeat(300), "bracket ".repeat(100));
+    let title = format!("Excerpt {}", username);
+
+    // Act: Create through the API
+    let resp = client
+        .post(format!("{}/api/posts", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&serde_json::json!({"title": title, "content": content}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+
+    // Assert: Stored at write time and returned in lists
+    let posts: Vec<serde_json::Value> = client
+        .get(format!("{}/api/posts?q={}", address, title))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(posts.len(), 1);
+    let excerpt = posts[0]["excerpt"].as_str().unwrap();
+    assert!(excerpt.starts_with("斗拱斗拱"));
+    assert!(excerpt.ends_with('…'));
+    assert!(!excerpt.contains('<'));
+    assert_eq!(posts[0]["reading_time_minutes"], 3);
+
+    // Act: A post inserted without them is filled in by the background job
+    let id = sqlx::query_scalar!(
+        "INSERT INTO posts (user_id, title, content) VALUES ($1, 'Raw', '<b>Short</b> post') RETURNING id",
+        user_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let mut row = None;
+    for _ in 0..10 {
+        backend::jobs::post_excerpts::run(pool.clone()).await.unwrap();
+        let filled = sqlx::query!(
+            "SELECT excerpt, reading_time_minutes FROM posts WHERE id = $1",
+            id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        if filled.excerpt.is_some() {
+            row = Some(filled);
+            break;
+        }
+    }
+    let row = row.expect("Job never reached the post");
+    assert_eq!(row.excerpt.as_deref(), Some("Short post"));
+    assert_eq!(row.reading_time_minutes, Some(1));
+}
+
+#[tokio::test]
+async fn test_post_source_only_for_author() {
+    // Arrange
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let mut tokens = Vec::new();
+    for _ in 0..2 {
+        let username = format!("raw_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let user: serde_json::Value = client
+            .post(format!("{}/api/auth/register", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        sqlx::query!("UPDATE users SET is_verified = TRUE WHERE id = $1", user["id"].as_i64().unwrap())
+            .execute(&pool)
+            .await
+            .unwrap();
+        let login: serde_json::Value = client
+            .post(format!("{}/api/auth/login", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        tokens.push(login["token"].as_str().unwrap().to_string());
+    }
+    let (author, other) = (&tokens[0], &tokens[1]);
+
+    let raw = "<p onclick=\"x()\">Dougong <span style=\"color:red\">detail</span></p>\n\n<script>1</script>";
+    let created: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .header("Authorization", format!("Bearer {}", author))
+        .json(&serde_json::json!({"title": "Raw content", "content": raw}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let url = format!("{}/api/posts/{}", address, created["id"]);
+
+    // Act & Assert: The author gets back exactly what they submitted
+    let post: serde_json::Value = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", author))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(post["content_md"], raw);
+    let content = post["content"].as_str().unwrap();
+    assert!(!content.contains("<script>"));
+    assert!(!content.contains("onclick"));
+
+    // Act & Assert: Other users and anonymous readers only get the sanitized render
+    let post: serde_json::Value = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", other))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(post["content_md"].is_null());
+    assert_eq!(post["content"], content);
+
+    let post: serde_json::Value = client.get(&url).send().await.unwrap().json().await.unwrap();
+    assert!(post["content_md"].is_null());
+
+    // Act & Assert: Lists carry the content once, without the source, even for the author
+    let posts: Vec<serde_json::Value> = client
+        .get(format!("{}/api/posts?q=Raw%20content&limit=100", address))
+        .bearer_auth(author)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let listed = posts.iter().find(|p| p["id"] == created["id"]).unwrap();
+    assert_eq!(listed["content"], content);
+    assert!(listed.get("content_html").is_none());
+    assert!(listed.get("content_md").is_none());
+}
+
+#[tokio::test]
+async fn test_post_markdown_rendered_and_sanitized() {
+    // Arrange
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let username = format!("md_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let creds = serde_json::json!({"username": username, "password": "password123"});
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE users SET is_verified = TRUE WHERE username = $1", username)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap();
+
+    let markdown = "## Dougong\n\n**Bracket** sets of the [Tang](javascript:alert(1)).\n\n| Dynasty | Sets |\n| --- | --- |\n| Tang | 7 |\n\n<script>alert(1)</script>\n\n<img src=\"https://example.com/a.jpg\" onerror=\"x()\">";
+
+    // Act
+    let created: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .bearer_auth(token)
+        .json(&serde_json::json!({"title": "Markdown", "content": markdown}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let post: serde_json::Value = client
+        .get(format!("{}/api/posts/{}", address, created["id"]))
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Assert: The source comes back as is, the render is formatted and safe
+    assert_eq!(post["content_md"], markdown);
+    let html = post["content"].as_str().unwrap();
+    assert!(html.contains("<h2>Dougong</h2>"));
+    assert!(html.contains("<strong>Bracket</strong>"));
+    assert!(html.contains("<td>Tang</td>"));
+    assert!(html.contains("<img src=\"https://example.com/a.jpg\""));
+    assert!(!html.contains("<script"));
+    assert!(!html.contains("javascript:"));
+    assert!(!html.contains("onerror"));
+    assert_eq!(post["excerpt"], "Dougong Bracket sets of the Tang. Dynasty Sets Tang 7");
+}
+
+#[tokio::test]
+async fn test_edit_post_keeps_revisions() {
+    // Arrange: A post by one user, liked by another
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let mut tokens = Vec::new();
+    for _ in 0..2 {
+        let username = format!("edit_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let user: serde_json::Value = client
+            .post(format!("{}/api/auth/register", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        sqlx::query!("UPDATE users SET is_verified = TRUE WHERE id = $1", user["id"].as_i64().unwrap())
+            .execute(&pool)
+            .await
+            .unwrap();
+        let login: serde_json::Value = client
+            .post(format!("{}/api/auth/login", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        tokens.push(login["token"].as_str().unwrap().to_string());
+    }
+    let (author, other) = (&tokens[0], &tokens[1]);
+
+    let created: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .bearer_auth(author)
+        .json(&serde_json::json!({"title": "Dougong typo", "content": "<p>Dougnog of the Tang</p>"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let url = format!("{}/api/posts/{}", address, created["id"]);
+    client
+        .post(format!("{}/like", url))
+        .bearer_auth(other)
+        .send()
+        .await
+        .unwrap();
+    let edit = |token: &str, body: serde_json::Value| {
+        client.put(&url).bearer_auth(token).json(&body).send()
+    };
+
+    // Act & Assert: Only the author may edit
+    let res = edit(other, serde_json::json!({"content": "<p>Hijacked</p>"})).await.unwrap();
+    assert_eq!(res.status().as_u16(), 401);
+
+    // Act: The author fixes the typo, then saves again without changes
+    let res = edit(author, serde_json::json!({"content": "<p>Dougong of the Tang</p>"}))
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+    let edited: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(edited["slug"], created["slug"]);
+    assert!(edited["edited_at"].is_string());
+    let res = edit(author, serde_json::json!({"title": "Dougong typo"})).await.unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+
+    // Assert: The post changed in place, with one revision holding the old text
+    let post: serde_json::Value = client
+        .get(&url)
+        .bearer_auth(author)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(post["content"], "<p>Dougong of the Tang</p>");
+    assert_eq!(post["content_md"], "<p>Dougong of the Tang</p>");
+    assert_eq!(post["title"], "Dougong typo");
+    assert_eq!(post["likes_count"], 1);
+    assert_eq!(post["edited_at"], edited["edited_at"]);
+
+    let revisions: Vec<serde_json::Value> = client
+        .get(format!("{}/revisions", url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0]["content"], "<p>Dougnog of the Tang</p>");
+    assert!(revisions[0]["content_md"].is_null());
+
+    let revisions: Vec<serde_json::Value> = client
+        .get(format!("{}/revisions", url))
+        .bearer_auth(author)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(revisions[0]["content_md"], "<p>Dougnog of the Tang</p>");
+
+    // Act & Assert: Deleted posts cannot be edited
+    client.delete(&url).bearer_auth(author).send().await.unwrap();
+    let res = edit(author, serde_json::json!({"title": "Too late"})).await.unwrap();
+    assert_eq!(res.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_comment_notifications_are_batched() {
+    // Arrange
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let mut tokens = Vec::new();
+    for _ in 0..3 {
+        let username = format!("cn_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let user: serde_json::Value = client
+            .post(format!("{}/api/auth/register", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        sqlx::query!("UPDATE users SET is_verified = TRUE WHERE id = $1", user["id"].as_i64().unwrap())
+            .execute(&pool)
+            .await
+            .unwrap();
+        let login: serde_json::Value = client
+            .post(format!("{}/api/auth/login", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        tokens.push(login["token"].as_str().unwrap().to_string());
+    }
+    let author = &tokens[0];
+
+    let post: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .header("Authorization", format!("Bearer {}", author))
+        .json(&serde_json::json!({"title": "Busy thread", "content": "Comments welcome"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let post_id = post["id"].as_i64().unwrap();
+
+    let comment = |token: &String, n: i32| {
+        let request = client
+            .post(format!("{}/api/posts/{}/comments", address, post_id))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&serde_json::json!({"content": format!("Comment {}", n)}));
+        let pool = &pool;
+        async move {
+            // Move earlier comments back so the comment throttle lets this one through
+            sqlx::query!(
+                "UPDATE comments SET created_at = created_at - INTERVAL '1 minute' WHERE post_id = $1",
+                post_id
+            )
+            .execute(pool)
+            .await
+            .unwrap();
+            request.send().await
+        }
+    };
+    let notifications = || async {
+        let list: serde_json::Value = client
+            .get(format!("{}/api/notifications?unread_only=true", address))
+            .header("Authorization", format!("Bearer {}", author))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        list["notifications"].as_array().unwrap().clone()
+    };
+
+    // Act: Two users comment several times; the author's own reply is not reported
+    let mut last_id = 0;
+    for (n, token) in [&tokens[1], &tokens[2], &tokens[1], author].into_iter().enumerate() {
+        let created: serde_json::Value = comment(token, n as i32).await.unwrap().json().await.unwrap();
+        if token != author {
+            last_id = created["id"].as_i64().unwrap();
+        }
+    }
+
+    // Assert: One notification counting all three
+    let unread = notifications().await;
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0]["kind"], "post_comments");
+    assert_eq!(unread[0]["payload"]["post_id"], post_id);
+    assert_eq!(unread[0]["payload"]["count"], 3);
+    assert_eq!(unread[0]["payload"]["comment_id"], last_id);
+
+    // Act: Once read, the next comment starts a new notification
+    let resp = client
+        .post(format!("{}/api/notifications/read", address))
+        .header("Authorization", format!("Bearer {}", author))
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+    comment(&tokens[2], 4).await.unwrap();
+
+    // Assert
+    let unread = notifications().await;
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0]["payload"]["count"], 1);
+}
+
+#[tokio::test]
+async fn test_like_and_reply_notifications() {
+    // Arrange: An author, a commenter and a replier
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let mut users = Vec::new();
+    for _ in 0..3 {
+        let username = format!("ln_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let user: serde_json::Value = client
+            .post(format!("{}/api/auth/register", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let id = user["id"].as_i64().unwrap();
+        sqlx::query!("UPDATE users SET is_verified = TRUE WHERE id = $1", id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let login: serde_json::Value = client
+            .post(format!("{}/api/auth/login", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        users.push((id, login["token"].as_str().unwrap().to_string()));
+    }
+    let [(_, author), (commenter_id, commenter), (replier_id, replier)] = &users[..] else {
+        unreachable!()
+    };
+
+    let post: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .bearer_auth(author)
+        .json(&serde_json::json!({"title": "Liked", "content": "Likes welcome"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let post_id = post["id"].as_i64().unwrap();
+    let notifications = |token: &String| {
+        let request = client
+            .get(format!("{}/api/notifications?unread_only=true", address))
+            .bearer_auth(token);
+        async move {
+            let list: serde_json::Value =
+                request.send().await.unwrap().json().await.unwrap();
+            list["notifications"].as_array().unwrap().clone()
+        }
+    };
+    let like = |token: &String| {
+        client
+            .post(format!("{}/api/posts/{}/like", address, post_id))
+            .bearer_auth(token)
+            .send()
+    };
+
+    // Act 1: Two likes, and the author's own
+    like(commenter).await.unwrap();
+    like(replier).await.unwrap();
+    like(author).await.unwrap();
+
+    // Assert: One batched notification naming the last liker
+    let unread = notifications(author).await;
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0]["kind"], "post_likes");
+    assert_eq!(unread[0]["payload"]["count"], 2);
+    assert_eq!(unread[0]["payload"]["user_id"], *replier_id);
+
+    // Act & Assert: Unliking and liking again is not another like
+    like(commenter).await.unwrap();
+    like(commenter).await.unwrap();
+    let unread = notifications(author).await;
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0]["payload"]["count"], 2);
+    assert_eq!(unread[0]["payload"]["user_id"], *replier_id);
+    assert_eq!(unread[0]["payload"]["user_ids"], serde_json::json!([*commenter_id, *replier_id]));
+
+    // Act 2: A reply to a comment, and a reply to the author's own comment
+    let comment = |token: &String, parent_id: Option<i64>| {
+        let request = client
+            .post(format!("{}/api/posts/{}/comments", address, post_id))
+            .bearer_auth(token)
+            .json(&serde_json::json!({"content": "A comment", "parent_id": parent_id}));
+        let pool = &pool;
+        async move {
+            // Move earlier comments back so the comment throttle lets this one through
+            sqlx::query!(
+                "UPDATE comments SET created_at = created_at - INTERVAL '1 minute' WHERE post_id = $1",
+                post_id
+            )
+            .execute(pool)
+            .await
+            .unwrap();
+            let created: serde_json::Value =
+                request.send().await.unwrap().json().await.unwrap();
+            created["id"].as_i64().unwrap()
+        }
+    };
+    let parent_id = comment(commenter, None).await;
+    let reply_id = comment(replier, Some(parent_id)).await;
+    let own_id = comment(author, None).await;
+    comment(replier, Some(own_id)).await;
+
+    // Assert: The commenter hears of the reply; the author only of new comments
+    let unread = notifications(commenter).await;
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0]["kind"], "comment_reply");
+    assert_eq!(unread[0]["payload"]["comment_id"], reply_id);
+    assert_eq!(unread[0]["payload"]["parent_id"], parent_id);
+    let kinds: Vec<_> = notifications(author)
+        .await
+        .iter()
+        .map(|n| n["kind"].as_str().unwrap().to_string())
+        .collect();
+    assert!(!kinds.contains(&"comment_reply".to_string()));
+    assert!(notifications(replier).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_comment_throttle_per_post() {
+    // Arrange: A verified user with two posts
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&std::env::var("DATABASE_URL").unwrap())
+        .await
+        .unwrap();
+
+    let username = format!("ct_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let user: serde_json::Value = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let user_id = user["id"].as_i64().unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap().to_string();
+    let mut posts = Vec::new();
+    for _ in 0..2 {
+        posts.push(
+            sqlx::query_scalar!(
+                "INSERT INTO posts (user_id, title, content) VALUES ($1, 'Busy', '...') RETURNING id",
+                user_id
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap(),
+        );
+    }
+    let comment = |post_id: i64| {
+        client
+            .post(format!("{}/api/posts/{}/comments", address, post_id))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&serde_json::json!({"content": "Me first"}))
+            .send()
+    };
+
+    // Act 1: Two comments in a row on one post, then one on the other
+    assert_eq!(comment(posts[0]).await.unwrap().status().as_u16(), 201);
+    let throttled = comment(posts[0]).await.unwrap();
+
+    // Assert 1: The second is refused with when to retry
+    assert_eq!(throttled.status().as_u16(), 429);
+    let retry_after: u64 = throttled.headers()["retry-after"].to_str().unwrap().parse().unwrap();
+    assert!((1..=15).contains(&retry_after));
+    let body: serde_json::Value = throttled.json().await.unwrap();
+    assert_eq!(body["code"], "rate_limited");
+    assert_eq!(body["retry_after"], retry_after);
+    assert_eq!(comment(posts[1]).await.unwrap().status().as_u16(), 201);
+
+    // Act 2: Thirty comments within the hour, the oldest half an hour ago
+    sqlx::query!(
+        r#"
+        INSERT INTO comments (post_id, user_id, content, created_at)
+        SELECT $1, $2, 'Earlier', CURRENT_TIMESTAMP - make_interval(mins => n + 1)
+        FROM generate_series(1, 29) n
+        "#,
+        posts[0],
+        user_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let throttled = comment(posts[0]).await.unwrap();
+
+    // Assert 2: Refused until the oldest of them is an hour old
+    assert_eq!(throttled.status().as_u16(), 429);
+    let body: serde_json::Value = throttled.json().await.unwrap();
+    let retry_after = body["retry_after"].as_u64().unwrap();
+    assert!((1790..=1800).contains(&retry_after), "retry_after = {}", retry_after);
+}
+
+#[tokio::test]
+async fn test_random_architecture() {
+    // Arrange: Three entries in a category of their own
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let category = format!("Rnd {}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let mut ids = Vec::new();
+    for (name, dynasty) in [("Tang Hall", "Tang"), ("Tang Gate", "Tang"), ("Liao Pagoda", "Liao")] {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO architectures (category, name, dynasty, location, description, cover_img, carousel_imgs) VALUES ($1, $2, $3, 'Somewhere', '...', 'http://img/c.jpg', '[]') RETURNING id",
+        )
+        .bind(&category)
+        .bind(name)
+        .bind(dynasty)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        ids.push(id);
+    }
+    // Lowest possible key: only found by wrapping around past the pivot
+    sqlx::query("UPDATE architectures SET random_key = 0 WHERE id = $1")
+        .bind(ids[2])
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let pick = |query: String| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            client
+                .get(format!("{}/api/architectures/random", address))
+                .query(&[("category", query.as_str())])
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+
+    // Act & Assert 1: Every pick comes from the category
+    for _ in 0..10 {
+        let res = pick(category.clone()).await;
+        assert_eq!(res.status().as_u16(), 200);
+        let body: serde_json::Value = res.json().await.unwrap();
+        assert!(ids.contains(&body["id"].as_i64().unwrap()));
+        assert_eq!(body["category"], category.as_str());
+    }
+
+    // Act & Assert 2: Filtered by dynasty too
+    let res = client
+        .get(format!("{}/api/architectures/random", address))
+        .query(&[("category", category.as_str()), ("dynasty", "Liao")])
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["id"], ids[2]);
+    assert_eq!(body["name"], "Liao Pagoda");
+
+    // Act & Assert 3: Nothing to pick from
+    let res = pick(format!("{} none", category)).await;
+    assert_eq!(res.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_trending_tags_and_topic_page() {
+    // Arrange: A verified author, and a tag nobody has used yet
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let username = format!("tg_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let user: serde_json::Value = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE users SET is_verified = TRUE WHERE id = $1", user["id"].as_i64().unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap();
+
+    let tag = format!("Topic {}", &username[3..]);
+    let slug = format!("topic-{}", &username[3..]);
+    let mut arch_ids = Vec::new();
+    for name in [tag.clone(), format!("Hall {}", username)] {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO architectures (category, name, dynasty, location, description, cover_img, carousel_imgs) VALUES ('Temple', $1, 'Tang', 'Somewhere', '...', 'http://img/c.jpg', '[]') RETURNING id",
+        )
+        .bind(name)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        arch_ids.push(id);
+    }
+
+    let create = |body: serde_json::Value| {
+        let client = client.clone();
+        let address = address.clone();
+        let token = token.to_string();
+        async move {
+            client
+                .post(format!("{}/api/posts", address))
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&body)
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+
+    // Act 1: Two posts with the tag (spelled two ways), one referencing an architecture
+    let first = create(serde_json::json!({
+        "title": "Tagged one",
+        "content": format!("See [[arch:{}]]", arch_ids[1]),
+        "tags": [tag, tag.to_uppercase(), "Other"]
+    }))
+    .await;
+    assert_eq!(first.status().as_u16(), 201);
+    let first_id = first.json::<serde_json::Value>().await.unwrap()["id"].as_i64().unwrap();
+    let second = create(serde_json::json!({
+        "title": "Tagged two",
+        "content": "No references",
+        "tags": [tag.to_uppercase()]
+    }))
+    .await;
+    assert_eq!(second.status().as_u16(), 201);
+
+    // Assert 1: Duplicates merge under the first spelling
+    let detail: serde_json::Value = client
+        .get(format!("{}/api/posts/{}", address, first_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let names: Vec<&str> = detail["tags"].as_array().unwrap().iter().map(|t| t["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["Other", tag.as_str()]);
+
+    // Act & Assert 2: The job puts the tag on the trending list
+    backend::jobs::trending_tags::run(pool.clone()).await.unwrap();
+    let trending: Vec<serde_json::Value> = client
+        .get(format!("{}/api/tags/trending", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entry = trending.iter().find(|t| t["slug"] == slug.as_str()).expect("tag is trending");
+    assert_eq!(entry["posts"], 2);
+    assert_eq!(entry["authors"], 1);
+    assert!(entry["score"].as_f64().unwrap() > 1.9);
+
+    // Act & Assert 3: The topic page lists both posts and both architectures, named one first
+    let topic: serde_json::Value = client
+        .get(format!("{}/api/tags/{}?limit=1", address, slug))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(topic["tag"]["name"], tag.as_str());
+    assert_eq!(topic["posts_count"], 2);
+    assert_eq!(topic["posts"].as_array().unwrap().len(), 1);
+    assert_eq!(topic["posts"][0]["title"], "Tagged two");
+    let related: Vec<i64> = topic["architectures"].as_array().unwrap().iter().map(|a| a["id"].as_i64().unwrap()).collect();
+    assert_eq!(related, arch_ids);
+    assert_eq!(topic["architectures"][1]["mentions"], 1);
+
+    // Act & Assert 4: Unknown tags and too many tags
+    let res = client.get(format!("{}/api/tags/{}-none", address, slug)).send().await.unwrap();
+    assert_eq!(res.status().as_u16(), 404);
+    let res = create(serde_json::json!({
+        "title": "Too many",
+        "content": "...",
+        "tags": ["a", "b", "c", "d", "e", "f"]
+    }))
+    .await;
+    assert_eq!(res.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_direct_messages() {
+    // Arrange: Two verified users and one who is not
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let mut users = Vec::new();
+    for verified in [true, false, true] {
+        let username = format!("dm_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let user: serde_json::Value = client
+            .post(format!("{}/api/auth/register", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let id = user["id"].as_i64().unwrap();
+        sqlx::query!("UPDATE users SET is_verified = $1 WHERE id = $2", verified, id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let login: serde_json::Value = client
+            .post(format!("{}/api/auth/login", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        users.push((id, login["token"].as_str().unwrap().to_string()));
+    }
+    let [(alice_id, alice), (bob_id, bob), (carol_id, carol)] = &users[..] else {
+        unreachable!()
+    };
+    let send = |token: &String, to: i64, body: &str| {
+        client
+            .post(format!("{}/api/messages/{}", address, to))
+            .bearer_auth(token)
+            .json(&serde_json::json!({"body": body}))
+            .send()
+    };
+
+    // Act & Assert: Only verified users start conversations, but anyone may reply
+    assert_eq!(send(bob, *alice_id, "Hi").await.unwrap().status().as_u16(), 401);
+    assert_eq!(send(alice, *bob_id, "About your photos").await.unwrap().status().as_u16(), 201);
+    assert_eq!(send(bob, *alice_id, "Sure, ask away").await.unwrap().status().as_u16(), 201);
+
+    let list: serde_json::Value = client
+        .get(format!("{}/api/conversations", address))
+        .bearer_auth(alice)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list["unread"], 1);
+    assert_eq!(list["conversations"][0]["user_id"], *bob_id);
+    assert_eq!(list["conversations"][0]["last_message"], "Sure, ask away");
+    let conversation_id = list["conversations"][0]["id"].as_i64().unwrap();
+
+    // Reading the conversation clears its unread count
+    let messages: serde_json::Value = client
+        .get(format!("{}/api/conversations/{}/messages", address, conversation_id))
+        .bearer_auth(alice)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(messages.as_array().unwrap().len(), 2);
+    assert_eq!(messages[0]["sender_id"], *bob_id);
+    let list: serde_json::Value = client
+        .get(format!("{}/api/conversations", address))
+        .bearer_auth(alice)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list["unread"], 0);
+
+    // Outsiders cannot read the conversation
+    let res = client
+        .get(format!("{}/api/conversations/{}/messages", address, conversation_id))
+        .bearer_auth(carol)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 404);
+
+    // A block stops messages both ways
+    sqlx::query!(
+        "INSERT INTO user_blocks (blocker_id, blocked_id) VALUES ($1, $2)",
+        carol_id,
+        alice_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    assert_eq!(send(alice, *carol_id, "Hello").await.unwrap().status().as_u16(), 401);
+    assert_eq!(send(carol, *alice_id, "Hello").await.unwrap().status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn test_block_and_mute_users() {
+    // Arrange: A user, someone they will block and someone they will mute
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to test DB");
+
+    let tag = &uuid::Uuid::new_v4().to_string()[..8];
+    let mut users = Vec::new();
+    for _ in 0..3 {
+        let username = format!("bm_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let user: serde_json::Value = client
+            .post(format!("{}/api/auth/register", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let id = user["id"].as_i64().unwrap();
+        sqlx::query!("UPDATE users SET is_verified = TRUE WHERE id = $1", id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let login: serde_json::Value = client
+            .post(format!("{}/api/auth/login", address))
+            .json(&serde_json::json!({"username": username, "password": "password123"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        users.push((id, login["token"].as_str().unwrap().to_string()));
+    }
+    let [(alice_id, alice), (bob_id, bob), (carol_id, carol)] = &users[..] else {
+        unreachable!()
+    };
+
+    let mut post_ids = Vec::new();
+    for token in [alice, bob, carol] {
+        let post: serde_json::Value = client
+            .post(format!("{}/api/posts", address))
+            .bearer_auth(token)
+            .json(&serde_json::json!({"title": format!("Block {}", tag), "content": "Hello"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        post_ids.push(post["id"].as_i64().unwrap());
+    }
+    let comment = |token: &String| {
+        client
+            .post(format!("{}/api/posts/{}/comments", address, post_ids[0]))
+            .bearer_auth(token)
+            .json(&serde_json::json!({"content": "Nice"}))
+            .send()
+    };
+    assert_eq!(comment(bob).await.unwrap().status().as_u16(), 201);
+    assert_eq!(comment(carol).await.unwrap().status().as_u16(), 201);
+
+    // Act
+    let res: serde_json::Value = client
+        .post(format!("{}/api/users/{}/block", address, bob_id))
+        .bearer_auth(alice)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(res["blocked"], true);
+    let res: serde_json::Value = client
+        .post(format!("{}/api/users/{}/mute", address, carol_id))
+        .bearer_auth(alice)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(res["muted"], true);
+
+    // Assert: Their comments and posts are hidden from Alice only
+    let comment_count = |token: Option<&String>| {
+        let mut request = client.get(format!("{}/api/posts/{}/comments", address, post_ids[0]));
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        async move {
+            let list: serde_json::Value = request.send().await.unwrap().json().await.unwrap();
+            list.as_array().unwrap().len()
+        }
+    };
+    assert_eq!(comment_count(Some(alice)).await, 0);
+    assert_eq!(comment_count(None).await, 2);
+
+    let post_count = |token: Option<&String>| {
+        let mut request = client.get(format!("{}/api/posts?q={}", address, tag));
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        async move {
+            let list: serde_json::Value = request.send().await.unwrap().json().await.unwrap();
+            list.as_array().unwrap().len()
+        }
+    };
+    assert_eq!(post_count(Some(alice)).await, 1);
+    assert_eq!(post_count(None).await, 3);
+
+    // Only the blocked user is kept from replying and messaging
+    sqlx::query!(
+        "UPDATE comments SET created_at = created_at - INTERVAL '1 hour' WHERE post_id = $1",
+        post_ids[0]
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    assert_eq!(comment(bob).await.unwrap().status().as_u16(), 401);
+    assert_eq!(comment(carol).await.unwrap().status().as_u16(), 201);
+    let res = client
+        .post(format!("{}/api/messages/{}", address, alice_id))
+        .bearer_auth(bob)
+        .json(&serde_json::json!({"body": "Hi"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 401);
+
+    let blocks: serde_json::Value = client
+        .get(format!("{}/api/profile/blocks", address))
+        .bearer_auth(alice)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(blocks["blocked"][0]["id"], *bob_id);
+    assert_eq!(blocks["muted"][0]["id"], *carol_id);
+
+    // Blocking again lifts the block
+    let res: serde_json::Value = client
+        .post(format!("{}/api/users/{}/block", address, bob_id))
+        .bearer_auth(alice)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(res["blocked"], false);
+    assert_eq!(comment(bob).await.unwrap().status().as_u16(), 201);
+}
@@ -0,0 +1,98 @@
+// tests/audit_stream_tests.rs
+
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use backend::utils::{
+    audit_stream::{self, AuditLayer},
+    log_filter::LogFilter,
+};
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry, filter::filter_fn, fmt, layer::SubscriberExt, reload,
+};
+
+/// Collects everything written to it.
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Buffer {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_audit_events_bypass_application_log() {
+    // Arrange: The same layers as `main`, writing to buffers
+    let (app_log, audit_log) = (Buffer::default(), Buffer::default());
+    let (env_filter, handle) = reload::Layer::new(EnvFilter::new("warn"));
+    let app_writer = app_log.clone();
+    let audit_writer = audit_log.clone();
+    let subscriber = Registry::default()
+        .with(
+            fmt::layer()
+                .with_writer(move || app_writer.clone())
+                .with_ansi(false)
+                .with_filter(env_filter)
+                .with_filter(filter_fn(|metadata| !audit_stream::is_audit(metadata))),
+        )
+        .with(
+            AuditLayer::new(move || audit_writer.clone())
+                .with_filter(filter_fn(audit_stream::is_audit)),
+        );
+    let _guard = tracing::subscriber::set_default(subscriber);
+    let log_filter = LogFilter::new(handle, "warn");
+
+    // Act: An audit event below the application level, and a warning
+    let ids = vec![3_i64, 4];
+    tracing::info!(
+        target: audit_stream::TARGET,
+        action = "questions_deleted",
+        actor_id = 7_i64,
+        confirmed = true,
+        question_ids = ?ids
+    );
+    tracing::warn!("disk almost full");
+
+    // Assert: Each went only to its own log
+    let audit = audit_log.lines();
+    assert_eq!(audit.len(), 1);
+    let event: serde_json::Value = serde_json::from_str(&audit[0]).unwrap();
+    assert_eq!(event["action"], "questions_deleted");
+    assert_eq!(event["actor_id"], 7);
+    assert_eq!(event["confirmed"], true);
+    assert_eq!(event["question_ids"], "[3, 4]");
+    assert!(
+        chrono::DateTime::parse_from_rfc3339(event["time"].as_str().unwrap()).is_ok()
+    );
+
+    let app = app_log.lines();
+    assert_eq!(app.len(), 1);
+    assert!(app[0].contains("disk almost full"));
+
+    // Act & Assert: Even at debug, the application log never gets audit events
+    log_filter.set("debug").unwrap();
+    tracing::info!(target: audit_stream::TARGET, action = "login", user_id = 1_i64);
+    tracing::info!("request served");
+    assert_eq!(audit_log.lines().len(), 2);
+    let app = app_log.lines();
+    assert_eq!(app.len(), 2);
+    assert!(app[1].contains("request served"));
+}
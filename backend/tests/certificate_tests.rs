@@ -0,0 +1,223 @@
+// tests/certificate_tests.rs
+
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig,
+        RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    state::AppState,
+    services::paper_snapshots,
+    storage::Storage,
+    utils::pdf::PdfFont,
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+async fn spawn_app() -> (String, PgPool, Arc<dyn Storage>) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: "certificate_test_secret".to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies: Vec::new(),
+    };
+
+    let state = AppState::new(pool.clone(), config);
+    let storage = state.storage.clone();
+    let app = routes::create_router(state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (address, pool, storage)
+}
+
+#[tokio::test]
+async fn test_quiz_certificate_issue_and_verify() {
+    // Arrange
+    let (address, pool, storage) = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let mut answers = HashMap::new();
+    for i in 0..10 {
+        let row = sqlx::query!(
+            "INSERT INTO questions (type, content, options, answer) VALUES ('single', $1, $2, 'B') RETURNING id",
+            format!("Certificate {} {}", i, uuid::Uuid::new_v4()),
+            serde_json::json!(["X", "Y"])
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        answers.insert(row.id, "B".to_string());
+    }
+
+    let question_ids: Vec<i64> = answers.keys().copied().collect();
+    let paper = || async {
+        let (snapshot_id, _) =
+            paper_snapshots::take(&mut pool.acquire().await.unwrap(), &question_ids)
+                .await
+                .unwrap();
+        paper_snapshots::sign(snapshot_id, "certificate_test_secret").unwrap()
+    };
+
+    let username = format!("cert_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap();
+
+    // Act 1: A perfect quiz earns a certificate
+    let result: serde_json::Value = client
+        .post(format!("{}/api/quiz/submit", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&serde_json::json!({"exam_token": paper().await, "answers": answers}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(result["score"], 100);
+    let code = result["certificate_code"]
+        .as_str()
+        .expect("Certificate code missing")
+        .to_string();
+
+    // Another perfect paper the same day earns no second certificate
+    let again: serde_json::Value = client
+        .post(format!("{}/api/quiz/submit", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&serde_json::json!({"exam_token": paper().await, "answers": answers}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(again["score"], 100);
+    assert!(again["certificate_code"].is_null());
+
+    // Assert: Publicly verifiable, PDF pending
+    let verify: serde_json::Value = client
+        .get(format!("{}/api/certificates/{}", address, code))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(verify["username"], username);
+    assert_eq!(verify["kind"], "quiz");
+    assert_eq!(verify["pdf_ready"], false);
+
+    let pending = client
+        .get(format!("{}/api/certificates/{}/pdf", address, code))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(pending.status().as_u16(), 404);
+
+    // Act 2: Drain the job queue, after a rename to a non-ASCII name
+    sqlx::query!(
+        "UPDATE users SET username = $1 WHERE username = $2",
+        format!("Zoë_{}", &username[5..]),
+        username
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let mailer = std::sync::Arc::new(backend::utils::mailer::LogMailer);
+    let font = PdfFont::load(Path::new("tests/fixtures/RobotoMedium.ttf")).unwrap();
+    backend::jobs::queue::run_pending(pool.clone(), storage, mailer, Some(font))
+        .await
+        .expect("Job queue failed");
+
+    // Assert: The download redirects to a signed link that serves the PDF
+    let no_redirects = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let redirect = no_redirects
+        .get(format!("{}/api/certificates/{}/pdf", address, code))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(redirect.status().as_u16(), 307);
+    let location = redirect.headers()["location"].to_str().unwrap().to_string();
+    assert!(location.starts_with("/api/files/certificates/"));
+
+    let tampered = client
+        .get(format!("{}{}", address, location.replace("signature=", "signature=00")))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(tampered.status().as_u16(), 401);
+
+    let pdf = client
+        .get(format!("{}/api/certificates/{}/pdf", address, code))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(pdf.status().as_u16(), 200);
+    assert_eq!(pdf.headers()["content-type"], "application/pdf");
+    let pdf = pdf.bytes().await.unwrap();
+    assert!(pdf.starts_with(b"%PDF-"));
+    // The username is drawn in the embedded font
+    assert!(String::from_utf8_lossy(&pdf).contains("/FontFile2"));
+
+    let unknown = client
+        .get(format!("{}/api/certificates/NOPE-NOPE", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unknown.status().as_u16(), 404);
+}
@@ -0,0 +1,448 @@
+// tests/competition_tests.rs
+//
+// A competition holds an exam event, which locks the question bank for
+// everyone while it runs, so these tests live in their own binary.
+
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig,
+        RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    state::AppState,
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+
+async fn spawn_app() -> (String, PgPool) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    // Leftovers from an aborted run would lock everyone else's question bank.
+    sqlx::query!("DELETE FROM competitions WHERE title LIKE 'test-%'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM exam_events WHERE title LIKE 'test-%'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: "competition_test_secret".to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies: Vec::new(),
+    };
+
+    let app = routes::create_router(AppState::new(pool.clone(), config));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (address, pool)
+}
+
+/// Registers a user with the given role and returns their ID and token.
+async fn create_user(
+    client: &reqwest::Client,
+    address: &str,
+    pool: &PgPool,
+    role: &str,
+) -> (i64, String) {
+    let username = format!("comp_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let creds = serde_json::json!({"username": username, "password": "password123"});
+
+    let user: serde_json::Value = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE users SET role = $1 WHERE username = $2", role, username)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    (
+        user["id"].as_i64().unwrap(),
+        login["token"].as_str().unwrap().to_string(),
+    )
+}
+
+#[tokio::test]
+async fn test_competition_registration_scoring_and_ranking() {
+    // Arrange: An admin, three entrants, an outsider and a two-question paper
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin) = create_user(&client, &address, &pool, "admin").await;
+    let mut entrants = Vec::new();
+    for _ in 0..3 {
+        entrants.push(create_user(&client, &address, &pool, "user").await);
+    }
+    let (_, outsider) = create_user(&client, &address, &pool, "user").await;
+
+    let mut question_ids = Vec::new();
+    for answer in ["A", "B"] {
+        let id = sqlx::query_scalar!(
+            r#"INSERT INTO questions (type, content, options, answer) VALUES ('single', $1, '["X", "Y"]', $2) RETURNING id"#,
+            format!("Competition {}", uuid::Uuid::new_v4()),
+            answer
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        question_ids.push(id);
+    }
+    let (q1, q2) = (question_ids[0], question_ids[1]);
+
+    let now = chrono::Utc::now();
+    let hour = chrono::Duration::hours(1);
+    let create = |ids: Vec<i64>| {
+        client
+            .post(format!("{}/api/admin/competitions", address))
+            .bearer_auth(&admin)
+            .json(&serde_json::json!({
+                "title": format!("test-{}", &uuid::Uuid::new_v4().to_string()[..8]),
+                "description": "Two questions",
+                "starts_at": now + hour,
+                "ends_at": now + hour * 2,
+                "question_ids": ids,
+            }))
+            .send()
+    };
+    let url = |id: i64, path: &str| format!("{}/api/competitions/{}{}", address, id, path);
+
+    // Errors: repeated and unknown questions
+    assert_eq!(create(vec![q1, q1]).await.unwrap().status().as_u16(), 400);
+    assert_eq!(create(vec![q1, -1]).await.unwrap().status().as_u16(), 400);
+
+    // Act 1: Create a competition and register before it starts; only the
+    // first entrant agrees to proctoring, the second changes their mind
+    let created = create(vec![q2, q1]).await.unwrap();
+    assert_eq!(created.status().as_u16(), 201);
+    let competition: serde_json::Value = created.json().await.unwrap();
+    let id = competition["id"].as_i64().unwrap();
+    assert_eq!(competition["question_count"], 2);
+    for (_, token) in &entrants {
+        let res = client.post(url(id, "/register")).bearer_auth(token).send().await.unwrap();
+        assert_eq!(res.status().as_u16(), 204);
+    }
+    for (token, consent) in [(&entrants[0].1, true), (&entrants[1].1, true), (&entrants[1].1, false)] {
+        let res = client
+            .post(url(id, "/register"))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "proctoring_consent": consent }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status().as_u16(), 204);
+    }
+    let early = client.get(url(id, "/paper")).bearer_auth(&entrants[0].1).send().await.unwrap();
+    assert_eq!(early.status().as_u16(), 409);
+
+    let detail: serde_json::Value = client
+        .get(url(id, ""))
+        .bearer_auth(&entrants[0].1)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["registrations"], 3);
+    assert_eq!(detail["registered"], true);
+    assert_eq!(detail["proctoring_consent"], true);
+
+    // An edit before the start leaves the paper as it was set
+    sqlx::query!(
+        "UPDATE questions SET content = 'Edited', answer = 'B' WHERE id = $1",
+        q1
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Act 2: Start it
+    sqlx::query!(
+        "UPDATE exam_events SET starts_at = NOW() - INTERVAL '1 minute' WHERE id = (SELECT exam_event_id FROM competitions WHERE id = $1)",
+        id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query!("UPDATE competitions SET starts_at = NOW() - INTERVAL '1 minute' WHERE id = $1", id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Assert: The bank is locked, outsiders get no paper, entrants get it in order
+    let locked = client
+        .post(format!("{}/api/admin/questions", address))
+        .bearer_auth(&admin)
+        .json(&serde_json::json!({
+            "question_type": "single",
+            "content": format!("Locked {}", uuid::Uuid::new_v4()),
+            "options": ["A", "B"],
+            "answer": "A",
+            "analysis": "None"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(locked.status().as_u16(), 409);
+    let outside = client.get(url(id, "/paper")).bearer_auth(&outsider).send().await.unwrap();
+    assert_eq!(outside.status().as_u16(), 401);
+
+    let answers = [
+        serde_json::json!({ q1.to_string(): "A", q2.to_string(): "b" }),
+        serde_json::json!({ q1.to_string(): "a", q2.to_string(): "B" }),
+        serde_json::json!({ q1.to_string(): "B", q2.to_string(): "B" }),
+    ];
+    let signals = serde_json::json!({ "focus_loss_count": 3, "paste_count": 1 });
+    let invalid = client
+        .post(url(id, "/submit"))
+        .bearer_auth(&entrants[0].1)
+        .json(&serde_json::json!({
+            "answers": {},
+            "proctoring": { "focus_loss_count": -1, "paste_count": 0 }
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(invalid.status().as_u16(), 400);
+    for ((user_id, token), answers) in entrants.iter().zip(&answers) {
+        let paper: serde_json::Value = client
+            .get(url(id, "/paper"))
+            .bearer_auth(token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let served: Vec<i64> = paper["questions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|q| q["id"].as_i64().unwrap())
+            .collect();
+        assert_eq!(served, vec![q2, q1]);
+        assert!(paper["questions"][0].get("answer").is_none());
+        assert_ne!(paper["questions"][1]["content"], "Edited");
+
+        let submitted: serde_json::Value = client
+            .post(url(id, "/submit"))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "answers": answers, "proctoring": signals }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(submitted["total_questions"], 2);
+        let expected = if *user_id == entrants[2].0 { 1 } else { 2 };
+        assert_eq!(submitted["score"], expected);
+    }
+    let again = client
+        .post(url(id, "/submit"))
+        .bearer_auth(&entrants[0].1)
+        .json(&serde_json::json!({ "answers": {} }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(again.status().as_u16(), 409);
+    let hidden = client.get(url(id, "/results")).send().await.unwrap();
+    assert_eq!(hidden.status().as_u16(), 409);
+
+    // Assert: Admins see the signals of the entrant who consented only
+    let entries: Vec<serde_json::Value> = client
+        .get(format!("{}/api/admin/competitions/{}/entries", address, id))
+        .bearer_auth(&admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entry = |user_id: i64| entries.iter().find(|e| e["user_id"] == user_id).unwrap();
+    assert_eq!(entry(entrants[0].0)["focus_loss_count"], 3);
+    assert_eq!(entry(entrants[0].0)["paste_count"], 1);
+    assert_eq!(entry(entrants[1].0)["proctoring_consent"], false);
+    assert!(entry(entrants[1].0)["focus_loss_count"].is_null());
+
+    // Act 3: The first two tie on score; the second was faster. Then it ends.
+    for (user_id, secs) in [(entrants[0].0, 60.0), (entrants[1].0, 30.0)] {
+        sqlx::query!(
+            "UPDATE competition_entries SET started_at = submitted_at - make_interval(secs => $3) WHERE competition_id = $1 AND user_id = $2",
+            id,
+            user_id,
+            secs
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    sqlx::query!(
+        "UPDATE exam_events SET ends_at = NOW() WHERE id = (SELECT exam_event_id FROM competitions WHERE id = $1)",
+        id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query!("UPDATE competitions SET ends_at = NOW() WHERE id = $1", id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Assert: Registration is closed and the ranking breaks the tie on time
+    let late = client.post(url(id, "/register")).bearer_auth(&outsider).send().await.unwrap();
+    assert_eq!(late.status().as_u16(), 409);
+    let results: Vec<serde_json::Value> = client
+        .get(url(id, "/results"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let ranking: Vec<(i64, i64, i64)> = results
+        .iter()
+        .map(|r| {
+            (
+                r["rank"].as_i64().unwrap(),
+                r["user_id"].as_i64().unwrap(),
+                r["score"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        ranking,
+        vec![(1, entrants[1].0, 2), (2, entrants[0].0, 2), (3, entrants[2].0, 1)]
+    );
+    assert_eq!(results[0]["duration_ms"], 30_000);
+
+    // Cleanup
+    sqlx::query!("DELETE FROM competitions WHERE id = $1", id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query!("DELETE FROM questions WHERE id = ANY($1)", &question_ids)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_delete_upcoming_competition() {
+    // Arrange: An upcoming competition
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin) = create_user(&client, &address, &pool, "admin").await;
+    let question_id = sqlx::query_scalar!(
+        r#"INSERT INTO questions (type, content, options, answer) VALUES ('single', $1, '["X", "Y"]', 'A') RETURNING id"#,
+        format!("Competition {}", uuid::Uuid::new_v4())
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let now = chrono::Utc::now();
+    let competition: serde_json::Value = client
+        .post(format!("{}/api/admin/competitions", address))
+        .bearer_auth(&admin)
+        .json(&serde_json::json!({
+            "title": format!("test-{}", &uuid::Uuid::new_v4().to_string()[..8]),
+            "starts_at": now + chrono::Duration::days(1),
+            "ends_at": now + chrono::Duration::days(2),
+            "question_ids": [question_id],
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = competition["id"].as_i64().unwrap();
+    let event_id = sqlx::query_scalar!("SELECT exam_event_id FROM competitions WHERE id = $1", id)
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Act
+    let res = client
+        .delete(format!("{}/api/admin/competitions/{}", address, id))
+        .bearer_auth(&admin)
+        .send()
+        .await
+        .unwrap();
+
+    // Assert: The competition and its exam event are gone
+    assert_eq!(res.status().as_u16(), 204);
+    let event_left = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM exam_events WHERE id = $1) as "exists!""#,
+        event_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert!(!event_left);
+    let gone = client
+        .get(format!("{}/api/competitions/{}", address, id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(gone.status().as_u16(), 404);
+
+    sqlx::query!("DELETE FROM questions WHERE id = $1", question_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,271 @@
+// tests/dossier_tests.rs
+
+use std::{path::Path, sync::Arc};
+
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig,
+        RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    services::dossiers::{self, CitedPost, Dossier, DossierImage},
+    state::AppState,
+    storage::Storage,
+    utils::pdf::{PdfFont, wrap},
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+
+async fn spawn_app() -> (String, PgPool, Arc<dyn Storage>) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: "dossier_test_secret".to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies: Vec::new(),
+    };
+
+    let state = AppState::new(pool.clone(), config);
+    let storage = state.storage.clone();
+    let app = routes::create_router(state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (address, pool, storage)
+}
+
+/// A 4x2 RGB JPEG.
+fn tiny_jpeg() -> Vec<u8> {
+    let mut jpeg = Vec::new();
+    image::RgbImage::from_pixel(4, 2, image::Rgb([200, 40, 40]))
+        .write_to(&mut std::io::Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+        .unwrap();
+    jpeg
+}
+
+fn dossier(locale: &str, description: &str) -> Dossier {
+    Dossier {
+        id: 7,
+        locale: locale.to_string(),
+        name: "佛光寺东大殿".to_string(),
+        category: "Temple".to_string(),
+        dynasty: "Tang".to_string(),
+        location: "山西省忻州市五台县".to_string(),
+        province: Some("山西省".to_string()),
+        city: Some("忻州市".to_string()),
+        district: Some("五台县".to_string()),
+        description: description.to_string(),
+        height_m: Some(13.0),
+        bays_wide: Some(7),
+        bays_deep: Some(4),
+        images: vec![
+            DossierImage {
+                url: "https://img.example.com/cover.jpg".to_string(),
+                jpeg: Some(tiny_jpeg()),
+                color: None,
+            },
+            DossierImage {
+                url: "https://img.example.com/hall.png".to_string(),
+                jpeg: None,
+                color: Some("#8a3324".to_string()),
+            },
+        ],
+        author: Some("liang".to_string()),
+        contribution_id: Some(3),
+        posts: vec![CitedPost {
+            id: 11,
+            title: "Visiting Foguang Temple".to_string(),
+            author: "lin".to_string(),
+            created_at: chrono::Utc::now(),
+        }],
+        generated_at: chrono::Utc::now(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_wrap_lines() {
+    // Words stay whole, CJK breaks anywhere, overlong words are split
+    assert_eq!(
+        wrap("the main hall", 10.0, 40.0),
+        vec!["the", "main", "hall"]
+    );
+    assert_eq!(wrap("东大殿东大殿", 10.0, 30.0), vec!["东大殿", "东大殿"]);
+    assert_eq!(wrap("abcdefghij", 10.0, 30.0), vec!["abcde", "fghij"]);
+    assert!(wrap("", 10.0, 30.0).is_empty());
+}
+
+/// Text as the hex digits it is drawn with in the built-in fonts.
+fn hex(text: &str) -> String {
+    text.bytes().map(|b| format!("{:02X}", b)).collect()
+}
+
+fn test_font() -> PdfFont {
+    PdfFont::load(Path::new("tests/fixtures/RobotoMedium.ttf")).expect("Test font missing")
+}
+
+#[test]
+fn test_render_dossier() {
+    let long = "<p>The great east hall was built in 857.</p>".repeat(120);
+    let pdf = dossiers::render(&dossier("en", &long), None).unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+
+    assert!(pdf.starts_with(b"%PDF-"));
+    assert!(text.trim_end().ends_with("%%EOF"));
+    // The description runs over several pages
+    let pages = text.matches("/Type/Page/").count();
+    assert!(pages >= 3, "{} pages", pages);
+    assert!(text.contains(&format!("/Type/Pages/Count {}", pages)));
+    // The JPEG is embedded, the PNG painted in its dominant color
+    assert_eq!(text.matches("/Subtype/Image").count(), 1);
+    assert!(text.contains("/Width 4/Height 2"));
+    assert!(text.contains("/ColorSpace/DeviceRGB"));
+    assert!(text.contains("0.5411765 0.2 0.14117648 rg"));
+    // Without a font, text is set in Helvetica
+    assert!(text.contains("/BaseFont/Helvetica-Bold"));
+    assert!(!text.contains("/FontFile2"));
+    assert!(text.contains(&hex("Sources")));
+    assert!(text.contains(&hex("Fig. 1 [2]")));
+    assert!(text.contains(&hex("www.openstreetmap.org/search?query=")));
+    assert!(text.contains(&hex("[4] Community post: Visiting Foguang Temple, lin")));
+}
+
+#[test]
+fn test_render_dossier_embeds_font() {
+    let pdf = dossiers::render(&dossier("zh-CN", "<p>东大殿</p>"), Some(&test_font())).unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+
+    // The font is embedded and text is drawn as its glyphs
+    assert!(text.contains("/Subtype/CIDFontType2"));
+    assert!(text.contains("/Encoding/Identity-H"));
+    assert!(text.contains("/FontFile2"));
+    assert!(text.contains("/ToUnicode"));
+    assert!(!text.contains("/BaseFont/Helvetica"));
+    // Headings are drawn bold by stroking the outlines
+    assert!(text.contains("2 Tr"));
+}
+
+#[tokio::test]
+async fn test_dossier_download() {
+    // Arrange
+    let (address, pool, storage) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let no_redirects = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+
+    let arch_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO architectures (category, name, dynasty, location, description, cover_img, carousel_imgs)
+        VALUES ('Temple', $1, 'Tang', '山西省忻州市五台县', '<p>东大殿</p>', 'http://127.0.0.1:9/cover.jpg', '[]')
+        RETURNING id
+        "#,
+        format!("Dossier {}", uuid::Uuid::new_v4())
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let url = format!("{}/api/architectures/{}/dossier.pdf", address, arch_id);
+    let jobs = || {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM jobs WHERE kind = 'architecture_dossier' AND (payload->>'architecture_id')::BIGINT = $1"#,
+            arch_id
+        )
+        .fetch_one(&pool)
+    };
+
+    // Act 1: The first requests queue a single render
+    for _ in 0..2 {
+        let res = client.get(&url).send().await.unwrap();
+        assert_eq!(res.status().as_u16(), 202);
+        assert_eq!(res.headers()["retry-after"], "5");
+    }
+    assert_eq!(jobs().await.unwrap(), 1);
+
+    let missing = client
+        .get(format!("{}/api/architectures/0/dossier.pdf", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status().as_u16(), 404);
+
+    // Act 2: Render; the unreachable cover is left out
+    let payload = serde_json::json!({"architecture_id": arch_id, "locale": "zh-CN"});
+    backend::jobs::architecture_dossier::run(&pool, storage.as_ref(), None, &payload)
+        .await
+        .unwrap();
+
+    // Assert: Redirected to a signed link serving the PDF
+    let redirect = no_redirects.get(&url).send().await.unwrap();
+    assert_eq!(redirect.status().as_u16(), 307);
+    let location = redirect.headers()["location"].to_str().unwrap().to_string();
+    assert!(location.starts_with("/api/files/dossiers/"));
+
+    let pdf = client
+        .get(format!("{}{}", address, location))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(pdf.status().as_u16(), 200);
+    assert_eq!(pdf.headers()["content-type"], "application/pdf");
+    assert!(pdf.bytes().await.unwrap().starts_with(b"%PDF-"));
+
+    // Each locale has its own dossier
+    let english = client
+        .get(&url)
+        .header("Accept-Language", "en")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(english.status().as_u16(), 202);
+    assert_eq!(jobs().await.unwrap(), 2);
+
+    // Act 3: Editing the architecture makes the dossier stale
+    sqlx::query!(
+        "UPDATE architectures SET description = '<p>东大殿，唐代木构</p>' WHERE id = $1",
+        arch_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let res = no_redirects.get(&url).send().await.unwrap();
+    assert_eq!(res.status().as_u16(), 202);
+    assert_eq!(jobs().await.unwrap(), 3);
+}
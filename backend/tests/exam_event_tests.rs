@@ -0,0 +1,297 @@
+// tests/exam_event_tests.rs
+//
+// A running exam event locks the question bank for everyone, so these tests
+// live in their own binary and end the events they schedule.
+
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig,
+        RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    state::AppState,
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+
+async fn spawn_app() -> (String, PgPool) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    // Leftovers from an aborted run would lock everyone else's question bank.
+    sqlx::query!("DELETE FROM exam_events WHERE title LIKE 'test-%'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: "exam_event_test_secret".to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies: Vec::new(),
+    };
+
+    let app = routes::create_router(AppState::new(pool.clone(), config));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (address, pool)
+}
+
+/// Registers a user with the given role and returns their ID and token.
+async fn create_user(
+    client: &reqwest::Client,
+    address: &str,
+    pool: &PgPool,
+    role: &str,
+) -> (i64, String) {
+    let username = format!("exev_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let creds = serde_json::json!({"username": username, "password": "password123"});
+
+    let user: serde_json::Value = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE users SET role = $1 WHERE username = $2", role, username)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    (
+        user["id"].as_i64().unwrap(),
+        login["token"].as_str().unwrap().to_string(),
+    )
+}
+
+#[tokio::test]
+async fn test_exam_event_locks_question_bank_and_holds_back_scores() {
+    // Arrange: An admin, a user, a pending question contribution and a score
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin) = create_user(&client, &address, &pool, "admin").await;
+    let (user_id, user) = create_user(&client, &address, &pool, "user").await;
+
+    let question = |tag: &str| {
+        serde_json::json!({
+            "question_type": "single",
+            "content": format!("Exam event {} {}", tag, uuid::Uuid::new_v4()),
+            "options": ["A", "B"],
+            "answer": "A",
+            "analysis": "None"
+        })
+    };
+    let contribution_id = sqlx::query_scalar!(
+        "INSERT INTO contributions (user_id, type, data) VALUES ($1, 'question', $2) RETURNING id",
+        user_id,
+        question("contributed")
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let create_question = || {
+        client
+            .post(format!("{}/api/admin/questions", address))
+            .bearer_auth(&admin)
+            .json(&question("direct"))
+            .send()
+    };
+    let on_leaderboard = || {
+        let request = client.get(format!("{}/api/quiz/leaderboard", address));
+        async move {
+            let entries: Vec<serde_json::Value> =
+                request.send().await.unwrap().json().await.unwrap();
+            entries.iter().any(|e| e["score"] == 1_000_000)
+        }
+    };
+    let now = chrono::Utc::now();
+    let schedule = |starts_at: chrono::DateTime<chrono::Utc>, ends_at: chrono::DateTime<chrono::Utc>| {
+        client
+            .post(format!("{}/api/admin/exam-events", address))
+            .bearer_auth(&admin)
+            .json(&serde_json::json!({
+                "title": format!("test-{}", &uuid::Uuid::new_v4().to_string()[..8]),
+                "starts_at": starts_at,
+                "ends_at": ends_at,
+            }))
+            .send()
+    };
+
+    // Errors: backwards, past, overlong events and non-admins
+    let hour = chrono::Duration::hours(1);
+    assert_eq!(schedule(now + hour, now).await.unwrap().status().as_u16(), 400);
+    assert_eq!(schedule(now - hour * 2, now - hour).await.unwrap().status().as_u16(), 400);
+    assert_eq!(schedule(now, now + hour * 24 * 8).await.unwrap().status().as_u16(), 400);
+    let forbidden = client
+        .post(format!("{}/api/admin/exam-events", address))
+        .bearer_auth(&user)
+        .json(&serde_json::json!({"title": "test-user", "starts_at": now, "ends_at": now + hour}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(forbidden.status().as_u16(), 403);
+
+    // Act 1: Start an event
+    let event: serde_json::Value = schedule(now - chrono::Duration::minutes(1), now + hour)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let event_id = event["id"].as_i64().unwrap();
+    sqlx::query!(
+        "INSERT INTO exam_records (user_id, score) VALUES ($1, 1000000)",
+        user_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Assert: The bank is readable but locked, and the new score is held back
+    assert_eq!(create_question().await.unwrap().status().as_u16(), 409);
+    let list = client
+        .get(format!("{}/api/admin/questions", address))
+        .bearer_auth(&admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(list.status().as_u16(), 200);
+    let review = client
+        .put(format!("{}/api/admin/contributions/{}/review", address, contribution_id))
+        .bearer_auth(&admin)
+        .json(&serde_json::json!({"status": "approved"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(review.status().as_u16(), 409);
+    assert!(!on_leaderboard().await);
+    let events: Vec<serde_json::Value> = client
+        .get(format!("{}/api/admin/exam-events", address))
+        .bearer_auth(&admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(events.iter().any(|e| e["id"] == event_id));
+
+    // Act 2: End it early
+    let ended = client
+        .delete(format!("{}/api/admin/exam-events/{}", address, event_id))
+        .bearer_auth(&admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(ended.status().as_u16(), 204);
+
+    // Assert: Edits and approvals work again, and the score is published
+    assert_eq!(create_question().await.unwrap().status().as_u16(), 201);
+    let review = client
+        .put(format!("{}/api/admin/contributions/{}/review", address, contribution_id))
+        .bearer_auth(&admin)
+        .json(&serde_json::json!({"status": "approved"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(review.status().as_u16(), 200);
+    assert!(on_leaderboard().await);
+    let again = client
+        .delete(format!("{}/api/admin/exam-events/{}", address, event_id))
+        .bearer_auth(&admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(again.status().as_u16(), 404);
+
+    // Act 3: A worse retake during the next event
+    let event: serde_json::Value = schedule(chrono::Utc::now(), now + hour)
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let paper: serde_json::Value = client
+        .get(format!("{}/api/quiz/generate", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let first_id = paper["questions"][0]["id"].as_i64().unwrap().to_string();
+    let submitted = client
+        .post(format!("{}/api/quiz/submit", address))
+        .bearer_auth(&user)
+        .json(&serde_json::json!({
+            "exam_token": paper["exam_token"],
+            "answers": { first_id: "not an option" }
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(submitted.status().as_u16(), 200);
+
+    // Assert: The earlier best score stays on the leaderboard
+    assert!(on_leaderboard().await);
+    client
+        .delete(format!("{}/api/admin/exam-events/{}", address, event["id"]))
+        .bearer_auth(&admin)
+        .send()
+        .await
+        .unwrap();
+
+    // Cleanup: The score would top the leaderboard for every other test
+    sqlx::query!("DELETE FROM exam_records WHERE user_id = $1", user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,398 @@
+// tests/feed_tests.rs
+
+use std::sync::Arc;
+
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig,
+        RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    services::feed::{FeedCandidate, FeedItemKind, FeedScorer, UserAffinity},
+    state::AppState,
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+
+/// Scorer that pins one post to the top, proving the ranking is pluggable.
+struct PinPost(i64);
+
+impl FeedScorer for PinPost {
+    fn score(&self, item: &FeedCandidate, _: &UserAffinity) -> f64 {
+        if item.kind == FeedItemKind::Post && item.id == self.0 {
+            1e9
+        } else {
+            0.0
+        }
+    }
+}
+
+async fn spawn_app(scorer: Option<Arc<dyn FeedScorer>>) -> (String, PgPool) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: "feed_test_secret".to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies: Vec::new(),
+    };
+
+    let mut state = AppState::new(pool.clone(), config);
+    if let Some(scorer) = scorer {
+        state.feed_scorer = scorer;
+    }
+    let app = routes::create_router(state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (address, pool)
+}
+
+/// Registers a verified user and returns their token.
+async fn verified_user(client: &reqwest::Client, address: &str, pool: &PgPool) -> String {
+    let username = format!("feed_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let creds = serde_json::json!({"username": username, "password": "password123"});
+
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE users SET is_verified = TRUE WHERE username = $1", username)
+        .execute(pool)
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    login["token"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_discover_feed_uses_scorer_and_hides_own_posts() {
+    // Arrange: Create a post, then spawn an app whose scorer pins it
+    let (address, pool) = spawn_app(None).await;
+    let client = reqwest::Client::new();
+    let author_token = verified_user(&client, &address, &pool).await;
+
+    let post: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .header("Authorization", format!("Bearer {}", author_token))
+        .json(&serde_json::json!({"title": "Any answers?", "content": "<p>Why <b>dougong</b>?</p>"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let post_id = post["id"].as_i64().unwrap();
+
+    let (pinned_address, _) = spawn_app(Some(Arc::new(PinPost(post_id)))).await;
+
+    // Act 1: Anonymous visitor
+    let feed: Vec<serde_json::Value> = client
+        .get(format!("{}/api/feed/discover?limit=5", pinned_address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(feed.len() <= 5);
+    assert_eq!(feed[0]["kind"], "post");
+    assert_eq!(feed[0]["id"], post_id);
+    assert_eq!(feed[0]["snippet"], "Why dougong ?");
+    assert_eq!(
+        feed.iter().filter(|i| i["kind"] == "post" && i["id"] == post_id).count(),
+        1,
+        "A post in several buckets appears once"
+    );
+
+    // Act 2: The author does not get their own post recommended
+    let own: Vec<serde_json::Value> = client
+        .get(format!("{}/api/feed/discover", pinned_address))
+        .header("Authorization", format!("Bearer {}", author_token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(!own.iter().any(|i| i["kind"] == "post" && i["id"] == post_id));
+}
+
+#[tokio::test]
+async fn test_follow_users_and_following_feed() {
+    // Arrange: A reader, two authors with a post each
+    let (address, pool) = spawn_app(None).await;
+    let client = reqwest::Client::new();
+    let reader = verified_user(&client, &address, &pool).await;
+    let followed = verified_user(&client, &address, &pool).await;
+    let other = verified_user(&client, &address, &pool).await;
+    let user_id = |token: String| {
+        let request = client.get(format!("{}/api/profile/me", address)).bearer_auth(token);
+        async move {
+            let me: serde_json::Value = request.send().await.unwrap().json().await.unwrap();
+            me["id"].as_i64().unwrap()
+        }
+    };
+    let reader_id = user_id(reader.clone()).await;
+    let followed_id = user_id(followed.clone()).await;
+    let tag = &uuid::Uuid::new_v4().to_string()[..8];
+    let mut post_ids = Vec::new();
+    for token in [&followed, &other] {
+        let post: serde_json::Value = client
+            .post(format!("{}/api/posts", address))
+            .bearer_auth(token)
+            .json(&serde_json::json!({"title": format!("Hall notes {}", tag), "content": "Notes"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        post_ids.push(post["id"].as_i64().unwrap());
+    }
+    let follow = |token: &str, id: i64| {
+        client
+            .post(format!("{}/api/users/{}/follow", address, id))
+            .bearer_auth(token)
+            .send()
+    };
+    let following_feed = |sort: &'static str| {
+        let request = client
+            .get(format!("{}/api/posts", address))
+            .query(&[("feed", "following"), ("q", tag), ("sort", sort)])
+            .bearer_auth(&reader);
+        async move {
+            let posts: Vec<serde_json::Value> = request.send().await.unwrap().json().await.unwrap();
+            posts.iter().map(|p| p["id"].as_i64().unwrap()).collect::<Vec<_>>()
+        }
+    };
+
+    // Act 1: Follow one author
+    let res: serde_json::Value = follow(&reader, followed_id).await.unwrap().json().await.unwrap();
+    assert_eq!(res["following"], true);
+
+    // Assert: The following feed only has their posts, in both orders
+    assert_eq!(following_feed("new").await, vec![post_ids[0]]);
+    assert_eq!(following_feed("hot").await, vec![post_ids[0]]);
+    let everyone: Vec<serde_json::Value> = client
+        .get(format!("{}/api/posts", address))
+        .query(&[("q", tag)])
+        .bearer_auth(&reader)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(everyone.len(), 2);
+
+    // Followers are listed and counted on the profile
+    let followers: Vec<serde_json::Value> = client
+        .get(format!("{}/api/users/{}/followers", address, followed_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(followers.len(), 1);
+    assert_eq!(followers[0]["id"], reader_id);
+    assert!(followers[0]["followed_at"].is_string());
+    let profile: serde_json::Value = client
+        .get(format!("{}/api/users/{}", address, followed_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(profile["followers_count"], 1);
+    assert_eq!(profile["following_count"], 0);
+
+    // Act 2: Following again unfollows
+    let res: serde_json::Value = follow(&reader, followed_id).await.unwrap().json().await.unwrap();
+    assert_eq!(res["following"], false);
+    assert!(following_feed("new").await.is_empty());
+
+    // Errors: self, unknown users, anonymous readers and unknown feeds
+    assert_eq!(follow(&reader, reader_id).await.unwrap().status().as_u16(), 400);
+    assert_eq!(follow(&reader, 0).await.unwrap().status().as_u16(), 404);
+    let anonymous = client
+        .get(format!("{}/api/posts?feed=following", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(anonymous.status().as_u16(), 401);
+    let unknown = client
+        .get(format!("{}/api/posts?feed=friends", address))
+        .bearer_auth(&reader)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unknown.status().as_u16(), 400);
+    let missing = client
+        .get(format!("{}/api/users/0/followers", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_user_activity_timeline() {
+    // Arrange: A user with a post, two comments (one deleted), an approved
+    // and a pending contribution
+    let (address, pool) = spawn_app(None).await;
+    let client = reqwest::Client::new();
+    let token = verified_user(&client, &address, &pool).await;
+    let me: serde_json::Value = client
+        .get(format!("{}/api/profile/me", address))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = me["id"].as_i64().unwrap();
+
+    let post: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({"title": "Timeline post", "content": "Notes"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let post_id = post["id"].as_i64().unwrap();
+    let mut comment_ids = Vec::new();
+    for content in ["<b>First</b> reply", "Second reply"] {
+        let comment: serde_json::Value = client
+            .post(format!("{}/api/posts/{}/comments", address, post_id))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({"content": content}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        comment_ids.push(comment["id"].as_i64().unwrap());
+        // Stay clear of the comment throttle, keeping the order of events
+        for sql in [
+            "UPDATE comments SET created_at = created_at - INTERVAL '1 hour' WHERE user_id = $1",
+            "UPDATE posts SET created_at = created_at - INTERVAL '1 hour' WHERE user_id = $1",
+        ] {
+            sqlx::query(sql).bind(id).execute(&pool).await.unwrap();
+        }
+    }
+    sqlx::query!("UPDATE comments SET deleted_at = NOW() WHERE id = $1", comment_ids[1])
+        .execute(&pool)
+        .await
+        .unwrap();
+    let approved = sqlx::query_scalar!(
+        r#"INSERT INTO contributions (user_id, type, data, status, created_at, reviewed_at)
+           VALUES ($1, 'architecture', '{"name": "Timeline Pagoda"}', 'approved',
+                   NOW() - INTERVAL '2 days', NOW() + INTERVAL '1 minute')
+           RETURNING id"#,
+        id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    sqlx::query!(
+        r#"INSERT INTO contributions (user_id, type, data) VALUES ($1, 'architecture', '{"name": "Pending"}')"#,
+        id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let activity = |query: Vec<(&'static str, String)>| {
+        let request = client
+            .get(format!("{}/api/users/{}/activity", address, id))
+            .query(&query);
+        async move {
+            let items: Vec<serde_json::Value> = request.send().await.unwrap().json().await.unwrap();
+            items
+        }
+    };
+
+    // Act
+    let items = activity(vec![]).await;
+
+    // Assert: Newest first, deleted and unapproved items left out
+    let kinds: Vec<_> = items.iter().map(|i| (i["kind"].as_str().unwrap(), i["id"].as_i64().unwrap())).collect();
+    assert_eq!(
+        kinds,
+        vec![("contribution", approved), ("comment", comment_ids[0]), ("post", post_id)]
+    );
+    assert_eq!(items[0]["title"], "Timeline Pagoda");
+    assert_eq!(items[0]["contribution_type"], "architecture");
+    assert_eq!(items[1]["title"], "Timeline post");
+    assert_eq!(items[1]["post_id"], post_id);
+    assert_eq!(items[1]["excerpt"], "First reply");
+
+    // Pages continue from the cursor
+    let cursor = items[0]["created_at"].as_str().unwrap().to_string();
+    let page = activity(vec![("cursor", cursor), ("limit", "1".to_string())]).await;
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0]["id"], comment_ids[0]);
+
+    // Unknown users are 404
+    let missing = client
+        .get(format!("{}/api/users/0/activity", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status().as_u16(), 404);
+}
@@ -0,0 +1,130 @@
+// tests/image_preview_tests.rs
+
+use std::io::Cursor;
+
+use backend::{
+    services::image_placeholders::{image_urls, placeholder},
+    utils::image_preview::{blurhash, decode, dominant_color},
+};
+use image::{ImageFormat, RgbImage, RgbaImage, codecs::jpeg::JpegEncoder};
+
+fn png(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 4]) -> Vec<u8> {
+    let image = RgbaImage::from_fn(width, height, |x, y| image::Rgba(pixel(x, y)));
+    let mut out = Cursor::new(Vec::new());
+    image.write_to(&mut out, ImageFormat::Png).unwrap();
+    out.into_inner()
+}
+
+fn jpeg(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 3]) -> Vec<u8> {
+    let image = RgbImage::from_fn(width, height, |x, y| image::Rgb(pixel(x, y)));
+    let mut out = Vec::new();
+    image.write_with_encoder(JpegEncoder::new_with_quality(&mut out, 100)).unwrap();
+    out
+}
+
+/// Whether `#rrggbb` is within a few levels of `rgb` per channel; JPEG is lossy.
+fn near(color: &str, rgb: [u8; 3]) -> bool {
+    (0..3).all(|c| {
+        let value = u8::from_str_radix(&color[1 + c * 2..3 + c * 2], 16).unwrap();
+        value.abs_diff(rgb[c]) <= 3
+    })
+}
+
+#[test]
+fn test_png_preview_and_dominant_color() {
+    // Two thirds red, one third blue
+    let image = png(30, 20, |x, _| {
+        if x < 20 {
+            [255, 0, 0, 255]
+        } else {
+            [0, 0, 255, 255]
+        }
+    });
+    let preview = decode(&image).unwrap();
+    assert_eq!((preview.width, preview.height), (30, 20));
+    assert_eq!((preview.grid_width, preview.grid_height), (30, 20));
+    assert_eq!(preview.pixels[0], [255, 0, 0]);
+    assert_eq!(preview.pixels[29], [0, 0, 255]);
+    assert_eq!(dominant_color(&preview), "#ff0000");
+
+    // Large images are averaged down to at most 32 cells a side
+    let preview = decode(&png(200, 100, |_, _| [10, 20, 30, 255])).unwrap();
+    assert_eq!((preview.grid_width, preview.grid_height), (32, 16));
+    assert_eq!(dominant_color(&preview), "#0a141e");
+}
+
+#[test]
+fn test_png_transparent_pixels_do_not_count() {
+    let image = png(10, 10, |x, _| {
+        if x < 8 {
+            [0, 255, 0, 0]
+        } else {
+            [200, 100, 50, 255]
+        }
+    });
+    let preview = decode(&image).unwrap();
+    assert_eq!(dominant_color(&preview), "#ffffff");
+    assert_eq!(preview.pixels[9], [200, 100, 50]);
+}
+
+#[test]
+fn test_blurhash() {
+    let preview = decode(&png(40, 30, |_, _| [255, 0, 0, 255])).unwrap();
+    let hash = blurhash(&preview);
+    // 4x3 components ('L') that decode back to pure red
+    assert_eq!(hash.len(), 28);
+    assert!(hash.starts_with('L'), "{}", hash);
+    let pixel = blurhash::decode(&hash, 1, 1, 1.0).unwrap();
+    assert!(pixel[0] >= 252 && pixel[1] <= 3 && pixel[2] <= 3, "{:?}", pixel);
+
+    // Portrait images get 3x4 components
+    let portrait = decode(&png(10, 30, |_, y| [y as u8 * 8, 0, 0, 255])).unwrap();
+    let gradient = blurhash(&portrait);
+    assert_eq!(gradient.len(), 28);
+    assert!(gradient.starts_with('T'), "{}", gradient);
+    assert_ne!(gradient[6..], hash[6..]);
+}
+
+#[test]
+fn test_jpeg_preview() {
+    // Left half light grey, right half dark grey
+    let image = jpeg(64, 16, |x, _| if x < 32 { [200; 3] } else { [50; 3] });
+    let preview = decode(&image).unwrap();
+    assert_eq!((preview.width, preview.height), (64, 16));
+    assert_eq!((preview.grid_width, preview.grid_height), (32, 8));
+    assert!(preview.pixels[0].iter().all(|v| v.abs_diff(200) <= 3), "{:?}", preview.pixels[0]);
+    assert!(preview.pixels[31].iter().all(|v| v.abs_diff(50) <= 3), "{:?}", preview.pixels[31]);
+
+    let red = jpeg(32, 32, |_, _| [255, 0, 0]);
+    let preview = decode(&red).unwrap();
+    assert!(near(&dominant_color(&preview), [255, 0, 0]), "{}", dominant_color(&preview));
+
+    let p = placeholder(&red).unwrap();
+    assert_eq!((p.width, p.height), (32, 32));
+    assert!(near(&p.dominant_color, [255, 0, 0]));
+    assert_eq!(p.blurhash.len(), 28);
+}
+
+#[test]
+fn test_unsupported_images() {
+    let truncated = png(10, 10, |_, _| [0, 0, 0, 255]);
+    assert!(decode(&truncated[..truncated.len() - 20]).is_none());
+    let truncated = jpeg(16, 16, |_, _| [0, 0, 0]);
+    assert!(decode(&truncated[..20]).is_none());
+    assert!(decode(b"GIF89a").is_none());
+    assert!(placeholder(b"").is_none());
+}
+
+#[test]
+fn test_image_urls() {
+    let html = r#"<p><img src="https://img.example.com/a.png?w=1&amp;h=2" alt="a"></p>
+        <img alt="b" src="http://img.example.com/b.jpg"><img src="/relative.png">
+        <img src="https://img.example.com/a.png?w=1&amp;h=2">"#;
+    assert_eq!(
+        image_urls(html),
+        vec![
+            "https://img.example.com/a.png?w=1&h=2",
+            "http://img.example.com/b.jpg"
+        ]
+    );
+}
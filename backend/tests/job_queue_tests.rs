@@ -0,0 +1,71 @@
+// tests/job_queue_tests.rs
+
+use backend::{config::JOB_MAX_ATTEMPTS, jobs::queue};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+
+async fn connect() -> PgPool {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    pool
+}
+
+#[tokio::test]
+async fn test_stale_running_jobs_are_claimed_again() {
+    // Arrange: Two jobs whose worker died an hour ago, due long before anything
+    // else in the queue; one of them was on its last attempt
+    let pool = connect().await;
+    let kind = format!("stale_{}", rand::random::<u32>());
+    let insert = |attempts: i32, run_at: &'static str| {
+        sqlx::query_scalar!(
+            r#"
+            INSERT INTO jobs (kind, status, attempts, run_at, locked_at)
+            VALUES ($1, 'running', $2, $3::TEXT::TIMESTAMPTZ, CURRENT_TIMESTAMP - INTERVAL '1 hour')
+            RETURNING id
+            "#,
+            kind,
+            attempts,
+            run_at
+        )
+        .fetch_one(&pool)
+    };
+    let exhausted = insert(JOB_MAX_ATTEMPTS, "2000-01-01T00:00:00Z").await.unwrap();
+    let retried = insert(1, "2000-01-02T00:00:00Z").await.unwrap();
+
+    // Act
+    let claimed = queue::claim_next(&pool).await.unwrap().expect("a job is due");
+
+    // Assert: The lost job is run again with a fresh lease...
+    assert_eq!(claimed.id, retried);
+    assert_eq!(claimed.attempts, 2);
+    let lease_renewed = sqlx::query_scalar!(
+        r#"SELECT locked_at > CURRENT_TIMESTAMP - INTERVAL '1 minute' as "renewed!" FROM jobs WHERE id = $1"#,
+        retried
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert!(lease_renewed);
+
+    // ...and the one out of attempts is given up on
+    let status = sqlx::query_scalar!("SELECT status FROM jobs WHERE id = $1", exhausted)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "failed");
+
+    sqlx::query!("DELETE FROM jobs WHERE kind = $1", kind)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,30 @@
+// tests/log_filter_tests.rs
+
+use backend::utils::log_filter::{LogFilter, LogFilterError};
+use tracing::Level;
+use tracing_subscriber::{EnvFilter, Registry, layer::SubscriberExt, reload};
+
+#[test]
+fn test_reload_changes_enabled_levels() {
+    // Arrange: A subscriber whose filter starts at warn
+    let (layer, handle) = reload::Layer::new(EnvFilter::new("warn"));
+    let _guard = tracing::subscriber::set_default(Registry::default().with(layer));
+    let log_filter = LogFilter::new(handle, "warn");
+    assert!(!tracing::enabled!(Level::DEBUG));
+
+    // Act
+    log_filter.set("debug").unwrap();
+
+    // Assert
+    assert!(tracing::enabled!(Level::DEBUG));
+    assert_eq!(log_filter.current(), "debug");
+}
+
+#[test]
+fn test_invalid_and_detached_filters_are_rejected() {
+    let detached = LogFilter::detached("info");
+
+    assert!(matches!(detached.set("backend=loud"), Err(LogFilterError::Invalid(_))));
+    assert!(matches!(detached.set("debug"), Err(LogFilterError::Detached)));
+    assert_eq!(detached.current(), "info", "Failed changes keep the old filter");
+}
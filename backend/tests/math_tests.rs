@@ -0,0 +1,51 @@
+// tests/math_tests.rs
+
+use backend::utils::math::render;
+
+#[test]
+fn test_render_plain_text_is_escaped() {
+    assert_eq!(render("Tang"), "Tang");
+    assert_eq!(
+        render("<img src=x onerror=alert(1)> & \"q\""),
+        "&lt;img src=x onerror=alert(1)&gt; &amp; &quot;q&quot;"
+    );
+    // Escaped and unmatched dollars stay as text
+    assert_eq!(render("costs \\$5"), "costs $5");
+    assert_eq!(render("$5 only"), "$5 only");
+}
+
+#[test]
+fn test_render_math() {
+    assert_eq!(
+        render("$\\frac{1}{2}$ bay"),
+        "<math><mfrac><mrow><mn>1</mn></mrow><mrow><mn>2</mn></mrow></mfrac></math> bay"
+    );
+    assert_eq!(
+        render("$3 \\times 4$"),
+        "<math><mn>3</mn><mo>×</mo><mn>4</mn></math>"
+    );
+    assert_eq!(
+        render("$x^2$"),
+        "<math><msup><mi>x</mi><mn>2</mn></msup></math>"
+    );
+    assert_eq!(
+        render("$\\sqrt{2}$"),
+        "<math><msqrt><mrow><mn>2</mn></mrow></msqrt></math>"
+    );
+}
+
+#[test]
+fn test_render_math_never_emits_markup_from_input() {
+    // Unknown commands and stray markup inside math are shown as text
+    assert_eq!(
+        render("$\\script <b>$"),
+        "<math><mtext>\\script</mtext><mo>&lt;</mo><mi>b</mi><mo>&gt;</mo></math>"
+    );
+    assert_eq!(
+        render("$\\text{</math><script>}$"),
+        "<math><mtext>&lt;/math&gt;&lt;script&gt;</mtext></math>"
+    );
+    // Deep nesting and unbalanced braces still terminate
+    let deep = format!("${}$", "{".repeat(100));
+    assert!(render(&deep).starts_with("<math>"));
+}
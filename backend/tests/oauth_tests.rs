@@ -0,0 +1,348 @@
+// tests/oauth_tests.rs
+//
+// Sign-in with a GitHub-style provider played by a local server: any code
+// `c<n>` is accepted and belongs to the provider account with ID `n`, named
+// `octo<n>`. Codes `x<n>` name the account `Oc-tö<n>` instead.
+
+use std::collections::HashMap;
+
+use axum::{Form, Json, Router, http::HeaderMap, routing::{get, post}};
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, OAuthProviderConfig,
+        QuotaConfig, RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    state::AppState,
+    utils::totp,
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+
+/// Starts the fake provider and returns its base URL.
+async fn spawn_provider() -> String {
+    let app = Router::new()
+        .route(
+            "/token",
+            post(|Form(form): Form<HashMap<String, String>>| async move {
+                match form.get("code").filter(|c| c.starts_with(['c', 'x'])) {
+                    Some(code) if form.get("client_secret").map(String::as_str) == Some("secret") => {
+                        Json(serde_json::json!({ "access_token": format!("t{}", code) }))
+                    }
+                    _ => Json(serde_json::json!({ "error": "bad_verification_code" })),
+                }
+            }),
+        )
+        .route(
+            "/user",
+            get(|headers: HeaderMap| async move {
+                let code = headers["authorization"].to_str().unwrap().trim_start_matches("Bearer t");
+                let n: i64 = code[1..].parse().unwrap();
+                let login = match &code[..1] {
+                    "c" => format!("octo{}", n),
+                    _ => format!("Oc-tö{}", n),
+                };
+                Json(serde_json::json!({ "id": n, "login": login }))
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port());
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    address
+}
+
+async fn spawn_app() -> (String, PgPool) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    let provider = spawn_provider().await;
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: "oauth_test_secret".to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig {
+            github: Some(OAuthProviderConfig {
+                client_id: "client".to_string(),
+                client_secret: "secret".to_string(),
+                redirect_uri: "http://localhost:5173/oauth/github".to_string(),
+                authorize_url: Some(format!("{}/authorize", provider)),
+                token_url: Some(format!("{}/token", provider)),
+                userinfo_url: Some(format!("{}/user", provider)),
+            }),
+            ..OAuthConfig::default()
+        },
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies: Vec::new(),
+    };
+
+    let app = routes::create_router(AppState::new(pool.clone(), config));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (address, pool)
+}
+
+/// Asks for an authorize URL (as `token`'s user, to link) and returns its `state`.
+async fn start(client: &reqwest::Client, address: &str, token: Option<&str>) -> String {
+    let request = match token {
+        None => client.get(format!("{}/api/auth/oauth/github/authorize", address)),
+        Some(token) => client
+            .post(format!("{}/api/auth/oauth/github/link", address))
+            .header("Authorization", format!("Bearer {}", token)),
+    };
+    let body: serde_json::Value = request.send().await.unwrap().json().await.unwrap();
+    let url = url::Url::parse(body["authorize_url"].as_str().unwrap()).unwrap();
+    let params: HashMap<_, _> = url.query_pairs().into_owned().collect();
+    assert_eq!(params["client_id"], "client");
+    assert_eq!(params["redirect_uri"], "http://localhost:5173/oauth/github");
+    params["state"].clone()
+}
+
+async fn callback(
+    client: &reqwest::Client,
+    address: &str,
+    code: &str,
+    state: &str,
+) -> reqwest::Response {
+    callback_with_totp(client, address, code, state, None).await
+}
+
+async fn callback_with_totp(
+    client: &reqwest::Client,
+    address: &str,
+    code: &str,
+    state: &str,
+    totp_code: Option<String>,
+) -> reqwest::Response {
+    client
+        .post(format!("{}/api/auth/oauth/github/callback", address))
+        .json(&serde_json::json!({ "code": code, "state": state, "totp_code": totp_code }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn me(client: &reqwest::Client, address: &str, token: &str) -> serde_json::Value {
+    client
+        .get(format!("{}/api/profile/me", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_oauth_sign_in_and_linking() {
+    // Arrange: Provider account IDs nobody has used yet
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let n = rand::random::<u32>() as i64;
+    let (new_account, linked_account) = (format!("c{}", n), format!("c{}", n + 1));
+
+    // Act 1: Sign in with an unknown account
+    let state = start(&client, &address, None).await;
+    let res = callback(&client, &address, &new_account, &state).await;
+    assert_eq!(res.status().as_u16(), 200);
+    let body: serde_json::Value = res.json().await.unwrap();
+
+    // Assert 1: A user named after the account was created and signed in
+    assert_eq!(body["created"], true);
+    let first = me(&client, &address, body["token"].as_str().unwrap()).await;
+    assert!(first["username"].as_str().unwrap().starts_with(&format!("octo{}", n)));
+
+    // Act & Assert 2: States are single-use; the next sign-in finds the same user
+    assert_eq!(callback(&client, &address, &new_account, &state).await.status().as_u16(), 400);
+    let state = start(&client, &address, None).await;
+    let body: serde_json::Value =
+        callback(&client, &address, &new_account, &state).await.json().await.unwrap();
+    assert_eq!(body["created"], false);
+    assert_eq!(me(&client, &address, body["token"].as_str().unwrap()).await["id"], first["id"]);
+
+    // Act 3: A password user links another account, then signs in with it
+    let username = format!("oa_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let password_token = login["token"].as_str().unwrap().to_string();
+
+    let state = start(&client, &address, Some(&password_token)).await;
+    let linked: serde_json::Value =
+        callback(&client, &address, &linked_account, &state).await.json().await.unwrap();
+    assert_eq!(linked["linked"], "github");
+
+    let state = start(&client, &address, None).await;
+    let body: serde_json::Value =
+        callback(&client, &address, &linked_account, &state).await.json().await.unwrap();
+
+    // Assert 3
+    assert_eq!(body["created"], false);
+    assert_eq!(me(&client, &address, body["token"].as_str().unwrap()).await["username"], username);
+
+    // Act & Assert 4: An account linked to someone else cannot be linked again
+    let state = start(&client, &address, Some(&password_token)).await;
+    assert_eq!(callback(&client, &address, &new_account, &state).await.status().as_u16(), 409);
+
+    // Act & Assert 5: Codes the provider rejects, and providers that are not set up
+    let state = start(&client, &address, None).await;
+    assert_eq!(callback(&client, &address, "bogus", &state).await.status().as_u16(), 401);
+    let google = client
+        .get(format!("{}/api/auth/oauth/google/authorize", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(google.status().as_u16(), 404);
+
+    let leftover: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM oauth_identities WHERE user_id = $1")
+        .bind(first["id"].as_i64().unwrap())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(leftover, 1);
+}
+
+#[tokio::test]
+async fn test_oauth_sign_in_needs_two_factor_code() {
+    // Arrange: A password user with two-factor authentication and a linked account
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let account = format!("c{}", rand::random::<u32>());
+    let username = format!("oa2_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap().to_string();
+
+    let state = start(&client, &address, Some(&token)).await;
+    assert_eq!(callback(&client, &address, &account, &state).await.status().as_u16(), 200);
+
+    client
+        .post(format!("{}/api/auth/2fa/enroll", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+    let stored = sqlx::query_scalar!("SELECT totp_secret FROM users WHERE username = $1", username)
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+        .unwrap();
+    let secret = totp::decrypt_secret("oauth_test_secret", &stored).unwrap();
+    let step = totp::step_at(chrono::Utc::now().timestamp());
+    let res = client
+        .post(format!("{}/api/auth/2fa/verify", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&serde_json::json!({ "code": totp::code_at(&secret, step) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+
+    // Act & Assert: Signing in with the provider alone is refused
+    let state = start(&client, &address, None).await;
+    let res = callback(&client, &address, &account, &state).await;
+    assert_eq!(res.status().as_u16(), 401);
+
+    // With a code it works, and the token counts as two-factor
+    let state = start(&client, &address, None).await;
+    let res = callback_with_totp(
+        &client,
+        &address,
+        &account,
+        &state,
+        Some(totp::code_at(&secret, step + 1)),
+    )
+    .await;
+    assert_eq!(res.status().as_u16(), 200);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["mfa"], true);
+    assert_eq!(me(&client, &address, body["token"].as_str().unwrap()).await["username"], username);
+}
+
+#[tokio::test]
+async fn test_oauth_usernames_follow_registration_rules() {
+    // Arrange: Someone registered the name the account's name turns into
+    let (address, _pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let n = rand::random::<u32>();
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": format!("oc_t{}", n), "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+
+    // Act: Sign in with the account named `Oc-tö<n>`
+    let state = start(&client, &address, None).await;
+    let res = callback(&client, &address, &format!("x{}", n), &state).await;
+    assert_eq!(res.status().as_u16(), 200);
+    let body: serde_json::Value = res.json().await.unwrap();
+
+    // Assert: Only allowed characters, and no look-alike of the taken name
+    assert_eq!(body["created"], true);
+    let user = me(&client, &address, body["token"].as_str().unwrap()).await;
+    let username = user["username"].as_str().unwrap();
+    assert!(username.starts_with(&format!("Oc_t{}_", n)), "{}", username);
+    assert!(username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+}
@@ -0,0 +1,93 @@
+// tests/panic_tests.rs
+
+use std::time::Duration;
+
+use axum::{Json, Router, body::Body, extract::Path, http::Request, routing::get, routing::post};
+use backend::utils::error_report::{ErrorReporter, SentryDsn, install_panic_hook};
+use tokio::sync::mpsc;
+use tower::ServiceExt;
+use tower_http::catch_panic::CatchPanicLayer;
+
+#[test]
+fn test_sentry_dsn_parsing() {
+    assert_eq!(
+        SentryDsn::parse("https://abc123@o1.ingest.sentry.io/42"),
+        Some(SentryDsn {
+            store_url: "https://o1.ingest.sentry.io/api/42/store/".to_string(),
+            public_key: "abc123".to_string(),
+        })
+    );
+    assert_eq!(
+        SentryDsn::parse("http://key@127.0.0.1:9000/7").unwrap().store_url,
+        "http://127.0.0.1:9000/api/7/store/"
+    );
+    assert_eq!(SentryDsn::parse("https://sentry.io/42"), None, "No key");
+    assert_eq!(SentryDsn::parse("not a dsn"), None);
+}
+
+#[tokio::test]
+async fn test_panic_becomes_json_500_and_is_reported() {
+    // Arrange: A fake Sentry that forwards received events
+    install_panic_hook();
+    let (tx, mut rx) = mpsc::unbounded_channel::<(String, serde_json::Value)>();
+    let sentry = Router::new().route(
+        "/api/42/store/",
+        post(move |headers: axum::http::HeaderMap, Json(event): Json<serde_json::Value>| {
+            let auth = headers["x-sentry-auth"].to_str().unwrap().to_string();
+            tx.send((auth, event)).unwrap();
+            async { "{}" }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        axum::serve(listener, sentry).await.unwrap();
+    });
+
+    let reporter = ErrorReporter::new(Some(&format!("http://pubkey@127.0.0.1:{}/42", port)));
+    let app = Router::new()
+        .route(
+            "/boom/{id}",
+            get(|Path(id): Path<String>| async move {
+                id.parse::<i64>().expect("user id is not a number").to_string()
+            }),
+        )
+        .layer(CatchPanicLayer::custom(move |panic| reporter.handle_panic(panic)));
+
+    // Act
+    let res = app
+        .oneshot(Request::get("/boom/abc").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    // Assert: A clean JSON 500
+    assert_eq!(res.status(), 500);
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"], "Internal Server Error");
+
+    // Assert: The event carries the message and a stack trace
+    let (auth, event) = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("Panic was not reported")
+        .unwrap();
+    assert!(auth.contains("sentry_key=pubkey"));
+    assert_eq!(event["level"], "fatal");
+    let exception = &event["exception"]["values"][0];
+    assert!(
+        exception["value"]
+            .as_str()
+            .unwrap()
+            .starts_with("user id is not a number")
+    );
+    let frames = exception["stacktrace"]["frames"].as_array().unwrap();
+    assert!(!frames.is_empty());
+    assert!(
+        event["extra"]["location"]
+            .as_str()
+            .unwrap()
+            .contains("panic_tests.rs"),
+        "{}",
+        event["extra"]["location"]
+    );
+}
@@ -1,9 +1,23 @@
 // tests/profile_tests.rs
 
-use backend::{config::Config, routes, state::AppState};
-use sqlx::postgres::PgPoolOptions;
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig,
+        RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    services::{experiments, paper_snapshots},
+    state::AppState,
+};
+use chrono::Datelike;
+use sqlx::{PgPool, postgres::PgPoolOptions};
+use std::net::SocketAddr;
 
 async fn spawn_app() -> String {
+    spawn_app_with(RegistrationConfig::default(), QuotaConfig::default()).await
+}
+
+async fn spawn_app_with(registration: RegistrationConfig, quotas: QuotaConfig) -> String {
     let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
 
     let pool = PgPoolOptions::new()
@@ -24,21 +38,53 @@ async fn spawn_app() -> String {
         rust_log: "error".to_string(),
         admin_username: None,
         admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration,
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas,
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        // As behind nginx, with an inner proxy hop on the private network
+        trusted_proxies: vec!["127.0.0.1/32".parse().unwrap(), "10.0.0.0/8".parse().unwrap()],
     };
 
-    let state = AppState { pool, config };
+    let state = AppState::new(pool, config);
     let app = routes::create_router(state);
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
     let port = listener.local_addr().unwrap().port();
     let address = format!("http://127.0.0.1:{}", port);
 
     tokio::spawn(async move {
-        axum::serve(listener, app).await.unwrap();
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
     });
 
     address
 }
 
+/// Freezes a practice paper of `question_ids` and signs its token, as
+/// `GET /api/quiz/generate` does for a random paper.
+async fn practice_paper(pool: &PgPool, question_ids: &[i64]) -> String {
+    let mut conn = pool.acquire().await.unwrap();
+    let (snapshot_id, _) = paper_snapshots::take(&mut conn, question_ids).await.unwrap();
+    paper_snapshots::sign(snapshot_id, "profile_test_secret").unwrap()
+}
+
 #[tokio::test]
 async fn test_profile_complex_flow() {
     // Arrange
@@ -58,7 +104,7 @@ async fn test_profile_complex_flow() {
 
     for u in &[&user_a, &user_b] {
         client
-            .post(&format!("{}/api/auth/register", address))
+            .post(format!("{}/api/auth/register", address))
             .json(&serde_json::json!({"username": u, "password": password}))
             .send()
             .await
@@ -72,7 +118,7 @@ async fn test_profile_complex_flow() {
 
     // Login A
     let login_a = client
-        .post(&format!("{}/api/auth/login", address))
+        .post(format!("{}/api/auth/login", address))
         .json(&serde_json::json!({"username": user_a, "password": password}))
         .send()
         .await
@@ -84,7 +130,7 @@ async fn test_profile_complex_flow() {
 
     // Login B
     let login_b = client
-        .post(&format!("{}/api/auth/login", address))
+        .post(format!("{}/api/auth/login", address))
         .json(&serde_json::json!({"username": user_b, "password": password}))
         .send()
         .await
@@ -97,7 +143,7 @@ async fn test_profile_complex_flow() {
     // 2. User A creates 2 posts
     for i in 1..=2 {
         client
-            .post(&format!("{}/api/posts", address))
+            .post(format!("{}/api/posts", address))
             .header("Authorization", format!("Bearer {}", token_a))
             .json(&serde_json::json!({"title": format!("A Post {}", i), "content": "Content"}))
             .send()
@@ -107,7 +153,7 @@ async fn test_profile_complex_flow() {
 
     // 3. User B likes A's first post and favorites A's second post
     let posts_a: Vec<serde_json::Value> = client
-        .get(&format!("{}/api/posts", address))
+        .get(format!("{}/api/posts", address))
         .send()
         .await
         .unwrap()
@@ -124,7 +170,7 @@ async fn test_profile_complex_flow() {
 
     // B likes A1
     client
-        .post(&format!("{}/api/posts/{}/like", address, post_a1_id))
+        .post(format!("{}/api/posts/{}/like", address, post_a1_id))
         .header("Authorization", format!("Bearer {}", token_b))
         .send()
         .await
@@ -132,7 +178,7 @@ async fn test_profile_complex_flow() {
 
     // B favorites A2
     client
-        .post(&format!("{}/api/posts/{}/favorite", address, post_a2_id))
+        .post(format!("{}/api/posts/{}/favorite", address, post_a2_id))
         .header("Authorization", format!("Bearer {}", token_b))
         .send()
         .await
@@ -140,7 +186,7 @@ async fn test_profile_complex_flow() {
 
     // 4. Test /api/profile/me for User A
     let me_a = client
-        .get(&format!("{}/api/profile/me", address))
+        .get(format!("{}/api/profile/me", address))
         .header("Authorization", format!("Bearer {}", token_a))
         .send()
         .await
@@ -153,9 +199,25 @@ async fn test_profile_complex_flow() {
     assert_eq!(me_a["posts_count"], 2);
     assert_eq!(me_a["total_likes_received"], 1);
 
+    // Experiment assignments are deterministic and their exposure is logged
+    let user_a_id = me_a["id"].as_i64().unwrap();
+    let expected = experiments::assignments(user_a_id);
+    assert_eq!(me_a["experiments"], serde_json::json!(expected));
+    let exposures = sqlx::query!(
+        "SELECT experiment_key, variant FROM experiment_exposures WHERE user_id = $1",
+        user_a_id
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(exposures.len(), experiments::EXPERIMENTS.len());
+    for row in exposures {
+        assert_eq!(expected[&row.experiment_key], row.variant);
+    }
+
     // 5. Test /api/profile/favorites for User B
     let favs_b = client
-        .get(&format!("{}/api/profile/favorites", address))
+        .get(format!("{}/api/profile/favorites", address))
         .header("Authorization", format!("Bearer {}", token_b))
         .send()
         .await
@@ -171,14 +233,14 @@ async fn test_profile_complex_flow() {
     // 6. Test /api/profile/posts for User A (Check interaction status)
     // A likes A1 (Self-like)
     client
-        .post(&format!("{}/api/posts/{}/like", address, post_a1_id))
+        .post(format!("{}/api/posts/{}/like", address, post_a1_id))
         .header("Authorization", format!("Bearer {}", token_a))
         .send()
         .await
         .unwrap();
 
     let my_posts_a = client
-        .get(&format!("{}/api/profile/posts", address))
+        .get(format!("{}/api/profile/posts", address))
         .header("Authorization", format!("Bearer {}", token_a))
         .send()
         .await
@@ -206,7 +268,7 @@ async fn test_profile_complex_flow() {
         }
     });
     client
-        .post(&format!("{}/api/contributions", address))
+        .post(format!("{}/api/contributions", address))
         .header("Authorization", format!("Bearer {}", token_a))
         .json(&contrib_payload)
         .send()
@@ -214,7 +276,7 @@ async fn test_profile_complex_flow() {
         .unwrap();
 
     let my_contribs_a = client
-        .get(&format!("{}/api/profile/contributions", address))
+        .get(format!("{}/api/profile/contributions", address))
         .header("Authorization", format!("Bearer {}", token_a))
         .send()
         .await
@@ -226,3 +288,1577 @@ async fn test_profile_complex_flow() {
     assert_eq!(my_contribs_a.len(), 1);
     assert_eq!(my_contribs_a[0]["type"], "question");
 }
+
+#[test]
+fn test_experiment_assignment_is_stable_and_split() {
+    let experiment = &experiments::EXPERIMENTS[0];
+
+    assert_eq!(experiments::assign(experiment, 42), experiments::assign(experiment, 42));
+
+    // Every variant gets a reasonable share of users
+    for variant in experiment.variants {
+        let share = (1..=1000)
+            .filter(|id| experiments::assign(experiment, *id) == *variant)
+            .count();
+        assert!(share > 1000 / experiment.variants.len() / 2, "{} got {}", variant, share);
+    }
+}
+
+#[tokio::test]
+async fn test_quiz_stats() {
+    // Arrange: A user and two fresh questions only they answer
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .unwrap();
+
+    let username = format!("qs_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let creds = serde_json::json!({"username": username, "password": "password123"});
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap();
+
+    let tag = uuid::Uuid::new_v4();
+    let single_id = sqlx::query_scalar!(
+        r#"INSERT INTO questions (type, content, options, answer) VALUES ('single', $1, '["A","B"]', 'A') RETURNING id"#,
+        format!("Stats single {}", tag)
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let multiple_id = sqlx::query_scalar!(
+        r#"INSERT INTO questions (type, content, options, answer) VALUES ('multiple', $1, '["A","B"]', 'AB') RETURNING id"#,
+        format!("Stats multiple {}", tag)
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    // Nothing yet
+    let empty: serde_json::Value = client
+        .get(format!("{}/api/profile/quiz-stats", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(empty["attempts"], 0);
+    assert!(empty["percentile"].is_null());
+
+    // Act: Two papers, single always right, multiple always wrong
+    for _ in 0..2 {
+        client
+            .post(format!("{}/api/quiz/submit", address))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&serde_json::json!({
+                "exam_token": practice_paper(&pool, &[single_id, multiple_id]).await,
+                "answers": { single_id.to_string(): "A", multiple_id.to_string(): "A" }
+            }))
+            .send()
+            .await
+            .unwrap();
+    }
+    let stats: serde_json::Value = client
+        .get(format!("{}/api/profile/quiz-stats", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(stats["attempts"], 2);
+    assert_eq!(stats["questions_answered"], 4);
+    assert_eq!(stats["correct_answers"], 2);
+    assert_eq!(stats["accuracy"], 50.0);
+    assert_eq!(stats["best_score"], 10);
+    assert!(stats["percentile"].as_f64().is_some());
+    assert_eq!(stats["current_streak_days"], 1);
+    assert_eq!(stats["longest_streak_days"], 1);
+    assert_eq!(
+        stats["by_type"],
+        serde_json::json!([
+            {"label": "multiple", "answered": 2, "correct": 0, "accuracy": 0.0},
+            {"label": "single", "answered": 2, "correct": 2, "accuracy": 100.0}
+        ])
+    );
+    assert_eq!(stats["by_difficulty"][0]["label"], "easy");
+    assert_eq!(stats["by_difficulty"][1]["label"], "hard");
+}
+
+#[tokio::test]
+async fn test_attempt_paper_is_frozen() {
+    // Arrange: A paper with a fresh question, which is edited before it is submitted
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .unwrap();
+
+    let mut tokens = Vec::new();
+    for _ in 0..2 {
+        let username = format!("snap_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let creds = serde_json::json!({"username": username, "password": "password123"});
+        client
+            .post(format!("{}/api/auth/register", address))
+            .json(&creds)
+            .send()
+            .await
+            .unwrap();
+        let login: serde_json::Value = client
+            .post(format!("{}/api/auth/login", address))
+            .json(&creds)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        tokens.push(login["token"].as_str().unwrap().to_string());
+    }
+
+    let content = format!("Snapshot question {}", uuid::Uuid::new_v4());
+    let question_id = sqlx::query_scalar!(
+        r#"INSERT INTO questions (type, content, options, answer) VALUES ('single', $1, '["A","B"]', 'A') RETURNING id"#,
+        content
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let paper_token = practice_paper(&pool, &[question_id]).await;
+
+    sqlx::query!(
+        r#"UPDATE questions SET content = 'Rewritten', options = '["X","Y","Z"]', answer = 'B' WHERE id = $1"#,
+        question_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Act 1: Scored against the paper as served
+    let submit = |exam_token: String, answers: serde_json::Value| {
+        client
+            .post(format!("{}/api/quiz/submit", address))
+            .header("Authorization", format!("Bearer {}", tokens[0]))
+            .json(&serde_json::json!({"exam_token": exam_token, "answers": answers}))
+            .send()
+    };
+    let answers = serde_json::json!({ question_id.to_string(): "A" });
+    let submitted: serde_json::Value = submit(paper_token.clone(), answers.clone())
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(submitted["score"], 10);
+    let attempt_id = sqlx::query_scalar!(
+        "SELECT attempt_id FROM quiz_answers WHERE question_id = $1",
+        question_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    // A paper is submitted once, only with its own questions and a valid token
+    let again = submit(paper_token.clone(), answers.clone()).await.unwrap();
+    assert_eq!(again.status(), 409);
+    let other = practice_paper(&pool, &[question_id]).await;
+    let foreign = submit(other, serde_json::json!({ (question_id + 1).to_string(): "A" }))
+        .await
+        .unwrap();
+    assert_eq!(foreign.status(), 400);
+    let forged = submit(String::new(), answers).await.unwrap();
+    assert_eq!(forged.status(), 400);
+
+    // Act 2
+    let res = client
+        .get(format!("{}/api/quiz/attempts/{}/paper", address, attempt_id))
+        .header("Authorization", format!("Bearer {}", tokens[0]))
+        .send()
+        .await
+        .unwrap();
+
+    // Assert: The paper as answered, still correct, without the answer key
+    assert_eq!(res.status(), 200);
+    let paper: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(paper["score"], 10);
+    let question = &paper["questions"][0];
+    assert_eq!(question["id"], question_id);
+    assert_eq!(question["content"], content);
+    assert_eq!(question["options"], serde_json::json!(["A", "B"]));
+    assert_eq!(question["user_answer"], "A");
+    assert_eq!(question["is_correct"], true);
+    assert!(question.get("answer").is_none());
+
+    // Other users do not see it
+    let res = client
+        .get(format!("{}/api/quiz/attempts/{}/paper", address, attempt_id))
+        .header("Authorization", format!("Bearer {}", tokens[1]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn test_registration_age_and_region_requirements() {
+    // Arrange
+    let address = spawn_app_with(RegistrationConfig {
+        min_age: Some(14),
+        regions: vec!["CN".to_string(), "HK".to_string()],
+        require_email: false,
+    }, QuotaConfig::default())
+    .await;
+    let client = reqwest::Client::new();
+    let username = format!("gate_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let this_year = chrono::Utc::now().year();
+
+    // Act 1: Missing details
+    let res = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["fields"]["birth_year"][0]["code"], "required");
+    assert_eq!(body["fields"]["region"][0]["code"], "required");
+
+    // Act 2: Too young, unsupported region
+    let body: serde_json::Value = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({
+            "username": username,
+            "password": "password123",
+            "birth_year": this_year - 10,
+            "region": "US"
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["fields"]["birth_year"][0]["code"], "min_age");
+    assert_eq!(body["fields"]["birth_year"][0]["params"]["min_age"], 14);
+    assert_eq!(body["fields"]["region"][0]["code"], "region");
+
+    // Act 3: Meets the requirements
+    let res = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({
+            "username": username,
+            "password": "password123",
+            "birth_year": this_year - 30,
+            "region": "HK"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+
+    // Assert: Stored on the profile
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let me: serde_json::Value = client
+        .get(format!("{}/api/profile/me", address))
+        .header("Authorization", format!("Bearer {}", login["token"].as_str().unwrap()))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["birth_year"], this_year - 30);
+    assert_eq!(me["region"], "HK");
+}
+
+/// The token in the newest verification email queued for `email`.
+/// The code after `label` in the newest email queued for `email`.
+async fn emailed_code(pool: &sqlx::PgPool, email: &str, label: &str) -> String {
+    let body: String = sqlx::query_scalar(
+        "SELECT payload->>'body' FROM jobs WHERE kind = 'send_email' AND payload->>'to' = $1 ORDER BY id DESC LIMIT 1",
+    )
+    .bind(email)
+    .fetch_one(pool)
+    .await
+    .expect("No email queued");
+    body.split(label)
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_email_registration_and_verification() {
+    // Arrange
+    let address = spawn_app_with(RegistrationConfig {
+        require_email: true,
+        ..RegistrationConfig::default()
+    }, QuotaConfig::default())
+    .await;
+    let client = reqwest::Client::new();
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&std::env::var("DATABASE_URL").unwrap())
+        .await
+        .unwrap();
+    let suffix = &uuid::Uuid::new_v4().to_string()[..8];
+    let email = format!("mail_{}@example.com", suffix);
+    let register = |username: String, email: Option<String>| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            client
+                .post(format!("{}/api/auth/register", address))
+                .json(&serde_json::json!({
+                    "username": username, "password": "password123", "email": email
+                }))
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    let login = |username: String| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            let body: serde_json::Value = client
+                .post(format!("{}/api/auth/login", address))
+                .json(&serde_json::json!({"username": username, "password": "password123"}))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            body["token"].as_str().unwrap().to_string()
+        }
+    };
+    let me = |token: String| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            client
+                .get(format!("{}/api/profile/me", address))
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await
+                .unwrap()
+                .json::<serde_json::Value>()
+                .await
+                .unwrap()
+        }
+    };
+
+    // Act & Assert 1: The address is required and must be valid
+    let missing = register(format!("em_{}", suffix), None).await;
+    assert_eq!(missing.status().as_u16(), 400);
+    let body: serde_json::Value = missing.json().await.unwrap();
+    assert_eq!(body["fields"]["email"][0]["code"], "required");
+    let invalid = register(format!("em_{}", suffix), Some("not-an-email".to_string())).await;
+    assert_eq!(invalid.status().as_u16(), 400);
+
+    // Act 2: Register, then follow the emailed token
+    let first = format!("em_{}", suffix);
+    assert_eq!(register(first.clone(), Some(email.clone())).await.status().as_u16(), 201);
+    let token = login(first.clone()).await;
+    let profile = me(token.clone()).await;
+    assert_eq!(profile["email"], email);
+    assert_eq!(profile["email_verified"], false);
+
+    let code = emailed_code(&pool, &email, "Verification code: ").await;
+    let verify = |code: String| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            client
+                .post(format!("{}/api/auth/verify-email", address))
+                .json(&serde_json::json!({"token": code}))
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    let verified = verify(code.clone()).await;
+    assert_eq!(verified.status().as_u16(), 200);
+
+    // Assert 2: Verified once; the token is single-use
+    assert_eq!(me(token.clone()).await["email_verified"], true);
+    assert_eq!(verify(code).await.status().as_u16(), 400);
+
+    // Act & Assert 3: A verified address cannot be claimed by another account
+    let second = format!("em2_{}", suffix);
+    let taken = register(second.clone(), Some(email.to_uppercase())).await;
+    assert_eq!(taken.status().as_u16(), 409);
+    assert_eq!(
+        register(second.clone(), Some(format!("other_{}", email))).await.status().as_u16(),
+        201
+    );
+    let second_token = login(second).await;
+    let claim = client
+        .put(format!("{}/api/profile/email", address))
+        .header("Authorization", format!("Bearer {}", second_token))
+        .json(&serde_json::json!({"email": email}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(claim.status().as_u16(), 409);
+
+    // Act & Assert 4: Changing the address needs a new verification,
+    // and links sent to the old address stop working
+    let old_code = emailed_code(&pool, &format!("other_{}", email), "Verification code: ").await;
+    let new_email = format!("new_{}", email);
+    let changed: serde_json::Value = client
+        .put(format!("{}/api/profile/email", address))
+        .header("Authorization", format!("Bearer {}", second_token))
+        .json(&serde_json::json!({"email": new_email}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(changed["email_verified"], false);
+    assert_eq!(verify(old_code).await.status().as_u16(), 400);
+    let new_code = emailed_code(&pool, &new_email, "Verification code: ").await;
+    assert_eq!(verify(new_code).await.status().as_u16(), 200);
+    let profile = me(second_token).await;
+    assert_eq!(profile["email"], new_email);
+    assert_eq!(profile["email_verified"], true);
+}
+
+#[tokio::test]
+async fn test_password_reset_by_email() {
+    // Arrange: A user with a verified address
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&std::env::var("DATABASE_URL").unwrap())
+        .await
+        .unwrap();
+    let suffix = &uuid::Uuid::new_v4().to_string()[..8];
+    let username = format!("reset_{}", suffix);
+    let email = format!("reset_{}@example.com", suffix);
+    let resp = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({
+            "username": username, "password": "password123", "email": email
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let code = emailed_code(&pool, &email, "Verification code: ").await;
+    let resp = client
+        .post(format!("{}/api/auth/verify-email", address))
+        .json(&serde_json::json!({"token": code}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let forgot = |email: String| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            client
+                .post(format!("{}/api/auth/forgot-password", address))
+                .json(&serde_json::json!({"email": email}))
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    let reset = |token: String, password: &'static str| {
+        let client = client.clone();
+        let address = address.clone();
+        async move {
+            client
+                .post(format!("{}/api/auth/reset-password", address))
+                .json(&serde_json::json!({"token": token, "password": password}))
+                .send()
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        }
+    };
+    let login = |password: &'static str| {
+        let client = client.clone();
+        let address = address.clone();
+        let username = username.clone();
+        async move {
+            client
+                .post(format!("{}/api/auth/login", address))
+                .json(&serde_json::json!({"username": username, "password": password}))
+                .send()
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        }
+    };
+
+    // Act & Assert 1: Unknown addresses get the same answer and no email
+    let unknown = format!("nobody_{}@example.com", suffix);
+    assert_eq!(forgot(unknown.clone()).await.status().as_u16(), 202);
+    let queued: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM jobs WHERE kind = 'send_email' AND payload->>'to' = $1",
+    )
+    .bind(&unknown)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(queued, 0);
+
+    let signed_in: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let old_token = signed_in["token"].as_str().unwrap().to_string();
+
+    // Act 2: Ask for a reset (in any letter case) and use the emailed code
+    assert_eq!(forgot(email.to_uppercase()).await.status().as_u16(), 202);
+    let code = emailed_code(&pool, &email, "Reset code: ").await;
+    assert_eq!(reset("not-a-token".to_string(), "newpass456").await, 400);
+    assert_eq!(reset(code.clone(), "newpass456").await, 204);
+
+    // Assert 2: Tokens from before the reset are revoked
+    let me = client
+        .get(format!("{}/api/profile/me", address))
+        .header("Authorization", format!("Bearer {}", old_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(me.status().as_u16(), 401);
+
+    // Only the new password works, and the code is single-use
+    assert_eq!(login("newpass456").await, 200);
+    assert_eq!(login("password123").await, 401);
+    assert_eq!(reset(code, "another789").await, 400);
+    let changed: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM security_events e JOIN users u ON u.id = e.user_id WHERE u.username = $1 AND e.kind = 'password_changed'",
+    )
+    .bind(&username)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(changed, 1);
+}
+
+#[tokio::test]
+async fn test_quotas_limit_posts_images_and_favorites() {
+    // Arrange: Small quotas, and a verified user
+    let address = spawn_app_with(
+        RegistrationConfig::default(),
+        QuotaConfig {
+            posts_per_day: Some(3),
+            images_per_user: Some(2),
+            favorites_per_user: Some(1),
+        },
+    )
+    .await;
+    let client = reqwest::Client::new();
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&std::env::var("DATABASE_URL").unwrap())
+        .await
+        .unwrap();
+    let username = format!("quota_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE users SET is_verified = TRUE WHERE username = $1", username)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap().to_string();
+
+    let post = |content: &'static str| {
+        let client = client.clone();
+        let address = address.clone();
+        let token = token.clone();
+        async move {
+            client
+                .post(format!("{}/api/posts", address))
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&serde_json::json!({"title": "Quota", "content": content}))
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    let favorite = |post_id: i64| {
+        let client = client.clone();
+        let address = address.clone();
+        let token = token.clone();
+        async move {
+            client
+                .post(format!("{}/api/posts/{}/favorite", address, post_id))
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await
+                .unwrap()
+                .status()
+                .as_u16()
+        }
+    };
+    let limits = || {
+        let client = client.clone();
+        let address = address.clone();
+        let token = token.clone();
+        async move {
+            client
+                .get(format!("{}/api/profile/limits", address))
+                .header("Authorization", format!("Bearer {}", token))
+                .send()
+                .await
+                .unwrap()
+                .json::<serde_json::Value>()
+                .await
+                .unwrap()
+        }
+    };
+
+    // Act & Assert 1: Images count across posts
+    let first = post(r#"<p>Two <img src="http://example.com/a.jpg"><img src="http://example.com/b.jpg"></p>"#).await;
+    assert_eq!(first.status().as_u16(), 201);
+    let first_id = first.json::<serde_json::Value>().await.unwrap()["id"].as_i64().unwrap();
+    let over = post(r#"<img src="http://example.com/c.jpg">"#).await;
+    assert_eq!(over.status().as_u16(), 429);
+
+    let status = limits().await;
+    assert_eq!(status["posts_per_day"]["used"], 1);
+    assert_eq!(status["posts_per_day"]["limit"], 3);
+    assert_eq!(status["images"]["used"], 2);
+    assert_eq!(status["images"]["limit"], 2);
+
+    // Act & Assert 2: Posts per day, with deleted posts still counted
+    let second = post("Plain text").await;
+    let second_id = second.json::<serde_json::Value>().await.unwrap()["id"].as_i64().unwrap();
+    client
+        .delete(format!("{}/api/posts/{}", address, second_id))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(post("Third").await.status().as_u16(), 201);
+    assert_eq!(post("Fourth").await.status().as_u16(), 429);
+
+    // Act & Assert 3: Favorites, where removing one frees the slot
+    assert_eq!(favorite(first_id).await, 200);
+    assert_eq!(favorite(second_id).await, 429);
+    assert_eq!(favorite(first_id).await, 200);
+    assert_eq!(limits().await["favorites"]["used"], 0);
+}
+
+#[tokio::test]
+async fn test_sessions_list_and_revoke() {
+    // Arrange: One user signed in on a phone and a laptop
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let username = format!("ss_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+
+    let login = |user_agent: &'static str, ip: &'static str| {
+        let request = client
+            .post(format!("{}/api/auth/login", address))
+            .header("User-Agent", user_agent)
+            .header("X-Forwarded-For", ip)
+            .json(&serde_json::json!({"username": username, "password": "password123"}));
+        async move {
+            let body: serde_json::Value = request.send().await.unwrap().json().await.unwrap();
+            body["token"].as_str().unwrap().to_string()
+        }
+    };
+    let phone = login(
+        "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 Version/17.0 Mobile/15E148 Safari/604.1",
+        "203.0.113.7",
+    )
+    .await;
+    let laptop = login(
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
+        "198.51.100.4",
+    )
+    .await;
+
+    let sessions = |token: String| {
+        let request = client
+            .get(format!("{}/api/profile/sessions", address))
+            .header("Authorization", format!("Bearer {}", token));
+        async move { request.send().await.unwrap() }
+    };
+
+    // Act 1
+    let listed: Vec<serde_json::Value> = sessions(laptop.clone()).await.json().await.unwrap();
+
+    // Assert 1: Both devices, the one asking marked as current
+    assert_eq!(listed.len(), 2);
+    let current = listed.iter().find(|s| s["current"] == true).unwrap();
+    assert_eq!(current["device"], "Firefox on Windows");
+    assert_eq!(current["ip"], "198.51.100.4");
+    let other = listed.iter().find(|s| s["current"] == false).unwrap();
+    assert_eq!(other["device"], "Safari on iOS");
+    assert!(other["last_used_at"].is_string() && other["expires_at"].is_string());
+
+    // Act 2: Sign the phone out from the laptop
+    let revoke = |id: i64, token: String| {
+        let request = client
+            .delete(format!("{}/api/profile/sessions/{}", address, id))
+            .header("Authorization", format!("Bearer {}", token));
+        async move { request.send().await.unwrap().status().as_u16() }
+    };
+    let phone_id = other["id"].as_i64().unwrap();
+    assert_eq!(revoke(phone_id, laptop.clone()).await, 204);
+
+    // Assert 2: The phone's token stops working; the laptop's does not
+    assert_eq!(sessions(phone.clone()).await.status().as_u16(), 401);
+    let listed: Vec<serde_json::Value> = sessions(laptop.clone()).await.json().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(revoke(phone_id, laptop.clone()).await, 404);
+
+    // Act & Assert 3: Other users' sessions cannot be revoked, and logging out ends one
+    let stranger = format!("sx_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": stranger, "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+    let stranger_token: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": stranger, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let stranger_token = stranger_token["token"].as_str().unwrap().to_string();
+    let laptop_id = listed[0]["id"].as_i64().unwrap();
+    assert_eq!(revoke(laptop_id, stranger_token.clone()).await, 404);
+
+    let logout = client
+        .post(format!("{}/api/auth/logout", address))
+        .header("Authorization", format!("Bearer {}", stranger_token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(logout.status().as_u16(), 204);
+    let relogin: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": stranger, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let listed: Vec<serde_json::Value> = sessions(relogin["token"].as_str().unwrap().to_string())
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["device"], "Unknown device");
+}
+
+#[tokio::test]
+async fn test_activity_heatmap() {
+    // Arrange: A user with activity spread over the past year and before it
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .unwrap();
+
+    let username = format!("hm_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let creds = serde_json::json!({"username": username, "password": "password123"});
+    let user: serde_json::Value = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let user_id = user["id"].as_i64().unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap();
+
+    for days_ago in [2, 2, 400] {
+        sqlx::query("INSERT INTO quiz_attempts (user_id, score, created_at) VALUES ($1, 50, NOW() - make_interval(days => $2))")
+            .bind(user_id)
+            .bind(days_ago)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    sqlx::query("INSERT INTO contributions (user_id, type, data, created_at) VALUES ($1, 'question', '{}', NOW() - INTERVAL '2 days')")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO posts (user_id, title, content, deleted_at) VALUES ($1, 'Deleted', '...', NOW())")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let heatmap = || async {
+        client
+            .get(format!("{}/api/profile/activity-heatmap", address))
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap()
+    };
+
+    // Act
+    let body = heatmap().await;
+
+    // Assert: One day counted, older and deleted activity left out
+    let today = chrono::Utc::now().date_naive();
+    assert_eq!(body["to"], today.to_string());
+    assert_eq!(body["from"], (today - chrono::Days::new(364)).to_string());
+    assert_eq!(body["total"], 3);
+    let days = body["days"].as_array().unwrap();
+    assert_eq!(days.len(), 1);
+    assert_eq!(days[0]["date"], (today - chrono::Days::new(2)).to_string());
+    assert_eq!(days[0]["contributions"], 1);
+    assert_eq!(days[0]["posts"], 0);
+    assert_eq!(days[0]["quizzes"], 2);
+
+    // Act & Assert 2: Served from the cache until it expires
+    sqlx::query("INSERT INTO quiz_attempts (user_id, score) VALUES ($1, 50)")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let cached = heatmap().await;
+    assert_eq!(cached["total"], 3);
+    assert_eq!(cached["generated_at"], body["generated_at"]);
+}
+
+#[tokio::test]
+async fn test_account_self_deletion() {
+    // Arrange: A user with a post
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .unwrap();
+
+    let username = format!("del_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let creds = serde_json::json!({"username": username, "password": "password123"});
+    let user: serde_json::Value = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let user_id = user["id"].as_i64().unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap();
+    let post_id: i64 = sqlx::query_scalar("INSERT INTO posts (user_id, title, content) VALUES ($1, 'Parting words', '...') RETURNING id")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    let delete_me = |password: &'static str| {
+        let client = client.clone();
+        let url = format!("{}/api/profile/me", address);
+        let token = token.to_string();
+        async move {
+            client
+                .delete(url)
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&serde_json::json!({ "password": password }))
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    let cancel = || async {
+        client
+            .delete(format!("{}/api/profile/me/deletion", address))
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    };
+
+    // Act & Assert 1: The password is required
+    assert_eq!(delete_me("wrong-password").await.status().as_u16(), 401);
+
+    // Act & Assert 2: Scheduled after the grace period; asking again keeps the date
+    let res = delete_me("password123").await;
+    assert_eq!(res.status().as_u16(), 202);
+    let scheduled: serde_json::Value = res.json().await.unwrap();
+    let at: chrono::DateTime<chrono::Utc> =
+        scheduled["deletion_scheduled_at"].as_str().unwrap().parse().unwrap();
+    assert!(at > chrono::Utc::now() + chrono::Duration::days(13));
+    let again: serde_json::Value = delete_me("password123").await.json().await.unwrap();
+    assert_eq!(again, scheduled);
+    let me: serde_json::Value = client
+        .get(format!("{}/api/profile/me", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["deletion_scheduled_at"], scheduled["deletion_scheduled_at"]);
+
+    // Act & Assert 3: Cancelling works once
+    assert_eq!(cancel().await, 204);
+    assert_eq!(cancel().await, 404);
+    backend::jobs::account_deletion::run(pool.clone()).await.unwrap();
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(exists);
+
+    // Act 4: Schedule again and let the grace period run out
+    assert_eq!(delete_me("password123").await.status().as_u16(), 202);
+    sqlx::query("UPDATE users SET deletion_scheduled_at = NOW() - INTERVAL '1 minute' WHERE id = $1")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    backend::jobs::account_deletion::run(pool.clone()).await.unwrap();
+
+    // Assert 4: The account is gone and the post belongs to the ghost user
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(!exists);
+    let owner: String = sqlx::query_scalar("SELECT u.username FROM posts p JOIN users u ON u.id = p.user_id WHERE p.id = $1")
+        .bind(post_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(owner, "ghost");
+}
+
+#[tokio::test]
+async fn test_security_events_list() {
+    // Arrange: Register from a phone, then a wrong password and a sign-in from a laptop
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let username = format!("se_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let phone = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 Version/17.0 Mobile/15E148 Safari/604.1";
+    let laptop = "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0";
+    client
+        .post(format!("{}/api/auth/register", address))
+        .header("User-Agent", phone)
+        .header("X-Forwarded-For", "203.0.113.7, 10.0.0.1")
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+    let login = |password: &'static str| {
+        client
+            .post(format!("{}/api/auth/login", address))
+            .header("User-Agent", laptop)
+            .header("X-Forwarded-For", "198.51.100.4")
+            .json(&serde_json::json!({"username": username, "password": password}))
+            .send()
+    };
+    assert_eq!(login("wrong-password").await.unwrap().status().as_u16(), 401);
+    let body: serde_json::Value = login("password123").await.unwrap().json().await.unwrap();
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let events = |query: &'static [(&'static str, &'static str)]| {
+        client
+            .get(format!("{}/api/profile/security-events", address))
+            .bearer_auth(&token)
+            .query(query)
+            .send()
+    };
+
+    // Act
+    let listed: Vec<serde_json::Value> = events(&[]).await.unwrap().json().await.unwrap();
+
+    // Assert: Newest first, with the forwarded client address and the device
+    let kinds: Vec<&str> = listed.iter().map(|e| e["kind"].as_str().unwrap()).collect();
+    assert_eq!(kinds, vec!["login", "login_failed", "registered"]);
+    assert_eq!(listed[0]["ip"], "198.51.100.4");
+    assert_eq!(listed[0]["device"], "Firefox on Windows");
+    assert_eq!(listed[1]["user_agent"], laptop);
+    assert_eq!(listed[2]["ip"], "203.0.113.7");
+    assert_eq!(listed[2]["device"], "Safari on iOS");
+
+    let page: Vec<serde_json::Value> = events(&[("limit", "1")]).await.unwrap().json().await.unwrap();
+    assert_eq!(page.len(), 1);
+    let res = client
+        .get(format!("{}/api/profile/security-events", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn test_edit_and_view_public_profile() {
+    // Arrange: A verified user with one post
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .unwrap();
+    let username = format!("bio_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+    let user_id = sqlx::query_scalar!(
+        "UPDATE users SET is_verified = TRUE WHERE username = $1 RETURNING id",
+        username
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap().to_string();
+    client
+        .post(format!("{}/api/posts", address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({"title": "Bracket sets", "content": "Content"}))
+        .send()
+        .await
+        .unwrap();
+
+    let update = |body: serde_json::Value| {
+        client
+            .put(format!("{}/api/profile/me", address))
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+    };
+
+    // Act 1: Set the profile fields; blanks are stored as unset
+    let res = update(serde_json::json!({
+        "bio": "  Sketching timber halls in Shanxi. \n",
+        "location": "太原",
+        "website": "https://example.com/halls",
+        "avatar_url": " "
+    }))
+    .await
+    .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+    let saved: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(saved["bio"], "Sketching timber halls in Shanxi.");
+    assert!(saved["avatar_url"].is_null());
+
+    // Assert: Anyone can view the public profile
+    let profile: serde_json::Value = client
+        .get(format!("{}/api/users/{}", address, user_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(profile["username"], username.as_str());
+    assert_eq!(profile["bio"], "Sketching timber halls in Shanxi.");
+    assert_eq!(profile["location"], "太原");
+    assert_eq!(profile["website"], "https://example.com/halls");
+    assert!(profile["avatar_url"].is_null());
+    assert_eq!(profile["posts_count"], 1);
+    assert_eq!(profile["is_verified"], true);
+    assert!(profile["joined_at"].is_string());
+    assert!(profile.get("email").is_none() && profile.get("role").is_none());
+
+    let me: serde_json::Value = client
+        .get(format!("{}/api/profile/me", address))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["location"], "太原");
+
+    // Act 2: Invalid links and overlong fields are rejected
+    for body in [
+        serde_json::json!({"website": "javascript:alert(1)"}),
+        serde_json::json!({"avatar_url": "data:image/png;base64,AAAA"}),
+        serde_json::json!({"website": "https://"}),
+        serde_json::json!({"bio": "字".repeat(501)}),
+        serde_json::json!({"location": "x".repeat(101)}),
+    ] {
+        assert_eq!(update(body).await.unwrap().status().as_u16(), 400);
+    }
+
+    // Omitted fields are cleared
+    update(serde_json::json!({"bio": "Still sketching."}))
+        .await
+        .unwrap();
+    let profile: serde_json::Value = client
+        .get(format!("{}/api/users/{}", address, user_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(profile["bio"], "Still sketching.");
+    assert!(profile["website"].is_null());
+
+    // Unknown users and anonymous edits
+    let missing = client
+        .get(format!("{}/api/users/0", address))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status().as_u16(), 404);
+    let anonymous = client
+        .put(format!("{}/api/profile/me", address))
+        .json(&serde_json::json!({"bio": "Hi"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(anonymous.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn test_user_settings() {
+    // Arrange: A verified user with two posts, the older one liked
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .unwrap();
+    let username = format!("set_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE users SET is_verified = TRUE WHERE username = $1", username)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap().to_string();
+    let tag = &uuid::Uuid::new_v4().to_string()[..8];
+    let mut post_ids = Vec::new();
+    for title in ["older", "newer"] {
+        let created: serde_json::Value = client
+            .post(format!("{}/api/posts", address))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({"title": format!("{} {}", tag, title), "content": "Content"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        post_ids.push(created["id"].as_i64().unwrap());
+    }
+    client
+        .post(format!("{}/api/posts/{}/like", address, post_ids[0]))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+
+    let settings_url = format!("{}/api/profile/settings", address);
+    let first_listed = |token: Option<String>| {
+        let request = client.get(format!("{}/api/posts", address)).query(&[("q", tag)]);
+        let request = match token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        async move {
+            let posts: Vec<serde_json::Value> = request.send().await.unwrap().json().await.unwrap();
+            posts[0]["id"].as_i64().unwrap()
+        }
+    };
+
+    // Act 1: Defaults
+    let defaults: serde_json::Value = client
+        .get(&settings_url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        defaults,
+        serde_json::json!({
+            "language": null,
+            "email_notifications": {"post_comments": false, "broadcasts": false},
+            "default_post_sort": "new"
+        })
+    );
+    assert_eq!(first_listed(Some(token.clone())).await, post_ids[1]);
+
+    // Act 2: Save; the language is canonicalized and shared with the profile
+    let res = client
+        .put(&settings_url)
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "language": "zh-tw",
+            "email_notifications": {"post_comments": true},
+            "default_post_sort": "hot"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status().as_u16(), 200);
+    let saved: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(saved["language"], "zh-TW");
+    assert_eq!(saved["email_notifications"]["broadcasts"], false);
+
+    let me: serde_json::Value = client
+        .get(format!("{}/api/profile/me", address))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(me["locale"], "zh-TW");
+    let stored: serde_json::Value = client
+        .get(&settings_url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stored, saved);
+
+    // Assert: The post list follows the saved order unless the request sets one
+    assert_eq!(first_listed(Some(token.clone())).await, post_ids[0]);
+    assert_eq!(first_listed(None).await, post_ids[1]);
+    let newest: Vec<serde_json::Value> = client
+        .get(format!("{}/api/posts", address))
+        .query(&[("q", tag), ("sort", "new")])
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(newest[0]["id"], post_ids[1]);
+
+    // Act 3: Unknown keys and values are rejected and change nothing
+    for body in [
+        serde_json::json!({"theme": "dark"}),
+        serde_json::json!({"email_notifications": {"digest": true}}),
+        serde_json::json!({"default_post_sort": "top"}),
+        serde_json::json!({"language": "fr"}),
+        serde_json::json!({"email_notifications": {"post_comments": "yes"}}),
+        serde_json::json!(["hot"]),
+    ] {
+        let res = client
+            .put(&settings_url)
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status().as_u16(), 400, "{}", body);
+    }
+    let unchanged: serde_json::Value = client
+        .get(&settings_url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(unchanged, saved);
+
+    // Omitted keys are reset
+    let reset: serde_json::Value = client
+        .put(&settings_url)
+        .bearer_auth(&token)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(reset, defaults);
+
+    let anonymous = client.get(&settings_url).send().await.unwrap();
+    assert_eq!(anonymous.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn test_export_my_data() {
+    // Arrange: A user with a post, a comment, a like and an exam record
+    let address = spawn_app().await;
+    let client = reqwest::Client::new();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .unwrap();
+
+    let username = format!("exp_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let creds = serde_json::json!({"username": username, "password": "password123"});
+    let user: serde_json::Value = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let user_id = user["id"].as_i64().unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap();
+    let post_id: i64 = sqlx::query_scalar("INSERT INTO posts (user_id, title, content) VALUES ($1, 'Mine', 'All mine') RETURNING id")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    for sql in [
+        "INSERT INTO comments (post_id, user_id, content) VALUES ($2, $1, 'Me too')",
+        "INSERT INTO post_likes (user_id, post_id) VALUES ($1, $2)",
+    ] {
+        sqlx::query(sql).bind(user_id).bind(post_id).execute(&pool).await.unwrap();
+    }
+    sqlx::query!("INSERT INTO exam_records (user_id, score) VALUES ($1, 80)", user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let export = || {
+        client
+            .post(format!("{}/api/profile/export", address))
+            .bearer_auth(token)
+            .send()
+    };
+    let latest = || {
+        let request = client
+            .get(format!("{}/api/profile/export", address))
+            .bearer_auth(token);
+        async move { request.send().await.unwrap() }
+    };
+    assert_eq!(latest().await.status().as_u16(), 404);
+
+    // Act 1: Request an export, twice while it is pending
+    let queued = export().await.unwrap();
+    assert_eq!(queued.status().as_u16(), 202);
+    let queued: serde_json::Value = queued.json().await.unwrap();
+    assert_eq!(queued["status"], "pending");
+    let export_id = queued["id"].as_i64().unwrap();
+    let again: serde_json::Value = export().await.unwrap().json().await.unwrap();
+    assert_eq!(again["id"], export_id);
+
+    // Run the job here rather than draining the shared queue
+    let payload = sqlx::query_scalar!(
+        "DELETE FROM jobs WHERE id = (SELECT job_id FROM data_exports WHERE id = $1) RETURNING payload",
+        export_id
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let storage = backend::storage::LocalStorage::new(
+        std::env::temp_dir().join("ancient-arch-test-storage"),
+        b"profile_test_secret",
+    );
+    backend::jobs::data_export::run(&pool, &storage, &payload)
+        .await
+        .expect("Export failed");
+
+    // Assert: The archive is ready, downloadable and has everything
+    let ready: serde_json::Value = latest().await.json().await.unwrap();
+    assert_eq!(ready["status"], "ready");
+    assert!(ready["expires_at"].is_string());
+    let bytes = client
+        .get(format!("{}{}", address, ready["download_url"].as_str().unwrap()))
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    let archive: serde_json::Value =
+        serde_json::from_reader(flate2::read::GzDecoder::new(&bytes[..])).unwrap();
+    assert_eq!(archive["format"], 1);
+    assert_eq!(archive["profile"]["username"], username);
+    assert!(archive["profile"].get("password").is_none());
+    assert_eq!(archive["posts"][0]["id"], post_id);
+    assert_eq!(archive["comments"][0]["content"], "Me too");
+    assert_eq!(archive["likes"][0]["post_id"], post_id);
+    assert_eq!(archive["exam_records"][0]["score"], 80);
+    assert_eq!(archive["contributions"], serde_json::json!([]));
+    let kinds: Vec<String> = sqlx::query_scalar("SELECT kind FROM notifications WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert!(kinds.contains(&"data_export_ready".to_string()));
+
+    // Another export has to wait a day
+    let limited = export().await.unwrap();
+    assert_eq!(limited.status().as_u16(), 429);
+    assert!(limited.headers().contains_key("retry-after"));
+
+    // Act 2: Expire it and run the cleanup
+    sqlx::query!(
+        "UPDATE data_exports SET expires_at = NOW() - INTERVAL '1 minute' WHERE id = $1",
+        export_id
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    backend::jobs::data_export::purge_expired(pool.clone(), std::sync::Arc::new(storage))
+        .await
+        .unwrap();
+
+    // Assert: The archive is gone
+    let expired: serde_json::Value = latest().await.json().await.unwrap();
+    assert_eq!(expired["status"], "expired");
+    assert!(expired["download_url"].is_null());
+    let gone = client
+        .get(format!("{}{}", address, ready["download_url"].as_str().unwrap()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(gone.status().as_u16(), 404);
+}
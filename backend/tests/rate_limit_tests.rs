@@ -0,0 +1,224 @@
+// tests/rate_limit_tests.rs
+//
+// Route group rate limits small enough to hit in a test: two auth requests
+// per address, three reads and two writes per user. None refill within the
+// test (one request a minute). The app trusts forwarding headers from the
+// loopback address the tests connect from, as it would from nginx.
+
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig, RateLimit,
+        RateLimitConfig, RateLimitKey, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    state::AppState,
+    utils::jwt::sign_jwt,
+};
+use ipnet::IpNet;
+use sqlx::{PgPool, postgres::PgPoolOptions};
+use std::net::SocketAddr;
+
+const SECRET: &str = "rate_limit_test_secret";
+
+async fn spawn_app() -> (String, PgPool) {
+    spawn_app_trusting(vec!["127.0.0.1/32".parse().unwrap()]).await
+}
+
+async fn spawn_app_trusting(trusted_proxies: Vec<IpNet>) -> (String, PgPool) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: SECRET.to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig {
+            auth: Some(RateLimit { per_minute: 1, burst: 2 }),
+            read: Some(RateLimit { per_minute: 1, burst: 3 }),
+            write: Some(RateLimit { per_minute: 1, burst: 2 }),
+            key: RateLimitKey::User,
+        },
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies,
+    };
+
+    let app = routes::create_router(AppState::new(pool.clone(), config));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+
+    (address, pool)
+}
+
+/// A private address no other test run is likely to use.
+fn random_ip() -> String {
+    let [a, b, c] = rand::random::<[u8; 3]>();
+    format!("10.{}.{}.{}", a, b, c)
+}
+
+async fn login(client: &reqwest::Client, address: &str, ip: &str) -> reqwest::Response {
+    client
+        .post(format!("{}/api/auth/login", address))
+        .header("X-Real-IP", ip)
+        .json(&serde_json::json!({"username": "nobody_here", "password": "wrong-password"}))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn read(client: &reqwest::Client, address: &str, ip: &str, token: Option<&str>) -> u16 {
+    let mut request = client
+        .get(format!("{}/api/architectures", address))
+        .header("X-Real-IP", ip);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    request.send().await.unwrap().status().as_u16()
+}
+
+async fn write(client: &reqwest::Client, address: &str, ip: &str, token: &str) -> u16 {
+    client
+        .post(format!("{}/api/notifications/read", address))
+        .header("X-Real-IP", ip)
+        .bearer_auth(token)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+#[tokio::test]
+async fn test_auth_routes_are_limited_per_address() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let ip = random_ip();
+
+    // Act: Two attempts fit the burst, the third does not
+    assert_eq!(login(&client, &address, &ip).await.status(), 401);
+    assert_eq!(login(&client, &address, &ip).await.status(), 401);
+    let res = login(&client, &address, &ip).await;
+
+    // Assert: A typed 429 saying when to retry
+    assert_eq!(res.status(), 429);
+    let retry_after: u64 = res.headers()["retry-after"].to_str().unwrap().parse().unwrap();
+    assert!(retry_after >= 1);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["code"], "rate_limited");
+    assert_eq!(body["retry_after"], retry_after);
+
+    // Another address has its own budget, and a flood is recorded once
+    assert_eq!(login(&client, &address, &random_ip()).await.status(), 401);
+    assert_eq!(login(&client, &address, &ip).await.status(), 429);
+    let recorded: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM security_events WHERE kind = 'rate_limited' AND ip = $1 AND created_at > NOW() - INTERVAL '1 minute'",
+    )
+    .bind(&ip)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(recorded, 1);
+}
+
+#[tokio::test]
+async fn test_forged_forwarding_hops_share_the_real_address_bucket() {
+    // Arrange: nginx appends the address it saw to whatever the client sent
+    let (address, _pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let ip = random_ip();
+    let forged_login = || {
+        client
+            .post(format!("{}/api/auth/login", address))
+            .header("X-Forwarded-For", format!("{}, {}", random_ip(), ip))
+            .json(&serde_json::json!({"username": "nobody_here", "password": "wrong-password"}))
+            .send()
+    };
+
+    // Act: A new leftmost hop on every attempt
+    assert_eq!(forged_login().await.unwrap().status(), 401);
+    assert_eq!(forged_login().await.unwrap().status(), 401);
+
+    // Assert: Still the same client, whichever header names it
+    assert_eq!(forged_login().await.unwrap().status(), 429);
+    assert_eq!(login(&client, &address, &ip).await.status(), 429);
+}
+
+#[tokio::test]
+async fn test_forwarding_headers_from_untrusted_peers_are_ignored() {
+    // Arrange: No trusted proxies, so every request is from 127.0.0.1
+    let (address, _pool) = spawn_app_trusting(Vec::new()).await;
+    let client = reqwest::Client::new();
+
+    // Act: A different claimed address on every attempt
+    assert_eq!(login(&client, &address, &random_ip()).await.status(), 401);
+    assert_eq!(login(&client, &address, &random_ip()).await.status(), 401);
+
+    // Assert: They all count against the peer address
+    assert_eq!(login(&client, &address, &random_ip()).await.status(), 429);
+}
+
+#[tokio::test]
+async fn test_reads_and_writes_are_limited_per_user() {
+    // Arrange: Two users behind the same address
+    let (address, _pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let ip = random_ip();
+    let first = rand::random::<u32>() as i64 + 1;
+    let alice = sign_jwt(first, "alice", "user", SECRET, 600, false).unwrap();
+    let bob = sign_jwt(first + 1, "bob", "user", SECRET, 600, false).unwrap();
+
+    // Act & Assert 1: Alice spends her reads
+    for _ in 0..3 {
+        assert_eq!(read(&client, &address, &ip, Some(&alice)).await, 200);
+    }
+    assert_eq!(read(&client, &address, &ip, Some(&alice)).await, 429);
+
+    // Act & Assert 2: Bob and anonymous visitors at the address are unaffected
+    assert_eq!(read(&client, &address, &ip, Some(&bob)).await, 200);
+    assert_eq!(read(&client, &address, &ip, None).await, 200);
+
+    // Act & Assert 3: Writes have a budget of their own
+    assert_ne!(write(&client, &address, &ip, &alice).await, 429);
+    assert_ne!(write(&client, &address, &ip, &alice).await, 429);
+    assert_eq!(write(&client, &address, &ip, &alice).await, 429);
+    assert_ne!(write(&client, &address, &ip, &bob).await, 429);
+}
@@ -0,0 +1,247 @@
+// tests/series_tests.rs
+
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig,
+        RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    state::AppState,
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+
+async fn spawn_app() -> (String, PgPool) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: "series_test_secret".to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies: Vec::new(),
+    };
+
+    let app = routes::create_router(AppState::new(pool.clone(), config));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (address, pool)
+}
+
+/// Registers a verified user and returns their token.
+async fn verified_user(client: &reqwest::Client, address: &str, pool: &PgPool) -> String {
+    let username = format!("series_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let creds = serde_json::json!({"username": username, "password": "password123"});
+
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE users SET is_verified = TRUE WHERE username = $1", username)
+        .execute(pool)
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    login["token"].as_str().unwrap().to_string()
+}
+
+async fn create_post(client: &reqwest::Client, address: &str, token: &str, title: &str) -> i64 {
+    let post: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&serde_json::json!({"title": title, "content": "Survey notes"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    post["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn test_series_order_and_navigation() {
+    // Arrange: Three parts of a survey, plus someone else's post
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let token = verified_user(&client, &address, &pool).await;
+    let other_token = verified_user(&client, &address, &pool).await;
+
+    let parts = [
+        create_post(&client, &address, &token, "Part A").await,
+        create_post(&client, &address, &token, "Part B").await,
+        create_post(&client, &address, &token, "Part C").await,
+    ];
+    let foreign = create_post(&client, &address, &other_token, "Not mine").await;
+
+    let series: serde_json::Value = client
+        .post(format!("{}/api/series", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&serde_json::json!({"title": "Shanxi timber halls"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let series_id = series["id"].as_i64().unwrap();
+    let posts_url = format!("{}/api/series/{}/posts", address, series_id);
+
+    // Act 1: Add the parts, the foreign post and one part twice
+    for post_id in parts {
+        let res = client
+            .post(&posts_url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&serde_json::json!({"post_id": post_id}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 201);
+    }
+    for (post_id, status) in [(foreign, 400), (parts[0], 409)] {
+        let res = client
+            .post(&posts_url)
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&serde_json::json!({"post_id": post_id}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), status);
+    }
+
+    // Act 2: Only the author may reorder, and only with every part listed
+    let res = client
+        .put(&posts_url)
+        .header("Authorization", format!("Bearer {}", other_token))
+        .json(&serde_json::json!({"post_ids": [parts[2], parts[0], parts[1]]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 401);
+    let res = client
+        .put(&posts_url)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&serde_json::json!({"post_ids": [parts[2], parts[0]]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 400);
+    let res = client
+        .put(&posts_url)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&serde_json::json!({"post_ids": [parts[2], parts[0], parts[1]]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 200);
+
+    // Assert 1: The series lists C, A, B
+    let detail: serde_json::Value = client
+        .get(format!("{}/api/series/{}", address, series_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["title"], "Shanxi timber halls");
+    let titles: Vec<&str> = detail["posts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, ["Part C", "Part A", "Part B"]);
+
+    // Assert 2: Post detail links to its neighbours
+    let post: serde_json::Value = client
+        .get(format!("{}/api/posts/{}", address, parts[0]))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(post["series"]["id"], series_id);
+    assert_eq!(post["series"]["part"], 2);
+    assert_eq!(post["series"]["total"], 3);
+    assert_eq!(post["series"]["previous"]["post_id"], parts[2]);
+    assert_eq!(post["series"]["next"]["title"], "Part B");
+
+    // Act 3: Remove the middle part
+    let res = client
+        .delete(format!("{}/{}", posts_url, parts[0]))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 204);
+
+    // Assert 3: The neighbours now link to each other; the removed post has no series
+    let post: serde_json::Value = client
+        .get(format!("{}/api/posts/{}", address, parts[2]))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(post["series"]["previous"].is_null());
+    assert_eq!(post["series"]["next"]["post_id"], parts[1]);
+
+    let post: serde_json::Value = client
+        .get(format!("{}/api/posts/{}", address, parts[0]))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(post["series"].is_null());
+}
@@ -0,0 +1,196 @@
+// tests/spam_tests.rs
+//
+// Bot checks on registration and post forms, with a honeypot field named
+// `website` and forms that must take at least five seconds.
+
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig,
+        RateLimitConfig, RegistrationConfig, SpamAction, SpamConfig, StorageConfig,
+    },
+    routes,
+    services::spam::form_token,
+    state::AppState,
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+
+const SECRET: &str = "spam_test_secret";
+
+async fn spawn_app(action: SpamAction) -> (String, PgPool) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: SECRET.to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig {
+            honeypot_field: Some("website".to_string()),
+            min_form_secs: 5,
+            action,
+        },
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies: Vec::new(),
+    };
+
+    let app = routes::create_router(AppState::new(pool.clone(), config));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (address, pool)
+}
+
+/// A token for a form shown ten seconds ago.
+fn aged_token() -> String {
+    form_token(SECRET, chrono::Utc::now().timestamp() - 10)
+}
+
+async fn register(
+    client: &reqwest::Client,
+    address: &str,
+    extra: serde_json::Value,
+) -> (String, reqwest::Response) {
+    let username = format!("sp_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let mut body = serde_json::json!({"username": username, "password": "password123"});
+    body.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+    let res = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    (username, res)
+}
+
+/// Security events about `spam_suspected` for a user.
+async fn flags(pool: &PgPool, user_id: i64) -> Vec<String> {
+    sqlx::query_scalar!(
+        r#"SELECT path as "path!" FROM security_events WHERE kind = 'spam_suspected' AND user_id = $1 ORDER BY id"#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_bot_registrations_are_rejected() {
+    // Arrange
+    let (address, _) = spawn_app(SpamAction::Reject).await;
+    let client = reqwest::Client::new();
+    let fresh: serde_json::Value = client
+        .get(format!("{}/api/auth/form-token", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Act & Assert: No token, a forged one, one just issued, and a filled honeypot
+    let cases = [
+        serde_json::json!({}),
+        serde_json::json!({"form_token": format!("{}.forged", chrono::Utc::now().timestamp() - 10)}),
+        serde_json::json!({"form_token": fresh["form_token"]}),
+        serde_json::json!({"form_token": aged_token(), "website": "http://spam.example"}),
+        serde_json::json!({"form_token": form_token(SECRET, chrono::Utc::now().timestamp() - 90_000)}),
+    ];
+    for extra in cases {
+        let (_, res) = register(&client, &address, extra.clone()).await;
+        assert_eq!(res.status().as_u16(), 400, "{}", extra);
+    }
+
+    // An empty honeypot and a token old enough pass
+    let (_, res) = register(&client, &address, serde_json::json!({
+        "form_token": aged_token(), "website": ""
+    }))
+    .await;
+    assert_eq!(res.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn test_bot_submissions_are_flagged() {
+    // Arrange
+    let (address, pool) = spawn_app(SpamAction::Flag).await;
+    let client = reqwest::Client::new();
+
+    // Act 1: A bot registration is accepted
+    let (username, res) = register(&client, &address, serde_json::json!({
+        "form_token": aged_token(), "website": "http://spam.example"
+    }))
+    .await;
+    assert_eq!(res.status().as_u16(), 201);
+    let user_id = res.json::<serde_json::Value>().await.unwrap()["id"].as_i64().unwrap();
+
+    // Assert 1: ...and flagged
+    assert_eq!(flags(&pool, user_id).await, ["/api/auth/register"]);
+
+    // Act 2: The same user posts, once like a person and once too fast
+    sqlx::query!("UPDATE users SET is_verified = TRUE WHERE id = $1", user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let token = login["token"].as_str().unwrap();
+    let mut post_ids = Vec::new();
+    for form_token in [aged_token(), form_token(SECRET, chrono::Utc::now().timestamp())] {
+        let res = client
+            .post(format!("{}/api/posts", address))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&serde_json::json!({
+                "title": "Cheap tiles", "content": "Buy now", "form_token": form_token
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status().as_u16(), 201);
+        post_ids.push(res.json::<serde_json::Value>().await.unwrap()["id"].as_i64().unwrap());
+    }
+
+    // Assert 2: Only the hasty post was flagged
+    assert_eq!(
+        flags(&pool, user_id).await,
+        ["/api/auth/register".to_string(), format!("/api/posts/{}", post_ids[1])]
+    );
+}
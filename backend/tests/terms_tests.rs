@@ -0,0 +1,312 @@
+// tests/terms_tests.rs
+//
+// Publishing terms affects every registration and post, so these tests live in
+// their own binary and remove the versions they publish.
+
+use std::collections::HashMap;
+
+use axum::{Form, Json, Router, http::HeaderMap, routing::{get, post}};
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, OAuthProviderConfig,
+        QuotaConfig, RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    state::AppState,
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+
+/// Starts a GitHub-style provider for which any code `c<n>` belongs to the
+/// account with ID `n`, and returns its base URL.
+async fn spawn_provider() -> String {
+    let app = Router::new()
+        .route(
+            "/token",
+            post(|Form(form): Form<HashMap<String, String>>| async move {
+                let n = form.get("code").and_then(|c| c.strip_prefix('c')).unwrap_or_default();
+                Json(serde_json::json!({ "access_token": format!("t{}", n) }))
+            }),
+        )
+        .route(
+            "/user",
+            get(|headers: HeaderMap| async move {
+                let n: i64 = headers["authorization"]
+                    .to_str()
+                    .unwrap()
+                    .trim_start_matches("Bearer t")
+                    .parse()
+                    .unwrap();
+                Json(serde_json::json!({ "id": n, "login": format!("terms_oauth{}", n) }))
+            }),
+        );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port());
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    address
+}
+
+async fn spawn_app() -> (String, PgPool) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    // Leftovers from an aborted run would block everyone else's registrations.
+    sqlx::query!("DELETE FROM terms_versions WHERE version LIKE 'test-%'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let provider = spawn_provider().await;
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: "terms_test_secret".to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig {
+            github: Some(OAuthProviderConfig {
+                client_id: "client".to_string(),
+                client_secret: "secret".to_string(),
+                redirect_uri: "http://localhost:5173/oauth/github".to_string(),
+                authorize_url: Some(format!("{}/authorize", provider)),
+                token_url: Some(format!("{}/token", provider)),
+                userinfo_url: Some(format!("{}/user", provider)),
+            }),
+            ..OAuthConfig::default()
+        },
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies: Vec::new(),
+    };
+
+    let app = routes::create_router(AppState::new(pool.clone(), config));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (address, pool)
+}
+
+/// Registers a verified user with the given role and returns their username and token.
+async fn create_user(
+    client: &reqwest::Client,
+    address: &str,
+    pool: &PgPool,
+    role: &str,
+) -> (String, String) {
+    let username = format!("terms_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let creds = serde_json::json!({"username": username, "password": "password123"});
+
+    let res = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), 201);
+    sqlx::query!(
+        "UPDATE users SET role = $1, is_verified = TRUE WHERE username = $2",
+        role,
+        username
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(login["terms_accepted"], true, "Nothing to accept yet");
+
+    (username, login["token"].as_str().unwrap().to_string())
+}
+
+async fn create_post(client: &reqwest::Client, address: &str, token: &str) -> u16 {
+    client
+        .post(format!("{}/api/posts", address))
+        .header("Authorization", format!("Bearer {}", token))
+        .json(&serde_json::json!({"title": "Terms", "content": "Hello"}))
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+#[tokio::test]
+async fn test_terms_acceptance_gates_registration_and_posting() {
+    // Arrange: Users who signed up before any terms existed
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, admin_token) = create_user(&client, &address, &pool, "admin").await;
+    let (username, user_token) = create_user(&client, &address, &pool, "user").await;
+    assert_eq!(create_post(&client, &address, &user_token).await, 201);
+
+    let version = format!("test-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+
+    // Act 1: Publish terms
+    let published = client
+        .post(format!("{}/api/admin/terms", address))
+        .header("Authorization", format!("Bearer {}", admin_token))
+        .json(&serde_json::json!({"version": version, "content": "<p>Be kind.</p>"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(published.status(), 201);
+
+    let current: serde_json::Value = client
+        .get(format!("{}/api/terms", address))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(current["version"], version.as_str());
+
+    // Assert 1: Existing users are blocked until they accept
+    assert_eq!(create_post(&client, &address, &user_token).await, 401);
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&serde_json::json!({"username": username, "password": "password123"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(login["terms_accepted"], false);
+
+    let stale = client
+        .post(format!("{}/api/terms/accept", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .json(&serde_json::json!({"version": "test-old"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(stale.status(), 400);
+
+    let accepted = client
+        .post(format!("{}/api/terms/accept", address))
+        .header("Authorization", format!("Bearer {}", user_token))
+        .json(&serde_json::json!({"version": version}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(accepted.status(), 200);
+    assert_eq!(create_post(&client, &address, &user_token).await, 201);
+
+    // Assert 2: Registration requires the current version
+    let newcomer = format!("terms_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let rejected = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({"username": newcomer, "password": "password123"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(rejected.status(), 400);
+
+    let registered = client
+        .post(format!("{}/api/auth/register", address))
+        .json(&serde_json::json!({
+            "username": newcomer,
+            "password": "password123",
+            "accepted_terms_version": version
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(registered.status(), 201);
+
+    // Assert 3: So does signing up with a provider account
+    let account = format!("c{}", rand::random::<u32>());
+    let oauth_sign_in = |version: Option<String>| {
+        let client = client.clone();
+        let address = address.clone();
+        let account = account.clone();
+        async move {
+            let authorize: serde_json::Value = client
+                .get(format!("{}/api/auth/oauth/github/authorize", address))
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            let url = url::Url::parse(authorize["authorize_url"].as_str().unwrap()).unwrap();
+            let state = url.query_pairs().find(|(k, _)| k == "state").unwrap().1.into_owned();
+            client
+                .post(format!("{}/api/auth/oauth/github/callback", address))
+                .json(&serde_json::json!({
+                    "code": account, "state": state, "accepted_terms_version": version
+                }))
+                .send()
+                .await
+                .unwrap()
+        }
+    };
+    assert_eq!(oauth_sign_in(None).await.status(), 400);
+    let res = oauth_sign_in(Some(version.clone())).await;
+    assert_eq!(res.status(), 200);
+    let body: serde_json::Value = res.json().await.unwrap();
+    assert_eq!(body["created"], true);
+    assert_eq!(body["terms_accepted"], true);
+
+    let recorded = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM terms_acceptances ta
+        JOIN terms_versions tv ON tv.id = ta.terms_version_id
+        JOIN users u ON u.id = ta.user_id
+        WHERE tv.version = $1 AND u.username = ANY($2)
+        "#,
+        version,
+        &[username, newcomer] as &[String]
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(recorded, 2);
+
+    // Cleanup
+    sqlx::query!("DELETE FROM terms_versions WHERE version = $1", version)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
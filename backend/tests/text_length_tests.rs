@@ -0,0 +1,71 @@
+// tests/text_length_tests.rs
+
+use backend::{
+    config::POST_TITLE_LENGTH,
+    models::{comment::CreateCommentRequest, post::CreatePostRequest, user::CreateUserRequest},
+    utils::text_length::{check, grapheme_count},
+};
+use validator::Validate;
+
+#[test]
+fn test_grapheme_count() {
+    assert_eq!(grapheme_count(""), 0);
+    assert_eq!(grapheme_count("abc"), 3);
+    // CJK: one per character, whatever the byte length
+    assert_eq!(grapheme_count("应县木塔"), 4);
+    assert_eq!(grapheme_count("かな漢字"), 4);
+    // Combining accent (e + U+0301) is one character
+    assert_eq!(grapheme_count("Caf\u{65}\u{301}"), 4);
+    // Emoji: ZWJ family, skin tone, flag pairs, keycap
+    assert_eq!(grapheme_count("👨\u{200D}👩\u{200D}👧"), 1);
+    assert_eq!(grapheme_count("👍🏽"), 1);
+    assert_eq!(grapheme_count("🇨🇳🇯🇵"), 2);
+    assert_eq!(grapheme_count("🇨🇳🇯"), 2);
+    assert_eq!(grapheme_count("1\u{FE0F}\u{20E3}"), 1);
+    // Conjoining Hangul jamo form one syllable
+    assert_eq!(grapheme_count("\u{1100}\u{1161}\u{11A8}"), 1);
+    assert_eq!(grapheme_count("한국"), 2);
+    assert_eq!(grapheme_count("a\r\nb"), 3);
+}
+
+#[test]
+fn test_check_reports_length_error() {
+    assert!(check(&"木".repeat(POST_TITLE_LENGTH.max), POST_TITLE_LENGTH).is_ok());
+
+    let err = check(&"木".repeat(POST_TITLE_LENGTH.max + 1), POST_TITLE_LENGTH).unwrap_err();
+    assert_eq!(err.code, "length");
+    assert_eq!(err.params["max"], POST_TITLE_LENGTH.max);
+    assert_eq!(err.params["actual"], POST_TITLE_LENGTH.max + 1);
+
+    assert!(check("", POST_TITLE_LENGTH).is_err());
+}
+
+#[test]
+fn test_dtos_count_cjk_characters() {
+    // 100 Chinese characters are 300 bytes but fit a 100-character title
+    let post = |title: String| CreatePostRequest {
+        title,
+        content: "斗拱".to_string(),
+        tags: Vec::new(),
+        traps: Default::default(),
+    };
+    assert!(post("殿".repeat(100)).validate().is_ok());
+    let errors = post("殿".repeat(101)).validate().unwrap_err();
+    assert!(errors.field_errors().contains_key("title"));
+
+    // Emoji count as what they look like
+    let comment = CreateCommentRequest {
+        content: "👨\u{200D}👩\u{200D}👧".repeat(1000),
+        parent_id: None,
+    };
+    assert!(comment.validate().is_ok());
+
+    // Usernames keep their ASCII-only rule
+    let user: CreateUserRequest = serde_json::from_value(serde_json::json!({
+        "username": "古建筑爱好者",
+        "password": "password123"
+    }))
+    .unwrap();
+    let errors = user.validate().unwrap_err();
+    assert!(errors.field_errors()["username"].iter().any(|e| e.code == "regex"));
+}
@@ -0,0 +1,69 @@
+// tests/timeout_tests.rs
+
+use std::time::Duration;
+
+use axum::{Router, body::Body, http::Request, middleware, response::IntoResponse, routing::get};
+use backend::{error::AppError, utils::timeout::timeout_middleware};
+use sqlx::postgres::PgPoolOptions;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn test_slow_handler_returns_504() {
+    // Arrange: One fast and one slow route behind a 50ms budget
+    let app = Router::new()
+        .route("/fast", get(|| async { "ok" }))
+        .route(
+            "/slow",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "too late"
+            }),
+        )
+        .layer(middleware::from_fn_with_state(
+            Duration::from_millis(50),
+            timeout_middleware,
+        ));
+
+    // Act
+    let fast = app
+        .clone()
+        .oneshot(Request::get("/fast").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let slow = app
+        .oneshot(Request::get("/slow").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(fast.status().as_u16(), 200);
+    assert_eq!(slow.status().as_u16(), 504);
+    let body = axum::body::to_bytes(slow.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(json["error"].as_str().unwrap().contains("took too long"));
+}
+
+#[tokio::test]
+async fn test_statement_timeout_maps_to_504() {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    // Act: Postgres cancels the statement once it exceeds statement_timeout
+    let mut tx = pool.begin().await.unwrap();
+    sqlx::query("SET LOCAL statement_timeout = '50ms'")
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    let err = sqlx::query("SELECT pg_sleep(2)")
+        .execute(&mut *tx)
+        .await
+        .unwrap_err();
+
+    // Assert
+    let resp = AppError::from(err).into_response();
+    assert_eq!(resp.status().as_u16(), 504);
+}
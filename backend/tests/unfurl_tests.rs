@@ -0,0 +1,198 @@
+// tests/unfurl_tests.rs
+
+use backend::{
+    config::{
+        AuditLogConfig, AutoApprovalConfig, Config, MailConfig, OAuthConfig, QuotaConfig,
+        RateLimitConfig, RegistrationConfig, SpamConfig, StorageConfig,
+    },
+    routes,
+    services::unfurl,
+    state::AppState,
+};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+use url::Url;
+
+async fn spawn_app() -> (String, PgPool) {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&database_url)
+        .await
+        .expect("Failed to connect to Postgres for testing.");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate database");
+
+    let config = Config {
+        database_url: database_url.clone(),
+        jwt_secret: "unfurl_test_secret".to_string(),
+        jwt_expiration: 600,
+        rust_log: "error".to_string(),
+        admin_username: None,
+        admin_password: None,
+        storage: StorageConfig::Local {
+            root: std::env::temp_dir().join("ancient-arch-test-storage"),
+        },
+        slow_query_threshold_ms: 200,
+        registration: RegistrationConfig::default(),
+        sentry_dsn: None,
+        revalidate_admin_role: true,
+        comment_notification_window_secs: 600,
+        admin_digest_webhook_url: None,
+        audit_log: AuditLogConfig::default(),
+        mail: MailConfig::default(),
+        quotas: QuotaConfig::default(),
+        oauth: OAuthConfig::default(),
+        spam: SpamConfig::default(),
+        rate_limit: RateLimitConfig::default(),
+        auto_approval: AutoApprovalConfig::default(),
+        pdf_font: None,
+        trusted_proxies: Vec::new(),
+    };
+
+    let state = AppState::new(pool.clone(), config);
+    let app = routes::create_router(state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    (address, pool)
+}
+
+/// Registers a verified user and returns their token.
+async fn verified_user(client: &reqwest::Client, address: &str, pool: &PgPool) -> String {
+    let username = format!("unfurl_{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let creds = serde_json::json!({"username": username, "password": "password123"});
+
+    client
+        .post(format!("{}/api/auth/register", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap();
+    sqlx::query!("UPDATE users SET is_verified = TRUE WHERE username = $1", username)
+        .execute(pool)
+        .await
+        .unwrap();
+    let login: serde_json::Value = client
+        .post(format!("{}/api/auth/login", address))
+        .json(&creds)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    login["token"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn test_parse_preview_prefers_open_graph() {
+    let html = r#"
+        <html><head>
+          <title>Fallback &amp; title</title>
+          <meta name="description" content="Plain description">
+          <meta property="og:title" content="佛光寺东大殿">
+          <meta content='/img/cover.jpg' property='og:image'>
+        </head><body>...</body></html>
+    "#;
+    let page = Url::parse("https://example.com/articles/1").unwrap();
+
+    let preview = unfurl::parse_preview(html, &page);
+
+    assert_eq!(preview.title.as_deref(), Some("佛光寺东大殿"));
+    assert_eq!(preview.description.as_deref(), Some("Plain description"));
+    assert_eq!(
+        preview.image_url.as_deref(),
+        Some("https://example.com/img/cover.jpg")
+    );
+
+    let bare = unfurl::parse_preview("<title> Fallback &amp; title </title>", &page);
+    assert_eq!(bare.title.as_deref(), Some("Fallback & title"));
+    assert_eq!(bare.image_url, None);
+}
+
+#[tokio::test]
+async fn test_post_link_cards_and_ssrf_block() {
+    // Arrange
+    let (address, pool) = spawn_app().await;
+    let client = reqwest::Client::new();
+    let token = verified_user(&client, &address, &pool).await;
+
+    let tag = uuid::Uuid::new_v4();
+    let public_url = format!("https://example.com/{}?a=1&b=2", tag);
+    let private_url = format!("http://127.0.0.1:9/{}", tag);
+
+    // A preview that has already been fetched for another post
+    sqlx::query!(
+        "INSERT INTO link_previews (url, status, title, fetched_at) VALUES ($1, 'ok', 'Cached', NOW())",
+        public_url
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    // Act 1: Post linking to both
+    let post: serde_json::Value = client
+        .post(format!("{}/api/posts", address))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "title": "Links",
+            "content": format!("See {}, and {}.", public_url, private_url)
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let post_id = post["id"].as_i64().unwrap();
+
+    // Assert: Only the unknown URL was queued
+    let queued = sqlx::query_scalar!(
+        r#"SELECT payload->>'url' as "url!" FROM jobs WHERE kind = 'unfurl_link' AND payload->>'url' LIKE '%' || $1"#,
+        tag.to_string()
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(queued, vec![private_url.clone()]);
+
+    // Act 2: Run the job; loopback targets are refused without a request
+    backend::jobs::unfurl_link::run(&pool, &serde_json::json!({"url": private_url}))
+        .await
+        .unwrap();
+
+    let blocked = sqlx::query!(
+        "SELECT status, error FROM link_previews WHERE url = $1",
+        private_url
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(blocked.status, "failed");
+    assert!(blocked.error.unwrap().contains("127.0.0.1"));
+
+    // Assert: The detail carries the cached card only
+    let detail: serde_json::Value = client
+        .get(format!("{}/api/posts/{}", address, post_id))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(detail["title"], "Links");
+    let cards = detail["link_cards"].as_array().unwrap();
+    assert_eq!(cards.len(), 1);
+    assert_eq!(cards[0]["url"], public_url.as_str());
+    assert_eq!(cards[0]["title"], "Cached");
+}
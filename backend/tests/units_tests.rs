@@ -0,0 +1,45 @@
+// tests/units_tests.rs
+
+use backend::utils::units::{Measure, metres_to_feet, round2};
+
+fn text(s: &str) -> Measure {
+    Measure::Text(s.to_string())
+}
+
+#[test]
+fn test_lengths_convert_to_metres() {
+    assert_eq!(Measure::Number(67.31).metres(), Some(67.31));
+    assert_eq!(text("67.31").metres(), Some(67.31));
+    assert_eq!(text(" 40 m ").metres(), Some(40.0));
+    assert_eq!(text("40米").metres(), Some(40.0));
+    assert_eq!(text("250 cm").metres(), Some(2.5));
+    assert_eq!(round2(text("100 ft").metres().unwrap()), 30.48);
+    assert_eq!(round2(text("3 尺").metres().unwrap()), 1.0);
+    assert_eq!(round2(text("3 丈").metres().unwrap()), 10.0);
+}
+
+#[test]
+fn test_areas_convert_to_square_metres() {
+    assert_eq!(text("1200 m²").square_metres(), Some(1200.0));
+    assert_eq!(text("1200㎡").square_metres(), Some(1200.0));
+    assert_eq!(text("3 亩").square_metres(), Some(2000.0));
+    assert_eq!(text("1.5 ha").square_metres(), Some(15_000.0));
+    assert_eq!(round2(text("1000 sq ft").square_metres().unwrap()), 92.9);
+    // A length unit is not an area
+    assert_eq!(text("10 m").square_metres(), None);
+}
+
+#[test]
+fn test_invalid_measures_rejected() {
+    assert_eq!(text("tall").metres(), None);
+    assert_eq!(text("10 furlongs").metres(), None);
+    assert_eq!(text("").metres(), None);
+    assert_eq!(Measure::Number(0.0).metres(), None);
+    assert_eq!(Measure::Number(-5.0).metres(), None);
+    assert_eq!(Measure::Number(f64::NAN).metres(), None);
+}
+
+#[test]
+fn test_metres_to_feet() {
+    assert_eq!(round2(metres_to_feet(30.48)), 100.0);
+}